@@ -105,6 +105,27 @@ impl CameraComponent {
         eye
     }
 
+    /// Projects a world space point to pixel coordinates in the camera's viewport, with the origin
+    /// at the top left. Returns `None` for points behind the camera.
+    pub fn world_to_viewport(&self, point: Vec3) -> Option<Vec2> {
+        let view = self
+            .parent()
+            .transform
+            .rigid_global_isometry()
+            .inverse()
+            .to_mat4();
+        let clip = self.projection * view * point.extend(1.0);
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+
+        let ndc = clip.xy() / clip.w;
+        Some(Vec2::new(
+            (ndc.x + 1.0) * 0.5 * self.width,
+            (1.0 - ndc.y) * 0.5 * self.height,
+        ))
+    }
+
     pub fn click_ray(&self, x: f32, y: f32) -> Ray {
         let eye = self.mouse_eye_dir(x, y);
 
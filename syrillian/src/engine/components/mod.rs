@@ -48,6 +48,7 @@ pub use ui_context::UiContext;
 pub use camera_debug::*;
 
 use crate::World;
+use crate::assets::AnimationEvent;
 use crate::core::GameObjectId;
 use crate::core::component_context_inference::ComponentContextInference;
 use crate::core::reflection::{ReflectedTypeInfo, Value, type_info};
@@ -65,8 +66,10 @@ use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
 use syrillian::core::reflection::{Reflect, ReflectSerialize};
 use syrillian_render::lighting::proxy::LightProxy;
+use syrillian_render::lighting::reflection_probe::ReflectionProbeProxy;
 use syrillian_render::proxies::SceneProxy;
 use syrillian_render::rendering::CPUDrawCtx;
+use syrillian_render::rendering::decals::DecalProxy;
 use syrillian_utils::{ComponentId, TypedComponentId};
 
 pub struct ComponentContext {
@@ -413,12 +416,23 @@ pub trait Component: Any {
         None
     }
 
+    fn create_reflection_probe(&mut self, world: &World) -> Option<Box<ReflectionProbeProxy>> {
+        None
+    }
+
+    fn create_decal(&mut self, world: &World) -> Option<Box<DecalProxy>> {
+        None
+    }
+
     fn update_proxy(&mut self, world: &World, draw_ctx: CPUDrawCtx) {}
 
     fn on_click(&mut self, _world: &mut World) {}
 
     fn on_gui(&mut self, world: &mut World, ctx: UiContext) {}
 
+    // Gets called when an animation on the same game object plays over an event marker
+    fn on_animation_event(&mut self, world: &mut World, event: &AnimationEvent) {}
+
     // Gets called when the component is about to be deleted
     fn delete(&mut self, world: &mut World) {}
 
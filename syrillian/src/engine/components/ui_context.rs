@@ -40,7 +40,7 @@ impl UiContext {
         );
         ui(&mut builder);
 
-        if root.children.is_empty() && root.element.is_none() {
+        if root.children.is_empty() && root.layers.is_empty() && root.element.is_none() {
             return true;
         }
 
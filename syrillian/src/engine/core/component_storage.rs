@@ -259,7 +259,7 @@ impl ComponentStorage {
         self.len = self.len.saturating_sub(1);
 
         if let Some(ref data) = comp.data {
-            ComponentContextInference::tl_remove(Rc::as_ptr(&data) as *const ());
+            ComponentContextInference::tl_remove(Rc::as_ptr(data) as *const ());
         }
     }
 
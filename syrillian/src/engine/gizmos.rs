@@ -0,0 +1,346 @@
+//! Immediate mode debug drawing.
+//!
+//! Gizmos can be drawn from anywhere with access to the [`World`](crate::World) through
+//! [`World::gizmos`](crate::World::gizmos):
+//!
+//! ```ignore
+//! let gizmos = world.gizmos();
+//! gizmos.line(Vec3::ZERO, Vec3::Y, Vec3::new(1.0, 0.0, 0.0));
+//! gizmos.sphere(target, 0.5, GizmoOptions::new().duration(Duration::from_secs(2)).overlay());
+//! ```
+//!
+//! A gizmo is drawn for the frame it was added in, or for as long as its duration. Gizmos are
+//! compiled into debug builds, and into release builds with the `gizmos` feature. Otherwise,
+//! drawing them does nothing.
+
+use crate::math::{Mat4, Quat, Vec3, Vec4};
+use std::f32::consts::TAU;
+use std::time::Duration;
+use syrillian_render::proxies::DebugLine;
+use syrillian_render::rendering::gizmos::GizmoFrame;
+
+pub const GIZMOS_ENABLED: bool = cfg!(any(debug_assertions, feature = "gizmos"));
+
+const CIRCLE_SEGMENTS: usize = 32;
+const ARROW_HEAD_SIZE: f32 = 0.15;
+
+#[derive(Debug, Copy, Clone)]
+pub struct GizmoOptions {
+    pub color: Vec4,
+    /// How long the gizmo stays after the frame it was drawn in
+    pub duration: Duration,
+    /// Draws the gizmo on top of the scene instead of hiding it behind geometry
+    pub overlay: bool,
+}
+
+impl Default for GizmoOptions {
+    fn default() -> Self {
+        Self {
+            color: Vec4::ONE,
+            duration: Duration::ZERO,
+            overlay: false,
+        }
+    }
+}
+
+impl GizmoOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the linear RGBA color of the gizmo
+    pub fn color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn overlay(mut self) -> Self {
+        self.overlay = true;
+        self
+    }
+}
+
+impl From<Vec4> for GizmoOptions {
+    fn from(color: Vec4) -> Self {
+        Self {
+            color,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<Vec3> for GizmoOptions {
+    fn from(color: Vec3) -> Self {
+        color.extend(1.0).into()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GizmoLine {
+    start: Vec3,
+    end: Vec3,
+    options: GizmoOptions,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct GizmoText {
+    pub position: Vec3,
+    pub text: String,
+    pub options: GizmoOptions,
+}
+
+#[derive(Debug, Default)]
+pub struct Gizmos {
+    lines: Vec<GizmoLine>,
+    texts: Vec<GizmoText>,
+    sent_lines: bool,
+}
+
+impl Gizmos {
+    pub fn line(&mut self, start: Vec3, end: Vec3, options: impl Into<GizmoOptions>) {
+        if !GIZMOS_ENABLED {
+            return;
+        }
+
+        self.lines.push(GizmoLine {
+            start,
+            end,
+            options: options.into(),
+        });
+    }
+
+    /// A line from `origin` along `direction`, which also sets its length
+    pub fn ray(&mut self, origin: Vec3, direction: Vec3, options: impl Into<GizmoOptions>) {
+        self.line(origin, origin + direction, options);
+    }
+
+    pub fn arrow(&mut self, start: Vec3, end: Vec3, options: impl Into<GizmoOptions>) {
+        let options = options.into();
+        let delta = end - start;
+        let length = delta.length();
+        if length <= f32::EPSILON {
+            return;
+        }
+
+        self.line(start, end, options);
+
+        let dir = delta / length;
+        let (side, up) = dir.any_orthonormal_pair();
+        let head = (length * 0.25).min(ARROW_HEAD_SIZE);
+        let base = end - dir * head;
+        for offset in [side, -side, up, -up] {
+            self.line(end, base + offset * head * 0.5, options);
+        }
+    }
+
+    pub fn circle(
+        &mut self,
+        center: Vec3,
+        normal: Vec3,
+        radius: f32,
+        options: impl Into<GizmoOptions>,
+    ) {
+        let options = options.into();
+        let (a, b) = normal.normalize_or(Vec3::Y).any_orthonormal_pair();
+
+        let point = |i: usize| {
+            let angle = i as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+            center + (a * angle.cos() + b * angle.sin()) * radius
+        };
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), options);
+        }
+    }
+
+    /// Draws a sphere as three circles around the main axes
+    pub fn sphere(&mut self, center: Vec3, radius: f32, options: impl Into<GizmoOptions>) {
+        let options = options.into();
+        self.circle(center, Vec3::X, radius, options);
+        self.circle(center, Vec3::Y, radius, options);
+        self.circle(center, Vec3::Z, radius, options);
+    }
+
+    /// An axis aligned box between `min` and `max`
+    pub fn aabb(&mut self, min: Vec3, max: Vec3, options: impl Into<GizmoOptions>) {
+        let center = (min + max) * 0.5;
+        let half_extents = (max - min) * 0.5;
+        self.cuboid(center, half_extents, Quat::IDENTITY, options);
+    }
+
+    /// A box around `center`, rotated by `rotation`
+    pub fn cuboid(
+        &mut self,
+        center: Vec3,
+        half_extents: Vec3,
+        rotation: Quat,
+        options: impl Into<GizmoOptions>,
+    ) {
+        let corners = [
+            Vec3::new(-1.0, -1.0, -1.0),
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(1.0, 1.0, -1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(1.0, -1.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(-1.0, 1.0, 1.0),
+        ]
+        .map(|corner| center + rotation * (corner * half_extents));
+
+        self.box_edges(&corners, options.into());
+    }
+
+    /// The frustum of a camera, given its combined projection and view matrix
+    pub fn frustum(&mut self, proj_view: Mat4, options: impl Into<GizmoOptions>) {
+        let inverse = proj_view.inverse();
+        let corners = [
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(-1.0, 1.0, 0.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(1.0, -1.0, 1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(-1.0, 1.0, 1.0),
+        ]
+        .map(|ndc| inverse.project_point3(ndc));
+
+        self.box_edges(&corners, options.into());
+    }
+
+    /// A label at a point in the world. Text is always drawn as an overlay.
+    pub fn text(
+        &mut self,
+        position: Vec3,
+        text: impl Into<String>,
+        options: impl Into<GizmoOptions>,
+    ) {
+        if !GIZMOS_ENABLED {
+            return;
+        }
+
+        self.texts.push(GizmoText {
+            position,
+            text: text.into(),
+            options: options.into(),
+        });
+    }
+
+    /// Removes all gizmos, including the ones that would stay for a while longer
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.texts.clear();
+    }
+
+    /// Eight corners, front face then back face, both wound the same way
+    fn box_edges(&mut self, corners: &[Vec3; 8], options: GizmoOptions) {
+        for i in 0..4 {
+            let next = (i + 1) % 4;
+            self.line(corners[i], corners[next], options);
+            self.line(corners[i + 4], corners[next + 4], options);
+            self.line(corners[i], corners[i + 4], options);
+        }
+    }
+
+    pub(crate) fn texts(&self) -> &[GizmoText] {
+        &self.texts
+    }
+
+    /// Collects the lines to draw this frame, or `None` if nothing changed for the renderer
+    pub(crate) fn frame(&mut self) -> Option<GizmoFrame> {
+        if self.lines.is_empty() && !self.sent_lines {
+            return None;
+        }
+        self.sent_lines = !self.lines.is_empty();
+
+        let mut frame = GizmoFrame::default();
+        for line in &self.lines {
+            let debug_line = DebugLine {
+                start: line.start,
+                start_color: line.options.color,
+                end: line.end,
+                end_color: line.options.color,
+            };
+
+            if line.options.overlay {
+                frame.overlay_lines.push(debug_line);
+            } else {
+                frame.lines.push(debug_line);
+            }
+        }
+
+        Some(frame)
+    }
+
+    /// Ages all gizmos by `delta`, and drops the ones that ran out
+    pub(crate) fn advance(&mut self, delta: Duration) {
+        fn age(options: &mut GizmoOptions, delta: Duration) -> bool {
+            match options.duration.checked_sub(delta) {
+                Some(left) if !left.is_zero() => {
+                    options.duration = left;
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        self.lines.retain_mut(|line| age(&mut line.options, delta));
+        self.texts.retain_mut(|text| age(&mut text.options, delta));
+    }
+}
+
+#[cfg(all(test, any(debug_assertions, feature = "gizmos")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gizmos_expire_after_their_duration() {
+        let mut gizmos = Gizmos::default();
+        gizmos.line(Vec3::ZERO, Vec3::X, Vec3::ONE);
+        gizmos.line(
+            Vec3::ZERO,
+            Vec3::Y,
+            GizmoOptions::new()
+                .duration(Duration::from_millis(30))
+                .overlay(),
+        );
+
+        let frame = gizmos.frame().unwrap();
+        assert_eq!(frame.lines.len(), 1);
+        assert_eq!(frame.overlay_lines.len(), 1);
+
+        gizmos.advance(Duration::from_millis(16));
+        let frame = gizmos.frame().unwrap();
+        assert!(frame.lines.is_empty());
+        assert_eq!(frame.overlay_lines.len(), 1);
+
+        gizmos.advance(Duration::from_millis(16));
+        let frame = gizmos
+            .frame()
+            .expect("Renderer has to clear the previous lines");
+        assert!(frame.overlay_lines.is_empty());
+        assert!(gizmos.frame().is_none());
+    }
+
+    #[test]
+    fn shapes_build_closed_outlines() {
+        let mut gizmos = Gizmos::default();
+        gizmos.aabb(Vec3::ZERO, Vec3::ONE, Vec3::ONE);
+        assert_eq!(gizmos.frame().unwrap().lines.len(), 12);
+
+        gizmos.clear();
+        gizmos.sphere(Vec3::ZERO, 1.0, Vec3::ONE);
+        let lines = gizmos.frame().unwrap().lines;
+        assert_eq!(lines.len(), 3 * CIRCLE_SEGMENTS);
+        assert!(
+            lines
+                .iter()
+                .all(|line| (line.start.length() - 1.0).abs() < 1e-4)
+        );
+    }
+}
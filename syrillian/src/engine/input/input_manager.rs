@@ -20,6 +20,7 @@ struct InputState {
     mouse_wheel_delta: f32,
     mouse_pos: PhysicalPosition<f32>,
     mouse_delta: Vec2,
+    typed_text: String,
     is_locked: bool,
     suppress_auto_cursor_lock: bool,
 }
@@ -120,6 +121,16 @@ impl InputManager {
 
                     state.key_states.insert(code, event.state);
                 }
+
+                if event.state.is_pressed() {
+                    if event.physical_key == PhysicalKey::Code(KeyCode::Backspace) {
+                        state.typed_text.push('\u{8}');
+                    } else if let Some(text) = &event.text {
+                        state
+                            .typed_text
+                            .extend(text.chars().filter(|ch| !ch.is_control()));
+                    }
+                }
             }
             WindowEvent::CursorMoved {
                 position,
@@ -233,6 +244,7 @@ impl InputManager {
 
     pub fn next_frame_all(&mut self) {
         self.state.key_just_updated.clear();
+        self.state.typed_text.clear();
         self.state.button_just_updated.clear();
         self.state.mouse_delta = Vec2::ZERO;
         self.state.mouse_wheel_delta = 0.0;
@@ -240,6 +252,11 @@ impl InputManager {
         self.gamepad.poll();
     }
 
+    /// Text typed since the last frame, with each backspace press as `'\u{8}'`.
+    pub fn typed_text(&self) -> &str {
+        &self.state().typed_text
+    }
+
     pub fn mouse_wheel_delta(&self) -> f32 {
         self.state().mouse_wheel_delta
     }
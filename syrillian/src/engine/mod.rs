@@ -6,6 +6,7 @@
 
 pub mod components;
 pub mod core;
+pub mod gizmos;
 pub mod input;
 pub mod physics;
 pub mod reflection;
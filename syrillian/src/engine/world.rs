@@ -8,6 +8,7 @@ use crate::audio::AudioScene;
 use crate::components::{CRef, CWeak, CameraComponent, Component, UiContext};
 use crate::core::component_storage::ComponentStorage;
 use crate::core::{EventType, GameObject, GameObjectId, GameObjectRef, ObjectHash, Transform};
+use crate::engine::gizmos::{GIZMOS_ENABLED, Gizmos};
 use crate::engine::prefabs::Prefab;
 use crate::game_thread::GameAppEvent;
 use crate::input::InputManager;
@@ -22,16 +23,18 @@ use std::sync::Arc;
 use syrillian_asset::store::{AssetRefreshMessage, Store};
 use syrillian_asset::{AssetStore, ComputeShader};
 use syrillian_asset::{
-    BGL, Cubemap, HCubemap, Material, MaterialInstance, Mesh, RenderCubemap, RenderTexture2D,
-    RenderTexture2DArray, Shader, Sound, Texture2D, Texture2DArray,
+    BGL, Cubemap, HCubemap, HRenderTexture2D, Material, MaterialInstance, Mesh, RenderCubemap,
+    RenderTexture2D, RenderTexture2DArray, Shader, Sound, Texture2D, Texture2DArray,
 };
-use syrillian_render::strobe::StrobeFrame;
 use syrillian_render::strobe::input::{HitRect, StrobeInputState};
+use syrillian_render::strobe::{
+    LayerAnchor, StrobeFrame, StrobeNode, StrobeRoot, UiBuilder, UiText,
+};
 use tracing::info;
 use web_time::{Duration, Instant};
 
 use crate::core::reflection::Value;
-use crate::math::UVec2;
+use crate::math::{UVec2, Vec2};
 use crossbeam_channel::unbounded;
 use crossbeam_channel::{Receiver, Sender};
 use syrillian_macros::Reflect;
@@ -241,6 +244,7 @@ pub struct World {
     thread_binding: Option<WorldBinding>,
     pub strobe: StrobeFrame,
     pub strobe_input: StrobeInputState,
+    gizmos: Gizmos,
 }
 
 impl World {
@@ -278,6 +282,7 @@ impl World {
             thread_binding: None,
             strobe: StrobeFrame::default(),
             strobe_input: StrobeInputState::default(),
+            gizmos: Gizmos::default(),
         })
     }
 
@@ -695,6 +700,11 @@ impl World {
         self.main_active_camera
     }
 
+    /// Immediate mode debug drawing. See [`gizmos`](crate::gizmos) for when gizmos are drawn.
+    pub fn gizmos(&mut self) -> &mut Gizmos {
+        &mut self.gizmos
+    }
+
     pub fn is_listening_for(&self, obj: GameObjectId, event: EventType) -> bool {
         match event {
             EventType::CLICK => self.click_listeners.contains(&obj),
@@ -979,6 +989,7 @@ impl World {
 
         self.update_strobe_input();
         self.execute_component_on_gui(world);
+        self.sync_gizmos();
         self.sync_fresh_components();
         self.sync_removed_components();
 
@@ -1005,6 +1016,65 @@ impl World {
         }
     }
 
+    #[profiling::function]
+    fn sync_gizmos(&mut self) {
+        if !GIZMOS_ENABLED {
+            return;
+        }
+
+        self.draw_gizmo_texts();
+
+        if let Some(frame) = self.gizmos.frame() {
+            self.channels
+                .render_tx
+                .send(RenderMsg::UpdateGizmos(frame))
+                .unwrap();
+        }
+
+        self.gizmos.advance(self.delta_time);
+    }
+
+    fn draw_gizmo_texts(&mut self) {
+        const GIZMO_CACHE_ID: u64 = u64::from_le_bytes(*b"gizmotxt");
+
+        if self.gizmos.texts().is_empty() {
+            return;
+        }
+
+        let targets = self.channels.viewports.keys().copied().collect::<Vec<_>>();
+        for target in targets {
+            let Some(camera) = self
+                .active_camera_for_target(target)
+                .and_then(|c| c.upgrade(self))
+            else {
+                continue;
+            };
+            let Some(size) = self.viewport_size(target) else {
+                continue;
+            };
+
+            let size = Vec2::new(size.width as f32, size.height as f32);
+            let mut root = StrobeNode::default();
+            let mut builder = UiBuilder::new(&mut root, size);
+            for text in self.gizmos.texts() {
+                let Some(point) = camera.world_to_viewport(text.position) else {
+                    continue;
+                };
+
+                let label = UiText::new(text.text.clone()).color(text.options.color.truncate());
+                builder.layer(0, LayerAnchor::At(point), |ui| {
+                    ui.add(label.into());
+                });
+            }
+
+            self.strobe.strobe_roots.push(StrobeRoot {
+                root,
+                target,
+                cache_id: GIZMO_CACHE_ID,
+            });
+        }
+    }
+
     #[profiling::function]
     fn sync_viewport_cameras(&mut self, command_batch: &mut Vec<RenderMsg>) {
         for target_id in self.channels.viewports.keys().copied() {
@@ -1040,6 +1110,7 @@ impl World {
         let just_released = self.input.is_button_released(MouseButton::Left);
         self.strobe_input
             .begin_frame(mouse_pos, mouse_down, just_pressed, just_released);
+        self.strobe_input.set_text_input(self.input.typed_text());
     }
 
     fn execute_component_on_gui(&mut self, world: *mut World) {
@@ -1140,6 +1211,18 @@ impl World {
                     .send(RenderMsg::RegisterLightProxy(cid, proxy))
                     .unwrap();
             }
+            if let Some(probe) = comp.create_reflection_probe(self) {
+                self.channels
+                    .render_tx
+                    .send(RenderMsg::RegisterReflectionProbe(cid, probe))
+                    .unwrap();
+            }
+            if let Some(decal) = comp.create_decal(self) {
+                self.channels
+                    .render_tx
+                    .send(RenderMsg::RegisterDecal(cid, decal))
+                    .unwrap();
+            }
         }
     }
 
@@ -1235,6 +1318,15 @@ impl World {
             .is_ok()
     }
 
+    /// Rebuilds the mip chain of a render texture created with `generate_mips` from its first
+    /// level. Call this after rendering into the texture so sampling it at a distance matches.
+    pub fn generate_render_texture_mips(&self, texture: HRenderTexture2D) -> bool {
+        self.channels
+            .render_tx
+            .send(RenderMsg::GenerateRenderTextureMips(texture))
+            .is_ok()
+    }
+
     pub fn set_gbuffer_debug_targets(
         &self,
         target: ViewportId,
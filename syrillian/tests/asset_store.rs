@@ -3,9 +3,12 @@ use syrillian::math::{Vec2, Vec3, Vec4};
 use syrillian_asset::mesh::static_mesh_data::{RawSkinningVertexBuffers, RawVertexBuffers};
 use syrillian_asset::mesh::{Bones, PartialMesh};
 use syrillian_asset::{
-    AssetStore, Font, HMaterial, HMaterialInstance, HMesh, HShader, HTexture2D, MaterialInstance,
-    Mesh, Shader, SkinnedMesh, Sound, Texture2D,
+    AssetStore, Font, HMaterial, HMaterialInstance, HMesh, HShader, HTexture2D, Material,
+    MaterialGraphAsset, MaterialGraphAssetError, MaterialInstance, Mesh, Shader, SkinnedMesh,
+    Sound, Texture2D,
 };
+use syrillian_shadergen::graph::{MaterialGraph, MaterialGraphNode};
+use syrillian_shadergen::value::MaterialValueType;
 
 fn sample_raw_mesh() -> Arc<RawVertexBuffers> {
     Arc::new(RawVertexBuffers {
@@ -145,6 +148,8 @@ fn test_predefined_shaders() {
     store.shaders.try_get(HShader::POST_PROCESS).unwrap();
     store.shaders.try_get(HShader::TEXT_2D).unwrap();
     store.shaders.try_get(HShader::TEXT_3D).unwrap();
+    store.shaders.try_get(HShader::GIZMO_LINES).unwrap();
+    store.shaders.try_get(HShader::GIZMO_LINES_OVERLAY).unwrap();
 
     #[cfg(debug_assertions)]
     {
@@ -213,3 +218,119 @@ fn test_iterate_assets() {
 
     assert!(count >= 7);
 }
+
+#[test]
+fn test_material_graph_store() {
+    let (store, _assets_rx) = AssetStore::new();
+
+    let mut graph = MaterialGraph::default();
+    let roughness = graph.add(MaterialGraphNode::Parameter {
+        name: "roughness".to_string(),
+        value_type: MaterialValueType::F32,
+    });
+    graph.surface.roughness = Some(roughness);
+
+    let asset = MaterialGraphAsset::new("Graph", Material::default_layout(), graph.clone());
+    let handle = store.register_material_graph(&asset).unwrap();
+    let first = store.materials.get(handle).shader_set();
+    assert_eq!(store.materials.get(handle).name(), "Graph");
+    assert!(store.shaders.contains(first.base));
+
+    graph.surface.metallic = Some(roughness);
+    let edited = MaterialGraphAsset::new("Graph", Material::default_layout(), graph.clone());
+    store.reload_material_graph(handle, &edited).unwrap();
+    let second = store.materials.get(handle).shader_set();
+    assert_ne!(first.base, second.base);
+    assert!(!store.shaders.contains(first.base));
+    assert!(store.shaders.contains(second.base));
+
+    graph.nodes[0] = MaterialGraphNode::Parameter {
+        name: "missing".to_string(),
+        value_type: MaterialValueType::F32,
+    };
+    let broken = MaterialGraphAsset::new("Graph", Material::default_layout(), graph);
+    assert!(matches!(
+        store.reload_material_graph(handle, &broken),
+        Err(MaterialGraphAssetError::UnknownParameter { .. })
+    ));
+    assert_eq!(store.materials.get(handle).shader_set().base, second.base);
+}
+
+#[test]
+fn test_material_graph_unsaveable_or_missing() {
+    let (store, _assets_rx) = AssetStore::new();
+
+    let mut graph = MaterialGraph::default();
+    let roughness = graph.add(MaterialGraphNode::Parameter {
+        name: "roughness".to_string(),
+        value_type: MaterialValueType::F32,
+    });
+    graph.surface.roughness = Some(roughness);
+
+    // Runtime textures can't be saved as layout defaults
+    let pixels = vec![255, 255, 255, 255];
+    let texture = Texture2D::load_pixels(pixels, 1, 1, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let mut layout = Material::default_layout();
+    layout.textures[0].default = store.textures.add(texture);
+    let runtime_default = MaterialGraphAsset::new("Runtime Default", layout, graph.clone());
+    assert!(matches!(
+        store.register_material_graph(&runtime_default),
+        Err(MaterialGraphAssetError::TextureDefault { .. })
+    ));
+
+    let asset = MaterialGraphAsset::new("Graph", Material::default_layout(), graph);
+    let handle = store.register_material_graph(&asset).unwrap();
+    store.materials.remove(handle);
+
+    let shaders = store.shaders.items().count();
+    assert!(matches!(
+        store.reload_material_graph(handle, &asset),
+        Err(MaterialGraphAssetError::MissingMaterial { .. })
+    ));
+    assert_eq!(store.shaders.items().count(), shaders);
+}
+
+#[test]
+fn test_material_permutations() {
+    let (store, _assets_rx) = AssetStore::new();
+
+    assert!(
+        store
+            .materials
+            .get(HMaterial::DEFAULT)
+            .permutations()
+            .is_some()
+    );
+    assert!(
+        store
+            .materials
+            .get(HMaterial::FALLBACK)
+            .permutations()
+            .is_none()
+    );
+
+    let mut graph = MaterialGraph::default();
+    let roughness = graph.add(MaterialGraphNode::Parameter {
+        name: "roughness".to_string(),
+        value_type: MaterialValueType::F32,
+    });
+    graph.surface.roughness = Some(roughness);
+
+    // Graphs only get permutations when their layout declares keys
+    let mut layout = Material::default_layout();
+    layout.permutation_keys.clear();
+    let plain = MaterialGraphAsset::new("Plain", layout.clone(), graph.clone());
+    let handle = store.register_material_graph(&plain).unwrap();
+    assert!(store.materials.get(handle).permutations().is_none());
+
+    let keyed = MaterialGraphAsset::new("Keyed", Material::default_layout(), graph.clone());
+    let handle = store.register_material_graph(&keyed).unwrap();
+    assert!(store.materials.get(handle).permutations().is_some());
+
+    layout.permutation_keys = vec!["roughness".to_string()];
+    let float_key = MaterialGraphAsset::new("Float Key", layout, graph);
+    assert!(matches!(
+        store.register_material_graph(&float_key),
+        Err(MaterialGraphAssetError::PermutationKey { .. })
+    ));
+}
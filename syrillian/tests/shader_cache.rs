@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::PathBuf;
+use syrillian::rendering::cache::{ModuleStatus, ShaderCache};
+
+const VALID: &str = "@fragment fn main() -> @location(0) vec4f { return vec4f(1.0); }";
+const INVALID: &str = "@fragment fn main() -> @location(0) vec4f { return missing; }";
+
+const KEY: &str = "0123456789abcdef";
+
+fn cache_root(test: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!(
+        "syrillian-shader-cache-{}-{test}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+fn module(i: u32) -> String {
+    format!("@fragment fn main() -> @location(0) vec4f {{ return vec4f({i}.0); }}")
+}
+
+#[test]
+fn opens_in_keyed_directory() {
+    let root = cache_root("opens");
+
+    let cache = ShaderCache::open(&root, KEY, ShaderCache::DEFAULT_MAX_SIZE).unwrap();
+    assert_eq!(cache.dir(), root.join(KEY));
+    assert!(cache.dir().is_dir());
+    assert!(cache.pipeline_cache().is_none());
+
+    drop(cache);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn remembers_validated_modules() {
+    let root = cache_root("remembers");
+
+    {
+        let cache = ShaderCache::open(&root, KEY, ShaderCache::DEFAULT_MAX_SIZE).unwrap();
+        assert!(!cache.contains_module(VALID));
+        assert_eq!(cache.check_module("valid", VALID), ModuleStatus::Validated);
+        assert_eq!(cache.check_module("valid", VALID), ModuleStatus::Cached);
+        assert_eq!(
+            cache.check_module("invalid", INVALID),
+            ModuleStatus::Invalid
+        );
+        assert!(cache.contains_module(VALID));
+        assert!(!cache.contains_module(INVALID));
+    }
+
+    let cache = ShaderCache::open(&root, KEY, ShaderCache::DEFAULT_MAX_SIZE).unwrap();
+    assert_eq!(cache.module_count(), 1);
+    assert_eq!(cache.size(), VALID.len() as u64);
+    assert_eq!(cache.check_module("valid", VALID), ModuleStatus::Cached);
+
+    cache.clear().unwrap();
+    assert_eq!(cache.module_count(), 0);
+    assert!(!cache.contains_module(VALID));
+
+    drop(cache);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn evicts_least_recently_used_modules() {
+    let root = cache_root("evicts");
+    let max_size = module(0).len() as u64 * 2;
+
+    let cache = ShaderCache::open(&root, KEY, max_size).unwrap();
+    cache.check_module("0", &module(0));
+    cache.check_module("1", &module(1));
+    cache.check_module("0", &module(0));
+    cache.check_module("2", &module(2));
+    cache.flush().unwrap();
+
+    assert_eq!(cache.module_count(), 2);
+    assert!(cache.size() <= cache.max_size());
+    assert!(cache.contains_module(&module(0)));
+    assert!(!cache.contains_module(&module(1)));
+    assert!(cache.contains_module(&module(2)));
+
+    drop(cache);
+    let reopened = ShaderCache::open(&root, KEY, max_size).unwrap();
+    assert_eq!(reopened.module_count(), 2);
+    assert!(!reopened.contains_module(&module(1)));
+
+    drop(reopened);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn other_keys_are_invalidated() {
+    let root = cache_root("invalidates");
+
+    {
+        let cache = ShaderCache::open(&root, KEY, ShaderCache::DEFAULT_MAX_SIZE).unwrap();
+        cache.check_module("valid", VALID);
+    }
+    fs::create_dir_all(root.join("not-a-cache")).unwrap();
+
+    let other =
+        ShaderCache::open(&root, "fedcba9876543210", ShaderCache::DEFAULT_MAX_SIZE).unwrap();
+    assert!(other.dir().is_dir());
+    assert!(!other.contains_module(VALID));
+    assert!(!root.join(KEY).exists());
+    assert!(root.join("not-a-cache").exists());
+
+    drop(other);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn adapter_key_names_a_cache_directory() {
+    let info = wgpu::AdapterInfo {
+        name: "Test Adapter".to_string(),
+        vendor: 1,
+        device: 2,
+        device_type: wgpu::DeviceType::Other,
+        device_pci_bus_id: String::new(),
+        driver: "Test Driver".to_string(),
+        driver_info: "1.0".to_string(),
+        backend: wgpu::Backend::Noop,
+        subgroup_min_size: 0,
+        subgroup_max_size: 0,
+        transient_saves_memory: false,
+    };
+    let key = ShaderCache::adapter_key(&info);
+    assert_eq!(key.len(), 16);
+    assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+
+    let updated = wgpu::AdapterInfo {
+        driver_info: "1.1".to_string(),
+        ..info
+    };
+    assert_ne!(ShaderCache::adapter_key(&updated), key);
+}
@@ -1,3 +1,4 @@
+use crate::assets::prefab::json_to_reflect_value;
 use crate::store::streaming::asset_store::{
     AssetType, StreamingAssetBlobInfo, StreamingAssetBlobInfos, StreamingAssetBlobKind,
     StreamingAssetFile, StreamingAssetPayload,
@@ -12,6 +13,7 @@ use serde_json::Value as JsonValue;
 use snafu::whatever;
 use std::cmp::Ordering;
 use std::collections::{HashMap, VecDeque};
+use syrillian_reflect::Value;
 use syrillian_reflect::serializer::JsonSerializer;
 use zerocopy::{FromBytes, Immutable, KnownLayout};
 
@@ -22,6 +24,17 @@ pub struct AnimationClip {
     pub name: String,
     pub duration: f32,
     pub channels: Vec<AnimationChannel>,
+    /// Named markers on the clip timeline, sorted by time.
+    pub events: Vec<AnimationEvent>,
+}
+
+/// A named notify placed on an [`AnimationClip`] timeline.
+/// `time` is in **seconds**, the payload is free-form user data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationEvent {
+    pub name: String,
+    pub time: f32,
+    pub payload: Value,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -42,6 +55,11 @@ pub struct TransformKeys {
 
     pub s_times: Vec<f32>,
     pub s_values: Vec<Vec3>,
+
+    /// Morph target weight keys. `w_values` holds one weight per morph target for every
+    /// entry in `w_times`, laid out key after key.
+    pub w_times: Vec<f32>,
+    pub w_values: Vec<f32>,
 }
 
 impl StoreType for AnimationClip {
@@ -61,6 +79,108 @@ impl StoreType for AnimationClip {
     }
 }
 
+impl AnimationEvent {
+    pub fn new(name: impl Into<String>, time: f32) -> Self {
+        Self {
+            name: name.into(),
+            time,
+            payload: Value::None,
+        }
+    }
+
+    pub fn with_payload(mut self, payload: Value) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    fn decode(value: &JsonValue) -> streaming::error::Result<AnimationEvent> {
+        let event = value.expect_object("animation event")?;
+        Ok(AnimationEvent {
+            name: event
+                .required_field("name")?
+                .expect_parse("animation event name")?,
+            time: event
+                .required_field("time")?
+                .expect_parse("animation event time")?,
+            payload: event
+                .optional_field("payload")
+                .map(json_to_reflect_value)
+                .unwrap_or(Value::None),
+        })
+    }
+}
+
+impl AnimationClip {
+    /// Keeps [`events`](Self::events) in timeline order. Call this after editing them by hand.
+    pub fn sort_events(&mut self) {
+        self.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    /// Calls `fire` for every event that playback crosses when moving `delta` seconds away
+    /// from `start`. A negative `delta` plays backwards.
+    ///
+    /// Looping clips wrap around the ends, at most one full cycle is reported per call.
+    /// Non-looping clips stop at the ends. Events exactly at `start` are only reported when
+    /// `include_start` is set, which is meant for the very first step of a fresh playback.
+    pub fn events_crossed(
+        &self,
+        start: f32,
+        delta: f32,
+        looping: bool,
+        include_start: bool,
+        mut fire: impl FnMut(&AnimationEvent),
+    ) {
+        if self.events.is_empty() || self.duration <= 0.0 {
+            return;
+        }
+
+        let duration = self.duration;
+        let mut include_start = include_start;
+        let mut cursor = start.clamp(0.0, duration);
+        let mut remaining = if looping {
+            delta.abs().min(duration)
+        } else {
+            delta.abs()
+        };
+
+        if delta >= 0.0 {
+            loop {
+                let end = (cursor + remaining).min(duration);
+                for event in &self.events {
+                    let after_start =
+                        event.time > cursor || (include_start && event.time == cursor);
+                    if after_start && event.time <= end {
+                        fire(event);
+                    }
+                }
+                remaining -= end - cursor;
+                if !looping || remaining <= 0.0 {
+                    break;
+                }
+                cursor = 0.0;
+                include_start = true;
+            }
+        } else {
+            loop {
+                let end = (cursor - remaining).max(0.0);
+                for event in self.events.iter().rev() {
+                    let before_start =
+                        event.time < cursor || (include_start && event.time == cursor);
+                    if before_start && event.time >= end {
+                        fire(event);
+                    }
+                }
+                remaining -= cursor - end;
+                if !looping || remaining <= 0.0 {
+                    break;
+                }
+                cursor = duration;
+                include_start = true;
+            }
+        }
+    }
+}
+
 impl TransformKeys {
     pub fn keyed_translation(times: &[f32], values: &[[f32; 3]]) -> Self {
         Self {
@@ -154,6 +274,43 @@ impl TransformKeys {
         Some(self.r_values[i].slerp(self.r_values[i + 1], a).normalize())
     }
 
+    /// Samples the morph target weights at `t`, linearly interpolating every target.
+    pub fn sample_weights(&self, t: f32) -> Option<Vec<f32>> {
+        let n = self.w_times.len();
+        if n == 0 {
+            return None;
+        }
+        let stride = self.w_values.len() / n;
+        let key = |i: usize| &self.w_values[i * stride..(i + 1) * stride];
+
+        let i = Self::find_key(&self.w_times, t);
+        if i == n - 1 {
+            return Some(key(i).to_vec());
+        }
+        let t0 = self.w_times[i];
+        let t1 = self.w_times[i + 1];
+        let a = if t1 > t0 {
+            ((t - t0) / (t1 - t0)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        Some(
+            key(i)
+                .iter()
+                .zip(key(i + 1))
+                .map(|(w0, w1)| w0 + (w1 - w0) * a)
+                .collect(),
+        )
+    }
+
+    /// Number of morph targets animated by the weight track.
+    pub fn weight_count(&self) -> usize {
+        self.w_values
+            .len()
+            .checked_div(self.w_times.len())
+            .unwrap_or(0)
+    }
+
     fn find_key(times: &[f32], t: f32) -> usize {
         if times.is_empty() {
             return 0;
@@ -187,6 +344,11 @@ impl TransformKeys {
             self.s_times.extend(&other.s_times);
             self.s_values.extend(&other.s_values);
         }
+        if !other.w_times.is_empty() {
+            debug_assert!(other.w_values.len().is_multiple_of(other.w_times.len()));
+            self.w_times.extend(&other.w_times);
+            self.w_values.extend(&other.w_values);
+        }
     }
 }
 
@@ -227,6 +389,16 @@ impl StreamableAsset for AnimationClip {
                 &keys.s_values,
                 &mut blobs,
             );
+            PackedBlob::maybe_pack_data_into(
+                StreamingAssetBlobKind::AnimationWeightTimes,
+                &keys.w_times,
+                &mut blobs,
+            );
+            PackedBlob::maybe_pack_data_into(
+                StreamingAssetBlobKind::AnimationWeightValues,
+                &keys.w_values,
+                &mut blobs,
+            );
         }
 
         BuiltPayload {
@@ -254,7 +426,14 @@ impl StreamableAsset for AnimationClip {
         }
         cursor.ensure_exhausted()?;
 
-        Ok(AnimationClip {
+        let mut events = Vec::new();
+        if let Some(events_value) = root.optional_field("events") {
+            for event_value in events_value.expect_array("animation events")? {
+                events.push(AnimationEvent::decode(event_value)?);
+            }
+        }
+
+        let mut clip = AnimationClip {
             name: root
                 .required_field("name")?
                 .expect_parse("animation name")?,
@@ -262,7 +441,11 @@ impl StreamableAsset for AnimationClip {
                 .required_field("duration")?
                 .expect_parse("animation duration")?,
             channels,
-        })
+            events,
+        };
+        clip.sort_events();
+
+        Ok(clip)
     }
 }
 
@@ -357,6 +540,12 @@ impl AnimationChannel {
         let s_values_count = keys
             .required_field("s_values_count")?
             .expect_parse("animation s_values_count")?;
+        let w_times_count: Option<usize> = keys
+            .optional_field("w_times_count")
+            .expect_parse("animation w_times_count")?;
+        let w_values_count: Option<usize> = keys
+            .optional_field("w_values_count")
+            .expect_parse("animation w_values_count")?;
 
         let t_times = cursor.decode_track_blob(
             StreamingAssetBlobKind::AnimationTranslationTimes,
@@ -400,6 +589,20 @@ impl AnimationChannel {
             "animation scale values",
         )?;
 
+        let w_times = cursor.decode_track_blob(
+            StreamingAssetBlobKind::AnimationWeightTimes,
+            w_times_count.unwrap_or(0),
+            package,
+            "animation weight times",
+        )?;
+
+        let w_values = cursor.decode_track_blob(
+            StreamingAssetBlobKind::AnimationWeightValues,
+            w_values_count.unwrap_or(0),
+            package,
+            "animation weight values",
+        )?;
+
         Ok(AnimationChannel {
             target_name,
             keys: TransformKeys {
@@ -409,6 +612,8 @@ impl AnimationChannel {
                 r_values,
                 s_times,
                 s_values,
+                w_times,
+                w_values,
             },
         })
     }
@@ -33,8 +33,16 @@ impl HBGL {
     pub const SSAO_COMPUTE_ID: u32 = 13;
     pub const SSAO_APPLY_COMPUTE_ID: u32 = 14;
     pub const FONT_ATLAS_ID: u32 = 15;
+    pub const LIGHT_CLUSTER_COMPUTE_ID: u32 = 16;
+    pub const IBL_COMPUTE_ID: u32 = 17;
+    pub const REFLECTION_PROBE_RESOLVE_ID: u32 = 18;
+    pub const HI_Z_COPY_ID: u32 = 19;
+    pub const HI_Z_DOWNSAMPLE_ID: u32 = 20;
+    pub const OCCLUSION_TEST_ID: u32 = 21;
+    pub const DECAL_ID: u32 = 22;
+    pub const DECAL_SURFACE_ID: u32 = 23;
 
-    const MAX_BUILTIN_ID: u32 = 15;
+    const MAX_BUILTIN_ID: u32 = 23;
 
     pub const RENDER: HBGL = HBGL::new(Self::RENDER_ID);
     pub const MODEL: HBGL = HBGL::new(Self::MODEL_ID);
@@ -52,6 +60,14 @@ impl HBGL {
     pub const SSAO_COMPUTE: HBGL = HBGL::new(Self::SSAO_COMPUTE_ID);
     pub const SSAO_APPLY_COMPUTE: HBGL = HBGL::new(Self::SSAO_APPLY_COMPUTE_ID);
     pub const FONT_ATLAS: HBGL = HBGL::new(Self::FONT_ATLAS_ID);
+    pub const LIGHT_CLUSTER_COMPUTE: HBGL = HBGL::new(Self::LIGHT_CLUSTER_COMPUTE_ID);
+    pub const IBL_COMPUTE: HBGL = HBGL::new(Self::IBL_COMPUTE_ID);
+    pub const REFLECTION_PROBE_RESOLVE: HBGL = HBGL::new(Self::REFLECTION_PROBE_RESOLVE_ID);
+    pub const HI_Z_COPY: HBGL = HBGL::new(Self::HI_Z_COPY_ID);
+    pub const HI_Z_DOWNSAMPLE: HBGL = HBGL::new(Self::HI_Z_DOWNSAMPLE_ID);
+    pub const OCCLUSION_TEST: HBGL = HBGL::new(Self::OCCLUSION_TEST_ID);
+    pub const DECAL: HBGL = HBGL::new(Self::DECAL_ID);
+    pub const DECAL_SURFACE: HBGL = HBGL::new(Self::DECAL_SURFACE_ID);
 }
 
 impl StoreType for BGL {
@@ -81,6 +97,18 @@ impl StoreType for BGL {
                 HandleName::Static("SSAO Apply Compute Bind Group Layout")
             }
             HBGL::FONT_ATLAS_ID => HandleName::Static("Font Atlas Bind Group Layout"),
+            HBGL::LIGHT_CLUSTER_COMPUTE_ID => {
+                HandleName::Static("Light Cluster Compute Bind Group Layout")
+            }
+            HBGL::IBL_COMPUTE_ID => HandleName::Static("IBL Compute Bind Group Layout"),
+            HBGL::REFLECTION_PROBE_RESOLVE_ID => {
+                HandleName::Static("Reflection Probe Resolve Bind Group Layout")
+            }
+            HBGL::HI_Z_COPY_ID => HandleName::Static("Hi-Z Copy Bind Group Layout"),
+            HBGL::HI_Z_DOWNSAMPLE_ID => HandleName::Static("Hi-Z Downsample Bind Group Layout"),
+            HBGL::OCCLUSION_TEST_ID => HandleName::Static("Occlusion Test Bind Group Layout"),
+            HBGL::DECAL_ID => HandleName::Static("Decal Bind Group Layout"),
+            HBGL::DECAL_SURFACE_ID => HandleName::Static("Decal Surface Bind Group Layout"),
             _ => HandleName::Id(handle),
         }
     }
@@ -99,7 +127,7 @@ impl StoreType for BGL {
     }
 }
 
-const RENDER_ENTRIES: [BindGroupLayoutEntry; 5] = [
+const RENDER_ENTRIES: [BindGroupLayoutEntry; 9] = [
     BindGroupLayoutEntry {
         binding: 0,
         visibility: ShaderStages::all(),
@@ -146,13 +174,49 @@ const RENDER_ENTRIES: [BindGroupLayoutEntry; 5] = [
         },
         count: None,
     },
+    BindGroupLayoutEntry {
+        binding: 5,
+        visibility: ShaderStages::all(),
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::Cube,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 6,
+        visibility: ShaderStages::all(),
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::Cube,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 7,
+        visibility: ShaderStages::all(),
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 8,
+        visibility: ShaderStages::all(),
+        ty: BindingType::Sampler(SamplerBindingType::Filtering),
+        count: None,
+    },
 ];
 
 const MODEL_ENTRIES: [BindGroupLayoutEntry; 1] = [BindGroupLayoutEntry {
     binding: 0,
     visibility: ShaderStages::all(),
     ty: BindingType::Buffer {
-        ty: BufferBindingType::Uniform,
+        ty: BufferBindingType::Storage { read_only: true },
         has_dynamic_offset: false,
         min_binding_size: None,
     },
@@ -233,7 +297,7 @@ const MATERIAL_ENTRIES: [BindGroupLayoutEntry; 6] = [
     },
 ];
 
-const LIGHT_ENTRIES: [BindGroupLayoutEntry; 2] = [
+const LIGHT_ENTRIES: [BindGroupLayoutEntry; 6] = [
     BindGroupLayoutEntry {
         binding: 0,
         visibility: ShaderStages::VERTEX_FRAGMENT,
@@ -254,6 +318,89 @@ const LIGHT_ENTRIES: [BindGroupLayoutEntry; 2] = [
         },
         count: None,
     },
+    BindGroupLayoutEntry {
+        binding: 2,
+        visibility: ShaderStages::VERTEX_FRAGMENT,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 3,
+        visibility: ShaderStages::VERTEX_FRAGMENT,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 4,
+        visibility: ShaderStages::VERTEX_FRAGMENT,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::CubeArray,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 5,
+        visibility: ShaderStages::VERTEX_FRAGMENT,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+];
+
+const LIGHT_CLUSTER_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 4] = [
+    BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 2,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 3,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
 ];
 
 const SHADOW_ENTRIES: [BindGroupLayoutEntry; 4] = [
@@ -403,7 +550,7 @@ const PP_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 6] = [
     },
 ];
 
-const MESH_SKINNING_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 6] = [
+const MESH_SKINNING_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 8] = [
     BindGroupLayoutEntry {
         binding: 0,
         visibility: ShaderStages::COMPUTE,
@@ -464,6 +611,26 @@ const MESH_SKINNING_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 6] = [
         },
         count: None,
     },
+    BindGroupLayoutEntry {
+        binding: 6,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 7,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
 ];
 
 const PARTICLE_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 4] = [
@@ -581,6 +748,211 @@ const BLOOM_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 5] = [
     },
 ];
 
+const IBL_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 5] = [
+    BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::Cube,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 2,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Sampler(SamplerBindingType::Filtering),
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 3,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 4,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::StorageTexture {
+            access: StorageTextureAccess::WriteOnly,
+            format: TextureFormat::Rgba16Float,
+            view_dimension: TextureViewDimension::D2Array,
+        },
+        count: None,
+    },
+];
+
+const REFLECTION_PROBE_RESOLVE_ENTRIES: [BindGroupLayoutEntry; 2] = [
+    BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: true },
+            view_dimension: TextureViewDimension::D2Array,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::StorageTexture {
+            access: StorageTextureAccess::WriteOnly,
+            format: TextureFormat::Rgba16Float,
+            view_dimension: TextureViewDimension::D2Array,
+        },
+        count: None,
+    },
+];
+
+const HI_Z_COPY_ENTRIES: [BindGroupLayoutEntry; 2] = [
+    BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Depth,
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::StorageTexture {
+            access: StorageTextureAccess::WriteOnly,
+            format: TextureFormat::R32Float,
+            view_dimension: TextureViewDimension::D2,
+        },
+        count: None,
+    },
+];
+
+const HI_Z_DOWNSAMPLE_ENTRIES: [BindGroupLayoutEntry; 2] = [
+    BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: false },
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::StorageTexture {
+            access: StorageTextureAccess::WriteOnly,
+            format: TextureFormat::R32Float,
+            view_dimension: TextureViewDimension::D2,
+        },
+        count: None,
+    },
+];
+
+const OCCLUSION_TEST_ENTRIES: [BindGroupLayoutEntry; 4] = [
+    BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 2,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: false },
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 3,
+        visibility: ShaderStages::COMPUTE,
+        ty: BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    },
+];
+
+const DECAL_ENTRIES: [BindGroupLayoutEntry; 1] = [BindGroupLayoutEntry {
+    binding: 0,
+    visibility: ShaderStages::VERTEX_FRAGMENT,
+    ty: BindingType::Buffer {
+        ty: BufferBindingType::Uniform,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+    },
+    count: None,
+}];
+
+const DECAL_SURFACE_ENTRIES: [BindGroupLayoutEntry; 3] = [
+    BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Depth,
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 1,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: false },
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    },
+    BindGroupLayoutEntry {
+        binding: 2,
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Texture {
+            sample_type: TextureSampleType::Float { filterable: false },
+            view_dimension: TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    },
+];
+
 const SSAO_COMPUTE_ENTRIES: [BindGroupLayoutEntry; 5] = [
     BindGroupLayoutEntry {
         binding: 0,
@@ -831,5 +1203,77 @@ impl StoreDefaults for BGL {
                 entries: FONT_ATLAS_ENTRIES.to_vec()
             }
         );
+
+        store_add_checked!(
+            store,
+            HBGL::LIGHT_CLUSTER_COMPUTE_ID,
+            BGL {
+                label: HBGL::LIGHT_CLUSTER_COMPUTE.ident(),
+                entries: LIGHT_CLUSTER_COMPUTE_ENTRIES.to_vec()
+            }
+        );
+
+        store_add_checked!(
+            store,
+            HBGL::IBL_COMPUTE_ID,
+            BGL {
+                label: HBGL::IBL_COMPUTE.ident(),
+                entries: IBL_COMPUTE_ENTRIES.to_vec()
+            }
+        );
+
+        store_add_checked!(
+            store,
+            HBGL::REFLECTION_PROBE_RESOLVE_ID,
+            BGL {
+                label: HBGL::REFLECTION_PROBE_RESOLVE.ident(),
+                entries: REFLECTION_PROBE_RESOLVE_ENTRIES.to_vec()
+            }
+        );
+
+        store_add_checked!(
+            store,
+            HBGL::HI_Z_COPY_ID,
+            BGL {
+                label: HBGL::HI_Z_COPY.ident(),
+                entries: HI_Z_COPY_ENTRIES.to_vec()
+            }
+        );
+
+        store_add_checked!(
+            store,
+            HBGL::HI_Z_DOWNSAMPLE_ID,
+            BGL {
+                label: HBGL::HI_Z_DOWNSAMPLE.ident(),
+                entries: HI_Z_DOWNSAMPLE_ENTRIES.to_vec()
+            }
+        );
+
+        store_add_checked!(
+            store,
+            HBGL::OCCLUSION_TEST_ID,
+            BGL {
+                label: HBGL::OCCLUSION_TEST.ident(),
+                entries: OCCLUSION_TEST_ENTRIES.to_vec()
+            }
+        );
+
+        store_add_checked!(
+            store,
+            HBGL::DECAL_ID,
+            BGL {
+                label: HBGL::DECAL.ident(),
+                entries: DECAL_ENTRIES.to_vec()
+            }
+        );
+
+        store_add_checked!(
+            store,
+            HBGL::DECAL_SURFACE_ID,
+            BGL {
+                label: HBGL::DECAL_SURFACE.ident(),
+                entries: DECAL_SURFACE_ENTRIES.to_vec()
+            }
+        );
     }
 }
@@ -25,6 +25,13 @@ const COMPUTE_POST_PROCESS_BLOOM_BLUR: &str =
     include_str!("shader/shaders/compute/bloom_blur_compute.wgsl");
 const COMPUTE_POST_PROCESS_BLOOM_COMPOSITE: &str =
     include_str!("shader/shaders/compute/bloom_composite_compute.wgsl");
+const COMPUTE_LIGHT_CLUSTERS: &str = include_str!("shader/shaders/compute/light_clusters.wgsl");
+const COMPUTE_IBL: &str = include_str!("shader/shaders/compute/ibl_compute.wgsl");
+const COMPUTE_REFLECTION_PROBE_RESOLVE: &str =
+    include_str!("shader/shaders/compute/reflection_probe_resolve.wgsl");
+const COMPUTE_HI_Z_COPY: &str = include_str!("shader/shaders/compute/hi_z_copy.wgsl");
+const COMPUTE_HI_Z_DOWNSAMPLE: &str = include_str!("shader/shaders/compute/hi_z_downsample.wgsl");
+const COMPUTE_OCCLUSION_TEST: &str = include_str!("shader/shaders/compute/occlusion_test.wgsl");
 
 #[derive(Debug, Clone, Builder)]
 pub struct ComputeShader {
@@ -50,7 +57,15 @@ impl H<ComputeShader> {
     pub const POST_PROCESS_SSAO_BLUR_X_ID: u32 = 8;
     pub const POST_PROCESS_SSAO_BLUR_Y_ID: u32 = 9;
     pub const POST_PROCESS_SSAO_APPLY_ID: u32 = 10;
-    pub const MAX_BUILTIN_ID: u32 = 10;
+    pub const LIGHT_CLUSTERS_ID: u32 = 11;
+    pub const IBL_IRRADIANCE_ID: u32 = 12;
+    pub const IBL_PREFILTER_ID: u32 = 13;
+    pub const IBL_BRDF_LUT_ID: u32 = 14;
+    pub const REFLECTION_PROBE_RESOLVE_ID: u32 = 15;
+    pub const HI_Z_COPY_ID: u32 = 16;
+    pub const HI_Z_DOWNSAMPLE_ID: u32 = 17;
+    pub const OCCLUSION_TEST_ID: u32 = 18;
+    pub const MAX_BUILTIN_ID: u32 = 18;
 
     pub const FALLBACK: H<ComputeShader> = H::new(Self::FALLBACK_ID);
     pub const MESH_SKINNING: H<ComputeShader> = H::new(Self::MESH_SKINNING_ID);
@@ -67,6 +82,15 @@ impl H<ComputeShader> {
     pub const POST_PROCESS_SSAO_BLUR_Y: H<ComputeShader> =
         H::new(Self::POST_PROCESS_SSAO_BLUR_Y_ID);
     pub const POST_PROCESS_SSAO_APPLY: H<ComputeShader> = H::new(Self::POST_PROCESS_SSAO_APPLY_ID);
+    pub const LIGHT_CLUSTERS: H<ComputeShader> = H::new(Self::LIGHT_CLUSTERS_ID);
+    pub const IBL_IRRADIANCE: H<ComputeShader> = H::new(Self::IBL_IRRADIANCE_ID);
+    pub const IBL_PREFILTER: H<ComputeShader> = H::new(Self::IBL_PREFILTER_ID);
+    pub const IBL_BRDF_LUT: H<ComputeShader> = H::new(Self::IBL_BRDF_LUT_ID);
+    pub const REFLECTION_PROBE_RESOLVE: H<ComputeShader> =
+        H::new(Self::REFLECTION_PROBE_RESOLVE_ID);
+    pub const HI_Z_COPY: H<ComputeShader> = H::new(Self::HI_Z_COPY_ID);
+    pub const HI_Z_DOWNSAMPLE: H<ComputeShader> = H::new(Self::HI_Z_DOWNSAMPLE_ID);
+    pub const OCCLUSION_TEST: H<ComputeShader> = H::new(Self::OCCLUSION_TEST_ID);
 }
 
 impl StoreDefaults for ComputeShader {
@@ -182,6 +206,89 @@ impl StoreDefaults for ComputeShader {
                 vec![HBGL::SSAO_APPLY_COMPUTE]
             )
         );
+
+        store_add_checked!(
+            store,
+            HComputeShader::LIGHT_CLUSTERS_ID,
+            ComputeShader::new(
+                "Light Cluster Compute",
+                COMPUTE_LIGHT_CLUSTERS,
+                vec![HBGL::RENDER, HBGL::LIGHT_CLUSTER_COMPUTE]
+            )
+        );
+
+        store_add_checked!(
+            store,
+            HComputeShader::IBL_IRRADIANCE_ID,
+            ComputeShader::new_with_entry_point(
+                "IBL Irradiance Compute",
+                COMPUTE_IBL,
+                "cs_irradiance",
+                vec![HBGL::IBL_COMPUTE]
+            )
+        );
+
+        store_add_checked!(
+            store,
+            HComputeShader::IBL_PREFILTER_ID,
+            ComputeShader::new_with_entry_point(
+                "IBL Prefilter Compute",
+                COMPUTE_IBL,
+                "cs_prefilter",
+                vec![HBGL::IBL_COMPUTE]
+            )
+        );
+
+        store_add_checked!(
+            store,
+            HComputeShader::IBL_BRDF_LUT_ID,
+            ComputeShader::new_with_entry_point(
+                "IBL BRDF LUT Compute",
+                COMPUTE_IBL,
+                "cs_brdf_lut",
+                vec![HBGL::IBL_COMPUTE]
+            )
+        );
+
+        store_add_checked!(
+            store,
+            HComputeShader::REFLECTION_PROBE_RESOLVE_ID,
+            ComputeShader::new(
+                "Reflection Probe Resolve Compute",
+                COMPUTE_REFLECTION_PROBE_RESOLVE,
+                vec![HBGL::REFLECTION_PROBE_RESOLVE]
+            )
+        );
+
+        store_add_checked!(
+            store,
+            HComputeShader::HI_Z_COPY_ID,
+            ComputeShader::new(
+                "Hi-Z Copy Compute",
+                COMPUTE_HI_Z_COPY,
+                vec![HBGL::HI_Z_COPY]
+            )
+        );
+
+        store_add_checked!(
+            store,
+            HComputeShader::HI_Z_DOWNSAMPLE_ID,
+            ComputeShader::new(
+                "Hi-Z Downsample Compute",
+                COMPUTE_HI_Z_DOWNSAMPLE,
+                vec![HBGL::HI_Z_DOWNSAMPLE]
+            )
+        );
+
+        store_add_checked!(
+            store,
+            HComputeShader::OCCLUSION_TEST_ID,
+            ComputeShader::new(
+                "Occlusion Test Compute",
+                COMPUTE_OCCLUSION_TEST,
+                vec![HBGL::RENDER, HBGL::OCCLUSION_TEST]
+            )
+        );
     }
 }
 
@@ -220,6 +327,22 @@ impl StoreType for ComputeShader {
             HComputeShader::POST_PROCESS_SSAO_APPLY_ID => {
                 HandleName::Static("SSAO Apply Compute Shader")
             }
+            HComputeShader::LIGHT_CLUSTERS_ID => HandleName::Static("Light Cluster Compute Shader"),
+            HComputeShader::IBL_IRRADIANCE_ID => {
+                HandleName::Static("IBL Irradiance Compute Shader")
+            }
+            HComputeShader::IBL_PREFILTER_ID => HandleName::Static("IBL Prefilter Compute Shader"),
+            HComputeShader::IBL_BRDF_LUT_ID => HandleName::Static("IBL BRDF LUT Compute Shader"),
+            HComputeShader::REFLECTION_PROBE_RESOLVE_ID => {
+                HandleName::Static("Reflection Probe Resolve Compute Shader")
+            }
+            HComputeShader::HI_Z_COPY_ID => HandleName::Static("Hi-Z Copy Compute Shader"),
+            HComputeShader::HI_Z_DOWNSAMPLE_ID => {
+                HandleName::Static("Hi-Z Downsample Compute Shader")
+            }
+            HComputeShader::OCCLUSION_TEST_ID => {
+                HandleName::Static("Occlusion Test Compute Shader")
+            }
             _ => HandleName::Id(handle),
         }
     }
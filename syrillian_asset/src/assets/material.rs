@@ -7,13 +7,30 @@ use crate::store::{
 };
 use crate::{MaterialShaderSet, store_add_checked};
 use crossbeam_channel::Sender;
-use glamx::Vec3;
+use glamx::{Vec2, Vec3};
+use std::fmt;
+use std::sync::Arc;
+use syrillian_shadergen::function::{MaterialExpression, PbrShader};
+use syrillian_shadergen::generator::MeshPass;
 use syrillian_shadergen::value::{MaterialValue, MaterialValueType};
+use syrillian_shadergen::{MaterialCompiler, MaterialGraphError, MaterialPermutation};
+
+type PermutationCompiler =
+    dyn Fn(&MaterialPermutation) -> Result<String, MaterialGraphError> + Send + Sync;
+
+/// Compiles the color pass of a material specialized for the values of its
+/// [permutation keys](MaterialInputLayout::permutation_keys).
+#[derive(Clone)]
+pub struct MaterialPermutations {
+    compile: Arc<PermutationCompiler>,
+    prewarm: Vec<MaterialPermutation>,
+}
 
 #[derive(Debug, Clone)]
 pub struct DefaultMaterial {
     name: String,
     layout: MaterialInputLayout,
+    permutations: MaterialPermutations,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +44,7 @@ pub struct CustomMaterial {
     name: String,
     layout: MaterialInputLayout,
     shader_set: MaterialShaderSet,
+    permutations: Option<MaterialPermutations>,
 }
 
 #[derive(Debug, Clone)]
@@ -36,11 +54,63 @@ pub enum Material {
     Custom(CustomMaterial),
 }
 
+impl MaterialPermutations {
+    pub fn new<M>(material: M) -> Self
+    where
+        M: MaterialExpression + Clone + Send + Sync + 'static,
+    {
+        Self {
+            compile: Arc::new(move |permutation| {
+                MaterialCompiler::try_compile_mesh_permutation(
+                    &mut material.clone(),
+                    0,
+                    MeshPass::Base,
+                    permutation,
+                )
+            }),
+            prewarm: Vec::new(),
+        }
+    }
+
+    /// Variants the render cache compiles as soon as the material is loaded, instead
+    /// of when the first instance using them is uploaded.
+    pub fn with_prewarm(
+        mut self,
+        permutations: impl IntoIterator<Item = MaterialPermutation>,
+    ) -> Self {
+        self.prewarm.extend(permutations);
+        self
+    }
+
+    pub fn prewarm(&self) -> &[MaterialPermutation] {
+        &self.prewarm
+    }
+
+    pub fn add_prewarm(&mut self, permutation: MaterialPermutation) {
+        if !self.prewarm.contains(&permutation) {
+            self.prewarm.push(permutation);
+        }
+    }
+
+    pub fn compile(&self, permutation: &MaterialPermutation) -> Result<String, MaterialGraphError> {
+        (self.compile)(permutation)
+    }
+}
+
+impl fmt::Debug for MaterialPermutations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaterialPermutations")
+            .field("prewarm", &self.prewarm)
+            .finish_non_exhaustive()
+    }
+}
+
 impl DefaultMaterial {
     pub fn new(name: impl Into<String>, layout: MaterialInputLayout) -> Self {
         Self {
             name: name.into(),
             layout,
+            permutations: MaterialPermutations::new(PbrShader::default()),
         }
     }
 }
@@ -64,6 +134,7 @@ impl CustomMaterial {
             name: name.into(),
             layout,
             shader_set,
+            permutations: None,
         }
     }
 
@@ -76,8 +147,15 @@ impl CustomMaterial {
             name: name.into(),
             layout,
             shader_set,
+            permutations: None,
         }
     }
+
+    /// Lets the renderer specialize the color pass for the layout's permutation keys.
+    pub fn with_permutations(mut self, permutations: MaterialPermutations) -> Self {
+        self.permutations = Some(permutations);
+        self
+    }
 }
 
 impl Material {
@@ -113,6 +191,27 @@ impl Material {
         }
     }
 
+    /// Source of specialized shader variants, `None` if the material always uses
+    /// its [`shader_set`](Self::shader_set).
+    pub fn permutations(&self) -> Option<&MaterialPermutations> {
+        match self {
+            Material::Default(m) => Some(&m.permutations),
+            Material::Fallback(_) => None,
+            Material::Custom(m) => m.permutations.as_ref(),
+        }
+    }
+
+    pub fn permutations_mut(&mut self) -> Option<&mut MaterialPermutations> {
+        match self {
+            Material::Default(m) => Some(&mut m.permutations),
+            Material::Fallback(_) => None,
+            Material::Custom(m) => m.permutations.as_mut(),
+        }
+    }
+
+    /// Inputs of the built-in PBR material. The immediates are ordered so the
+    /// vectors land on their WGSL alignment without padding and the whole block
+    /// fits into the 128 bytes of immediate data the renderer requests.
     pub fn default_layout() -> MaterialInputLayout {
         MaterialInputLayout {
             immediates: vec![
@@ -171,6 +270,71 @@ impl Material {
                     ty: MaterialValueType::Bool,
                     default: MaterialValue::Bool(false),
                 },
+                MaterialImmediateDef {
+                    name: "use_emissive_texture".to_string(),
+                    ty: MaterialValueType::Bool,
+                    default: MaterialValue::Bool(false),
+                },
+                MaterialImmediateDef {
+                    name: "use_occlusion_texture".to_string(),
+                    ty: MaterialValueType::Bool,
+                    default: MaterialValue::Bool(false),
+                },
+                MaterialImmediateDef {
+                    name: "double_sided".to_string(),
+                    ty: MaterialValueType::Bool,
+                    default: MaterialValue::Bool(false),
+                },
+                MaterialImmediateDef {
+                    name: "emissive".to_string(),
+                    ty: MaterialValueType::Vec3,
+                    default: MaterialValue::Vec3(Vec3::ZERO),
+                },
+                MaterialImmediateDef {
+                    name: "emissive_strength".to_string(),
+                    ty: MaterialValueType::F32,
+                    default: MaterialValue::F32(1.0),
+                },
+                MaterialImmediateDef {
+                    name: "occlusion_strength".to_string(),
+                    ty: MaterialValueType::F32,
+                    default: MaterialValue::F32(1.0),
+                },
+                MaterialImmediateDef {
+                    name: "alpha_cutoff".to_string(),
+                    ty: MaterialValueType::F32,
+                    default: MaterialValue::F32(0.0),
+                },
+                MaterialImmediateDef {
+                    name: "clearcoat".to_string(),
+                    ty: MaterialValueType::F32,
+                    default: MaterialValue::F32(0.0),
+                },
+                MaterialImmediateDef {
+                    name: "clearcoat_roughness".to_string(),
+                    ty: MaterialValueType::F32,
+                    default: MaterialValue::F32(0.0),
+                },
+                MaterialImmediateDef {
+                    name: "transmission".to_string(),
+                    ty: MaterialValueType::F32,
+                    default: MaterialValue::F32(0.0),
+                },
+                MaterialImmediateDef {
+                    name: "uv_rotation".to_string(),
+                    ty: MaterialValueType::F32,
+                    default: MaterialValue::F32(0.0),
+                },
+                MaterialImmediateDef {
+                    name: "uv_offset".to_string(),
+                    ty: MaterialValueType::Vec2,
+                    default: MaterialValue::Vec2(Vec2::ZERO),
+                },
+                MaterialImmediateDef {
+                    name: "uv_scale".to_string(),
+                    ty: MaterialValueType::Vec2,
+                    default: MaterialValue::Vec2(Vec2::ONE),
+                },
             ],
             textures: vec![
                 MaterialTextureDef {
@@ -185,7 +349,25 @@ impl Material {
                     name: "roughness".to_string(),
                     default: HTexture2D::FALLBACK_ROUGHNESS,
                 },
+                MaterialTextureDef {
+                    name: "emissive".to_string(),
+                    default: HTexture2D::FALLBACK_DIFFUSE,
+                },
+                MaterialTextureDef {
+                    name: "occlusion".to_string(),
+                    default: HTexture2D::FALLBACK_ROUGHNESS,
+                },
             ],
+            permutation_keys: [
+                "use_diffuse_texture",
+                "use_normal_texture",
+                "use_roughness_texture",
+                "use_metallic_texture",
+                "lit",
+                "grayscale_diffuse",
+            ]
+            .map(String::from)
+            .to_vec(),
         }
     }
 }
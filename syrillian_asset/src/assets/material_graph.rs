@@ -0,0 +1,588 @@
+//! Materials authored as node graphs.
+//!
+//! A [`MaterialGraphAsset`] bundles a [`MaterialGraph`] with the
+//! [`MaterialInputLayout`] it reads from. It is packaged as a material payload
+//! and compiled into a [`Material`] when loaded.
+//!
+//! The payload is plain JSON, so graphs can also be written by hand and packed
+//! from `.symat` files:
+//!
+//! ```json
+//! {
+//!     "name": "Tinted",
+//!     "layout": {
+//!         "immediates": [{ "name": "tint", "type": "vec4", "default": [1, 0.5, 0.5, 1] }],
+//!         "textures": [{ "name": "diffuse", "default": "diffuse" }],
+//!         "permutation_keys": []
+//!     },
+//!     "graph": {
+//!         "nodes": [
+//!             { "kind": "uv" },
+//!             { "kind": "texture", "texture": "diffuse", "uv": 0 },
+//!             { "kind": "parameter", "name": "tint", "type": "vec4" },
+//!             { "kind": "op", "op": "mul", "inputs": [1, 2] }
+//!         ],
+//!         "surface": { "base_color": 3 }
+//!     }
+//! }
+//! ```
+//!
+//! Connections are either a node index or a `[node, output]` pair.
+
+use crate::assets::{HTexture2D, Material};
+use crate::material_inputs::{MaterialImmediateDef, MaterialInputLayout, MaterialTextureDef};
+use crate::store::streaming;
+use crate::store::streaming::asset_store::{StreamingAssetFile, StreamingAssetPayload};
+use crate::store::streaming::decode_helper::{DecodeHelper, MapDecodeHelper, ParseDecode};
+use crate::store::streaming::packaged_scene::BuiltPayload;
+use crate::store::streaming::payload::StreamableAsset;
+use glamx::{Vec2, Vec3, Vec4};
+use serde_json::{Map, Value as JsonValue};
+use snafu::{Snafu, whatever};
+use std::collections::BTreeMap;
+use syrillian_reflect::serializer::JsonSerializer;
+use syrillian_reflect::{ReflectSerialize, Value};
+use syrillian_shadergen::graph::{
+    GraphOp, GraphPin, MaterialGraph, MaterialGraphNode, MaterialGraphSurface, MaterialGraphVertex,
+};
+use syrillian_shadergen::value::{MaterialValue, MaterialValueType};
+use syrillian_shadergen::{MaterialCompiler, MaterialGraphError};
+
+#[derive(Debug, Clone)]
+pub struct MaterialGraphAsset {
+    pub name: String,
+    pub layout: MaterialInputLayout,
+    pub graph: MaterialGraph,
+}
+
+#[derive(Debug, Clone, Snafu)]
+#[snafu(context(suffix(Err)), visibility(pub))]
+pub enum MaterialGraphAssetError {
+    #[snafu(display("Parameter '{name}' is not an immediate of the material layout"))]
+    UnknownParameter { name: String },
+    #[snafu(display(
+        "Parameter '{name}' is declared as {declared} in the material layout, but read as {read}"
+    ))]
+    ParameterType {
+        name: String,
+        declared: &'static str,
+        read: &'static str,
+    },
+    #[snafu(display("Texture '{name}' is not a texture of the material layout"))]
+    UnknownTexture { name: String },
+    #[snafu(display(
+        "Permutation key '{name}' is not a bool or u32 immediate of the material layout"
+    ))]
+    PermutationKey { name: String },
+    #[snafu(display(
+        "Texture '{name}' defaults to a texture that isn't a built-in fallback, which can't be saved"
+    ))]
+    TextureDefault { name: String },
+    #[snafu(display("Material {handle} doesn't exist"))]
+    MissingMaterial { handle: String },
+    #[snafu(transparent)]
+    Graph { source: MaterialGraphError },
+}
+
+/// Built-in textures a graph layout can fall back to, by their serialized name.
+const FALLBACK_TEXTURES: [(&str, HTexture2D); 3] = [
+    ("diffuse", HTexture2D::FALLBACK_DIFFUSE),
+    ("normal", HTexture2D::FALLBACK_NORMAL),
+    ("roughness", HTexture2D::FALLBACK_ROUGHNESS),
+];
+
+/// The serialized name of a built-in fallback texture.
+fn fallback_texture_name(texture: HTexture2D) -> Option<&'static str> {
+    FALLBACK_TEXTURES
+        .iter()
+        .find(|(_, handle)| *handle == texture)
+        .map(|(name, _)| *name)
+}
+
+impl MaterialGraphAsset {
+    pub fn new(name: impl Into<String>, layout: MaterialInputLayout, graph: MaterialGraph) -> Self {
+        Self {
+            name: name.into(),
+            layout,
+            graph,
+        }
+    }
+
+    /// Checks that the graph only reads inputs of its layout, that the layout can be saved
+    /// and that the graph compiles.
+    pub fn validate(&self) -> Result<(), MaterialGraphAssetError> {
+        for (name, read) in self.graph.parameters() {
+            let Some(field) = self.layout.immediate(name) else {
+                return UnknownParameterErr { name }.fail();
+            };
+            if field.ty != read {
+                return ParameterTypeErr {
+                    name,
+                    declared: field.ty.name(),
+                    read: read.name(),
+                }
+                .fail();
+            }
+        }
+
+        for name in self.graph.textures() {
+            if self.layout.texture(name).is_none() {
+                return UnknownTextureErr { name }.fail();
+            }
+        }
+
+        // Other textures only exist at runtime, so their handles aren't stable across saves
+        for texture in &self.layout.textures {
+            if fallback_texture_name(texture.default).is_none() {
+                return TextureDefaultErr {
+                    name: &texture.name,
+                }
+                .fail();
+            }
+        }
+
+        for name in &self.layout.permutation_keys {
+            if !self.layout.can_be_permutation_key(name) {
+                return PermutationKeyErr { name }.fail();
+            }
+        }
+
+        self.graph.validate()?;
+        Ok(())
+    }
+
+    /// Whether a material payload holds a graph rather than a [`PrefabMaterial`](crate::PrefabMaterial).
+    pub fn is_graph_payload(payload: &JsonValue) -> bool {
+        payload.get("graph").is_some()
+    }
+
+    /// Compiles the shaders of every pass.
+    pub fn compile(
+        &self,
+    ) -> Result<syrillian_shadergen::generator::MaterialShaderSetCode, MaterialGraphAssetError>
+    {
+        self.validate()?;
+        Ok(MaterialCompiler::try_compile_shader_set(
+            &mut self.graph.clone(),
+        )?)
+    }
+}
+
+impl StreamableAsset for MaterialGraphAsset {
+    fn encode(&self) -> BuiltPayload {
+        BuiltPayload {
+            payload: JsonSerializer::serialize_to_string(self),
+            blobs: vec![],
+        }
+    }
+
+    fn decode(
+        payload: &StreamingAssetPayload,
+        _package: &mut StreamingAssetFile,
+    ) -> streaming::error::Result<Self> {
+        payload.data.expect_parse("material graph")
+    }
+}
+
+// Encoding
+
+impl ReflectSerialize for MaterialGraphAsset {
+    fn serialize(this: &Self) -> Value {
+        Value::Object(BTreeMap::from([
+            ("name".to_string(), Value::String(this.name.clone())),
+            ("layout".to_string(), serialize_layout(&this.layout)),
+            ("graph".to_string(), serialize_graph(&this.graph)),
+        ]))
+    }
+}
+
+fn serialize_layout(layout: &MaterialInputLayout) -> Value {
+    let immediates = layout
+        .immediates
+        .iter()
+        .map(|field| {
+            Value::Object(BTreeMap::from([
+                ("name".to_string(), Value::String(field.name.clone())),
+                (
+                    "type".to_string(),
+                    Value::String(field.ty.name().to_string()),
+                ),
+                ("default".to_string(), serialize_value(&field.default)),
+            ]))
+        })
+        .collect();
+
+    let textures = layout
+        .textures
+        .iter()
+        .map(|texture| {
+            // Validation rejects other defaults, they load as the diffuse fallback
+            let default = fallback_texture_name(texture.default)
+                .map_or(Value::None, |name| Value::String(name.to_string()));
+            Value::Object(BTreeMap::from([
+                ("name".to_string(), Value::String(texture.name.clone())),
+                ("default".to_string(), default),
+            ]))
+        })
+        .collect();
+
+    let permutation_keys = layout
+        .permutation_keys
+        .iter()
+        .map(|name| Value::String(name.clone()))
+        .collect();
+
+    Value::Object(BTreeMap::from([
+        ("immediates".to_string(), Value::Array(immediates)),
+        ("textures".to_string(), Value::Array(textures)),
+        (
+            "permutation_keys".to_string(),
+            Value::Array(permutation_keys),
+        ),
+    ]))
+}
+
+fn serialize_value(value: &MaterialValue) -> Value {
+    match value {
+        MaterialValue::F32(v) => Value::Float(*v),
+        MaterialValue::U32(v) => Value::UInt(*v),
+        MaterialValue::Bool(v) => Value::Bool(*v),
+        MaterialValue::Vec2(v) => ReflectSerialize::serialize(v),
+        MaterialValue::Vec3(v) => ReflectSerialize::serialize(v),
+        MaterialValue::Vec4(v) => ReflectSerialize::serialize(v),
+    }
+}
+
+fn serialize_pin(pin: GraphPin) -> Value {
+    Value::Array(vec![Value::UInt(pin.node), Value::UInt(pin.output)])
+}
+
+fn serialize_pins(pins: &[GraphPin]) -> Value {
+    Value::Array(pins.iter().copied().map(serialize_pin).collect())
+}
+
+fn serialize_node(node: &MaterialGraphNode) -> Value {
+    let mut fields = BTreeMap::new();
+    let kind = match node {
+        MaterialGraphNode::VertexUv => "uv",
+        MaterialGraphNode::VertexWorldPosition => "vertex_world_position",
+        MaterialGraphNode::VertexWorldNormal => "vertex_world_normal",
+        MaterialGraphNode::VertexObjectPosition => "vertex_object_position",
+        MaterialGraphNode::SurfaceNormal => "surface_normal",
+        MaterialGraphNode::Time => "time",
+        MaterialGraphNode::Constant(value) => {
+            fields.insert("value".to_string(), Value::Float(*value));
+            "constant"
+        }
+        MaterialGraphNode::Parameter { name, value_type } => {
+            fields.insert("name".to_string(), Value::String(name.clone()));
+            fields.insert(
+                "type".to_string(),
+                Value::String(value_type.name().to_string()),
+            );
+            "parameter"
+        }
+        MaterialGraphNode::TextureSample { texture, uv } => {
+            fields.insert("texture".to_string(), Value::String(texture.clone()));
+            fields.insert("uv".to_string(), serialize_pin(*uv));
+            "texture"
+        }
+        MaterialGraphNode::Op { op, inputs } => {
+            fields.insert("op".to_string(), Value::String(op.name().to_string()));
+            fields.insert("inputs".to_string(), serialize_pins(inputs));
+            "op"
+        }
+        MaterialGraphNode::Swizzle { input, components } => {
+            fields.insert("input".to_string(), serialize_pin(*input));
+            fields.insert("components".to_string(), Value::String(components.clone()));
+            "swizzle"
+        }
+        MaterialGraphNode::Construct { value_type, parts } => {
+            fields.insert(
+                "type".to_string(),
+                Value::String(value_type.name().to_string()),
+            );
+            fields.insert("parts".to_string(), serialize_pins(parts));
+            "construct"
+        }
+        MaterialGraphNode::Split(input) => {
+            fields.insert("input".to_string(), serialize_pin(*input));
+            "split"
+        }
+        MaterialGraphNode::Call { function, args } => {
+            fields.insert("function".to_string(), Value::String(function.clone()));
+            fields.insert("args".to_string(), serialize_pins(args));
+            "call"
+        }
+    };
+    fields.insert("kind".to_string(), Value::String(kind.to_string()));
+    Value::Object(fields)
+}
+
+fn serialize_outputs<'a>(outputs: impl IntoIterator<Item = (&'a str, Option<GraphPin>)>) -> Value {
+    Value::Object(
+        outputs
+            .into_iter()
+            .filter_map(|(name, pin)| Some((name.to_string(), serialize_pin(pin?))))
+            .collect(),
+    )
+}
+
+fn serialize_graph(graph: &MaterialGraph) -> Value {
+    let surface = &graph.surface;
+    let vertex = &graph.vertex;
+    Value::Object(BTreeMap::from([
+        (
+            "nodes".to_string(),
+            Value::Array(graph.nodes.iter().map(serialize_node).collect()),
+        ),
+        (
+            "surface".to_string(),
+            serialize_outputs([
+                ("base_color", surface.base_color),
+                ("normal", surface.normal),
+                ("roughness", surface.roughness),
+                ("metallic", surface.metallic),
+                ("alpha", surface.alpha),
+                ("emissive", surface.emissive),
+                ("occlusion", surface.occlusion),
+                ("lit", surface.lit),
+                ("cast_shadows", surface.cast_shadows),
+            ]),
+        ),
+        (
+            "vertex".to_string(),
+            serialize_outputs([
+                ("world_position_offset", vertex.world_position_offset),
+                ("normal", vertex.normal),
+            ]),
+        ),
+    ]))
+}
+
+// Decoding
+
+impl ParseDecode<MaterialValueType> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<MaterialValueType> {
+        let name = self.expect_str(label)?;
+        match MaterialValueType::from_name(name) {
+            Some(ty) => Ok(ty),
+            None => whatever!("{label} has unknown value type '{name}'"),
+        }
+    }
+}
+
+impl ParseDecode<GraphPin> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<GraphPin> {
+        if self.is_number() {
+            return Ok(GraphPin::node(self.expect_u32(label)?));
+        }
+
+        let pair = self.expect_array(label)?;
+        if pair.len() != 2 {
+            whatever!(
+                "{label} expected [node, output] but found {} elements",
+                pair.len()
+            );
+        }
+        Ok(GraphPin::new(
+            pair[0].expect_u32(label)?,
+            pair[1].expect_u32(label)?,
+        ))
+    }
+}
+
+fn decode_value(
+    value: &JsonValue,
+    ty: MaterialValueType,
+) -> streaming::error::Result<MaterialValue> {
+    let label = "material parameter default";
+    Ok(match ty {
+        MaterialValueType::F32 => MaterialValue::F32(value.expect_f32(label)?),
+        MaterialValueType::U32 => MaterialValue::U32(value.expect_u32(label)?),
+        MaterialValueType::Bool => MaterialValue::Bool(value.expect_bool(label)?),
+        MaterialValueType::Vec2 => MaterialValue::Vec2(value.expect_parse(label)?),
+        MaterialValueType::Vec3 => MaterialValue::Vec3(value.expect_parse(label)?),
+        MaterialValueType::Vec4 => MaterialValue::Vec4(value.expect_parse(label)?),
+    })
+}
+
+fn zero_value(ty: MaterialValueType) -> MaterialValue {
+    match ty {
+        MaterialValueType::F32 => MaterialValue::F32(0.0),
+        MaterialValueType::U32 => MaterialValue::U32(0),
+        MaterialValueType::Bool => MaterialValue::Bool(false),
+        MaterialValueType::Vec2 => MaterialValue::Vec2(Vec2::ZERO),
+        MaterialValueType::Vec3 => MaterialValue::Vec3(Vec3::ZERO),
+        MaterialValueType::Vec4 => MaterialValue::Vec4(Vec4::ZERO),
+    }
+}
+
+impl ParseDecode<MaterialImmediateDef> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<MaterialImmediateDef> {
+        let object = self.expect_object(label)?;
+        let ty: MaterialValueType = object.required_field("type")?.expect_parse(label)?;
+        let default = match object.optional_field("default") {
+            None | Some(JsonValue::Null) => zero_value(ty),
+            Some(value) => decode_value(value, ty)?,
+        };
+        Ok(MaterialImmediateDef {
+            name: object.required_field("name")?.expect_parse(label)?,
+            ty,
+            default,
+        })
+    }
+}
+
+impl ParseDecode<MaterialTextureDef> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<MaterialTextureDef> {
+        let object = self.expect_object(label)?;
+        let default = match object.optional_field("default") {
+            None | Some(JsonValue::Null) => HTexture2D::FALLBACK_DIFFUSE,
+            Some(JsonValue::String(name)) => {
+                match FALLBACK_TEXTURES.iter().find(|(n, _)| n == name) {
+                    Some((_, handle)) => *handle,
+                    None => whatever!("{label} has unknown fallback texture '{name}'"),
+                }
+            }
+            Some(_) => whatever!("{label} default must name a fallback texture"),
+        };
+        Ok(MaterialTextureDef {
+            name: object.required_field("name")?.expect_parse(label)?,
+            default,
+        })
+    }
+}
+
+impl ParseDecode<MaterialInputLayout> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<MaterialInputLayout> {
+        let object = self.expect_object(label)?;
+        Ok(MaterialInputLayout {
+            immediates: object
+                .optional_field("immediates")
+                .expect_parse("material layout immediates")?
+                .unwrap_or_default(),
+            textures: object
+                .optional_field("textures")
+                .expect_parse("material layout textures")?
+                .unwrap_or_default(),
+            permutation_keys: object
+                .optional_field("permutation_keys")
+                .expect_parse("material layout permutation keys")?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl ParseDecode<MaterialGraphNode> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<MaterialGraphNode> {
+        let object = self.expect_object(label)?;
+        let field = |name: &str| object.required_field(name);
+
+        let kind = field("kind")?.expect_str(label)?;
+        Ok(match kind {
+            "uv" => MaterialGraphNode::VertexUv,
+            "vertex_world_position" => MaterialGraphNode::VertexWorldPosition,
+            "vertex_world_normal" => MaterialGraphNode::VertexWorldNormal,
+            "vertex_object_position" => MaterialGraphNode::VertexObjectPosition,
+            "surface_normal" => MaterialGraphNode::SurfaceNormal,
+            "time" => MaterialGraphNode::Time,
+            "constant" => MaterialGraphNode::Constant(field("value")?.expect_f32(label)?),
+            "parameter" => MaterialGraphNode::Parameter {
+                name: field("name")?.expect_parse(label)?,
+                value_type: field("type")?.expect_parse(label)?,
+            },
+            "texture" => MaterialGraphNode::TextureSample {
+                texture: field("texture")?.expect_parse(label)?,
+                uv: field("uv")?.expect_parse(label)?,
+            },
+            "op" => {
+                let name = field("op")?.expect_str(label)?;
+                let Some(op) = GraphOp::from_name(name) else {
+                    whatever!("{label} has unknown operation '{name}'");
+                };
+                MaterialGraphNode::Op {
+                    op,
+                    inputs: field("inputs")?.expect_parse(label)?,
+                }
+            }
+            "swizzle" => MaterialGraphNode::Swizzle {
+                input: field("input")?.expect_parse(label)?,
+                components: field("components")?.expect_parse(label)?,
+            },
+            "construct" => MaterialGraphNode::Construct {
+                value_type: field("type")?.expect_parse(label)?,
+                parts: field("parts")?.expect_parse(label)?,
+            },
+            "split" => MaterialGraphNode::Split(field("input")?.expect_parse(label)?),
+            "call" => MaterialGraphNode::Call {
+                function: field("function")?.expect_parse(label)?,
+                args: field("args")?.expect_parse(label)?,
+            },
+            unknown => whatever!("{label} has unknown kind '{unknown}'"),
+        })
+    }
+}
+
+fn optional_pin(
+    object: Option<&Map<String, JsonValue>>,
+    name: &str,
+) -> streaming::error::Result<Option<GraphPin>> {
+    object
+        .and_then(|object| object.optional_field(name))
+        .expect_parse(name)
+}
+
+impl ParseDecode<MaterialGraph> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<MaterialGraph> {
+        let object = self.expect_object(label)?;
+        let surface = match object.optional_field("surface") {
+            None | Some(JsonValue::Null) => None,
+            Some(surface) => Some(surface.expect_object("material graph surface")?),
+        };
+        let vertex = match object.optional_field("vertex") {
+            None | Some(JsonValue::Null) => None,
+            Some(vertex) => Some(vertex.expect_object("material graph vertex")?),
+        };
+
+        Ok(MaterialGraph {
+            nodes: object
+                .required_field("nodes")?
+                .expect_parse("material graph node")?,
+            surface: MaterialGraphSurface {
+                base_color: optional_pin(surface, "base_color")?,
+                normal: optional_pin(surface, "normal")?,
+                roughness: optional_pin(surface, "roughness")?,
+                metallic: optional_pin(surface, "metallic")?,
+                alpha: optional_pin(surface, "alpha")?,
+                emissive: optional_pin(surface, "emissive")?,
+                occlusion: optional_pin(surface, "occlusion")?,
+                lit: optional_pin(surface, "lit")?,
+                cast_shadows: optional_pin(surface, "cast_shadows")?,
+            },
+            vertex: MaterialGraphVertex {
+                world_position_offset: optional_pin(vertex, "world_position_offset")?,
+                normal: optional_pin(vertex, "normal")?,
+            },
+        })
+    }
+}
+
+impl ParseDecode<MaterialGraphAsset> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<MaterialGraphAsset> {
+        let root = self.expect_object(label)?;
+        let layout = match root.optional_field("layout") {
+            None | Some(JsonValue::Null) => Material::default_layout(),
+            Some(layout) => layout.expect_parse("material graph layout")?,
+        };
+        Ok(MaterialGraphAsset {
+            name: root
+                .required_field("name")?
+                .expect_parse("material graph name")?,
+            layout,
+            graph: root
+                .required_field("graph")?
+                .expect_parse("material graph")?,
+        })
+    }
+}
@@ -1,6 +1,7 @@
 use crate::HTexture2D;
 use std::collections::HashMap;
 use std::hash::{DefaultHasher, Hash, Hasher};
+use syrillian_shadergen::MaterialPermutation;
 use syrillian_shadergen::value::{MaterialValue, MaterialValueType};
 use wgpu::{
     BindGroupLayoutEntry, BindingType, SamplerBindingType, ShaderStages, TextureSampleType,
@@ -24,6 +25,10 @@ pub struct MaterialTextureDef {
 pub struct MaterialInputLayout {
     pub immediates: Vec<MaterialImmediateDef>,
     pub textures: Vec<MaterialTextureDef>,
+    /// `bool` and `u32` immediates baked into specialized shader variants instead
+    /// of being branched on at runtime. Materials without a permutation source
+    /// ignore them.
+    pub permutation_keys: Vec<String>,
 }
 
 fn align_to(offset: usize, align: usize) -> usize {
@@ -50,13 +55,46 @@ impl MaterialInputLayout {
         self.texture(name).map(|tex| tex.default)
     }
 
+    /// Whether `name` is a `bool` or `u32` immediate that can be a permutation key.
+    pub fn can_be_permutation_key(&self, name: &str) -> bool {
+        self.immediate(name)
+            .is_some_and(|field| !field.ty.is_float())
+    }
+
+    /// Values of the permutation keys in `values`, falling back to the defaults.
+    pub fn permutation(&self, values: &HashMap<String, MaterialValue>) -> MaterialPermutation {
+        let mut permutation = MaterialPermutation::new();
+        for name in &self.permutation_keys {
+            let Some(field) = self.immediate(name) else {
+                continue;
+            };
+            match values.get(name).unwrap_or(&field.default) {
+                MaterialValue::Bool(value) => permutation.set_bool(name.clone(), *value),
+                MaterialValue::U32(value) => permutation.set_u32(name.clone(), *value),
+                _ => {}
+            }
+        }
+        permutation
+    }
+
+    /// Byte offset of an immediate, following the WGSL struct layout rules.
+    fn field_offset(offset: usize, field: &MaterialImmediateDef) -> usize {
+        let align = field
+            .ty
+            .align()
+            .max(wgpu::IMMEDIATE_DATA_ALIGNMENT as usize);
+        align_to(offset, align)
+    }
+
     pub fn immediate_size(&self) -> u32 {
         let mut offset = 0usize;
+        let mut struct_align = wgpu::IMMEDIATE_DATA_ALIGNMENT as usize;
         for field in &self.immediates {
-            offset = align_to(offset, wgpu::IMMEDIATE_DATA_ALIGNMENT as usize);
+            offset = Self::field_offset(offset, field);
             offset += field.ty.size();
+            struct_align = struct_align.max(field.ty.align());
         }
-        let size = align_to(offset, wgpu::IMMEDIATE_DATA_ALIGNMENT as usize);
+        let size = align_to(offset, struct_align);
         size as u32
     }
 
@@ -66,7 +104,7 @@ impl MaterialInputLayout {
         let mut offset = 0usize;
 
         for field in &self.immediates {
-            offset = align_to(offset, wgpu::IMMEDIATE_DATA_ALIGNMENT as usize);
+            offset = Self::field_offset(offset, field);
 
             let value = values.get(&field.name).unwrap_or(&field.default);
             debug_assert_eq!(
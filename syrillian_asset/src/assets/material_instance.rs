@@ -6,7 +6,7 @@ use crate::store::{
 };
 use crate::{HMaterial, HMaterialInstance, HTexture2D, store_add_checked};
 use crossbeam_channel::Sender;
-use glamx::Vec3;
+use glamx::{Vec2, Vec3};
 use std::collections::HashMap;
 use syrillian_shadergen::value::MaterialValue;
 
@@ -60,6 +60,20 @@ impl MaterialInstance {
         }
     }
 
+    pub fn value_vec2(&self, name: &str) -> Option<Vec2> {
+        match self.value(name) {
+            Some(MaterialValue::Vec2(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn value_vec3(&self, name: &str) -> Option<Vec3> {
+        match self.value(name) {
+            Some(MaterialValue::Vec3(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
     pub fn set_bool(&mut self, name: &str, value: bool, layout: &MaterialInputLayout) {
         if layout.immediates.iter().any(|field| field.name == name) {
             self.values
@@ -184,6 +198,80 @@ impl MaterialInstanceBuilder {
         self.texture("roughness", texture)
     }
 
+    pub fn emissive_texture(self, texture: impl Into<Option<HTexture2D>>) -> Self {
+        self.texture("emissive", texture)
+    }
+
+    pub fn occlusion_texture(self, texture: impl Into<Option<HTexture2D>>) -> Self {
+        self.texture("occlusion", texture)
+    }
+
+    pub fn emissive(mut self, color: Vec3) -> Self {
+        self.values
+            .insert("emissive".to_string(), MaterialValue::Vec3(color));
+        self
+    }
+
+    pub fn emissive_strength(mut self, strength: f32) -> Self {
+        self.values.insert(
+            "emissive_strength".to_string(),
+            MaterialValue::F32(strength),
+        );
+        self
+    }
+
+    pub fn occlusion_strength(mut self, strength: f32) -> Self {
+        self.values.insert(
+            "occlusion_strength".to_string(),
+            MaterialValue::F32(strength),
+        );
+        self
+    }
+
+    /// Discards fragments with an alpha below `cutoff`. Zero disables alpha masking.
+    pub fn alpha_cutoff(mut self, cutoff: f32) -> Self {
+        self.values
+            .insert("alpha_cutoff".to_string(), MaterialValue::F32(cutoff));
+        self
+    }
+
+    /// Renders back faces and lights them with the flipped normal.
+    pub fn double_sided(mut self, double_sided: bool) -> Self {
+        self.values.insert(
+            "double_sided".to_string(),
+            MaterialValue::Bool(double_sided),
+        );
+        self
+    }
+
+    pub fn clearcoat(mut self, clearcoat: f32, roughness: f32) -> Self {
+        self.values
+            .insert("clearcoat".to_string(), MaterialValue::F32(clearcoat));
+        self.values.insert(
+            "clearcoat_roughness".to_string(),
+            MaterialValue::F32(roughness),
+        );
+        self
+    }
+
+    pub fn transmission(mut self, transmission: f32) -> Self {
+        self.values
+            .insert("transmission".to_string(), MaterialValue::F32(transmission));
+        self
+    }
+
+    /// Transforms the texture coordinates of all material textures: scaled first,
+    /// then rotated by `rotation` radians and finally offset.
+    pub fn uv_transform(mut self, offset: Vec2, rotation: f32, scale: Vec2) -> Self {
+        self.values
+            .insert("uv_offset".to_string(), MaterialValue::Vec2(offset));
+        self.values
+            .insert("uv_rotation".to_string(), MaterialValue::F32(rotation));
+        self.values
+            .insert("uv_scale".to_string(), MaterialValue::Vec2(scale));
+        self
+    }
+
     pub fn use_metallic_texture(mut self, enabled: bool) -> Self {
         self.values.insert(
             "use_metallic_texture".to_string(),
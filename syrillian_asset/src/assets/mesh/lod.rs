@@ -0,0 +1,248 @@
+use crate::mesh::Mesh;
+use crate::mesh::simplify::simplify;
+use crate::mesh::static_mesh_data::VertexBufferExt;
+use crate::store::streaming;
+use crate::store::streaming::decode_helper::{DecodeHelper, MapDecodeHelper, ParseDecode};
+use serde_json::Value as JsonValue;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A simplified level of a [`Mesh`], drawn from its own ranges of the mesh index buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshLod {
+    /// One index range per material, matching [`Mesh::material_ranges`]
+    pub material_ranges: Vec<Range<u32>>,
+    /// Largest distance the surface moved from the base mesh, relative to the mesh extent
+    pub error: f32,
+}
+
+/// Controls how [`Mesh::generate_lods`] simplifies a mesh
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LodSettings {
+    /// Most levels generated in addition to the base mesh
+    pub max_levels: usize,
+    /// Fraction of the triangles of the previous level every level aims for
+    pub reduction: f32,
+    /// Largest error a level may have, relative to the mesh extent
+    pub max_error: f32,
+    /// Meshes with fewer triangles don't get any further levels
+    pub min_triangles: usize,
+}
+
+impl Default for LodSettings {
+    fn default() -> Self {
+        Self {
+            max_levels: 4,
+            reduction: 0.5,
+            max_error: 0.05,
+            min_triangles: 64,
+        }
+    }
+}
+
+/// Levels that drop fewer triangles than this, relative to the previous level, are discarded
+const MIN_LEVEL_REDUCTION: f32 = 0.9;
+
+impl Mesh {
+    /// Replaces the LODs of this mesh with levels simplified from the base mesh, appending
+    /// their indices to the index buffer. Non-indexed meshes are indexed first.
+    ///
+    /// Returns the amount of generated levels.
+    pub fn generate_lods(&mut self, settings: &LodSettings) -> usize {
+        let base_len = if self.lods.is_empty() {
+            self.data.len() as u32
+        } else {
+            self.material_ranges
+                .iter()
+                .map(|r| r.end)
+                .max()
+                .unwrap_or(0)
+        };
+        self.lods.clear();
+
+        if base_len < 3 {
+            return 0;
+        }
+
+        let buffers = Arc::make_mut(&mut self.data);
+        let mut indices = buffers
+            .indices
+            .take()
+            .unwrap_or_else(|| (0..base_len).collect());
+        indices.truncate(base_len as usize);
+
+        if self.material_ranges.is_empty() {
+            self.material_ranges.push(0..base_len);
+        }
+
+        let mut previous_ranges = self.material_ranges.clone();
+        let mut previous_error = 0.0;
+
+        for _ in 0..settings.max_levels {
+            let previous_count: usize = previous_ranges.iter().map(|r| r.len()).sum();
+            if previous_count / 3 < settings.min_triangles {
+                break;
+            }
+
+            let mut simplified = Vec::with_capacity(previous_ranges.len());
+            let mut level_error: f32 = 0.0;
+            for range in &previous_ranges {
+                let source = &indices[range.start as usize..range.end as usize];
+                let target = (source.len() as f32 * settings.reduction) as usize / 3 * 3;
+                let result = simplify(buffers, source, target, settings.max_error);
+
+                level_error = level_error.max(result.error);
+                simplified.push(result.indices);
+            }
+
+            let level_count: usize = simplified.iter().map(Vec::len).sum();
+            if level_count == 0 || level_count as f32 > previous_count as f32 * MIN_LEVEL_REDUCTION
+            {
+                break;
+            }
+
+            let mut material_ranges = Vec::with_capacity(simplified.len());
+            for level_indices in simplified {
+                let start = indices.len() as u32;
+                indices.extend(level_indices);
+                material_ranges.push(start..indices.len() as u32);
+            }
+
+            previous_error += level_error;
+            previous_ranges = material_ranges.clone();
+            self.lods.push(MeshLod {
+                material_ranges,
+                error: previous_error,
+            });
+        }
+
+        buffers.indices = Some(indices);
+        self.lods.len()
+    }
+
+    /// The material ranges of LOD `level`, where level zero is the base mesh
+    pub fn lod_ranges(&self, level: usize) -> &[Range<u32>] {
+        match level {
+            0 => &self.material_ranges,
+            _ => self
+                .lods
+                .get(level - 1)
+                .map_or(&self.material_ranges, |lod| &lod.material_ranges),
+        }
+    }
+}
+
+impl ParseDecode<MeshLod> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<MeshLod> {
+        let lod = self.expect_object(label)?;
+        Ok(MeshLod {
+            material_ranges: lod
+                .required_field("material_ranges")?
+                .expect_parse("mesh lod material ranges")?,
+            error: lod
+                .required_field("error")?
+                .expect_parse("mesh lod error")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::static_mesh_data::RawVertexBuffers;
+    use glamx::{Vec2, Vec3};
+
+    /// A flat `size` x `size` quad grid, split along the middle into two materials
+    fn grid(size: u32) -> Mesh {
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+
+        for y in 0..=size {
+            for x in 0..=size {
+                positions.push(Vec3::new(x as f32, 0.0, y as f32));
+            }
+        }
+
+        for y in 0..size {
+            for x in 0..size {
+                let i = y * (size + 1) + x;
+                let next_row = i + size + 1;
+                indices.extend([i, next_row, i + 1, i + 1, next_row, next_row + 1]);
+            }
+        }
+
+        let mut buffers = RawVertexBuffers::from_positions(positions, None);
+        buffers.normals.fill(Vec3::Y);
+        buffers.uvs = buffers
+            .positions
+            .iter()
+            .map(|p| Vec2::new(p.x, p.z))
+            .collect();
+        let half = indices.len() as u32 / 2;
+        buffers.indices = Some(indices);
+
+        Mesh::builder()
+            .data(Arc::new(buffers))
+            .material_ranges(vec![0..half, half..half * 2])
+            .build()
+    }
+
+    #[test]
+    fn lods_shrink_and_stay_in_bounds() {
+        let mut mesh = grid(16);
+        let base_ranges = mesh.material_ranges.clone();
+        let vertex_count = mesh.data.positions.len() as u32;
+
+        let levels = mesh.generate_lods(&LodSettings::default());
+        assert!(levels > 0, "a flat grid should simplify");
+        assert_eq!(mesh.material_ranges, base_ranges);
+
+        let indices = mesh.data.indices.as_ref().unwrap();
+        let mut previous: usize = base_ranges.iter().map(|r| r.len()).sum();
+        for lod in &mesh.lods {
+            assert_eq!(lod.material_ranges.len(), base_ranges.len());
+            assert!(lod.error < 1e-3, "flat surfaces simplify without error");
+
+            let count: usize = lod.material_ranges.iter().map(|r| r.len()).sum();
+            assert!(count < previous);
+            assert_eq!(count % 3, 0);
+            previous = count;
+
+            for range in &lod.material_ranges {
+                let level = &indices[range.start as usize..range.end as usize];
+                assert!(level.iter().all(|&i| i < vertex_count));
+            }
+        }
+    }
+
+    #[test]
+    fn regenerating_replaces_previous_levels() {
+        let mut mesh = grid(16);
+        let settings = LodSettings::default();
+
+        let first = mesh.generate_lods(&settings);
+        let len = mesh.data.len();
+        let second = mesh.generate_lods(&settings);
+
+        assert_eq!(first, second);
+        assert_eq!(mesh.data.len(), len);
+    }
+
+    #[test]
+    fn non_indexed_meshes_get_indexed() {
+        let cube = Mesh::load_from_obj_slice(crate::mesh::CUBE_OBJ).unwrap();
+        let mut mesh = cube.clone();
+
+        mesh.generate_lods(&LodSettings::default());
+
+        let indices = mesh.data.indices.as_ref().unwrap();
+        assert_eq!(mesh.lod_ranges(0), cube.material_ranges.as_slice());
+        assert!(indices.len() >= cube.data.len());
+        assert!(
+            indices[..cube.data.len()]
+                .iter()
+                .copied()
+                .eq(0..cube.data.len() as u32)
+        );
+    }
+}
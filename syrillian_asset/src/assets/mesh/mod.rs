@@ -1,7 +1,12 @@
 pub mod bone;
 pub mod buffer;
 pub mod generic_vertex;
+pub mod lod;
+pub mod morph_target;
+pub mod optimize;
+pub mod quantize;
 pub mod simple_vertex;
+pub mod simplify;
 pub mod skinned_static_mesh;
 pub mod skinned_vertex;
 pub mod static_mesh;
@@ -12,6 +17,9 @@ pub use skinned_static_mesh::{SkinnedMesh, SkinnedMeshBuilder};
 pub use static_mesh::{Mesh, MeshBuilder};
 
 pub use bone::{Bone, Bones};
+pub use lod::{LodSettings, MeshLod};
+pub use morph_target::{MorphTarget, MorphTargets};
+pub use optimize::MeshStats;
 pub use simple_vertex::SimpleVertex3D;
 pub use skinned_vertex::SkinnedVertex3D;
 pub use unskinned_vertex::UnskinnedVertex3D;
@@ -0,0 +1,107 @@
+use crate::store::streaming::asset_store::StreamingAssetBlobKind;
+use crate::store::streaming::packaged_scene::PackedBlob;
+use glamx::Vec3;
+use std::ops::Range;
+
+/// A single blend shape: per-vertex offsets that are added on top of the base mesh,
+/// scaled by the target weight.
+#[derive(Debug, Default, Clone)]
+pub struct MorphTarget {
+    pub name: String,
+    /// Index-aligned with the mesh positions.
+    pub position_deltas: Vec<Vec3>,
+    /// Index-aligned with the mesh normals. May be empty if the target only moves positions.
+    pub normal_deltas: Vec<Vec3>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MorphTargets {
+    pub targets: Vec<MorphTarget>,
+    /// Weights the mesh uses when nothing else drives them.
+    pub default_weights: Vec<f32>,
+}
+
+impl MorphTargets {
+    /// Floats per target and vertex in [`packed_deltas`](Self::packed_deltas).
+    pub const FLOATS_PER_TARGET: usize = 6;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.targets.iter().position(|t| t.name == name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.targets.iter().map(|t| t.name.as_str())
+    }
+
+    /// Default weight for every target, missing entries are zero.
+    pub fn initial_weights(&self) -> Vec<f32> {
+        (0..self.len())
+            .map(|i| self.default_weights.get(i).copied().unwrap_or(0.0))
+            .collect()
+    }
+
+    /// Interleaves the deltas of the `vertices` range vertex-major for the skinning compute pass:
+    /// `[v0: t0.pos, t0.nrm, t1.pos, t1.nrm, ..., v1: ...]`.
+    pub fn packed_deltas(&self, vertices: Range<usize>) -> Vec<f32> {
+        let mut packed = Vec::with_capacity(vertices.len() * self.len() * Self::FLOATS_PER_TARGET);
+        for vertex in vertices {
+            for target in &self.targets {
+                let position = target
+                    .position_deltas
+                    .get(vertex)
+                    .copied()
+                    .unwrap_or(Vec3::ZERO);
+                let normal = target
+                    .normal_deltas
+                    .get(vertex)
+                    .copied()
+                    .unwrap_or(Vec3::ZERO);
+                packed.extend_from_slice(&position.to_array());
+                packed.extend_from_slice(&normal.to_array());
+            }
+        }
+        packed
+    }
+
+    /// Appends one position and, if present, one normal delta blob per target, in target order.
+    pub(crate) fn pack_blobs(&self, blobs: &mut Vec<PackedBlob>) {
+        for target in &self.targets {
+            PackedBlob::maybe_pack_data_into(
+                StreamingAssetBlobKind::MorphPositionDeltas,
+                &target.position_deltas,
+                blobs,
+            );
+            PackedBlob::maybe_pack_data_into(
+                StreamingAssetBlobKind::MorphNormalDeltas,
+                &target.normal_deltas,
+                blobs,
+            );
+        }
+    }
+
+    /// Applies the weighted position deltas on the CPU.
+    pub fn morphed_positions(&self, base: &[Vec3], weights: &[f32]) -> Vec<Vec3> {
+        let mut positions = base.to_vec();
+        for (target, weight) in self.targets.iter().zip(weights) {
+            if *weight == 0.0 {
+                continue;
+            }
+            for (position, delta) in positions.iter_mut().zip(&target.position_deltas) {
+                *position += *delta * *weight;
+            }
+        }
+        positions
+    }
+}
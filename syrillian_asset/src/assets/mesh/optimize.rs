@@ -0,0 +1,566 @@
+use crate::mesh::Mesh;
+use crate::mesh::static_mesh_data::VertexBufferExt;
+use glamx::Vec3;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Entries of the FIFO vertex cache the statistics and overdraw clusters are simulated with
+const FIFO_CACHE_SIZE: usize = 16;
+
+/// Entries of the LRU cache the vertex cache optimization scores vertices with
+const LRU_CACHE_SIZE: usize = 32;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+
+/// Marks vertices that are dropped while remapping
+const UNUSED: u32 = u32::MAX;
+
+/// Size and vertex cache efficiency of the base level of a [`Mesh`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MeshStats {
+    pub vertices: usize,
+    pub triangles: usize,
+    /// Vertex shader invocations per triangle. 3 is the worst case, around 0.5 is ideal.
+    pub acmr: f32,
+    /// Vertex shader invocations per vertex. 1 is ideal.
+    pub atvr: f32,
+}
+
+impl Mesh {
+    /// Counts the base mesh and simulates how well it hits the post-transform vertex cache
+    pub fn stats(&self) -> MeshStats {
+        let indices = self.base_indices();
+        let vertices = self.data.positions.len();
+        let triangles = indices.len() / 3;
+        let misses = cache_misses(&indices, vertices) as f32;
+
+        MeshStats {
+            vertices,
+            triangles,
+            acmr: misses / triangles.max(1) as f32,
+            atvr: misses / vertices.max(1) as f32,
+        }
+    }
+
+    /// Merges vertices that share all their attributes and morph deltas. Non-indexed meshes
+    /// are indexed first.
+    ///
+    /// Returns the amount of removed vertices.
+    pub fn weld_vertices(&mut self) -> usize {
+        let vertex_count = self.data.positions.len();
+        let mut unique: HashMap<Vec<u32>, u32> = HashMap::with_capacity(vertex_count);
+        let mut remap = Vec::with_capacity(vertex_count);
+
+        for vertex in 0..vertex_count {
+            let next = unique.len() as u32;
+            remap.push(*unique.entry(self.vertex_key(vertex)).or_insert(next));
+        }
+
+        let welded = vertex_count - unique.len();
+        if welded > 0 || self.data.indices.is_none() {
+            self.remap_vertices(&remap, unique.len());
+        }
+
+        welded
+    }
+
+    /// Reorders the triangles of every material range and level of detail so consecutive
+    /// triangles share vertices while they are still in the post-transform cache.
+    pub fn optimize_vertex_cache(&mut self) {
+        let ranges = self.index_ranges();
+        let vertex_count = self.data.positions.len();
+        let Some(indices) = Arc::make_mut(&mut self.data).indices.as_mut() else {
+            return;
+        };
+
+        for range in ranges {
+            if let Some(indices) = indices.get_mut(range.start as usize..range.end as usize) {
+                optimize_cache(indices, vertex_count);
+            }
+        }
+    }
+
+    /// Splits every range into clusters along vertex cache restarts and draws the clusters
+    /// facing away from the mesh center first, so they occlude more of the rest.
+    ///
+    /// Meant to run after [`optimize_vertex_cache`](Self::optimize_vertex_cache).
+    pub fn optimize_overdraw(&mut self) {
+        let ranges = self.index_ranges();
+        let buffers = Arc::make_mut(&mut self.data);
+        let Some(indices) = buffers.indices.as_mut() else {
+            return;
+        };
+
+        for range in ranges {
+            if let Some(indices) = indices.get_mut(range.start as usize..range.end as usize) {
+                optimize_overdraw(indices, &buffers.positions);
+            }
+        }
+    }
+
+    /// Orders the vertices by their first use in the index buffer and drops unused ones, so
+    /// vertex fetches stay close together in memory.
+    pub fn optimize_vertex_fetch(&mut self) {
+        let Some(indices) = &self.data.indices else {
+            return;
+        };
+
+        let mut remap = vec![UNUSED; self.data.positions.len()];
+        let mut next = 0;
+        for &index in indices {
+            let slot = &mut remap[index as usize];
+            if *slot == UNUSED {
+                *slot = next;
+                next += 1;
+            }
+        }
+
+        self.remap_vertices(&remap, next as usize);
+    }
+
+    fn base_indices(&self) -> Vec<u32> {
+        let Some(indices) = &self.data.indices else {
+            return (0..self.data.len() as u32).collect();
+        };
+
+        if self.material_ranges.is_empty() {
+            return indices.clone();
+        }
+
+        self.material_ranges
+            .iter()
+            .filter_map(|range| indices.get(range.start as usize..range.end as usize))
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    fn index_ranges(&self) -> Vec<Range<u32>> {
+        let mut ranges = self.material_ranges.clone();
+        if ranges.is_empty() {
+            ranges.push(0..self.data.len() as u32);
+        }
+
+        ranges.extend(
+            self.lods
+                .iter()
+                .flat_map(|lod| lod.material_ranges.iter().cloned()),
+        );
+        ranges
+    }
+
+    fn vertex_key(&self, vertex: usize) -> Vec<u32> {
+        let data = &self.data;
+        let mut key = Vec::with_capacity(12 + self.morph_targets.len() * 6);
+
+        key.extend(data.positions[vertex].to_array().map(f32::to_bits));
+        key.extend(data.uvs[vertex].to_array().map(f32::to_bits));
+        key.extend(data.normals[vertex].to_array().map(f32::to_bits));
+        key.extend(data.tangents[vertex].to_array().map(f32::to_bits));
+
+        for target in &self.morph_targets.targets {
+            for deltas in [&target.position_deltas, &target.normal_deltas] {
+                if let Some(delta) = deltas.get(vertex) {
+                    key.extend(delta.to_array().map(f32::to_bits));
+                }
+            }
+        }
+
+        key
+    }
+
+    /// Moves every vertex to `remap[vertex]`, dropping the ones mapped to [`UNUSED`], and
+    /// rewrites the indices to match
+    fn remap_vertices(&mut self, remap: &[u32], vertex_count: usize) {
+        let buffers = Arc::make_mut(&mut self.data);
+
+        let indices = match buffers.indices.take() {
+            Some(indices) => indices.iter().map(|&i| remap[i as usize]).collect(),
+            None => remap.to_vec(),
+        };
+
+        buffers.positions = remap_stream(&buffers.positions, remap, vertex_count);
+        buffers.uvs = remap_stream(&buffers.uvs, remap, vertex_count);
+        buffers.normals = remap_stream(&buffers.normals, remap, vertex_count);
+        buffers.tangents = remap_stream(&buffers.tangents, remap, vertex_count);
+        buffers.indices = Some(indices);
+
+        if self.morph_targets.is_empty() {
+            return;
+        }
+
+        for target in &mut Arc::make_mut(&mut self.morph_targets).targets {
+            target.position_deltas = remap_stream(&target.position_deltas, remap, vertex_count);
+            target.normal_deltas = remap_stream(&target.normal_deltas, remap, vertex_count);
+        }
+    }
+}
+
+fn remap_stream<T: Copy + Default>(stream: &[T], remap: &[u32], vertex_count: usize) -> Vec<T> {
+    if stream.is_empty() {
+        return Vec::new();
+    }
+
+    let mut remapped = vec![T::default(); vertex_count];
+    for (value, &target) in stream.iter().zip(remap) {
+        if target != UNUSED {
+            remapped[target as usize] = *value;
+        }
+    }
+    remapped
+}
+
+/// Simulated FIFO cache of [`FIFO_CACHE_SIZE`] entries, tracking when every vertex entered it
+struct FifoCache {
+    entered: Vec<usize>,
+    time: usize,
+}
+
+impl FifoCache {
+    fn new(vertex_count: usize) -> Self {
+        Self {
+            entered: vec![0; vertex_count],
+            time: FIFO_CACHE_SIZE + 1,
+        }
+    }
+
+    /// Returns whether the vertex had to be transformed
+    fn access(&mut self, vertex: u32) -> bool {
+        let entered = &mut self.entered[vertex as usize];
+        if self.time - *entered <= FIFO_CACHE_SIZE {
+            return false;
+        }
+
+        *entered = self.time;
+        self.time += 1;
+        true
+    }
+}
+
+fn cache_misses(indices: &[u32], vertex_count: usize) -> usize {
+    let mut cache = FifoCache::new(vertex_count);
+    indices.iter().filter(|&&i| cache.access(i)).count()
+}
+
+fn vertex_score(cache_position: Option<usize>, remaining: u32) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+
+    let cache_score = match cache_position {
+        Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+        Some(position) => {
+            let scale = 1.0 / (LRU_CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+        }
+        None => 0.0,
+    };
+
+    cache_score + VALENCE_BOOST_SCALE / (remaining as f32).sqrt()
+}
+
+/// Greedy triangle ordering after Tom Forsyth's "Linear-Speed Vertex Cache Optimisation".
+/// Always emits the triangle whose vertices score highest, favoring vertices recently used
+/// and vertices with few triangles left.
+fn optimize_cache(indices: &mut [u32], vertex_count: usize) {
+    let triangles = indices.as_chunks::<3>().0.to_vec();
+    if triangles.len() < 2 {
+        return;
+    }
+
+    let mut offsets = vec![0usize; vertex_count + 1];
+    for &v in triangles.as_flattened() {
+        offsets[v as usize + 1] += 1;
+    }
+    for v in 0..vertex_count {
+        offsets[v + 1] += offsets[v];
+    }
+
+    let mut adjacency = vec![0u32; offsets[vertex_count]];
+    let mut remaining = vec![0u32; vertex_count];
+    for (t, triangle) in triangles.iter().enumerate() {
+        for &v in triangle {
+            let v = v as usize;
+            adjacency[offsets[v] + remaining[v] as usize] = t as u32;
+            remaining[v] += 1;
+        }
+    }
+
+    let mut cache_positions: Vec<Option<usize>> = vec![None; vertex_count];
+    let mut vertex_scores: Vec<f32> = remaining.iter().map(|&r| vertex_score(None, r)).collect();
+    let triangle_score =
+        |scores: &[f32], triangle: &[u32; 3]| triangle.iter().map(|&v| scores[v as usize]).sum();
+
+    let mut emitted = vec![false; triangles.len()];
+    let mut cache: Vec<u32> = Vec::with_capacity(LRU_CACHE_SIZE + 3);
+    let mut output = Vec::with_capacity(triangles.len() * 3);
+    let mut scan = 0;
+
+    let mut best = triangles
+        .iter()
+        .enumerate()
+        .map(|(t, triangle)| (t, triangle_score(&vertex_scores, triangle)))
+        .fold(None, |best: Option<(usize, f32)>, (t, score)| match best {
+            Some((_, best_score)) if best_score >= score => best,
+            _ => Some((t, score)),
+        })
+        .map(|(t, _)| t);
+
+    while let Some(t) = best {
+        let triangle = triangles[t];
+        emitted[t] = true;
+        output.extend(triangle);
+
+        for v in triangle {
+            let v = v as usize;
+            let live = &mut adjacency[offsets[v]..offsets[v] + remaining[v] as usize];
+            if let Some(slot) = live.iter().position(|&other| other == t as u32) {
+                live.swap(slot, live.len() - 1);
+            }
+            remaining[v] -= 1;
+        }
+
+        let mut next_cache = triangle.to_vec();
+        next_cache.extend(cache.iter().filter(|v| !triangle.contains(v)));
+
+        for (position, &v) in next_cache.iter().enumerate() {
+            let v = v as usize;
+            cache_positions[v] = (position < LRU_CACHE_SIZE).then_some(position);
+            vertex_scores[v] = vertex_score(cache_positions[v], remaining[v]);
+        }
+
+        let mut best_score = f32::NEG_INFINITY;
+        best = None;
+        for &v in &next_cache {
+            let v = v as usize;
+            for &other in &adjacency[offsets[v]..offsets[v] + remaining[v] as usize] {
+                let score = triangle_score(&vertex_scores, &triangles[other as usize]);
+                if score > best_score {
+                    best_score = score;
+                    best = Some(other as usize);
+                }
+            }
+        }
+
+        next_cache.truncate(LRU_CACHE_SIZE);
+        cache = next_cache;
+
+        if best.is_none() {
+            while scan < triangles.len() && emitted[scan] {
+                scan += 1;
+            }
+            best = (scan < triangles.len()).then_some(scan);
+        }
+    }
+
+    indices[..output.len()].copy_from_slice(&output);
+}
+
+fn optimize_overdraw(indices: &mut [u32], positions: &[Vec3]) {
+    let triangles = indices.as_chunks::<3>().0.to_vec();
+    if triangles.len() < 2 {
+        return;
+    }
+
+    let mut cache = FifoCache::new(positions.len());
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    for (t, triangle) in triangles.iter().enumerate() {
+        let misses = triangle.iter().filter(|&&v| cache.access(v)).count();
+        if misses == 3 && t > start {
+            clusters.push(start..t);
+            start = t;
+        }
+    }
+    clusters.push(start..triangles.len());
+
+    if clusters.len() < 2 {
+        return;
+    }
+
+    // area weighted centroid and normal, the cross product is twice the area
+    let weigh = |triangles: &[[u32; 3]]| {
+        let mut centroid = Vec3::ZERO;
+        let mut normal = Vec3::ZERO;
+        let mut area = 0.0;
+        for &[a, b, c] in triangles {
+            let [a, b, c] = [a, b, c].map(|v| positions[v as usize]);
+            let cross = (b - a).cross(c - a);
+            let weight = cross.length();
+            centroid += (a + b + c) / 3.0 * weight;
+            normal += cross;
+            area += weight;
+        }
+        (
+            centroid / area.max(f32::EPSILON),
+            normal.normalize_or_zero(),
+        )
+    };
+
+    let (mesh_centroid, _) = weigh(&triangles);
+    let mut sorted: Vec<(f32, Range<usize>)> = clusters
+        .into_iter()
+        .map(|cluster| {
+            let (centroid, normal) = weigh(&triangles[cluster.clone()]);
+            ((centroid - mesh_centroid).dot(normal), cluster)
+        })
+        .collect();
+    sorted.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    let reordered: Vec<u32> = sorted
+        .into_iter()
+        .flat_map(|(_, cluster)| triangles[cluster].as_flattened().to_vec())
+        .collect();
+    indices[..reordered.len()].copy_from_slice(&reordered);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::static_mesh_data::RawVertexBuffers;
+    use crate::mesh::{LodSettings, MorphTarget, MorphTargets};
+    use glamx::{Vec2, Vec4};
+    use std::collections::HashSet;
+
+    /// A flat `size` x `size` grid with every triangle using its own vertices
+    fn unwelded_grid(size: u32) -> Mesh {
+        let mut positions = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                let corner = |dx: u32, dy: u32| Vec3::new((x + dx) as f32, 0.0, (y + dy) as f32);
+                positions.extend([corner(0, 0), corner(0, 1), corner(1, 0)]);
+                positions.extend([corner(1, 0), corner(0, 1), corner(1, 1)]);
+            }
+        }
+
+        let mut buffers = RawVertexBuffers::from_positions(positions, None);
+        buffers.normals.fill(Vec3::Y);
+        buffers.tangents.fill(Vec4::X);
+        buffers.uvs = buffers
+            .positions
+            .iter()
+            .map(|p| Vec2::new(p.x, p.z))
+            .collect();
+
+        Mesh::builder().data(Arc::new(buffers)).build()
+    }
+
+    fn triangle_set(mesh: &Mesh) -> HashSet<[[u32; 3]; 3]> {
+        mesh.data
+            .make_triangle_indices()
+            .into_iter()
+            .map(|triangle| {
+                let mut corners =
+                    triangle.map(|v| mesh.data.positions[v as usize].to_array().map(f32::to_bits));
+                let start = (0..3).min_by_key(|&i| corners[i]).unwrap();
+                corners.rotate_left(start);
+                corners
+            })
+            .collect()
+    }
+
+    #[test]
+    fn welding_shares_identical_vertices() {
+        let mut mesh = unwelded_grid(8);
+        let triangles = triangle_set(&mesh);
+
+        let welded = mesh.weld_vertices();
+
+        assert_eq!(welded, 8 * 8 * 6 - 9 * 9);
+        assert_eq!(mesh.data.positions.len(), 9 * 9);
+        assert!(mesh.data.is_valid());
+        assert_eq!(triangle_set(&mesh), triangles);
+    }
+
+    #[test]
+    fn welding_keeps_vertices_with_different_morph_deltas() {
+        let mut mesh = unwelded_grid(1);
+        let mut deltas = vec![Vec3::ZERO; 6];
+        deltas[3] = Vec3::Y;
+        mesh.morph_targets = Arc::new(MorphTargets {
+            targets: vec![MorphTarget {
+                name: "lift".to_string(),
+                position_deltas: deltas,
+                normal_deltas: Vec::new(),
+            }],
+            default_weights: Vec::new(),
+        });
+
+        let welded = mesh.weld_vertices();
+
+        assert_eq!(welded, 1);
+        assert_eq!(mesh.morph_targets.targets[0].position_deltas.len(), 5);
+        assert!(mesh.morph_targets.targets[0].normal_deltas.is_empty());
+    }
+
+    #[test]
+    fn optimizations_improve_cache_and_keep_triangles() {
+        let mut mesh = unwelded_grid(24);
+        mesh.weld_vertices();
+
+        // scatter the triangles so the cache has something to fix
+        let buffers = Arc::make_mut(&mut mesh.data);
+        let indices = buffers.indices.as_mut().unwrap();
+        let mut triangles = indices.as_chunks::<3>().0.to_vec();
+        let count = triangles.len();
+        triangles.sort_by_key(|triangle| (triangle[0] as usize * 7919) % count);
+        *indices = triangles.as_flattened().to_vec();
+
+        let triangles = triangle_set(&mesh);
+        let before = mesh.stats();
+
+        mesh.optimize_vertex_cache();
+        mesh.optimize_overdraw();
+        mesh.optimize_vertex_fetch();
+
+        let after = mesh.stats();
+        assert_eq!(triangle_set(&mesh), triangles);
+        assert_eq!(after.triangles, before.triangles);
+        assert_eq!(after.vertices, before.vertices);
+        assert!(after.acmr < before.acmr, "{after:?} vs {before:?}");
+
+        let indices = mesh.data.indices.as_ref().unwrap();
+        let mut next = 0;
+        for &index in indices {
+            assert!(index <= next, "vertices are ordered by first use");
+            next = next.max(index + 1);
+        }
+    }
+
+    #[test]
+    fn optimizations_keep_lod_ranges_apart() {
+        let mut mesh = unwelded_grid(16);
+        mesh.weld_vertices();
+        mesh.generate_lods(&LodSettings::default());
+        assert!(!mesh.lods.is_empty());
+
+        let range_sets = |mesh: &Mesh| -> Vec<HashSet<[u32; 3]>> {
+            let indices = mesh.data.indices.as_ref().unwrap();
+            mesh.index_ranges()
+                .iter()
+                .map(|range| {
+                    let range = &indices[range.start as usize..range.end as usize];
+                    range
+                        .as_chunks::<3>()
+                        .0
+                        .iter()
+                        .map(|t| {
+                            let mut t = *t;
+                            t.sort();
+                            t
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        let before = range_sets(&mesh);
+
+        mesh.optimize_vertex_cache();
+        mesh.optimize_overdraw();
+
+        let after = range_sets(&mesh);
+        assert_eq!(before, after);
+    }
+}
@@ -0,0 +1,101 @@
+//! Compact storage formats for vertex attributes in asset packages.
+//!
+//! Normals are octahedral encoded into two 16 bit snorms and uvs are stored as half floats.
+//! Both are expanded back to full floats when a package is loaded.
+
+use glamx::{Vec2, Vec3};
+use half::f16;
+
+/// Maps a unit vector onto the octahedron and stores it as two snorms
+pub fn encode_octahedral(normal: Vec3) -> [i16; 2] {
+    let normal = normal / (normal.x.abs() + normal.y.abs() + normal.z.abs()).max(f32::EPSILON);
+
+    let mut p = Vec2::new(normal.x, normal.y);
+    if normal.z < 0.0 {
+        let sign = Vec2::new(sign_not_zero(p.x), sign_not_zero(p.y));
+        p = (Vec2::ONE - Vec2::new(p.y.abs(), p.x.abs())) * sign;
+    }
+
+    [to_snorm16(p.x), to_snorm16(p.y)]
+}
+
+/// Unpacks a normal stored with [`encode_octahedral`]
+pub fn decode_octahedral([x, y]: [i16; 2]) -> Vec3 {
+    let p = Vec2::new(from_snorm16(x), from_snorm16(y));
+    let mut normal = Vec3::new(p.x, p.y, 1.0 - p.x.abs() - p.y.abs());
+
+    let fold = (-normal.z).max(0.0);
+    normal.x -= fold * sign_not_zero(normal.x);
+    normal.y -= fold * sign_not_zero(normal.y);
+
+    normal.normalize_or_zero()
+}
+
+pub fn quantize_normals(normals: &[Vec3]) -> Vec<[i16; 2]> {
+    normals.iter().copied().map(encode_octahedral).collect()
+}
+
+pub fn dequantize_normals(normals: &[[i16; 2]]) -> Vec<Vec3> {
+    normals.iter().copied().map(decode_octahedral).collect()
+}
+
+/// Stores uvs as the bits of two half floats
+pub fn quantize_uvs(uvs: &[Vec2]) -> Vec<[u16; 2]> {
+    uvs.iter()
+        .map(|uv| [f16::from_f32(uv.x).to_bits(), f16::from_f32(uv.y).to_bits()])
+        .collect()
+}
+
+pub fn dequantize_uvs(uvs: &[[u16; 2]]) -> Vec<Vec2> {
+    uvs.iter()
+        .map(|&[u, v]| Vec2::new(f16::from_bits(u).to_f32(), f16::from_bits(v).to_f32()))
+        .collect()
+}
+
+fn sign_not_zero(value: f32) -> f32 {
+    if value >= 0.0 { 1.0 } else { -1.0 }
+}
+
+fn to_snorm16(value: f32) -> i16 {
+    (value.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+fn from_snorm16(value: i16) -> f32 {
+    (value as f32 / i16::MAX as f32).max(-1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn octahedral_normals_round_trip() {
+        let normals = [
+            Vec3::X,
+            Vec3::NEG_Y,
+            Vec3::Z,
+            Vec3::NEG_Z,
+            Vec3::new(1.0, -2.0, -3.0).normalize(),
+            Vec3::new(-0.3, 0.8, 0.1).normalize(),
+        ];
+
+        for normal in normals {
+            let decoded = decode_octahedral(encode_octahedral(normal));
+            assert!(
+                decoded.dot(normal) > 0.99999,
+                "{normal} decoded to {decoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn half_uvs_keep_tiling_coordinates() {
+        let uvs = [Vec2::ZERO, Vec2::new(0.5, 1.0), Vec2::new(3.25, -2.0)];
+
+        let decoded = dequantize_uvs(&quantize_uvs(&uvs));
+
+        for (uv, decoded) in uvs.iter().zip(decoded) {
+            assert!(uv.distance(decoded) < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,452 @@
+//! Quadric error edge-collapse simplification of indexed triangle lists.
+//!
+//! Vertices sharing a position are welded for the topology, so split vertices (hard edges,
+//! UV seams) don't tear apart when their neighbours collapse. Vertices on open borders and
+//! on attribute seams are locked, which keeps silhouettes and texture layouts intact.
+
+use crate::mesh::static_mesh_data::RawVertexBuffers;
+use glamx::Vec3;
+use std::collections::HashMap;
+
+/// Normals closer than this (in `1 - dot`) and UVs closer than this count as the same attributes
+const ATTRIBUTE_EPSILON: f32 = 1e-4;
+
+/// Indices of a simplified triangle list with the error the simplification introduced
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simplified {
+    pub indices: Vec<u32>,
+    /// Largest distance a surface moved, relative to the extent of the mesh
+    pub error: f32,
+}
+
+/// Collapses edges of the triangle list `indices` until at most `target_index_count` indices
+/// are left or a collapse would move the surface further than `target_error`.
+///
+/// `target_error` is relative to the largest extent of the mesh. The returned indices only
+/// reference vertices that are already in `buffers`.
+pub fn simplify(
+    buffers: &RawVertexBuffers,
+    indices: &[u32],
+    target_index_count: usize,
+    target_error: f32,
+) -> Simplified {
+    debug_assert_eq!(indices.len() % 3, 0);
+
+    let welded = Welding::new(buffers, indices);
+    let mut simplifier = Simplifier::new(buffers, welded, indices);
+
+    let target_triangles = target_index_count / 3;
+    let max_cost = target_error * target_error;
+
+    while simplifier.triangles.len() > target_triangles {
+        let collapsed = simplifier.collapse_pass(target_triangles, max_cost);
+        simplifier.rebuild_triangles();
+
+        if collapsed == 0 {
+            break;
+        }
+    }
+
+    Simplified {
+        indices: simplifier.triangles.iter().flatten().copied().collect(),
+        error: simplifier.error.sqrt(),
+    }
+}
+
+/// Groups the referenced vertices by position
+struct Welding {
+    /// Welded position of every vertex
+    welded_of: Vec<u32>,
+    /// Vertices sharing every welded position
+    siblings: Vec<Vec<u32>>,
+    positions: Vec<Vec3>,
+}
+
+impl Welding {
+    fn new(buffers: &RawVertexBuffers, indices: &[u32]) -> Self {
+        let mut welded_of = vec![u32::MAX; buffers.positions.len()];
+        let mut siblings: Vec<Vec<u32>> = Vec::new();
+        let mut positions = Vec::new();
+        let mut by_position: HashMap<[u32; 3], u32> = HashMap::new();
+
+        for &vertex in indices {
+            if welded_of[vertex as usize] != u32::MAX {
+                continue;
+            }
+
+            let position = buffers.positions[vertex as usize];
+            let key = position.to_array().map(|c| (c + 0.0).to_bits());
+            let welded = *by_position.entry(key).or_insert_with(|| {
+                siblings.push(Vec::new());
+                positions.push(position);
+                siblings.len() as u32 - 1
+            });
+
+            welded_of[vertex as usize] = welded;
+            siblings[welded as usize].push(vertex);
+        }
+
+        Self {
+            welded_of,
+            siblings,
+            positions,
+        }
+    }
+}
+
+/// Symmetric 4x4 error quadric, weighted by the area of the planes it was built from
+#[derive(Debug, Default, Copy, Clone)]
+struct Quadric {
+    a00: f32,
+    a11: f32,
+    a22: f32,
+    a10: f32,
+    a20: f32,
+    a21: f32,
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    c: f32,
+    weight: f32,
+}
+
+impl Quadric {
+    fn from_triangle(p0: Vec3, p1: Vec3, p2: Vec3) -> Self {
+        let cross = (p1 - p0).cross(p2 - p0);
+        let area = cross.length() * 0.5;
+        if area <= f32::EPSILON {
+            return Self::default();
+        }
+
+        let n = cross.normalize();
+        let d = -n.dot(p0);
+
+        Self {
+            a00: n.x * n.x * area,
+            a11: n.y * n.y * area,
+            a22: n.z * n.z * area,
+            a10: n.y * n.x * area,
+            a20: n.z * n.x * area,
+            a21: n.z * n.y * area,
+            b0: n.x * d * area,
+            b1: n.y * d * area,
+            b2: n.z * d * area,
+            c: d * d * area,
+            weight: area,
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.a00 += other.a00;
+        self.a11 += other.a11;
+        self.a22 += other.a22;
+        self.a10 += other.a10;
+        self.a20 += other.a20;
+        self.a21 += other.a21;
+        self.b0 += other.b0;
+        self.b1 += other.b1;
+        self.b2 += other.b2;
+        self.c += other.c;
+        self.weight += other.weight;
+    }
+
+    /// Mean squared distance of `p` to the planes of this quadric
+    fn error(&self, p: Vec3) -> f32 {
+        if self.weight <= 0.0 {
+            return 0.0;
+        }
+
+        let rx = self.a00 * p.x + self.a10 * p.y + self.a20 * p.z;
+        let ry = self.a10 * p.x + self.a11 * p.y + self.a21 * p.z;
+        let rz = self.a20 * p.x + self.a21 * p.y + self.a22 * p.z;
+        let value = rx * p.x
+            + ry * p.y
+            + rz * p.z
+            + 2.0 * (self.b0 * p.x + self.b1 * p.y + self.b2 * p.z)
+            + self.c;
+
+        (value / self.weight).abs()
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Collapse {
+    from: u32,
+    to: u32,
+    cost: f32,
+}
+
+struct Simplifier<'a> {
+    buffers: &'a RawVertexBuffers,
+    welded: Welding,
+    /// Positions scaled so the largest extent of the mesh is one
+    positions: Vec<Vec3>,
+    quadrics: Vec<Quadric>,
+    locked: Vec<bool>,
+    /// Welded vertex every welded vertex was collapsed into, itself if still alive
+    welded_remap: Vec<u32>,
+    /// Vertex every vertex was replaced with
+    vertex_remap: Vec<u32>,
+    triangles: Vec<[u32; 3]>,
+    error: f32,
+}
+
+impl<'a> Simplifier<'a> {
+    fn new(buffers: &'a RawVertexBuffers, welded: Welding, indices: &[u32]) -> Self {
+        let (min, max) = welded
+            .positions
+            .iter()
+            .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), p| {
+                (min.min(*p), max.max(*p))
+            });
+        let extent = (max - min).max_element();
+        let scale = if extent > 0.0 { 1.0 / extent } else { 1.0 };
+        let positions: Vec<Vec3> = welded
+            .positions
+            .iter()
+            .map(|p| (*p - min) * scale)
+            .collect();
+
+        let welded_count = positions.len();
+        let mut quadrics = vec![Quadric::default(); welded_count];
+        let mut edge_uses: HashMap<(u32, u32), u32> = HashMap::new();
+
+        let triangles: Vec<[u32; 3]> = indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .filter(|t| {
+                let w = t.map(|v| welded.welded_of[v as usize]);
+                w[0] != w[1] && w[1] != w[2] && w[0] != w[2]
+            })
+            .collect();
+
+        for triangle in &triangles {
+            let w = triangle.map(|v| welded.welded_of[v as usize]);
+            let quadric = Quadric::from_triangle(
+                positions[w[0] as usize],
+                positions[w[1] as usize],
+                positions[w[2] as usize],
+            );
+            for i in 0..3 {
+                quadrics[w[i] as usize].add(&quadric);
+
+                let (a, b) = (w[i], w[(i + 1) % 3]);
+                *edge_uses.entry((a.min(b), a.max(b))).or_default() += 1;
+            }
+        }
+
+        let mut locked: Vec<bool> = welded
+            .siblings
+            .iter()
+            .map(|siblings| is_seam(buffers, siblings))
+            .collect();
+
+        // Open borders and non-manifold edges keep their vertices
+        for ((a, b), uses) in edge_uses {
+            if uses != 2 {
+                locked[a as usize] = true;
+                locked[b as usize] = true;
+            }
+        }
+
+        Self {
+            buffers,
+            welded_remap: (0..welded_count as u32).collect(),
+            vertex_remap: (0..buffers.positions.len() as u32).collect(),
+            welded,
+            positions,
+            quadrics,
+            locked,
+            triangles,
+            error: 0.0,
+        }
+    }
+
+    fn welded_of(&self, vertex: u32) -> u32 {
+        self.welded.welded_of[vertex as usize]
+    }
+
+    /// Current welded corners of `triangle`, following collapses made in this pass
+    fn welded_corners(&self, triangle: &[u32; 3]) -> [u32; 3] {
+        triangle.map(|v| self.welded_remap[self.welded_of(v) as usize])
+    }
+
+    fn candidate_collapses(&self) -> Vec<Collapse> {
+        let mut best: HashMap<(u32, u32), Collapse> = HashMap::new();
+
+        for triangle in &self.triangles {
+            let w = self.welded_corners(triangle);
+            for i in 0..3 {
+                let (a, b) = (w[i], w[(i + 1) % 3]);
+                let key = (a.min(b), a.max(b));
+                if best.contains_key(&key) {
+                    continue;
+                }
+
+                let options = [self.collapse(a, b), self.collapse(b, a)];
+                if let Some(collapse) = options
+                    .into_iter()
+                    .flatten()
+                    .min_by(|x, y| x.cost.total_cmp(&y.cost))
+                {
+                    best.insert(key, collapse);
+                }
+            }
+        }
+
+        let mut collapses: Vec<Collapse> = best.into_values().collect();
+        collapses.sort_by(|a, b| {
+            a.cost
+                .total_cmp(&b.cost)
+                .then(a.from.cmp(&b.from))
+                .then(a.to.cmp(&b.to))
+        });
+        collapses
+    }
+
+    fn collapse(&self, from: u32, to: u32) -> Option<Collapse> {
+        if self.locked[from as usize] {
+            return None;
+        }
+
+        let mut quadric = self.quadrics[from as usize];
+        quadric.add(&self.quadrics[to as usize]);
+
+        Some(Collapse {
+            from,
+            to,
+            cost: quadric.error(self.positions[to as usize]),
+        })
+    }
+
+    /// Collapses the cheapest independent edges, returning how many were collapsed
+    fn collapse_pass(&mut self, target_triangles: usize, max_cost: f32) -> usize {
+        let collapses = self.candidate_collapses();
+
+        let mut around: Vec<Vec<u32>> = vec![Vec::new(); self.positions.len()];
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            for welded in self.welded_corners(triangle) {
+                around[welded as usize].push(index as u32);
+            }
+        }
+
+        // Every collapse removes about two triangles
+        let budget = (self.triangles.len() - target_triangles).div_ceil(2);
+        let mut touched = vec![false; self.positions.len()];
+        let mut collapsed = 0;
+
+        for collapse in collapses {
+            if collapsed >= budget || collapse.cost > max_cost {
+                break;
+            }
+
+            let (from, to) = (collapse.from as usize, collapse.to as usize);
+            if touched[from] || touched[to] || self.flips_triangles(&around[from], from, to) {
+                continue;
+            }
+
+            touched[from] = true;
+            touched[to] = true;
+            for &triangle in &around[from] {
+                for welded in self.welded_corners(&self.triangles[triangle as usize]) {
+                    touched[welded as usize] = true;
+                }
+            }
+
+            self.apply(collapse);
+            self.error = self.error.max(collapse.cost);
+            collapsed += 1;
+        }
+
+        collapsed
+    }
+
+    /// Whether moving `from` onto `to` would turn any remaining triangle around
+    fn flips_triangles(&self, around: &[u32], from: usize, to: usize) -> bool {
+        around.iter().any(|&triangle| {
+            let w = self.welded_corners(&self.triangles[triangle as usize]);
+            if w.contains(&(to as u32)) {
+                return false;
+            }
+
+            let p = w.map(|v| self.positions[v as usize]);
+            let moved = w.map(|v| {
+                if v as usize == from {
+                    self.positions[to]
+                } else {
+                    self.positions[v as usize]
+                }
+            });
+
+            let before = (p[1] - p[0]).cross(p[2] - p[0]);
+            let after = (moved[1] - moved[0]).cross(moved[2] - moved[0]);
+
+            before.dot(after) <= 0.0 || after.length_squared() <= f32::EPSILON * f32::EPSILON
+        })
+    }
+
+    fn apply(&mut self, collapse: Collapse) {
+        let (from, to) = (collapse.from as usize, collapse.to as usize);
+
+        let quadric = self.quadrics[from];
+        self.quadrics[to].add(&quadric);
+        self.welded_remap[from] = collapse.to;
+
+        for &vertex in &self.welded.siblings[from] {
+            self.vertex_remap[vertex as usize] =
+                closest_sibling(self.buffers, vertex, &self.welded.siblings[to]);
+        }
+    }
+
+    /// Replaces collapsed vertices in the triangle list and drops the triangles that vanished
+    fn rebuild_triangles(&mut self) {
+        let triangles = std::mem::take(&mut self.triangles);
+
+        self.triangles = triangles
+            .into_iter()
+            .map(|t| t.map(|v| self.vertex_remap[v as usize]))
+            .filter(|t| {
+                let w = t.map(|v| self.welded_of(v));
+                w[0] != w[1] && w[1] != w[2] && w[0] != w[2]
+            })
+            .collect();
+    }
+}
+
+/// Whether the vertices at one position carry different normals or UVs
+fn is_seam(buffers: &RawVertexBuffers, siblings: &[u32]) -> bool {
+    let Some((&first, rest)) = siblings.split_first() else {
+        return false;
+    };
+
+    rest.iter()
+        .any(|&other| attribute_distance(buffers, first, other) > ATTRIBUTE_EPSILON)
+}
+
+fn attribute_distance(buffers: &RawVertexBuffers, a: u32, b: u32) -> f32 {
+    let (a, b) = (a as usize, b as usize);
+
+    let normal = buffers
+        .normals
+        .get(a)
+        .zip(buffers.normals.get(b))
+        .map_or(0.0, |(a, b)| 1.0 - a.dot(*b));
+    let uv = buffers
+        .uvs
+        .get(a)
+        .zip(buffers.uvs.get(b))
+        .map_or(0.0, |(a, b)| a.distance(*b));
+
+    normal.max(uv)
+}
+
+fn closest_sibling(buffers: &RawVertexBuffers, vertex: u32, siblings: &[u32]) -> u32 {
+    siblings
+        .iter()
+        .copied()
+        .min_by(|a, b| {
+            attribute_distance(buffers, vertex, *a)
+                .total_cmp(&attribute_distance(buffers, vertex, *b))
+        })
+        .expect("welded vertices always have a sibling")
+}
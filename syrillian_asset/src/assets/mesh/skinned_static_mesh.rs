@@ -1,5 +1,5 @@
 use crate::mesh::static_mesh_data::{RawSkinningVertexBuffers, RawVertexBuffers};
-use crate::mesh::{Bones, PartialMesh};
+use crate::mesh::{Bones, MorphTargets, PartialMesh};
 use crate::store::streaming::asset_store::{
     AssetType, StreamingAssetBlobKind, StreamingAssetFile, StreamingAssetPayload,
 };
@@ -23,6 +23,8 @@ pub struct SkinnedMesh {
     pub skinning_data: Arc<RawSkinningVertexBuffers>,
     #[builder(default)]
     pub material_ranges: Vec<Range<u32>>,
+    #[builder(default)]
+    pub morph_targets: Arc<MorphTargets>,
     pub bones: Bones,
     pub bounding_sphere: Option<BoundingSphere>,
 }
@@ -127,6 +129,8 @@ impl StreamableAsset for SkinnedMesh {
             blobs.push(bind_locals);
         }
 
+        self.morph_targets.pack_blobs(&mut blobs);
+
         BuiltPayload {
             payload: JsonSerializer::serialize_to_string(self),
             blobs,
@@ -151,6 +155,12 @@ impl StreamableAsset for SkinnedMesh {
             .optional_field("bounding_sphere")
             .expect_parse("mesh bounding sphere")?;
 
+        let morph_targets = root
+            .optional_field("morph_targets")
+            .map(|m| m.expect_parse_blobs(&payload.blob_infos, package))
+            .transpose()?
+            .unwrap_or_default();
+
         let indices = payload
             .blob_infos
             .find(StreamingAssetBlobKind::MeshIndices)
@@ -202,6 +212,7 @@ impl StreamableAsset for SkinnedMesh {
             data: Arc::new(buffers),
             skinning_data: Arc::new(skinning_buffers),
             material_ranges,
+            morph_targets: Arc::new(morph_targets),
             bones,
             bounding_sphere,
         })
@@ -1,10 +1,15 @@
 use crate::mesh::buffer::UNIT_SQUARE_VERT;
 use crate::mesh::static_mesh_data::{RawVertexBuffers, VertexBufferExt};
-use crate::mesh::{BOUNDS_GIZMO, CUBE_OBJ, DEBUG_ARROW, MeshError, PartialMesh, SPHERE};
+use crate::mesh::{
+    BOUNDS_GIZMO, CUBE_OBJ, DEBUG_ARROW, MeshError, MeshLod, MorphTargets, PartialMesh, SPHERE,
+    quantize,
+};
 use crate::store::streaming::asset_store::{
     AssetType, StreamingAssetBlobKind, StreamingAssetFile, StreamingAssetPayload,
 };
-use crate::store::streaming::decode_helper::{DecodeHelper, MapDecodeHelper, ParseDecode};
+use crate::store::streaming::decode_helper::{
+    DecodeHelper, MapDecodeHelper, ParseDecode, ParseDecodeWithBlobs,
+};
 use crate::store::streaming::packaged_scene::{BuiltPayload, PackedBlob};
 use crate::store::streaming::payload::StreamableAsset;
 use crate::store::{
@@ -15,6 +20,7 @@ use crate::{HMesh, store_add_checked};
 use crossbeam_channel::Sender;
 use glamx::Vec3;
 use obj::IndexTuple;
+use std::io::{self, Write};
 use std::ops::Range;
 use std::sync::Arc;
 use syrillian_reflect::serializer::JsonSerializer;
@@ -25,7 +31,12 @@ pub struct Mesh {
     pub data: Arc<RawVertexBuffers>,
     #[builder(default)]
     pub material_ranges: Vec<Range<u32>>,
+    #[builder(default)]
+    pub morph_targets: Arc<MorphTargets>,
     pub bounding_sphere: Option<BoundingSphere>,
+    /// Simplified levels after the base mesh, from finest to coarsest
+    #[builder(default)]
+    pub lods: Vec<MeshLod>,
 }
 
 impl Mesh {
@@ -56,7 +67,7 @@ impl Mesh {
                     }
                 }
 
-                let mat_end = (mat_start as usize + buffers.positions.len()) as u32;
+                let mat_end = buffers.len() as u32;
                 material_ranges.push(mat_start..mat_end);
             }
         }
@@ -72,7 +83,9 @@ impl Mesh {
         Ok(Mesh {
             data: Arc::new(buffers),
             material_ranges,
+            morph_targets: Arc::default(),
             bounding_sphere,
+            lods: Vec::new(),
         })
     }
 
@@ -139,10 +152,76 @@ impl Mesh {
         Ok(Mesh {
             data: Arc::new(buffers),
             material_ranges: vec![],
+            morph_targets: Arc::default(),
             bounding_sphere,
+            lods: Vec::new(),
         })
     }
 
+    /// Writes the mesh as a Wavefront OBJ with one group per material range.
+    ///
+    /// Every vertex keeps its own `v`/`vt`/`vn` entry, so the output can be read back with
+    /// [`Mesh::load_from_obj_slice`] without reindexing. Morph targets are not exported.
+    pub fn write_obj<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let data = &self.data;
+        let has_uvs = data.uvs.len() == data.positions.len();
+        let has_normals = data.normals.len() == data.positions.len();
+
+        for p in &data.positions {
+            writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        if has_uvs {
+            for uv in &data.uvs {
+                writeln!(writer, "vt {} {}", uv.x, uv.y)?;
+            }
+        }
+        if has_normals {
+            for n in &data.normals {
+                writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)?;
+            }
+        }
+
+        let whole_mesh = 0..data.len() as u32;
+        let ranges = if self.material_ranges.is_empty() {
+            std::slice::from_ref(&whole_mesh)
+        } else {
+            &self.material_ranges[..]
+        };
+
+        for (material, range) in ranges.iter().enumerate() {
+            writeln!(writer, "g material_{material}")?;
+
+            let corners: Vec<u32> = match &data.indices {
+                Some(indices) => indices[range.start as usize..range.end as usize].to_vec(),
+                None => range.clone().collect(),
+            };
+
+            for triangle in corners.chunks_exact(3) {
+                write!(writer, "f")?;
+                for &corner in triangle {
+                    let i = corner + 1;
+                    match (has_uvs, has_normals) {
+                        (true, true) => write!(writer, " {i}/{i}/{i}")?,
+                        (true, false) => write!(writer, " {i}/{i}")?,
+                        (false, true) => write!(writer, " {i}//{i}")?,
+                        (false, false) => write!(writer, " {i}")?,
+                    }
+                }
+                writeln!(writer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Convenience wrapper around [`Mesh::write_obj`] that collects the output into a string.
+    pub fn to_obj_string(&self) -> String {
+        let mut out = Vec::new();
+        self.write_obj(&mut out)
+            .expect("writing into a Vec cannot fail");
+        String::from_utf8(out).expect("OBJ output is always valid UTF-8")
+    }
+
     fn resolve_obj_index(index: isize, len: usize) -> Option<usize> {
         if index > 0 {
             let idx = index as usize - 1;
@@ -235,8 +314,14 @@ impl StoreType for Mesh {
     }
 }
 
-impl StreamableAsset for Mesh {
-    fn encode(&self) -> BuiltPayload {
+impl Mesh {
+    /// Encodes the mesh like [`StreamableAsset::encode`], but with octahedral encoded normals
+    /// and half float uvs. Both are expanded to full floats again when decoded.
+    pub fn encode_quantized(&self) -> BuiltPayload {
+        self.encode_with(true)
+    }
+
+    fn encode_with(&self, quantized: bool) -> BuiltPayload {
         let mut blobs = Vec::new();
 
         if let Some(positions) =
@@ -245,13 +330,27 @@ impl StreamableAsset for Mesh {
             blobs.push(positions);
         }
 
-        if let Some(uvs) = PackedBlob::pack_data(StreamingAssetBlobKind::MeshUVs, &self.data.uvs) {
+        let uvs = if quantized {
+            PackedBlob::pack_data(
+                StreamingAssetBlobKind::MeshUVsHalf,
+                &quantize::quantize_uvs(&self.data.uvs),
+            )
+        } else {
+            PackedBlob::pack_data(StreamingAssetBlobKind::MeshUVs, &self.data.uvs)
+        };
+        if let Some(uvs) = uvs {
             blobs.push(uvs);
         }
 
-        if let Some(normals) =
+        let normals = if quantized {
+            PackedBlob::pack_data(
+                StreamingAssetBlobKind::MeshNormalsOctahedral,
+                &quantize::quantize_normals(&self.data.normals),
+            )
+        } else {
             PackedBlob::pack_data(StreamingAssetBlobKind::MeshNormals, &self.data.normals)
-        {
+        };
+        if let Some(normals) = normals {
             blobs.push(normals);
         }
 
@@ -268,11 +367,19 @@ impl StreamableAsset for Mesh {
             blobs.push(indices);
         }
 
+        self.morph_targets.pack_blobs(&mut blobs);
+
         BuiltPayload {
             payload: JsonSerializer::serialize_to_string(self),
             blobs,
         }
     }
+}
+
+impl StreamableAsset for Mesh {
+    fn encode(&self) -> BuiltPayload {
+        self.encode_with(false)
+    }
 
     fn decode(
         payload: &StreamingAssetPayload,
@@ -288,6 +395,17 @@ impl StreamableAsset for Mesh {
             .optional_field("bounding_sphere")
             .expect_parse("mesh bounding sphere")?;
 
+        let lods = root
+            .optional_field("lods")
+            .expect_parse("mesh lods")?
+            .unwrap_or_default();
+
+        let morph_targets = root
+            .optional_field("morph_targets")
+            .map(|m| m.expect_parse_blobs(&payload.blob_infos, package))
+            .transpose()?
+            .unwrap_or_default();
+
         let indices = payload
             .blob_infos
             .find(StreamingAssetBlobKind::MeshIndices)
@@ -299,14 +417,23 @@ impl StreamableAsset for Mesh {
             .blob_infos
             .find(StreamingAssetBlobKind::MeshPositions)?
             .decode_all_from_io(package)?;
-        let uvs = payload
-            .blob_infos
-            .find(StreamingAssetBlobKind::MeshUVs)?
-            .decode_all_from_io(package)?;
-        let normals = payload
+        let uvs = match payload.blob_infos.find(StreamingAssetBlobKind::MeshUVsHalf) {
+            Ok(uvs) => quantize::dequantize_uvs(&uvs.decode_all_from_io(package)?),
+            Err(_) => payload
+                .blob_infos
+                .find(StreamingAssetBlobKind::MeshUVs)?
+                .decode_all_from_io(package)?,
+        };
+        let normals = match payload
             .blob_infos
-            .find(StreamingAssetBlobKind::MeshNormals)?
-            .decode_all_from_io(package)?;
+            .find(StreamingAssetBlobKind::MeshNormalsOctahedral)
+        {
+            Ok(normals) => quantize::dequantize_normals(&normals.decode_all_from_io(package)?),
+            Err(_) => payload
+                .blob_infos
+                .find(StreamingAssetBlobKind::MeshNormals)?
+                .decode_all_from_io(package)?,
+        };
         let tangents = payload
             .blob_infos
             .find(StreamingAssetBlobKind::MeshTangents)?
@@ -331,7 +458,9 @@ impl StreamableAsset for Mesh {
         Ok(Mesh {
             data: Arc::new(buffers),
             material_ranges,
+            morph_targets: Arc::new(morph_targets),
             bounding_sphere,
+            lods,
         })
     }
 }
@@ -3,7 +3,7 @@ use glam::{Vec2, Vec4};
 use glamx::Vec3;
 use itertools::Itertools;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct RawVertexBuffers {
     pub positions: Vec<Vec3>,
     pub uvs: Vec<Vec2>,
@@ -32,6 +32,7 @@ pub mod bind_group_layout;
 pub mod cubemap;
 pub mod font;
 pub mod material;
+pub mod material_graph;
 pub mod material_instance;
 pub mod prefab;
 pub mod sound;
@@ -41,6 +42,7 @@ pub mod render_texture_2d;
 pub mod render_texture_2d_array;
 pub mod texture_2d;
 pub mod texture_2d_array;
+pub mod texture_compression;
 
 pub use self::animation_clip::*;
 pub use self::bind_group_layout::*;
@@ -48,6 +50,7 @@ pub use self::compute_shader::*;
 pub use self::cubemap::*;
 pub use self::font::Font;
 pub use self::material::*;
+pub use self::material_graph::*;
 pub use self::material_instance::*;
 pub use self::mesh::{Mesh, SkinnedMesh};
 pub use self::prefab::*;
@@ -58,6 +61,7 @@ pub use self::shader::*;
 pub use self::sound::*;
 pub use self::texture_2d::*;
 pub use self::texture_2d_array::*;
+pub use self::texture_compression::{TextureCompression, TextureCookSettings, TextureUsage};
 
 use crate::store::H;
 
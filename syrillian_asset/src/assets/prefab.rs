@@ -4,8 +4,9 @@ use crate::store::streaming::packaged_scene::BuiltPayload;
 use crate::store::streaming::payload::StreamableAsset;
 use crate::store::{AssetKey, AssetRefreshMessage, H, HandleName, StoreType, streaming};
 use crossbeam_channel::Sender;
-use glamx::{Quat, Vec3, Vec4};
+use glamx::{Quat, Vec2, Vec3, Vec4};
 use serde_json::Value as JsonValue;
+use snafu::whatever;
 use std::collections::BTreeMap;
 use syrillian_reflect::Value;
 use syrillian_reflect::serializer::JsonSerializer;
@@ -36,6 +37,45 @@ pub struct PrefabNode {
     pub mesh: Option<PrefabMeshBinding>,
     pub extras_json: Option<String>,
     pub components: Vec<PrefabComponent>,
+    pub camera: Option<PrefabCamera>,
+    pub light: Option<PrefabLight>,
+}
+
+/// A perspective camera on a prefab node. Angles are in **degrees**.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrefabCamera {
+    pub yfov: f32,
+    pub znear: f32,
+    pub zfar: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefabLightKind {
+    Point,
+    Spot,
+    Sun,
+}
+
+/// A punctual light on a prefab node. Angles are in **degrees**.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrefabLight {
+    pub kind: PrefabLightKind,
+    pub color: Vec3,
+    pub intensity: f32,
+    /// `None` means the light is not range limited.
+    pub range: Option<f32>,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+}
+
+impl PrefabLightKind {
+    pub const fn name(self) -> &'static str {
+        match self {
+            PrefabLightKind::Point => "Point",
+            PrefabLightKind::Spot => "Spot",
+            PrefabLightKind::Sun => "Sun",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -60,6 +100,14 @@ pub struct PrefabMaterial {
     pub metallic_roughness_texture: Option<String>,
     pub emissive_texture: Option<String>,
     pub occlusion_texture: Option<String>,
+    pub emissive_strength: f32,
+    pub occlusion_strength: f32,
+    pub uv_offset: Vec2,
+    pub uv_rotation: f32,
+    pub uv_scale: Vec2,
+    pub clearcoat: f32,
+    pub clearcoat_roughness: f32,
+    pub transmission: f32,
 }
 
 impl StoreType for PrefabMaterial {
@@ -96,7 +144,7 @@ impl StoreType for PrefabAsset {
     }
 }
 
-fn json_to_reflect_value(json: &JsonValue) -> Value {
+pub(crate) fn json_to_reflect_value(json: &JsonValue) -> Value {
     match json {
         JsonValue::Null => Value::None,
         JsonValue::Bool(b) => Value::Bool(*b),
@@ -170,6 +218,16 @@ impl ParseDecode<PrefabNode> for JsonValue {
             _ => Vec::new(),
         };
 
+        let camera = match object.optional_field("camera") {
+            None | Some(JsonValue::Null) => None,
+            Some(camera) => Some(camera.expect_parse("prefab camera")?),
+        };
+
+        let light = match object.optional_field("light") {
+            None | Some(JsonValue::Null) => None,
+            Some(light) => Some(light.expect_parse("prefab light")?),
+        };
+
         Ok(PrefabNode {
             name: object
                 .required_field("name")?
@@ -189,6 +247,63 @@ impl ParseDecode<PrefabNode> for JsonValue {
             mesh,
             extras_json,
             components,
+            camera,
+            light,
+        })
+    }
+}
+
+impl ParseDecode<PrefabCamera> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<PrefabCamera> {
+        let camera = self.expect_object(label)?;
+        Ok(PrefabCamera {
+            yfov: camera
+                .required_field("yfov")?
+                .expect_parse("prefab camera yfov")?,
+            znear: camera
+                .required_field("znear")?
+                .expect_parse("prefab camera znear")?,
+            zfar: camera
+                .required_field("zfar")?
+                .expect_parse("prefab camera zfar")?,
+        })
+    }
+}
+
+impl ParseDecode<PrefabLightKind> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<PrefabLightKind> {
+        let kind: String = self.expect_parse(label)?;
+        Ok(match kind.as_str() {
+            "Point" => PrefabLightKind::Point,
+            "Spot" => PrefabLightKind::Spot,
+            "Sun" => PrefabLightKind::Sun,
+            _ => whatever!("Unknown light kind '{kind}' in {label}"),
+        })
+    }
+}
+
+impl ParseDecode<PrefabLight> for JsonValue {
+    fn expect_parse(&self, label: &str) -> streaming::error::Result<PrefabLight> {
+        let light = self.expect_object(label)?;
+        Ok(PrefabLight {
+            kind: light
+                .required_field("kind")?
+                .expect_parse("prefab light kind")?,
+            color: light
+                .required_field("color")?
+                .expect_parse("prefab light color")?,
+            intensity: light
+                .required_field("intensity")?
+                .expect_parse("prefab light intensity")?,
+            range: light
+                .optional_field("range")
+                .expect_parse("prefab light range")?,
+            inner_angle: light
+                .required_field("inner_angle")?
+                .expect_parse("prefab light inner_angle")?,
+            outer_angle: light
+                .required_field("outer_angle")?
+                .expect_parse("prefab light outer_angle")?,
         })
     }
 }
@@ -241,6 +356,31 @@ impl StreamableAsset for PrefabMaterial {
             .data
             .expect_object("prefab material metadata root")?;
 
+        let emissive_strength: Option<f32> = root
+            .optional_field("emissive_strength")
+            .expect_parse("material emissive_strength")?;
+        let occlusion_strength: Option<f32> = root
+            .optional_field("occlusion_strength")
+            .expect_parse("material occlusion_strength")?;
+        let uv_offset: Option<Vec2> = root
+            .optional_field("uv_offset")
+            .expect_parse("material uv_offset")?;
+        let uv_rotation: Option<f32> = root
+            .optional_field("uv_rotation")
+            .expect_parse("material uv_rotation")?;
+        let uv_scale: Option<Vec2> = root
+            .optional_field("uv_scale")
+            .expect_parse("material uv_scale")?;
+        let clearcoat: Option<f32> = root
+            .optional_field("clearcoat")
+            .expect_parse("material clearcoat")?;
+        let clearcoat_roughness: Option<f32> = root
+            .optional_field("clearcoat_roughness")
+            .expect_parse("material clearcoat_roughness")?;
+        let transmission: Option<f32> = root
+            .optional_field("transmission")
+            .expect_parse("material transmission")?;
+
         Ok(PrefabMaterial {
             name: root.required_field("name")?.expect_parse("material name")?,
             base_color: root
@@ -282,6 +422,14 @@ impl StreamableAsset for PrefabMaterial {
             occlusion_texture: root
                 .optional_field("occlusion_texture")
                 .expect_parse("material occlusion_texture")?,
+            emissive_strength: emissive_strength.unwrap_or(1.0),
+            occlusion_strength: occlusion_strength.unwrap_or(1.0),
+            uv_offset: uv_offset.unwrap_or(Vec2::ZERO),
+            uv_rotation: uv_rotation.unwrap_or(0.0),
+            uv_scale: uv_scale.unwrap_or(Vec2::ONE),
+            clearcoat: clearcoat.unwrap_or(0.0),
+            clearcoat_roughness: clearcoat_roughness.unwrap_or(0.0),
+            transmission: transmission.unwrap_or(0.0),
         })
     }
 }
@@ -9,6 +9,9 @@ pub struct RenderTexture2D {
     pub width: u32,
     pub height: u32,
     pub format: TextureFormat,
+    /// Allocates a full mip chain, which is filled by
+    /// `AssetCache::generate_render_texture_mips` after rendering into the texture
+    pub generate_mips: bool,
 }
 
 impl StoreType for RenderTexture2D {
@@ -1,7 +1,8 @@
-use wgpu::naga::WithSpan;
+use syrillian_shadergen::preprocess::{ROOT_ORIGIN, source_location};
 use wgpu::naga::front::wgsl;
 use wgpu::naga::front::wgsl::ParseError;
 use wgpu::naga::valid::{Capabilities, ModuleInfo, ValidationError, ValidationFlags, Validator};
+use wgpu::naga::{SourceLocation, WithSpan};
 
 #[derive(Debug)]
 pub enum ShaderValidError {
@@ -15,20 +16,58 @@ impl ShaderValidError {
             ShaderValidError::Parse(e) => e.emit_to_stderr(source),
             ShaderValidError::ValidationError(e) => e.emit_to_stderr(source),
         }
+        if let Some(note) = self.origin_note(source, ROOT_ORIGIN) {
+            eprintln!("{note}");
+        }
     }
 
+    /// Emits the error for the generated `source`. Errors in code that came from the
+    /// shader's own source or an imported module also name the line there, with
+    /// `path` standing in for the shader's own source.
     pub fn emit_to_stderr_with_path(&self, source: &str, path: &str) {
         match self {
             ShaderValidError::Parse(e) => e.emit_to_stderr_with_path(source, path),
             ShaderValidError::ValidationError(e) => e.emit_to_stderr_with_path(source, path),
         }
+        if let Some(note) = self.origin_note(source, path) {
+            eprintln!("{note}");
+        }
     }
 
     pub fn emit_to_string(&self, source: &str) -> String {
-        match self {
+        let mut out = match self {
             ShaderValidError::Parse(e) => e.emit_to_string(source),
             ShaderValidError::ValidationError(e) => e.emit_to_string(source),
+        };
+        if let Some(note) = self.origin_note(source, ROOT_ORIGIN) {
+            out.push_str(&note);
+            out.push('\n');
         }
+        out
+    }
+
+    pub fn location(&self, source: &str) -> Option<SourceLocation> {
+        match self {
+            ShaderValidError::Parse(e) => e.location(source),
+            ShaderValidError::ValidationError(e) => e.location(source),
+        }
+    }
+
+    /// Where the failing line of the generated `source` was written.
+    pub fn origin(&self, source: &str) -> Option<(String, usize)> {
+        let location = self.location(source)?;
+        let (origin, line) = source_location(source, location.line_number as usize)?;
+        Some((origin.to_string(), line))
+    }
+
+    fn origin_note(&self, source: &str, path: &str) -> Option<String> {
+        let location = self.location(source)?;
+        let (origin, line) = self.origin(source)?;
+        let origin = if origin == ROOT_ORIGIN { path } else { &origin };
+        Some(format!(
+            "note: generated line {} is {origin}:{line}:{}",
+            location.line_number, location.line_position
+        ))
     }
 }
 
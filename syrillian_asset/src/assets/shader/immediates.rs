@@ -35,3 +35,23 @@ pub struct UiLineImmediate {
     pub to_color: [f32; 4],
     pub thickness: f32,
 }
+
+#[repr(C)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    zerocopy::Immutable,
+    zerocopy::IntoBytes,
+    zerocopy::FromBytes,
+    zerocopy::KnownLayout,
+)]
+pub struct UiRectImmediate {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub color: [f32; 4],
+    pub border_color: [f32; 4],
+    pub corner_radius: f32,
+    pub border_thickness: f32,
+    pub padding: [f32; 2],
+}
@@ -13,7 +13,7 @@ use self::defaults::{
 use crate::HShader;
 use crate::defaults::{PARTICLE_VERTEX_LAYOUT, PICKING_COLOR_TARGET};
 use crate::material_inputs::{MaterialInputLayout, MaterialTextureDef};
-use crate::shader::immediates::{TextImmediate, UiLineImmediate};
+use crate::shader::immediates::{TextImmediate, UiLineImmediate, UiRectImmediate};
 use crate::store::streaming::asset_store::{AssetType, StreamingAssetFile, StreamingAssetPayload};
 use crate::store::streaming::decode_helper::{DecodeHelper, MapDecodeHelper, ParseDecode};
 use crate::store::streaming::packaged_scene::BuiltPayload;
@@ -33,7 +33,10 @@ use std::path::Path;
 use syrillian_shadergen::function::{PbrShader, PostProcessPassthroughMaterial};
 use syrillian_shadergen::generator::MeshPass;
 use syrillian_shadergen::generator::{MaterialGroupOverrides, ShaderKind};
-use syrillian_shadergen::{MaterialCompiler, PostProcessCompiler, ShaderGenerator};
+use syrillian_shadergen::preprocess::Preprocessor;
+use syrillian_shadergen::{
+    MaterialCompiler, PostProcessCompiler, PreprocessError, ShaderGenerator,
+};
 use syrillian_utils::sizes::{VEC2_SIZE, VEC3_SIZE, VEC4_SIZE, WGPU_VEC4_ALIGN};
 use wgpu::{
     ColorTargetState, PolygonMode, PrimitiveTopology, VertexAttribute, VertexBufferLayout,
@@ -103,6 +106,9 @@ pub struct Shader {
     shader_type: ShaderType,
     material_layout: Option<MaterialInputLayout>,
     material_groups: Option<MaterialShaderGroups>,
+    /// Names defined for the `#ifdef` directives of the source.
+    #[builder(default)]
+    defines: Vec<String>,
 }
 
 impl<S: shader_builder::State> ShaderBuilder<S>
@@ -158,7 +164,10 @@ impl H<Shader> {
     pub const DEBUG_TEXT2D_GEOMETRY_ID: u32 = 21;
     pub const DEBUG_TEXT3D_GEOMETRY_ID: u32 = 22;
     pub const DEBUG_LIGHT_ID: u32 = 23;
-    pub const MAX_BUILTIN_ID: u32 = 23;
+    pub const RECT_2D_ID: u32 = 24;
+    pub const GIZMO_LINES_ID: u32 = 25;
+    pub const GIZMO_LINES_OVERLAY_ID: u32 = 26;
+    pub const MAX_BUILTIN_ID: u32 = 26;
 
     // The fallback shader if a pipeline fails
     pub const FALLBACK: H<Shader> = H::new(Self::FALLBACK_ID);
@@ -200,6 +209,15 @@ impl H<Shader> {
     // Shader for drawing single 2D lines.
     pub const LINE_2D: H<Shader> = H::new(Self::LINE_2D_ID);
 
+    // Shader for drawing filled and bordered 2D rectangles with rounded corners.
+    pub const RECT_2D: H<Shader> = H::new(Self::RECT_2D_ID);
+
+    // Gizmo lines, hidden behind scene geometry
+    pub const GIZMO_LINES: H<Shader> = H::new(Self::GIZMO_LINES_ID);
+
+    // Gizmo lines, drawn on top of everything
+    pub const GIZMO_LINES_OVERLAY: H<Shader> = H::new(Self::GIZMO_LINES_OVERLAY_ID);
+
     // An addon shader ID that is used for drawing debug edges on meshes
     pub const DEBUG_EDGES: H<Shader> = H::new(Self::DEBUG_EDGES_ID);
 
@@ -226,6 +244,7 @@ const SHADER_TEXT3D: &str = include_str!("shaders/text3d.wgsl");
 const SHADER_TEXT3D_PICKER: &str = include_str!("shaders/picking_text3d.wgsl");
 const SHADER_TEXT3D_SHADOW: &str = include_str!("shaders/text3d_shadow.wgsl");
 const SHADER_LINE2D: &str = include_str!("shaders/line.wgsl");
+const SHADER_RECT2D: &str = include_str!("shaders/rect.wgsl");
 const SHADER_POST_PROCESS_FXAA: &str = include_str!("shaders/post_process_fxaa.wgsl");
 const SHADER_SKYBOX: &str = include_str!("shaders/skybox.wgsl");
 const SHADER_SKYBOX_PROCEDURAL: &str = include_str!("shaders/skybox_procedural.wgsl");
@@ -261,6 +280,7 @@ impl StoreDefaults for Shader {
                 name: "atlas".to_string(),
                 default: H::FALLBACK_DIFFUSE,
             }],
+            permutation_keys: vec![],
         };
 
         store_add_checked!(
@@ -306,14 +326,7 @@ impl StoreDefaults for Shader {
         store_add_checked!(
             store,
             HShader::DIM3_ID,
-            Shader::builder()
-                .shader_type(ShaderType::Custom)
-                .name("3D Default")
-                .code(ShaderCode::Full(mesh3d))
-                .immediate_size(material_immediates)
-                .material_layout(default_layout.clone())
-                .material_groups(material_groups.clone())
-                .build()
+            Shader::new_material("3D Default", mesh3d, &default_layout)
         );
 
         store_add_checked!(
@@ -617,6 +630,50 @@ impl StoreDefaults for Shader {
                 .immediate_size(4)
                 .build()
         );
+
+        store_add_checked!(
+            store,
+            HShader::RECT_2D_ID,
+            Shader::builder()
+                .shader_type(ShaderType::Custom)
+                .name("Rect 2D Shader")
+                .code(ShaderCode::Full(SHADER_RECT2D.to_string()))
+                .color_target(ONLY_COLOR_TARGET_SRGB)
+                .topology(PrimitiveTopology::TriangleList)
+                .vertex_buffers(&[])
+                .immediate_size(size_of::<UiRectImmediate>() as u32)
+                .depth_enabled(false)
+                .build()
+        );
+
+        store_add_checked!(
+            store,
+            HShader::GIZMO_LINES_ID,
+            Shader::builder()
+                .shader_type(ShaderType::Custom)
+                .name("Gizmo Lines")
+                .code(ShaderCode::Full(DEBUG_LINES_SHADER.to_string()))
+                .topology(PrimitiveTopology::LineList)
+                .polygon_mode(PolygonMode::Line)
+                .vertex_buffers(DEBUG_LINE_VBL)
+                .color_target(ONLY_COLOR_TARGET)
+                .build()
+        );
+
+        store_add_checked!(
+            store,
+            HShader::GIZMO_LINES_OVERLAY_ID,
+            Shader::builder()
+                .shader_type(ShaderType::Custom)
+                .name("Gizmo Lines Overlay")
+                .code(ShaderCode::Full(DEBUG_LINES_SHADER.to_string()))
+                .topology(PrimitiveTopology::LineList)
+                .polygon_mode(PolygonMode::Line)
+                .vertex_buffers(DEBUG_LINE_VBL)
+                .color_target(ONLY_COLOR_TARGET)
+                .depth_enabled(false)
+                .build()
+        );
     }
 }
 
@@ -641,6 +698,7 @@ impl StoreType for Shader {
             HShader::TEXT_2D_ID => "2D Text Shader",
             HShader::TEXT_3D_ID => "3D Text Shader",
             HShader::TEXT_3D_SHADOW_ID => "3D Text Shadow Shader",
+            HShader::RECT_2D_ID => "2D Rect Shader",
             HShader::POST_PROCESS_ID => "Post Process Shader",
             HShader::POST_PROCESS_FXAA_ID => "Post Process FXAA Shader",
             HShader::SKYBOX_ID => "Skybox Background Shader",
@@ -653,6 +711,8 @@ impl StoreType for Shader {
             HShader::DEBUG_TEXT3D_GEOMETRY_ID => "Debug Text 3D Geometry Shader",
             HShader::DEBUG_LIGHT_ID => "Debug Lights Shader",
             HShader::DEBUG_MESH_BOUNDS_ID => "Debug Mesh Bounds Shader",
+            HShader::GIZMO_LINES_ID => "Gizmo Lines Shader",
+            HShader::GIZMO_LINES_OVERLAY_ID => "Gizmo Lines Overlay Shader",
 
             _ => return HandleName::Id(handle),
         };
@@ -711,6 +771,7 @@ impl Shader {
             shader_type: ShaderType::PostProcessing,
             material_layout: None,
             material_groups: None,
+            defines: Vec::new(),
         }
     }
 
@@ -732,6 +793,7 @@ impl Shader {
             shader_type: ShaderType::PostProcessing,
             material_layout: None,
             material_groups: None,
+            defines: Vec::new(),
         }
     }
 
@@ -753,6 +815,7 @@ impl Shader {
             shader_type: ShaderType::PostProcessing,
             material_layout: None,
             material_groups: None,
+            defines: Vec::new(),
         }
     }
 
@@ -774,6 +837,7 @@ impl Shader {
             shader_type: ShaderType::Default,
             material_layout: None,
             material_groups: None,
+            defines: Vec::new(),
         }
     }
 
@@ -804,9 +868,29 @@ impl Shader {
             shader_type: ShaderType::Default,
             material_layout: None,
             material_groups: None,
+            defines: Vec::new(),
         }
     }
 
+    /// The color pass shader of a material reading the inputs of `layout`.
+    pub fn new_material<S, S2>(name: S, code: S2, layout: &MaterialInputLayout) -> Shader
+    where
+        S: Into<String>,
+        S2: Into<String>,
+    {
+        Shader::builder()
+            .name(name)
+            .shader_type(ShaderType::Custom)
+            .code(ShaderCode::Full(code.into()))
+            .immediate_size(layout.immediate_size())
+            .material_layout(layout.clone())
+            .material_groups(MaterialShaderGroups {
+                material: layout.wgsl_material_group(),
+                material_textures: layout.wgsl_material_textures_group(),
+            })
+            .build()
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -831,6 +915,16 @@ impl Shader {
         self.code = ShaderCode::Fragment(source);
     }
 
+    pub fn defines(&self) -> &[String] {
+        &self.defines
+    }
+
+    /// Defines `name` for the `#ifdef` directives of the source.
+    pub fn with_define(mut self, name: impl Into<String>) -> Self {
+        self.defines.push(name.into());
+        self
+    }
+
     pub fn stage(&self) -> ShaderType {
         self.shader_type
     }
@@ -879,12 +973,26 @@ impl Shader {
         self.opaque
     }
 
+    /// # Panics
+    /// If the source has invalid preprocessor directives, see [`try_gen_code`](Self::try_gen_code).
     pub fn gen_code(&self) -> String {
         let map = self.bind_group_map();
         self.gen_code_with_map(&map)
     }
 
+    pub fn try_gen_code(&self) -> Result<String, PreprocessError> {
+        let map = self.bind_group_map();
+        self.try_gen_code_with_map(&map)
+    }
+
+    /// # Panics
+    /// If the source has invalid preprocessor directives.
     pub fn gen_code_with_map(&self, map: &BindGroupMap) -> String {
+        self.try_gen_code_with_map(map)
+            .unwrap_or_else(|e| panic!("Shader {:?} has an invalid source: {e}", self.name))
+    }
+
+    pub fn try_gen_code_with_map(&self, map: &BindGroupMap) -> Result<String, PreprocessError> {
         let fragment_only = self.code().is_only_fragment_shader();
         let kind = match self.stage() {
             ShaderType::PostProcessing => ShaderKind::PostProcess,
@@ -899,15 +1007,25 @@ impl Shader {
                 material_textures: groups.material_textures.as_str(),
             });
 
-        let generated = ShaderGenerator::assemble_shader(
+        let generated = ShaderGenerator::try_assemble_shader(
             self.code().code(),
+            &self.defines,
             fragment_only,
             kind,
             self.depth_enabled,
             material_groups,
-        );
+        )?;
+
+        Ok(rewrite_bind_groups(generated, map))
+    }
 
-        rewrite_bind_groups(generated, map)
+    /// The source with its imports and conditionals expanded.
+    fn expanded_source(&self) -> Option<String> {
+        let mut preprocessor = Preprocessor::new();
+        for define in &self.defines {
+            preprocessor.define(define.clone());
+        }
+        preprocessor.process(self.code().code()).ok()
     }
 
     pub fn needs_bgl(&self, bgl: HBGL) -> bool {
@@ -933,7 +1051,9 @@ impl Shader {
             HBGL::RENDER_ID => return true,
             _ => return false,
         };
-        let source = self.code().code();
+        // Imported modules can use bind groups as well
+        let expanded = self.expanded_source();
+        let source = expanded.as_deref().unwrap_or(self.code().code());
 
         for line in source.lines() {
             let Some(i) = line.find("#use ") else {
@@ -1061,6 +1181,9 @@ impl StreamableAsset for Shader {
         let opaque = root
             .optional_field("opaque")
             .expect_parse("shader opaque")?;
+        let defines = root
+            .optional_field("defines")
+            .expect_parse("shader defines")?;
 
         Ok(Shader::builder()
             .name(name)
@@ -1071,6 +1194,7 @@ impl StreamableAsset for Shader {
             .maybe_immediate_size(immediate_size)
             .maybe_depth_enabled(depth_enabled)
             .maybe_opaque(opaque)
+            .maybe_defines(defines)
             .build())
     }
 }
@@ -0,0 +1,14 @@
+// Copies the scene depth into the first level of the Hi-Z pyramid.
+@group(0) @binding(0) var scene_depth: texture_depth_2d;
+@group(0) @binding(1) var hi_z: texture_storage_2d<r32float, write>;
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let size = textureDimensions(hi_z);
+    if (gid.x >= size.x || gid.y >= size.y) {
+        return;
+    }
+
+    let depth = textureLoad(scene_depth, gid.xy, 0);
+    textureStore(hi_z, gid.xy, vec4<f32>(depth, 0.0, 0.0, 0.0));
+}
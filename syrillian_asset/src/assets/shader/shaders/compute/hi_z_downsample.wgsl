@@ -0,0 +1,41 @@
+// Reduces one level of the Hi-Z pyramid into the next, keeping the farthest depth.
+// When the source level has an odd size, the last row and column are folded into
+// the edge texels, so every texel stays conservative for the area it covers.
+@group(0) @binding(0) var src_level: texture_2d<f32>;
+@group(0) @binding(1) var dst_level: texture_storage_2d<r32float, write>;
+
+fn fetch(coord: vec2<u32>) -> f32 {
+    let size = textureDimensions(src_level);
+    return textureLoad(src_level, min(coord, size - 1u), 0).r;
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let size = textureDimensions(dst_level);
+    if (gid.x >= size.x || gid.y >= size.y) {
+        return;
+    }
+
+    let src_size = textureDimensions(src_level);
+    let base = gid.xy * 2u;
+
+    var depth = max(
+        max(fetch(base), fetch(base + vec2<u32>(1u, 0u))),
+        max(fetch(base + vec2<u32>(0u, 1u)), fetch(base + vec2<u32>(1u, 1u))),
+    );
+
+    let extra_x = gid.x == size.x - 1u && (src_size.x & 1u) == 1u;
+    let extra_y = gid.y == size.y - 1u && (src_size.y & 1u) == 1u;
+
+    if (extra_x) {
+        depth = max(depth, max(fetch(base + vec2<u32>(2u, 0u)), fetch(base + vec2<u32>(2u, 1u))));
+    }
+    if (extra_y) {
+        depth = max(depth, max(fetch(base + vec2<u32>(0u, 2u)), fetch(base + vec2<u32>(1u, 2u))));
+    }
+    if (extra_x && extra_y) {
+        depth = max(depth, fetch(base + vec2<u32>(2u, 2u)));
+    }
+
+    textureStore(dst_level, gid.xy, vec4<f32>(depth, 0.0, 0.0, 0.0));
+}
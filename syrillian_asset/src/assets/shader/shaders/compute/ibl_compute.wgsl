@@ -0,0 +1,207 @@
+#import syrillian::atmosphere
+
+struct SkyData {
+    sun_direction: vec3<f32>,
+    mode: u32,
+    sun_intensity: f32,
+    sun_strength: f32,
+    sun_elevation: f32,
+    sun_rotation: f32,
+    altitude: f32,
+    air_density: f32,
+    aerosols: f32,
+    _pad0: f32,
+}
+
+struct IblParams {
+    roughness: f32,
+    sample_count: u32,
+    _pad0: vec2<u32>,
+}
+
+@group(0) @binding(0) var<uniform> sky: SkyData;
+@group(0) @binding(1) var envSource: texture_cube<f32>;
+@group(0) @binding(2) var envSampler: sampler;
+@group(0) @binding(3) var<uniform> iblParams: IblParams;
+@group(0) @binding(4) var iblOutput: texture_storage_2d_array<rgba16float, write>;
+
+const SKY_MODE_PROCEDURAL: u32 = 1u;
+
+fn radical_inverse_vdc(bits_in: u32) -> f32 {
+    var bits = (bits_in << 16u) | (bits_in >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return f32(bits) * 2.3283064365386963e-10;
+}
+
+fn hammersley(i: u32, n: u32) -> vec2<f32> {
+    return vec2<f32>(f32(i) / f32(n), radical_inverse_vdc(i));
+}
+
+fn tangent_to_world(v: vec3<f32>, n: vec3<f32>) -> vec3<f32> {
+    let up = select(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(0.0, 0.0, 1.0), abs(n.z) < 0.999);
+    let t = normalize(cross(up, n));
+    let b = cross(n, t);
+    return normalize(t * v.x + b * v.y + n * v.z);
+}
+
+fn importance_sample_ggx(xi: vec2<f32>, n: vec3<f32>, a: f32) -> vec3<f32> {
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    let sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+    return tangent_to_world(vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta), n);
+}
+
+fn d_ggx(n_dot_h: f32, a: f32) -> f32 {
+    let a2 = a * a;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    return a2 / max(PI * d * d, 1e-8);
+}
+
+// Matches the direct lighting visibility term, so the LUT agrees with analytic lights
+fn v_smith_ggx_correlated(n_dot_v: f32, n_dot_l: f32, a: f32) -> f32 {
+    let a2 = a * a;
+    let gv = n_dot_l * sqrt(n_dot_v * n_dot_v * (1.0 - a2) + a2);
+    let gl = n_dot_v * sqrt(n_dot_l * n_dot_l * (1.0 - a2) + a2);
+    return 0.5 / max(gv + gl, 1e-8);
+}
+
+// Same face orientation as wgpu cube sampling
+fn cube_dir(face: u32, texel: vec2<u32>, size: u32) -> vec3<f32> {
+    let uv = (vec2<f32>(texel) + vec2<f32>(0.5)) / f32(size) * 2.0 - vec2<f32>(1.0);
+    let u = uv.x;
+    let v = uv.y;
+
+    switch face {
+        case 0u: { return normalize(vec3<f32>(1.0, -v, -u)); }
+        case 1u: { return normalize(vec3<f32>(-1.0, -v, u)); }
+        case 2u: { return normalize(vec3<f32>(u, 1.0, v)); }
+        case 3u: { return normalize(vec3<f32>(u, -1.0, -v)); }
+        case 4u: { return normalize(vec3<f32>(u, -v, 1.0)); }
+        default: { return normalize(vec3<f32>(-u, -v, -1.0)); }
+    }
+}
+
+// Radiance arriving from `dir`. The sun disk is left out, it's lit analytically.
+fn environment(dir: vec3<f32>, lod: f32) -> vec3<f32> {
+    if (sky.mode == SKY_MODE_PROCEDURAL) {
+        return atmosphere_eval(dir, sky_sun_direction()).sky_radiance;
+    }
+
+    return textureSampleLevel(envSource, envSampler, dir, lod).rgb;
+}
+
+// Source mip whose texels cover about as much solid angle as one sample
+fn sample_lod(pdf: f32, sample_count: u32) -> f32 {
+    let size = f32(textureDimensions(envSource).x);
+    let texel_solid_angle = 4.0 * PI / (6.0 * size * size);
+    let sample_solid_angle = 1.0 / (f32(sample_count) * pdf + 1e-6);
+    let max_lod = f32(textureNumLevels(envSource) - 1u);
+    return clamp(0.5 * log2(sample_solid_angle / texel_solid_angle) + 1.0, 0.0, max_lod);
+}
+
+fn output_texel(gid: vec3<u32>) -> bool {
+    let size = textureDimensions(iblOutput);
+    return gid.x < size.x && gid.y < size.y && gid.z < textureNumLayers(iblOutput);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_irradiance(@builtin(global_invocation_id) gid: vec3<u32>) {
+    if (!output_texel(gid)) {
+        return;
+    }
+
+    let n = cube_dir(gid.z, gid.xy, textureDimensions(iblOutput).x);
+    let count = max(iblParams.sample_count, 1u);
+
+    // Cosine weighted hemisphere, the pdf cancels the cosine and 1/PI of Lambert
+    var irradiance = vec3<f32>(0.0);
+    for (var i: u32 = 0u; i < count; i = i + 1u) {
+        let xi = hammersley(i, count);
+        let phi = 2.0 * PI * xi.x;
+        let cos_theta = sqrt(1.0 - xi.y);
+        let sin_theta = sqrt(xi.y);
+        let l = tangent_to_world(vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta), n);
+
+        let pdf = cos_theta / PI;
+        irradiance += environment(l, sample_lod(pdf, count));
+    }
+
+    textureStore(iblOutput, gid.xy, gid.z, vec4<f32>(irradiance / f32(count), 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_prefilter(@builtin(global_invocation_id) gid: vec3<u32>) {
+    if (!output_texel(gid)) {
+        return;
+    }
+
+    let n = cube_dir(gid.z, gid.xy, textureDimensions(iblOutput).x);
+    let roughness = iblParams.roughness;
+
+    if (roughness <= 0.0) {
+        textureStore(iblOutput, gid.xy, gid.z, vec4<f32>(environment(n, 0.0), 1.0));
+        return;
+    }
+
+    // Split sum approximation: view and reflection direction equal the normal
+    let a = roughness * roughness;
+    let count = max(iblParams.sample_count, 1u);
+    var color = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i: u32 = 0u; i < count; i = i + 1u) {
+        let h = importance_sample_ggx(hammersley(i, count), n, a);
+        let l = normalize(2.0 * dot(n, h) * h - n);
+        let n_dot_l = dot(n, l);
+        if (n_dot_l <= 0.0) {
+            continue;
+        }
+
+        let n_dot_h = saturate(dot(n, h));
+        let pdf = d_ggx(n_dot_h, a) * 0.25;
+        color += environment(l, sample_lod(pdf, count)) * n_dot_l;
+        weight += n_dot_l;
+    }
+
+    textureStore(iblOutput, gid.xy, gid.z, vec4<f32>(color / max(weight, 1e-4), 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_brdf_lut(@builtin(global_invocation_id) gid: vec3<u32>) {
+    if (!output_texel(gid)) {
+        return;
+    }
+
+    let size = textureDimensions(iblOutput);
+    let n_dot_v = max((f32(gid.x) + 0.5) / f32(size.x), 1e-3);
+    let roughness = (f32(gid.y) + 0.5) / f32(size.y);
+    let a = roughness * roughness;
+
+    let v = vec3<f32>(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    let n = vec3<f32>(0.0, 0.0, 1.0);
+
+    // Scale and bias to F0 of the specular response
+    let count = max(iblParams.sample_count, 1u);
+    var scale = 0.0;
+    var bias = 0.0;
+    for (var i: u32 = 0u; i < count; i = i + 1u) {
+        let h = importance_sample_ggx(hammersley(i, count), n, a);
+        let l = normalize(2.0 * dot(v, h) * h - v);
+
+        let n_dot_l = saturate(l.z);
+        let n_dot_h = saturate(h.z);
+        let v_dot_h = saturate(dot(v, h));
+        if (n_dot_l <= 0.0) {
+            continue;
+        }
+
+        let vis = v_smith_ggx_correlated(n_dot_v, n_dot_l, a) * 4.0 * n_dot_l * v_dot_h / max(n_dot_h, 1e-4);
+        let fc = pow(1.0 - v_dot_h, 5.0);
+        scale += (1.0 - fc) * vis;
+        bias += fc * vis;
+    }
+
+    textureStore(iblOutput, gid.xy, 0u, vec4<f32>(scale, bias, 0.0, 0.0) / f32(count));
+}
@@ -0,0 +1,124 @@
+#use render
+
+const LIGHT_TYPE_SUN: u32 = 1u;
+
+const MAX_LIGHTS_PER_CLUSTER: u32 = 64u;
+const CLUSTER_STRIDE: u32 = MAX_LIGHTS_PER_CLUSTER + 1u;
+
+struct Light {
+    position: vec3<f32>,
+    up: vec3<f32>,
+    radius: f32,
+    direction: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+    inner_angle: f32,
+    outer_angle: f32,
+    cos_inner: f32,
+    cos_outer: f32,
+    type_id: u32,
+    shadow_map_id: u32,
+    shadow_mat_base: u32,
+}
+
+struct LightClusterParams {
+    viewport: vec4<f32>,
+    grid: vec3<u32>,
+    debug_view: u32,
+}
+
+@group(1) @binding(0) var<uniform> light_count: u32;
+@group(1) @binding(1) var<storage, read> lights: array<Light>;
+@group(1) @binding(2) var<uniform> params: LightClusterParams;
+@group(1) @binding(3) var<storage, read_write> clusters: array<u32>;
+
+// Exponential slice distribution keeps clusters roughly cubic in view space
+fn slice_depth(slice: u32) -> f32 {
+    let near = max(camera.near, 1e-4);
+    let far = max(camera.far, near + 1e-3);
+    return near * pow(far / near, f32(slice) / f32(params.grid.z));
+}
+
+fn unproject_view(ndc: vec3<f32>) -> vec3<f32> {
+    let world_h = camera.inv_view_proj_mat * vec4<f32>(ndc, 1.0);
+    let world = world_h.xyz / world_h.w;
+    return (camera.view_mat * vec4<f32>(world, 1.0)).xyz;
+}
+
+// Point of the pixel ray through `ndc_xy` at the given (positive) view distance
+fn ray_at_depth(ndc_xy: vec2<f32>, depth: f32) -> vec3<f32> {
+    let a = unproject_view(vec3<f32>(ndc_xy, 0.0));
+    let b = unproject_view(vec3<f32>(ndc_xy, 1.0));
+    let dz = b.z - a.z;
+    let t = select((-depth - a.z) / dz, 0.0, abs(dz) < 1e-6);
+    return mix(a, b, t);
+}
+
+fn sphere_intersects_aabb(center: vec3<f32>, radius: f32, aabb_min: vec3<f32>, aabb_max: vec3<f32>) -> bool {
+    let closest = clamp(center, aabb_min, aabb_max);
+    let d = center - closest;
+    return dot(d, d) <= radius * radius;
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let grid = params.grid;
+    let cluster_count = grid.x * grid.y * grid.z;
+    let idx = gid.x;
+    if (idx >= cluster_count) {
+        return;
+    }
+
+    let x = idx % grid.x;
+    let y = (idx / grid.x) % grid.y;
+    let z = idx / (grid.x * grid.y);
+
+    // Tile bounds in uv space (y down), flipped into ndc
+    let uv_min = vec2<f32>(f32(x), f32(y)) / vec2<f32>(grid.xy);
+    let uv_max = vec2<f32>(f32(x + 1u), f32(y + 1u)) / vec2<f32>(grid.xy);
+    let ndc_min = vec2<f32>(uv_min.x * 2.0 - 1.0, 1.0 - uv_max.y * 2.0);
+    let ndc_max = vec2<f32>(uv_max.x * 2.0 - 1.0, 1.0 - uv_min.y * 2.0);
+
+    let near_depth = slice_depth(z);
+    let far_depth = slice_depth(z + 1u);
+
+    var aabb_min = vec3<f32>(1e30);
+    var aabb_max = vec3<f32>(-1e30);
+    for (var c: u32 = 0u; c < 4u; c = c + 1u) {
+        let corner = vec2<f32>(
+            select(ndc_min.x, ndc_max.x, (c & 1u) != 0u),
+            select(ndc_min.y, ndc_max.y, (c & 2u) != 0u),
+        );
+        let p_near = ray_at_depth(corner, near_depth);
+        let p_far = ray_at_depth(corner, far_depth);
+        aabb_min = min(aabb_min, min(p_near, p_far));
+        aabb_max = max(aabb_max, max(p_near, p_far));
+    }
+
+    let base = idx * CLUSTER_STRIDE;
+    var count: u32 = 0u;
+    for (var i: u32 = 0u; i < light_count; i = i + 1u) {
+        if (count >= MAX_LIGHTS_PER_CLUSTER) {
+            break;
+        }
+
+        let light = lights[i];
+        // The sun is evaluated separately and reaches every cluster
+        if (light.type_id == LIGHT_TYPE_SUN) {
+            continue;
+        }
+
+        if (light.range > 0.0) {
+            let center = (camera.view_mat * vec4<f32>(light.position, 1.0)).xyz;
+            if (!sphere_intersects_aabb(center, light.range, aabb_min, aabb_max)) {
+                continue;
+            }
+        }
+
+        clusters[base + 1u + count] = i;
+        count = count + 1u;
+    }
+
+    clusters[base] = count;
+}
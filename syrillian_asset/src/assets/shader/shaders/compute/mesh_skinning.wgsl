@@ -6,6 +6,8 @@ const BASE_NORMAL: u32 = BASE_UV + 2;
 const BASE_TANGENT: u32 = BASE_NORMAL + 3;
 const BASE_BONE_IDX: u32 = BASE_TANGENT + 4;
 const BASE_BONE_WEIGHTS: u32 = BASE_BONE_IDX + 2;
+// position delta (3) + normal delta (3) per morph target and vertex
+const MORPH_FLOATS_PER_TARGET: u32 = 6u;
 
 struct BoneData {
     mats: array<mat4x4<f32>, MAX_BONES>,
@@ -13,7 +15,7 @@ struct BoneData {
 
 struct SkinningParams {
     vertex_count: u32,
-    _pad0: u32,
+    morph_target_count: u32,
     _pad1: u32,
     _pad2: u32,
 }
@@ -24,6 +26,40 @@ struct SkinningParams {
 @group(0) @binding(3) var<storage, read_write> dst_position_words: array<u32>;
 @group(0) @binding(4) var<storage, read_write> dst_normal_words: array<u32>;
 @group(0) @binding(5) var<storage, read_write> dst_tangent_words: array<u32>;
+@group(0) @binding(6) var<storage, read> morph_deltas: array<f32>;
+@group(0) @binding(7) var<storage, read> morph_weights: array<f32>;
+
+struct Morphed {
+    position: vec3<f32>,
+    normal: vec3<f32>,
+}
+
+fn load_morph_vec3(base: u32) -> vec3<f32> {
+    return vec3<f32>(morph_deltas[base], morph_deltas[base + 1u], morph_deltas[base + 2u]);
+}
+
+// Adds the weighted blend shape offsets before the vertex gets skinned
+fn apply_morph_targets(idx: u32, position: vec3<f32>, normal: vec3<f32>) -> Morphed {
+    var out = Morphed(position, normal);
+    let count = params.morph_target_count;
+    if (count == 0u) {
+        return out;
+    }
+
+    let vertex_base = idx * count * MORPH_FLOATS_PER_TARGET;
+    for (var i = 0u; i < count; i++) {
+        let w = morph_weights[i];
+        if (w == 0.0) {
+            continue;
+        }
+        let base = vertex_base + i * MORPH_FLOATS_PER_TARGET;
+        out.position += load_morph_vec3(base) * w;
+        out.normal += load_morph_vec3(base + 3u) * w;
+    }
+
+    out.normal = normalize(out.normal);
+    return out;
+}
 
 fn normalize_weights(w_in: vec4<f32>) -> vec4<f32> {
     let w = max(w_in, vec4<f32>(0.0));
@@ -153,8 +189,9 @@ fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
 
     let base = idx * WORDS_PER_VERTEX;
 
-    let p_obj = vec4<f32>(load_vec3(base + BASE_POS), 1.0);
-    let n_obj = load_vec3(base + BASE_NORMAL);
+    let morphed = apply_morph_targets(idx, load_vec3(base + BASE_POS), load_vec3(base + BASE_NORMAL));
+    let p_obj = vec4<f32>(morphed.position, 1.0);
+    let n_obj = morphed.normal;
     let t_obj = load_vec4(base + BASE_TANGENT);
     let bone_idx = load_u16x4(base + BASE_BONE_IDX);
     let bone_w = load_vec4(base + BASE_BONE_WEIGHTS);
@@ -0,0 +1,81 @@
+#use render
+
+// Tests bounding spheres against the Hi-Z pyramid of the current frame. A candidate is
+// hidden when the nearest point of its bounds is behind the farthest depth of every
+// pixel it covers.
+
+struct OcclusionParams {
+    // Pixel rect (x, y, width, height) the camera renders into
+    viewport: vec4<f32>,
+    candidate_count: u32,
+    mip_count: u32,
+}
+
+@group(1) @binding(0) var<uniform> params: OcclusionParams;
+// Bounding spheres as center (xyz) and radius (w)
+@group(1) @binding(1) var<storage, read> candidates: array<vec4<f32>>;
+@group(1) @binding(2) var hi_z: texture_2d<f32>;
+@group(1) @binding(3) var<storage, read_write> visibility: array<u32>;
+
+fn is_visible(sphere: vec4<f32>) -> bool {
+    var ndc_min = vec3<f32>(1.0e9);
+    var ndc_max = vec3<f32>(-1.0e9);
+
+    for (var i = 0u; i < 8u; i++) {
+        let signs = vec3<f32>(
+            select(-1.0, 1.0, (i & 1u) != 0u),
+            select(-1.0, 1.0, (i & 2u) != 0u),
+            select(-1.0, 1.0, (i & 4u) != 0u),
+        );
+        let clip = camera.view_proj_mat * vec4<f32>(sphere.xyz + signs * sphere.w, 1.0);
+
+        // Bounds reaching behind the near plane can't be tested reliably
+        if (clip.w <= 1.0e-5) {
+            return true;
+        }
+
+        let ndc = clip.xyz / clip.w;
+        ndc_min = min(ndc_min, ndc);
+        ndc_max = max(ndc_max, ndc);
+    }
+
+    if (ndc_min.z <= 0.0) {
+        return true;
+    }
+
+    let uv_min = clamp(vec2<f32>(ndc_min.x, -ndc_max.y) * 0.5 + 0.5, vec2<f32>(0.0), vec2<f32>(1.0));
+    let uv_max = clamp(vec2<f32>(ndc_max.x, -ndc_min.y) * 0.5 + 0.5, vec2<f32>(0.0), vec2<f32>(1.0));
+    let px_min = params.viewport.xy + uv_min * params.viewport.zw;
+    let px_max = params.viewport.xy + uv_max * params.viewport.zw;
+
+    // Pick the level where the bounds cover at most 2x2 texels
+    let extent = max(px_max.x - px_min.x, px_max.y - px_min.y);
+    let level = u32(clamp(ceil(log2(max(extent, 1.0))), 0.0, f32(params.mip_count - 1u)));
+    let scale = 1.0 / f32(1u << level);
+    let size = textureDimensions(hi_z, level);
+
+    let texel_min = min(vec2<u32>(px_min * scale), size - 1u);
+    let texel_max = min(vec2<u32>(px_max * scale), size - 1u);
+    if (any(texel_max - texel_min > vec2<u32>(1u))) {
+        return true;
+    }
+
+    var farthest = 0.0;
+    for (var y = texel_min.y; y <= texel_max.y; y++) {
+        for (var x = texel_min.x; x <= texel_max.x; x++) {
+            farthest = max(farthest, textureLoad(hi_z, vec2<u32>(x, y), i32(level)).r);
+        }
+    }
+
+    return ndc_min.z <= farthest;
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let index = gid.x;
+    if (index >= params.candidate_count) {
+        return;
+    }
+
+    visibility[index] = u32(is_visible(candidates[index]));
+}
@@ -0,0 +1,15 @@
+// Copies the faces of a reflection probe capture into cube face layout. The capture
+// cameras are right-handed, which mirrors every face horizontally against the cube.
+@group(0) @binding(0) var capture: texture_2d_array<f32>;
+@group(0) @binding(1) var resolved: texture_storage_2d_array<rgba16float, write>;
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
+    let size = textureDimensions(resolved);
+    if (gid.x >= size.x || gid.y >= size.y || gid.z >= textureNumLayers(resolved)) {
+        return;
+    }
+
+    let source = vec2<u32>(size.x - 1u - gid.x, gid.y);
+    textureStore(resolved, gid.xy, gid.z, textureLoad(capture, source, gid.z, 0));
+}
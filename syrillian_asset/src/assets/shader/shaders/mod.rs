@@ -63,6 +63,7 @@ test_custom_shader!(text2d, "Text 2D Shader" => "text2d.wgsl");
 test_custom_shader!(text3d, "Text 3D Shader" => "text3d.wgsl");
 test_custom_shader!(text3d_shadow, "Text 3D Shadow Shader" => "text3d_shadow.wgsl");
 test_custom_shader!(debug_line2d, "Debug Line 2D" => "line.wgsl");
+test_custom_shader!(rect2d, "Rect 2D Shader" => "rect.wgsl");
 
 // Debug shaders
 test_custom_shader!(debug_edges, "Debug Edges Shader" => "debug/edges.wgsl");
@@ -110,3 +111,346 @@ fn shadergen_mesh3d() {
         .inspect_err(|e| e.emit_to_stderr_with_path(&shader, "shadergen/mesh3d"))
         .unwrap();
 }
+
+#[test]
+fn compute_mesh_skinning() {
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::generator::ShaderGenerator;
+
+    let shader =
+        ShaderGenerator::assemble_compute_shader(include_str!("compute/mesh_skinning.wgsl"));
+    validate_wgsl_source(&shader)
+        .inspect_err(|e| e.emit_to_stderr_with_path(&shader, "compute/mesh_skinning.wgsl"))
+        .unwrap();
+}
+
+#[test]
+fn compute_light_clusters() {
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::generator::ShaderGenerator;
+
+    let shader =
+        ShaderGenerator::assemble_compute_shader(include_str!("compute/light_clusters.wgsl"));
+    validate_wgsl_source(&shader)
+        .inspect_err(|e| e.emit_to_stderr_with_path(&shader, "compute/light_clusters.wgsl"))
+        .unwrap();
+}
+
+#[test]
+fn compute_ibl() {
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::generator::ShaderGenerator;
+
+    let shader = ShaderGenerator::assemble_compute_shader(include_str!("compute/ibl_compute.wgsl"));
+    validate_wgsl_source(&shader)
+        .inspect_err(|e| e.emit_to_stderr_with_path(&shader, "compute/ibl_compute.wgsl"))
+        .unwrap();
+}
+
+#[test]
+fn compute_reflection_probe_resolve() {
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::generator::ShaderGenerator;
+
+    let shader = ShaderGenerator::assemble_compute_shader(include_str!(
+        "compute/reflection_probe_resolve.wgsl"
+    ));
+    validate_wgsl_source(&shader)
+        .inspect_err(|e| {
+            e.emit_to_stderr_with_path(&shader, "compute/reflection_probe_resolve.wgsl")
+        })
+        .unwrap();
+}
+
+#[test]
+fn compute_hi_z_copy() {
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::generator::ShaderGenerator;
+
+    let shader = ShaderGenerator::assemble_compute_shader(include_str!("compute/hi_z_copy.wgsl"));
+    validate_wgsl_source(&shader)
+        .inspect_err(|e| e.emit_to_stderr_with_path(&shader, "compute/hi_z_copy.wgsl"))
+        .unwrap();
+}
+
+#[test]
+fn compute_hi_z_downsample() {
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::generator::ShaderGenerator;
+
+    let shader =
+        ShaderGenerator::assemble_compute_shader(include_str!("compute/hi_z_downsample.wgsl"));
+    validate_wgsl_source(&shader)
+        .inspect_err(|e| e.emit_to_stderr_with_path(&shader, "compute/hi_z_downsample.wgsl"))
+        .unwrap();
+}
+
+#[test]
+fn compute_occlusion_test() {
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::generator::ShaderGenerator;
+
+    let shader =
+        ShaderGenerator::assemble_compute_shader(include_str!("compute/occlusion_test.wgsl"));
+    validate_wgsl_source(&shader)
+        .inspect_err(|e| e.emit_to_stderr_with_path(&shader, "compute/occlusion_test.wgsl"))
+        .unwrap();
+}
+
+#[test]
+fn shadergen_mesh3d_vertex_offset() {
+    use crate::Shader;
+    use crate::shader::checks::validate_wgsl_source;
+    use crate::shader::{ShaderCode, ShaderType};
+    use syrillian_shadergen::MaterialCompiler;
+    use syrillian_shadergen::chunks::NodeId;
+    use syrillian_shadergen::compiler::MaterialVertexOutput;
+    use syrillian_shadergen::function::{MaterialExpression, MaterialExpressionValue, PbrShader};
+
+    struct WavyPbr(PbrShader);
+
+    impl MaterialExpression for WavyPbr {
+        fn bind_inputs(&mut self, compiler: &mut MaterialCompiler) {
+            self.0.bind_inputs(compiler);
+        }
+
+        fn outputs(&self) -> Vec<MaterialExpressionValue> {
+            self.0.outputs()
+        }
+
+        fn compile(&self, compiler: &mut MaterialCompiler, output_index: u32) -> NodeId {
+            self.0.compile(compiler, output_index)
+        }
+
+        fn compile_vertex(&self, compiler: &mut MaterialCompiler) -> MaterialVertexOutput {
+            let position = compiler.vertex_world_position();
+            let normal = compiler.vertex_world_normal();
+            let x = compiler.swizzle(position, "x");
+            let time = compiler.time();
+            let phase = compiler.add(x, time);
+            let wave = compiler.call("sin", vec![phase]);
+            let amplitude = compiler.constant_f32(0.1);
+            let height = compiler.mul(wave, amplitude);
+            MaterialVertexOutput {
+                world_position_offset: Some(compiler.mul(normal, height)),
+                normal: Some(normal),
+            }
+        }
+    }
+
+    let set = MaterialCompiler::compile_shader_set(&mut WavyPbr(PbrShader::default()));
+    assert!(set.custom_vertex);
+
+    for (code, shader_type, name) in [
+        (set.base, ShaderType::Custom, "base"),
+        (set.picking, ShaderType::Picking, "picking"),
+        (set.shadow, ShaderType::Shadow, "shadow"),
+    ] {
+        assert!(code.contains("world_position + ("), "{name}: {code}");
+
+        let shader = Shader::builder()
+            .shader_type(shader_type)
+            .name("Shadergen Mesh3D Vertex Offset")
+            .code(ShaderCode::Full(code))
+            .build()
+            .gen_code();
+
+        validate_wgsl_source(&shader)
+            .inspect_err(|e| e.emit_to_stderr_with_path(&shader, name))
+            .unwrap();
+    }
+}
+
+#[test]
+fn shadergen_material_graph() {
+    use crate::MaterialGraphAsset;
+    use crate::Shader;
+    use crate::shader::checks::validate_wgsl_source;
+    use crate::shader::{ShaderCode, ShaderType};
+    use crate::store::streaming::decode_helper::ParseDecode;
+    use syrillian_reflect::serializer::JsonSerializer;
+
+    const SOURCE: &str = r#"{
+        "name": "Wavy Tint",
+        "graph": {
+            "nodes": [
+                { "kind": "uv" },
+                { "kind": "texture", "texture": "diffuse", "uv": 0 },
+                { "kind": "parameter", "name": "diffuse", "type": "vec3" },
+                { "kind": "constant", "value": 1.0 },
+                { "kind": "construct", "type": "vec4", "parts": [2, 3] },
+                { "kind": "op", "op": "mul", "inputs": [1, 4] },
+                { "kind": "vertex_world_position" },
+                { "kind": "vertex_world_normal" },
+                { "kind": "split", "input": 6 },
+                { "kind": "time" },
+                { "kind": "op", "op": "add", "inputs": [[8, 0], 9] },
+                { "kind": "call", "function": "sin", "args": [10] },
+                { "kind": "op", "op": "mul", "inputs": [7, 11] }
+            ],
+            "surface": { "base_color": 5 },
+            "vertex": { "world_position_offset": 12 }
+        }
+    }"#;
+
+    let json: serde_json::Value = serde_json::from_str(SOURCE).unwrap();
+    let asset: MaterialGraphAsset = json.expect_parse("material graph").unwrap();
+    asset.validate().unwrap();
+
+    let encoded: serde_json::Value =
+        serde_json::from_str(&JsonSerializer::serialize_to_string(&asset)).unwrap();
+    assert!(MaterialGraphAsset::is_graph_payload(&encoded));
+    let decoded: MaterialGraphAsset = encoded.expect_parse("material graph").unwrap();
+    assert_eq!(decoded.graph, asset.graph);
+    assert_eq!(decoded.layout.layout_key(), asset.layout.layout_key());
+
+    let set = asset.compile().unwrap();
+    assert!(set.custom_vertex);
+
+    for (code, shader_type, name) in [
+        (set.base, ShaderType::Custom, "base"),
+        (set.picking, ShaderType::Picking, "picking"),
+        (set.shadow, ShaderType::Shadow, "shadow"),
+    ] {
+        let shader = Shader::builder()
+            .shader_type(shader_type)
+            .name("Shadergen Material Graph")
+            .code(ShaderCode::Full(code))
+            .build()
+            .gen_code();
+
+        validate_wgsl_source(&shader)
+            .inspect_err(|e| e.emit_to_stderr_with_path(&shader, name))
+            .unwrap();
+    }
+}
+
+#[test]
+fn hand_written_mesh_shaders_only_take_decals_from_their_layer() {
+    use crate::Shader;
+    use crate::shader::checks::validate_wgsl_source;
+
+    // Writing a fixed layer must not pull in decals of another layer
+    const SOURCE: &str = "\
+@fragment
+fn fs_main(in: FInput) -> FOutput {
+    var out: FOutput;
+    out.out_color = vec4(1.0);
+    out.out_normal = vec4(oct_encode(in.normal), 0.0, 1.0);
+    out.out_material = vec4(1.0, 0.0, 1.0, 1.0);
+    return out;
+}";
+
+    let code = Shader::new_default("Fixed Layer", SOURCE).gen_code();
+    let module = naga::front::wgsl::parse_str(&code).unwrap();
+    validate_wgsl_source(&code)
+        .inspect_err(|e| e.emit_to_stderr_with_path(&code, "fixed_layer.wgsl"))
+        .unwrap();
+
+    let entries: Vec<_> = module
+        .entry_points
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect();
+    assert!(entries.contains(&"fs_main"), "{entries:?}");
+    assert!(!entries.contains(&"fs_main_surface"), "{entries:?}");
+
+    let stored = code.rfind("surface.out_material.b = f32(model.decal_layer) / 255.0;");
+    let written = code.find("out.out_material = vec4(1.0, 0.0, 1.0, 1.0);");
+    assert!(stored > written, "{code}");
+}
+
+#[test]
+fn preprocessed_fragment_shader() {
+    use crate::Shader;
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::preprocess::register_module;
+
+    register_module(
+        "test::tint",
+        "fn tint(color: vec3f) -> vec3f {\n    return color * vec3f(1.0, 0.8, 0.8);\n}",
+    )
+    .unwrap();
+
+    const SOURCE: &str = "\
+#import syrillian::math
+#import syrillian::pbr
+#import test::tint
+
+@fragment
+fn fs_main(in: FInput) -> @location(0) vec4f {
+#ifdef TINTED
+    return vec4f(tint(safe_normalize(in.normal)), 1.0);
+#else
+    return vec4f(in.normal, 1.0);
+#endif
+}";
+
+    let shader = Shader::new_fragment("Preprocessed", SOURCE).with_define("TINTED");
+    let code = shader.gen_code();
+    assert!(code.contains("return vec4f(tint("), "{code}");
+    assert!(!code.contains("return vec4f(in.normal"), "{code}");
+
+    validate_wgsl_source(&code)
+        .inspect_err(|e| e.emit_to_stderr_with_path(&code, "preprocessed.wgsl"))
+        .unwrap();
+}
+
+#[test]
+fn preprocessed_errors_map_to_modules() {
+    use crate::Shader;
+    use crate::shader::checks::validate_wgsl_source;
+    use syrillian_shadergen::preprocess::register_module;
+
+    register_module(
+        "test::broken",
+        "fn fine() -> f32 {\n    return 1.0;\n}\n\nfn broken() -> f32 {\n    return missing;\n}",
+    )
+    .unwrap();
+
+    let shader = Shader::new_fragment(
+        "Broken Import",
+        "#import test::broken\n\n@fragment\nfn fs_main(in: FInput) -> @location(0) vec4f {\n    return vec4f(broken());\n}",
+    );
+    let code = shader.gen_code();
+
+    let error = validate_wgsl_source(&code).unwrap_err();
+    assert_eq!(error.origin(&code), Some(("test::broken".to_string(), 6)));
+}
+
+#[test]
+fn default_material_permutations() {
+    use crate::Shader;
+    use crate::assets::{DefaultMaterial, Material};
+    use crate::shader::checks::validate_wgsl_source;
+    use std::collections::HashMap;
+    use syrillian_shadergen::value::MaterialValue;
+
+    let layout = Material::default_layout();
+    let material = Material::Default(DefaultMaterial::new("Permuted", layout.clone()));
+    let permutations = material.permutations().unwrap();
+    let keys = &layout.permutation_keys;
+
+    for bits in 0..1u32 << keys.len() {
+        let values: HashMap<String, MaterialValue> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (key.clone(), MaterialValue::Bool(bits & (1 << i) != 0)))
+            .collect();
+        let permutation = layout.permutation(&values);
+        assert_eq!(permutation.len(), keys.len());
+
+        let code = permutations.compile(&permutation).unwrap();
+        for key in keys {
+            assert!(
+                !code.contains(&format!("material.{key}")),
+                "{key} wasn't baked"
+            );
+        }
+
+        let shader = Shader::new_material("Permuted", code, &layout).gen_code();
+        validate_wgsl_source(&shader)
+            .inspect_err(|e| e.emit_to_stderr_with_path(&shader, "shadergen/permutation"))
+            .unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+struct VOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) p_px: vec2<f32>,   // interpolated pixel position for this fragment
+};
+
+struct PushConstants {
+    position: vec2<f32>,
+    size: vec2<f32>,
+    color: vec4<f32>,
+    border_color: vec4<f32>,
+    corner_radius: f32,
+    border_thickness: f32,
+};
+
+var<immediate> pc: PushConstants;
+
+fn to_ndc(px: vec2<f32>) -> vec4<f32> {
+    let screen = vec2<f32>(system.screen);
+    let ndc = vec2<f32>(
+        (px.x / screen.x) * 2.0 - 1.0,
+        1.0 - (px.y / screen.y) * 2.0
+    );
+    return vec4<f32>(ndc, 0.0, 1.0);
+}
+
+fn corner_from_vid(vid: u32, c0: vec2<f32>, c1: vec2<f32>, c2: vec2<f32>, c3: vec2<f32>) -> vec2<f32> {
+    switch(vid) {
+        case 0u: { return c0; }
+        case 1u: { return c1; }
+        case 2u: { return c2; }
+        case 3u: { return c0; }
+        case 4u: { return c2; }
+        default: { return c3; }
+    }
+}
+
+@vertex
+fn ui_rect_vs(@builtin(vertex_index) vid: u32) -> VOut {
+    var out: VOut;
+
+    let aa_pad = 1.5;
+    let min_px = pc.position - vec2<f32>(aa_pad);
+    let max_px = pc.position + pc.size + vec2<f32>(aa_pad);
+
+    let c0 = min_px;
+    let c1 = vec2<f32>(min_px.x, max_px.y);
+    let c2 = max_px;
+    let c3 = vec2<f32>(max_px.x, min_px.y);
+
+    let p = corner_from_vid(vid, c0, c1, c2, c3);
+
+    out.position = to_ndc(p);
+    out.p_px = p;
+
+    return out;
+}
+
+// signed distance to a rounded box centered at the origin
+fn rounded_box_sdf(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + vec2<f32>(radius);
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+@fragment
+fn ui_rect_fs(in: VOut) -> @location(0) vec4<f32> {
+    let half_size = max(pc.size * 0.5, vec2<f32>(0.0));
+    let center = pc.position + half_size;
+    let radius = clamp(pc.corner_radius, 0.0, min(half_size.x, half_size.y));
+
+    let d = rounded_box_sdf(in.p_px - center, half_size, radius);
+    let w = max(fwidth(d), 1e-3);
+    let coverage = smoothstep(w, -w, d);
+
+    let border = max(pc.border_thickness, 0.0);
+    let fill_amount = select(1.0, smoothstep(w, -w, d + border), border > 0.0);
+    let color = mix(pc.border_color, pc.color, fill_amount);
+
+    let alpha = coverage * color.a;
+    if (alpha <= 1e-4) { discard; }
+
+    return vec4<f32>(color.rgb, alpha);
+}
@@ -8,6 +8,7 @@ use crate::store::{
     AssetKey, AssetRefreshMessage, H, HandleName, Store, StoreDefaults, StoreType,
     StoreTypeFallback, UpdateAssetMessage, streaming,
 };
+use crate::texture_compression::{self, TextureCookSettings, TextureUsage, mips};
 use crate::{HTexture2D, store_add_checked};
 use crossbeam_channel::Sender;
 use std::error::Error;
@@ -20,11 +21,16 @@ pub struct Texture2D {
     pub width: u32,
     pub height: u32,
     pub format: TextureFormat,
+    /// Amount of mip levels stored in `data`
+    pub mip_level_count: u32,
+    /// Pixels of every mip level, largest level first
     pub data: Option<Vec<u8>>,
     pub repeat_mode: AddressMode,
     pub filter_mode: FilterMode,
     pub mip_filter_mode: MipmapFilterMode,
     pub has_transparency: bool,
+    /// Fills the remaining mip chain on the GPU when the texture is uploaded with a single level
+    pub generate_mips: bool,
 }
 
 impl H<Texture2D> {
@@ -89,11 +95,13 @@ impl Texture2D {
             width,
             height,
             format,
+            mip_level_count: 1,
             data: Some(pixels),
             repeat_mode: AddressMode::Repeat,
             filter_mode: FilterMode::Linear,
             mip_filter_mode: MipmapFilterMode::Linear,
             has_transparency,
+            generate_mips: false,
         }
     }
 
@@ -108,11 +116,13 @@ impl Texture2D {
             width,
             height,
             format,
+            mip_level_count: 1,
             data: Some(pixels),
             repeat_mode: AddressMode::Repeat,
             filter_mode: FilterMode::Linear,
             mip_filter_mode: MipmapFilterMode::Linear,
             has_transparency,
+            generate_mips: false,
         }
     }
 
@@ -140,7 +150,159 @@ impl Texture2D {
 
     pub fn refresh_transparency(&mut self) {
         if let Some(data) = &self.data {
-            self.has_transparency = Self::calculate_transparency(self.format, data);
+            let base = &data[..self.mip_level_size(0).min(data.len())];
+            self.has_transparency = Self::calculate_transparency(self.format, base);
+        }
+    }
+
+    /// Size of mip `level` in pixels
+    pub fn mip_extent(&self, level: u32) -> (u32, u32) {
+        mips::mip_extent(self.width, self.height, level)
+    }
+
+    /// Size of mip `level` in bytes, in the format of this texture
+    pub fn mip_level_size(&self, level: u32) -> usize {
+        let (width, height) = self.mip_extent(level);
+        let (block_width, block_height) = self.format.block_dimensions();
+        let block_size = self.format.block_copy_size(None).unwrap_or(4) as usize;
+        width.div_ceil(block_width) as usize * height.div_ceil(block_height) as usize * block_size
+    }
+
+    /// The data of every mip level, largest level first. Levels missing from `data` are left out.
+    pub fn mip_levels(&self) -> Vec<&[u8]> {
+        let Some(mut data) = self.data.as_deref() else {
+            return Vec::new();
+        };
+
+        let mut levels = Vec::with_capacity(self.mip_level_count as usize);
+        for level in 0..self.mip_level_count.max(1) {
+            let Some((pixels, rest)) = data.split_at_checked(self.mip_level_size(level)) else {
+                break;
+            };
+            levels.push(pixels);
+            data = rest;
+        }
+        levels
+    }
+
+    /// Expands the largest mip level to tightly packed RGBA8, if the format is supported.
+    pub fn to_rgba8(&self) -> Option<Vec<u8>> {
+        let data = self.data.as_deref()?;
+        let pixels = self.width as usize * self.height as usize;
+
+        match self.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+                data.get(..pixels * 4).map(<[u8]>::to_vec)
+            }
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                let bgra = data.get(..pixels * 4)?;
+                Some(
+                    bgra.chunks_exact(4)
+                        .flat_map(|p| [p[2], p[1], p[0], p[3]])
+                        .collect(),
+                )
+            }
+            TextureFormat::R8Unorm => {
+                let r = data.get(..pixels)?;
+                Some(r.iter().flat_map(|&r| [r, r, r, 255]).collect())
+            }
+            // Two channel textures hold luminance and alpha, like grayscale diffuse maps
+            TextureFormat::Rg8Unorm => {
+                let la = data.get(..pixels * 2)?;
+                Some(
+                    la.chunks_exact(2)
+                        .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                        .collect(),
+                )
+            }
+            format => texture_compression::decompress(format, self.width, self.height, data),
+        }
+    }
+
+    /// Decompresses every mip level of a block compressed texture to RGBA8, keeping its sRGB
+    /// encoding. Used where the GPU can't sample the compressed format.
+    ///
+    /// Returns `None` if the texture isn't compressed or its format isn't supported.
+    pub fn decompressed(&self) -> Option<Texture2D> {
+        if !texture_compression::is_supported_compressed_format(self.format) {
+            return None;
+        }
+
+        let mut data = Vec::new();
+        for (level, blocks) in self.mip_levels().into_iter().enumerate() {
+            let (width, height) = self.mip_extent(level as u32);
+            data.extend(texture_compression::decompress(
+                self.format,
+                width,
+                height,
+                blocks,
+            )?);
+        }
+
+        let format = if self.format.is_srgb() {
+            TextureFormat::Rgba8UnormSrgb
+        } else {
+            TextureFormat::Rgba8Unorm
+        };
+
+        Some(Texture2D {
+            format,
+            data: Some(data),
+            ..self.clone()
+        })
+    }
+
+    /// Prepares the texture for packaging: generates its mip chain and compresses every level
+    /// to the format `settings` picks for `usage`.
+    ///
+    /// Textures that aren't a multiple of 4 pixels wide and high are kept uncompressed. Returns
+    /// an unchanged copy if the pixel format can't be read.
+    pub fn cook(&self, usage: TextureUsage, settings: &TextureCookSettings) -> Texture2D {
+        let Some(rgba) = self.to_rgba8() else {
+            return self.clone();
+        };
+
+        let srgb = self.format.is_srgb() && usage == TextureUsage::Color;
+        let levels = if settings.generate_mips {
+            mips::generate_mips(self.width, self.height, &rgba, usage, srgb)
+        } else {
+            vec![rgba]
+        };
+
+        let block_aligned = self.width.is_multiple_of(4) && self.height.is_multiple_of(4);
+        let compressed_format = settings
+            .compression
+            .format(usage, srgb, self.has_transparency)
+            .filter(|_| block_aligned);
+
+        let compressed = compressed_format.and_then(|format| {
+            let mut data = Vec::new();
+            for (level, pixels) in levels.iter().enumerate() {
+                let (width, height) = self.mip_extent(level as u32);
+                data.extend(texture_compression::compress(
+                    format, width, height, pixels,
+                )?);
+            }
+            Some((format, data))
+        });
+
+        let (format, data) = match compressed {
+            Some(compressed) => compressed,
+            None if srgb => (TextureFormat::Rgba8UnormSrgb, levels.concat()),
+            None => (TextureFormat::Rgba8Unorm, levels.concat()),
+        };
+
+        Texture2D {
+            width: self.width,
+            height: self.height,
+            format,
+            mip_level_count: levels.len() as u32,
+            data: Some(data),
+            repeat_mode: self.repeat_mode,
+            filter_mode: self.filter_mode,
+            mip_filter_mode: self.mip_filter_mode,
+            has_transparency: self.has_transparency,
+            generate_mips: self.generate_mips,
         }
     }
 }
@@ -224,8 +386,12 @@ impl StreamableAsset for Texture2D {
     fn encode(&self) -> BuiltPayload {
         let mut blobs = Vec::new();
 
-        if let Some(data) = self.data.as_deref() {
-            PackedBlob::maybe_pack_data_into(StreamingAssetBlobKind::TextureData, data, &mut blobs);
+        for level in self.mip_levels() {
+            PackedBlob::maybe_pack_data_into(
+                StreamingAssetBlobKind::TextureMipLevel,
+                level,
+                &mut blobs,
+            );
         }
 
         BuiltPayload {
@@ -249,6 +415,9 @@ impl StreamableAsset for Texture2D {
         let format = root
             .required_field("format")?
             .expect_parse("texture format")?;
+        let mip_level_count: Option<u32> = root
+            .optional_field("mip_level_count")
+            .expect_parse("texture mip level count")?;
         let repeat_mode = root
             .required_field("repeat_mode")?
             .expect_parse("texture repeat mode")?;
@@ -261,25 +430,123 @@ impl StreamableAsset for Texture2D {
         let has_transparency = root
             .required_field("has_transparency")?
             .expect_parse("texture has_transparency")?;
+        let generate_mips: Option<bool> = root
+            .optional_field("generate_mips")
+            .expect_parse("texture generate_mips")?;
 
-        let data = payload
+        let mut levels = payload
             .blob_infos
-            .find(StreamingAssetBlobKind::TextureData)
-            .ok()
-            .map(|b| b.decode_all_from_io(package))
-            .transpose()?;
+            .infos
+            .iter()
+            .filter(|b| b.kind == StreamingAssetBlobKind::TextureMipLevel)
+            .peekable();
+
+        let data = if levels.peek().is_some() {
+            let mut data = Vec::new();
+            for level in levels {
+                data.extend(level.decode_all_from_io::<u8>(package)?);
+            }
+            Some(data)
+        } else {
+            payload
+                .blob_infos
+                .find(StreamingAssetBlobKind::TextureData)
+                .ok()
+                .map(|b| b.decode_all_from_io(package))
+                .transpose()?
+        };
 
         Ok(Texture2D {
             width,
             height,
             format,
+            mip_level_count: mip_level_count.unwrap_or(1),
             data,
             repeat_mode,
             filter_mode,
             mip_filter_mode,
             has_transparency,
+            generate_mips: generate_mips.unwrap_or(false),
         })
     }
 }
 
 impl Store<Texture2D> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextureCompression;
+
+    fn checker(size: u32) -> Texture2D {
+        let pixels = (0..size * size)
+            .flat_map(|i| {
+                let value = if (i % size + i / size).is_multiple_of(2) {
+                    30
+                } else {
+                    220
+                };
+                [value, value, value, 255]
+            })
+            .collect();
+        Texture2D::load_pixels(pixels, size, size, TextureFormat::Rgba8UnormSrgb)
+    }
+
+    #[test]
+    fn cooking_builds_compressed_mip_chains() {
+        let texture = checker(16).cook(TextureUsage::Color, &TextureCookSettings::default());
+
+        assert_eq!(texture.format, TextureFormat::Bc1RgbaUnormSrgb);
+        assert_eq!(texture.mip_level_count, 5);
+
+        let levels = texture.mip_levels();
+        assert_eq!(levels.len(), 5);
+        assert_eq!(levels[0].len(), 16 * 8);
+        assert!(levels[2..].iter().all(|level| level.len() == 8));
+
+        let decompressed = texture.decompressed().unwrap();
+        assert_eq!(decompressed.format, TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(
+            decompressed.data.unwrap().len(),
+            (16 * 16 + 8 * 8 + 4 * 4 + 2 * 2 + 1) * 4
+        );
+    }
+
+    #[test]
+    fn usages_keep_their_encoding() {
+        let settings = TextureCookSettings {
+            compression: TextureCompression::Etc2,
+            generate_mips: false,
+        };
+
+        let normal = checker(8).cook(TextureUsage::Normal, &settings);
+        assert_eq!(normal.format, TextureFormat::EacRg11Unorm);
+        assert_eq!(normal.mip_level_count, 1);
+
+        let mask = checker(8).cook(TextureUsage::Mask, &settings);
+        assert_eq!(mask.format, TextureFormat::Etc2Rgb8Unorm);
+    }
+
+    #[test]
+    fn luminance_alpha_cooks_to_gray() {
+        let settings = TextureCookSettings {
+            compression: TextureCompression::None,
+            generate_mips: false,
+        };
+        let la = Texture2D::load_pixels(vec![40, 255, 200, 0], 2, 1, TextureFormat::Rg8Unorm);
+
+        let texture = la.cook(TextureUsage::Color, &settings);
+        assert_eq!(texture.format, TextureFormat::Rgba8Unorm);
+        assert_eq!(texture.data.unwrap(), [40, 40, 40, 255, 200, 200, 200, 0]);
+    }
+
+    #[test]
+    fn unaligned_textures_stay_uncompressed() {
+        let texture = checker(6).cook(TextureUsage::Color, &TextureCookSettings::default());
+
+        assert_eq!(texture.format, TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(texture.mip_level_count, 3);
+        assert_eq!(texture.data.unwrap().len(), (36 + 9 + 1) * 4);
+        assert!(checker(6).decompressed().is_none());
+    }
+}
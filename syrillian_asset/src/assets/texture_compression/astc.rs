@@ -0,0 +1,192 @@
+//! ASTC 4x4 blocks with a single partition and direct LDR endpoints.
+//!
+//! Opaque blocks use RGB endpoints with 3 bit weights, blocks with alpha RGBA endpoints with
+//! 2 bit weights. Both fit their endpoints into the block unquantized, so no integer sequence
+//! encoding is needed.
+
+use super::Block;
+
+/// 4x4 weight grid with 2 bit weights
+const BLOCK_MODE_RGBA: u128 = 0x42;
+/// 4x4 weight grid with 3 bit weights
+const BLOCK_MODE_RGB: u128 = 0x53;
+
+const CEM_RGB_DIRECT: u128 = 8;
+const CEM_RGBA_DIRECT: u128 = 12;
+
+const WEIGHTS_2_BIT: [i32; 4] = [0, 21, 43, 64];
+const WEIGHTS_3_BIT: [i32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+
+/// Encodes a 4x4 block, alpha is only stored if `alpha` is set
+pub fn encode_block(block: &Block, alpha: bool) -> [u8; 16] {
+    let channels = if alpha { 4 } else { 3 };
+    let weight_table: &[i32] = if alpha {
+        &WEIGHTS_2_BIT
+    } else {
+        &WEIGHTS_3_BIT
+    };
+    let max_weight = weight_table.len() - 1;
+
+    let pixels = block.map(|p| p.map(|c| c as f32));
+    let (mut e0, mut e1) = principal_endpoints(&pixels, channels);
+
+    let rgb_sum = |e: &[u8; 4]| e[..3].iter().map(|&c| c as u32).sum::<u32>();
+    if rgb_sum(&e1) < rgb_sum(&e0) {
+        std::mem::swap(&mut e0, &mut e1);
+    }
+
+    let weights = pixels.map(|pixel| {
+        (0..=max_weight)
+            .min_by_key(|&w| {
+                let w = weight_table[w];
+                (0..channels)
+                    .map(|c| {
+                        let decoded = (e0[c] as i32 * (64 - w) + e1[c] as i32 * w + 32) >> 6;
+                        (decoded - pixel[c] as i32).pow(2)
+                    })
+                    .sum::<i32>()
+            })
+            .unwrap_or_default()
+    });
+
+    let mut bits = if alpha {
+        BLOCK_MODE_RGBA | CEM_RGBA_DIRECT << 13
+    } else {
+        BLOCK_MODE_RGB | CEM_RGB_DIRECT << 13
+    };
+
+    for c in 0..channels {
+        bits |= (e0[c] as u128) << (17 + c * 16);
+        bits |= (e1[c] as u128) << (25 + c * 16);
+    }
+
+    let weight_bits = max_weight.count_ones() as usize;
+    for (i, &weight) in weights.iter().enumerate() {
+        for k in 0..weight_bits {
+            let bit = (weight >> k) as u128 & 1;
+            bits |= bit << (127 - (i * weight_bits + k));
+        }
+    }
+
+    bits.to_le_bytes()
+}
+
+/// Decodes a block written by [`encode_block`]. Other ASTC configurations aren't supported
+/// and decode to magenta.
+pub fn decode_block(data: &[u8], srgb: bool) -> Block {
+    let bits = u128::from_le_bytes(data[..16].try_into().unwrap_or_default());
+
+    let (channels, weight_table): (usize, &[i32]) = match (bits & 0x7FF, bits >> 13 & 0xF) {
+        (BLOCK_MODE_RGBA, CEM_RGBA_DIRECT) => (4, &WEIGHTS_2_BIT),
+        (BLOCK_MODE_RGB, CEM_RGB_DIRECT) => (3, &WEIGHTS_3_BIT),
+        _ => return [[255, 0, 255, 255]; 16],
+    };
+    let weight_bits = (weight_table.len() - 1).count_ones() as usize;
+
+    let mut e0 = [255; 4];
+    let mut e1 = [255; 4];
+    for c in 0..channels {
+        e0[c] = (bits >> (17 + c * 16)) as u8 as i32;
+        e1[c] = (bits >> (25 + c * 16)) as u8 as i32;
+    }
+
+    std::array::from_fn(|i| {
+        let index = (0..weight_bits)
+            .map(|k| ((bits >> (127 - (i * weight_bits + k))) as usize & 1) << k)
+            .sum::<usize>();
+        let w = weight_table[index];
+
+        std::array::from_fn(|c| {
+            let expand = |e: i32| if srgb { e << 8 | 0x80 } else { e << 8 | e };
+            let color = (expand(e0[c]) * (64 - w) + expand(e1[c]) * w + 32) >> 6;
+            (color >> 8) as u8
+        })
+    })
+}
+
+/// Fits a line through the block colors and returns its ends clamped to the pixels
+fn principal_endpoints(pixels: &[[f32; 4]; 16], channels: usize) -> ([u8; 4], [u8; 4]) {
+    let mut mean = [0.0f32; 4];
+    for pixel in pixels {
+        for c in 0..channels {
+            mean[c] += pixel[c] / 16.0;
+        }
+    }
+
+    let mut covariance = [[0.0f32; 4]; 4];
+    for pixel in pixels {
+        for a in 0..channels {
+            for b in 0..channels {
+                covariance[a][b] += (pixel[a] - mean[a]) * (pixel[b] - mean[b]);
+            }
+        }
+    }
+
+    let mut axis = [1.0f32; 4];
+    for _ in 0..8 {
+        let mut next = [0.0; 4];
+        for a in 0..channels {
+            for b in 0..channels {
+                next[a] += covariance[a][b] * axis[b];
+            }
+        }
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length < f32::EPSILON {
+            break;
+        }
+        axis = next.map(|v| v / length);
+    }
+
+    let project = |pixel: &[f32; 4]| (0..channels).map(|c| (pixel[c] - mean[c]) * axis[c]).sum();
+    let (min, max) = pixels
+        .iter()
+        .map(project)
+        .fold((f32::MAX, f32::MIN), |(min, max), t: f32| {
+            (min.min(t), max.max(t))
+        });
+
+    let endpoint = |t: f32| {
+        let mut color = [255; 4];
+        for c in 0..channels {
+            color[c] = (mean[c] + axis[c] * t).round().clamp(0.0, 255.0) as u8;
+        }
+        color
+    };
+
+    (endpoint(min), endpoint(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_round_trip() {
+        let block: Block = std::array::from_fn(|i| {
+            let t = i as u8 * 4;
+            [20 + t, 200 - t, 90 + t / 2, 255 - t]
+        });
+
+        for alpha in [false, true] {
+            let decoded = decode_block(&encode_block(&block, alpha), false);
+            let channels = if alpha { 4 } else { 3 };
+
+            for (pixel, decoded) in block.iter().zip(decoded) {
+                for c in 0..channels {
+                    let error = (pixel[c] as i32 - decoded[c] as i32).abs();
+                    assert!(error <= 12, "{pixel:?} decoded to {decoded:?}");
+                }
+                if !alpha {
+                    assert_eq!(decoded[3], 255);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flat_blocks_are_exact() {
+        let block = [[37, 140, 222, 99]; 16];
+        let decoded = decode_block(&encode_block(&block, true), false);
+        assert!(decoded.iter().all(|pixel| *pixel == block[0]));
+    }
+}
@@ -0,0 +1,337 @@
+//! ETC2 color and EAC channel blocks.
+//!
+//! Colors are only encoded in the individual and differential modes ETC2 inherited from ETC1,
+//! which keeps the encoder simple and is readable by every ETC2 decoder. The T, H and planar
+//! modes are neither written nor read.
+
+use super::Block;
+
+const ETC1_MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+/// Pixel index values map to `+small`, `+large`, `-small` and `-large`
+fn etc1_modifier(table: usize, index: usize) -> i32 {
+    let [small, large] = ETC1_MODIFIERS[table];
+    [small, large, -small, -large][index]
+}
+
+/// Bit of a pixel in the index planes, pixels are stored column by column
+fn pixel_bit(x: usize, y: usize) -> usize {
+    x * 4 + y
+}
+
+fn in_first_subblock(flip: bool, x: usize, y: usize) -> bool {
+    if flip { y < 2 } else { x < 2 }
+}
+
+fn expand4(value: i32) -> i32 {
+    (value << 4) | value
+}
+
+fn expand5(value: i32) -> i32 {
+    (value << 3) | (value >> 2)
+}
+
+struct Subblock {
+    error: u32,
+    table: usize,
+    indices: [usize; 16],
+}
+
+/// Picks the modifier table and pixel indices that fit the subblock pixels best around `base`
+fn fit_subblock(block: &Block, flip: bool, first: bool, base: [i32; 3]) -> Subblock {
+    let mut best = Subblock {
+        error: u32::MAX,
+        table: 0,
+        indices: [0; 16],
+    };
+
+    for table in 0..ETC1_MODIFIERS.len() {
+        let mut error = 0;
+        let mut indices = [0; 16];
+
+        for (i, pixel) in block.iter().enumerate() {
+            let (x, y) = (i % 4, i / 4);
+            if in_first_subblock(flip, x, y) != first {
+                continue;
+            }
+
+            let (index, pixel_error) = (0..4)
+                .map(|index| {
+                    let modifier = etc1_modifier(table, index);
+                    let pixel_error: u32 = (0..3)
+                        .map(|c| {
+                            let decoded = (base[c] + modifier).clamp(0, 255);
+                            (decoded - pixel[c] as i32).pow(2) as u32
+                        })
+                        .sum();
+                    (index, pixel_error)
+                })
+                .min_by_key(|&(_, pixel_error)| pixel_error)
+                .unwrap_or_default();
+
+            indices[i] = index;
+            error += pixel_error;
+        }
+
+        if error < best.error {
+            best = Subblock {
+                error,
+                table,
+                indices,
+            };
+        }
+    }
+
+    best
+}
+
+fn subblock_average(block: &Block, flip: bool, first: bool) -> [f32; 3] {
+    let mut sum = [0.0; 3];
+    for (i, pixel) in block.iter().enumerate() {
+        if in_first_subblock(flip, i % 4, i / 4) == first {
+            for c in 0..3 {
+                sum[c] += pixel[c] as f32;
+            }
+        }
+    }
+    sum.map(|s| s / 8.0)
+}
+
+/// Encodes the color channels of a 4x4 block into an ETC2 RGB block
+pub fn encode_rgb_block(block: &Block) -> [u8; 8] {
+    let mut best: Option<(u32, u64)> = None;
+
+    for flip in [false, true] {
+        let averages = [true, false].map(|first| subblock_average(block, flip, first));
+
+        let individual = averages.map(|avg| avg.map(|c| (c * 15.0 / 255.0).round() as i32));
+        let mut candidates = vec![(false, individual.map(|q| q.map(expand4)), individual)];
+
+        let differential = averages.map(|avg| avg.map(|c| (c * 31.0 / 255.0).round() as i32));
+        if (0..3).all(|c| (-4..=3).contains(&(differential[1][c] - differential[0][c]))) {
+            candidates.push((true, differential.map(|q| q.map(expand5)), differential));
+        }
+
+        for (diff, bases, quantized) in candidates {
+            let [first, second] = [(true, bases[0]), (false, bases[1])]
+                .map(|(first, base)| fit_subblock(block, flip, first, base));
+            let error = first.error + second.error;
+            if best.is_some_and(|(best_error, _)| best_error <= error) {
+                continue;
+            }
+
+            let mut bits = 0u64;
+            if diff {
+                for (c, (first, second)) in quantized[0].into_iter().zip(quantized[1]).enumerate() {
+                    let delta = (second - first) & 0b111;
+                    bits |= (first as u64) << (59 - c * 8);
+                    bits |= (delta as u64) << (56 - c * 8);
+                }
+                bits |= 1 << 33;
+            } else {
+                for (c, (first, second)) in quantized[0].into_iter().zip(quantized[1]).enumerate() {
+                    bits |= (first as u64) << (60 - c * 8);
+                    bits |= (second as u64) << (56 - c * 8);
+                }
+            }
+
+            bits |= (first.table as u64) << 37;
+            bits |= (second.table as u64) << 34;
+            bits |= (flip as u64) << 32;
+
+            for i in 0..16 {
+                let (x, y) = (i % 4, i / 4);
+                let index = if in_first_subblock(flip, x, y) {
+                    first.indices[i]
+                } else {
+                    second.indices[i]
+                };
+                let bit = pixel_bit(x, y);
+                bits |= ((index >> 1) as u64) << (16 + bit);
+                bits |= ((index & 1) as u64) << bit;
+            }
+
+            best = Some((error, bits));
+        }
+    }
+
+    best.map_or(0, |(_, bits)| bits).to_be_bytes()
+}
+
+/// Decodes an ETC2 RGB block in individual or differential mode, alpha is set to opaque
+pub fn decode_rgb_block(data: &[u8]) -> Block {
+    let bits = u64::from_be_bytes(data[..8].try_into().unwrap_or_default());
+    let diff = bits >> 33 & 1 == 1;
+    let flip = bits >> 32 & 1 == 1;
+    let tables = [(bits >> 37 & 0b111) as usize, (bits >> 34 & 0b111) as usize];
+
+    let channels: [[i32; 2]; 3] = std::array::from_fn(|c| {
+        if diff {
+            let base = (bits >> (59 - c * 8) & 0b11111) as i32;
+            let delta = ((bits >> (56 - c * 8) & 0b111) as i32) << 29 >> 29;
+            [expand5(base), expand5((base + delta).clamp(0, 31))]
+        } else {
+            [
+                expand4((bits >> (60 - c * 8) & 0b1111) as i32),
+                expand4((bits >> (56 - c * 8) & 0b1111) as i32),
+            ]
+        }
+    });
+    let bases = [channels.map(|c| c[0]), channels.map(|c| c[1])];
+
+    let mut block = [[0, 0, 0, 255]; 16];
+    for (i, pixel) in block.iter_mut().enumerate() {
+        let (x, y) = (i % 4, i / 4);
+        let subblock = usize::from(!in_first_subblock(flip, x, y));
+        let bit = pixel_bit(x, y);
+        let index = ((bits >> (16 + bit) & 1) << 1 | (bits >> bit & 1)) as usize;
+        let modifier = etc1_modifier(tables[subblock], index);
+        for c in 0..3 {
+            pixel[c] = (bases[subblock][c] + modifier).clamp(0, 255) as u8;
+        }
+    }
+    block
+}
+
+/// Value of an EAC pixel, either as 8 bit ETC2 alpha or as 11 bit R11/RG11 channel scaled to 8 bits
+fn eac_value(base: i32, modifier: i32, multiplier: i32, eleven_bit: bool) -> i32 {
+    if eleven_bit {
+        let value = (base * 8 + 4 + modifier * multiplier * 8).clamp(0, 2047);
+        (value * 255 + 1023) / 2047
+    } else {
+        (base + modifier * multiplier).clamp(0, 255)
+    }
+}
+
+/// Encodes one channel of a 4x4 block into an EAC block. ETC2 alpha uses the 8 bit variant,
+/// the channels of EAC R11 and RG11 textures the 11 bit one.
+pub fn encode_eac_block(block: &Block, channel: usize, eleven_bit: bool) -> [u8; 8] {
+    let values = block.map(|pixel| pixel[channel] as i32);
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    let base = (min + max + 1) / 2;
+
+    let mut best = (u32::MAX, 0u64);
+    for (table, modifiers) in EAC_MODIFIERS.iter().enumerate() {
+        let span = modifiers[7] - modifiers[3];
+        let estimate = ((max - min) as f32 / span as f32).round() as i32;
+
+        for multiplier in (estimate - 1).max(1)..=(estimate + 1).min(15) {
+            let mut error = 0;
+            let mut bits = (base as u64) << 56 | (multiplier as u64) << 52 | (table as u64) << 48;
+
+            for (i, &value) in values.iter().enumerate() {
+                let (index, value_error) = modifiers
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &modifier)| {
+                        let decoded = eac_value(base, modifier, multiplier, eleven_bit);
+                        (index, (decoded - value).pow(2) as u32)
+                    })
+                    .min_by_key(|&(_, value_error)| value_error)
+                    .unwrap_or_default();
+
+                error += value_error;
+                bits |= (index as u64) << (45 - 3 * pixel_bit(i % 4, i / 4));
+            }
+
+            if error < best.0 {
+                best = (error, bits);
+            }
+        }
+    }
+
+    best.1.to_be_bytes()
+}
+
+/// Decodes an EAC block into one channel of `block`
+pub fn decode_eac_block(data: &[u8], block: &mut Block, channel: usize, eleven_bit: bool) {
+    let bits = u64::from_be_bytes(data[..8].try_into().unwrap_or_default());
+    let base = (bits >> 56) as i32;
+    let multiplier = (bits >> 52 & 0b1111) as i32;
+    let modifiers = EAC_MODIFIERS[(bits >> 48 & 0b1111) as usize];
+
+    for (i, pixel) in block.iter_mut().enumerate() {
+        let index = (bits >> (45 - 3 * pixel_bit(i % 4, i / 4)) & 0b111) as usize;
+        pixel[channel] = eac_value(base, modifiers[index], multiplier, eleven_bit) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> Block {
+        std::array::from_fn(|i| {
+            let t = (i % 4 + i / 4) as u8 * 10;
+            [40 + t, 90 + t, 150 + t / 2, 255 - t]
+        })
+    }
+
+    fn max_error(a: &Block, b: &Block, channels: std::ops::Range<usize>) -> i32 {
+        a.iter()
+            .zip(b)
+            .flat_map(|(a, b)| channels.clone().map(|c| (a[c] as i32 - b[c] as i32).abs()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn flat_blocks_round_trip_closely() {
+        let block = [[120, 64, 200, 255]; 16];
+        let decoded = decode_rgb_block(&encode_rgb_block(&block));
+        assert!(max_error(&block, &decoded, 0..3) <= 4);
+    }
+
+    #[test]
+    fn gradients_stay_close() {
+        let block = gradient();
+        let decoded = decode_rgb_block(&encode_rgb_block(&block));
+        assert!(max_error(&block, &decoded, 0..3) <= 12);
+    }
+
+    #[test]
+    fn eac_channels_round_trip() {
+        let block = gradient();
+
+        for eleven_bit in [false, true] {
+            let mut decoded = [[0; 4]; 16];
+            decode_eac_block(
+                &encode_eac_block(&block, 3, eleven_bit),
+                &mut decoded,
+                3,
+                eleven_bit,
+            );
+            let error = max_error(&block, &decoded, 3..4);
+            assert!(error <= 4, "off by {error}");
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Box filtered mip chains for RGBA8 images.
+
+use super::TextureUsage;
+use once_cell::sync::Lazy;
+
+static SRGB_TO_LINEAR: Lazy<[f32; 256]> = Lazy::new(|| {
+    std::array::from_fn(|i| {
+        let c = i as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    })
+});
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let srgb = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round() as u8
+}
+
+/// Amount of levels in a full mip chain of a `width` x `height` image
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Size of mip `level` of a `width` x `height` image
+pub fn mip_extent(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
+
+/// Builds the full mip chain of an RGBA8 image, starting with the image itself.
+///
+/// Color channels of sRGB images are averaged in linear space and normal maps are
+/// renormalized after every step.
+pub fn generate_mips(
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+    usage: TextureUsage,
+    srgb: bool,
+) -> Vec<Vec<u8>> {
+    let mut levels = vec![rgba.to_vec()];
+
+    for level in 1..mip_level_count(width, height) {
+        let (src_width, src_height) = mip_extent(width, height, level - 1);
+        let (dst_width, dst_height) = mip_extent(width, height, level);
+        let src = &levels[level as usize - 1];
+
+        let mut dst = Vec::with_capacity((dst_width * dst_height * 4) as usize);
+        for y in 0..dst_height {
+            for x in 0..dst_width {
+                let mut sum = [0.0f32; 4];
+                for (sx, sy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (x * 2 + sx).min(src_width - 1);
+                    let sy = (y * 2 + sy).min(src_height - 1);
+                    let i = ((sy * src_width + sx) * 4) as usize;
+                    for c in 0..4 {
+                        sum[c] += decode_channel(src[i + c], c, usage, srgb) / 4.0;
+                    }
+                }
+                dst.extend(encode_pixel(sum, usage, srgb));
+            }
+        }
+
+        levels.push(dst);
+    }
+
+    levels
+}
+
+fn decode_channel(value: u8, channel: usize, usage: TextureUsage, srgb: bool) -> f32 {
+    match usage {
+        TextureUsage::Color if srgb && channel < 3 => SRGB_TO_LINEAR[value as usize],
+        TextureUsage::Normal if channel < 3 => value as f32 / 255.0 * 2.0 - 1.0,
+        _ => value as f32 / 255.0,
+    }
+}
+
+fn encode_pixel(mut pixel: [f32; 4], usage: TextureUsage, srgb: bool) -> [u8; 4] {
+    let unorm = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    match usage {
+        TextureUsage::Color if srgb => [
+            linear_to_srgb(pixel[0]),
+            linear_to_srgb(pixel[1]),
+            linear_to_srgb(pixel[2]),
+            unorm(pixel[3]),
+        ],
+        TextureUsage::Normal => {
+            let length = pixel[..3].iter().map(|c| c * c).sum::<f32>().sqrt();
+            if length > f32::EPSILON {
+                pixel[..3].iter_mut().for_each(|c| *c /= length);
+            }
+            [
+                unorm(pixel[0] * 0.5 + 0.5),
+                unorm(pixel[1] * 0.5 + 0.5),
+                unorm(pixel[2] * 0.5 + 0.5),
+                unorm(pixel[3]),
+            ]
+        }
+        _ => pixel.map(unorm),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_end_at_one_pixel() {
+        assert_eq!(mip_level_count(1, 1), 1);
+        assert_eq!(mip_level_count(256, 64), 9);
+        assert_eq!(mip_level_count(5, 3), 3);
+
+        let levels = generate_mips(5, 3, &[128; 5 * 3 * 4], TextureUsage::Mask, false);
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[1].len(), 2 * 4);
+        assert_eq!(levels[2].len(), 4);
+        assert!(levels.iter().flatten().all(|&c| c == 128));
+    }
+
+    #[test]
+    fn srgb_colors_average_in_linear_space() {
+        let rgba = [[0, 0, 0, 255], [255, 255, 255, 255]].repeat(2).concat();
+
+        let linear = generate_mips(2, 2, &rgba, TextureUsage::Color, false);
+        let srgb = generate_mips(2, 2, &rgba, TextureUsage::Color, true);
+
+        assert_eq!(linear[1][0], 128);
+        assert_eq!(srgb[1][0], 188);
+        assert_eq!(srgb[1][3], 255);
+    }
+
+    #[test]
+    fn normals_stay_unit_length() {
+        let rgba = [[255, 128, 128, 255], [128, 255, 128, 255]]
+            .repeat(2)
+            .concat();
+
+        let levels = generate_mips(2, 2, &rgba, TextureUsage::Normal, false);
+        let normal = levels[1][..3].iter().map(|&c| c as f32 / 255.0 * 2.0 - 1.0);
+
+        let length = normal.map(|c| c * c).sum::<f32>().sqrt();
+        assert!((length - 1.0).abs() < 0.02);
+    }
+}
@@ -0,0 +1,347 @@
+//! Block compression and mip generation for cooked textures.
+//!
+//! Textures are compressed according to how they're sampled: colors keep their sRGB encoding,
+//! normal maps only store their x and y components and masks are kept linear. BC formats are
+//! meant for desktop GPUs, ETC2 and ASTC for mobile and web targets.
+
+pub mod astc;
+pub mod etc2;
+pub mod mips;
+
+use block_compression::{BC7Settings, CompressionVariant};
+use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+
+/// Pixels of a 4x4 block, row by row
+pub type Block = [[u8; 4]; 16];
+
+/// How a texture is sampled by materials
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TextureUsage {
+    /// Albedo and emissive colors, stored in sRGB if the source is
+    #[default]
+    Color,
+    /// Tangent space normal maps, the z component is reconstructed in the shader
+    Normal,
+    /// Linear data like roughness, metallic or occlusion
+    Mask,
+}
+
+/// The family of block compressed formats textures are cooked to
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TextureCompression {
+    /// Keep uncompressed RGBA8 pixels
+    None,
+    /// BC1, BC3, BC5 and BC7
+    #[default]
+    Bc,
+    /// ETC2 and EAC
+    Etc2,
+    /// ASTC with 4x4 blocks
+    Astc,
+}
+
+/// Controls how [`Texture2D::cook`](crate::Texture2D::cook) prepares a texture for packaging
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TextureCookSettings {
+    pub compression: TextureCompression,
+    pub generate_mips: bool,
+}
+
+impl Default for TextureCookSettings {
+    fn default() -> Self {
+        Self {
+            compression: TextureCompression::default(),
+            generate_mips: true,
+        }
+    }
+}
+
+impl TextureCookSettings {
+    /// Settings for textures loaded while the game is running. Skips the CPU mip chain and
+    /// block compression, leaving the mips to the GPU on upload.
+    pub const RUNTIME: Self = Self {
+        compression: TextureCompression::None,
+        generate_mips: false,
+    };
+}
+
+impl TextureCompression {
+    /// The format a texture with the given usage is compressed to
+    pub fn format(self, usage: TextureUsage, srgb: bool, alpha: bool) -> Option<TextureFormat> {
+        let srgb = srgb && usage == TextureUsage::Color;
+
+        let format = match (self, usage) {
+            (Self::None, _) => return None,
+            (Self::Bc, TextureUsage::Color) if alpha => TextureFormat::Bc3RgbaUnorm,
+            (Self::Bc, TextureUsage::Color) => TextureFormat::Bc1RgbaUnorm,
+            (Self::Bc, TextureUsage::Normal) => TextureFormat::Bc5RgUnorm,
+            (Self::Bc, TextureUsage::Mask) => TextureFormat::Bc7RgbaUnorm,
+            (Self::Etc2, TextureUsage::Normal) => TextureFormat::EacRg11Unorm,
+            (Self::Etc2, _) if alpha => TextureFormat::Etc2Rgba8Unorm,
+            (Self::Etc2, _) => TextureFormat::Etc2Rgb8Unorm,
+            (Self::Astc, _) => TextureFormat::Astc {
+                block: AstcBlock::B4x4,
+                channel: AstcChannel::Unorm,
+            },
+        };
+
+        Some(if srgb {
+            format.add_srgb_suffix()
+        } else {
+            format
+        })
+    }
+}
+
+/// Whether this crate can compress to and decompress from `format`
+pub fn is_supported_compressed_format(format: TextureFormat) -> bool {
+    compressed_kind(format).is_some()
+}
+
+enum CompressedKind {
+    Bc(CompressionVariant),
+    Etc2Rgb,
+    Etc2Rgba,
+    EacRg,
+    Astc { srgb: bool },
+}
+
+fn compressed_kind(format: TextureFormat) -> Option<CompressedKind> {
+    Some(match format.remove_srgb_suffix() {
+        TextureFormat::Bc1RgbaUnorm => CompressedKind::Bc(CompressionVariant::BC1),
+        TextureFormat::Bc3RgbaUnorm => CompressedKind::Bc(CompressionVariant::BC3),
+        TextureFormat::Bc5RgUnorm => CompressedKind::Bc(CompressionVariant::BC5),
+        TextureFormat::Bc7RgbaUnorm => {
+            CompressedKind::Bc(CompressionVariant::BC7(BC7Settings::alpha_fast()))
+        }
+        TextureFormat::Etc2Rgb8Unorm => CompressedKind::Etc2Rgb,
+        TextureFormat::Etc2Rgba8Unorm => CompressedKind::Etc2Rgba,
+        TextureFormat::EacRg11Unorm => CompressedKind::EacRg,
+        TextureFormat::Astc {
+            block: AstcBlock::B4x4,
+            channel: AstcChannel::Unorm | AstcChannel::UnormSrgb,
+        } => CompressedKind::Astc {
+            srgb: format.is_srgb(),
+        },
+        _ => return None,
+    })
+}
+
+/// Compresses an RGBA8 image into `format`. Images that don't fill their last blocks are
+/// padded by repeating their edge pixels.
+///
+/// Returns `None` if `format` isn't a supported compressed format.
+pub fn compress(format: TextureFormat, width: u32, height: u32, rgba: &[u8]) -> Option<Vec<u8>> {
+    let kind = compressed_kind(format)?;
+    let (blocks_x, blocks_y) = (width.div_ceil(4), height.div_ceil(4));
+    let padded = pad_to_blocks(width, height, rgba);
+
+    if let CompressedKind::Bc(variant) = kind {
+        let (padded_width, padded_height) = (blocks_x * 4, blocks_y * 4);
+        let mut data = vec![0; variant.blocks_byte_size(padded_width, padded_height)];
+        block_compression::encode::compress_rgba8(
+            variant,
+            &padded,
+            &mut data,
+            padded_width,
+            padded_height,
+            padded_width * 4,
+        );
+        return Some(data);
+    }
+
+    let mut data = Vec::new();
+    for block_y in 0..blocks_y {
+        for block_x in 0..blocks_x {
+            let block = read_block(&padded, blocks_x * 4, block_x, block_y);
+            match kind {
+                CompressedKind::Etc2Rgb => data.extend(etc2::encode_rgb_block(&block)),
+                CompressedKind::Etc2Rgba => {
+                    data.extend(etc2::encode_eac_block(&block, 3, false));
+                    data.extend(etc2::encode_rgb_block(&block));
+                }
+                CompressedKind::EacRg => {
+                    data.extend(etc2::encode_eac_block(&block, 0, true));
+                    data.extend(etc2::encode_eac_block(&block, 1, true));
+                }
+                CompressedKind::Astc { .. } => {
+                    let alpha = block.iter().any(|pixel| pixel[3] < u8::MAX);
+                    data.extend(astc::encode_block(&block, alpha));
+                }
+                CompressedKind::Bc(_) => unreachable!("bc is compressed as a whole"),
+            }
+        }
+    }
+
+    Some(data)
+}
+
+/// Decompresses an image in `format` back to RGBA8. Channels the format doesn't store are
+/// zero for color and opaque for alpha.
+///
+/// Returns `None` if `format` isn't a supported compressed format or `data` is too short.
+pub fn decompress(format: TextureFormat, width: u32, height: u32, data: &[u8]) -> Option<Vec<u8>> {
+    let kind = compressed_kind(format)?;
+    let (blocks_x, blocks_y) = (width.div_ceil(4), height.div_ceil(4));
+    let (padded_width, padded_height) = (blocks_x * 4, blocks_y * 4);
+    let block_size = format.block_copy_size(None)? as usize;
+    let data = data.get(..(blocks_x * blocks_y) as usize * block_size)?;
+
+    let mut padded = vec![0; (padded_width * padded_height * 4) as usize];
+    if let CompressedKind::Bc(variant) = kind {
+        block_compression::decode::decompress_blocks_as_rgba8(
+            variant,
+            padded_width,
+            padded_height,
+            data,
+            &mut padded,
+        );
+    } else {
+        for (i, data) in data.chunks_exact(block_size).enumerate() {
+            let block = match kind {
+                CompressedKind::Etc2Rgb => etc2::decode_rgb_block(data),
+                CompressedKind::Etc2Rgba => {
+                    let mut block = etc2::decode_rgb_block(&data[8..]);
+                    etc2::decode_eac_block(data, &mut block, 3, false);
+                    block
+                }
+                CompressedKind::EacRg => {
+                    let mut block = [[0, 0, 0, 255]; 16];
+                    etc2::decode_eac_block(data, &mut block, 0, true);
+                    etc2::decode_eac_block(&data[8..], &mut block, 1, true);
+                    block
+                }
+                CompressedKind::Astc { srgb } => astc::decode_block(data, srgb),
+                CompressedKind::Bc(_) => unreachable!("bc is decompressed as a whole"),
+            };
+            let (block_x, block_y) = (i as u32 % blocks_x, i as u32 / blocks_x);
+            write_block(&mut padded, padded_width, block_x, block_y, &block);
+        }
+    }
+
+    Some(crop(&padded, padded_width, width, height))
+}
+
+fn pad_to_blocks(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let (padded_width, padded_height) = (width.div_ceil(4) * 4, height.div_ceil(4) * 4);
+    if (padded_width, padded_height) == (width, height) {
+        return rgba.to_vec();
+    }
+
+    let mut padded = Vec::with_capacity((padded_width * padded_height * 4) as usize);
+    for y in 0..padded_height {
+        let row = (y.min(height - 1) * width * 4) as usize;
+        for x in 0..padded_width {
+            let i = row + (x.min(width - 1) * 4) as usize;
+            padded.extend_from_slice(&rgba[i..i + 4]);
+        }
+    }
+    padded
+}
+
+fn crop(rgba: &[u8], stride_width: u32, width: u32, height: u32) -> Vec<u8> {
+    if stride_width == width && rgba.len() == (width * height * 4) as usize {
+        return rgba.to_vec();
+    }
+
+    (0..height)
+        .flat_map(|y| {
+            let start = (y * stride_width * 4) as usize;
+            &rgba[start..start + (width * 4) as usize]
+        })
+        .copied()
+        .collect()
+}
+
+fn read_block(rgba: &[u8], width: u32, block_x: u32, block_y: u32) -> Block {
+    std::array::from_fn(|i| {
+        let (x, y) = (block_x * 4 + i as u32 % 4, block_y * 4 + i as u32 / 4);
+        let offset = ((y * width + x) * 4) as usize;
+        rgba[offset..offset + 4].try_into().unwrap_or_default()
+    })
+}
+
+fn write_block(rgba: &mut [u8], width: u32, block_x: u32, block_y: u32, block: &Block) {
+    for (i, pixel) in block.iter().enumerate() {
+        let (x, y) = (block_x * 4 + i as u32 % 4, block_y * 4 + i as u32 / 4);
+        let offset = ((y * width + x) * 4) as usize;
+        rgba[offset..offset + 4].copy_from_slice(pixel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32) -> Vec<u8> {
+        (0..width * height)
+            .flat_map(|i| {
+                let v = (i % width + i / width) as u8 * 8;
+                [v + 20, v / 2 + 40, 128, 255]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn usages_pick_formats() {
+        let bc = TextureCompression::Bc;
+        assert_eq!(
+            bc.format(TextureUsage::Color, true, false),
+            Some(TextureFormat::Bc1RgbaUnormSrgb)
+        );
+        assert_eq!(
+            bc.format(TextureUsage::Color, true, true),
+            Some(TextureFormat::Bc3RgbaUnormSrgb)
+        );
+        assert_eq!(
+            bc.format(TextureUsage::Normal, true, false),
+            Some(TextureFormat::Bc5RgUnorm)
+        );
+        assert_eq!(
+            TextureCompression::Etc2.format(TextureUsage::Mask, false, false),
+            Some(TextureFormat::Etc2Rgb8Unorm)
+        );
+        assert_eq!(
+            TextureCompression::None.format(TextureUsage::Color, true, true),
+            None
+        );
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        let (width, height) = (6, 5);
+        let rgba = image(width, height);
+
+        for compression in [
+            TextureCompression::Bc,
+            TextureCompression::Etc2,
+            TextureCompression::Astc,
+        ] {
+            for usage in [
+                TextureUsage::Color,
+                TextureUsage::Normal,
+                TextureUsage::Mask,
+            ] {
+                let format = compression.format(usage, false, false).unwrap();
+                let data = compress(format, width, height, &rgba).unwrap();
+
+                let blocks = (width.div_ceil(4) * height.div_ceil(4)) as usize;
+                assert_eq!(
+                    data.len(),
+                    blocks * format.block_copy_size(None).unwrap() as usize
+                );
+
+                let decoded = decompress(format, width, height, &data).unwrap();
+                assert_eq!(decoded.len(), rgba.len());
+
+                let max_error = rgba
+                    .chunks_exact(4)
+                    .zip(decoded.chunks_exact(4))
+                    .flat_map(|(a, b)| (0..2).map(move |c| (a[c] as i32 - b[c] as i32).abs()))
+                    .max()
+                    .unwrap();
+                assert!(max_error <= 12, "{format:?} is off by {max_error}");
+            }
+        }
+    }
+}
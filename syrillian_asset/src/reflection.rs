@@ -1,11 +1,12 @@
-use crate::assets::mesh::{Bones, Mesh};
+use crate::assets::mesh::{Bones, Mesh, MeshLod, MorphTargets};
 use crate::assets::prefab::{
-    PrefabAsset, PrefabComponent, PrefabMaterial, PrefabMeshBinding, PrefabNode,
+    PrefabAsset, PrefabCamera, PrefabComponent, PrefabLight, PrefabMaterial, PrefabMeshBinding,
+    PrefabNode,
 };
 use crate::assets::shader::{Shader, ShaderCode, ShaderType};
 use crate::assets::texture_2d::Texture2D;
 use crate::mesh::{SkinnedVertex3D, UnskinnedVertex3D};
-use crate::{AnimationChannel, AnimationClip, SkinnedMesh, TransformKeys};
+use crate::{AnimationChannel, AnimationClip, AnimationEvent, SkinnedMesh, TransformKeys};
 use std::collections::BTreeMap;
 use syrillian_reflect::{ReflectSerialize, Value};
 
@@ -84,6 +85,53 @@ impl ReflectSerialize for Bones {
     }
 }
 
+impl ReflectSerialize for MorphTargets {
+    fn serialize(this: &Self) -> Value {
+        let names = this
+            .targets
+            .iter()
+            .map(|t| t.name.clone())
+            .collect::<Vec<_>>();
+        let has_positions = this
+            .targets
+            .iter()
+            .map(|t| !t.position_deltas.is_empty())
+            .collect::<Vec<_>>();
+        let has_normals = this
+            .targets
+            .iter()
+            .map(|t| !t.normal_deltas.is_empty())
+            .collect::<Vec<_>>();
+        Value::Object(BTreeMap::from([
+            ("names".to_string(), ReflectSerialize::serialize(&names)),
+            (
+                "default_weights".to_string(),
+                ReflectSerialize::serialize(&this.default_weights),
+            ),
+            (
+                "has_positions".to_string(),
+                ReflectSerialize::serialize(&has_positions),
+            ),
+            (
+                "has_normals".to_string(),
+                ReflectSerialize::serialize(&has_normals),
+            ),
+        ]))
+    }
+}
+
+impl ReflectSerialize for MeshLod {
+    fn serialize(this: &Self) -> Value {
+        Value::Object(BTreeMap::from([
+            (
+                "material_ranges".to_string(),
+                ReflectSerialize::serialize(&this.material_ranges),
+            ),
+            ("error".to_string(), Value::Float(this.error)),
+        ]))
+    }
+}
+
 impl ReflectSerialize for Mesh {
     fn serialize(this: &Self) -> Value {
         Value::Object(BTreeMap::from([
@@ -91,6 +139,11 @@ impl ReflectSerialize for Mesh {
                 "material_ranges".to_string(),
                 ReflectSerialize::serialize(&this.material_ranges),
             ),
+            ("lods".to_string(), ReflectSerialize::serialize(&this.lods)),
+            (
+                "morph_targets".to_string(),
+                ReflectSerialize::serialize(&*this.morph_targets),
+            ),
             (
                 "bounding_sphere".to_string(),
                 match this.bounding_sphere {
@@ -112,6 +165,10 @@ impl ReflectSerialize for SkinnedMesh {
                 "material_ranges".to_string(),
                 ReflectSerialize::serialize(&this.material_ranges),
             ),
+            (
+                "morph_targets".to_string(),
+                ReflectSerialize::serialize(&*this.morph_targets),
+            ),
             (
                 "bones".to_string(),
                 ReflectSerialize::serialize(&this.bones),
@@ -139,6 +196,10 @@ impl ReflectSerialize for Texture2D {
                 "format".to_string(),
                 Value::String(format!("{:?}", this.format)),
             ),
+            (
+                "mip_level_count".to_string(),
+                Value::UInt(this.mip_level_count),
+            ),
             (
                 "repeat_mode".to_string(),
                 Value::String(format!("{:?}", this.repeat_mode)),
@@ -155,6 +216,7 @@ impl ReflectSerialize for Texture2D {
                 "has_transparency".to_string(),
                 Value::Bool(this.has_transparency),
             ),
+            ("generate_mips".to_string(), Value::Bool(this.generate_mips)),
         ]))
     }
 }
@@ -213,6 +275,10 @@ impl ReflectSerialize for Shader {
                 Value::Bool(this.is_depth_enabled()),
             ),
             ("opaque".to_string(), Value::Bool(this.is_opaque())),
+            (
+                "defines".to_string(),
+                ReflectSerialize::serialize(&this.defines().to_vec()),
+            ),
         ]))
     }
 }
@@ -232,6 +298,38 @@ impl ReflectSerialize for PrefabMeshBinding {
     }
 }
 
+impl ReflectSerialize for PrefabCamera {
+    fn serialize(this: &Self) -> Value {
+        Value::Object(BTreeMap::from([
+            ("yfov".to_string(), Value::Float(this.yfov)),
+            ("znear".to_string(), Value::Float(this.znear)),
+            ("zfar".to_string(), Value::Float(this.zfar)),
+        ]))
+    }
+}
+
+impl ReflectSerialize for PrefabLight {
+    fn serialize(this: &Self) -> Value {
+        Value::Object(BTreeMap::from([
+            (
+                "kind".to_string(),
+                Value::String(this.kind.name().to_string()),
+            ),
+            (
+                "color".to_string(),
+                ReflectSerialize::serialize(&this.color),
+            ),
+            ("intensity".to_string(), Value::Float(this.intensity)),
+            (
+                "range".to_string(),
+                ReflectSerialize::serialize(&this.range),
+            ),
+            ("inner_angle".to_string(), Value::Float(this.inner_angle)),
+            ("outer_angle".to_string(), Value::Float(this.outer_angle)),
+        ]))
+    }
+}
+
 impl ReflectSerialize for TransformKeys {
     fn serialize(this: &Self) -> Value {
         Value::Object(BTreeMap::from([
@@ -259,6 +357,14 @@ impl ReflectSerialize for TransformKeys {
                 "s_values_count".to_string(),
                 Value::BigUInt(this.s_values.len() as u64),
             ),
+            (
+                "w_times_count".to_string(),
+                Value::BigUInt(this.w_times.len() as u64),
+            ),
+            (
+                "w_values_count".to_string(),
+                Value::BigUInt(this.w_values.len() as u64),
+            ),
         ]))
     }
 }
@@ -282,11 +388,27 @@ impl ReflectSerialize for AnimationClip {
             .iter()
             .map(ReflectSerialize::serialize)
             .collect::<Vec<_>>();
+        let events = this
+            .events
+            .iter()
+            .map(ReflectSerialize::serialize)
+            .collect::<Vec<_>>();
 
         Value::Object(BTreeMap::from([
             ("name".to_string(), Value::String(this.name.clone())),
             ("duration".to_string(), Value::Float(this.duration)),
             ("channels".to_string(), Value::Array(channels)),
+            ("events".to_string(), Value::Array(events)),
+        ]))
+    }
+}
+
+impl ReflectSerialize for AnimationEvent {
+    fn serialize(this: &Self) -> Value {
+        Value::Object(BTreeMap::from([
+            ("name".to_string(), Value::String(this.name.clone())),
+            ("time".to_string(), Value::Float(this.time)),
+            ("payload".to_string(), this.payload.clone()),
         ]))
     }
 }
@@ -339,6 +461,14 @@ impl ReflectSerialize for PrefabNode {
             map.insert("components".to_string(), Value::Array(components));
         }
 
+        if let Some(camera) = &this.camera {
+            map.insert("camera".to_string(), ReflectSerialize::serialize(camera));
+        }
+
+        if let Some(light) = &this.light {
+            map.insert("light".to_string(), ReflectSerialize::serialize(light));
+        }
+
         Value::Object(map)
     }
 }
@@ -387,6 +517,29 @@ impl ReflectSerialize for PrefabMaterial {
                 "occlusion_texture".to_string(),
                 ReflectSerialize::serialize(&this.occlusion_texture),
             ),
+            (
+                "emissive_strength".to_string(),
+                Value::Float(this.emissive_strength),
+            ),
+            (
+                "occlusion_strength".to_string(),
+                Value::Float(this.occlusion_strength),
+            ),
+            (
+                "uv_offset".to_string(),
+                ReflectSerialize::serialize(&this.uv_offset),
+            ),
+            ("uv_rotation".to_string(), Value::Float(this.uv_rotation)),
+            (
+                "uv_scale".to_string(),
+                ReflectSerialize::serialize(&this.uv_scale),
+            ),
+            ("clearcoat".to_string(), Value::Float(this.clearcoat)),
+            (
+                "clearcoat_roughness".to_string(),
+                Value::Float(this.clearcoat_roughness),
+            ),
+            ("transmission".to_string(), Value::Float(this.transmission)),
         ]))
     }
 }
@@ -423,6 +576,10 @@ syrillian_reflect::register_type!(syrillian_reflect::reflect_type_info!(
     syrillian_asset::assets::mesh,
     Bones
 ));
+syrillian_reflect::register_type!(syrillian_reflect::reflect_type_info!(
+    syrillian_asset::assets::mesh,
+    MeshLod
+));
 syrillian_reflect::register_type!(syrillian_reflect::reflect_type_info!(
     syrillian_asset::assets,
     Mesh
@@ -467,6 +624,14 @@ syrillian_reflect::register_type!(syrillian_reflect::reflect_type_info!(
     syrillian_asset::assets,
     PrefabNode
 ));
+syrillian_reflect::register_type!(syrillian_reflect::reflect_type_info!(
+    syrillian_asset::assets,
+    PrefabCamera
+));
+syrillian_reflect::register_type!(syrillian_reflect::reflect_type_info!(
+    syrillian_asset::assets,
+    PrefabLight
+));
 syrillian_reflect::register_type!(syrillian_reflect::reflect_type_info!(
     syrillian_asset::assets,
     PrefabMaterial
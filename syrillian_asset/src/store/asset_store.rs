@@ -141,57 +141,148 @@ impl AssetStore {
         let name = name.into();
 
         let shader_code = MaterialCompiler::compile_shader_set(&mut material_expr);
-        let shader_set = self.store_shader_set(&name, shader_code, &layout);
+        let shader_set = store_shader_set(&self.shaders, &name, shader_code, &layout);
 
         let material = Material::Custom(CustomMaterial::new(name, layout, shader_set));
         self.materials.add(material)
     }
 
-    fn store_shader_set(
+    /// Like [`register_custom_material_with_layout`](Self::register_custom_material_with_layout),
+    /// but keeps the expression around so the renderer can compile variants specialized
+    /// for the layout's [permutation keys](MaterialInputLayout::permutation_keys).
+    pub fn register_permuted_material<M>(
         &self,
-        base_name: &str,
-        set: MaterialShaderSetCode,
-        layout: &MaterialInputLayout,
-    ) -> MaterialShaderSet {
-        let groups = MaterialShaderGroups {
-            material: layout.wgsl_material_group(),
-            material_textures: layout.wgsl_material_textures_group(),
+        name: impl Into<String>,
+        mut material_expr: M,
+        layout: MaterialInputLayout,
+    ) -> HMaterial
+    where
+        M: MaterialExpression + Clone + Send + Sync + 'static,
+    {
+        let name = name.into();
+
+        let shader_code = MaterialCompiler::compile_shader_set(&mut material_expr);
+        let shader_set = store_shader_set(&self.shaders, &name, shader_code, &layout);
+
+        let material = CustomMaterial::new(name, layout, shader_set)
+            .with_permutations(MaterialPermutations::new(material_expr));
+        self.materials.add(Material::Custom(material))
+    }
+
+    /// Compiles a material graph and adds it as a custom material.
+    pub fn register_material_graph(
+        &self,
+        asset: &MaterialGraphAsset,
+    ) -> Result<HMaterial, MaterialGraphAssetError> {
+        let material = compile_material_graph(&self.shaders, asset)?;
+        Ok(self.materials.add(material))
+    }
+
+    /// Recompiles `handle` from an edited graph. The previous shaders are only
+    /// replaced if the new graph compiles.
+    pub fn reload_material_graph(
+        &self,
+        handle: HMaterial,
+        asset: &MaterialGraphAsset,
+    ) -> Result<(), MaterialGraphAssetError> {
+        if !self.materials.contains(handle) {
+            return MissingMaterialErr {
+                handle: handle.ident(),
+            }
+            .fail();
+        }
+
+        let material = compile_material_graph(&self.shaders, asset)?;
+        let previous = match self.materials.try_get_mut(handle) {
+            Some(mut slot) => std::mem::replace(&mut *slot, material),
+            None => {
+                // Removed while the graph was compiling
+                self.remove_shader_set(&material);
+                return MissingMaterialErr {
+                    handle: handle.ident(),
+                }
+                .fail();
+            }
         };
-        let imm_size = layout.immediate_size();
 
-        let base = Shader::builder()
-            .name(format!("{} (Base)", base_name))
-            .shader_type(ShaderType::Custom)
-            .code(ShaderCode::Full(set.base))
-            .material_layout(layout.clone())
-            .material_groups(groups.clone())
-            .immediate_size(imm_size)
-            .build()
-            .store(self);
+        if let Material::Custom(_) = previous {
+            self.remove_shader_set(&previous);
+        }
+        Ok(())
+    }
+
+    fn remove_shader_set(&self, material: &Material) {
+        let set = material.shader_set();
+        self.shaders.remove(set.base);
+        self.shaders.remove(set.picking);
+        self.shaders.remove(set.shadow);
+    }
+}
+
+pub(crate) fn compile_material_graph(
+    shaders: &Store<Shader>,
+    asset: &MaterialGraphAsset,
+) -> Result<Material, MaterialGraphAssetError> {
+    let shader_code = asset.compile()?;
+    let shader_set = store_shader_set(shaders, &asset.name, shader_code, &asset.layout);
+    let mut material = CustomMaterial::new(asset.name.clone(), asset.layout.clone(), shader_set);
+    if !asset.layout.permutation_keys.is_empty() {
+        material = material.with_permutations(MaterialPermutations::new(asset.graph.clone()));
+    }
+    Ok(Material::Custom(material))
+}
 
-        let picking = Shader::builder()
-            .name(format!("{} (Picking)", base_name))
-            .shader_type(ShaderType::Picking)
-            .code(ShaderCode::Full(set.picking))
+fn store_shader_set(
+    shaders: &Store<Shader>,
+    base_name: &str,
+    set: MaterialShaderSetCode,
+    layout: &MaterialInputLayout,
+) -> MaterialShaderSet {
+    let groups = MaterialShaderGroups {
+        material: layout.wgsl_material_group(),
+        material_textures: layout.wgsl_material_textures_group(),
+    };
+    let imm_size = layout.immediate_size();
+
+    let base =
+        Shader::new_material(format!("{} (Base)", base_name), set.base, layout).store(shaders);
+
+    let picking = Shader::builder()
+        .name(format!("{} (Picking)", base_name))
+        .shader_type(ShaderType::Picking)
+        .code(ShaderCode::Full(set.picking))
+        .material_layout(layout.clone())
+        .material_groups(groups.clone())
+        .immediate_size(imm_size)
+        .transparent()
+        .build()
+        .store(shaders);
+
+    let shadow = if set.custom_vertex {
+        // Offset vertices need the material and every vertex attribute in the shadow pass
+        Shader::builder()
+            .name(format!("{} (Shadow)", base_name))
+            .shader_type(ShaderType::Shadow)
+            .code(ShaderCode::Full(set.shadow))
             .material_layout(layout.clone())
-            .material_groups(groups.clone())
+            .material_groups(groups)
             .immediate_size(imm_size)
             .transparent()
             .build()
-            .store(self);
-
-        let shadow = Shader::builder()
+            .store(shaders)
+    } else {
+        Shader::builder()
             .name(format!("{} (Shadow)", base_name))
             .shader_type(ShaderType::Shadow)
             .code(ShaderCode::Full(set.shadow))
             .build()
-            .store(self);
+            .store(shaders)
+    };
 
-        MaterialShaderSet {
-            base,
-            picking,
-            shadow,
-        }
+    MaterialShaderSet {
+        base,
+        picking,
+        shadow,
     }
 }
 
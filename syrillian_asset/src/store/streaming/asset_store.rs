@@ -121,6 +121,17 @@ pub enum StreamingAssetBlobKind {
     AnimationRotationValues = 14,
     AnimationScaleTimes = 15,
     AnimationScaleValues = 16,
+
+    MorphPositionDeltas = 17,
+    MorphNormalDeltas = 18,
+
+    AnimationWeightTimes = 19,
+    AnimationWeightValues = 20,
+
+    MeshNormalsOctahedral = 21,
+    MeshUVsHalf = 22,
+
+    TextureMipLevel = 23,
 }
 
 impl StreamingAssetBlobKind {
@@ -146,6 +157,17 @@ impl StreamingAssetBlobKind {
             Self::AnimationRotationValues => "AnimationRotationValues",
             Self::AnimationScaleTimes => "AnimationScaleTimes",
             Self::AnimationScaleValues => "AnimationScaleValues",
+
+            Self::MorphPositionDeltas => "MorphPositionDeltas",
+            Self::MorphNormalDeltas => "MorphNormalDeltas",
+
+            Self::AnimationWeightTimes => "AnimationWeightTimes",
+            Self::AnimationWeightValues => "AnimationWeightValues",
+
+            Self::MeshNormalsOctahedral => "MeshNormalsOctahedral",
+            Self::MeshUVsHalf => "MeshUVsHalf",
+
+            Self::TextureMipLevel => "TextureMipLevel",
         }
     }
 }
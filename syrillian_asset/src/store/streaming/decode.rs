@@ -1,5 +1,6 @@
 use crate::assets::prefab::{PrefabAsset, PrefabMaterial};
-use crate::assets::{Mesh, Shader, Texture2D};
+use crate::assets::{Material, MaterialGraphAsset, Mesh, Shader, Texture2D};
+use crate::store::asset_store::compile_material_graph;
 use crate::store::streaming::AssetStreamingError;
 use crate::store::streaming::asset_store::{
     AssetType, StreamingAssetBlobInfo, StreamingAssetEntryInfo, StreamingAssetFile,
@@ -64,6 +65,14 @@ impl StreamingLoadableAsset for PrefabMaterial {
     }
 }
 
+impl StreamingLoadableAsset for Material {
+    const PACKAGE_TYPE: AssetType = AssetType::Material;
+
+    fn insert_into(store: &AssetStore, asset: Self) -> H<Self> {
+        store.materials.add(asset)
+    }
+}
+
 impl StreamingLoadableAsset for PrefabAsset {
     const PACKAGE_TYPE: AssetType = AssetType::Prefab;
 
@@ -228,6 +237,7 @@ struct WorkerRuntime {
     textures: Arc<Store<Texture2D>>,
     cubemaps: Arc<Store<Cubemap>>,
     shaders: Arc<Store<Shader>>,
+    materials: Arc<Store<Material>>,
     animation_clips: Arc<Store<AnimationClip>>,
     prefab_materials: Arc<Store<PrefabMaterial>>,
     prefabs: Arc<Store<PrefabAsset>>,
@@ -241,6 +251,7 @@ impl WorkerRuntime {
             textures: store.textures.clone(),
             cubemaps: store.cubemaps.clone(),
             shaders: store.shaders.clone(),
+            materials: store.materials.clone(),
             animation_clips: store.animation_clips.clone(),
             prefab_materials: store.prefab_materials.clone(),
             prefabs: store.prefabs.clone(),
@@ -597,6 +608,16 @@ impl WorkerRuntime {
                 let shader = Shader::decode(&payload, &mut package)?;
                 Ok(ErasedHandle::of(self.shaders.add(shader)))
             }
+            AssetType::Material if MaterialGraphAsset::is_graph_payload(&payload.data) => {
+                let asset = MaterialGraphAsset::decode(&payload, &mut package)?;
+                let material = compile_material_graph(&self.shaders, &asset).map_err(|e| {
+                    AssetStreamingError::AssetParse {
+                        path: job.path.clone(),
+                        reason: e.to_string(),
+                    }
+                })?;
+                Ok(ErasedHandle::of(self.materials.add(material)))
+            }
             AssetType::Material => {
                 let material = PrefabMaterial::decode(&payload, &mut package)?;
                 Ok(ErasedHandle::of(self.prefab_materials.add(material)))
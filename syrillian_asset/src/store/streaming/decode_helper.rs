@@ -1,17 +1,18 @@
 use crate::ShaderType;
-use crate::mesh::Bones;
+use crate::mesh::{Bones, MorphTarget, MorphTargets};
 use crate::store::streaming::asset_store::{
     StreamingAssetBlobInfos, StreamingAssetBlobKind, StreamingAssetFile,
 };
 use crate::store::streaming::error::Result;
-use glam::{Quat, Vec3, Vec4};
+use glam::{Quat, Vec2, Vec3, Vec4};
 use serde_json::{Map, Value};
 use snafu::{OptionExt, ResultExt, whatever};
 use std::collections::HashMap;
 use std::ops::Range;
 use syrillian_utils::BoundingSphere;
 use wgpu::{
-    AddressMode, FilterMode, MipmapFilterMode, PolygonMode, PrimitiveTopology, TextureFormat,
+    AddressMode, AstcBlock, AstcChannel, FilterMode, MipmapFilterMode, PolygonMode,
+    PrimitiveTopology, TextureFormat,
 };
 
 pub trait MapDecodeHelper {
@@ -214,6 +215,19 @@ where
     }
 }
 
+impl ParseDecode<Vec2> for Value {
+    fn expect_parse(&self, label: &str) -> Result<Vec2> {
+        let array = self.expect_array(label)?;
+        if array.len() != 2 {
+            whatever!("{label} expected 2 elements but found {}", array.len());
+        }
+        Ok(Vec2::new(
+            array[0].expect_f32(label)?,
+            array[1].expect_f32(label)?,
+        ))
+    }
+}
+
 impl ParseDecode<Vec3> for Value {
     fn expect_parse(&self, label: &str) -> Result<Vec3> {
         let array = self.expect_array(label)?;
@@ -278,6 +292,22 @@ impl ParseDecode<TextureFormat> for Value {
             "Bc1RgbaUnormSrgb" => Ok(TextureFormat::Bc1RgbaUnormSrgb),
             "Bc3RgbaUnorm" => Ok(TextureFormat::Bc3RgbaUnorm),
             "Bc3RgbaUnormSrgb" => Ok(TextureFormat::Bc3RgbaUnormSrgb),
+            "Bc5RgUnorm" => Ok(TextureFormat::Bc5RgUnorm),
+            "Bc7RgbaUnorm" => Ok(TextureFormat::Bc7RgbaUnorm),
+            "Bc7RgbaUnormSrgb" => Ok(TextureFormat::Bc7RgbaUnormSrgb),
+            "Etc2Rgb8Unorm" => Ok(TextureFormat::Etc2Rgb8Unorm),
+            "Etc2Rgb8UnormSrgb" => Ok(TextureFormat::Etc2Rgb8UnormSrgb),
+            "Etc2Rgba8Unorm" => Ok(TextureFormat::Etc2Rgba8Unorm),
+            "Etc2Rgba8UnormSrgb" => Ok(TextureFormat::Etc2Rgba8UnormSrgb),
+            "EacRg11Unorm" => Ok(TextureFormat::EacRg11Unorm),
+            "Astc { block: B4x4, channel: Unorm }" => Ok(TextureFormat::Astc {
+                block: AstcBlock::B4x4,
+                channel: AstcChannel::Unorm,
+            }),
+            "Astc { block: B4x4, channel: UnormSrgb }" => Ok(TextureFormat::Astc {
+                block: AstcBlock::B4x4,
+                channel: AstcChannel::UnormSrgb,
+            }),
             "R16Unorm" => Ok(TextureFormat::R16Unorm),
             "Rg16Snorm" => Ok(TextureFormat::Rg16Snorm),
             "Rgba16Unorm" => Ok(TextureFormat::Rgba16Unorm),
@@ -409,3 +439,65 @@ impl ParseDecodeWithBlobs<Bones> for Value {
         })
     }
 }
+
+impl ParseDecodeWithBlobs<MorphTargets> for Value {
+    fn expect_parse_blobs(
+        &self,
+        blobs: &StreamingAssetBlobInfos,
+        package: &mut StreamingAssetFile,
+    ) -> Result<MorphTargets> {
+        let morph = self.expect_object("mesh morph targets")?;
+        let names: Vec<String> = morph
+            .required_field("names")?
+            .expect_parse("mesh morph target names")?;
+        let default_weights = morph
+            .required_field("default_weights")?
+            .expect_parse("mesh morph target default weights")?;
+        let has_positions: Vec<bool> = morph
+            .required_field("has_positions")?
+            .expect_parse("mesh morph target position flags")?;
+        let has_normals: Vec<bool> = morph
+            .required_field("has_normals")?
+            .expect_parse("mesh morph target normal flags")?;
+
+        let mut position_blobs = blobs
+            .infos
+            .iter()
+            .filter(|b| b.kind == StreamingAssetBlobKind::MorphPositionDeltas);
+        let mut normal_blobs = blobs
+            .infos
+            .iter()
+            .filter(|b| b.kind == StreamingAssetBlobKind::MorphNormalDeltas);
+
+        let mut targets = Vec::with_capacity(names.len());
+        for (i, name) in names.into_iter().enumerate() {
+            let position_deltas = if has_positions.get(i).copied().unwrap_or(false) {
+                match position_blobs.next() {
+                    Some(blob) => blob.decode_all_from_io(package)?,
+                    None => whatever!("missing position deltas for morph target '{name}'"),
+                }
+            } else {
+                Vec::new()
+            };
+            let normal_deltas = if has_normals.get(i).copied().unwrap_or(false) {
+                match normal_blobs.next() {
+                    Some(blob) => blob.decode_all_from_io(package)?,
+                    None => whatever!("missing normal deltas for morph target '{name}'"),
+                }
+            } else {
+                Vec::new()
+            };
+
+            targets.push(MorphTarget {
+                name,
+                position_deltas,
+                normal_deltas,
+            });
+        }
+
+        Ok(MorphTargets {
+            targets,
+            default_weights,
+        })
+    }
+}
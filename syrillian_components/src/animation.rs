@@ -1,11 +1,12 @@
 // TODO: refactor
 
-use crate::SkeletalComponent;
+use crate::animation_graph::{AnimationGraph, AnimationGraphState, GraphSample};
+use crate::{SkeletalComponent, SkinnedMeshRenderer};
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use syrillian::Reflect;
 use syrillian::World;
-use syrillian::assets::AnimationClip;
+use syrillian::assets::{AnimationClip, AnimationEvent};
 use syrillian::components::Component;
 use syrillian::core::GameObjectId;
 use syrillian::math::{Quat, Vec3};
@@ -13,6 +14,9 @@ use syrillian::tracing::warn;
 
 const DEFAULT_CROSSFADE_DURATION: f32 = 0.2;
 const LAYER_REMOVE_EPSILON: f32 = 1e-3;
+/// Clips blended in below this weight don't fire their events, so fading
+/// clips and minor blend space contributions stay quiet.
+const EVENT_MIN_WEIGHT: f32 = 0.25;
 
 #[derive(Debug, Default, Clone)]
 pub struct ClipIndex {
@@ -64,7 +68,7 @@ impl Default for Playback {
 }
 
 #[derive(Debug, Clone)]
-struct ActiveLayer {
+struct ActiveClip {
     clip_index: usize,
     time: f32,
     speed: f32,
@@ -72,9 +76,13 @@ struct ActiveLayer {
     weight: f32,
     target_weight: f32,
     fade_rate: f32,
+    // Clip seconds covered by the last advance
+    delta: f32,
+    started: bool,
+    from_start: bool,
 }
 
-impl ActiveLayer {
+impl ActiveClip {
     fn new_immediate(
         clip_index: usize,
         looping: bool,
@@ -90,6 +98,9 @@ impl ActiveLayer {
             weight: start_weight.clamp(0.0, 1.0),
             target_weight: target_weight.clamp(0.0, 1.0),
             fade_rate: 0.0,
+            delta: 0.0,
+            started: false,
+            from_start: false,
         }
     }
 
@@ -105,6 +116,21 @@ impl ActiveLayer {
         self.fade_rate = (self.target_weight - self.weight) / duration;
     }
 
+    fn from_sample(sample: &GraphSample) -> Self {
+        Self {
+            clip_index: sample.clip_index,
+            time: sample.time,
+            speed: 0.0,
+            looping: sample.looping,
+            weight: sample.weight,
+            target_weight: sample.weight,
+            fade_rate: 0.0,
+            delta: sample.delta,
+            started: true,
+            from_start: sample.from_start,
+        }
+    }
+
     fn step_weight(&mut self, dt: f32) {
         if self.fade_rate == 0.0 {
             return;
@@ -121,6 +147,65 @@ impl ActiveLayer {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LayerBlendMode {
+    /// Blends from the pose below towards the layer's pose by the layer weight.
+    #[default]
+    Override,
+    /// Adds the difference between the current and the first frame of the layer's clips.
+    Additive,
+}
+
+/// Limits a layer to a part of the rig. Names match skeleton bones and animated game objects.
+#[derive(Debug, Clone, Default)]
+pub struct BoneMask {
+    /// Bones or nodes that are included together with everything below them.
+    pub subtrees: Vec<String>,
+    /// Bones or nodes that are included on their own.
+    pub bones: Vec<String>,
+}
+
+impl BoneMask {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subtree(mut self, root: impl Into<String>) -> Self {
+        self.subtrees.push(root.into());
+        self
+    }
+
+    pub fn bone(mut self, name: impl Into<String>) -> Self {
+        self.bones.push(name.into());
+        self
+    }
+}
+
+/// A layer that is blended on top of the base playback, see [`AnimationComponent::add_layer`].
+#[derive(Debug, Clone)]
+pub struct AnimationLayer {
+    pub name: String,
+    pub mode: LayerBlendMode,
+    weight: f32,
+    mask: Option<BoneMask>,
+    mask_targets: Option<HashSet<String>>,
+    active: Vec<ActiveClip>,
+}
+
+impl AnimationLayer {
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    pub fn mask(&self) -> Option<&BoneMask> {
+        self.mask.as_ref()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        !self.active.is_empty()
+    }
+}
+
 #[derive(Default, Reflect)]
 #[reflect(default)]
 pub struct AnimationComponent {
@@ -128,11 +213,19 @@ pub struct AnimationComponent {
     clips: Vec<AnimationClip>,
     clip_indices: Vec<ClipIndex>,
 
-    // Active layered playback stack
-    #[dont_reflect]
-    layers: Vec<ActiveLayer>,
+    // Playback stack of the base layer
+    active: Vec<ActiveClip>,
+    layers: Vec<AnimationLayer>,
 
     bindings: Vec<Vec<ChannelBinding>>,
+
+    // Drives the layer stack while it has any states
+    #[reflect]
+    graph: AnimationGraph,
+    graph_state: AnimationGraphState,
+    graph_samples: Vec<GraphSample>,
+
+    fired_events: Vec<AnimationEvent>,
 }
 
 /// Position, Rotation, Scale
@@ -219,6 +312,36 @@ impl QuatAccumulator {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+struct WeightsAccumulator {
+    sum: Vec<f32>,
+    weight_sum: f32,
+}
+
+impl WeightsAccumulator {
+    fn add(&mut self, values: &[f32], weight: f32) {
+        if weight <= 0.0 {
+            return;
+        }
+        if self.sum.len() < values.len() {
+            self.sum.resize(values.len(), 0.0);
+        }
+        for (sum, value) in self.sum.iter_mut().zip(values) {
+            *sum += value * weight;
+        }
+        self.weight_sum += weight;
+    }
+
+    fn mixed(&self) -> Option<Vec<f32>> {
+        (self.weight_sum > LAYER_REMOVE_EPSILON)
+            .then(|| self.sum.iter().map(|w| w / self.weight_sum).collect())
+    }
+
+    fn blend_weight(&self) -> f32 {
+        self.weight_sum.clamp(0.0, 1.0)
+    }
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 struct PoseAccumulator {
     translation: Vec3Accumulator,
@@ -226,20 +349,211 @@ struct PoseAccumulator {
     scale: Vec3Accumulator,
 }
 
+/// Everything one layer samples this frame, per bound target.
+#[derive(Debug, Default)]
+struct LayerPose {
+    transforms: HashMap<GameObjectId, PoseAccumulator>,
+    skeletons: HashMap<GameObjectId, Vec<PoseAccumulator>>,
+    morph_weights: HashMap<GameObjectId, WeightsAccumulator>,
+}
+
+/// Only the channels some layer animated are written back to a transform.
+#[derive(Debug, Copy, Clone, Default)]
+struct TransformPose {
+    translation: Option<Vec3>,
+    rotation: Option<Quat>,
+    scale: Option<Vec3>,
+}
+
+/// The final local poses, built up layer by layer.
+#[derive(Debug, Default)]
+struct TargetPoses {
+    transforms: HashMap<GameObjectId, TransformPose>,
+    skeletons: HashMap<GameObjectId, Vec<SkeletonLocals>>,
+    morph_weights: HashMap<GameObjectId, Vec<f32>>,
+}
+
+impl TargetPoses {
+    fn blend(&mut self, layer: &LayerPose, weight: f32, mode: LayerBlendMode) {
+        for (&go, accum) in &layer.transforms {
+            let pose = self.transforms.entry(go).or_default();
+            let tr = &go.transform;
+            let mut local = (
+                pose.translation.unwrap_or(*tr.local_position()),
+                pose.rotation.unwrap_or(*tr.local_rotation()),
+                pose.scale.unwrap_or(*tr.local_scale()),
+            );
+
+            blend_local(&mut local, accum, weight, mode);
+
+            if accum.translation.mixed().is_some() {
+                pose.translation = Some(local.0);
+            }
+            if accum.rotation.mixed().is_some() {
+                pose.rotation = Some(local.1);
+            }
+            if accum.scale.mixed().is_some() {
+                pose.scale = Some(local.2);
+            }
+        }
+
+        for (&skel_go, accum) in &layer.skeletons {
+            let locals = match self.skeletons.entry(skel_go) {
+                Entry::Occupied(o) => o.into_mut(),
+                Entry::Vacant(e) => {
+                    let Some(skel) = skel_go.get_component::<SkeletalComponent>() else {
+                        warn!("Skeleton not found on supposed Bone Channel Binding");
+                        continue;
+                    };
+
+                    let bind_pose = skel
+                        .bones()
+                        .bind_local
+                        .iter()
+                        .map(|bind_local| {
+                            let (scale, rotation, translation) =
+                                bind_local.to_scale_rotation_translation();
+                            (translation, rotation, scale)
+                        })
+                        .collect();
+                    e.insert(bind_pose)
+                }
+            };
+
+            for (local, pose) in locals.iter_mut().zip(accum) {
+                blend_local(local, pose, weight, mode);
+            }
+        }
+
+        for (&go, accum) in &layer.morph_weights {
+            let Some(mixed) = accum.mixed() else {
+                continue;
+            };
+
+            let current = self.morph_weights.entry(go).or_insert_with(|| {
+                go.get_component::<SkinnedMeshRenderer>()
+                    .map(|renderer| renderer.morph_weights().to_vec())
+                    .unwrap_or_default()
+            });
+            if current.len() < mixed.len() {
+                current.resize(mixed.len(), 0.0);
+            }
+
+            let w = accum.blend_weight() * weight;
+            for (current, target) in current.iter_mut().zip(mixed) {
+                match mode {
+                    LayerBlendMode::Override => *current += (target - *current) * w,
+                    LayerBlendMode::Additive => *current += target * w,
+                }
+            }
+        }
+    }
+
+    fn apply(self) {
+        for (mut go, pose) in self.transforms {
+            if !go.exists() {
+                warn!("Animation game object was not found");
+                continue;
+            }
+
+            let tr = &mut go.transform;
+            if let Some(t) = pose.translation {
+                tr.set_local_position_vec(t);
+            }
+            if let Some(r) = pose.rotation {
+                tr.set_local_rotation(r);
+            }
+            if let Some(s) = pose.scale {
+                tr.set_nonuniform_local_scale(s);
+            }
+        }
+
+        for (skel_go, locals) in self.skeletons {
+            let Some(mut skel) = skel_go.get_component::<SkeletalComponent>() else {
+                warn!("Skeleton not found on supposed Bone Channel Binding");
+                continue;
+            };
+
+            skel.set_local_pose_trs(&locals);
+        }
+
+        for (go, weights) in self.morph_weights {
+            let Some(mut renderer) = go.get_component::<SkinnedMeshRenderer>() else {
+                warn!("Morph target weights were animated on an object without a skinned mesh");
+                continue;
+            };
+
+            renderer.set_morph_weights(&weights);
+        }
+    }
+}
+
+fn blend_local(
+    local: &mut SkeletonLocals,
+    pose: &PoseAccumulator,
+    weight: f32,
+    mode: LayerBlendMode,
+) {
+    let (translation, rotation, scale) = local;
+
+    let t_weight = pose.translation.blend_weight() * weight;
+    let r_weight = pose.rotation.blend_weight() * weight;
+    let s_weight = pose.scale.blend_weight() * weight;
+
+    match mode {
+        LayerBlendMode::Override => {
+            if let Some(t) = pose.translation.mixed() {
+                *translation = translation.lerp(t, t_weight);
+            }
+            if let Some(r) = pose.rotation.mixed() {
+                *rotation = rotation.slerp(r, r_weight);
+            }
+            if let Some(s) = pose.scale.mixed() {
+                *scale = scale.lerp(s, s_weight);
+            }
+        }
+        LayerBlendMode::Additive => {
+            if let Some(t) = pose.translation.mixed() {
+                *translation += t * t_weight;
+            }
+            if let Some(r) = pose.rotation.mixed() {
+                *rotation = (*rotation * Quat::IDENTITY.slerp(r, r_weight)).normalize();
+            }
+            if let Some(s) = pose.scale.mixed() {
+                *scale *= Vec3::ONE.lerp(s, s_weight);
+            }
+        }
+    }
+}
+
 impl Component for AnimationComponent {
     fn update(&mut self, world: &mut World) {
-        if self.layers.is_empty() || self.clips.is_empty() {
+        if self.clips.is_empty() {
             return;
         }
 
         let dt = world.delta_time().as_secs_f32();
-        self.advance_layers(dt);
-        self.prune_layers();
-        if self.layers.is_empty() {
+
+        if !self.graph.is_empty() {
+            self.advance_graph(dt);
+        } else {
+            advance_clips(&mut self.active, &self.clips, dt);
+            prune_clips(&mut self.active);
+        }
+
+        for layer in &mut self.layers {
+            advance_clips(&mut layer.active, &self.clips, dt);
+            prune_clips(&mut layer.active);
+        }
+
+        let layers_playing = self.layers.iter().any(|layer| !layer.active.is_empty());
+        if self.active.is_empty() && !layers_playing {
             return;
         }
 
         self.evaluate_and_apply_layers();
+        self.collect_events();
+        self.dispatch_events(world);
     }
 }
 
@@ -249,7 +563,141 @@ impl AnimationComponent {
         self.clips = clips;
         self.clip_indices = clip_indices;
         self.resolve_bindings();
-        self.layers.clear();
+        self.active.clear();
+        for layer in &mut self.layers {
+            layer.active.clear();
+        }
+        self.graph_state.reset();
+    }
+
+    /// Hands playback over to an animation graph. While the graph has states, it replaces
+    /// whatever was started through the `play_*` and `crossfade_*` functions.
+    pub fn set_graph(&mut self, graph: AnimationGraph) {
+        self.graph = graph;
+        self.graph_state.reset();
+        self.active.clear();
+    }
+
+    pub fn clear_graph(&mut self) {
+        self.set_graph(AnimationGraph::default());
+    }
+
+    pub fn graph(&self) -> &AnimationGraph {
+        &self.graph
+    }
+
+    pub fn graph_mut(&mut self) -> &mut AnimationGraph {
+        &mut self.graph
+    }
+
+    /// Name of the graph state that is currently playing.
+    pub fn current_state(&self) -> Option<&str> {
+        let index = self.graph_state.current_state()?;
+        self.graph.states.get(index).map(|s| s.name.as_str())
+    }
+
+    pub fn set_float(&mut self, parameter: &str, value: f32) -> bool {
+        self.graph.set_float(parameter, value)
+    }
+
+    pub fn set_bool(&mut self, parameter: &str, value: bool) -> bool {
+        self.graph.set_bool(parameter, value)
+    }
+
+    pub fn set_trigger(&mut self, parameter: &str) -> bool {
+        self.graph.set_trigger(parameter)
+    }
+
+    /// Adds a layer that is blended over the base playback and all layers added before it.
+    /// Returns the index the layer is addressed by.
+    pub fn add_layer(
+        &mut self,
+        name: impl Into<String>,
+        mode: LayerBlendMode,
+        mask: Option<BoneMask>,
+    ) -> usize {
+        let mask_targets = mask.as_ref().map(|m| resolve_mask(m, self.parent()));
+        self.layers.push(AnimationLayer {
+            name: name.into(),
+            mode,
+            weight: 1.0,
+            mask,
+            mask_targets,
+            active: Vec::new(),
+        });
+        self.layers.len() - 1
+    }
+
+    pub fn remove_layer(&mut self, layer: usize) {
+        if layer < self.layers.len() {
+            self.layers.remove(layer);
+        }
+    }
+
+    pub fn layers(&self) -> &[AnimationLayer] {
+        &self.layers
+    }
+
+    pub fn layer_index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|l| l.name == name)
+    }
+
+    pub fn set_layer_weight(&mut self, layer: usize, weight: f32) {
+        if let Some(layer) = self.layers.get_mut(layer) {
+            layer.weight = weight.clamp(0.0, 1.0);
+        }
+    }
+
+    pub fn set_layer_mask(&mut self, layer: usize, mask: Option<BoneMask>) {
+        let parent = self.parent();
+        if let Some(layer) = self.layers.get_mut(layer) {
+            layer.mask_targets = mask.as_ref().map(|m| resolve_mask(m, parent));
+            layer.mask = mask;
+        }
+    }
+
+    pub fn play_on_layer(&mut self, layer: usize, name: &str, looping: bool, speed: f32) -> bool {
+        self.crossfade_on_layer(layer, name, 0.0, looping, speed)
+    }
+
+    pub fn crossfade_on_layer(
+        &mut self,
+        layer: usize,
+        name: &str,
+        duration: f32,
+        looping: bool,
+        speed: f32,
+    ) -> bool {
+        let Some(index) = self.find_clip_index_by_name(name) else {
+            warn!("No clip \"{name}\" found in {}", self.parent().name);
+            return false;
+        };
+        let Some(layer) = self.layers.get_mut(layer) else {
+            warn!(
+                "No animation layer #{layer} found in {}",
+                self.parent().name
+            );
+            return false;
+        };
+
+        crossfade_into(&mut layer.active, index, duration, looping, speed, 1.0);
+        true
+    }
+
+    /// Fades out everything playing on the layer.
+    pub fn stop_layer(&mut self, layer: usize, duration: f32) {
+        let Some(layer) = self.layers.get_mut(layer) else {
+            return;
+        };
+
+        if duration <= 0.0 {
+            layer.active.clear();
+            return;
+        }
+
+        for playing in &mut layer.active {
+            playing.set_fade_target(0.0, duration);
+        }
     }
 
     pub fn resolve_bindings(&mut self) {
@@ -316,6 +764,11 @@ impl AnimationComponent {
             }
             self.bindings.push(binds);
         }
+
+        let parent = self.parent();
+        for layer in &mut self.layers {
+            layer.mask_targets = layer.mask.as_ref().map(|m| resolve_mask(m, parent));
+        }
     }
 
     pub fn play_by_name(&mut self, name: &str, looping: bool, speed: f32, weight: f32) {
@@ -333,7 +786,7 @@ impl AnimationComponent {
 
     pub fn play_indices(&mut self, indices: &[usize], looping: bool, speed: f32, weight: f32) {
         let target_weight = weight.clamp(0.0, 1.0);
-        self.layers.clear();
+        self.active.clear();
         if target_weight <= 0.0 {
             return;
         }
@@ -348,7 +801,7 @@ impl AnimationComponent {
                 continue;
             }
 
-            self.layers.push(ActiveLayer::new_immediate(
+            self.active.push(ActiveClip::new_immediate(
                 index,
                 looping,
                 speed,
@@ -387,32 +840,14 @@ impl AnimationComponent {
             return false;
         }
 
-        let target_weight = target_weight.clamp(0.0, 1.0);
-        if duration <= 0.0 {
-            self.layers.clear();
-            if target_weight > 0.0 {
-                self.layers.push(ActiveLayer::new_immediate(
-                    index,
-                    looping,
-                    speed,
-                    target_weight,
-                    target_weight,
-                ));
-            }
-            return true;
-        }
-
-        for layer in &mut self.layers {
-            layer.set_fade_target(0.0, duration);
-        }
-
-        if target_weight > 0.0 {
-            let mut incoming = ActiveLayer::new_immediate(index, looping, speed, 0.0, 0.0);
-            incoming.set_fade_target(target_weight, duration);
-            self.layers.push(incoming);
-        }
-
-        self.prune_layers();
+        crossfade_into(
+            &mut self.active,
+            index,
+            duration,
+            looping,
+            speed,
+            target_weight,
+        );
         true
     }
 
@@ -452,30 +887,14 @@ impl AnimationComponent {
         self.clips.iter().position(|c| c.name == name)
     }
 
-    fn advance_layers(&mut self, dt: f32) {
-        for layer in &mut self.layers {
-            layer.time += dt * layer.speed;
-            let clip = &self.clips[layer.clip_index];
-            if clip.duration > 0.0 {
-                if layer.looping {
-                    layer.time = layer.time.rem_euclid(clip.duration);
-                } else if layer.time > clip.duration {
-                    layer.time = clip.duration;
-                } else if layer.time < 0.0 {
-                    layer.time = 0.0;
-                }
-            } else {
-                layer.time = 0.0;
-            }
+    fn advance_graph(&mut self, dt: f32) {
+        self.graph_state.advance(&mut self.graph, &self.clips, dt);
+        self.graph_state
+            .samples(&self.graph, &self.clips, &mut self.graph_samples);
 
-            layer.step_weight(dt);
-        }
-    }
-
-    fn prune_layers(&mut self) {
-        self.layers.retain(|layer| {
-            layer.weight > LAYER_REMOVE_EPSILON || layer.target_weight > LAYER_REMOVE_EPSILON
-        });
+        self.active.clear();
+        self.active
+            .extend(self.graph_samples.iter().map(ActiveClip::from_sample));
     }
 
     fn ensure_pose_accumulator(
@@ -495,116 +914,168 @@ impl AnimationComponent {
         }
     }
 
-    fn evaluate_and_apply_layers(&mut self) {
-        let mut skel_locals: HashMap<GameObjectId, Vec<PoseAccumulator>> = HashMap::new();
-        let mut transform_locals: HashMap<GameObjectId, PoseAccumulator> = HashMap::new();
-
-        for layer in self
-            .layers
+    /// Samples all clips of one layer into `out`. Additive layers accumulate the difference
+    /// to the first frame of each clip instead of the sampled pose.
+    fn accumulate(
+        &self,
+        active: &[ActiveClip],
+        mask: Option<&HashSet<String>>,
+        additive: bool,
+        out: &mut LayerPose,
+    ) {
+        for playing in active
             .iter()
-            .filter(|layer| layer.weight > LAYER_REMOVE_EPSILON)
+            .filter(|playing| playing.weight > LAYER_REMOVE_EPSILON)
         {
-            let clip = &self.clips[layer.clip_index];
-            let binds = &self.bindings[layer.clip_index];
-            let weight = layer.weight;
-            let time = layer.time;
+            let clip = &self.clips[playing.clip_index];
+            let binds = &self.bindings[playing.clip_index];
+            let weight = playing.weight;
+            let time = playing.time;
 
             for b in binds {
                 let ch = &clip.channels[b.ch_index];
-                let (t, r, s) = ch.keys.sample(time);
+                if mask.is_some_and(|mask| !mask.contains(&ch.target_name)) {
+                    continue;
+                }
+
+                let (mut t, mut r, mut s) = ch.keys.sample(time);
+                if additive {
+                    let (t0, r0, s0) = ch.keys.sample(0.0);
+                    t = t.zip(t0).map(|(t, t0)| t - t0);
+                    r = r.zip(r0).map(|(r, r0)| r0.inverse() * r);
+                    s = s
+                        .zip(s0)
+                        .map(|(s, s0)| s / s0.max(Vec3::splat(f32::EPSILON)));
+                }
 
-                match b.target {
+                if let Binding::Transform(go) = b.target
+                    && let Some(mut w) = ch.keys.sample_weights(time)
+                {
+                    if additive && let Some(w0) = ch.keys.sample_weights(0.0) {
+                        for (w, w0) in w.iter_mut().zip(w0) {
+                            *w -= w0;
+                        }
+                    }
+                    out.morph_weights.entry(go).or_default().add(&w, weight);
+                }
+
+                let pose = match b.target {
                     Binding::Transform(go) => {
                         if !go.exists() {
                             warn!("Animation game object was not found");
                             continue;
                         }
 
-                        let pose = transform_locals.entry(go).or_default();
-                        if let Some(t) = t {
-                            pose.translation.add(t, weight);
-                        }
-                        if let Some(r) = r {
-                            pose.rotation.add(r, weight);
-                        }
-                        if let Some(s) = s {
-                            pose.scale.add(s, weight);
-                        }
+                        out.transforms.entry(go).or_default()
                     }
                     Binding::Bone { skel, idx } => {
-                        if let Some(locals) = Self::ensure_pose_accumulator(skel, &mut skel_locals)
-                        {
-                            if idx >= locals.len() {
-                                warn!("Binding bone index {idx} is out of range");
-                                continue;
-                            }
-
-                            let pose = &mut locals[idx];
-                            if let Some(t) = t {
-                                pose.translation.add(t, weight);
-                            }
-                            if let Some(r) = r {
-                                pose.rotation.add(r, weight);
-                            }
-                            if let Some(s) = s {
-                                pose.scale.add(s, weight);
-                            }
-                        } else {
+                        let Some(locals) = Self::ensure_pose_accumulator(skel, &mut out.skeletons)
+                        else {
                             warn!("Binding bone not found");
-                        }
+                            continue;
+                        };
+                        let Some(pose) = locals.get_mut(idx) else {
+                            warn!("Binding bone index {idx} is out of range");
+                            continue;
+                        };
+                        pose
                     }
+                };
+
+                if let Some(t) = t {
+                    pose.translation.add(t, weight);
+                }
+                if let Some(r) = r {
+                    pose.rotation.add(r, weight);
+                }
+                if let Some(s) = s {
+                    pose.scale.add(s, weight);
                 }
             }
         }
+    }
 
-        for (mut go, pose) in transform_locals {
-            if !go.exists() {
-                warn!("Animation game object was not found");
+    fn evaluate_and_apply_layers(&mut self) {
+        let mut pose = TargetPoses::default();
+
+        let mut base = LayerPose::default();
+        self.accumulate(&self.active, None, false, &mut base);
+        pose.blend(&base, 1.0, LayerBlendMode::Override);
+
+        for layer in &self.layers {
+            if layer.weight <= LAYER_REMOVE_EPSILON || layer.active.is_empty() {
                 continue;
             }
 
-            let tr = &mut go.transform;
-            if let Some(t) = pose.translation.mixed() {
-                tr.set_local_position_vec(
-                    tr.local_position().lerp(t, pose.translation.blend_weight()),
-                );
-            }
-            if let Some(r) = pose.rotation.mixed() {
-                tr.set_local_rotation(tr.local_rotation().slerp(r, pose.rotation.blend_weight()));
-            }
-            if let Some(s) = pose.scale.mixed() {
-                tr.set_nonuniform_local_scale(tr.local_scale().lerp(s, pose.scale.blend_weight()));
-            }
+            let additive = layer.mode == LayerBlendMode::Additive;
+            let mut layer_pose = LayerPose::default();
+            self.accumulate(
+                &layer.active,
+                layer.mask_targets.as_ref(),
+                additive,
+                &mut layer_pose,
+            );
+            pose.blend(&layer_pose, layer.weight, layer.mode);
         }
 
-        for (skel_go, accum) in skel_locals {
-            let Some(mut skel) = skel_go.get_component::<SkeletalComponent>() else {
-                warn!("Skeleton not found on supposed Bone Channel Binding");
+        pose.apply();
+    }
+
+    /// Gathers the events that playback crossed during the last advance, base layer first.
+    fn collect_events(&mut self) {
+        self.fired_events.clear();
+
+        let base = self.active.iter().map(|playing| (playing, 1.0));
+        let layers = self.layers.iter().flat_map(|layer| {
+            layer
+                .active
+                .iter()
+                .map(move |playing| (playing, layer.weight))
+        });
+
+        for (playing, layer_weight) in base.chain(layers) {
+            if playing.weight * layer_weight < EVENT_MIN_WEIGHT {
                 continue;
+            }
+
+            let clip = &self.clips[playing.clip_index];
+            let start = if playing.looping && clip.duration > 0.0 {
+                (playing.time - playing.delta).rem_euclid(clip.duration)
+            } else {
+                playing.time - playing.delta
             };
+            clip.events_crossed(
+                start,
+                playing.delta,
+                playing.looping,
+                playing.from_start,
+                |event| self.fired_events.push(event.clone()),
+            );
+        }
+    }
 
-            let bones = skel.bones();
-            let mut locals: Vec<SkeletonLocals> = Vec::with_capacity(bones.len());
-            for (i, bind_local) in bones.bind_local.iter().enumerate() {
-                let (bind_scale, bind_rotation, bind_translation) =
-                    bind_local.to_scale_rotation_translation();
-                let pose = accum.get(i).copied().unwrap_or_default();
-
-                let translation = pose.translation.mixed().map_or(bind_translation, |t| {
-                    bind_translation.lerp(t, pose.translation.blend_weight())
-                });
-                let rotation = pose.rotation.mixed().map_or(bind_rotation, |r| {
-                    bind_rotation.slerp(r, pose.rotation.blend_weight())
-                });
-                let scale = pose.scale.mixed().map_or(bind_scale, |s| {
-                    bind_scale.lerp(s, pose.scale.blend_weight())
-                });
-
-                locals.push((translation, rotation, scale));
-            }
+    /// Hands the collected events to all other components on this game object.
+    fn dispatch_events(&mut self, world: &mut World) {
+        if self.fired_events.is_empty() {
+            return;
+        }
 
-            skel.set_local_pose_trs(&locals);
+        let events = std::mem::take(&mut self.fired_events);
+        let this = self as *const Self;
+        let siblings: Vec<_> = self
+            .parent()
+            .iter_dyn_components()
+            .filter(|comp| !std::ptr::addr_eq(&***comp as *const dyn Component, this))
+            .cloned()
+            .collect();
+
+        for event in &events {
+            for comp in &siblings {
+                comp.get_mut().on_animation_event(world, event);
+            }
         }
+
+        self.fired_events = events;
     }
 
     pub fn clips(&self) -> &[AnimationClip] {
@@ -618,3 +1089,107 @@ fn collect_subtree_by_name(root: GameObjectId, out: &mut HashMap<String, Vec<Gam
         collect_subtree_by_name(child, out);
     }
 }
+
+fn advance_clips(active: &mut [ActiveClip], clips: &[AnimationClip], dt: f32) {
+    for playing in active {
+        let previous = playing.time;
+        let delta = dt * playing.speed;
+        playing.time += delta;
+        let clip = &clips[playing.clip_index];
+        if clip.duration > 0.0 {
+            if playing.looping {
+                playing.time = playing.time.rem_euclid(clip.duration);
+            } else if playing.time > clip.duration {
+                playing.time = clip.duration;
+            } else if playing.time < 0.0 {
+                playing.time = 0.0;
+            }
+        } else {
+            playing.time = 0.0;
+        }
+
+        playing.delta = if playing.looping {
+            delta
+        } else {
+            playing.time - previous
+        };
+        playing.from_start = !playing.started;
+        playing.started = true;
+
+        playing.step_weight(dt);
+    }
+}
+
+fn prune_clips(active: &mut Vec<ActiveClip>) {
+    active.retain(|playing| {
+        playing.weight > LAYER_REMOVE_EPSILON || playing.target_weight > LAYER_REMOVE_EPSILON
+    });
+}
+
+fn crossfade_into(
+    active: &mut Vec<ActiveClip>,
+    index: usize,
+    duration: f32,
+    looping: bool,
+    speed: f32,
+    target_weight: f32,
+) {
+    let target_weight = target_weight.clamp(0.0, 1.0);
+    if duration <= 0.0 {
+        active.clear();
+        if target_weight > 0.0 {
+            active.push(ActiveClip::new_immediate(
+                index,
+                looping,
+                speed,
+                target_weight,
+                target_weight,
+            ));
+        }
+        return;
+    }
+
+    for playing in active.iter_mut() {
+        playing.set_fade_target(0.0, duration);
+    }
+
+    if target_weight > 0.0 {
+        let mut incoming = ActiveClip::new_immediate(index, looping, speed, 0.0, 0.0);
+        incoming.set_fade_target(target_weight, duration);
+        active.push(incoming);
+    }
+
+    prune_clips(active);
+}
+
+/// Collects the names of all bones and game objects below `root` that are part of the mask.
+fn resolve_mask(mask: &BoneMask, root: GameObjectId) -> HashSet<String> {
+    let mut targets: HashSet<String> = mask.bones.iter().cloned().collect();
+
+    let mut stack = vec![(root, false)];
+    while let Some((go, parent_inside)) = stack.pop() {
+        let inside = parent_inside || mask.subtrees.contains(&go.name);
+        if inside {
+            targets.insert(go.name.clone());
+        }
+
+        if let Some(skel) = go.get_component::<SkeletalComponent>() {
+            let bones = skel.bones();
+            for subtree in &mask.subtrees {
+                let Some(start) = bones.index(subtree) else {
+                    continue;
+                };
+
+                let mut pending = vec![start];
+                while let Some(i) = pending.pop() {
+                    targets.insert(bones.names[i].clone());
+                    pending.extend(bones.children[i].iter().copied());
+                }
+            }
+        }
+
+        stack.extend(go.children().iter().map(|&child| (child, inside)));
+    }
+
+    targets
+}
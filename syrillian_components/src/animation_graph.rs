@@ -0,0 +1,942 @@
+//! Data-driven animation state machines, played back by an
+//! [`AnimationComponent`](crate::AnimationComponent).
+//!
+//! A graph is made of states that either play a single clip or blend several clips in a 1D or
+//! 2D blend space, and transitions between them which fire on conditions over typed parameters.
+//!
+//! ```rust
+//! use syrillian_components::animation_graph::*;
+//!
+//! let graph = AnimationGraph::new("Locomotion")
+//!     .float("speed", 0.0)
+//!     .trigger("jump")
+//!     .state(AnimationState::blend_1d(
+//!         "Locomotion",
+//!         "speed",
+//!         [("Idle", 0.0), ("Walk", 2.0), ("Run", 5.5)],
+//!     ))
+//!     .state(AnimationState::clip("Jump", "Jump").looping(false))
+//!     .transition(
+//!         AnimationTransition::new("Locomotion", "Jump")
+//!             .when(TransitionCondition::triggered("jump"))
+//!             .duration(0.1),
+//!     )
+//!     .transition(AnimationTransition::new("Jump", "Locomotion").exit_time(0.9));
+//! ```
+
+use std::collections::BTreeMap;
+use syrillian::Reflect;
+use syrillian::assets::AnimationClip;
+use syrillian::core::reflection::{ReflectDeserialize, ReflectSerialize, Value};
+use syrillian::math::Vec2;
+
+const BLEND_EPSILON: f32 = 1e-4;
+
+/// Current value of a graph parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterValue {
+    Float(f32),
+    Bool(bool),
+    /// Stays set until a transition that tests it fires.
+    Trigger(bool),
+}
+
+impl Default for ParameterValue {
+    fn default() -> Self {
+        ParameterValue::Float(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect_all]
+#[reflect(default)]
+pub struct AnimationParameter {
+    pub name: String,
+    pub value: ParameterValue,
+}
+
+/// A clip placed in a blend space. 1D blend spaces only use `position.x`.
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect_all]
+#[reflect(default)]
+pub struct BlendPoint {
+    pub clip: String,
+    pub position: Vec2,
+}
+
+/// What a state plays. Clips are referenced by name.
+#[derive(Debug, Clone)]
+pub enum Motion {
+    Clip(String),
+    /// Blends linearly between the two points closest to the parameter on each side.
+    BlendSpace1D {
+        parameter: String,
+        points: Vec<BlendPoint>,
+    },
+    /// Blends all points by inverse squared distance to the `(x, y)` parameters.
+    BlendSpace2D {
+        parameters: [String; 2],
+        points: Vec<BlendPoint>,
+    },
+}
+
+impl Default for Motion {
+    fn default() -> Self {
+        Motion::Clip(String::new())
+    }
+}
+
+#[derive(Debug, Clone, Reflect)]
+#[reflect_all]
+#[reflect(default)]
+pub struct AnimationState {
+    pub name: String,
+    pub motion: Motion,
+    pub speed: f32,
+    pub looping: bool,
+}
+
+impl Default for AnimationState {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            motion: Motion::default(),
+            speed: 1.0,
+            looping: true,
+        }
+    }
+}
+
+impl AnimationState {
+    pub fn new(name: impl Into<String>, motion: Motion) -> Self {
+        Self {
+            name: name.into(),
+            motion,
+            ..Self::default()
+        }
+    }
+
+    pub fn clip(name: impl Into<String>, clip: impl Into<String>) -> Self {
+        Self::new(name, Motion::Clip(clip.into()))
+    }
+
+    pub fn blend_1d<S: Into<String>>(
+        name: impl Into<String>,
+        parameter: impl Into<String>,
+        points: impl IntoIterator<Item = (S, f32)>,
+    ) -> Self {
+        let points = points
+            .into_iter()
+            .map(|(clip, x)| BlendPoint {
+                clip: clip.into(),
+                position: Vec2::new(x, 0.0),
+            })
+            .collect();
+
+        Self::new(
+            name,
+            Motion::BlendSpace1D {
+                parameter: parameter.into(),
+                points,
+            },
+        )
+    }
+
+    pub fn blend_2d<S: Into<String>>(
+        name: impl Into<String>,
+        parameters: [&str; 2],
+        points: impl IntoIterator<Item = (S, Vec2)>,
+    ) -> Self {
+        let points = points
+            .into_iter()
+            .map(|(clip, position)| BlendPoint {
+                clip: clip.into(),
+                position,
+            })
+            .collect();
+
+        Self::new(
+            name,
+            Motion::BlendSpace2D {
+                parameters: parameters.map(str::to_string),
+                points,
+            },
+        )
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConditionTest {
+    Greater(f32),
+    Less(f32),
+    #[default]
+    IsTrue,
+    IsFalse,
+    Triggered,
+}
+
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect_all]
+#[reflect(default)]
+pub struct TransitionCondition {
+    pub parameter: String,
+    pub test: ConditionTest,
+}
+
+impl TransitionCondition {
+    pub fn new(parameter: impl Into<String>, test: ConditionTest) -> Self {
+        Self {
+            parameter: parameter.into(),
+            test,
+        }
+    }
+
+    pub fn greater(parameter: impl Into<String>, value: f32) -> Self {
+        Self::new(parameter, ConditionTest::Greater(value))
+    }
+
+    pub fn less(parameter: impl Into<String>, value: f32) -> Self {
+        Self::new(parameter, ConditionTest::Less(value))
+    }
+
+    pub fn is_true(parameter: impl Into<String>) -> Self {
+        Self::new(parameter, ConditionTest::IsTrue)
+    }
+
+    pub fn is_false(parameter: impl Into<String>) -> Self {
+        Self::new(parameter, ConditionTest::IsFalse)
+    }
+
+    pub fn triggered(parameter: impl Into<String>) -> Self {
+        Self::new(parameter, ConditionTest::Triggered)
+    }
+}
+
+#[derive(Debug, Clone, Reflect)]
+#[reflect_all]
+#[reflect(default)]
+pub struct AnimationTransition {
+    /// Name of the source state. Empty matches every state except `to`.
+    pub from: String,
+    pub to: String,
+    /// All conditions have to pass for the transition to fire.
+    pub conditions: Vec<TransitionCondition>,
+    pub has_exit_time: bool,
+    /// Normalized time the source state has to have played before the transition can fire.
+    pub exit_time: f32,
+    /// Crossfade duration in seconds.
+    pub duration: f32,
+}
+
+impl Default for AnimationTransition {
+    fn default() -> Self {
+        Self {
+            from: String::new(),
+            to: String::new(),
+            conditions: Vec::new(),
+            has_exit_time: false,
+            exit_time: 1.0,
+            duration: 0.2,
+        }
+    }
+}
+
+impl AnimationTransition {
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            ..Self::default()
+        }
+    }
+
+    /// A transition that can fire from any state.
+    pub fn from_any(to: impl Into<String>) -> Self {
+        Self::new(String::new(), to)
+    }
+
+    pub fn when(mut self, condition: TransitionCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn exit_time(mut self, normalized_time: f32) -> Self {
+        self.has_exit_time = true;
+        self.exit_time = normalized_time;
+        self
+    }
+
+    pub fn duration(mut self, seconds: f32) -> Self {
+        self.duration = seconds.max(0.0);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, Reflect)]
+#[reflect_all]
+#[reflect(default)]
+pub struct AnimationGraph {
+    /// Name of the state the graph starts in. Falls back to the first state.
+    pub entry: String,
+    pub parameters: Vec<AnimationParameter>,
+    pub states: Vec<AnimationState>,
+    /// Checked in order, the first one that passes fires.
+    pub transitions: Vec<AnimationTransition>,
+}
+
+impl AnimationGraph {
+    pub fn new(entry: impl Into<String>) -> Self {
+        Self {
+            entry: entry.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn float(self, name: impl Into<String>, value: f32) -> Self {
+        self.parameter(name, ParameterValue::Float(value))
+    }
+
+    pub fn bool(self, name: impl Into<String>, value: bool) -> Self {
+        self.parameter(name, ParameterValue::Bool(value))
+    }
+
+    pub fn trigger(self, name: impl Into<String>) -> Self {
+        self.parameter(name, ParameterValue::Trigger(false))
+    }
+
+    pub fn parameter(mut self, name: impl Into<String>, value: ParameterValue) -> Self {
+        self.parameters.push(AnimationParameter {
+            name: name.into(),
+            value,
+        });
+        self
+    }
+
+    pub fn state(mut self, state: AnimationState) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    pub fn transition(mut self, transition: AnimationTransition) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn state_index(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|s| s.name == name)
+    }
+
+    pub fn value(&self, name: &str) -> Option<ParameterValue> {
+        self.parameters
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.value)
+    }
+
+    pub fn get_float(&self, name: &str) -> Option<f32> {
+        match self.value(name)? {
+            ParameterValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.value(name)? {
+            ParameterValue::Bool(value) | ParameterValue::Trigger(value) => Some(value),
+            ParameterValue::Float(_) => None,
+        }
+    }
+
+    /// Sets a float parameter. Returns false if there is no float parameter with that name.
+    pub fn set_float(&mut self, name: &str, value: f32) -> bool {
+        match self.value_mut(name) {
+            Some(ParameterValue::Float(target)) => {
+                *target = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sets a bool parameter. Returns false if there is no bool parameter with that name.
+    pub fn set_bool(&mut self, name: &str, value: bool) -> bool {
+        match self.value_mut(name) {
+            Some(ParameterValue::Bool(target)) => {
+                *target = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sets a trigger, which stays set until a transition testing it fires.
+    /// Returns false if there is no trigger with that name.
+    pub fn set_trigger(&mut self, name: &str) -> bool {
+        self.set_trigger_value(name, true)
+    }
+
+    pub fn reset_trigger(&mut self, name: &str) -> bool {
+        self.set_trigger_value(name, false)
+    }
+
+    fn set_trigger_value(&mut self, name: &str, value: bool) -> bool {
+        match self.value_mut(name) {
+            Some(ParameterValue::Trigger(target)) => {
+                *target = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn value_mut(&mut self, name: &str) -> Option<&mut ParameterValue> {
+        self.parameters
+            .iter_mut()
+            .find(|p| p.name == name)
+            .map(|p| &mut p.value)
+    }
+
+    fn passes(&self, condition: &TransitionCondition) -> bool {
+        let Some(value) = self.value(&condition.parameter) else {
+            return false;
+        };
+
+        match (condition.test, value) {
+            (ConditionTest::Greater(threshold), ParameterValue::Float(v)) => v > threshold,
+            (ConditionTest::Less(threshold), ParameterValue::Float(v)) => v < threshold,
+            (ConditionTest::IsTrue, ParameterValue::Bool(v)) => v,
+            (ConditionTest::IsFalse, ParameterValue::Bool(v)) => !v,
+            (ConditionTest::Triggered, ParameterValue::Trigger(v)) => v,
+            _ => false,
+        }
+    }
+
+    /// Appends the clip indices and normalized weights a motion resolves to right now.
+    fn motion_weights(
+        &self,
+        motion: &Motion,
+        clips: &[AnimationClip],
+        out: &mut Vec<(usize, f32)>,
+    ) {
+        let find_clip = |name: &str| clips.iter().position(|c| c.name == name);
+
+        match motion {
+            Motion::Clip(clip) => {
+                if let Some(index) = find_clip(clip) {
+                    out.push((index, 1.0));
+                }
+            }
+            Motion::BlendSpace1D { parameter, points } => {
+                let x = self.get_float(parameter).unwrap_or_default();
+                let mut resolved: Vec<(usize, f32)> = points
+                    .iter()
+                    .filter_map(|p| Some((find_clip(&p.clip)?, p.position.x)))
+                    .collect();
+                resolved.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+                let (Some(first), Some(last)) = (resolved.first(), resolved.last()) else {
+                    return;
+                };
+
+                if x <= first.1 {
+                    out.push((first.0, 1.0));
+                } else if x >= last.1 {
+                    out.push((last.0, 1.0));
+                } else if let Some(pair) = resolved.windows(2).find(|w| x <= w[1].1) {
+                    let (a, b) = (pair[0], pair[1]);
+                    let t = (x - a.1) / (b.1 - a.1).max(BLEND_EPSILON);
+                    out.push((a.0, 1.0 - t));
+                    out.push((b.0, t));
+                }
+            }
+            Motion::BlendSpace2D { parameters, points } => {
+                let sample = Vec2::new(
+                    self.get_float(&parameters[0]).unwrap_or_default(),
+                    self.get_float(&parameters[1]).unwrap_or_default(),
+                );
+
+                let start = out.len();
+                let mut total = 0.0;
+                for point in points {
+                    let Some(index) = find_clip(&point.clip) else {
+                        continue;
+                    };
+
+                    let distance = point.position.distance_squared(sample);
+                    if distance < BLEND_EPSILON {
+                        out.truncate(start);
+                        out.push((index, 1.0));
+                        return;
+                    }
+
+                    let weight = 1.0 / distance;
+                    total += weight;
+                    out.push((index, weight));
+                }
+
+                for (_, weight) in &mut out[start..] {
+                    *weight /= total;
+                }
+            }
+        }
+    }
+}
+
+/// One clip to sample, as produced by [`AnimationGraphState::samples`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphSample {
+    pub clip_index: usize,
+    pub time: f32,
+    pub weight: f32,
+    /// Clip seconds covered by the last advance, negative when playing backwards.
+    pub delta: f32,
+    pub looping: bool,
+    /// The last advance started at the very beginning of the state.
+    pub from_start: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct StatePlayback {
+    state: usize,
+    /// Normalized time. Keeps growing past 1 while looping.
+    time: f32,
+    /// Normalized time covered by the last advance.
+    delta: f32,
+    started: bool,
+    from_start: bool,
+}
+
+impl StatePlayback {
+    fn new(state: usize) -> Self {
+        Self {
+            state,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Crossfade {
+    from: StatePlayback,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// Runtime playback of an [`AnimationGraph`].
+#[derive(Debug, Clone, Default)]
+pub struct AnimationGraphState {
+    current: Option<StatePlayback>,
+    crossfade: Option<Crossfade>,
+    weights: Vec<(usize, f32)>,
+}
+
+impl AnimationGraphState {
+    pub fn reset(&mut self) {
+        self.current = None;
+        self.crossfade = None;
+    }
+
+    pub fn current_state(&self) -> Option<usize> {
+        self.current.map(|c| c.state)
+    }
+
+    /// Normalized time of the current state. Keeps growing past 1 while looping.
+    pub fn normalized_time(&self) -> f32 {
+        self.current.map_or(0.0, |c| c.time)
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.crossfade.is_some()
+    }
+
+    /// Advances playback by `dt` seconds, then fires the first passing transition.
+    /// Transitions are not checked while a crossfade is still running.
+    pub fn advance(&mut self, graph: &mut AnimationGraph, clips: &[AnimationClip], dt: f32) {
+        let valid = self.current.is_some_and(|c| c.state < graph.states.len());
+        if !valid {
+            self.crossfade = None;
+            self.current = graph
+                .state_index(&graph.entry)
+                .or((!graph.states.is_empty()).then_some(0))
+                .map(StatePlayback::new);
+        }
+
+        let Some(mut current) = self.current else {
+            return;
+        };
+
+        self.advance_playback(&mut current, graph, clips, dt);
+
+        if let Some(mut fade) = self.crossfade {
+            self.advance_playback(&mut fade.from, graph, clips, dt);
+            fade.elapsed += dt;
+            self.crossfade = (fade.elapsed < fade.duration && fade.from.state < graph.states.len())
+                .then_some(fade);
+        }
+
+        if self.crossfade.is_none()
+            && let Some((index, target)) = find_transition(graph, current)
+        {
+            let transition = &graph.transitions[index];
+            if transition.duration > 0.0 {
+                self.crossfade = Some(Crossfade {
+                    from: current,
+                    elapsed: 0.0,
+                    duration: transition.duration,
+                });
+            }
+
+            consume_triggers(graph, index);
+            current = StatePlayback::new(target);
+        }
+
+        self.current = Some(current);
+    }
+
+    /// Collects the clips that make up the current pose into `out`.
+    pub fn samples(
+        &mut self,
+        graph: &AnimationGraph,
+        clips: &[AnimationClip],
+        out: &mut Vec<GraphSample>,
+    ) {
+        out.clear();
+
+        let Some(current) = self.current else {
+            return;
+        };
+
+        let blend = self
+            .crossfade
+            .map_or(1.0, |fade| (fade.elapsed / fade.duration).clamp(0.0, 1.0));
+
+        self.push_samples(current, blend, graph, clips, out);
+        if let Some(fade) = self.crossfade {
+            self.push_samples(fade.from, 1.0 - blend, graph, clips, out);
+        }
+    }
+
+    fn advance_playback(
+        &mut self,
+        playback: &mut StatePlayback,
+        graph: &AnimationGraph,
+        clips: &[AnimationClip],
+        dt: f32,
+    ) {
+        let Some(state) = graph.states.get(playback.state) else {
+            return;
+        };
+
+        playback.from_start = !playback.started;
+        playback.started = true;
+        playback.delta = 0.0;
+
+        self.weights.clear();
+        graph.motion_weights(&state.motion, clips, &mut self.weights);
+
+        // blended clips stay in sync by sharing one normalized time over their weighted length
+        let duration: f32 = self
+            .weights
+            .iter()
+            .map(|&(clip, weight)| clips[clip].duration * weight)
+            .sum();
+        if duration <= 0.0 {
+            playback.time = 0.0;
+            return;
+        }
+
+        let previous = playback.time;
+        playback.time += dt * state.speed / duration;
+        if !state.looping {
+            playback.time = playback.time.clamp(0.0, 1.0);
+        }
+        playback.delta = playback.time - previous;
+        if state.looping && playback.time < 0.0 {
+            playback.time = playback.time.rem_euclid(1.0);
+        }
+    }
+
+    fn push_samples(
+        &mut self,
+        playback: StatePlayback,
+        weight: f32,
+        graph: &AnimationGraph,
+        clips: &[AnimationClip],
+        out: &mut Vec<GraphSample>,
+    ) {
+        let Some(state) = graph.states.get(playback.state) else {
+            return;
+        };
+
+        let phase = if state.looping {
+            playback.time.rem_euclid(1.0)
+        } else {
+            playback.time.clamp(0.0, 1.0)
+        };
+
+        self.weights.clear();
+        graph.motion_weights(&state.motion, clips, &mut self.weights);
+
+        for &(clip_index, clip_weight) in &self.weights {
+            let weight = clip_weight * weight;
+            if weight <= BLEND_EPSILON {
+                continue;
+            }
+
+            let duration = clips[clip_index].duration;
+            out.push(GraphSample {
+                clip_index,
+                time: phase * duration,
+                weight,
+                delta: playback.delta * duration,
+                looping: state.looping,
+                from_start: playback.from_start,
+            });
+        }
+    }
+}
+
+fn find_transition(graph: &AnimationGraph, current: StatePlayback) -> Option<(usize, usize)> {
+    graph
+        .transitions
+        .iter()
+        .enumerate()
+        .find_map(|(index, transition)| {
+            let target = graph.state_index(&transition.to)?;
+
+            let from_matches = if transition.from.is_empty() {
+                target != current.state
+            } else {
+                graph.state_index(&transition.from) == Some(current.state)
+            };
+            if !from_matches {
+                return None;
+            }
+
+            if transition.has_exit_time && current.time < transition.exit_time {
+                return None;
+            }
+
+            // transitions without exit time need at least one condition, or they'd fire every frame
+            if !transition.has_exit_time && transition.conditions.is_empty() {
+                return None;
+            }
+
+            transition
+                .conditions
+                .iter()
+                .all(|c| graph.passes(c))
+                .then_some((index, target))
+        })
+}
+
+fn consume_triggers(graph: &mut AnimationGraph, transition: usize) {
+    let triggers: Vec<String> = graph.transitions[transition]
+        .conditions
+        .iter()
+        .filter(|c| c.test == ConditionTest::Triggered)
+        .map(|c| c.parameter.clone())
+        .collect();
+
+    for trigger in triggers {
+        graph.reset_trigger(&trigger);
+    }
+}
+
+fn kind_of(value: &Value) -> Option<(&str, &BTreeMap<String, Value>)> {
+    let Value::Object(fields) = value else {
+        return None;
+    };
+    let Some(Value::String(kind)) = fields.get("kind") else {
+        return None;
+    };
+    Some((kind, fields))
+}
+
+fn field_or_default<T: ReflectDeserialize + Default>(
+    fields: &BTreeMap<String, Value>,
+    name: &str,
+) -> T {
+    let mut target = T::default();
+    if let Some(value) = fields.get(name) {
+        T::apply(&mut target, value);
+    }
+    target
+}
+
+impl ReflectSerialize for ParameterValue {
+    fn serialize(this: &Self) -> Value {
+        let (kind, value) = match *this {
+            ParameterValue::Float(v) => ("Float", Value::Float(v)),
+            ParameterValue::Bool(v) => ("Bool", Value::Bool(v)),
+            ParameterValue::Trigger(v) => ("Trigger", Value::Bool(v)),
+        };
+
+        Value::Object(BTreeMap::from([
+            ("kind".to_string(), Value::String(kind.to_string())),
+            ("value".to_string(), value),
+        ]))
+    }
+}
+
+impl ReflectDeserialize for ParameterValue {
+    fn apply(target: &mut Self, value: &Value) {
+        let Some((kind, fields)) = kind_of(value) else {
+            return;
+        };
+
+        *target = match kind {
+            "Float" => ParameterValue::Float(field_or_default(fields, "value")),
+            "Bool" => ParameterValue::Bool(field_or_default(fields, "value")),
+            "Trigger" => ParameterValue::Trigger(field_or_default(fields, "value")),
+            _ => return,
+        };
+    }
+}
+
+impl ReflectSerialize for Motion {
+    fn serialize(this: &Self) -> Value {
+        let mut fields = BTreeMap::new();
+        let kind = match this {
+            Motion::Clip(clip) => {
+                fields.insert("clip".to_string(), Value::String(clip.clone()));
+                "Clip"
+            }
+            Motion::BlendSpace1D { parameter, points } => {
+                fields.insert("parameter".to_string(), Value::String(parameter.clone()));
+                fields.insert("points".to_string(), ReflectSerialize::serialize(points));
+                "BlendSpace1D"
+            }
+            Motion::BlendSpace2D { parameters, points } => {
+                let parameters = parameters.iter().cloned().map(Value::String).collect();
+                fields.insert("parameters".to_string(), Value::Array(parameters));
+                fields.insert("points".to_string(), ReflectSerialize::serialize(points));
+                "BlendSpace2D"
+            }
+        };
+
+        fields.insert("kind".to_string(), Value::String(kind.to_string()));
+        Value::Object(fields)
+    }
+}
+
+impl ReflectDeserialize for Motion {
+    fn apply(target: &mut Self, value: &Value) {
+        let Some((kind, fields)) = kind_of(value) else {
+            return;
+        };
+
+        *target = match kind {
+            "Clip" => Motion::Clip(field_or_default(fields, "clip")),
+            "BlendSpace1D" => Motion::BlendSpace1D {
+                parameter: field_or_default(fields, "parameter"),
+                points: field_or_default(fields, "points"),
+            },
+            "BlendSpace2D" => {
+                let names: Vec<String> = field_or_default(fields, "parameters");
+                let mut parameters: [String; 2] = Default::default();
+                for (target, name) in parameters.iter_mut().zip(names) {
+                    *target = name;
+                }
+
+                Motion::BlendSpace2D {
+                    parameters,
+                    points: field_or_default(fields, "points"),
+                }
+            }
+            _ => return,
+        };
+    }
+}
+
+impl ReflectSerialize for ConditionTest {
+    fn serialize(this: &Self) -> Value {
+        let mut fields = BTreeMap::new();
+        let kind = match *this {
+            ConditionTest::Greater(v) => {
+                fields.insert("value".to_string(), Value::Float(v));
+                "Greater"
+            }
+            ConditionTest::Less(v) => {
+                fields.insert("value".to_string(), Value::Float(v));
+                "Less"
+            }
+            ConditionTest::IsTrue => "IsTrue",
+            ConditionTest::IsFalse => "IsFalse",
+            ConditionTest::Triggered => "Triggered",
+        };
+
+        fields.insert("kind".to_string(), Value::String(kind.to_string()));
+        Value::Object(fields)
+    }
+}
+
+impl ReflectDeserialize for ConditionTest {
+    fn apply(target: &mut Self, value: &Value) {
+        let Some((kind, fields)) = kind_of(value) else {
+            return;
+        };
+
+        *target = match kind {
+            "Greater" => ConditionTest::Greater(field_or_default(fields, "value")),
+            "Less" => ConditionTest::Less(field_or_default(fields, "value")),
+            "IsTrue" => ConditionTest::IsTrue,
+            "IsFalse" => ConditionTest::IsFalse,
+            "Triggered" => ConditionTest::Triggered,
+            _ => return,
+        };
+    }
+}
+
+syrillian::register_type!(syrillian::reflect_type_info!(
+    syrillian_components::animation_graph,
+    ParameterValue,
+    &[],
+    default
+));
+syrillian::register_type!(syrillian::reflect_type_info!(
+    syrillian_components::animation_graph,
+    Motion,
+    &[],
+    default
+));
+syrillian::register_type!(syrillian::reflect_type_info!(
+    syrillian_components::animation_graph,
+    ConditionTest,
+    &[],
+    default
+));
+syrillian::register_type!(syrillian::reflect_type_info!(
+    primitive,
+    Vec<AnimationParameter>,
+    default
+));
+syrillian::register_type!(syrillian::reflect_type_info!(
+    primitive,
+    Vec<AnimationState>,
+    default
+));
+syrillian::register_type!(syrillian::reflect_type_info!(
+    primitive,
+    Vec<AnimationTransition>,
+    default
+));
+syrillian::register_type!(syrillian::reflect_type_info!(
+    primitive,
+    Vec<TransitionCondition>,
+    default
+));
@@ -0,0 +1,189 @@
+use syrillian::assets::HMaterialInstance;
+use syrillian::components::Component;
+use syrillian::math::{Affine3A, Vec3};
+use syrillian::{Reflect, World};
+use syrillian_render::rendering::CPUDrawCtx;
+use syrillian_render::rendering::decals::{DecalProxy, MAX_DECAL_LAYERS};
+
+/// Projects a material onto the opaque surfaces inside a box around its game object, like
+/// bullet holes, blood splats or road markings.
+///
+/// The material is projected along the forward axis of the game object, with the right axis
+/// along the texture U. Only the textures and values of the default material are used.
+///
+/// Meshes are put on a layer with e.g. [`MeshRenderer::set_decal_layer`], and decals only
+/// project onto the layers in their mask.
+///
+/// [`MeshRenderer::set_decal_layer`]: crate::MeshRenderer::set_decal_layer
+#[derive(Debug, Reflect)]
+#[reflect(default)]
+pub struct Decal {
+    /// Size of the projected box, the depth is the distance the decal reaches along forward
+    #[reflect]
+    size: Vec3,
+    /// Angle between surface and projection in degrees, from where the decal fades out
+    #[reflect]
+    angle_fade_start: f32,
+    /// Angle between surface and projection in degrees, from where the decal is invisible
+    #[reflect]
+    angle_fade_end: f32,
+    /// Decals with a higher order are drawn over ones with a lower order
+    #[reflect]
+    sort_order: i32,
+    /// Bit mask of the decal layers the decal projects onto
+    #[reflect]
+    layer_mask: u32,
+    material: HMaterialInstance,
+
+    world_affine: Affine3A,
+    proxy: DecalProxy,
+    dirty: bool,
+}
+
+impl Default for Decal {
+    fn default() -> Self {
+        let proxy = DecalProxy::default();
+
+        Decal {
+            size: Vec3::ONE,
+            angle_fade_start: proxy.angle_fade_start.to_degrees(),
+            angle_fade_end: proxy.angle_fade_end.to_degrees(),
+            sort_order: proxy.sort_order,
+            layer_mask: proxy.layer_mask,
+            material: proxy.material,
+            world_affine: Affine3A::IDENTITY,
+            proxy,
+            dirty: false,
+        }
+    }
+}
+
+impl Component for Decal {
+    fn init(&mut self, _world: &mut World) {
+        self.world_affine = self.parent().transform.affine();
+        self.sync_proxy();
+    }
+
+    fn late_update(&mut self, _world: &mut World) {
+        let parent = self.parent();
+        if parent.transform.is_dirty() {
+            self.world_affine = parent.transform.affine();
+            self.sync_proxy();
+        }
+
+        // Reflected fields can be edited without going through the setters
+        if self.synced_proxy() != self.proxy {
+            self.sync_proxy();
+        }
+    }
+
+    fn create_decal(&mut self, _world: &World) -> Option<Box<DecalProxy>> {
+        Some(Box::new(self.proxy))
+    }
+
+    fn update_proxy(&mut self, _world: &World, mut ctx: CPUDrawCtx) {
+        if !self.dirty {
+            return;
+        }
+
+        let new_proxy = self.proxy;
+        ctx.send_decal_update(move |proxy| {
+            *proxy = new_proxy;
+        });
+
+        self.dirty = false;
+    }
+}
+
+impl Decal {
+    pub fn material(&self) -> HMaterialInstance {
+        self.material
+    }
+
+    pub fn set_material(&mut self, material: HMaterialInstance) {
+        self.material = material;
+        self.sync_proxy();
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: Vec3) {
+        self.size = size.abs();
+        self.sync_proxy();
+    }
+
+    pub fn angle_fade(&self) -> (f32, f32) {
+        (self.angle_fade_start, self.angle_fade_end)
+    }
+
+    /// Fades the decal out on surfaces turned away from the projection, starting at `start`
+    /// and fully gone at `end` degrees between surface normal and projection.
+    pub fn set_angle_fade(&mut self, start: f32, end: f32) {
+        self.angle_fade_end = end.clamp(0.0, 90.0);
+        self.angle_fade_start = start.clamp(0.0, self.angle_fade_end);
+        self.sync_proxy();
+    }
+
+    pub fn sort_order(&self) -> i32 {
+        self.sort_order
+    }
+
+    pub fn set_sort_order(&mut self, order: i32) {
+        self.sort_order = order;
+        self.sync_proxy();
+    }
+
+    pub fn layer_mask(&self) -> u32 {
+        self.layer_mask
+    }
+
+    /// Sets the bit mask of the [`MAX_DECAL_LAYERS`] decal layers the decal projects onto
+    pub fn set_layer_mask(&mut self, mask: u32) {
+        self.layer_mask = mask;
+        self.sync_proxy();
+    }
+
+    /// Whether the decal projects onto meshes on `layer`
+    pub fn projects_onto(&self, layer: u32) -> bool {
+        layer < MAX_DECAL_LAYERS && self.layer_mask & (1 << layer) != 0
+    }
+
+    /// Projects onto meshes on `layer`, or stops doing so
+    pub fn set_projects_onto(&mut self, layer: u32, enabled: bool) {
+        if layer >= MAX_DECAL_LAYERS {
+            return;
+        }
+
+        if enabled {
+            self.layer_mask |= 1 << layer;
+        } else {
+            self.layer_mask &= !(1 << layer);
+        }
+        self.sync_proxy();
+    }
+
+    /// The proxy with the current field values applied.
+    fn synced_proxy(&self) -> DecalProxy {
+        // A flat box can't be inverted
+        let size = self.size.abs().max(Vec3::splat(1e-4));
+        let angle_fade_end = self.angle_fade_end.clamp(0.0, 90.0);
+        DecalProxy {
+            transform: self.world_affine * Affine3A::from_scale(size),
+            material: self.material,
+            angle_fade_start: self
+                .angle_fade_start
+                .clamp(0.0, angle_fade_end)
+                .to_radians(),
+            angle_fade_end: angle_fade_end.to_radians(),
+            sort_order: self.sort_order,
+            layer_mask: self.layer_mask,
+        }
+    }
+
+    fn sync_proxy(&mut self) {
+        self.proxy = self.synced_proxy();
+        self.dirty = true;
+    }
+}
@@ -0,0 +1,444 @@
+use std::any::TypeId;
+use std::collections::HashSet;
+use syrillian::World;
+use syrillian::components::{CRef, Component, UiContext};
+use syrillian::core::GameObjectId;
+use syrillian::input::KeyCode;
+use syrillian::math::{EulerRot, Quat, Vec3, Vec4, vec2};
+use syrillian::reflect::serializer::JsonSerializer;
+use syrillian::reflect::{Value, type_info};
+use syrillian_render::rendering::viewport::ViewportId;
+use syrillian_render::strobe::style::Size;
+use syrillian_render::strobe::{UiBuilder, UiRect};
+
+const LABEL_WIDTH: f32 = 110.0;
+const INDENT: f32 = 12.0;
+const DRAG_SPEED: f32 = 0.01;
+
+/// Editor state that has to survive between frames, like which text field has focus.
+#[derive(Debug, Default)]
+pub struct InspectorState {
+    focused_field: Option<String>,
+    /// Drag distance on an integer field that didn't add up to a whole step yet
+    drag_remainder: Option<(String, f32)>,
+}
+
+/// Draws an editable widget for every reflected field of `component` and writes changed
+/// values back through the field type's reflected deserializer.
+///
+/// Returns true if any field was changed this frame.
+pub fn inspect_component(
+    ui: &mut UiBuilder,
+    component: &CRef<dyn Component>,
+    state: &mut InspectorState,
+) -> bool {
+    let Some(info) = component.type_info() else {
+        ui.label("Not reflected");
+        return false;
+    };
+
+    if info.fields.is_empty() {
+        ui.label("No reflected fields");
+        return false;
+    }
+
+    let base = std::ptr::from_mut(component.get_mut()).cast::<u8>();
+    let mut changed = false;
+
+    for field in info.fields {
+        let Some(field_info) = type_info(field.type_id) else {
+            continue;
+        };
+
+        let ptr = unsafe { base.add(field.offset) };
+        let mut value = (field_info.actions.serialize)(ptr);
+        let path = format!("{:?}.{}", component.typed_id(), field.name);
+
+        if edit_value(
+            ui,
+            field.name,
+            Some(field.type_id),
+            &mut value,
+            &path,
+            state,
+            0,
+        ) {
+            (field_info.actions.deserialize)(ptr, &value);
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+fn edit_value(
+    ui: &mut UiBuilder,
+    name: &str,
+    type_id: Option<TypeId>,
+    value: &mut Value,
+    path: &str,
+    state: &mut InspectorState,
+    depth: usize,
+) -> bool {
+    if let Value::Object(entries) = value {
+        indented_row(ui, depth, |ui| {
+            ui.label(name);
+        });
+
+        let fields = type_id
+            .and_then(type_info)
+            .map_or(&[][..], |info| info.fields);
+
+        let mut changed = false;
+        for (key, entry) in entries.iter_mut() {
+            let entry_type = fields.iter().find(|f| f.name == key).map(|f| f.type_id);
+            let entry_path = format!("{path}.{key}");
+            changed |= edit_value(ui, key, entry_type, entry, &entry_path, state, depth + 1);
+        }
+        return changed;
+    }
+
+    let mut changed = false;
+    indented_row(ui, depth, |ui| {
+        ui.style.width = Size::Fixed(LABEL_WIDTH);
+        ui.label(name);
+        ui.style.width = Size::Auto;
+
+        changed = edit_leaf(ui, name, type_id, value, path, state);
+    });
+    changed
+}
+
+fn edit_leaf(
+    ui: &mut UiBuilder,
+    name: &str,
+    type_id: Option<TypeId>,
+    value: &mut Value,
+    path: &str,
+    state: &mut InspectorState,
+) -> bool {
+    match value {
+        Value::Bool(checked) => ui.checkbox(checked, ""),
+        Value::String(text) => {
+            let mut focused = state.focused_field.as_deref() == Some(path);
+            let changed = ui.text_field(text, &mut focused);
+            if focused {
+                state.focused_field = Some(path.to_string());
+            } else if state.focused_field.as_deref() == Some(path) {
+                state.focused_field = None;
+            }
+            changed
+        }
+        Value::Array(items) if type_id == Some(TypeId::of::<Quat>()) => edit_rotation(ui, items),
+        Value::Array(items) if is_number_vector(items) => {
+            if name.to_lowercase().contains("color") {
+                color_swatch(ui, items);
+            }
+
+            let mut changed = false;
+            for (i, item) in items.iter_mut().enumerate() {
+                changed |= edit_number(ui, item, &format!("{path}.{i}"), state);
+            }
+            changed
+        }
+        Value::None => {
+            ui.label("None");
+            false
+        }
+        value if value.to_f64().is_some() => edit_number(ui, value, path, state),
+        other => {
+            ui.label(JsonSerializer::value_to_string(other));
+            false
+        }
+    }
+}
+
+fn edit_number(
+    ui: &mut UiBuilder,
+    value: &mut Value,
+    path: &str,
+    state: &mut InspectorState,
+) -> bool {
+    let Some(current) = value.to_f64() else {
+        return false;
+    };
+
+    let fractional = matches!(value, Value::Float(_) | Value::Double(_));
+    let speed = if fractional { DRAG_SPEED } else { 1.0 };
+
+    let mut edited = current as f32;
+    if !ui.drag_value(&mut edited, speed) {
+        return false;
+    }
+
+    // Integers only move in whole steps, so slow drags are summed up until they reach one
+    let mut steps = 0.0;
+    if !fractional {
+        let remainder = match state.drag_remainder.take() {
+            Some((field, remainder)) if field == path => remainder,
+            _ => 0.0,
+        };
+        let total = remainder + (edited - current as f32);
+        steps = total.trunc();
+        state.drag_remainder = Some((path.to_string(), total - steps));
+        if steps == 0.0 {
+            return false;
+        }
+    }
+
+    let rounded = (current + steps as f64).round();
+    *value = match value {
+        Value::Float(_) => Value::Float(edited),
+        Value::Double(_) => Value::Double(edited as f64),
+        Value::Int(_) => Value::Int(rounded as i32),
+        Value::UInt(_) => Value::UInt(rounded.max(0.0) as u32),
+        Value::BigInt(_) => Value::BigInt(rounded as i64),
+        Value::BigUInt(_) => Value::BigUInt(rounded.max(0.0) as u64),
+        Value::VeryBigInt(_) => Value::VeryBigInt(rounded as i128),
+        Value::VeryBigUInt(_) => Value::VeryBigUInt(rounded.max(0.0) as u128),
+        _ => return false,
+    };
+
+    true
+}
+
+/// Shows a quaternion as euler angles in degrees.
+fn edit_rotation(ui: &mut UiBuilder, items: &mut [Value]) -> bool {
+    let [x, y, z, w] = items else {
+        return false;
+    };
+    let component = |v: &Value| v.to_f64().unwrap_or_default() as f32;
+    let rotation = Quat::from_xyzw(component(x), component(y), component(z), component(w));
+
+    let (mut ex, mut ey, mut ez) = rotation.to_euler(EulerRot::XYZ);
+    let mut degrees = Vec3::new(ex, ey, ez) * f32::to_degrees(1.0);
+    if !edit_vec3(ui, &mut degrees, 1.0) {
+        return false;
+    }
+
+    (ex, ey, ez) = (
+        degrees.x.to_radians(),
+        degrees.y.to_radians(),
+        degrees.z.to_radians(),
+    );
+    let rotation = Quat::from_euler(EulerRot::XYZ, ex, ey, ez);
+    *x = Value::Float(rotation.x);
+    *y = Value::Float(rotation.y);
+    *z = Value::Float(rotation.z);
+    *w = Value::Float(rotation.w);
+
+    true
+}
+
+fn edit_vec3(ui: &mut UiBuilder, value: &mut Vec3, speed: f32) -> bool {
+    let mut changed = false;
+    changed |= ui.drag_value(&mut value.x, speed);
+    changed |= ui.drag_value(&mut value.y, speed);
+    changed |= ui.drag_value(&mut value.z, speed);
+    changed
+}
+
+fn color_swatch(ui: &mut UiBuilder, items: &[Value]) {
+    let channel = |i: usize| items.get(i).and_then(Value::to_f64).unwrap_or(1.0) as f32;
+    let color = Vec4::new(channel(0), channel(1), channel(2), channel(3));
+    let size = ui.theme.check_size;
+    let border = ui.theme.border;
+
+    ui.add(
+        UiRect::new()
+            .size(vec2(size, size))
+            .color(color)
+            .border(border, 1.0)
+            .into(),
+    );
+}
+
+fn is_number_vector(items: &[Value]) -> bool {
+    (1..=4).contains(&items.len()) && items.iter().all(|v| v.to_f64().is_some())
+}
+
+fn indented_row(ui: &mut UiBuilder, depth: usize, content: impl FnOnce(&mut UiBuilder)) {
+    ui.horizontal(|ui| {
+        ui.spacing(vec2(INDENT * depth as f32, 0.0));
+        content(ui);
+    });
+}
+
+/// Runtime debug inspector. Shows the object hierarchy of the world and lets you edit the
+/// transform and reflected component fields of the selected object.
+///
+/// Starts closed and is toggled with F10.
+#[derive(Default)]
+pub struct Inspector {
+    open: bool,
+    selected: Option<GameObjectId>,
+    expanded: HashSet<GameObjectId>,
+    state: InspectorState,
+}
+
+struct HierarchyRow {
+    id: GameObjectId,
+    depth: usize,
+    name: String,
+    has_children: bool,
+}
+
+struct TransformEdit {
+    position: Vec3,
+    rotation: Vec3,
+    scale: Vec3,
+    changed: bool,
+}
+
+impl Component for Inspector {
+    fn on_gui(&mut self, world: &mut World, ctx: UiContext) {
+        if world.input.is_key_down(KeyCode::F10) {
+            self.open = !self.open;
+        }
+
+        if !self.open {
+            return;
+        }
+
+        if self
+            .selected
+            .is_some_and(|id| world.get_object(id).is_none())
+        {
+            self.selected = None;
+        }
+
+        let mut rows = Vec::new();
+        for &id in &world.children {
+            self.collect_rows(world, id, 0, &mut rows);
+        }
+
+        let selected = self.selected.and_then(|id| world.get_object(id));
+        let components: Vec<CRef<dyn Component>> = selected
+            .map(|obj| obj.iter_dyn_components().cloned().collect())
+            .unwrap_or_default();
+        let mut transform = selected.map(|obj| TransformEdit {
+            position: *obj.transform.local_position(),
+            rotation: obj.transform.local_euler_rotation() * f32::to_degrees(1.0),
+            scale: *obj.transform.local_scale(),
+            changed: false,
+        });
+        let selected_name = selected.map(|obj| obj.name.clone());
+
+        ctx.draw(world, ViewportId::PRIMARY, |ui| {
+            ui.horizontal(|ui| {
+                ui.panel(|ui| {
+                    ui.label("Hierarchy");
+                    for row in &rows {
+                        self.draw_row(ui, row);
+                    }
+                });
+
+                let Some(name) = selected_name else {
+                    return;
+                };
+
+                ui.panel(|ui| {
+                    ui.label(name);
+
+                    if let Some(edit) = transform.as_mut() {
+                        Self::draw_transform(ui, edit);
+                    }
+
+                    for component in &components {
+                        let name = component.type_info().map_or("Component", |info| info.name);
+                        ui.label(name);
+                        inspect_component(ui, component, &mut self.state);
+                    }
+                });
+            });
+        });
+
+        if let (Some(id), Some(edit)) = (self.selected, transform)
+            && edit.changed
+            && let Some(obj) = world.get_object_mut(id)
+        {
+            obj.transform.set_local_position_vec(edit.position);
+            let rotation = edit.rotation * f32::to_radians(1.0);
+            obj.transform.set_local_rotation(Quat::from_euler(
+                EulerRot::XYZ,
+                rotation.x,
+                rotation.y,
+                rotation.z,
+            ));
+            obj.transform.set_nonuniform_local_scale(edit.scale);
+        }
+    }
+}
+
+impl Inspector {
+    fn collect_rows(
+        &self,
+        world: &World,
+        id: GameObjectId,
+        depth: usize,
+        rows: &mut Vec<HierarchyRow>,
+    ) {
+        let Some(obj) = world.get_object(id) else {
+            return;
+        };
+
+        rows.push(HierarchyRow {
+            id,
+            depth,
+            name: obj.name.clone(),
+            has_children: !obj.children().is_empty(),
+        });
+
+        if self.expanded.contains(&id) {
+            for &child in obj.children() {
+                self.collect_rows(world, child, depth + 1, rows);
+            }
+        }
+    }
+
+    fn draw_row(&mut self, ui: &mut UiBuilder, row: &HierarchyRow) {
+        let expanded = self.expanded.contains(&row.id);
+
+        indented_row(ui, row.depth, |ui| {
+            let toggle = if !row.has_children {
+                " "
+            } else if expanded {
+                "-"
+            } else {
+                "+"
+            };
+            if ui.selectable(toggle, false).clicked && row.has_children {
+                if expanded {
+                    self.expanded.remove(&row.id);
+                } else {
+                    self.expanded.insert(row.id);
+                }
+            }
+
+            let selected = self.selected == Some(row.id);
+            if ui.selectable(row.name.as_str(), selected).clicked {
+                self.selected = Some(row.id);
+            }
+        });
+    }
+
+    fn draw_transform(ui: &mut UiBuilder, edit: &mut TransformEdit) {
+        ui.label("Transform");
+
+        let mut changed = false;
+        for (name, value, speed) in [
+            ("position", &mut edit.position, DRAG_SPEED),
+            ("rotation", &mut edit.rotation, 1.0),
+            ("scale", &mut edit.scale, DRAG_SPEED),
+        ] {
+            indented_row(ui, 1, |ui| {
+                ui.style.width = Size::Fixed(LABEL_WIDTH);
+                ui.label(name);
+                ui.style.width = Size::Auto;
+
+                changed |= edit_vec3(ui, value, speed);
+            });
+        }
+        edit.changed = changed;
+    }
+}
@@ -0,0 +1,189 @@
+use syrillian::assets::{HMaterialInstance, HMesh};
+use syrillian::components::Component;
+use syrillian::math::Affine3A;
+use syrillian::tracing::warn;
+use syrillian::{Reflect, World};
+use syrillian_render::proxies::{InstancedMeshSceneProxy, SceneProxy};
+use syrillian_render::proxy_data_mut;
+use syrillian_render::rendering::CPUDrawCtx;
+use syrillian_render::rendering::decals::MAX_DECAL_LAYERS;
+
+/// Renders one mesh many times, at transforms relative to the parent object.
+///
+/// All instances are drawn with a single instanced draw per material, which is much cheaper
+/// than one [`MeshRenderer`](crate::MeshRenderer) per copy. They are culled and picked as
+/// a whole.
+#[derive(Debug, Reflect)]
+#[reflect(default)]
+pub struct InstancedMeshRenderer {
+    mesh: HMesh,
+    materials: Vec<HMaterialInstance>,
+    #[reflect]
+    instances: Vec<Affine3A>,
+    decal_layer: u32,
+    dirty_mesh: bool,
+    dirty_materials: bool,
+    dirty_instances: bool,
+    dirty_decal_layer: bool,
+}
+
+impl Default for InstancedMeshRenderer {
+    fn default() -> Self {
+        InstancedMeshRenderer {
+            mesh: HMesh::invalid(),
+            materials: vec![],
+            instances: vec![],
+            decal_layer: 0,
+            dirty_mesh: false,
+            dirty_materials: false,
+            dirty_instances: false,
+            dirty_decal_layer: false,
+        }
+    }
+}
+
+impl Component for InstancedMeshRenderer {
+    fn create_render_proxy(&mut self, world: &World) -> Option<Box<dyn SceneProxy>> {
+        let Some(mesh) = world.assets.meshes.try_get(self.mesh) else {
+            warn!(
+                "Instanced Mesh Renderer couldn't create its proxy because the mesh wasn't found in the asset store"
+            );
+            return None;
+        };
+
+        self.dirty_instances = false;
+        self.dirty_decal_layer = false;
+
+        let mut proxy = InstancedMeshSceneProxy::new(
+            self.mesh,
+            self.materials.clone(),
+            mesh.material_ranges.clone(),
+            mesh.bounding_sphere,
+            self.instances.clone(),
+        );
+        proxy.decal_layer = self.decal_layer;
+        Some(Box::new(proxy))
+    }
+
+    fn update_proxy(&mut self, world: &World, mut ctx: CPUDrawCtx) {
+        if self.dirty_decal_layer {
+            let decal_layer = self.decal_layer;
+            ctx.send_proxy_update(move |sc| {
+                let data: &mut InstancedMeshSceneProxy = proxy_data_mut!(sc);
+                data.decal_layer = decal_layer;
+                data.dirty_instances = true;
+            });
+            self.dirty_decal_layer = false;
+        }
+
+        if !self.dirty_mesh && !self.dirty_materials && !self.dirty_instances {
+            return;
+        }
+
+        let Some(mesh) = world.assets.meshes.try_get(self.mesh) else {
+            warn!(
+                "Instanced Mesh Renderer couldn't update its proxy because the mesh wasn't found in the asset store"
+            );
+            return;
+        };
+
+        if self.dirty_mesh {
+            let h_mesh = self.mesh;
+            let bounds = mesh.bounding_sphere;
+            ctx.send_proxy_update(move |sc| {
+                let data: &mut InstancedMeshSceneProxy = proxy_data_mut!(sc);
+                data.mesh = h_mesh;
+                data.bounding = bounds;
+                data.dirty_instances = true;
+            });
+            self.dirty_mesh = false;
+        }
+
+        if self.dirty_materials {
+            let materials = self.materials.clone();
+            let material_ranges = mesh.material_ranges.clone();
+            ctx.send_proxy_update(move |sc| {
+                let data: &mut InstancedMeshSceneProxy = proxy_data_mut!(sc);
+                data.materials = materials;
+                data.material_ranges = material_ranges;
+            });
+            self.dirty_materials = false;
+        }
+
+        if self.dirty_instances {
+            let instances = self.instances.clone();
+            ctx.send_proxy_update(move |sc| {
+                let data: &mut InstancedMeshSceneProxy = proxy_data_mut!(sc);
+                data.instances = instances;
+                data.dirty_instances = true;
+            });
+            self.dirty_instances = false;
+        }
+    }
+}
+
+impl InstancedMeshRenderer {
+    pub fn change_mesh(&mut self, mesh: HMesh, materials: Option<Vec<HMaterialInstance>>) {
+        let materials = materials.unwrap_or_default();
+        self.set_mesh(mesh);
+        self.set_materials(materials);
+    }
+
+    pub fn set_mesh(&mut self, mesh: HMesh) {
+        self.mesh = mesh;
+        self.dirty_mesh = true;
+    }
+
+    pub fn set_materials(&mut self, materials: Vec<HMaterialInstance>) {
+        self.materials = materials;
+        self.dirty_materials = true;
+    }
+
+    pub fn set_material_slot(&mut self, idx: usize, material: HMaterialInstance) {
+        let size = idx + 1;
+        if self.materials.len() < size {
+            self.materials.resize(size, HMaterialInstance::FALLBACK);
+        }
+        self.materials[idx] = material;
+        self.dirty_materials = true;
+    }
+
+    /// Replaces all instances with the given transforms, relative to the parent object.
+    pub fn set_instances(&mut self, instances: Vec<Affine3A>) {
+        self.instances = instances;
+        self.dirty_instances = true;
+    }
+
+    pub fn add_instance(&mut self, instance: Affine3A) {
+        self.instances.push(instance);
+        self.dirty_instances = true;
+    }
+
+    pub fn clear_instances(&mut self) {
+        self.instances.clear();
+        self.dirty_instances = true;
+    }
+
+    /// Puts all instances on one of the [`MAX_DECAL_LAYERS`] decal layers. Decals only project
+    /// onto the layers in their mask.
+    pub fn set_decal_layer(&mut self, layer: u32) {
+        self.decal_layer = layer.min(MAX_DECAL_LAYERS - 1);
+        self.dirty_decal_layer = true;
+    }
+
+    pub fn decal_layer(&self) -> u32 {
+        self.decal_layer
+    }
+
+    pub fn mesh(&self) -> HMesh {
+        self.mesh
+    }
+
+    pub fn materials(&self) -> &[HMaterialInstance] {
+        &self.materials
+    }
+
+    pub fn instances(&self) -> &[Affine3A] {
+        &self.instances
+    }
+}
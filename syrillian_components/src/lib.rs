@@ -35,18 +35,22 @@
 //! ```
 
 pub mod animation;
+pub mod animation_graph;
 pub mod audio;
 pub mod button;
 pub mod collider;
+pub mod decal;
 pub mod flashlight;
 pub mod fp_camera;
 pub mod fp_movement;
 pub mod freecam;
 pub mod gravity;
+pub mod instanced_mesh_renderer;
 pub mod joints;
 pub mod light;
 pub mod mesh_renderer;
 pub mod particle_system;
+pub mod reflection_probe;
 pub mod rigid_body;
 pub mod rotate;
 pub mod skeletal;
@@ -55,6 +59,7 @@ pub mod text;
 pub mod third_person_controller;
 
 pub mod extensions;
+pub mod inspector;
 pub mod prefabs;
 pub mod profiler;
 pub mod proxy;
@@ -63,11 +68,14 @@ pub use animation::AnimationComponent;
 pub use audio::{AudioEmitter, AudioReceiver};
 pub use button::Button;
 pub use collider::Collider3D;
+pub use decal::Decal;
 pub use flashlight::FlashlightComponent;
 pub use fp_camera::FirstPersonCameraController;
 pub use fp_movement::FirstPersonMovementController;
 pub use freecam::FreecamController;
 pub use gravity::GravityComponent;
+pub use inspector::Inspector;
+pub use instanced_mesh_renderer::InstancedMeshRenderer;
 pub use joints::{
     FixedJoint, PrismaticJoint, RevoluteJoint, RopeJoint, SphericalJoint, SpringJoint,
 };
@@ -75,6 +83,7 @@ pub use light::{PointLightComponent, SpotLightComponent, SunLightComponent};
 pub use mesh_renderer::MeshRenderer;
 pub use particle_system::ParticleSystemComponent;
 pub use profiler::Profiler;
+pub use reflection_probe::ReflectionProbe;
 pub use rigid_body::RigidBodyComponent;
 pub use rotate::RotateComponent;
 pub use skeletal::SkeletalComponent;
@@ -3,9 +3,10 @@ use syrillian::components::Component;
 use syrillian::math::Vec3;
 use syrillian::tracing::warn;
 use syrillian::{Reflect, World};
-use syrillian_render::proxies::{MeshSceneProxy, SceneProxy};
+use syrillian_render::proxies::{LodSelection, MeshSceneProxy, SceneProxy};
 use syrillian_render::proxy_data_mut;
 use syrillian_render::rendering::CPUDrawCtx;
+use syrillian_render::rendering::decals::MAX_DECAL_LAYERS;
 
 #[repr(C)]
 #[derive(
@@ -27,8 +28,13 @@ pub struct DebugVertexNormal {
 pub struct MeshRenderer {
     mesh: HMesh,
     materials: Vec<HMaterialInstance>,
+    lod_thresholds: Vec<f32>,
+    lod_crossfade: f32,
+    decal_layer: u32,
     dirty_mesh: bool,
     dirty_materials: bool,
+    dirty_lod: bool,
+    dirty_decal_layer: bool,
 }
 
 impl Default for MeshRenderer {
@@ -36,8 +42,13 @@ impl Default for MeshRenderer {
         MeshRenderer {
             mesh: HMesh::invalid(),
             materials: vec![],
+            lod_thresholds: vec![],
+            lod_crossfade: 0.0,
+            decal_layer: 0,
             dirty_mesh: false,
             dirty_materials: false,
+            dirty_lod: false,
+            dirty_decal_layer: false,
         }
     }
 }
@@ -60,12 +71,33 @@ impl Component for MeshRenderer {
             mesh: self.mesh,
             materials: self.materials.clone(),
             material_ranges: mesh.material_ranges.clone(),
+            lods: mesh.lods.clone(),
+            lod_selection: self.lod_selection(),
             bounding: mesh.bounding_sphere,
             model_bounding,
+            decal_layer: self.decal_layer,
         }))
     }
 
     fn update_proxy(&mut self, world: &World, mut ctx: CPUDrawCtx) {
+        if self.dirty_lod {
+            let lod_selection = self.lod_selection();
+            ctx.send_proxy_update(move |sc| {
+                let data: &mut MeshSceneProxy = proxy_data_mut!(sc);
+                data.lod_selection = lod_selection;
+            });
+            self.dirty_lod = false;
+        }
+
+        if self.dirty_decal_layer {
+            let decal_layer = self.decal_layer;
+            ctx.send_proxy_update(move |sc| {
+                let data: &mut MeshSceneProxy = proxy_data_mut!(sc);
+                data.decal_layer = decal_layer;
+            });
+            self.dirty_decal_layer = false;
+        }
+
         if !self.dirty_mesh && !self.dirty_materials {
             return;
         }
@@ -80,10 +112,12 @@ impl Component for MeshRenderer {
         if self.dirty_mesh {
             let h_mesh = self.mesh;
             let bounds = mesh.bounding_sphere;
+            let lods = mesh.lods.clone();
             ctx.send_proxy_update(move |sc| {
                 let data: &mut MeshSceneProxy = proxy_data_mut!(sc);
                 data.mesh = h_mesh;
                 data.bounding = bounds;
+                data.lods = lods;
             })
         }
 
@@ -128,4 +162,49 @@ impl MeshRenderer {
     pub fn mesh(&self) -> HMesh {
         self.mesh
     }
+
+    pub fn materials(&self) -> &[HMaterialInstance] {
+        &self.materials
+    }
+
+    /// Sets the fractions of the screen height the mesh bounds have to drop below to switch
+    /// to each level of detail after the base mesh. Levels without a threshold switch once
+    /// their simplification error would become visible.
+    pub fn set_lod_thresholds(&mut self, thresholds: Vec<f32>) {
+        self.lod_thresholds = thresholds;
+        self.dirty_lod = true;
+    }
+
+    pub fn lod_thresholds(&self) -> &[f32] {
+        &self.lod_thresholds
+    }
+
+    /// Dithers neighbouring levels of detail into each other in a band of `width` above every
+    /// threshold, relative to the threshold. Zero switches levels instantly.
+    pub fn set_lod_crossfade(&mut self, width: f32) {
+        self.lod_crossfade = width.max(0.0);
+        self.dirty_lod = true;
+    }
+
+    pub fn lod_crossfade(&self) -> f32 {
+        self.lod_crossfade
+    }
+
+    /// Puts the mesh on one of the [`MAX_DECAL_LAYERS`] decal layers. Decals only project onto
+    /// the layers in their mask, which keeps e.g. characters free of them.
+    pub fn set_decal_layer(&mut self, layer: u32) {
+        self.decal_layer = layer.min(MAX_DECAL_LAYERS - 1);
+        self.dirty_decal_layer = true;
+    }
+
+    pub fn decal_layer(&self) -> u32 {
+        self.decal_layer
+    }
+
+    fn lod_selection(&self) -> LodSelection {
+        LodSelection {
+            thresholds: self.lod_thresholds.clone(),
+            crossfade: self.lod_crossfade,
+        }
+    }
 }
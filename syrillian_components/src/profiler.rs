@@ -202,11 +202,13 @@ impl Profiler {
             width: size.0,
             height: size.1,
             format,
+            mip_level_count: 1,
             data: None,
             repeat_mode: base.repeat_mode,
             filter_mode: base.filter_mode,
             mip_filter_mode: base.mip_filter_mode,
             has_transparency: false,
+            generate_mips: false,
         };
 
         store.add(texture)
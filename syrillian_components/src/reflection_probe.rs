@@ -0,0 +1,168 @@
+use syrillian::Reflect;
+use syrillian::World;
+use syrillian::components::Component;
+use syrillian::math::Vec3;
+use syrillian_render::lighting::reflection_probe::{ReflectionProbeProxy, ReflectionProbeRefresh};
+use syrillian_render::rendering::CPUDrawCtx;
+
+/// Captures the surroundings of its game object into a cubemap, which replaces the sky in
+/// specular reflections of everything inside the probe's box.
+///
+/// Probes capture once when they're added. Call [`ReflectionProbe::request_capture`] after
+/// the scene changed, or set a refresh interval to recapture periodically.
+#[derive(Debug, Reflect)]
+#[reflect(default)]
+pub struct ReflectionProbe {
+    /// Size of the box affected by the probe, centered on the game object
+    #[reflect]
+    size: Vec3,
+    /// Distance from the box faces over which the probe fades into its surroundings
+    #[reflect]
+    blend_distance: f32,
+    #[reflect]
+    intensity: f32,
+    /// Projects reflections onto the box, so they line up with the walls of a room
+    #[reflect]
+    box_projection: bool,
+    /// Frames between captures, or 0 to only capture on request
+    #[reflect]
+    refresh_interval: u32,
+
+    proxy: ReflectionProbeProxy,
+    dirty: bool,
+}
+
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        let proxy = ReflectionProbeProxy::default();
+
+        ReflectionProbe {
+            size: proxy.extents * 2.0,
+            blend_distance: proxy.blend_distance,
+            intensity: proxy.intensity,
+            box_projection: proxy.box_projection,
+            refresh_interval: 0,
+            proxy,
+            dirty: false,
+        }
+    }
+}
+
+impl Component for ReflectionProbe {
+    fn init(&mut self, _world: &mut World) {
+        self.proxy.position = self.parent().transform.position();
+        self.sync_proxy();
+    }
+
+    fn late_update(&mut self, _world: &mut World) {
+        let parent = self.parent();
+        if parent.transform.is_dirty() {
+            self.proxy.position = parent.transform.position();
+            self.dirty = true;
+        }
+
+        // Reflected fields can be edited without going through the setters
+        if self.synced_proxy() != self.proxy {
+            self.sync_proxy();
+        }
+    }
+
+    fn create_reflection_probe(&mut self, _world: &World) -> Option<Box<ReflectionProbeProxy>> {
+        Some(Box::new(self.proxy))
+    }
+
+    fn update_proxy(&mut self, _world: &World, mut ctx: CPUDrawCtx) {
+        if !self.dirty {
+            return;
+        }
+
+        let new_proxy = self.proxy;
+        ctx.send_reflection_probe_update(move |proxy| {
+            *proxy = new_proxy;
+        });
+
+        self.proxy.capture_requested = false;
+        self.dirty = false;
+    }
+}
+
+impl ReflectionProbe {
+    pub fn size(&self) -> Vec3 {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: Vec3) {
+        self.size = size.abs();
+        self.sync_proxy();
+    }
+
+    pub fn blend_distance(&self) -> f32 {
+        self.blend_distance
+    }
+
+    pub fn set_blend_distance(&mut self, distance: f32) {
+        self.blend_distance = distance.max(0.0);
+        self.sync_proxy();
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.max(0.0);
+        self.sync_proxy();
+    }
+
+    pub fn box_projection(&self) -> bool {
+        self.box_projection
+    }
+
+    pub fn set_box_projection(&mut self, enabled: bool) {
+        self.box_projection = enabled;
+        self.sync_proxy();
+    }
+
+    pub fn refresh(&self) -> ReflectionProbeRefresh {
+        self.proxy.refresh
+    }
+
+    /// Recaptures the probe every `frames` frames. Only one probe is captured per frame.
+    pub fn set_refresh_interval(&mut self, frames: Option<u32>) {
+        self.refresh_interval = frames.unwrap_or(0);
+        self.sync_proxy();
+    }
+
+    /// Sets the clipping planes of the capture cameras
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.proxy.near = near;
+        self.proxy.far = far;
+        self.dirty = true;
+    }
+
+    /// Recaptures the probe on one of the next frames
+    pub fn request_capture(&mut self) {
+        self.proxy.capture_requested = true;
+        self.dirty = true;
+    }
+
+    /// The proxy with the current field values applied.
+    fn synced_proxy(&self) -> ReflectionProbeProxy {
+        ReflectionProbeProxy {
+            extents: self.size.abs() * 0.5,
+            blend_distance: self.blend_distance.max(0.0),
+            intensity: self.intensity.max(0.0),
+            box_projection: self.box_projection,
+            refresh: match self.refresh_interval {
+                0 => ReflectionProbeRefresh::OnDemand,
+                n => ReflectionProbeRefresh::EveryNFrames(n),
+            },
+            ..self.proxy
+        }
+    }
+
+    fn sync_proxy(&mut self) {
+        self.proxy = self.synced_proxy();
+        self.dirty = true;
+    }
+}
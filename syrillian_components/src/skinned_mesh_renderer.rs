@@ -10,6 +10,7 @@ use syrillian_render::proxies::SceneProxy;
 use syrillian_render::proxies::skinned_mesh_proxy::SkinnedMeshSceneProxy;
 use syrillian_render::proxy_data_mut;
 use syrillian_render::rendering::CPUDrawCtx;
+use syrillian_render::rendering::decals::MAX_DECAL_LAYERS;
 
 #[repr(C)]
 #[derive(
@@ -31,8 +32,12 @@ pub struct DebugVertexNormal {
 pub struct SkinnedMeshRenderer {
     mesh: HSkinnedMesh,
     materials: Vec<HMaterialInstance>,
+    morph_weights: Vec<f32>,
+    decal_layer: u32,
     dirty_mesh: bool,
     dirty_materials: bool,
+    dirty_morph_weights: bool,
+    dirty_decal_layer: bool,
 }
 
 impl Default for SkinnedMeshRenderer {
@@ -40,8 +45,12 @@ impl Default for SkinnedMeshRenderer {
         SkinnedMeshRenderer {
             mesh: HSkinnedMesh::invalid(),
             materials: vec![],
+            morph_weights: vec![],
+            decal_layer: 0,
             dirty_mesh: false,
             dirty_materials: false,
+            dirty_morph_weights: false,
+            dirty_decal_layer: false,
         }
     }
 }
@@ -60,14 +69,22 @@ impl Component for SkinnedMeshRenderer {
             bounds.transformed(&(full_trs.into()))
         });
 
+        if self.morph_weights.is_empty() {
+            self.morph_weights = mesh.morph_targets.initial_weights();
+        }
+        self.dirty_morph_weights = false;
+
         Some(Box::new(SkinnedMeshSceneProxy {
             mesh: self.mesh,
             materials: self.materials.clone(),
             material_ranges: mesh.material_ranges.clone(),
             bone_data: BoneData::new_full_identity(),
             bones_dirty: false,
+            morph_weights: self.morph_weights.clone(),
+            morph_weights_dirty: true,
             bounding: mesh.bounding_sphere,
             model_bounding,
+            decal_layer: self.decal_layer,
         }))
     }
 
@@ -85,7 +102,16 @@ impl Component for SkinnedMeshRenderer {
             });
         }
 
-        if !self.dirty_mesh && !self.dirty_materials {
+        if self.dirty_decal_layer {
+            let decal_layer = self.decal_layer;
+            ctx.send_proxy_update(move |sc| {
+                let data: &mut SkinnedMeshSceneProxy = proxy_data_mut!(sc);
+                data.decal_layer = decal_layer;
+            });
+            self.dirty_decal_layer = false;
+        }
+
+        if !self.dirty_mesh && !self.dirty_materials && !self.dirty_morph_weights {
             return;
         }
 
@@ -105,7 +131,22 @@ impl Component for SkinnedMeshRenderer {
                 data.mesh = h_mesh;
                 data.bounding = bounds;
                 data.bones_dirty = skinned;
-            })
+            });
+
+            if self.morph_weights.is_empty() {
+                self.morph_weights = mesh.morph_targets.initial_weights();
+                self.dirty_morph_weights = true;
+            }
+        }
+
+        if self.dirty_morph_weights {
+            let weights = self.morph_weights.clone();
+            ctx.send_proxy_update(move |sc| {
+                let data: &mut SkinnedMeshSceneProxy = proxy_data_mut!(sc);
+                data.morph_weights = weights;
+                data.morph_weights_dirty = true;
+            });
+            self.dirty_morph_weights = false;
         }
 
         if self.dirty_materials {
@@ -129,9 +170,33 @@ impl SkinnedMeshRenderer {
 
     pub fn set_mesh(&mut self, mesh: HSkinnedMesh) {
         self.mesh = mesh;
+        self.morph_weights.clear();
         self.dirty_mesh = true;
     }
 
+    /// Sets the weight of every morph target, in the order of the mesh's morph targets.
+    pub fn set_morph_weights(&mut self, weights: &[f32]) {
+        if self.morph_weights == weights {
+            return;
+        }
+        self.morph_weights.clear();
+        self.morph_weights.extend_from_slice(weights);
+        self.dirty_morph_weights = true;
+    }
+
+    pub fn set_morph_weight(&mut self, idx: usize, weight: f32) {
+        let size = idx + 1;
+        if self.morph_weights.len() < size {
+            self.morph_weights.resize(size, 0.0);
+        }
+        self.morph_weights[idx] = weight;
+        self.dirty_morph_weights = true;
+    }
+
+    pub fn morph_weights(&self) -> &[f32] {
+        &self.morph_weights
+    }
+
     pub fn set_materials(&mut self, materials: Vec<HMaterialInstance>) {
         self.materials = materials;
         self.dirty_materials = true;
@@ -149,6 +214,21 @@ impl SkinnedMeshRenderer {
     pub fn mesh(&self) -> HSkinnedMesh {
         self.mesh
     }
+
+    pub fn materials(&self) -> &[HMaterialInstance] {
+        &self.materials
+    }
+
+    /// Puts the mesh on one of the [`MAX_DECAL_LAYERS`] decal layers. Decals only project onto
+    /// the layers in their mask, which keeps e.g. characters free of them.
+    pub fn set_decal_layer(&mut self, layer: u32) {
+        self.decal_layer = layer.min(MAX_DECAL_LAYERS - 1);
+        self.dirty_decal_layer = true;
+    }
+
+    pub fn decal_layer(&self) -> u32 {
+        self.decal_layer
+    }
 }
 
 impl<V: Vertex3D> From<&V> for DebugVertexNormal {
@@ -1,9 +1,10 @@
 // TODO: Refactor & Merge First Person Controller
 
-use crate::{Collider3D, RigidBodyComponent};
+use crate::{AnimationComponent, Collider3D, RigidBodyComponent};
 use syrillian::Reflect;
 use syrillian::World;
 use syrillian::components::{CRef, CWeak, CameraComponent, Component};
+use syrillian::core::GameObjectId;
 use syrillian::gilrs::Axis;
 use syrillian::input::KeyCode;
 use syrillian::math::{FloatExt, Pose, Quat, Vec2, Vec3};
@@ -54,6 +55,11 @@ pub struct ThirdPersonCharacterController {
     pub stick_deadzone: f32,
     pub turn_sharpness: f32,
 
+    /// Float parameter of the character's animation graph that receives the horizontal speed
+    pub animation_speed_parameter: String,
+    /// Bool parameter of the character's animation graph that receives the grounded state
+    pub animation_grounded_parameter: String,
+
     pub velocity: Vec3,
     pub is_grounded: bool,
 
@@ -62,6 +68,11 @@ pub struct ThirdPersonCharacterController {
     #[dont_reflect]
     camera: CWeak<CameraComponent>,
     #[dont_reflect]
+    animation: CWeak<AnimationComponent>,
+    /// Number of children the parent had when the animation was last searched for
+    #[dont_reflect]
+    animation_search_children: Option<usize>,
+    #[dont_reflect]
     self_collider_handle: Option<ColliderHandle>,
     #[dont_reflect]
     warned_missing_rigidbody: bool,
@@ -108,11 +119,16 @@ impl Default for ThirdPersonCharacterController {
             stick_deadzone: 0.15,
             turn_sharpness: 14.0,
 
+            animation_speed_parameter: "speed".to_string(),
+            animation_grounded_parameter: "grounded".to_string(),
+
             velocity: Vec3::ZERO,
             is_grounded: false,
 
             rigid_body: CWeak::null(),
             camera: CWeak::null(),
+            animation: CWeak::null(),
+            animation_search_children: None,
             self_collider_handle: None,
             warned_missing_rigidbody: false,
             warned_missing_camera: false,
@@ -153,6 +169,8 @@ impl Component for ThirdPersonCharacterController {
             .map(CRef::downgrade)
             .unwrap_or_default();
 
+        self.search_animation();
+
         self.sync_initial_angles();
         self.camera_pitch = self.camera_pitch.clamp(self.min_pitch, self.max_pitch);
         self.smoothed_camera_distance = self
@@ -281,10 +299,37 @@ impl Component for ThirdPersonCharacterController {
     fn post_update(&mut self, world: &mut World) {
         let dt = world.delta_time().as_secs_f32();
         self.update_camera_transform(world, (self.camera_smoothing * dt).clamp(0.0, 1.0));
+        self.update_animation_parameters(world);
     }
 }
 
 impl ThirdPersonCharacterController {
+    fn update_animation_parameters(&mut self, world: &World) {
+        // the character model might only be attached after the controller was initialized.
+        // Searching walks the whole hierarchy, so only retry once the children changed.
+        if !self.animation.exists(world)
+            && self.animation_search_children != Some(self.parent().children().len())
+        {
+            self.search_animation();
+        }
+
+        let Some(mut animation) = self.animation.upgrade(world) else {
+            return;
+        };
+
+        let speed = Vec2::new(self.velocity.x, self.velocity.z).length();
+        animation.set_float(&self.animation_speed_parameter, speed);
+        animation.set_bool(&self.animation_grounded_parameter, self.is_grounded);
+    }
+
+    fn search_animation(&mut self) {
+        let parent = self.parent();
+        self.animation = find_animation(parent)
+            .map(CRef::downgrade)
+            .unwrap_or_default();
+        self.animation_search_children = Some(parent.children().len());
+    }
+
     fn read_movement_input(&self, world: &World) -> Vec2 {
         let mut fb: f32 = 0.0;
         if world.input.is_key_pressed(KeyCode::KeyW) {
@@ -566,3 +611,11 @@ fn shortest_angle_delta(current: f32, target: f32) -> f32 {
     }
     delta
 }
+
+fn find_animation(root: GameObjectId) -> Option<CRef<AnimationComponent>> {
+    root.get_component::<AnimationComponent>().or_else(|| {
+        root.children()
+            .iter()
+            .find_map(|&child| find_animation(child))
+    })
+}
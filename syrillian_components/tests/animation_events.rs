@@ -0,0 +1,99 @@
+use std::thread::sleep;
+use std::time::Duration;
+use syrillian::World;
+use syrillian::assets::{AnimationClip, AnimationEvent};
+use syrillian::components::Component;
+use syrillian::core::reflection::Value;
+use syrillian_components::AnimationComponent;
+
+fn clip_with_events() -> AnimationClip {
+    let mut clip = AnimationClip {
+        name: "Walk".to_string(),
+        duration: 1.0,
+        channels: Vec::new(),
+        events: vec![
+            AnimationEvent::new("end", 1.0),
+            AnimationEvent::new("start", 0.0),
+            AnimationEvent::new("step", 0.5).with_payload(Value::String("left".to_string())),
+        ],
+    };
+    clip.sort_events();
+    clip
+}
+
+fn crossed(
+    clip: &AnimationClip,
+    start: f32,
+    delta: f32,
+    looping: bool,
+    from_start: bool,
+) -> Vec<String> {
+    let mut names = Vec::new();
+    clip.events_crossed(start, delta, looping, from_start, |event| {
+        names.push(event.name.clone())
+    });
+    names
+}
+
+#[test]
+fn events_crossed_forward_and_backward() {
+    let clip = clip_with_events();
+
+    assert_eq!(crossed(&clip, 0.0, 0.25, true, true), ["start"]);
+    assert_eq!(crossed(&clip, 0.0, 0.25, true, false), Vec::<String>::new());
+    assert_eq!(crossed(&clip, 0.25, 0.25, true, false), ["step"]);
+    assert_eq!(crossed(&clip, 0.75, -0.5, true, false), ["step"]);
+    assert_eq!(
+        crossed(&clip, 0.5, -0.25, true, false),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn events_crossed_wraps_when_looping() {
+    let clip = clip_with_events();
+
+    assert_eq!(crossed(&clip, 0.75, 0.5, true, false), ["end", "start"]);
+    assert_eq!(crossed(&clip, 0.25, -0.5, true, false), ["start", "end"]);
+    assert_eq!(
+        crossed(&clip, 0.25, 10.0, true, false),
+        ["step", "end", "start"]
+    );
+
+    assert_eq!(crossed(&clip, 0.75, 0.5, false, false), ["end"]);
+    assert_eq!(crossed(&clip, 0.25, -0.5, false, false), ["start"]);
+}
+
+#[derive(Default)]
+struct EventRecorder {
+    received: Vec<(String, Value)>,
+}
+
+impl Component for EventRecorder {
+    fn on_animation_event(&mut self, _world: &mut World, event: &AnimationEvent) {
+        self.received
+            .push((event.name.clone(), event.payload.clone()));
+    }
+}
+
+#[test]
+fn component_fires_events_to_siblings() {
+    let (mut world, ..) = World::fresh();
+    let mut object = world.new_object("Character");
+    world.add_child(object);
+
+    let mut animation = object.add_component::<AnimationComponent>();
+    let recorder = object.add_component::<EventRecorder>();
+    animation.set_clips(vec![clip_with_events()]);
+    animation.play_by_name("Walk", false, f32::MAX, 1.0);
+
+    for _ in 0..3 {
+        world.update();
+        world.next_frame();
+        sleep(Duration::from_millis(2));
+    }
+
+    let names: Vec<_> = recorder.received.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["start", "step", "end"]);
+    assert_eq!(recorder.received[1].1, Value::String("left".to_string()));
+}
@@ -0,0 +1,165 @@
+use syrillian::assets::AnimationClip;
+use syrillian::core::reflection::{ReflectDeserialize, ReflectSerialize};
+use syrillian::math::Vec2;
+use syrillian_components::animation_graph::{
+    AnimationGraph, AnimationGraphState, AnimationState, AnimationTransition, GraphSample,
+    TransitionCondition,
+};
+
+fn clip(name: &str, duration: f32) -> AnimationClip {
+    AnimationClip {
+        name: name.to_string(),
+        duration,
+        channels: Vec::new(),
+        events: Vec::new(),
+    }
+}
+
+fn clips() -> Vec<AnimationClip> {
+    vec![
+        clip("Idle", 2.0),
+        clip("Walk", 1.0),
+        clip("Run", 0.5),
+        clip("Jump", 1.0),
+    ]
+}
+
+fn locomotion_graph() -> AnimationGraph {
+    AnimationGraph::new("Locomotion")
+        .float("speed", 0.0)
+        .trigger("jump")
+        .state(AnimationState::blend_1d(
+            "Locomotion",
+            "speed",
+            [("Idle", 0.0), ("Walk", 2.0), ("Run", 6.0)],
+        ))
+        .state(AnimationState::clip("Jump", "Jump").looping(false))
+        .transition(
+            AnimationTransition::new("Locomotion", "Jump")
+                .when(TransitionCondition::triggered("jump"))
+                .duration(0.5),
+        )
+        .transition(
+            AnimationTransition::new("Jump", "Locomotion")
+                .exit_time(1.0)
+                .duration(0.0),
+        )
+}
+
+fn step(
+    state: &mut AnimationGraphState,
+    graph: &mut AnimationGraph,
+    clips: &[AnimationClip],
+    dt: f32,
+) -> Vec<GraphSample> {
+    let mut samples = Vec::new();
+    state.advance(graph, clips, dt);
+    state.samples(graph, clips, &mut samples);
+    samples
+}
+
+#[test]
+fn blend_space_1d_weights() {
+    let clips = clips();
+    let mut graph = locomotion_graph();
+    let mut state = AnimationGraphState::default();
+
+    let samples = step(&mut state, &mut graph, &clips, 0.0);
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].clip_index, 0);
+    assert_eq!(samples[0].weight, 1.0);
+
+    assert!(graph.set_float("speed", 1.0));
+    let samples = step(&mut state, &mut graph, &clips, 0.0);
+    assert_eq!(samples.len(), 2);
+    assert!((samples[0].weight - 0.5).abs() < 1e-5);
+    assert!((samples[1].weight - 0.5).abs() < 1e-5);
+
+    graph.set_float("speed", 100.0);
+    let samples = step(&mut state, &mut graph, &clips, 0.0);
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].clip_index, 2);
+}
+
+#[test]
+fn blend_space_2d_snaps_to_point() {
+    let clips = clips();
+    let mut graph = AnimationGraph::new("Strafe")
+        .float("x", 1.0)
+        .float("y", 0.0)
+        .state(AnimationState::blend_2d(
+            "Strafe",
+            ["x", "y"],
+            [("Idle", Vec2::ZERO), ("Walk", Vec2::X), ("Run", Vec2::Y)],
+        ));
+    let mut state = AnimationGraphState::default();
+
+    let samples = step(&mut state, &mut graph, &clips, 0.0);
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].clip_index, 1);
+
+    graph.set_float("x", 0.5);
+    let samples = step(&mut state, &mut graph, &clips, 0.0);
+    let total: f32 = samples.iter().map(|s| s.weight).sum();
+    assert!((total - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn trigger_transition_crossfades_and_is_consumed() {
+    let clips = clips();
+    let mut graph = locomotion_graph();
+    let mut state = AnimationGraphState::default();
+
+    step(&mut state, &mut graph, &clips, 0.1);
+    assert_eq!(state.current_state(), graph.state_index("Locomotion"));
+
+    assert!(graph.set_trigger("jump"));
+    step(&mut state, &mut graph, &clips, 0.1);
+    assert_eq!(state.current_state(), graph.state_index("Jump"));
+    assert!(state.is_transitioning());
+    assert_eq!(graph.get_bool("jump"), Some(false));
+
+    let samples = step(&mut state, &mut graph, &clips, 0.25);
+    let jump: f32 = samples
+        .iter()
+        .filter(|s| s.clip_index == 3)
+        .map(|s| s.weight)
+        .sum();
+    assert!((jump - 0.5).abs() < 1e-5);
+
+    step(&mut state, &mut graph, &clips, 0.25);
+    assert!(!state.is_transitioning());
+}
+
+#[test]
+fn exit_time_transition() {
+    let clips = clips();
+    let mut graph = locomotion_graph();
+    let mut state = AnimationGraphState::default();
+
+    graph.set_trigger("jump");
+    step(&mut state, &mut graph, &clips, 0.0);
+    assert_eq!(state.current_state(), graph.state_index("Jump"));
+
+    step(&mut state, &mut graph, &clips, 0.5);
+    assert_eq!(state.current_state(), graph.state_index("Jump"));
+
+    step(&mut state, &mut graph, &clips, 0.5);
+    step(&mut state, &mut graph, &clips, 0.1);
+    assert_eq!(state.current_state(), graph.state_index("Locomotion"));
+}
+
+#[test]
+fn graph_reflection_roundtrip() {
+    let graph = locomotion_graph();
+    let value = ReflectSerialize::serialize(&graph);
+
+    let mut restored = AnimationGraph::default();
+    ReflectDeserialize::apply(&mut restored, &value);
+
+    assert_eq!(restored.entry, "Locomotion");
+    assert_eq!(restored.states.len(), 2);
+    assert_eq!(restored.transitions.len(), 2);
+    assert_eq!(restored.get_float("speed"), Some(0.0));
+    assert_eq!(ReflectSerialize::serialize(&restored), value);
+}
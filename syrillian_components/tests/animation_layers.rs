@@ -0,0 +1,130 @@
+use std::thread::sleep;
+use std::time::Duration;
+use syrillian::World;
+use syrillian::assets::{AnimationChannel, AnimationClip, TransformKeys};
+use syrillian::core::GameObjectId;
+use syrillian::math::Vec3;
+use syrillian_components::AnimationComponent;
+use syrillian_components::animation::{BoneMask, LayerBlendMode};
+
+fn translation_channel(target: &str, times: &[f32], values: &[Vec3]) -> AnimationChannel {
+    AnimationChannel {
+        target_name: target.to_string(),
+        keys: TransformKeys {
+            t_times: times.to_vec(),
+            t_values: values.to_vec(),
+            ..TransformKeys::default()
+        },
+    }
+}
+
+fn clip(name: &str, channels: Vec<AnimationChannel>) -> AnimationClip {
+    AnimationClip {
+        name: name.to_string(),
+        duration: 1.0,
+        channels,
+        events: Vec::new(),
+    }
+}
+
+fn rig(world: &mut World) -> (GameObjectId, GameObjectId, GameObjectId) {
+    let mut root = world.new_object("Character");
+    let mut spine = world.new_object("Spine");
+    let arm = world.new_object("Arm");
+    let leg = world.new_object("Leg");
+
+    spine.add_child(arm);
+    root.add_child(spine);
+    root.add_child(leg);
+    world.add_child(root);
+
+    let mut animation = root.add_component::<AnimationComponent>();
+    animation.set_clips(vec![
+        clip(
+            "Walk",
+            vec![
+                translation_channel("Arm", &[0.0], &[Vec3::X]),
+                translation_channel("Leg", &[0.0], &[Vec3::X]),
+            ],
+        ),
+        clip(
+            "Shoot",
+            vec![
+                translation_channel("Arm", &[0.0], &[Vec3::Y]),
+                translation_channel("Leg", &[0.0], &[Vec3::Y]),
+            ],
+        ),
+        clip(
+            "Lean",
+            vec![translation_channel(
+                "Arm",
+                &[0.0, 1.0],
+                &[Vec3::ZERO, Vec3::Z],
+            )],
+        ),
+    ]);
+
+    (root, arm, leg)
+}
+
+fn run_frames(world: &mut World, frames: usize) {
+    for _ in 0..frames {
+        world.update();
+        world.next_frame();
+        sleep(Duration::from_millis(2));
+    }
+}
+
+#[test]
+fn masked_override_layer() {
+    let (mut world, ..) = World::fresh();
+    let (root, arm, leg) = rig(&mut world);
+
+    let mut animation = root.get_component::<AnimationComponent>().unwrap();
+    animation.play_by_name("Walk", true, 1.0, 1.0);
+    let upper = animation.add_layer(
+        "Upper Body",
+        LayerBlendMode::Override,
+        Some(BoneMask::new().subtree("Spine")),
+    );
+    assert!(animation.play_on_layer(upper, "Shoot", true, 1.0));
+
+    run_frames(&mut world, 1);
+
+    assert_eq!(*arm.transform.local_position(), Vec3::Y);
+    assert_eq!(*leg.transform.local_position(), Vec3::X);
+
+    animation.set_layer_weight(upper, 0.5);
+    run_frames(&mut world, 1);
+
+    assert!(
+        arm.transform
+            .local_position()
+            .abs_diff_eq(Vec3::new(0.5, 0.5, 0.0), 1e-5)
+    );
+    assert_eq!(*leg.transform.local_position(), Vec3::X);
+}
+
+#[test]
+fn additive_layer_adds_difference_to_first_frame() {
+    let (mut world, ..) = World::fresh();
+    let (root, arm, leg) = rig(&mut world);
+
+    let mut animation = root.get_component::<AnimationComponent>().unwrap();
+    animation.play_by_name("Walk", true, 1.0, 1.0);
+    let lean = animation.add_layer(
+        "Lean",
+        LayerBlendMode::Additive,
+        Some(BoneMask::new().bone("Arm")),
+    );
+    assert!(animation.play_on_layer(lean, "Lean", false, f32::MAX));
+
+    run_frames(&mut world, 2);
+
+    assert!(
+        arm.transform
+            .local_position()
+            .abs_diff_eq(Vec3::X + Vec3::Z, 1e-5)
+    );
+    assert_eq!(*leg.transform.local_position(), Vec3::X);
+}
@@ -0,0 +1,177 @@
+use syrillian::Reflect;
+use syrillian::World;
+use syrillian::components::{CRef, Component};
+use syrillian::math::{Quat, Vec2};
+use syrillian_components::inspector::{InspectorState, inspect_component};
+use syrillian_render::strobe::input::{HitRect, StrobeInputState};
+use syrillian_render::strobe::ui_element::Rect;
+use syrillian_render::strobe::{StrobeNode, UiBuilder};
+
+#[derive(Debug, Default, Reflect)]
+#[reflect(default)]
+struct Tunable {
+    #[reflect]
+    count: u32,
+    #[reflect]
+    speed: f32,
+    #[reflect]
+    rotation: Quat,
+    #[reflect]
+    name: String,
+}
+
+impl Component for Tunable {}
+
+const COUNT_ROW: usize = 0;
+const SPEED_ROW: usize = 1;
+const ROTATION_ROW: usize = 2;
+const NAME_ROW: usize = 3;
+
+fn tunable(world: &mut World) -> CRef<Tunable> {
+    world.new_object("Tunable").add_component::<Tunable>()
+}
+
+/// Lays out the inspector for one frame and returns whether it changed the component.
+fn inspect(
+    component: &CRef<dyn Component>,
+    state: &mut InspectorState,
+    input: &StrobeInputState,
+) -> (bool, StrobeNode) {
+    let mut root = StrobeNode::default();
+    let mut ui = UiBuilder::new_with_input(&mut root, Vec2::splat(500.0), input);
+    let changed = inspect_component(&mut ui, component, state);
+    (changed, root)
+}
+
+/// The id of the `index`th widget on the row of a reflected field, after its indent and label.
+fn widget_id(root: &StrobeNode, row: usize, index: usize) -> u32 {
+    root.children[row].children[2 + index].id
+}
+
+fn widget_ids(component: &CRef<dyn Component>) -> StrobeNode {
+    let mut state = InspectorState::default();
+    inspect(component, &mut state, &StrobeInputState::default()).1
+}
+
+/// Puts the pointer over `id` only, so every press lands on that widget.
+fn target(input: &mut StrobeInputState, id: u32) {
+    input.update_hit_rects(vec![HitRect {
+        rect: Rect::new(Vec2::ZERO, Vec2::splat(1000.0)),
+        node_id: id,
+    }]);
+}
+
+#[test]
+fn slow_integer_drags_accumulate() {
+    let (mut world, ..) = World::fresh();
+    let tunable = tunable(&mut world);
+    let component = tunable.as_dyn();
+    let count_id = widget_id(&widget_ids(&component), COUNT_ROW, 0);
+
+    let mut input = StrobeInputState::default();
+    let mut state = InspectorState::default();
+    target(&mut input, count_id);
+
+    let mut pointer = Vec2::new(100.0, 100.0);
+    input.begin_frame(pointer, true, true, false);
+    assert!(!inspect(&component, &mut state, &input).0);
+
+    let mut changed_frames = Vec::new();
+    for frame in 0..4 {
+        pointer.x += 0.3;
+        input.begin_frame(pointer, true, false, false);
+        if inspect(&component, &mut state, &input).0 {
+            changed_frames.push(frame);
+        }
+    }
+
+    // 0.3 px per frame only adds up to a whole step after a few frames
+    assert_eq!(changed_frames, [3]);
+    assert_eq!(tunable.count, 1);
+}
+
+#[test]
+fn float_drags_write_back() {
+    let (mut world, ..) = World::fresh();
+    let tunable = tunable(&mut world);
+    let component = tunable.as_dyn();
+    let speed_id = widget_id(&widget_ids(&component), SPEED_ROW, 0);
+
+    let mut input = StrobeInputState::default();
+    let mut state = InspectorState::default();
+    target(&mut input, speed_id);
+
+    input.begin_frame(Vec2::new(100.0, 100.0), true, true, false);
+    inspect(&component, &mut state, &input);
+    input.begin_frame(Vec2::new(150.0, 100.0), true, false, false);
+    assert!(inspect(&component, &mut state, &input).0);
+
+    assert!((tunable.speed - 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn rotation_is_edited_in_degrees() {
+    let (mut world, ..) = World::fresh();
+    let tunable = tunable(&mut world);
+    let component = tunable.as_dyn();
+    let pitch_id = widget_id(&widget_ids(&component), ROTATION_ROW, 0);
+
+    let mut input = StrobeInputState::default();
+    let mut state = InspectorState::default();
+    target(&mut input, pitch_id);
+
+    input.begin_frame(Vec2::new(100.0, 100.0), true, true, false);
+    inspect(&component, &mut state, &input);
+    input.begin_frame(Vec2::new(190.0, 100.0), true, false, false);
+    assert!(inspect(&component, &mut state, &input).0);
+
+    let expected = Quat::from_rotation_x(90f32.to_radians());
+    assert!(tunable.rotation.angle_between(expected) < 1e-3);
+}
+
+#[test]
+fn text_fields_edit_after_focus() {
+    let (mut world, ..) = World::fresh();
+    let tunable = tunable(&mut world);
+    let component = tunable.as_dyn();
+    let name_id = widget_id(&widget_ids(&component), NAME_ROW, 0);
+
+    let mut input = StrobeInputState::default();
+    let mut state = InspectorState::default();
+    target(&mut input, name_id);
+    let pointer = Vec2::new(100.0, 100.0);
+
+    // typing without focus does nothing
+    input.set_text_input("x");
+    input.begin_frame(pointer, false, false, false);
+    assert!(!inspect(&component, &mut state, &input).0);
+
+    input.set_text_input("");
+    input.begin_frame(pointer, true, true, false);
+    inspect(&component, &mut state, &input);
+    input.begin_frame(pointer, false, false, true);
+    inspect(&component, &mut state, &input);
+
+    input.set_text_input("abc");
+    input.begin_frame(pointer, false, false, false);
+    assert!(inspect(&component, &mut state, &input).0);
+    assert_eq!(tunable.name, "abc");
+
+    input.set_text_input("\u{8}");
+    input.begin_frame(pointer, false, false, false);
+    assert!(inspect(&component, &mut state, &input).0);
+    assert_eq!(tunable.name, "ab");
+
+    // releasing the pointer over something else drops the focus
+    input.set_text_input("");
+    input.update_hit_rects(Vec::new());
+    input.begin_frame(pointer, true, true, false);
+    inspect(&component, &mut state, &input);
+    input.begin_frame(pointer, false, false, true);
+    inspect(&component, &mut state, &input);
+
+    input.set_text_input("z");
+    input.begin_frame(pointer, false, false, false);
+    assert!(!inspect(&component, &mut state, &input).0);
+    assert_eq!(tunable.name, "ab");
+}
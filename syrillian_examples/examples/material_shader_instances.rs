@@ -173,6 +173,7 @@ fn wavy_material_layout() -> MaterialInputLayout {
                 default: HTexture2D::FALLBACK_DIFFUSE,
             },
         ],
+        permutation_keys: vec![],
     }
 }
 
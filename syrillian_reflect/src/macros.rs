@@ -1,13 +1,13 @@
 #[macro_export]
 macro_rules! reflect_type_info {
     (primitive, $type_name:ty) => {
-        ::syrillian_reflect::ReflectedTypeInfo {
+        $crate::ReflectedTypeInfo {
             type_id: std::any::TypeId::of::<$type_name>(),
             full_path: stringify!($type_name),
             name: stringify!($type_name),
-            actions: ::syrillian_reflect::ReflectedTypeActions {
-                serialize: ::syrillian_reflect::serialize_as::<$type_name>,
-                deserialize: ::syrillian_reflect::deserialize_as::<$type_name>,
+            actions: $crate::ReflectedTypeActions {
+                serialize: $crate::serialize_as::<$type_name>,
+                deserialize: $crate::deserialize_as::<$type_name>,
             },
             fields: &[],
             default_fn: None,
@@ -15,27 +15,27 @@ macro_rules! reflect_type_info {
     };
 
     (primitive, $type_name:ty, default) => {
-        ::syrillian_reflect::ReflectedTypeInfo {
+        $crate::ReflectedTypeInfo {
             type_id: std::any::TypeId::of::<$type_name>(),
             full_path: stringify!($type_name),
             name: stringify!($type_name),
-            actions: ::syrillian_reflect::ReflectedTypeActions {
-                serialize: ::syrillian_reflect::serialize_as::<$type_name>,
-                deserialize: ::syrillian_reflect::deserialize_as::<$type_name>,
+            actions: $crate::ReflectedTypeActions {
+                serialize: $crate::serialize_as::<$type_name>,
+                deserialize: $crate::deserialize_as::<$type_name>,
             },
             fields: &[],
-            default_fn: Some(::syrillian_reflect::default_as::<$type_name>),
+            default_fn: Some($crate::default_as::<$type_name>),
         }
     };
 
     ($path:path, $type_name:ty, $fields:expr) => {
-        ::syrillian_reflect::ReflectedTypeInfo {
+        $crate::ReflectedTypeInfo {
             type_id: std::any::TypeId::of::<$type_name>(),
             full_path: concat!(stringify!($path), "::", stringify!($type_name)),
             name: stringify!($type_name),
-            actions: ::syrillian_reflect::ReflectedTypeActions {
-                serialize: ::syrillian_reflect::serialize_as::<$type_name>,
-                deserialize: ::syrillian_reflect::deserialize_as::<$type_name>,
+            actions: $crate::ReflectedTypeActions {
+                serialize: $crate::serialize_as::<$type_name>,
+                deserialize: $crate::deserialize_as::<$type_name>,
             },
             fields: $fields,
             default_fn: None,
@@ -43,27 +43,27 @@ macro_rules! reflect_type_info {
     };
 
     ($path:path, $type_name:ty, $fields:expr, default) => {
-        ::syrillian_reflect::ReflectedTypeInfo {
+        $crate::ReflectedTypeInfo {
             type_id: std::any::TypeId::of::<$type_name>(),
             full_path: concat!(stringify!($path), "::", stringify!($type_name)),
             name: stringify!($type_name),
-            actions: ::syrillian_reflect::ReflectedTypeActions {
-                serialize: ::syrillian_reflect::serialize_as::<$type_name>,
-                deserialize: ::syrillian_reflect::deserialize_as::<$type_name>,
+            actions: $crate::ReflectedTypeActions {
+                serialize: $crate::serialize_as::<$type_name>,
+                deserialize: $crate::deserialize_as::<$type_name>,
             },
             fields: $fields,
-            default_fn: Some(::syrillian_reflect::default_as::<$type_name>),
+            default_fn: Some($crate::default_as::<$type_name>),
         }
     };
 
     ($path:path, $type_name:ty) => {
-        ::syrillian_reflect::ReflectedTypeInfo {
+        $crate::ReflectedTypeInfo {
             type_id: std::any::TypeId::of::<$type_name>(),
             full_path: concat!(stringify!($path), "::", stringify!($type_name)),
             name: stringify!($type_name),
-            actions: ::syrillian_reflect::ReflectedTypeActions {
-                serialize: ::syrillian_reflect::serialize_as::<$type_name>,
-                deserialize: ::syrillian_reflect::noop_deserialize,
+            actions: $crate::ReflectedTypeActions {
+                serialize: $crate::serialize_as::<$type_name>,
+                deserialize: $crate::noop_deserialize,
             },
             fields: &[],
             default_fn: None,
@@ -74,9 +74,9 @@ macro_rules! reflect_type_info {
 #[macro_export]
 macro_rules! impl_reflect {
     ($path:path, $type_name:ty, $fields:expr) => {
-        impl ::syrillian_reflect::PartialReflect for $type_name {
-            const DATA: ::syrillian_reflect::ReflectedTypeInfo =
-                ::syrillian_reflect::reflect_type_info!($path, $type_name, $fields);
+        impl $crate::PartialReflect for $type_name {
+            const DATA: $crate::ReflectedTypeInfo =
+                $crate::reflect_type_info!($path, $type_name, $fields);
         }
     };
 }
@@ -85,7 +85,7 @@ macro_rules! impl_reflect {
 macro_rules! impl_reflect_generic {
     ($path:path, $type_name:ident<[$( $generics:ty ),*]>, $fields:expr) => {
         $(
-            ::syrillian_reflect::impl_reflect!($path, $type_name<$generics>, $fields);
+            $crate::impl_reflect!($path, $type_name<$generics>, $fields);
         )*
     };
 }
@@ -93,7 +93,7 @@ macro_rules! impl_reflect_generic {
 #[macro_export]
 macro_rules! reflect_field {
     ($offset_type:ty, $name:ident, $field_type:ty) => {
-        ::syrillian_reflect::ReflectedField {
+        $crate::ReflectedField {
             name: stringify!($name),
             offset: std::mem::offset_of!($offset_type, $name),
             type_id: std::any::TypeId::of::<$field_type>(),
@@ -104,7 +104,7 @@ macro_rules! reflect_field {
 #[macro_export]
 macro_rules! register_type {
     ($( $type_info:tt )*) => {
-        ::syrillian_reflect::inventory::submit! {
+        $crate::inventory::submit! {
             $( $type_info )*
         }
     };
@@ -2,18 +2,27 @@
 //!
 //! For more information please see module level documentation.
 
-use crate::cache::generic_cache::Cache;
-use crate::cache::{FontAtlas, GpuTexture, RuntimeComputeShader, RuntimeMaterial, RuntimeShader};
+use crate::cache::generic_cache::{Cache, CacheType};
+use crate::cache::{
+    FontAtlas, GpuTexture, MipGenerator, RuntimeComputeShader, RuntimeMaterial, RuntimeShader,
+    ShaderCache,
+};
 use crate::rendering::mesh::RenderMesh;
 use crate::rendering::state::State;
 use crossbeam_channel::Receiver;
 use dashmap::DashMap;
+use std::borrow::Cow;
 use std::sync::Arc;
 use syrillian_asset::material_inputs::MaterialInputLayout;
 use syrillian_asset::store::streaming::asset_store::AssetType;
-use syrillian_asset::store::{AssetRefreshMessage, UpdateAssetMessage};
+use syrillian_asset::store::{AssetKey, AssetRefreshMessage, UpdateAssetMessage};
 use syrillian_asset::*;
-use wgpu::{BindGroupLayout, BindGroupLayoutDescriptor, Device};
+use syrillian_shadergen::MaterialPermutation;
+use tracing::warn;
+use wgpu::{
+    BindGroupLayout, BindGroupLayoutDescriptor, Device, PipelineCache, Queue, ShaderModule,
+    ShaderModuleDescriptor, ShaderSource,
+};
 
 pub struct AssetCache {
     pub meshes: Cache<Mesh>,
@@ -32,9 +41,22 @@ pub struct AssetCache {
     pub fonts: Cache<Font>,
 
     device: Device,
+    queue: Queue,
     assets_rx: Receiver<AssetRefreshMessage>,
 
     material_layouts: DashMap<u64, BindGroupLayout>,
+    /// Specialized color pass shaders by material and [`MaterialPermutation::key`].
+    material_variants: DashMap<(AssetKey, u64), MaterialVariant>,
+    shader_cache: Option<ShaderCache>,
+    mip_generator: MipGenerator,
+}
+
+/// A compiled color pass variant and the permutation it was compiled for, so it can be
+/// recompiled when its material changes.
+struct MaterialVariant {
+    permutation: MaterialPermutation,
+    /// `None` if the variant failed to compile
+    shader: Option<Arc<RuntimeShader>>,
 }
 
 impl AssetCache {
@@ -57,8 +79,12 @@ impl AssetCache {
             bgls: Cache::new(device.clone(), queue.clone()),
             fonts: Cache::new(device.clone(), queue.clone()),
             device: device.clone(),
+            queue: queue.clone(),
             assets_rx,
             material_layouts: DashMap::new(),
+            material_variants: DashMap::new(),
+            shader_cache: ShaderCache::for_device(device, &state.adapter.get_info()),
+            mip_generator: MipGenerator::new(device),
         }
     }
 
@@ -128,6 +154,30 @@ impl AssetCache {
         self.render_cubemaps.try_get(handle)
     }
 
+    /// Renders the mip chain of a render texture from its first level, e.g. after rendering
+    /// into it. Returns `false` if the texture isn't uploaded or has no mips.
+    pub fn generate_render_texture_mips(&self, handle: HRenderTexture2D) -> bool {
+        let Some(texture) = self.render_texture(handle) else {
+            return false;
+        };
+        if texture.texture.mip_level_count() <= 1 {
+            return false;
+        }
+
+        self.generate_mips(&texture);
+        true
+    }
+
+    /// Renders every mip level after the first of `texture` from the previous level
+    pub fn generate_mips(&self, texture: &GpuTexture) {
+        self.mip_generator.generate(
+            &self.device,
+            &self.queue,
+            &texture.texture,
+            self.pipeline_cache(),
+        );
+    }
+
     pub fn texture_fallback(&self) -> Arc<GpuTexture> {
         self.textures.get(HTexture2D::FALLBACK_DIFFUSE)
     }
@@ -177,6 +227,54 @@ impl AssetCache {
             .expect("Light is a default layout")
     }
 
+    pub fn bgl_light_cluster_compute(&self) -> BindGroupLayout {
+        self.bgls
+            .try_get(HBGL::LIGHT_CLUSTER_COMPUTE)
+            .expect("Light Cluster Compute is a default layout")
+    }
+
+    pub fn bgl_ibl_compute(&self) -> BindGroupLayout {
+        self.bgls
+            .try_get(HBGL::IBL_COMPUTE)
+            .expect("IBL Compute is a default layout")
+    }
+
+    pub fn bgl_reflection_probe_resolve(&self) -> BindGroupLayout {
+        self.bgls
+            .try_get(HBGL::REFLECTION_PROBE_RESOLVE)
+            .expect("Reflection Probe Resolve is a default layout")
+    }
+
+    pub fn bgl_hi_z_copy(&self) -> BindGroupLayout {
+        self.bgls
+            .try_get(HBGL::HI_Z_COPY)
+            .expect("Hi-Z Copy is a default layout")
+    }
+
+    pub fn bgl_hi_z_downsample(&self) -> BindGroupLayout {
+        self.bgls
+            .try_get(HBGL::HI_Z_DOWNSAMPLE)
+            .expect("Hi-Z Downsample is a default layout")
+    }
+
+    pub fn bgl_occlusion_test(&self) -> BindGroupLayout {
+        self.bgls
+            .try_get(HBGL::OCCLUSION_TEST)
+            .expect("Occlusion Test is a default layout")
+    }
+
+    pub fn bgl_decal(&self) -> BindGroupLayout {
+        self.bgls
+            .try_get(HBGL::DECAL)
+            .expect("Decal is a default layout")
+    }
+
+    pub fn bgl_decal_surface(&self) -> BindGroupLayout {
+        self.bgls
+            .try_get(HBGL::DECAL_SURFACE)
+            .expect("Decal Surface is a default layout")
+    }
+
     pub fn bgl_shadow(&self) -> BindGroupLayout {
         self.bgls
             .try_get(HBGL::SHADOW)
@@ -260,6 +358,102 @@ impl AssetCache {
         bgl
     }
 
+    /// The color pass of `material` specialized for `permutation`, if it was prewarmed.
+    /// Never compiles, so drawing doesn't stall on a miss; `None` if the variant wasn't
+    /// prewarmed, the material has no permutations or the variant doesn't compile.
+    pub fn material_variant(
+        &self,
+        material: HMaterial,
+        permutation: &MaterialPermutation,
+    ) -> Option<Arc<RuntimeShader>> {
+        let key = (AssetKey::from(material), permutation.key());
+        self.material_variants.get(&key)?.shader.clone()
+    }
+
+    /// Compiles the given variants of `material` ahead of time, e.g. while loading a level.
+    /// Material instances prewarm their own permutation when they're loaded.
+    pub fn prewarm_material(&self, material: HMaterial, permutations: &[MaterialPermutation]) {
+        for permutation in permutations {
+            let key = (AssetKey::from(material), permutation.key());
+            if self.material_variants.contains_key(&key) {
+                continue;
+            }
+
+            // Compiling takes a while, don't hold the map locked during it
+            let shader = self.compile_material_variant(material, permutation);
+            self.material_variants
+                .entry(key)
+                .or_insert(MaterialVariant {
+                    permutation: permutation.clone(),
+                    shader,
+                });
+        }
+    }
+
+    /// Number of specialized material shaders compiled so far.
+    pub fn material_variant_count(&self) -> usize {
+        self.material_variants
+            .iter()
+            .filter(|variant| variant.shader.is_some())
+            .count()
+    }
+
+    fn compile_material_variant(
+        &self,
+        handle: HMaterial,
+        permutation: &MaterialPermutation,
+    ) -> Option<Arc<RuntimeShader>> {
+        let material = self.materials.try_get(handle)?;
+        let code = match material.permutations()?.compile(permutation) {
+            Ok(code) => code,
+            Err(e) => {
+                warn!(
+                    "Failed to compile variant [{permutation}] of material {:?}: {e}",
+                    material.name()
+                );
+                return None;
+            }
+        };
+
+        let name = format!("{} (Base) [{permutation}]", material.name());
+        let shader = Shader::new_material(name, code, material.layout());
+        Some(Shader::upload(shader, &self.device, &self.queue, self))
+    }
+
+    /// The on-disk shader cache, `None` if it's disabled.
+    pub fn shader_cache(&self) -> Option<&ShaderCache> {
+        self.shader_cache.as_ref()
+    }
+
+    /// Creates a shader module for `code`, through the on-disk shader cache if it's enabled.
+    pub fn create_shader_module(&self, name: &str, code: String) -> ShaderModule {
+        match &self.shader_cache {
+            Some(disk_cache) => disk_cache.create_module(&self.device, name, code),
+            None => self.device.create_shader_module(ShaderModuleDescriptor {
+                label: Some(name),
+                source: ShaderSource::Wgsl(Cow::Owned(code)),
+            }),
+        }
+    }
+
+    /// The pipeline cache that pipelines should be created with, if the backend supports one.
+    pub fn pipeline_cache(&self) -> Option<&PipelineCache> {
+        self.shader_cache.as_ref()?.pipeline_cache()
+    }
+
+    /// Drops the variants of a material and returns the permutations they were compiled for.
+    fn invalidate_material_variants(&self, key: AssetKey) -> Vec<MaterialPermutation> {
+        let mut permutations = Vec::new();
+        self.material_variants.retain(|(material, _), variant| {
+            if *material != key {
+                return true;
+            }
+            permutations.push(variant.permutation.clone());
+            false
+        });
+        permutations
+    }
+
     pub fn font(&self, handle: HFont) -> Arc<FontAtlas> {
         self.fonts.get(handle)
     }
@@ -299,10 +493,26 @@ impl AssetCache {
                         self.render_cubemaps.refresh_item(key, texture, self)
                     }
                     UpdateAssetMessage::UpdateMaterial(material) => {
-                        self.materials.refresh_item(key, material, self)
+                        // Recompile the variants that were in use along with the prewarm list
+                        let mut prewarm = self.invalidate_material_variants(key);
+                        if let Some(permutations) = material.permutations() {
+                            prewarm.extend_from_slice(permutations.prewarm());
+                        }
+                        self.materials.refresh_item(key, material, self);
+                        self.prewarm_material(key.into(), &prewarm);
                     }
                     UpdateAssetMessage::UpdateMaterialInstance(material) => {
-                        self.material_instances.refresh_item(key, material, self)
+                        self.material_instances.refresh_item(key, material, self);
+                        // Compile the variant of the instance as it loads, drawing never
+                        // compiles and would use the base shader instead
+                        if let Some(instance) = self.material_instances.try_get(key.into())
+                            && let Some(permutation) = &instance.permutation
+                        {
+                            self.prewarm_material(
+                                instance.material,
+                                std::slice::from_ref(permutation),
+                            );
+                        }
                     }
                     UpdateAssetMessage::UpdateBGL(layout) => {
                         self.bgls.refresh_item(key, layout, self)
@@ -337,6 +547,7 @@ impl AssetCache {
                     self.render_cubemaps.remove(key)
                 }
                 AssetRefreshMessage::Deleted(key, AssetType::Material) => {
+                    self.invalidate_material_variants(key);
                     self.materials.remove(key)
                 }
                 AssetRefreshMessage::Deleted(key, AssetType::MaterialInstance) => {
@@ -348,5 +559,11 @@ impl AssetCache {
                 AssetRefreshMessage::Deleted(_, AssetType::Prefab) => {}
             }
         }
+
+        if let Some(shader_cache) = &self.shader_cache
+            && let Err(e) = shader_cache.flush()
+        {
+            warn!("Couldn't write shader cache: {e}");
+        }
     }
 }
@@ -1,12 +1,11 @@
 use crate::cache::AssetCache;
 use crate::cache::generic_cache::CacheType;
-use std::borrow::Cow;
 use std::sync::Arc;
 use syrillian_asset::ComputeShader;
 use syrillian_shadergen::ShaderGenerator;
 use wgpu::{
     ComputePipeline, ComputePipelineDescriptor, Device, PipelineCompilationOptions, Queue,
-    ShaderModule, ShaderModuleDescriptor, ShaderSource,
+    ShaderModule,
 };
 
 #[derive(Debug, Clone)]
@@ -23,10 +22,8 @@ impl CacheType for ComputeShader {
     #[profiling::function]
     fn upload(this: Self, device: &Device, _queue: &Queue, cache: &AssetCache) -> Self::Hot {
         let code = ShaderGenerator::assemble_compute_shader(this.code());
-        let module = device.create_shader_module(ShaderModuleDescriptor {
-            label: Some(this.name()),
-            source: ShaderSource::Wgsl(Cow::Owned(code)),
-        });
+
+        let module = cache.create_shader_module(this.name(), code);
 
         let bgls = this
             .bind_group_layouts()
@@ -51,8 +48,11 @@ impl CacheType for ComputeShader {
             module: &module,
             entry_point: Some(this.entry_point()),
             compilation_options: PipelineCompilationOptions::default(),
-            cache: None,
+            cache: cache.pipeline_cache(),
         });
+        if let Some(disk_cache) = cache.shader_cache() {
+            disk_cache.mark_dirty();
+        }
 
         Arc::new(RuntimeComputeShader {
             name: this.name().to_string(),
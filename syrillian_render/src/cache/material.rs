@@ -1,9 +1,10 @@
-use crate::cache::{AssetCache, CacheType, GpuTexture};
+use crate::cache::{AssetCache, CacheType, GpuTexture, RuntimeShader};
 use std::collections::HashMap;
 use std::sync::Arc;
-use syrillian_asset::MaterialShaderSet;
 use syrillian_asset::material_inputs::MaterialInputLayout;
+use syrillian_asset::{HMaterial, MaterialShaderSet};
 use syrillian_asset::{Material, MaterialInstance};
+use syrillian_shadergen::MaterialPermutation;
 use wgpu::{
     BindGroup, BindGroupDescriptor, BindGroupEntry, BindingResource, Device, Queue, TextureFormat,
 };
@@ -12,9 +13,24 @@ use wgpu::{
 pub struct RuntimeMaterial {
     pub immediates: Vec<u8>,
     pub bind_group: BindGroup,
+    pub material: HMaterial,
     pub shader_set: MaterialShaderSet,
+    /// Values of the material's permutation keys, if its color pass is specialized.
+    pub permutation: Option<MaterialPermutation>,
     pub transparent: bool,
     pub cast_shadows: bool,
+    pub double_sided: bool,
+}
+
+impl RuntimeMaterial {
+    /// The color pass shader, specialized for this instance's permutation when the
+    /// material supports it.
+    pub fn base_shader(&self, cache: &AssetCache) -> Arc<RuntimeShader> {
+        self.permutation
+            .as_ref()
+            .and_then(|permutation| cache.material_variant(self.material, permutation))
+            .unwrap_or_else(|| cache.shader(self.shader_set.base))
+    }
 }
 
 #[derive(Debug)]
@@ -86,16 +102,23 @@ impl CacheType for MaterialInstance {
         }
 
         let cast_shadows = this.value_bool("cast_shadows").unwrap_or(true);
+        let double_sided = this.value_bool("double_sided").unwrap_or(false);
+        let alpha_masked = this.value_f32("alpha_cutoff").unwrap_or(0.0) > 0.0;
 
         let alpha = this.value_f32("alpha").unwrap_or(1.0);
         let has_transparency_flag = this.value_bool("has_transparency").unwrap_or(false);
         let diffuse_has_transparency = texture_map
             .get("diffuse")
             .is_some_and(|t| t.has_transparency);
-        let transparent = alpha < 1.0 || has_transparency_flag || diffuse_has_transparency;
+        let transparent =
+            has_transparency_flag || (!alpha_masked && (alpha < 1.0 || diffuse_has_transparency));
 
         let immediates = layout.pack_immediates(&this.values);
 
+        let permutation = (material_def.permutations().is_some()
+            && !layout.permutation_keys.is_empty())
+        .then(|| layout.permutation(&this.values));
+
         let bgl = cache.material_layout(&layout);
         let mut entries: Vec<BindGroupEntry> = Vec::new();
         let mut binding = 0u32;
@@ -121,9 +144,12 @@ impl CacheType for MaterialInstance {
         Arc::new(RuntimeMaterial {
             immediates,
             bind_group,
+            material: this.material,
             shader_set,
+            permutation,
             transparent,
             cast_shadows,
+            double_sided,
         })
     }
 }
@@ -16,6 +16,7 @@ pub use self::font::*;
 pub use self::material::*;
 pub use self::render_mesh::mesh;
 pub use self::shader::builder::*;
+pub use self::shader::disk_cache::*;
 pub use self::shader::*;
 pub use self::texture::*;
 
@@ -8,7 +8,7 @@ use std::mem::size_of;
 use std::ops::Range;
 use std::sync::Arc;
 use syrillian_asset::mesh::static_mesh_data::{RawSkinningVertexBuffers, RawVertexBuffers};
-use syrillian_asset::mesh::{PartialMesh, SkinnedVertex3D};
+use syrillian_asset::mesh::{MorphTargets, PartialMesh, SkinnedVertex3D};
 use syrillian_asset::{Mesh, SkinnedMesh};
 use syrillian_utils::debug_panic;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
@@ -26,6 +26,9 @@ pub struct RenderVertexBuffers {
 #[derive(Debug, Clone)]
 pub struct PreSkinMeshlet {
     pub packed_input: wgpu::Buffer,
+    /// Vertex-major morph target deltas, a tiny placeholder if the mesh has no morph targets.
+    pub morph_deltas: wgpu::Buffer,
+    pub morph_target_count: u32,
 }
 
 #[derive(Debug)]
@@ -149,6 +152,7 @@ impl PreSkinMeshlet {
     pub fn upload(
         raw: &RawVertexBuffers,
         skinning: &RawSkinningVertexBuffers,
+        morph_targets: &MorphTargets,
         range: Range<usize>,
         device: &Device,
     ) -> Self {
@@ -166,6 +170,7 @@ impl PreSkinMeshlet {
         );
 
         let packed_vertices: Vec<SkinnedVertex3D> = range
+            .clone()
             .map(|idx| {
                 let position = raw.positions[idx];
                 let uv = raw.uvs[idx];
@@ -191,7 +196,23 @@ impl PreSkinMeshlet {
             usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
         });
 
-        Self { packed_input }
+        let mut packed_deltas = morph_targets.packed_deltas(range);
+        if packed_deltas.is_empty() {
+            // storage bindings can't be empty
+            packed_deltas.resize(4, 0.0);
+        }
+
+        let morph_deltas = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Mesh Morph Target Deltas Buffer"),
+            contents: packed_deltas.as_bytes(),
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        });
+
+        Self {
+            packed_input,
+            morph_deltas,
+            morph_target_count: morph_targets.len() as u32,
+        }
     }
 }
 
@@ -265,6 +286,17 @@ impl Meshlet {
         range: Range<u32>,
         pass: &mut wgpu::RenderPass<'_>,
         to_bind: BindMeshBuffers,
+    ) {
+        self.draw_instanced(range, 0..1, pass, to_bind);
+    }
+
+    /// Draws the points in `range` once for every instance in `instances`.
+    pub fn draw_instanced(
+        &self,
+        range: Range<u32>,
+        instances: Range<u32>,
+        pass: &mut wgpu::RenderPass<'_>,
+        to_bind: BindMeshBuffers,
     ) {
         let Some(inner_range) = self.clamp_point_range(range) else {
             debug_panic!("Meshlet received invalid draw command");
@@ -274,9 +306,9 @@ impl Meshlet {
         self.bind(pass, to_bind);
 
         if self.has_indices() {
-            pass.draw_indexed(inner_range, 0, 0..1);
+            pass.draw_indexed(inner_range, 0, instances);
         } else {
-            pass.draw(inner_range, 0..1);
+            pass.draw(inner_range, instances);
         }
     }
 
@@ -378,13 +410,25 @@ impl RenderMesh {
         range: Range<u32>,
         pass: &mut wgpu::RenderPass<'_>,
         bind_buffers: BindMeshBuffers,
+    ) {
+        self.draw_instanced(range, 0..1, pass, bind_buffers);
+    }
+
+    /// Draws the points in `range` once for every instance in `instances`. Instanced
+    /// shaders read the model data of each instance by its index.
+    pub fn draw_instanced(
+        &self,
+        range: Range<u32>,
+        instances: Range<u32>,
+        pass: &mut wgpu::RenderPass<'_>,
+        bind_buffers: BindMeshBuffers,
     ) {
         for meshlet in &self.meshlets {
             if range.end < meshlet.offset || range.start > meshlet.offset + meshlet.point_count() {
                 continue;
             }
 
-            meshlet.draw(range.clone(), pass, bind_buffers);
+            meshlet.draw_instanced(range.clone(), instances.clone(), pass, bind_buffers);
         }
     }
 
@@ -470,6 +514,7 @@ fn upload_skinned_mesh(msg: SkinnedMesh, device: &Device) -> Arc<RenderMesh> {
     let mut meshlets = Vec::new();
     let raw = msg.buffers();
     let skinning = msg.skinning_data.as_ref();
+    let morph_targets = msg.morph_targets.as_ref();
 
     if let Some(indices) = msg.indices() {
         if vertices_num > max_buffer_verts {
@@ -480,7 +525,8 @@ fn upload_skinned_mesh(msg: SkinnedMesh, device: &Device) -> Arc<RenderMesh> {
 
         let max_buffer_indices = max_buffer_size / size_of::<u32>();
         let vertex_buffers = RenderVertexBuffers::upload(raw, device);
-        let pre_skin = PreSkinMeshlet::upload(raw, skinning, 0..vertices_num, device);
+        let pre_skin =
+            PreSkinMeshlet::upload(raw, skinning, morph_targets, 0..vertices_num, device);
 
         for i in 0..=(indices_num / max_buffer_indices) {
             let start = i * max_buffer_indices;
@@ -513,7 +559,7 @@ fn upload_skinned_mesh(msg: SkinnedMesh, device: &Device) -> Arc<RenderMesh> {
             }
 
             let vertex_buffers = RenderVertexBuffers::upload(raw, device);
-            let pre_skin = PreSkinMeshlet::upload(raw, skinning, start..end, device);
+            let pre_skin = PreSkinMeshlet::upload(raw, skinning, morph_targets, start..end, device);
 
             meshlets.push(Meshlet {
                 vertex_buffers,
@@ -3,9 +3,9 @@ use syrillian_asset::shader::ShaderType;
 use syrillian_asset::shader::defaults::{DEFAULT_VBL, PICKING_COLOR_TARGET};
 use wgpu::{
     ColorTargetState, CompareFunction, DepthBiasState, DepthStencilState, Device, Face,
-    FragmentState, MultisampleState, PipelineCompilationOptions, PipelineLayout, PolygonMode,
-    PrimitiveState, PrimitiveTopology, RenderPipeline, RenderPipelineDescriptor, ShaderModule,
-    StencilFaceState, StencilState, TextureFormat, VertexBufferLayout, VertexState,
+    FragmentState, MultisampleState, PipelineCache, PipelineCompilationOptions, PipelineLayout,
+    PolygonMode, PrimitiveState, PrimitiveTopology, RenderPipeline, RenderPipelineDescriptor,
+    ShaderModule, StencilFaceState, StencilState, TextureFormat, VertexBufferLayout, VertexState,
 };
 
 const DEFAULT_DEPTH_STENCIL: DepthStencilState = DepthStencilState {
@@ -55,7 +55,9 @@ pub struct RenderPipelineBuilder<'a> {
     pub vertex_buffers: &'a [VertexBufferLayout<'a>],
     pub is_custom: bool,
     pub is_opaque: bool,
+    pub double_sided: bool,
     pub color_target: &'a [Option<ColorTargetState>],
+    pub cache: Option<&'a PipelineCache>,
 }
 
 impl<'a> RenderPipelineBuilder<'a> {
@@ -68,7 +70,7 @@ impl<'a> RenderPipelineBuilder<'a> {
     }
 
     pub fn cull_mode(&self) -> Option<Face> {
-        (self.is_opaque && !self.is_post_process).then_some(Face::Back)
+        (self.is_opaque && !self.is_post_process && !self.double_sided).then_some(Face::Back)
     }
 
     pub fn desc(&'a self) -> RenderPipelineDescriptor<'a> {
@@ -111,7 +113,7 @@ impl<'a> RenderPipelineBuilder<'a> {
             multisample: MultisampleState::default(),
             fragment,
             multiview_mask: None,
-            cache: None,
+            cache: self.cache,
         }
     }
 
@@ -147,7 +149,7 @@ impl<'a> RenderPipelineBuilder<'a> {
             multisample: MultisampleState::default(),
             fragment,
             multiview_mask: None,
-            cache: None,
+            cache: self.cache,
         })
     }
 
@@ -194,10 +196,12 @@ impl<'a> RenderPipelineBuilder<'a> {
             has_depth,
             is_custom,
             is_opaque,
+            double_sided: false,
             polygon_mode,
             topology,
             vertex_buffers,
             color_target,
+            cache: None,
         }
     }
 }
@@ -0,0 +1,473 @@
+//! On-disk cache for shader modules and compiled pipelines.
+//!
+//! The [`ShaderCache`] remembers which generated WGSL modules already passed naga
+//! validation. wgpu validates every module it creates, but modules seen before are created
+//! as trusted, without the loop bounds wgpu otherwise adds to every loop. On backends that
+//! support it, it also keeps wgpu's [`PipelineCache`], which lets the driver skip
+//! recompiling pipelines it has seen before.
+//!
+//! Entries live in a directory keyed by the adapter, its driver, the wgpu version and
+//! the engine version. Opening the cache with a different key removes the directories
+//! of other keys, so updating the driver, wgpu or the engine starts over with an empty
+//! cache. Least recently used modules are evicted once the cache grows past its size cap,
+//! and pipeline data that doesn't fit on its own is thrown away to be rebuilt.
+
+use parking_lot::Mutex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use syrillian_asset::shader::checks::validate_wgsl_source;
+use syrillian_utils::EngineArgs;
+use tracing::{debug, error, warn};
+use wgpu::{
+    AdapterInfo, Device, Features, PipelineCache, PipelineCacheDescriptor, ShaderModule,
+    ShaderModuleDescriptor, ShaderRuntimeChecks, ShaderSource,
+};
+
+/// Major version of the wgpu dependency, pipeline data isn't portable between versions.
+/// Bump this together with the dependency.
+const WGPU_VERSION: &str = "28";
+const INDEX_FILE: &str = "index";
+const INDEX_HEADER: &str = "syrillian-shader-cache 1";
+const MODULE_DIR: &str = "modules";
+const MODULE_EXTENSION: &str = "wgsl";
+
+/// What [`ShaderCache::check_module`] found out about a module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModuleStatus {
+    /// The module passed validation on an earlier check
+    Cached,
+    /// The module passed validation now and was remembered
+    Validated,
+    /// The module failed validation
+    Invalid,
+}
+
+struct ModuleEntry {
+    size: u64,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheIndex {
+    modules: HashMap<u64, ModuleEntry>,
+    tick: u64,
+}
+
+impl CacheIndex {
+    fn load(dir: &Path) -> Self {
+        let mut index = CacheIndex::default();
+        let Ok(text) = fs::read_to_string(dir.join(INDEX_FILE)) else {
+            return index;
+        };
+
+        let mut lines = text.lines();
+        if lines.next() != Some(INDEX_HEADER) {
+            return index;
+        }
+
+        for line in lines {
+            let mut parts = line.split_whitespace();
+            let (Some(hash), Some(size), Some(last_used)) = (
+                parts.next().and_then(|h| u64::from_str_radix(h, 16).ok()),
+                parts.next().and_then(|s| s.parse().ok()),
+                parts.next().and_then(|t| t.parse().ok()),
+            ) else {
+                continue;
+            };
+
+            index.tick = index.tick.max(last_used);
+            index.modules.insert(hash, ModuleEntry { size, last_used });
+        }
+
+        index
+    }
+
+    fn save(&self, dir: &Path) -> io::Result<()> {
+        let mut text = String::from(INDEX_HEADER);
+        text.push('\n');
+        for (hash, entry) in &self.modules {
+            text.push_str(&format!("{hash:016x} {} {}\n", entry.size, entry.last_used));
+        }
+        write_atomic(&dir.join(INDEX_FILE), text.as_bytes())
+    }
+
+    fn size(&self) -> u64 {
+        self.modules.values().map(|entry| entry.size).sum()
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn least_recently_used(&self) -> Option<u64> {
+        self.modules
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(hash, _)| *hash)
+    }
+}
+
+/// Persistent cache of validated shader modules and driver pipeline data.
+/// Changes are written on [`flush`](Self::flush) and when the cache is dropped.
+pub struct ShaderCache {
+    dir: PathBuf,
+    max_size: u64,
+    index: Mutex<CacheIndex>,
+    /// The wgpu pipeline cache and the name of the file it's stored in
+    pipeline_cache: Option<(PipelineCache, String)>,
+    dirty: AtomicBool,
+}
+
+impl ShaderCache {
+    pub const DEFAULT_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+    /// Opens the cache for `device` in the [default directory](Self::default_dir).
+    /// Returns `None` if it was disabled with `--no-shader-cache` or can't be opened.
+    pub fn for_device(device: &Device, adapter: &AdapterInfo) -> Option<Self> {
+        if EngineArgs::get().no_shader_cache {
+            debug!("Shader cache disabled");
+            return None;
+        }
+
+        let root = Self::default_dir()?;
+        let key = Self::adapter_key(adapter);
+        let mut cache = match Self::open(&root, &key, Self::DEFAULT_MAX_SIZE) {
+            Ok(cache) => cache,
+            Err(e) => {
+                warn!("Couldn't open shader cache in {}: {e}", root.display());
+                return None;
+            }
+        };
+
+        cache.load_pipeline_cache(device, adapter);
+        Some(cache)
+    }
+
+    /// The platform's cache directory, e.g. `~/.cache/syrillian/shaders` on Linux.
+    pub fn default_dir() -> Option<PathBuf> {
+        if cfg!(target_arch = "wasm32") {
+            return None;
+        }
+
+        let var = |name| {
+            std::env::var_os(name)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        let base = if cfg!(windows) {
+            var("LOCALAPPDATA")?
+        } else if cfg!(target_os = "macos") {
+            var("HOME")?.join("Library").join("Caches")
+        } else {
+            var("XDG_CACHE_HOME").or_else(|| Some(var("HOME")?.join(".cache")))?
+        };
+
+        Some(base.join("syrillian").join("shaders"))
+    }
+
+    /// Identifies the adapter, its driver, and the wgpu and engine versions.
+    pub fn adapter_key(info: &AdapterInfo) -> String {
+        let key = format!(
+            "{}|{}|{}|{}|{}|{:?}|wgpu {WGPU_VERSION}|syrillian {}",
+            info.name,
+            info.vendor,
+            info.device,
+            info.driver,
+            info.driver_info,
+            info.backend,
+            env!("CARGO_PKG_VERSION"),
+        );
+        format!("{:016x}", stable_hash(key.as_bytes()))
+    }
+
+    /// Opens the cache stored under `root` for `key`, removing caches of other keys.
+    pub fn open(root: &Path, key: &str, max_size: u64) -> io::Result<Self> {
+        let dir = root.join(key);
+        fs::create_dir_all(dir.join(MODULE_DIR))?;
+        remove_stale_caches(root, key);
+
+        let mut index = CacheIndex::load(&dir);
+        index
+            .modules
+            .retain(|hash, _| module_path(&dir, *hash).is_file());
+        remove_orphaned_modules(&dir, &index);
+
+        Ok(Self {
+            dir,
+            max_size,
+            index: Mutex::new(index),
+            pipeline_cache: None,
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    fn load_pipeline_cache(&mut self, device: &Device, adapter: &AdapterInfo) {
+        if !device.features().contains(Features::PIPELINE_CACHE) {
+            return;
+        }
+        let Some(file) = wgpu::util::pipeline_cache_key(adapter) else {
+            return;
+        };
+
+        let data = fs::read(self.dir.join(&file)).ok();
+        // SAFETY: The cache directory is keyed by adapter, driver and wgpu version, so the
+        // data was returned by `PipelineCache::get_data` for this adapter. wgpu also checks
+        // its header and starts with an empty cache if it doesn't match.
+        let cache = unsafe {
+            device.create_pipeline_cache(&PipelineCacheDescriptor {
+                label: Some("Pipeline Cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+
+        self.pipeline_cache = Some((cache, file));
+    }
+
+    /// The wgpu pipeline cache, if the backend supports one.
+    pub fn pipeline_cache(&self) -> Option<&PipelineCache> {
+        self.pipeline_cache.as_ref().map(|(cache, _)| cache)
+    }
+
+    /// Creates the shader module for `code`. Modules that passed validation before are
+    /// created as trusted, new ones are [checked](Self::check_module) and fully guarded.
+    pub fn create_module(&self, device: &Device, name: &str, code: String) -> ShaderModule {
+        let status = self.check_module(name, &code);
+        let desc = ShaderModuleDescriptor {
+            label: Some(name),
+            source: ShaderSource::Wgsl(Cow::Owned(code)),
+        };
+
+        if status != ModuleStatus::Cached {
+            return device.create_shader_module(desc);
+        }
+
+        // Bounds stay checked, only the loop bounding wgpu injects into every loop is left out
+        let checks = ShaderRuntimeChecks {
+            force_loop_bounding: false,
+            ..ShaderRuntimeChecks::checked()
+        };
+        // SAFETY: Only loop bounding is left out, which requires loops to terminate. The exact
+        // same source already passed validation and ran with bounded loops on this adapter and
+        // wgpu version.
+        unsafe { device.create_shader_module_trusted(desc, checks) }
+    }
+
+    /// Whether `code` is a valid shader module. Modules that passed validation before are
+    /// trusted, new ones are validated and remembered. Errors are logged for `name`.
+    pub fn check_module(&self, name: &str, code: &str) -> ModuleStatus {
+        let hash = stable_hash(code.as_bytes());
+        self.dirty.store(true, Ordering::Release);
+
+        if self.is_cached(hash, code) {
+            let mut index = self.index.lock();
+            let tick = index.next_tick();
+            if let Some(entry) = index.modules.get_mut(&hash) {
+                entry.last_used = tick;
+            }
+            return ModuleStatus::Cached;
+        }
+
+        if let Err(e) = validate_wgsl_source(code) {
+            error!(
+                "Shader {name:?} failed validation:\n{}",
+                e.emit_to_string(code)
+            );
+            return ModuleStatus::Invalid;
+        }
+
+        if let Err(e) = write_atomic(&module_path(&self.dir, hash), code.as_bytes()) {
+            warn!("Couldn't cache shader {name:?}: {e}");
+            return ModuleStatus::Validated;
+        }
+
+        let mut index = self.index.lock();
+        let last_used = index.next_tick();
+        index.modules.insert(
+            hash,
+            ModuleEntry {
+                size: code.len() as u64,
+                last_used,
+            },
+        );
+        ModuleStatus::Validated
+    }
+
+    /// Whether `code` passed validation before.
+    pub fn contains_module(&self, code: &str) -> bool {
+        self.is_cached(stable_hash(code.as_bytes()), code)
+    }
+
+    fn is_cached(&self, hash: u64, code: &str) -> bool {
+        if !self.index.lock().modules.contains_key(&hash) {
+            return false;
+        }
+        // Compare the stored module so hash collisions can't skip validation
+        fs::read(module_path(&self.dir, hash)).is_ok_and(|stored| stored == code.as_bytes())
+    }
+
+    pub fn module_count(&self) -> usize {
+        self.index.lock().modules.len()
+    }
+
+    /// Size of all cached modules in bytes.
+    pub fn size(&self) -> u64 {
+        self.index.lock().size()
+    }
+
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Notes that pipelines were created, which may have added to the pipeline cache.
+    pub fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// The directory this cache's files are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Evicts least recently used modules until the cache fits its size cap, then writes
+    /// the index and pipeline data. Pipeline data that's larger than the cap by itself is
+    /// removed, so the next launch rebuilds it from the pipelines it actually uses.
+    /// Does nothing if nothing changed.
+    pub fn flush(&self) -> io::Result<()> {
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        let mut pipeline_data = self
+            .pipeline_cache
+            .as_ref()
+            .and_then(|(cache, file)| Some((cache.get_data()?, file)));
+        if let Some((data, file)) = &pipeline_data
+            && data.len() as u64 > self.max_size
+        {
+            warn!(
+                "Pipeline cache grew to {} bytes, past the {} byte cap. Starting over",
+                data.len(),
+                self.max_size
+            );
+            remove_file(&self.dir.join(file))?;
+            pipeline_data = None;
+        }
+        let pipeline_size = pipeline_data
+            .as_ref()
+            .map_or(0, |(data, _)| data.len() as u64);
+
+        let mut index = self.index.lock();
+        while index.size() + pipeline_size > self.max_size {
+            let Some(hash) = index.least_recently_used() else {
+                break;
+            };
+            index.modules.remove(&hash);
+            remove_file(&module_path(&self.dir, hash))?;
+        }
+
+        if let Some((data, file)) = pipeline_data {
+            write_atomic(&self.dir.join(file), &data)?;
+        }
+        index.save(&self.dir)
+    }
+
+    /// Removes all cached modules and pipeline data.
+    pub fn clear(&self) -> io::Result<()> {
+        let mut index = self.index.lock();
+        *index = CacheIndex::default();
+
+        fs::remove_dir_all(self.dir.join(MODULE_DIR))?;
+        fs::create_dir_all(self.dir.join(MODULE_DIR))?;
+        if let Some((_, file)) = &self.pipeline_cache {
+            remove_file(&self.dir.join(file))?;
+        }
+
+        self.dirty.store(false, Ordering::Release);
+        index.save(&self.dir)
+    }
+}
+
+impl Drop for ShaderCache {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Couldn't write shader cache: {e}");
+        }
+    }
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is stable across Rust versions.
+fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn module_path(dir: &Path, hash: u64) -> PathBuf {
+    dir.join(MODULE_DIR)
+        .join(format!("{hash:016x}.{MODULE_EXTENSION}"))
+}
+
+/// Writes through a temporary file, so an interrupted write never leaves a partial file.
+fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let temp = path.with_extension("temp");
+    fs::write(&temp, data)?;
+    fs::rename(&temp, path)
+}
+
+/// Removes a file, which is fine if it's already gone.
+fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn is_cache_key(name: &str) -> bool {
+    name.len() == 16 && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn remove_stale_caches(root: &Path, key: &str) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if name == key || !is_cache_key(name) || !entry.path().is_dir() {
+            continue;
+        }
+
+        debug!("Removing outdated shader cache {name}");
+        if let Err(e) = fs::remove_dir_all(entry.path()) {
+            warn!("Couldn't remove outdated shader cache {name}: {e}");
+        }
+    }
+}
+
+fn remove_orphaned_modules(dir: &Path, index: &CacheIndex) {
+    let Ok(entries) = fs::read_dir(dir.join(MODULE_DIR)) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hash = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|_| path.extension().is_some_and(|ext| ext == MODULE_EXTENSION))
+            .and_then(|stem| u64::from_str_radix(stem, 16).ok());
+
+        if hash.is_none_or(|hash| !index.modules.contains_key(&hash)) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
@@ -3,24 +3,28 @@ use crate::cache::shader::bindings::ShaderBindings;
 use crate::cache::{AssetCache, RenderPipelineBuilder};
 use crate::rendering::GPUDrawCtx;
 use crate::strobe::UiDrawContext;
-use std::borrow::Cow;
 use std::sync::Arc;
-use syrillian_asset::Shader;
 use syrillian_asset::shader::{BindGroupMap, ShaderType};
+use syrillian_asset::{HShader, Shader};
+use syrillian_shadergen::generator::ShaderGenerator;
+use tracing::error;
 use wgpu::*;
 
 mod bindings;
 pub mod builder;
+pub mod disk_cache;
 
 #[derive(Debug, Clone)]
 pub struct RuntimeShader {
     name: String,
     pub module: ShaderModule,
     pipeline: RenderPipeline,
+    double_sided_pipeline: Option<RenderPipeline>,
     pub immediate_size: u32,
     bind_groups: BindGroupMap,
     pub shader_type: ShaderType,
     opaque: bool,
+    instanced: bool,
 }
 
 impl CacheType for Shader {
@@ -30,7 +34,17 @@ impl CacheType for Shader {
     #[profiling::function]
     fn upload(this: Self, device: &Device, _queue: &Queue, cache: &AssetCache) -> Self::Hot {
         let bind_groups = this.bind_group_map();
-        let code = this.gen_code_with_map(&bind_groups);
+        let code = match this.try_gen_code_with_map(&bind_groups) {
+            Ok(code) => code,
+            Err(e) => {
+                // The error names the line in the shader or imported module it came from
+                error!(
+                    "Shader {:?} couldn't be preprocessed, drawing with the fallback shader: {e}",
+                    this.name()
+                );
+                return fallback_for(&this, cache);
+            }
+        };
 
         debug_assert!(
             code.contains("@fragment") || this.stage() == ShaderType::Shadow,
@@ -44,28 +58,48 @@ impl CacheType for Shader {
             this.name()
         );
 
-        let module = device.create_shader_module(ShaderModuleDescriptor {
-            label: Some(this.name()),
-            source: ShaderSource::Wgsl(Cow::Owned(code)),
-        });
+        let instanced = ShaderGenerator::is_instanced(&code);
+        let module = cache.create_shader_module(this.name(), code);
         let name = this.name().to_string();
 
         let solid_layout = this.pipeline_layout(device, cache);
-        let solid_builder = RenderPipelineBuilder::builder(&this, &solid_layout, &module);
+        let mut solid_builder = RenderPipelineBuilder::builder(&this, &solid_layout, &module);
+        solid_builder.cache = cache.pipeline_cache();
         let pipeline = solid_builder.build(device);
 
+        // Culling pipelines get a no-cull twin for double-sided materials
+        let double_sided_pipeline = solid_builder.cull_mode().is_some().then(|| {
+            solid_builder.double_sided = true;
+            solid_builder.label.push_str(" (Double Sided)");
+            solid_builder.build(device)
+        });
+        if let Some(disk_cache) = cache.shader_cache() {
+            disk_cache.mark_dirty();
+        }
+
         Arc::new(RuntimeShader {
             name,
             module,
             pipeline,
+            double_sided_pipeline,
             immediate_size: this.immediate_size(),
             bind_groups,
             shader_type: this.stage(),
             opaque: this.is_opaque(),
+            instanced,
         })
     }
 }
 
+/// The built-in shader drawn in place of `shader` when it can't be used.
+fn fallback_for(shader: &Shader, cache: &AssetCache) -> Arc<RuntimeShader> {
+    if shader.is_post_process() {
+        cache.shaders.get(HShader::POST_PROCESS)
+    } else {
+        cache.shaders.get(HShader::FALLBACK)
+    }
+}
+
 impl RuntimeShader {
     pub fn solid_pipeline(&self) -> &RenderPipeline {
         &self.pipeline
@@ -79,8 +113,28 @@ impl RuntimeShader {
         self.opaque
     }
 
+    /// Whether the shader reads its model data per instance, so it can draw a whole
+    /// batch of objects from one instance buffer.
+    pub fn is_instanced(&self) -> bool {
+        self.instanced
+    }
+
+    pub fn double_sided_pipeline(&self) -> &RenderPipeline {
+        self.double_sided_pipeline
+            .as_ref()
+            .unwrap_or(&self.pipeline)
+    }
+
     pub fn activate(&self, pass: &mut RenderPass, ctx: &GPUDrawCtx) {
-        pass.set_pipeline(&self.pipeline);
+        self.activate_sided(pass, ctx, false);
+    }
+
+    pub fn activate_sided(&self, pass: &mut RenderPass, ctx: &GPUDrawCtx, double_sided: bool) {
+        if double_sided {
+            pass.set_pipeline(self.double_sided_pipeline());
+        } else {
+            pass.set_pipeline(&self.pipeline);
+        }
         pass.set_bind_group(self.bind_groups.render, ctx.render_bind_group, &[]);
         if let Some(light) = self.bind_groups.light {
             pass.set_bind_group(light, ctx.light_bind_group, &[]);
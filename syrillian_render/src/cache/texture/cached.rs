@@ -43,7 +43,12 @@ impl<T: TextureAsset> CacheType for T {
     type Hot = Arc<GpuTexture>;
     type UpdateMessage = Self;
 
-    fn upload(this: Self, device: &Device, queue: &Queue, _cache: &AssetCache) -> Self::Hot {
-        this.upload(device, queue)
+    fn upload(this: Self, device: &Device, queue: &Queue, cache: &AssetCache) -> Self::Hot {
+        let generates_mips = this.generates_mips();
+        let texture = this.upload(device, queue);
+        if generates_mips {
+            cache.generate_mips(&texture);
+        }
+        texture
     }
 }
@@ -0,0 +1,25 @@
+// Renders one mip level from the previous one. A bilinear tap at the center of every
+// destination texel averages the 2x2 source texels it covers. Views of sRGB textures
+// decode when sampled and encode when written, so the average is taken in linear space.
+@group(0) @binding(0) var src_level: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+
+struct VOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VOut {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VOut;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VOut) -> @location(0) vec4<f32> {
+    return textureSampleLevel(src_level, src_sampler, in.uv, 0.0);
+}
@@ -0,0 +1,343 @@
+use dashmap::DashMap;
+use std::borrow::Cow;
+use syrillian_asset::texture_compression::mips;
+use wgpu::{
+    AddressMode, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Color, ColorTargetState, ColorWrites,
+    CommandEncoderDescriptor, Device, Features, FilterMode, FragmentState, LoadOp,
+    MipmapFilterMode, MultisampleState, Operations, PipelineCache, PipelineCompilationOptions,
+    PipelineLayout, PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerBindingType,
+    SamplerDescriptor, ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, StoreOp,
+    Texture, TextureFormat, TextureFormatFeatureFlags, TextureSampleType, TextureUsages,
+    TextureViewDescriptor, TextureViewDimension, VertexState,
+};
+
+const MIP_DOWNSAMPLE: &str = include_str!("mip_downsample.wgsl");
+
+/// Fills the mip chain of textures on the GPU by rendering every level from the previous one.
+///
+/// Levels are rendered through views in the format of the texture, so sRGB textures are
+/// filtered in linear space.
+pub struct MipGenerator {
+    module: ShaderModule,
+    bgl: BindGroupLayout,
+    layout: PipelineLayout,
+    sampler: Sampler,
+    pipelines: DashMap<TextureFormat, RenderPipeline>,
+}
+
+impl MipGenerator {
+    pub fn new(device: &Device) -> Self {
+        let module = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Mip Downsample Shader"),
+            source: ShaderSource::Wgsl(Cow::Borrowed(MIP_DOWNSAMPLE)),
+        });
+
+        let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Mip Downsample Bind Group Layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Mip Downsample Pipeline Layout"),
+            bind_group_layouts: &[&bgl],
+            immediate_size: 0,
+        });
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("Mip Downsample Sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: MipmapFilterMode::Nearest,
+            ..SamplerDescriptor::default()
+        });
+
+        Self {
+            module,
+            bgl,
+            layout,
+            sampler,
+            pipelines: DashMap::new(),
+        }
+    }
+
+    /// Whether mips of textures in `format` can be generated on every device
+    pub fn supports_format(format: TextureFormat) -> bool {
+        let features = format.guaranteed_format_features(Features::empty());
+        features
+            .allowed_usages
+            .contains(TextureUsages::RENDER_ATTACHMENT)
+            && features
+                .flags
+                .contains(TextureFormatFeatureFlags::FILTERABLE)
+    }
+
+    /// Amount of levels in a full mip chain of a `width` x `height` texture
+    pub fn full_chain_len(width: u32, height: u32) -> u32 {
+        mips::mip_level_count(width, height)
+    }
+
+    /// Renders every level after the first of each layer of `texture` from the previous level.
+    ///
+    /// The texture needs `TEXTURE_BINDING` and `RENDER_ATTACHMENT` usages and a format that
+    /// [`MipGenerator::supports_format`]. Textures with a single level are left untouched.
+    pub fn generate(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        texture: &Texture,
+        cache: Option<&PipelineCache>,
+    ) {
+        profiling::function_scope!("generate mips");
+
+        if texture.mip_level_count() <= 1 {
+            return;
+        }
+
+        let format = texture.format();
+        debug_assert!(
+            Self::supports_format(format),
+            "Mips of {format:?} textures can't be rendered"
+        );
+
+        let pipeline = self.pipeline(device, format, cache);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Mip Generation Encoder"),
+        });
+
+        for layer in 0..texture.depth_or_array_layers() {
+            for level in 1..texture.mip_level_count() {
+                let level_view = |mip| {
+                    texture.create_view(&TextureViewDescriptor {
+                        label: Some("Mip Level View"),
+                        dimension: Some(TextureViewDimension::D2),
+                        base_mip_level: mip,
+                        mip_level_count: Some(1),
+                        base_array_layer: layer,
+                        array_layer_count: Some(1),
+                        ..TextureViewDescriptor::default()
+                    })
+                };
+                let src = level_view(level - 1);
+                let dst = level_view(level);
+
+                let bind_group = device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("Mip Downsample Bind Group"),
+                    layout: &self.bgl,
+                    entries: &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::TextureView(&src),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::Sampler(&self.sampler),
+                        },
+                    ],
+                });
+
+                let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                    label: Some("Mip Downsample Pass"),
+                    color_attachments: &[Some(RenderPassColorAttachment {
+                        view: &dst,
+                        depth_slice: None,
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(Color::TRANSPARENT),
+                            store: StoreOp::Store,
+                        },
+                    })],
+                    ..RenderPassDescriptor::default()
+                });
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+
+    fn pipeline(
+        &self,
+        device: &Device,
+        format: TextureFormat,
+        cache: Option<&PipelineCache>,
+    ) -> RenderPipeline {
+        self.pipelines
+            .entry(format)
+            .or_insert_with(|| {
+                device.create_render_pipeline(&RenderPipelineDescriptor {
+                    label: Some(&format!("Mip Downsample {format:?} Pipeline")),
+                    layout: Some(&self.layout),
+                    vertex: VertexState {
+                        module: &self.module,
+                        entry_point: Some("vs_main"),
+                        compilation_options: PipelineCompilationOptions::default(),
+                        buffers: &[],
+                    },
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    fragment: Some(FragmentState {
+                        module: &self.module,
+                        entry_point: Some("fs_main"),
+                        compilation_options: PipelineCompilationOptions::default(),
+                        targets: &[Some(ColorTargetState {
+                            format,
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    multiview_mask: None,
+                    cache,
+                })
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syrillian_asset::shader::checks::validate_wgsl_source;
+    use syrillian_asset::texture_compression::TextureUsage;
+    use wgpu::{
+        BufferDescriptor, BufferUsages, DeviceDescriptor, Extent3d, Instance, MapMode, Origin3d,
+        PollType, TexelCopyBufferInfo, TexelCopyBufferLayout, TexelCopyTextureInfo, TextureAspect,
+        TextureDescriptor, TextureDimension,
+    };
+
+    fn gpu() -> Option<(Device, Queue)> {
+        futures::executor::block_on(async {
+            let adapter = Instance::default()
+                .request_adapter(&Default::default())
+                .await
+                .ok()?;
+            adapter
+                .request_device(&DeviceDescriptor::default())
+                .await
+                .ok()
+        })
+    }
+
+    fn read_pixel(device: &Device, queue: &Queue, texture: &Texture, mip_level: u32) -> [u8; 4] {
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            TexelCopyTextureInfo {
+                texture,
+                mip_level,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: TexelCopyBufferLayout::default(),
+            },
+            Extent3d::default(),
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(MapMode::Read, |res| res.unwrap());
+        device.poll(PollType::wait_indefinitely()).unwrap();
+        let data = slice.get_mapped_range();
+        data[..4].try_into().unwrap()
+    }
+
+    #[test]
+    fn downsample_shader_is_valid() {
+        validate_wgsl_source(MIP_DOWNSAMPLE).unwrap();
+    }
+
+    #[test]
+    fn color_formats_are_supported() {
+        assert!(MipGenerator::supports_format(TextureFormat::Rgba8UnormSrgb));
+        assert!(MipGenerator::supports_format(TextureFormat::Bgra8UnormSrgb));
+        assert!(MipGenerator::supports_format(TextureFormat::Rgba16Float));
+        assert!(!MipGenerator::supports_format(
+            TextureFormat::Bc1RgbaUnormSrgb
+        ));
+        assert!(!MipGenerator::supports_format(TextureFormat::Rgba8Uint));
+        assert_eq!(MipGenerator::full_chain_len(256, 64), 9);
+    }
+
+    #[test]
+    fn srgb_mips_average_in_linear_space() {
+        let Some((device, queue)) = gpu() else {
+            eprintln!("No GPU adapter available, skipping");
+            return;
+        };
+
+        #[rustfmt::skip]
+        let pixels = [
+            255, 0, 0, 255,   0, 0, 0, 255,
+            0, 255, 128, 255, 0, 0, 255, 255,
+        ];
+
+        let format = TextureFormat::Rgba8UnormSrgb;
+        let texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: 2,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 2,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            texture.as_image_copy(),
+            &pixels,
+            TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(8),
+                rows_per_image: Some(2),
+            },
+            texture.size(),
+        );
+
+        MipGenerator::new(&device).generate(&device, &queue, &texture, None);
+
+        let expected = mips::generate_mips(2, 2, &pixels, TextureUsage::Color, true);
+        let level_1 = read_pixel(&device, &queue, &texture, 1);
+        for (gpu, cpu) in level_1.iter().zip(&expected[1]) {
+            assert!(gpu.abs_diff(*cpu) <= 1, "{level_1:?} != {:?}", expected[1]);
+        }
+    }
+}
@@ -3,15 +3,18 @@ use std::sync::Arc;
 use syrillian_asset::store::StoreType;
 use wgpu::util::{DeviceExt, TextureDataOrder};
 use wgpu::{
-    AddressMode, Device, Extent3d, FilterMode, MipmapFilterMode, Queue, TextureAspect,
-    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureViewDimension,
+    AddressMode, Device, Extent3d, FilterMode, MipmapFilterMode, Origin3d, Queue,
+    TexelCopyBufferLayout, TexelCopyTextureInfo, TextureAspect, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureViewDimension,
 };
 
 mod cached;
+mod mip_generator;
 mod render_textures;
 mod textures;
 
 pub use cached::GpuTexture;
+pub use mip_generator::MipGenerator;
 
 pub trait TextureAsset: StoreType {
     fn desc(&self) -> TextureDescriptor<'_> {
@@ -97,12 +100,53 @@ pub trait TextureAsset: StoreType {
     fn data(&self) -> Option<&[u8]>;
     fn has_transparency(&self) -> bool;
 
+    /// An uncompressed copy of the texture, uploaded instead when the device can't sample
+    /// the stored format
+    fn uncompressed_fallback(&self) -> Option<Self> {
+        None
+    }
+
+    /// Whether `data` only holds the first level and the rest of the chain is rendered
+    /// by the [`MipGenerator`] after uploading
+    fn generates_mips(&self) -> bool {
+        false
+    }
+
     fn upload(self, device: &Device, queue: &Queue) -> Arc<GpuTexture> {
         profiling::function_scope!("upload texture");
+
+        if !device
+            .features()
+            .contains(self.format().required_features())
+            && let Some(fallback) = self.uncompressed_fallback()
+        {
+            return fallback.upload(device, queue);
+        }
+
         let desc = self.desc();
 
         let texture = match self.data() {
             None => device.create_texture(&self.desc()),
+            Some(data) if self.generates_mips() => {
+                let texture = device.create_texture(&desc);
+                let block_size = self.format().block_copy_size(None).unwrap_or(4);
+                queue.write_texture(
+                    TexelCopyTextureInfo {
+                        texture: &texture,
+                        mip_level: 0,
+                        origin: Origin3d::ZERO,
+                        aspect: TextureAspect::All,
+                    },
+                    data,
+                    TexelCopyBufferLayout {
+                        offset: 0,
+                        bytes_per_row: Some(self.width() * block_size),
+                        rows_per_image: Some(self.height()),
+                    },
+                    desc.size,
+                );
+                texture
+            }
             Some(data) => {
                 device.create_texture_with_data(queue, &desc, TextureDataOrder::LayerMajor, data)
             }
@@ -1,4 +1,4 @@
-use crate::cache::TextureAsset;
+use crate::cache::{MipGenerator, TextureAsset};
 use crate::rendering::TextureFormat;
 use std::slice;
 use syrillian_asset::{RenderCubemap, RenderTexture2D, RenderTexture2DArray};
@@ -36,7 +36,11 @@ impl TextureAsset for RenderTexture2D {
     }
 
     fn mip_level_count(&self) -> u32 {
-        1
+        if self.generate_mips && MipGenerator::supports_format(self.format) {
+            MipGenerator::full_chain_len(self.width, self.height)
+        } else {
+            1
+        }
     }
 
     fn sample_count(&self) -> u32 {
@@ -56,11 +60,19 @@ impl TextureAsset for RenderTexture2D {
     }
 
     fn filter_mode(&self) -> FilterMode {
-        FilterMode::Nearest
+        if self.generate_mips {
+            FilterMode::Linear
+        } else {
+            FilterMode::Nearest
+        }
     }
 
     fn mip_filter_mode(&self) -> MipmapFilterMode {
-        MipmapFilterMode::Nearest
+        if self.generate_mips {
+            MipmapFilterMode::Linear
+        } else {
+            MipmapFilterMode::Nearest
+        }
     }
 
     fn data(&self) -> Option<&[u8]> {
@@ -1,4 +1,4 @@
-use crate::cache::TextureAsset;
+use crate::cache::{MipGenerator, TextureAsset};
 use syrillian_asset::{Cubemap, Texture2D, Texture2DArray};
 use wgpu::{
     AddressMode, FilterMode, MipmapFilterMode, TextureDimension, TextureFormat, TextureUsages,
@@ -11,7 +11,14 @@ impl TextureAsset for Texture2D {
     }
 
     fn flags(&self) -> TextureUsages {
-        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST
+        let flags =
+            TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::COPY_DST;
+
+        if self.generates_mips() {
+            flags | TextureUsages::RENDER_ATTACHMENT
+        } else {
+            flags
+        }
     }
 
     fn width(&self) -> u32 {
@@ -31,7 +38,11 @@ impl TextureAsset for Texture2D {
     }
 
     fn mip_level_count(&self) -> u32 {
-        1
+        if self.generates_mips() {
+            MipGenerator::full_chain_len(self.width, self.height)
+        } else {
+            self.mip_level_count.max(1)
+        }
     }
 
     fn sample_count(&self) -> u32 {
@@ -65,6 +76,17 @@ impl TextureAsset for Texture2D {
     fn has_transparency(&self) -> bool {
         self.has_transparency
     }
+
+    fn uncompressed_fallback(&self) -> Option<Self> {
+        self.decompressed()
+    }
+
+    fn generates_mips(&self) -> bool {
+        self.generate_mips
+            && self.mip_level_count <= 1
+            && self.data.is_some()
+            && MipGenerator::supports_format(self.format)
+    }
 }
 
 impl TextureAsset for Texture2DArray {
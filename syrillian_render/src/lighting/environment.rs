@@ -0,0 +1,369 @@
+//! Image based lighting from the sky of a viewport.
+//!
+//! Whenever the skybox or the sky settings change, compute passes convolve the sky into
+//! a diffuse irradiance cubemap and a GGX-prefiltered specular cubemap, with one mip per
+//! roughness step. Together with the BRDF lookup table of the split sum approximation,
+//! they're bound into the render group for PBR materials.
+
+use crate::cache::{AssetCache, GpuTexture};
+use crate::rendering::render_data::{SkyUniform, SkyboxMode};
+use crate::rendering::uniform::ShaderUniform;
+use syrillian_asset::{HComputeShader, ensure_aligned};
+use syrillian_macros::UniformIndex;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::{
+    AddressMode, Buffer, BufferUsages, CommandEncoderDescriptor, ComputePass,
+    ComputePassDescriptor, Device, Extent3d, FilterMode, MipmapFilterMode, Queue, Sampler,
+    SamplerDescriptor, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    TextureView, TextureViewDescriptor, TextureViewDimension,
+};
+use zerocopy::IntoBytes;
+
+pub const IRRADIANCE_SIZE: u32 = 32;
+pub const PREFILTERED_SIZE: u32 = 128;
+/// Mip `i` of the prefiltered map holds roughness `i / (PREFILTERED_MIPS - 1)`
+pub const PREFILTERED_MIPS: u32 = 5;
+pub const BRDF_LUT_SIZE: u32 = 128;
+
+const IRRADIANCE_SAMPLES: u32 = 256;
+const PREFILTER_SAMPLES: u32 = 128;
+const BRDF_LUT_SAMPLES: u32 = 512;
+
+const IBL_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+const WORKGROUP_SIZE: u32 = 8;
+
+#[repr(C)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    zerocopy::Immutable,
+    zerocopy::IntoBytes,
+    zerocopy::FromBytes,
+    zerocopy::KnownLayout,
+)]
+struct IblParams {
+    roughness: f32,
+    sample_count: u32,
+    _pad0: [u32; 2],
+}
+
+ensure_aligned!(IblParams { roughness }, align <= 16 * 1 => size);
+
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, UniformIndex)]
+enum IblUniformIndex {
+    Sky = 0,
+    Source = 1,
+    Sampler = 2,
+    Params = 3,
+    Output = 4,
+}
+
+/// Views the render group samples the environment through
+#[derive(Debug, Clone)]
+pub struct EnvironmentViews {
+    pub skybox: TextureView,
+    pub skybox_sampler: Sampler,
+    pub irradiance: TextureView,
+    pub prefiltered: TextureView,
+    pub brdf_lut: TextureView,
+    pub ibl_sampler: Sampler,
+}
+
+impl EnvironmentViews {
+    /// Binds the fallback cubemap everywhere, for passes that aren't lit by the sky.
+    pub fn fallback(cache: &AssetCache) -> Self {
+        let cubemap = cache.cubemap_fallback();
+        let texture = cache.texture_fallback();
+
+        Self {
+            skybox: cubemap.view().clone(),
+            skybox_sampler: cubemap.sampler().clone(),
+            irradiance: cubemap.view().clone(),
+            prefiltered: cubemap.view().clone(),
+            brdf_lut: texture.view().clone(),
+            ibl_sampler: cubemap.sampler().clone(),
+        }
+    }
+}
+
+/// One compute dispatch filtering a source cubemap into a single mip of an IBL target
+pub(crate) struct IblPass {
+    shader: HComputeShader,
+    params: IblParams,
+    output: TextureView,
+    size: u32,
+    layers: u32,
+    uniform: Option<ShaderUniform<IblUniformIndex>>,
+}
+
+impl IblPass {
+    fn new(shader: HComputeShader, params: IblParams, output: TextureView, size: u32) -> Self {
+        let layers = if shader == HComputeShader::IBL_BRDF_LUT {
+            1
+        } else {
+            6
+        };
+
+        Self {
+            shader,
+            params,
+            output,
+            size,
+            layers,
+            uniform: None,
+        }
+    }
+
+    /// GGX prefilter passes for every mip of the cube starting at `base_layer` in `texture`.
+    pub(crate) fn prefilter_chain(texture: &Texture, base_layer: u32) -> Vec<Self> {
+        let size = texture.width();
+        (0..texture.mip_level_count())
+            .map(|mip| {
+                Self::new(
+                    HComputeShader::IBL_PREFILTER,
+                    IblParams {
+                        roughness: mip as f32 / (PREFILTERED_MIPS - 1) as f32,
+                        sample_count: PREFILTER_SAMPLES,
+                        _pad0: [0; 2],
+                    },
+                    storage_view(texture, mip, base_layer, 6),
+                    (size >> mip).max(1),
+                )
+            })
+            .collect()
+    }
+
+    /// Binds the pass to `source`. `sky` decides whether the source or the procedural sky is filtered.
+    pub(crate) fn bind(
+        &mut self,
+        device: &Device,
+        cache: &AssetCache,
+        sky: &Buffer,
+        source: &TextureView,
+        sampler: &Sampler,
+    ) {
+        self.uniform = Some(
+            ShaderUniform::builder(cache.bgl_ibl_compute())
+                .with_buffer(sky.clone())
+                .with_texture(source.clone())
+                .with_sampler(sampler.clone())
+                .with_buffer_data(&self.params)
+                .with_texture(self.output.clone())
+                .build(device),
+        );
+    }
+
+    pub(crate) fn dispatch(&self, pass: &mut ComputePass, cache: &AssetCache) {
+        let Some(uniform) = &self.uniform else {
+            return;
+        };
+
+        let shader = cache.compute_shader(self.shader);
+        let groups = self.size.div_ceil(WORKGROUP_SIZE);
+
+        pass.set_pipeline(shader.pipeline());
+        pass.set_bind_group(0, uniform.bind_group(), &[]);
+        pass.dispatch_workgroups(groups, groups, self.layers);
+    }
+}
+
+pub struct EnvironmentLighting {
+    irradiance_view: TextureView,
+    prefiltered_view: TextureView,
+    brdf_lut_view: TextureView,
+    sampler: Sampler,
+    sky: Buffer,
+    filter_passes: Vec<IblPass>,
+    brdf_lut_pass: IblPass,
+    baked_sky: Option<SkyUniform>,
+    source_dirty: bool,
+    brdf_lut_dirty: bool,
+}
+
+impl EnvironmentLighting {
+    pub fn new(device: &Device, cache: &AssetCache) -> Self {
+        let irradiance = create_ibl_texture(device, "IBL Irradiance", IRRADIANCE_SIZE, 6, 1);
+        let prefiltered = create_ibl_texture(
+            device,
+            "IBL Prefiltered Specular",
+            PREFILTERED_SIZE,
+            6,
+            PREFILTERED_MIPS,
+        );
+        let brdf_lut = create_ibl_texture(device, "IBL BRDF LUT", BRDF_LUT_SIZE, 1, 1);
+
+        let cube_view = |texture: &Texture| {
+            texture.create_view(&TextureViewDescriptor {
+                dimension: Some(TextureViewDimension::Cube),
+                ..TextureViewDescriptor::default()
+            })
+        };
+        let irradiance_view = cube_view(&irradiance);
+        let prefiltered_view = cube_view(&prefiltered);
+        let brdf_lut_view = brdf_lut.create_view(&TextureViewDescriptor::default());
+
+        let mut filter_passes = vec![IblPass::new(
+            HComputeShader::IBL_IRRADIANCE,
+            IblParams {
+                roughness: 1.0,
+                sample_count: IRRADIANCE_SAMPLES,
+                _pad0: [0; 2],
+            },
+            storage_view(&irradiance, 0, 0, 6),
+            IRRADIANCE_SIZE,
+        )];
+        filter_passes.extend(IblPass::prefilter_chain(&prefiltered, 0));
+
+        let brdf_lut_pass = IblPass::new(
+            HComputeShader::IBL_BRDF_LUT,
+            IblParams {
+                roughness: 0.0,
+                sample_count: BRDF_LUT_SAMPLES,
+                _pad0: [0; 2],
+            },
+            storage_view(&brdf_lut, 0, 0, 1),
+            BRDF_LUT_SIZE,
+        );
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("IBL Sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: MipmapFilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
+
+        let sky = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("IBL Sky Buffer"),
+            contents: SkyUniform::default().as_bytes(),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let mut environment = Self {
+            irradiance_view,
+            prefiltered_view,
+            brdf_lut_view,
+            sampler,
+            sky,
+            filter_passes,
+            brdf_lut_pass,
+            baked_sky: None,
+            source_dirty: true,
+            brdf_lut_dirty: true,
+        };
+        environment.set_source(device, cache, &cache.cubemap_fallback());
+        environment
+    }
+
+    /// Sets the cubemap that's filtered when the sky isn't procedural.
+    pub fn set_source(&mut self, device: &Device, cache: &AssetCache, source: &GpuTexture) {
+        for pass in self
+            .filter_passes
+            .iter_mut()
+            .chain(std::iter::once(&mut self.brdf_lut_pass))
+        {
+            pass.bind(device, cache, &self.sky, source.view(), source.sampler());
+        }
+
+        self.source_dirty = true;
+    }
+
+    /// The render group views, with `skybox` as the visible sky.
+    pub fn views(&self, skybox: &GpuTexture) -> EnvironmentViews {
+        EnvironmentViews {
+            skybox: skybox.view().clone(),
+            skybox_sampler: skybox.sampler().clone(),
+            irradiance: self.irradiance_view.clone(),
+            prefiltered: self.prefiltered_view.clone(),
+            brdf_lut: self.brdf_lut_view.clone(),
+            ibl_sampler: self.sampler.clone(),
+        }
+    }
+
+    fn sky_changed(&self, sky: &SkyUniform) -> bool {
+        let Some(baked) = &self.baked_sky else {
+            return true;
+        };
+
+        if baked.mode != sky.mode {
+            return true;
+        }
+
+        // A cubemap sky doesn't depend on the atmosphere
+        sky.mode == SkyboxMode::Procedural.as_raw() && baked.as_bytes() != sky.as_bytes()
+    }
+
+    /// Refilters the environment if its source or the sky changed since the last bake.
+    #[profiling::function]
+    pub fn update(&mut self, cache: &AssetCache, device: &Device, queue: &Queue, sky: &SkyUniform) {
+        let refilter = self.source_dirty || self.sky_changed(sky);
+        if !refilter && !self.brdf_lut_dirty {
+            return;
+        }
+
+        queue.write_buffer(&self.sky, 0, sky.as_bytes());
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("IBL Encoder"),
+        });
+
+        {
+            let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("IBL Compute Pass"),
+                ..ComputePassDescriptor::default()
+            });
+
+            let lut = self.brdf_lut_dirty.then_some(&self.brdf_lut_pass);
+            let filters: &[IblPass] = if refilter { &self.filter_passes } else { &[] };
+
+            for ibl_pass in lut.into_iter().chain(filters) {
+                ibl_pass.dispatch(&mut pass, cache);
+            }
+        }
+
+        queue.submit(Some(encoder.finish()));
+
+        self.baked_sky = Some(*sky);
+        self.source_dirty = false;
+        self.brdf_lut_dirty = false;
+    }
+}
+
+pub(crate) fn create_ibl_texture(
+    device: &Device,
+    label: &str,
+    size: u32,
+    layers: u32,
+    mip_level_count: u32,
+) -> Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some(label),
+        size: Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: layers,
+        },
+        mip_level_count,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: IBL_FORMAT,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::STORAGE_BINDING,
+        view_formats: &[],
+    })
+}
+
+fn storage_view(texture: &Texture, mip: u32, base_layer: u32, layers: u32) -> TextureView {
+    texture.create_view(&TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::D2Array),
+        base_mip_level: mip,
+        mip_level_count: Some(1),
+        base_array_layer: base_layer,
+        array_layer_count: Some(layers),
+        ..TextureViewDescriptor::default()
+    })
+}
@@ -1,19 +1,25 @@
 use crate::cache::{AssetCache, TextureAsset};
-use crate::lighting::proxy::{LightProxy, LightType, LightUniformIndex, ShadowUniformIndex};
+use crate::lighting::environment::EnvironmentViews;
+use crate::lighting::proxy::{
+    LIGHT_CLUSTER_COUNT, LIGHT_CLUSTER_GRID, LightClusterComputeIndex, LightClusterParams,
+    LightProxy, LightType, LightUniformIndex, MAX_LIGHTS_PER_CLUSTER, ShadowUniformIndex,
+};
+use crate::lighting::reflection_probe::ReflectionProbeManager;
 use crate::rendering::message::LightProxyCommand;
 use crate::rendering::render_data::RenderUniformData;
 #[cfg(debug_assertions)]
 use crate::rendering::renderer::Renderer;
 use crate::rendering::uniform::ShaderUniform;
-use glamx::Mat4;
+use glamx::{Mat4, Vec4};
 use itertools::Itertools;
 use std::sync::Arc;
-use syrillian_asset::RenderTexture2DArray;
+use syrillian_asset::{HComputeShader, RenderTexture2DArray};
 use syrillian_render::cache::GpuTexture;
 use syrillian_utils::{TypedComponentId, debug_panic};
 use tracing::{trace, warn};
 use wgpu::{
-    AddressMode, Device, FilterMode, MipmapFilterMode, Queue, Sampler, SamplerDescriptor,
+    AddressMode, BindGroup, Buffer, BufferDescriptor, BufferUsages, CommandEncoder,
+    ComputePassDescriptor, Device, FilterMode, MipmapFilterMode, Queue, Sampler, SamplerDescriptor,
     TextureView,
 };
 use zerocopy::IntoBytes;
@@ -27,6 +33,7 @@ pub struct LightManager {
     render_data: Vec<RenderUniformData>,
 
     uniform: ShaderUniform<LightUniformIndex>,
+    cluster_uniform: ShaderUniform<LightClusterComputeIndex>,
     shadow_uniform: ShaderUniform<ShadowUniformIndex>,
     empty_shadow_uniform: ShaderUniform<ShadowUniformIndex>,
     pub shadow_texture: Arc<GpuTexture>,
@@ -36,6 +43,7 @@ pub struct LightManager {
     shadow_assignments_dirty: bool,
     lights_uniform_dirty: bool,
     shadow_camera_uniforms_dirty: bool,
+    pub reflection_probes: ReflectionProbeManager,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -64,7 +72,7 @@ impl LightManager {
         let mut mapping_changed = false;
 
         let render_bgl = cache.bgl_render();
-        let fallback_skybox = cache.cubemap_fallback();
+        let environment = EnvironmentViews::fallback(cache);
 
         let mut next_layer = 0;
         for (idx, light) in self.proxies.iter_mut().enumerate() {
@@ -103,12 +111,8 @@ impl LightManager {
 
             while self.render_data.len() < self.shadow_assignments.len() {
                 profiling::scope!("add render uniform");
-                self.render_data.push(RenderUniformData::empty(
-                    device,
-                    &render_bgl,
-                    fallback_skybox.view().clone(),
-                    fallback_skybox.sampler().clone(),
-                ));
+                self.render_data
+                    .push(RenderUniformData::empty(device, &render_bgl, &environment));
             }
 
             next_layer += required_layers;
@@ -253,12 +257,22 @@ impl LightManager {
             1.0 / empty_shadow_texture.size().height.max(1) as f32,
         ];
 
-        let bgl = cache.bgl_light();
         let count: u32 = 0;
-        let uniform = ShaderUniform::builder(bgl)
+        let cluster_params = LightClusterParams {
+            viewport: Vec4::ZERO,
+            grid: LIGHT_CLUSTER_GRID,
+            debug_view: 0,
+        };
+        let reflection_probes = ReflectionProbeManager::new(device, cache);
+        let uniform = ShaderUniform::builder(cache.bgl_light())
             .with_buffer_data(&count)
             .with_storage_buffer_data(&[DUMMY_POINT_LIGHT])
+            .with_buffer_data(&cluster_params)
+            .with_storage_buffer(Self::create_cluster_buffer(device))
+            .with_texture(reflection_probes.maps_view().clone())
+            .with_buffer(reflection_probes.uniform_buffer().clone())
             .build(device);
+        let cluster_uniform = Self::build_cluster_uniform(cache, device, &uniform);
 
         let shadow_sampler = device.create_sampler(&SamplerDescriptor {
             label: None,
@@ -296,6 +310,7 @@ impl LightManager {
             shadow_assignments: vec![],
             render_data: vec![],
             uniform,
+            cluster_uniform,
             shadow_uniform,
             empty_shadow_uniform,
             shadow_texture,
@@ -305,11 +320,14 @@ impl LightManager {
             shadow_assignments_dirty: true,
             lights_uniform_dirty: false,
             shadow_camera_uniforms_dirty: false,
+            reflection_probes,
         }
     }
 
     #[profiling::function]
     pub fn update(&mut self, cache: &AssetCache, queue: &Queue, device: &Device) {
+        self.reflection_probes.update(queue);
+
         if !self.lights_uniform_dirty && !self.shadow_camera_uniforms_dirty {
             return;
         }
@@ -373,10 +391,17 @@ impl LightManager {
             let data = self.uniform.buffer(LightUniformIndex::Lights);
             if size_of_val(proxies) > data.size() as usize {
                 let bgl = cache.bgl_light();
+                let params = self.uniform.buffer(LightUniformIndex::ClusterParams);
+                let clusters = self.uniform.buffer(LightUniformIndex::Clusters);
                 self.uniform = ShaderUniform::builder(bgl)
                     .with_buffer(count.clone())
                     .with_storage_buffer_data(proxies)
+                    .with_buffer(params.clone())
+                    .with_storage_buffer(clusters.clone())
+                    .with_texture(self.reflection_probes.maps_view().clone())
+                    .with_buffer(self.reflection_probes.uniform_buffer().clone())
                     .build(device);
+                self.cluster_uniform = Self::build_cluster_uniform(cache, device, &self.uniform);
             } else {
                 queue.write_buffer(data, 0, proxies.as_bytes());
             }
@@ -385,6 +410,68 @@ impl LightManager {
         }
     }
 
+    fn create_cluster_buffer(device: &Device) -> Buffer {
+        let size = LIGHT_CLUSTER_COUNT as u64 * (MAX_LIGHTS_PER_CLUSTER as u64 + 1) * 4;
+        device.create_buffer(&BufferDescriptor {
+            label: Some("Light Clusters"),
+            size,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// The culling pass shares all buffers with the light uniform, but writes the clusters
+    fn build_cluster_uniform(
+        cache: &AssetCache,
+        device: &Device,
+        uniform: &ShaderUniform<LightUniformIndex>,
+    ) -> ShaderUniform<LightClusterComputeIndex> {
+        ShaderUniform::builder(cache.bgl_light_cluster_compute())
+            .with_buffer(uniform.buffer(LightUniformIndex::Count).clone())
+            .with_storage_buffer(uniform.buffer(LightUniformIndex::Lights).clone())
+            .with_buffer(uniform.buffer(LightUniformIndex::ClusterParams).clone())
+            .with_storage_buffer(uniform.buffer(LightUniformIndex::Clusters).clone())
+            .build(device)
+    }
+
+    /// Bins all lights into the froxels of the camera in `render_bind_group`, so lit
+    /// shaders only need to evaluate the lights of the cluster they're shading.
+    ///
+    /// `viewport` is the pixel rect (x, y, width, height) the camera renders into.
+    #[profiling::function]
+    pub fn cull_light_clusters(
+        &self,
+        encoder: &mut CommandEncoder,
+        cache: &AssetCache,
+        queue: &Queue,
+        render_bind_group: &BindGroup,
+        viewport: Vec4,
+    ) {
+        #[cfg(debug_assertions)]
+        let debug_view = crate::rendering::debug_renderer::DebugRenderer::light_clusters() as u32;
+        #[cfg(not(debug_assertions))]
+        let debug_view = 0;
+
+        let params = LightClusterParams {
+            viewport,
+            grid: LIGHT_CLUSTER_GRID,
+            debug_view,
+        };
+        self.uniform
+            .write_buffer(LightUniformIndex::ClusterParams, &params, queue);
+
+        let shader = cache.compute_shader(HComputeShader::LIGHT_CLUSTERS);
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("Light Cluster Compute Pass"),
+            ..ComputePassDescriptor::default()
+        });
+
+        pass.set_pipeline(shader.pipeline());
+        pass.set_bind_group(0, render_bind_group, &[]);
+        pass.set_bind_group(1, self.cluster_uniform.bind_group(), &[]);
+        pass.dispatch_workgroups(LIGHT_CLUSTER_COUNT.div_ceil(64), 1, 1);
+    }
+
     #[cfg(debug_assertions)]
     pub fn render_debug_lights(&self, renderer: &Renderer, ctx: &crate::rendering::GPUDrawCtx) {
         use syrillian_asset::HShader;
@@ -1,2 +1,4 @@
+pub mod environment;
 pub mod manager;
 pub mod proxy;
+pub mod reflection_probe;
@@ -1,4 +1,4 @@
-use glamx::Vec3;
+use glamx::{UVec3, Vec3, Vec4};
 use num_enum::TryFromPrimitive;
 use syrillian_asset::ensure_aligned;
 use syrillian_macros::UniformIndex;
@@ -57,6 +57,32 @@ impl LightProxy {
 
 ensure_aligned!(LightProxy { position, up, direction, color }, align <= 16 * 6 => size);
 
+/// Froxel grid the view frustum is split into for light culling
+pub const LIGHT_CLUSTER_GRID: UVec3 = UVec3::new(16, 9, 24);
+/// Lights that can affect a single cluster, further lights are dropped
+pub const MAX_LIGHTS_PER_CLUSTER: u32 = 64;
+pub const LIGHT_CLUSTER_COUNT: u32 =
+    LIGHT_CLUSTER_GRID.x * LIGHT_CLUSTER_GRID.y * LIGHT_CLUSTER_GRID.z;
+
+#[repr(C)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    zerocopy::Immutable,
+    zerocopy::IntoBytes,
+    zerocopy::FromBytes,
+    zerocopy::KnownLayout,
+)]
+pub struct LightClusterParams {
+    /// Viewport rect in pixels (x, y, width, height)
+    pub viewport: Vec4,
+    pub grid: UVec3,
+    pub debug_view: u32,
+}
+
+ensure_aligned!(LightClusterParams { viewport, grid }, align <= 16 * 2 => size);
+
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, TryFromPrimitive)]
 pub enum LightType {
@@ -70,6 +96,19 @@ pub enum LightType {
 pub enum LightUniformIndex {
     Count = 0,
     Lights = 1,
+    ClusterParams = 2,
+    Clusters = 3,
+    ReflectionProbeMaps = 4,
+    ReflectionProbes = 5,
+}
+
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, UniformIndex)]
+pub enum LightClusterComputeIndex {
+    Count = 0,
+    Lights = 1,
+    Params = 2,
+    Clusters = 3,
 }
 
 #[repr(u8)]
@@ -0,0 +1,661 @@
+//! Local reflection probes.
+//!
+//! A probe renders the scene around it into a cubemap, which is then prefiltered for roughness
+//! by the same passes that filter the sky for image based lighting. Lit shaders blend all probes
+//! whose box contains the shaded point, and fall back to the sky where no probe reaches.
+
+use crate::cache::AssetCache;
+use crate::lighting::environment::{
+    EnvironmentViews, IblPass, PREFILTERED_MIPS, PREFILTERED_SIZE, create_ibl_texture,
+};
+use crate::rendering::message::ReflectionProbeCommand;
+use crate::rendering::render_data::{RenderUniformData, SkyUniform};
+use crate::rendering::uniform::ShaderUniform;
+use glamx::{Mat4, UVec2, Vec3};
+use itertools::Itertools;
+use std::f32::consts::FRAC_PI_2;
+use syrillian_asset::{HComputeShader, ensure_aligned};
+use syrillian_macros::UniformIndex;
+use syrillian_utils::TypedComponentId;
+use tracing::{trace, warn};
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::{
+    AddressMode, Buffer, BufferUsages, CommandEncoder, ComputePassDescriptor, Device, Extent3d,
+    FilterMode, MipmapFilterMode, Queue, Sampler, SamplerDescriptor, Texture, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor,
+    TextureViewDimension,
+};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+pub const MAX_REFLECTION_PROBES: usize = 8;
+/// Resolution of a single captured cube face
+pub const REFLECTION_PROBE_SIZE: u32 = 128;
+
+/// Has to match the offscreen surface, so the scene pipelines can render into the capture
+const CAPTURE_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+const WORKGROUP_SIZE: u32 = 8;
+
+/// View direction and up vector of the capture camera for each cube face
+const CAPTURE_FACES: [(Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::Y),
+    (Vec3::NEG_X, Vec3::Y),
+    (Vec3::Y, Vec3::NEG_Z),
+    (Vec3::NEG_Y, Vec3::Z),
+    (Vec3::Z, Vec3::Y),
+    (Vec3::NEG_Z, Vec3::Y),
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReflectionProbeRefresh {
+    /// Captures once, and again whenever a capture is requested
+    OnDemand,
+    /// Recaptures every n frames
+    EveryNFrames(u32),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ReflectionProbeProxy {
+    pub position: Vec3,
+    /// Half size of the axis aligned box around the probe it affects
+    pub extents: Vec3,
+    /// Distance from the box faces over which the probe fades out
+    pub blend_distance: f32,
+    pub intensity: f32,
+    pub box_projection: bool,
+    pub near: f32,
+    pub far: f32,
+    pub refresh: ReflectionProbeRefresh,
+    pub capture_requested: bool,
+}
+
+impl Default for ReflectionProbeProxy {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            extents: Vec3::splat(5.0),
+            blend_distance: 1.0,
+            intensity: 1.0,
+            box_projection: true,
+            near: 0.05,
+            far: 500.0,
+            refresh: ReflectionProbeRefresh::OnDemand,
+            capture_requested: true,
+        }
+    }
+}
+
+impl ReflectionProbeProxy {
+    fn data(&self) -> ReflectionProbeData {
+        ReflectionProbeData {
+            position: self.position,
+            blend_distance: self.blend_distance.max(0.0),
+            box_min: self.position - self.extents.abs(),
+            intensity: self.intensity.max(0.0),
+            box_max: self.position + self.extents.abs(),
+            box_projection: self.box_projection as u32,
+        }
+    }
+
+    fn capture_inputs_changed(&self, other: &Self) -> bool {
+        self.position != other.position || self.near != other.near || self.far != other.far
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Immutable, FromBytes, IntoBytes, KnownLayout)]
+pub struct ReflectionProbeData {
+    pub position: Vec3,
+    pub blend_distance: f32,
+    pub box_min: Vec3,
+    pub intensity: f32,
+    pub box_max: Vec3,
+    pub box_projection: u32,
+}
+
+ensure_aligned!(ReflectionProbeData { position, box_min, box_max }, align <= 16 * 3 => size);
+
+impl ReflectionProbeData {
+    /// An inverted box, which no point is inside of
+    const EMPTY: Self = Self {
+        position: Vec3::ZERO,
+        blend_distance: 0.0,
+        box_min: Vec3::MAX,
+        intensity: 0.0,
+        box_max: Vec3::MIN,
+        box_projection: 0,
+    };
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Immutable, FromBytes, IntoBytes, KnownLayout)]
+pub struct ReflectionProbeUniform {
+    pub count: u32,
+    pub _pad0: [u32; 3],
+    pub probes: [ReflectionProbeData; MAX_REFLECTION_PROBES],
+}
+
+ensure_aligned!(ReflectionProbeUniform { probes }, align <= 16 * 25 => size);
+
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, UniformIndex)]
+enum ReflectionProbeResolveIndex {
+    Capture = 0,
+    Resolved = 1,
+}
+
+/// A probe that is due for a capture this frame
+#[derive(Debug, Copy, Clone)]
+pub struct ReflectionProbeCapture {
+    pub slot: usize,
+    pub proxy: ReflectionProbeProxy,
+}
+
+struct ProbeEntry {
+    owner: TypedComponentId,
+    proxy: ReflectionProbeProxy,
+    slot: Option<usize>,
+    captured: bool,
+    frames_since_capture: u32,
+}
+
+impl ProbeEntry {
+    fn is_due(&self) -> bool {
+        if self.slot.is_none() {
+            return false;
+        }
+        if !self.captured || self.proxy.capture_requested {
+            return true;
+        }
+
+        match self.proxy.refresh {
+            ReflectionProbeRefresh::OnDemand => false,
+            ReflectionProbeRefresh::EveryNFrames(n) => self.frames_since_capture >= n.max(1),
+        }
+    }
+}
+
+/// The lowest slot no entry holds, `None` once all [`MAX_REFLECTION_PROBES`] are taken.
+fn free_slot(entries: &[ProbeEntry]) -> Option<usize> {
+    (0..MAX_REFLECTION_PROBES).find(|slot| entries.iter().all(|entry| entry.slot != Some(*slot)))
+}
+
+/// Removes the entry of `owner` and hands its slot to a probe that didn't get one.
+/// Returns whether there was an entry.
+fn remove_entry(entries: &mut Vec<ProbeEntry>, owner: TypedComponentId) -> bool {
+    let Some((pos, _)) = entries.iter().find_position(|e| e.owner == owner) else {
+        return false;
+    };
+
+    let freed = entries.remove(pos).slot;
+    if freed.is_some()
+        && let Some(waiting) = entries.iter_mut().find(|e| e.slot.is_none())
+    {
+        waiting.slot = freed;
+        waiting.captured = false;
+    }
+
+    true
+}
+
+pub struct ReflectionProbeManager {
+    entries: Vec<ProbeEntry>,
+    uniform: Buffer,
+    uniform_dirty: bool,
+    maps_view: TextureView,
+
+    face_views: Vec<TextureView>,
+    face_data: Vec<RenderUniformData>,
+    g_normal: TextureView,
+    g_material: TextureView,
+    depth: TextureView,
+
+    resolve: ShaderUniform<ReflectionProbeResolveIndex>,
+    slot_filters: Vec<Vec<IblPass>>,
+}
+
+impl ReflectionProbeManager {
+    pub fn new(device: &Device, cache: &AssetCache) -> Self {
+        let maps = create_ibl_texture(
+            device,
+            "Reflection Probe Maps",
+            PREFILTERED_SIZE,
+            6 * MAX_REFLECTION_PROBES as u32,
+            PREFILTERED_MIPS,
+        );
+        let maps_view = maps.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::CubeArray),
+            ..TextureViewDescriptor::default()
+        });
+
+        let uniform = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Reflection Probe Buffer"),
+            contents: Self::uniform_data(&[]).as_bytes(),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
+        let capture = create_capture_texture(device, "Reflection Probe Capture", CAPTURE_FORMAT, 6);
+        let face_views = (0..6)
+            .map(|face| {
+                capture.create_view(&TextureViewDescriptor {
+                    dimension: Some(TextureViewDimension::D2),
+                    base_array_layer: face,
+                    array_layer_count: Some(1),
+                    ..TextureViewDescriptor::default()
+                })
+            })
+            .collect();
+        let capture_view = capture.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2Array),
+            ..TextureViewDescriptor::default()
+        });
+
+        let g_normal = create_capture_texture(
+            device,
+            "Reflection Probe GBuffer (Normals)",
+            TextureFormat::Rg16Float,
+            1,
+        )
+        .create_view(&TextureViewDescriptor::default());
+        let g_material = create_capture_texture(
+            device,
+            "Reflection Probe GBuffer (Material)",
+            TextureFormat::Bgra8Unorm,
+            1,
+        )
+        .create_view(&TextureViewDescriptor::default());
+        let depth = create_capture_texture(
+            device,
+            "Reflection Probe Depth",
+            TextureFormat::Depth32Float,
+            1,
+        )
+        .create_view(&TextureViewDescriptor::default());
+
+        let render_bgl = cache.bgl_render();
+        let fallback_environment = EnvironmentViews::fallback(cache);
+        let face_data = (0..6)
+            .map(|_| RenderUniformData::empty(device, &render_bgl, &fallback_environment))
+            .collect();
+
+        // The capture is mirrored into cube layout before it's filtered
+        let resolved = create_ibl_texture(
+            device,
+            "Reflection Probe Resolved Capture",
+            REFLECTION_PROBE_SIZE,
+            6,
+            1,
+        );
+        let resolved_storage = resolved.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::D2Array),
+            ..TextureViewDescriptor::default()
+        });
+        let resolved_cube = resolved.create_view(&TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..TextureViewDescriptor::default()
+        });
+
+        let resolve = ShaderUniform::builder(cache.bgl_reflection_probe_resolve())
+            .with_texture(capture_view)
+            .with_texture(resolved_storage)
+            .build(device);
+
+        let sampler = create_capture_sampler(device);
+        let sky = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Reflection Probe Sky Buffer"),
+            contents: SkyUniform::default().as_bytes(),
+            usage: BufferUsages::UNIFORM,
+        });
+
+        let slot_filters = (0..MAX_REFLECTION_PROBES as u32)
+            .map(|slot| {
+                let mut passes = IblPass::prefilter_chain(&maps, slot * 6);
+                for pass in &mut passes {
+                    pass.bind(device, cache, &sky, &resolved_cube, &sampler);
+                }
+                passes
+            })
+            .collect();
+
+        Self {
+            entries: Vec::new(),
+            uniform,
+            uniform_dirty: false,
+            maps_view,
+            face_views,
+            face_data,
+            g_normal,
+            g_material,
+            depth,
+            resolve,
+            slot_filters,
+        }
+    }
+
+    #[profiling::function]
+    pub fn add_proxy(&mut self, owner: TypedComponentId, proxy: ReflectionProbeProxy) {
+        trace!("Registered Reflection Probe for #{:?}", owner.type_id());
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.owner == owner) {
+            entry.proxy = proxy;
+            entry.captured = false;
+            self.uniform_dirty = true;
+            return;
+        }
+
+        let slot = free_slot(&self.entries);
+        if slot.is_none() {
+            warn!(
+                "Only {MAX_REFLECTION_PROBES} reflection probes can be active at once. The probe is ignored."
+            );
+        }
+
+        self.entries.push(ProbeEntry {
+            owner,
+            proxy,
+            slot,
+            captured: false,
+            frames_since_capture: 0,
+        });
+        self.uniform_dirty = true;
+    }
+
+    #[profiling::function]
+    pub fn remove_proxy(&mut self, owner: TypedComponentId) {
+        if remove_entry(&mut self.entries, owner) {
+            self.uniform_dirty = true;
+        }
+    }
+
+    #[profiling::function]
+    pub fn execute_command(&mut self, owner: TypedComponentId, cmd: ReflectionProbeCommand) {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.owner == owner) else {
+            warn!("Requested Reflection Probe not found");
+            return;
+        };
+
+        let old = entry.proxy;
+        cmd(&mut entry.proxy);
+
+        // Pending captures are cleared by the renderer only
+        entry.proxy.capture_requested |= old.capture_requested;
+        if old.capture_inputs_changed(&entry.proxy) {
+            entry.proxy.capture_requested = true;
+        }
+
+        self.uniform_dirty = true;
+    }
+
+    pub fn maps_view(&self) -> &TextureView {
+        &self.maps_view
+    }
+
+    pub fn uniform_buffer(&self) -> &Buffer {
+        &self.uniform
+    }
+
+    pub fn update(&mut self, queue: &Queue) {
+        for entry in &mut self.entries {
+            entry.frames_since_capture = entry.frames_since_capture.saturating_add(1);
+        }
+
+        if !self.uniform_dirty {
+            return;
+        }
+
+        let probes = self
+            .entries
+            .iter()
+            .filter(|e| e.captured)
+            .filter_map(|e| Some((e.slot?, e.proxy.data())))
+            .collect::<Vec<_>>();
+        queue.write_buffer(&self.uniform, 0, Self::uniform_data(&probes).as_bytes());
+
+        self.uniform_dirty = false;
+    }
+
+    fn uniform_data(probes: &[(usize, ReflectionProbeData)]) -> ReflectionProbeUniform {
+        let mut data = ReflectionProbeUniform {
+            count: 0,
+            _pad0: [0; 3],
+            probes: [ReflectionProbeData::EMPTY; MAX_REFLECTION_PROBES],
+        };
+
+        for (slot, probe) in probes {
+            data.probes[*slot] = *probe;
+            data.count = data.count.max(*slot as u32 + 1);
+        }
+
+        data
+    }
+
+    /// Takes the probe that waited longest for its capture. Only one probe is captured per frame.
+    pub fn next_capture(&mut self) -> Option<ReflectionProbeCapture> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .filter(|e| e.is_due())
+            .max_by_key(|e| (!e.captured, e.frames_since_capture))?;
+
+        entry.captured = true;
+        entry.frames_since_capture = 0;
+        entry.proxy.capture_requested = false;
+        self.uniform_dirty = true;
+
+        Some(ReflectionProbeCapture {
+            slot: entry.slot?,
+            proxy: entry.proxy,
+        })
+    }
+
+    /// Points the capture cameras of all faces at the probe, lit by `environment`.
+    pub fn prepare_capture(
+        &mut self,
+        device: &Device,
+        cache: &AssetCache,
+        probe: &ReflectionProbeProxy,
+        environment: &EnvironmentViews,
+        sky: &SkyUniform,
+    ) {
+        let render_bgl = cache.bgl_render();
+        let near = probe.near.max(1e-3);
+        let far = probe.far.max(near + 0.01);
+        let projection = Mat4::perspective_rh(FRAC_PI_2, 1.0, near, far);
+
+        for (data, (dir, up)) in self.face_data.iter_mut().zip(CAPTURE_FACES) {
+            let view = Mat4::look_at_rh(probe.position, probe.position + dir, up);
+            data.camera_data.update(&projection, &probe.position, &view);
+            data.camera_data.near = near;
+            data.camera_data.far = far;
+            data.camera_data.fov = 90.0;
+            data.system_data.screen_size = UVec2::splat(REFLECTION_PROBE_SIZE);
+            data.sky_data = *sky;
+            data.rebuild_bind_group(device, &render_bgl, environment);
+        }
+    }
+
+    pub fn face_view(&self, face: usize) -> Option<&TextureView> {
+        self.face_views.get(face)
+    }
+
+    pub fn face_render_data(&self, face: usize) -> Option<&RenderUniformData> {
+        self.face_data.get(face)
+    }
+
+    pub fn g_normal_view(&self) -> &TextureView {
+        &self.g_normal
+    }
+
+    pub fn g_material_view(&self) -> &TextureView {
+        &self.g_material
+    }
+
+    pub fn depth_view(&self) -> &TextureView {
+        &self.depth
+    }
+
+    /// Turns the rendered faces into the prefiltered cube of the probe in `slot`.
+    pub fn filter_capture(&self, encoder: &mut CommandEncoder, cache: &AssetCache, slot: usize) {
+        let Some(filters) = self.slot_filters.get(slot) else {
+            return;
+        };
+
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("Reflection Probe Filter Pass"),
+            ..ComputePassDescriptor::default()
+        });
+
+        let resolve = cache.compute_shader(HComputeShader::REFLECTION_PROBE_RESOLVE);
+        let groups = REFLECTION_PROBE_SIZE.div_ceil(WORKGROUP_SIZE);
+        pass.set_pipeline(resolve.pipeline());
+        pass.set_bind_group(0, self.resolve.bind_group(), &[]);
+        pass.dispatch_workgroups(groups, groups, 6);
+
+        for filter in filters {
+            filter.dispatch(&mut pass, cache);
+        }
+    }
+}
+
+fn create_capture_texture(
+    device: &Device,
+    label: &str,
+    format: TextureFormat,
+    layers: u32,
+) -> Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some(label),
+        size: Extent3d {
+            width: REFLECTION_PROBE_SIZE,
+            height: REFLECTION_PROBE_SIZE,
+            depth_or_array_layers: layers,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    })
+}
+
+fn create_capture_sampler(device: &Device) -> Sampler {
+    device.create_sampler(&SamplerDescriptor {
+        label: Some("Reflection Probe Capture Sampler"),
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        address_mode_w: AddressMode::ClampToEdge,
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        mipmap_filter: MipmapFilterMode::Linear,
+        ..SamplerDescriptor::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syrillian_utils::{ComponentId, Key};
+
+    struct First;
+    struct Second;
+
+    fn entry<T: 'static>(slot: Option<usize>) -> ProbeEntry {
+        ProbeEntry {
+            owner: TypedComponentId::new::<T>(ComponentId::null()),
+            proxy: ReflectionProbeProxy {
+                capture_requested: false,
+                ..ReflectionProbeProxy::default()
+            },
+            slot,
+            captured: true,
+            frames_since_capture: 0,
+        }
+    }
+
+    #[test]
+    fn probes_without_slot_are_never_due() {
+        let mut probe = entry::<First>(None);
+        probe.captured = false;
+        probe.proxy.capture_requested = true;
+        assert!(!probe.is_due());
+    }
+
+    #[test]
+    fn uncaptured_or_requested_probes_are_due() {
+        let mut probe = entry::<First>(Some(0));
+        assert!(!probe.is_due());
+
+        probe.captured = false;
+        assert!(probe.is_due());
+
+        probe.captured = true;
+        probe.proxy.capture_requested = true;
+        assert!(probe.is_due());
+    }
+
+    #[test]
+    fn refresh_interval_counts_frames() {
+        let mut probe = entry::<First>(Some(0));
+        probe.frames_since_capture = 1000;
+        assert!(!probe.is_due(), "on demand probes only capture on request");
+
+        probe.proxy.refresh = ReflectionProbeRefresh::EveryNFrames(3);
+        probe.frames_since_capture = 2;
+        assert!(!probe.is_due());
+        probe.frames_since_capture = 3;
+        assert!(probe.is_due());
+
+        // An interval of zero still waits a frame between captures
+        probe.proxy.refresh = ReflectionProbeRefresh::EveryNFrames(0);
+        probe.frames_since_capture = 0;
+        assert!(!probe.is_due());
+        probe.frames_since_capture = 1;
+        assert!(probe.is_due());
+    }
+
+    #[test]
+    fn slots_run_out_after_max_probes() {
+        let mut entries = Vec::new();
+        for expected in 0..MAX_REFLECTION_PROBES {
+            let slot = free_slot(&entries);
+            assert_eq!(slot, Some(expected));
+            entries.push(entry::<First>(slot));
+        }
+
+        assert_eq!(free_slot(&entries), None);
+
+        // Freed slots are reused, lowest first
+        entries.retain(|e| e.slot != Some(5) && e.slot != Some(2));
+        assert_eq!(free_slot(&entries), Some(2));
+    }
+
+    #[test]
+    fn removing_a_probe_hands_its_slot_to_a_waiting_one() {
+        let mut entries = vec![entry::<First>(Some(3)), entry::<Second>(None)];
+
+        assert!(remove_entry(
+            &mut entries,
+            TypedComponentId::new::<First>(ComponentId::null())
+        ));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].slot, Some(3));
+        assert!(!entries[0].captured, "the new owner has to capture first");
+
+        assert!(!remove_entry(
+            &mut entries,
+            TypedComponentId::new::<First>(ComponentId::null())
+        ));
+    }
+
+    #[test]
+    fn removing_a_waiting_probe_keeps_the_slots() {
+        let mut entries = vec![entry::<First>(Some(0)), entry::<Second>(None)];
+
+        assert!(remove_entry(
+            &mut entries,
+            TypedComponentId::new::<Second>(ComponentId::null())
+        ));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].slot, Some(0));
+        assert!(entries[0].captured);
+    }
+}
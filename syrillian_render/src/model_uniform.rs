@@ -9,9 +9,14 @@ pub struct ModelUniform {
     pub transform: Mat4,
     pub normal: Mat3A,
     pub object_hash: [f32; 4],
+    /// Dithers the draw out while crossfading between levels of detail, zero draws it whole
+    pub lod_fade: f32,
+    /// Layer of the surface, decals only project onto layers in their mask
+    pub decal_layer: u32,
+    pub _p0: [f32; 2],
 }
 
-ensure_aligned!(ModelUniform { transform, normal, object_hash }, align <= 16 * 8 => size);
+ensure_aligned!(ModelUniform { transform, normal, object_hash }, align <= 16 * 9 => size);
 
 impl ModelUniform {
     pub fn empty() -> Self {
@@ -36,6 +41,9 @@ impl ModelUniform {
             normal: normal_matrix(full_trs),
             transform: *full_trs,
             object_hash: [0.0; 4],
+            lod_fade: 0.0,
+            decal_layer: 0,
+            _p0: [0.0; 2],
         }
     }
 
@@ -5,6 +5,7 @@ use crate::passes::post_process::{
     ScreenSpaceAmbientOcclusionRenderPass, ScreenSpaceReflectionRenderPass,
 };
 use crate::passes::ui_pass::UiRenderPass;
+use crate::rendering::decals::DecalSurface;
 use crate::rendering::offscreen_surface::OffscreenSurface;
 use crate::rendering::render_data::RenderUniformData;
 use crate::rendering::renderer::RenderedFrame;
@@ -68,6 +69,8 @@ pub struct RenderPipeline {
     pub g_normal: Texture,
     pub g_material: Texture,
     pub g_velocity: Texture,
+    /// G-buffer copies decals read from
+    pub decal_surface: DecalSurface,
     shared_views: PostProcessSharedViews,
 
     pub ssr_pass: ScreenSpaceReflectionRenderPass,
@@ -89,6 +92,7 @@ impl RenderPipeline {
         let material_texture = Self::create_material_texture(device, config);
         let velocity_texture = Self::create_velocity_texture(device, config);
         let depth_texture = Self::create_depth_texture(device, config);
+        let decal_surface = DecalSurface::new(device, cache, config, &depth_texture);
         let shared_views = PostProcessSharedViews {
             depth: depth_texture.create_view(&TextureViewDescriptor::default()),
             g_normal: normal_texture.create_view(&TextureViewDescriptor::default()),
@@ -174,6 +178,7 @@ impl RenderPipeline {
             g_normal: normal_texture,
             g_material: material_texture,
             g_velocity: velocity_texture,
+            decal_surface,
             shared_views,
             ssr_pass,
             ssao_pass,
@@ -9,6 +9,7 @@ use crate::rendering::uniform::ShaderUniform;
 use crate::{proxy_data, proxy_data_mut};
 use glamx::{Affine3A, Vec3, Vec4};
 use std::any::Any;
+use std::slice;
 use syrillian_asset::{HMesh, HShader};
 use syrillian_utils::debug_panic;
 use tracing::warn;
@@ -41,7 +42,7 @@ pub struct DebugLine {
     zerocopy::FromBytes,
     zerocopy::KnownLayout,
 )]
-struct DebugLineVertex {
+pub(crate) struct DebugLineVertex {
     position: [f32; 3],
     color: [f32; 4],
 }
@@ -53,6 +54,15 @@ impl DebugLineVertex {
             color: [color.x, color.y, color.z, color.w],
         }
     }
+
+    pub(crate) fn from_lines(lines: &[DebugLine]) -> Vec<Self> {
+        let mut vertices = Vec::with_capacity(lines.len() * 2);
+        for line in lines {
+            vertices.push(Self::new(line.start, line.start_color));
+            vertices.push(Self::new(line.end, line.end_color));
+        }
+        vertices
+    }
 }
 
 #[derive(Debug)]
@@ -136,12 +146,7 @@ impl DebugSceneProxy {
             return None;
         }
 
-        let mut vertices = Vec::with_capacity(self.lines.len() * 2);
-        for line in &self.lines {
-            vertices.push(DebugLineVertex::new(line.start, line.start_color));
-            vertices.push(DebugLineVertex::new(line.end, line.end_color));
-        }
-
+        let vertices = DebugLineVertex::from_lines(&self.lines);
         Some(device.create_buffer_init(&BufferInitDescriptor {
             label: Some("Debug Ray Data Buffer"),
             contents: vertices.as_bytes(),
@@ -162,7 +167,7 @@ impl DebugSceneProxy {
         let bgl = cache.bgl_model();
         let mesh_data = ModelUniform::from_affine(model_mat);
         let uniform = ShaderUniform::builder(bgl)
-            .with_buffer_data(&mesh_data)
+            .with_storage_buffer_data(slice::from_ref(&mesh_data))
             .build(device);
 
         Some(RenderMeshData {
@@ -0,0 +1,255 @@
+use crate::cache::AssetCache;
+use crate::model_uniform::ModelUniform;
+use crate::proxies::mesh_proxy::{ModelBinding, draw_materials};
+use crate::proxies::{
+    MeshUniformIndex, PROXY_PRIORITY_SOLID, PROXY_PRIORITY_TRANSPARENT, SceneProxy,
+    SceneProxyBinding,
+};
+use crate::rendering::picking::hash_to_rgba;
+use crate::rendering::renderer::Renderer;
+use crate::rendering::uniform::ShaderUniform;
+use crate::rendering::{GPUDrawCtx, RenderPassType};
+use crate::{proxy_data, proxy_data_mut};
+use glamx::Affine3A;
+use std::any::Any;
+use std::ops::Range;
+use syrillian_asset::{HMaterialInstance, HMesh};
+use syrillian_utils::BoundingSphere;
+use zerocopy::IntoBytes;
+
+#[derive(Debug, Clone)]
+pub struct InstancedMeshData {
+    pub instances: Vec<ModelUniform>,
+    pub uniform: ShaderUniform<MeshUniformIndex>,
+    capacity: usize,
+}
+
+/// Draws one mesh at many local transforms with a single draw call per material.
+#[derive(Debug, Clone)]
+pub struct InstancedMeshSceneProxy {
+    pub mesh: HMesh,
+    pub materials: Vec<HMaterialInstance>,
+    pub material_ranges: Vec<Range<u32>>,
+    pub bounding: Option<BoundingSphere>,
+    pub instances: Vec<Affine3A>,
+    /// Layer of all instances, decals only project onto layers in their mask
+    pub decal_layer: u32,
+    pub dirty_instances: bool,
+    render_affine: Affine3A,
+    model_bounding: Option<BoundingSphere>,
+}
+
+impl InstancedMeshSceneProxy {
+    pub fn new(
+        mesh: HMesh,
+        materials: Vec<HMaterialInstance>,
+        material_ranges: Vec<Range<u32>>,
+        bounding: Option<BoundingSphere>,
+        instances: Vec<Affine3A>,
+    ) -> Self {
+        Self {
+            mesh,
+            materials,
+            material_ranges,
+            bounding,
+            instances,
+            decal_layer: 0,
+            dirty_instances: false,
+            render_affine: Affine3A::IDENTITY,
+            model_bounding: None,
+        }
+    }
+
+    fn instance_data(&self) -> Vec<ModelUniform> {
+        self.instances
+            .iter()
+            .map(|instance| {
+                let mut model = ModelUniform::from_affine(&(self.render_affine * *instance));
+                model.decal_layer = self.decal_layer;
+                model
+            })
+            .collect()
+    }
+
+    fn build_uniform(
+        renderer: &Renderer,
+        instances: &[ModelUniform],
+    ) -> ShaderUniform<MeshUniformIndex> {
+        // storage buffers can't be empty
+        let empty = [ModelUniform::empty()];
+        let data = if instances.is_empty() {
+            &empty[..]
+        } else {
+            instances
+        };
+
+        ShaderUniform::<MeshUniformIndex>::builder(renderer.cache.bgl_model())
+            .with_storage_buffer_data(data)
+            .build(&renderer.state.device)
+    }
+
+    fn upload(&self, renderer: &Renderer, data: &mut InstancedMeshData) {
+        data.instances = self.instance_data();
+
+        if data.instances.len() > data.capacity {
+            data.uniform = Self::build_uniform(renderer, &data.instances);
+            data.capacity = data.instances.len();
+        } else if !data.instances.is_empty() {
+            renderer.state.queue.write_buffer(
+                data.uniform.buffer(MeshUniformIndex::MeshData),
+                0,
+                data.instances.as_bytes(),
+            );
+        }
+    }
+
+    fn update_model_bounds(&mut self) {
+        let Some(bounding) = self.bounding else {
+            self.model_bounding = None;
+            return;
+        };
+
+        self.model_bounding = self
+            .instances
+            .iter()
+            .map(|instance| bounding.transformed(&(self.render_affine * *instance).into()))
+            .reduce(|a, b| a.union(&b));
+    }
+
+    fn draw(
+        &self,
+        renderer: &Renderer,
+        ctx: &GPUDrawCtx,
+        data: &InstancedMeshData,
+        pass_type: RenderPassType,
+    ) {
+        if data.instances.is_empty() {
+            return;
+        }
+
+        let Some(mesh) = renderer.cache.mesh(self.mesh) else {
+            return;
+        };
+
+        let model =
+            ModelBinding::Instances(data.uniform.bind_group(), 0..data.instances.len() as u32);
+        let mut pass = ctx.pass.write();
+        draw_materials(
+            ctx,
+            &renderer.cache,
+            &mesh,
+            &self.materials,
+            &self.material_ranges,
+            model,
+            &mut pass,
+            pass_type,
+        );
+    }
+}
+
+impl SceneProxy for InstancedMeshSceneProxy {
+    fn setup_render(
+        &mut self,
+        renderer: &Renderer,
+        render_affine: Affine3A,
+        _world_affine: Option<Affine3A>,
+    ) -> Box<dyn Any + Send> {
+        self.render_affine = render_affine;
+        self.dirty_instances = false;
+        self.update_model_bounds();
+
+        let instances = self.instance_data();
+        let uniform = Self::build_uniform(renderer, &instances);
+
+        Box::new(InstancedMeshData {
+            capacity: instances.len().max(1),
+            instances,
+            uniform,
+        })
+    }
+
+    fn refresh_transform(
+        &mut self,
+        renderer: &Renderer,
+        data: &mut (dyn Any + Send),
+        render_affine: Affine3A,
+        _world_affine: Option<Affine3A>,
+    ) {
+        let data: &mut InstancedMeshData = proxy_data_mut!(data);
+
+        self.render_affine = render_affine;
+        self.update_model_bounds();
+        self.upload(renderer, data);
+    }
+
+    fn update_render(&mut self, renderer: &Renderer, data: &mut (dyn Any + Send)) {
+        if !self.dirty_instances {
+            return;
+        }
+
+        let data: &mut InstancedMeshData = proxy_data_mut!(data);
+
+        self.dirty_instances = false;
+        self.update_model_bounds();
+        self.upload(renderer, data);
+    }
+
+    fn render(&self, renderer: &Renderer, ctx: &GPUDrawCtx, binding: &SceneProxyBinding) {
+        let data: &InstancedMeshData = proxy_data!(binding.proxy_data());
+
+        self.draw(renderer, ctx, data, RenderPassType::Color);
+    }
+
+    fn render_shadows(&self, renderer: &Renderer, ctx: &GPUDrawCtx, binding: &SceneProxyBinding) {
+        let data: &InstancedMeshData = proxy_data!(binding.proxy_data());
+
+        self.draw(renderer, ctx, data, RenderPassType::Shadow);
+    }
+
+    fn render_picking(&self, renderer: &Renderer, ctx: &GPUDrawCtx, binding: &SceneProxyBinding) {
+        let data: &InstancedMeshData = proxy_data!(binding.proxy_data());
+
+        if data.instances.is_empty() {
+            return;
+        }
+
+        // All instances pick the component they belong to
+        let object_hash = hash_to_rgba(binding.object_hash);
+        let picking_instances: Vec<ModelUniform> = data
+            .instances
+            .iter()
+            .map(|instance| ModelUniform {
+                object_hash,
+                ..*instance
+            })
+            .collect();
+        renderer.state.queue.write_buffer(
+            data.uniform.buffer(MeshUniformIndex::MeshData),
+            0,
+            picking_instances.as_bytes(),
+        );
+
+        self.draw(renderer, ctx, data, RenderPassType::Picking);
+    }
+
+    fn priority(&self, cache: Option<&AssetCache>) -> u32 {
+        let Some(cache) = cache else {
+            return PROXY_PRIORITY_SOLID;
+        };
+
+        if self.materials.iter().any(|m| {
+            cache
+                .material_instances
+                .inspect(*m, |m| m.transparent)
+                .unwrap_or(false)
+        }) {
+            PROXY_PRIORITY_TRANSPARENT
+        } else {
+            PROXY_PRIORITY_SOLID
+        }
+    }
+
+    fn bounds(&self) -> Option<BoundingSphere> {
+        self.model_bounding
+    }
+}
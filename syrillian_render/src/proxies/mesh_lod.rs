@@ -0,0 +1,140 @@
+use crate::rendering::render_data::CameraUniform;
+use syrillian_asset::mesh::MeshLod;
+use syrillian_utils::BoundingSphere;
+
+/// Screen error, as a fraction of the screen height, a level may show before a finer
+/// level is drawn. Used for levels without an explicit threshold.
+pub const LOD_MAX_SCREEN_ERROR: f32 = 0.002;
+
+/// How a [`MeshSceneProxy`](super::MeshSceneProxy) picks between the levels of detail of its mesh
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LodSelection {
+    /// Screen height fractions the bounds have to drop below for each level after the base
+    /// mesh. Missing thresholds are derived from the error of the level.
+    pub thresholds: Vec<f32>,
+    /// Width of the band above every threshold, relative to the threshold, in which both
+    /// levels are dithered into each other. Zero switches levels instantly.
+    pub crossfade: f32,
+}
+
+/// The levels a mesh is drawn with in one view
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LodChoice {
+    pub level: usize,
+    /// The next coarser level, with the fraction of pixels it already covers
+    pub fade_to: Option<(usize, f32)>,
+}
+
+impl LodChoice {
+    pub const BASE: LodChoice = LodChoice {
+        level: 0,
+        fade_to: None,
+    };
+}
+
+impl LodSelection {
+    /// Screen height fraction below which `level` (counting from one) is drawn
+    pub fn threshold(&self, lods: &[MeshLod], level: usize) -> f32 {
+        if let Some(threshold) = self.thresholds.get(level - 1) {
+            return *threshold;
+        }
+
+        match lods.get(level - 1) {
+            Some(lod) if lod.error > 0.0 => LOD_MAX_SCREEN_ERROR / lod.error,
+            Some(_) => f32::INFINITY,
+            None => 0.0,
+        }
+    }
+
+    /// Picks the level for bounds covering `screen_size` of the screen height
+    pub fn choose(&self, lods: &[MeshLod], screen_size: f32) -> LodChoice {
+        let level = (1..=lods.len())
+            .take_while(|&level| screen_size < self.threshold(lods, level))
+            .last()
+            .unwrap_or(0);
+
+        if self.crossfade <= 0.0 || level == lods.len() {
+            return LodChoice {
+                level,
+                fade_to: None,
+            };
+        }
+
+        let threshold = self.threshold(lods, level + 1);
+        let band = threshold * self.crossfade;
+        let fade_to = (screen_size < threshold + band && band > 0.0).then(|| {
+            let coverage = 1.0 - (screen_size - threshold) / band;
+            (level + 1, coverage.clamp(0.0, 1.0))
+        });
+
+        LodChoice { level, fade_to }
+    }
+}
+
+/// Fraction of the screen height `bounds` cover when seen through `camera`
+pub fn projected_screen_size(bounds: &BoundingSphere, camera: &CameraUniform) -> f32 {
+    let scale_y = camera.projection_mat.y_axis.y;
+    let orthographic = camera.projection_mat.w_axis.w == 1.0;
+
+    if orthographic {
+        return bounds.radius * scale_y;
+    }
+
+    let distance = bounds.center.distance(camera.pos).max(f32::EPSILON);
+    bounds.radius * scale_y / distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lods(errors: &[f32]) -> Vec<MeshLod> {
+        errors
+            .iter()
+            .map(|&error| MeshLod {
+                material_ranges: vec![],
+                error,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn smaller_bounds_pick_coarser_levels() {
+        let lods = lods(&[0.01, 0.02]);
+        let selection = LodSelection {
+            thresholds: vec![0.5, 0.25],
+            crossfade: 0.0,
+        };
+
+        assert_eq!(selection.choose(&lods, 0.8).level, 0);
+        assert_eq!(selection.choose(&lods, 0.4).level, 1);
+        assert_eq!(selection.choose(&lods, 0.1).level, 2);
+        assert_eq!(selection.choose(&[], 0.1), LodChoice::BASE);
+    }
+
+    #[test]
+    fn missing_thresholds_follow_level_error() {
+        let lods = lods(&[0.01]);
+        let selection = LodSelection::default();
+
+        let threshold = LOD_MAX_SCREEN_ERROR / 0.01;
+        assert_eq!(selection.threshold(&lods, 1), threshold);
+        assert_eq!(selection.choose(&lods, threshold * 1.1).level, 0);
+        assert_eq!(selection.choose(&lods, threshold * 0.9).level, 1);
+    }
+
+    #[test]
+    fn crossfade_band_blends_into_next_level() {
+        let lods = lods(&[0.01]);
+        let selection = LodSelection {
+            thresholds: vec![0.5],
+            crossfade: 0.2,
+        };
+
+        assert_eq!(selection.choose(&lods, 0.7).fade_to, None);
+        let (level, coverage) = selection.choose(&lods, 0.55).fade_to.unwrap();
+        assert_eq!(level, 1);
+        assert!((coverage - 0.5).abs() < 1e-4);
+        assert_eq!(selection.choose(&lods, 0.45).fade_to, None);
+    }
+}
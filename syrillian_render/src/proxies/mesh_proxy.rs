@@ -4,7 +4,8 @@ use crate::cache::mesh::{BindMeshBuffers, RenderMesh};
 use crate::cache::{AssetCache, RuntimeShader};
 use crate::model_uniform::ModelUniform;
 use crate::proxies::{
-    PROXY_PRIORITY_SOLID, PROXY_PRIORITY_TRANSPARENT, SceneProxy, SceneProxyBinding,
+    InstanceBatch, InstanceKey, LodChoice, LodSelection, PROXY_PRIORITY_SOLID,
+    PROXY_PRIORITY_TRANSPARENT, SceneProxy, SceneProxyBinding, projected_screen_size,
 };
 #[cfg(debug_assertions)]
 use crate::rendering::debug_renderer::DebugRenderer;
@@ -17,12 +18,16 @@ use glamx::Affine3A;
 use parking_lot::RwLockWriteGuard;
 use std::any::Any;
 use std::ops::Range;
+use std::slice;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use syrillian_asset::mesh::MeshLod;
 use syrillian_asset::shader::ShaderType;
-use syrillian_asset::store::H;
-use syrillian_asset::{HMaterialInstance, HMesh, Shader};
+use syrillian_asset::{HMaterialInstance, HMesh};
 use syrillian_macros::UniformIndex;
 use syrillian_utils::BoundingSphere;
-use wgpu::RenderPass;
+use tracing::warn;
+use wgpu::{BindGroup, RenderPass};
 use zerocopy::IntoBytes;
 
 #[repr(u8)]
@@ -52,8 +57,41 @@ pub struct MeshSceneProxy {
     pub mesh: HMesh,
     pub materials: Vec<HMaterialInstance>,
     pub material_ranges: Vec<Range<u32>>,
+    pub lods: Vec<MeshLod>,
+    pub lod_selection: LodSelection,
     pub bounding: Option<BoundingSphere>,
     pub model_bounding: Option<BoundingSphere>,
+    /// Layer the mesh is on, decals only project onto layers in their mask
+    pub decal_layer: u32,
+}
+
+/// The models the two levels of a crossfade are drawn with, in this pass's slice of the
+/// renderer's instance buffer.
+pub(crate) struct FadeModels {
+    bind_group: BindGroup,
+    /// Instance of the fading out level, the fading in level follows it
+    fade_out: u32,
+}
+
+impl FadeModels {
+    /// Writes the crossfade models for the current pass, `coverage` being the fraction of
+    /// pixels the coarser level covers.
+    fn push(renderer: &Renderer, model: ModelUniform, coverage: f32) -> Self {
+        let fade_out = ModelUniform {
+            lod_fade: -coverage.max(f32::EPSILON),
+            ..model
+        };
+        let fade_in = ModelUniform {
+            lod_fade: coverage.max(f32::EPSILON),
+            ..model
+        };
+
+        let (bind_group, instances) = renderer.push_instances(&[fade_out, fade_in]);
+        Self {
+            bind_group,
+            fade_out: instances.start,
+        }
+    }
 }
 
 impl RenderMeshData {
@@ -72,8 +110,14 @@ impl RenderMeshData {
             bounds_uniform: None,
         }
     }
-    pub fn activate_shader(&self, shader: &RuntimeShader, ctx: &GPUDrawCtx, pass: &mut RenderPass) {
-        shader.activate(pass, ctx);
+    pub fn activate_shader(
+        &self,
+        shader: &RuntimeShader,
+        ctx: &GPUDrawCtx,
+        pass: &mut RenderPass,
+        double_sided: bool,
+    ) {
+        shader.activate_sided(pass, ctx, double_sided);
 
         if let Some(idx) = shader.bind_groups().model {
             pass.set_bind_group(idx, self.visible_uniform.bind_group(), &[]);
@@ -116,6 +160,22 @@ impl SceneProxy for MeshSceneProxy {
         self.refresh_transform_debug(data, renderer, &render_affine, _world_affine.as_ref());
     }
 
+    fn update_render(&mut self, renderer: &Renderer, data: &mut (dyn Any + Send)) {
+        let data: &mut RenderMeshData = proxy_data_mut!(data);
+
+        let decal_layer = data.visible_mesh_data.decal_layer;
+        if decal_layer == self.decal_layer {
+            return;
+        }
+
+        data.visible_mesh_data.decal_layer = self.decal_layer;
+        renderer.state.queue.write_buffer(
+            data.visible_uniform.buffer(MeshUniformIndex::MeshData),
+            0,
+            data.visible_mesh_data.as_bytes(),
+        );
+    }
+
     fn render<'a>(&self, renderer: &Renderer, ctx: &GPUDrawCtx, binding: &SceneProxyBinding) {
         let data: &RenderMeshData = proxy_data!(binding.proxy_data());
 
@@ -123,12 +183,18 @@ impl SceneProxy for MeshSceneProxy {
             return;
         };
 
+        let lod = self.lod_choice(ctx);
+        let fade = lod
+            .fade_to
+            .map(|(_, coverage)| FadeModels::push(renderer, data.visible_mesh_data, coverage));
+
         let mut pass = ctx.pass.write();
-        self.draw_mesh_base(ctx, &renderer.cache, &mesh, data, &mut pass);
+        self.draw_mesh_base(ctx, &renderer.cache, &mesh, data, lod, fade, &mut pass);
 
         #[cfg(debug_assertions)]
         if !ctx.transparency_pass && DebugRenderer::mesh_edges() {
-            draw_edges(ctx, &renderer.cache, &mesh, data, &mut pass);
+            let ranges = self.lod_ranges(lod.level);
+            draw_edges(ctx, &renderer.cache, &mesh, data, ranges, &mut pass);
         }
 
         #[cfg(debug_assertions)]
@@ -149,8 +215,9 @@ impl SceneProxy for MeshSceneProxy {
             return;
         };
 
+        let level = self.lod_choice(ctx).level;
         let mut pass = ctx.pass.write();
-        self.draw_mesh_shadow(ctx, &renderer.cache, &mesh, data, &mut pass);
+        self.draw_mesh_shadow(ctx, &renderer.cache, &mesh, data, level, &mut pass);
     }
 
     fn render_picking(&self, renderer: &Renderer, ctx: &GPUDrawCtx, binding: &SceneProxyBinding) {
@@ -162,17 +229,18 @@ impl SceneProxy for MeshSceneProxy {
             return;
         };
 
-        let mut picking_uniform = data.visible_mesh_data;
-        picking_uniform.object_hash = hash_to_rgba(binding.object_hash);
-        renderer.state.queue.write_buffer(
-            data.visible_uniform.buffer(MeshUniformIndex::MeshData),
-            0,
-            picking_uniform.as_bytes(),
-        );
+        write_picking_hash(renderer, data, binding);
+
+        let lod = self.lod_choice(ctx);
+        let fade = lod.fade_to.map(|(_, coverage)| {
+            let mut picking_model = data.visible_mesh_data;
+            picking_model.object_hash = hash_to_rgba(binding.object_hash);
+            FadeModels::push(renderer, picking_model, coverage)
+        });
 
         let mut pass = ctx.pass.write();
 
-        self.draw_mesh_picking(ctx, &renderer.cache, &mesh, data, &mut pass);
+        self.draw_mesh_picking(ctx, &renderer.cache, &mesh, data, lod, fade, &mut pass);
     }
 
     fn priority(&self, cache: Option<&AssetCache>) -> u32 {
@@ -195,19 +263,103 @@ impl SceneProxy for MeshSceneProxy {
     fn bounds(&self) -> Option<BoundingSphere> {
         self.model_bounding
     }
+
+    fn instance_key(&self, ctx: &GPUDrawCtx) -> Option<InstanceKey<'_>> {
+        let lod = self.lod_choice(ctx);
+        if lod.fade_to.is_some() {
+            // Crossfading draws two levels with their own models
+            return None;
+        }
+
+        Some(InstanceKey {
+            mesh: self.mesh,
+            materials: &self.materials,
+            material_ranges: self.lod_ranges(lod.level),
+        })
+    }
+
+    fn instance_data(&self, binding: &SceneProxyBinding) -> Option<ModelUniform> {
+        let data = binding.proxy_data().downcast_ref::<RenderMeshData>()?;
+        Some(data.visible_mesh_data)
+    }
+
+    fn render_instances(&self, renderer: &Renderer, ctx: &GPUDrawCtx, batch: &InstanceBatch) {
+        let Some(mesh) = renderer.cache.mesh(self.mesh) else {
+            return;
+        };
+
+        let model = ModelBinding::Batch(batch, renderer);
+        let ranges = self.lod_ranges(self.lod_choice(ctx).level);
+        let mut pass = ctx.pass.write();
+        self.draw_materials(
+            ctx,
+            &renderer.cache,
+            &mesh,
+            ranges,
+            model,
+            &mut pass,
+            ctx.pass_type,
+        );
+
+        #[cfg(debug_assertions)]
+        if ctx.pass_type == RenderPassType::Color {
+            for binding in batch.members {
+                let data: &RenderMeshData = proxy_data!(binding.proxy_data());
+                if DebugRenderer::mesh_edges() {
+                    draw_edges(ctx, &renderer.cache, &mesh, data, ranges, &mut pass);
+                }
+                if DebugRenderer::mesh_vertex_normals() {
+                    draw_vertex_normals(ctx, &renderer.cache, &mesh, data, &mut pass);
+                }
+                if DebugRenderer::mesh_bounds() {
+                    draw_bounds(ctx, &renderer.cache, data, &mut pass);
+                }
+            }
+        }
+    }
 }
 
 impl MeshSceneProxy {
+    /// The levels this mesh is drawn with for the view of `ctx`
+    pub fn lod_choice(&self, ctx: &GPUDrawCtx) -> LodChoice {
+        let Some(bounds) = self.model_bounding.filter(|_| !self.lods.is_empty()) else {
+            return LodChoice::BASE;
+        };
+
+        let screen_size = projected_screen_size(&bounds, ctx.lod_camera);
+        let choice = self.lod_selection.choose(&self.lods, screen_size);
+
+        match ctx.pass_type {
+            RenderPassType::Shadow => LodChoice {
+                fade_to: None,
+                ..choice
+            },
+            _ => choice,
+        }
+    }
+
+    /// The material ranges of LOD `level`, where level zero is the base mesh
+    pub fn lod_ranges(&self, level: usize) -> &[Range<u32>] {
+        match level.checked_sub(1).and_then(|lod| self.lods.get(lod)) {
+            Some(lod) => &lod.material_ranges,
+            None => &self.material_ranges,
+        }
+    }
+
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     fn draw_mesh_base(
         &self,
         ctx: &GPUDrawCtx,
         cache: &AssetCache,
         mesh: &RenderMesh,
         runtime: &RenderMeshData,
+        lod: LodChoice,
+        fade: Option<FadeModels>,
         pass: &mut RwLockWriteGuard<RenderPass>,
     ) {
-        self.draw_materials(ctx, cache, mesh, runtime, pass, RenderPassType::Color);
+        let model = (runtime, fade.as_ref());
+        self.draw_lods(ctx, cache, mesh, model, lod, pass, RenderPassType::Color);
     }
 
     #[inline]
@@ -217,102 +369,95 @@ impl MeshSceneProxy {
         cache: &AssetCache,
         mesh: &RenderMesh,
         runtime: &RenderMeshData,
+        level: usize,
         pass: &mut RwLockWriteGuard<RenderPass>,
     ) {
-        self.draw_materials(ctx, cache, mesh, runtime, pass, RenderPassType::Shadow);
+        let model = ModelBinding::Single(runtime);
+        let ranges = self.lod_ranges(level);
+        self.draw_materials(
+            ctx,
+            cache,
+            mesh,
+            ranges,
+            model,
+            pass,
+            RenderPassType::Shadow,
+        );
     }
 
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     fn draw_mesh_picking(
         &self,
         ctx: &GPUDrawCtx,
         cache: &AssetCache,
         mesh: &RenderMesh,
         runtime: &RenderMeshData,
+        lod: LodChoice,
+        fade: Option<FadeModels>,
         pass: &mut RwLockWriteGuard<RenderPass>,
     ) {
-        self.draw_materials(ctx, cache, mesh, runtime, pass, RenderPassType::Picking);
+        self.draw_lods(
+            ctx,
+            cache,
+            mesh,
+            (runtime, fade.as_ref()),
+            lod,
+            pass,
+            RenderPassType::Picking,
+        );
     }
 
-    fn draw_materials(
+    /// Draws the chosen level, dithered together with the next one while crossfading with
+    /// the models in `fade`
+    #[allow(clippy::too_many_arguments)]
+    fn draw_lods(
         &self,
         ctx: &GPUDrawCtx,
         cache: &AssetCache,
         mesh: &RenderMesh,
-        runtime: &RenderMeshData,
+        (runtime, fade): (&RenderMeshData, Option<&FadeModels>),
+        lod: LodChoice,
         pass: &mut RwLockWriteGuard<RenderPass>,
         pass_type: RenderPassType,
     ) {
-        let mut current_shader: Option<H<Shader>> = None;
+        let ranges = self.lod_ranges(lod.level);
 
-        let ranges: &[Range<u32>] = if self.material_ranges.is_empty() {
-            &[Range {
-                start: 0,
-                end: mesh.total_point_count(),
-            }]
-        } else {
-            &self.material_ranges
+        let (Some((next_level, _)), Some(fade)) = (lod.fade_to, fade) else {
+            let model = ModelBinding::Single(runtime);
+            self.draw_materials(ctx, cache, mesh, ranges, model, pass, pass_type);
+            return;
         };
 
-        for (i, range) in ranges.iter().enumerate() {
-            let h_mat = self
-                .materials
-                .get(i)
-                .copied()
-                .unwrap_or(HMaterialInstance::DEFAULT);
-            let material = cache.material_instance(h_mat);
-            let shader_set = material.shader_set;
-
-            let target_shader = match pass_type {
-                RenderPassType::Picking | RenderPassType::PickingUi => shader_set.picking,
-                RenderPassType::Shadow => shader_set.shadow,
-                _ => shader_set.base,
-            };
-
-            if pass_type == RenderPassType::Color && material.transparent ^ ctx.transparency_pass {
-                continue; // either transparent in a non-transparency pass, or non-transparent in a transparency pass
-            }
-
-            if pass_type == RenderPassType::Shadow
-                && (!material.cast_shadows || material.transparent)
-            {
-                continue;
-            }
-
-            let shader = cache.shader(target_shader);
-
-            if current_shader != Some(target_shader) {
-                runtime.activate_shader(&shader, ctx, pass);
-                current_shader = Some(target_shader);
-            }
-
-            if let Some(idx) = shader.bind_groups().material {
-                pass.set_bind_group(idx, &material.bind_group, &[]);
-            }
-
-            if matches!(pass_type, RenderPassType::Color | RenderPassType::Picking)
-                && shader.immediate_size > 0
-            {
-                debug_assert_eq!(
-                    shader.immediate_size as usize,
-                    material.immediates.len(),
-                    "Immediate size of shader and material did not match. Shader requested {}, but material only supplied {}",
-                    shader.immediate_size,
-                    material.immediates.len()
-                );
-
-                pass.set_immediates(0, &material.immediates);
-            }
+        let fade_out = ModelBinding::Fading(runtime, fade, fade.fade_out);
+        self.draw_materials(ctx, cache, mesh, ranges, fade_out, pass, pass_type);
 
-            let mesh_buffers = match shader.shader_type {
-                ShaderType::Picking | ShaderType::Shadow if shader.is_opaque() => {
-                    BindMeshBuffers::POSITION
-                }
-                _ => BindMeshBuffers::all(),
-            };
+        let fade_in = ModelBinding::Fading(runtime, fade, fade.fade_out + 1);
+        let next_ranges = self.lod_ranges(next_level);
+        self.draw_materials(ctx, cache, mesh, next_ranges, fade_in, pass, pass_type);
+    }
 
-            mesh.draw(range.clone(), pass, mesh_buffers);
-        }
+    #[allow(clippy::too_many_arguments)]
+    fn draw_materials(
+        &self,
+        ctx: &GPUDrawCtx,
+        cache: &AssetCache,
+        mesh: &RenderMesh,
+        material_ranges: &[Range<u32>],
+        model: ModelBinding,
+        pass: &mut RwLockWriteGuard<RenderPass>,
+        pass_type: RenderPassType,
+    ) {
+        draw_materials(
+            ctx,
+            cache,
+            mesh,
+            &self.materials,
+            material_ranges,
+            model,
+            pass,
+            pass_type,
+        );
     }
 
     fn setup_mesh_data(
@@ -323,10 +468,11 @@ impl MeshSceneProxy {
     ) -> RenderMeshData {
         let device = &renderer.state.device;
         let model_bgl = renderer.cache.bgl_model();
-        let visible_mesh_data = ModelUniform::from_affine(&render_affine);
+        let mut visible_mesh_data = ModelUniform::from_affine(&render_affine);
+        visible_mesh_data.decal_layer = self.decal_layer;
 
         let visible_uniform = ShaderUniform::<MeshUniformIndex>::builder(model_bgl.clone())
-            .with_buffer_data(&visible_mesh_data)
+            .with_storage_buffer_data(slice::from_ref(&visible_mesh_data))
             .build(device);
 
         #[cfg(debug_assertions)]
@@ -338,7 +484,7 @@ impl MeshSceneProxy {
             let mesh_data = ModelUniform::from_affine(&real_affine);
             real_uniform = Some(
                 ShaderUniform::<MeshUniformIndex>::builder(model_bgl.clone())
-                    .with_buffer_data(&mesh_data)
+                    .with_storage_buffer_data(slice::from_ref(&mesh_data))
                     .build(device),
             );
             real_mesh_data = Some(mesh_data);
@@ -349,7 +495,7 @@ impl MeshSceneProxy {
             .bounds_model_uniform(&render_affine)
             .map(|bounds_data| {
                 ShaderUniform::<MeshUniformIndex>::builder(model_bgl.clone())
-                    .with_buffer_data(&bounds_data)
+                    .with_storage_buffer_data(slice::from_ref(&bounds_data))
                     .build(device)
             });
 
@@ -406,7 +552,7 @@ impl MeshSceneProxy {
             } else {
                 data.real_uniform = Some(
                     ShaderUniform::<MeshUniformIndex>::builder(renderer.cache.bgl_model().clone())
-                        .with_buffer_data(&mesh_data)
+                        .with_storage_buffer_data(slice::from_ref(&mesh_data))
                         .build(&renderer.state.device),
                 );
             };
@@ -428,12 +574,194 @@ impl MeshSceneProxy {
     }
 }
 
+/// Where a mesh draw reads its model data from.
+#[derive(Clone)]
+pub(crate) enum ModelBinding<'a> {
+    Single(&'a RenderMeshData),
+    /// One of the crossfade models of a single mesh, by instance. Shaders that can't read
+    /// it per instance fall back to the single model.
+    Fading(&'a RenderMeshData, &'a FadeModels, u32),
+    Batch(&'a InstanceBatch<'a>, &'a Renderer),
+    Instances(&'a BindGroup, Range<u32>),
+}
+
+impl ModelBinding<'_> {
+    fn activate_shader(
+        &self,
+        shader: &RuntimeShader,
+        ctx: &GPUDrawCtx,
+        pass: &mut RenderPass,
+        double_sided: bool,
+    ) {
+        match self {
+            ModelBinding::Fading(_, fade, _) if shader.is_instanced() => {
+                shader.activate_sided(pass, ctx, double_sided);
+
+                if let Some(idx) = shader.bind_groups().model {
+                    pass.set_bind_group(idx, &fade.bind_group, &[]);
+                }
+            }
+            ModelBinding::Single(runtime) | ModelBinding::Fading(runtime, ..) => {
+                runtime.activate_shader(shader, ctx, pass, double_sided)
+            }
+            ModelBinding::Batch(batch, _) => {
+                shader.activate_sided(pass, ctx, double_sided);
+
+                if let Some(idx) = shader.bind_groups().model {
+                    pass.set_bind_group(idx, &batch.bind_group, &[]);
+                }
+            }
+            ModelBinding::Instances(bind_group, _) => {
+                shader.activate_sided(pass, ctx, double_sided);
+
+                if let Some(idx) = shader.bind_groups().model {
+                    pass.set_bind_group(idx, *bind_group, &[]);
+                }
+            }
+        }
+    }
+}
+
+fn write_picking_hash(renderer: &Renderer, data: &RenderMeshData, binding: &SceneProxyBinding) {
+    let mut picking_uniform = data.visible_mesh_data;
+    picking_uniform.object_hash = hash_to_rgba(binding.object_hash);
+    renderer.state.queue.write_buffer(
+        data.visible_uniform.buffer(MeshUniformIndex::MeshData),
+        0,
+        picking_uniform.as_bytes(),
+    );
+}
+
+/// Draws every material range of `mesh` that belongs into `pass_type`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn draw_materials(
+    ctx: &GPUDrawCtx,
+    cache: &AssetCache,
+    mesh: &RenderMesh,
+    materials: &[HMaterialInstance],
+    material_ranges: &[Range<u32>],
+    model: ModelBinding,
+    pass: &mut RwLockWriteGuard<RenderPass>,
+    pass_type: RenderPassType,
+) {
+    let mut current_shader: Option<(Arc<RuntimeShader>, bool)> = None;
+
+    let ranges: &[Range<u32>] = if material_ranges.is_empty() {
+        &[Range {
+            start: 0,
+            end: mesh.total_point_count(),
+        }]
+    } else {
+        material_ranges
+    };
+
+    for (i, range) in ranges.iter().enumerate() {
+        let h_mat = materials
+            .get(i)
+            .copied()
+            .unwrap_or(HMaterialInstance::DEFAULT);
+        let material = cache.material_instance(h_mat);
+        let shader_set = material.shader_set;
+
+        if pass_type == RenderPassType::Color && material.transparent ^ ctx.transparency_pass {
+            continue; // either transparent in a non-transparency pass, or non-transparent in a transparency pass
+        }
+
+        if pass_type == RenderPassType::Shadow && (!material.cast_shadows || material.transparent) {
+            continue;
+        }
+
+        let shader = match pass_type {
+            RenderPassType::Picking | RenderPassType::PickingUi => cache.shader(shader_set.picking),
+            RenderPassType::Shadow => cache.shader(shader_set.shadow),
+            _ => material.base_shader(cache),
+        };
+
+        let is_current = current_shader
+            .as_ref()
+            .is_some_and(|(current, double_sided)| {
+                Arc::ptr_eq(current, &shader) && *double_sided == material.double_sided
+            });
+        if !is_current {
+            model.activate_shader(&shader, ctx, pass, material.double_sided);
+            current_shader = Some((shader.clone(), material.double_sided));
+        }
+
+        if let Some(idx) = shader.bind_groups().material {
+            pass.set_bind_group(idx, &material.bind_group, &[]);
+        }
+
+        if matches!(
+            pass_type,
+            RenderPassType::Color | RenderPassType::Picking | RenderPassType::Shadow
+        ) && shader.immediate_size > 0
+        {
+            debug_assert_eq!(
+                shader.immediate_size as usize,
+                material.immediates.len(),
+                "Immediate size of shader and material did not match. Shader requested {}, but material only supplied {}",
+                shader.immediate_size,
+                material.immediates.len()
+            );
+
+            pass.set_immediates(0, &material.immediates);
+        }
+
+        let mesh_buffers = match shader.shader_type {
+            ShaderType::Picking | ShaderType::Shadow if shader.is_opaque() => {
+                BindMeshBuffers::POSITION
+            }
+            _ => BindMeshBuffers::all(),
+        };
+
+        match &model {
+            ModelBinding::Single(_) => mesh.draw(range.clone(), pass, mesh_buffers),
+            ModelBinding::Fading(_, _, instance) if shader.is_instanced() => {
+                mesh.draw_instanced(range.clone(), *instance..*instance + 1, pass, mesh_buffers)
+            }
+            // Shaders reading a single model can't dither, they draw both levels whole
+            ModelBinding::Fading(..) => mesh.draw(range.clone(), pass, mesh_buffers),
+            ModelBinding::Batch(batch, _) if shader.is_instanced() => {
+                mesh.draw_instanced(range.clone(), batch.instances.clone(), pass, mesh_buffers)
+            }
+            ModelBinding::Instances(_, instances) if shader.is_instanced() => {
+                mesh.draw_instanced(range.clone(), instances.clone(), pass, mesh_buffers)
+            }
+            ModelBinding::Instances(..) => {
+                static WARNED: AtomicBool = AtomicBool::new(false);
+                if !WARNED.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "Shader {:?} can't draw instances, skipping instanced mesh",
+                        shader.name()
+                    );
+                }
+            }
+            ModelBinding::Batch(batch, renderer) => {
+                // Shaders that only read one model get one draw per member
+                let Some(idx) = shader.bind_groups().model else {
+                    continue;
+                };
+                for binding in batch.members {
+                    let data: &RenderMeshData = proxy_data!(binding.proxy_data());
+                    if pass_type == RenderPassType::Picking {
+                        write_picking_hash(renderer, data, binding);
+                    }
+                    pass.set_bind_group(idx, data.visible_uniform.bind_group(), &[]);
+                    mesh.draw(range.clone(), pass, mesh_buffers);
+                }
+                current_shader = None;
+            }
+        }
+    }
+}
+
 #[cfg(debug_assertions)]
 fn draw_edges(
     ctx: &GPUDrawCtx,
     cache: &AssetCache,
     mesh: &RenderMesh,
     runtime: &RenderMeshData,
+    ranges: &[Range<u32>],
     pass: &mut RenderPass,
 ) {
     use glamx::Vec4;
@@ -443,10 +771,19 @@ fn draw_edges(
     const REAL_COLOR: Vec4 = Vec4::new(1.0, 0.2, 0.2, 1.0);
 
     let shader = cache.shader(HShader::DEBUG_EDGES);
-    runtime.activate_shader(&shader, ctx, pass);
+    runtime.activate_shader(&shader, ctx, pass, false);
+
+    let draw_ranges = |pass: &mut RenderPass| {
+        if ranges.is_empty() {
+            mesh.draw_all(pass, BindMeshBuffers::POSITION);
+        }
+        for range in ranges {
+            mesh.draw(range.clone(), pass, BindMeshBuffers::POSITION);
+        }
+    };
 
     pass.set_immediates(0, COLOR.as_bytes());
-    mesh.draw_all(pass, BindMeshBuffers::POSITION);
+    draw_ranges(pass);
 
     if let Some(real_uniform) = &runtime.real_uniform {
         pass.set_immediates(0, REAL_COLOR.as_bytes());
@@ -455,7 +792,7 @@ fn draw_edges(
             pass.set_bind_group(model, real_uniform.bind_group(), &[]);
         }
 
-        mesh.draw_all(pass, BindMeshBuffers::POSITION);
+        draw_ranges(pass);
     }
 }
 
@@ -470,7 +807,7 @@ fn draw_vertex_normals(
     use syrillian_asset::HShader;
 
     let shader = cache.shader(HShader::DEBUG_VERTEX_NORMALS);
-    runtime.activate_shader(&shader, ctx, pass);
+    runtime.activate_shader(&shader, ctx, pass, false);
 
     mesh.draw_all_as_instances(0..2, pass, BindMeshBuffers::POSITION_NORMAL);
 }
@@ -495,7 +832,7 @@ fn draw_bounds(
     };
 
     let shader = cache.shader(HShader::DEBUG_MESH_BOUNDS);
-    runtime.activate_shader(&shader, ctx, pass);
+    runtime.activate_shader(&shader, ctx, pass, false);
     pass.set_immediates(0, COLOR.as_bytes());
 
     if let Some(idx) = shader.bind_groups().model {
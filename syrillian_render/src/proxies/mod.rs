@@ -1,20 +1,28 @@
 use glamx::Affine3A;
 use std::any::Any;
 use std::fmt::Debug;
+use std::ops::Range;
 
 pub mod debug_proxy;
+pub mod instanced_mesh_proxy;
+pub mod mesh_lod;
 pub mod mesh_proxy;
 pub mod skinned_mesh_proxy;
 pub mod text_proxy;
 
+use crate::model_uniform::ModelUniform;
 use crate::rendering::renderer::Renderer;
 use crate::rendering::{GPUDrawCtx, RenderPassType};
 use crate::{AssetCache, ObjectHash};
 pub use debug_proxy::*;
+pub use instanced_mesh_proxy::*;
+pub use mesh_lod::*;
 pub use mesh_proxy::*;
+use syrillian_asset::{HMaterialInstance, HMesh};
 use syrillian_utils::BoundingSphere;
 use syrillian_utils::component_id::TypedComponentId;
 pub use text_proxy::*;
+use wgpu::BindGroup;
 
 #[macro_export]
 macro_rules! proxy_data_mut {
@@ -122,6 +130,37 @@ pub trait SceneProxy: Send + Any + Debug {
     fn bounds(&self) -> Option<BoundingSphere> {
         None
     }
+
+    /// Proxies that return the same key are drawn together in one instanced batch
+    /// by the first proxy of the batch. Keys may change with the view drawn by `ctx`.
+    fn instance_key(&self, _ctx: &GPUDrawCtx) -> Option<InstanceKey<'_>> {
+        None
+    }
+
+    /// The model data this proxy contributes to an instanced batch.
+    fn instance_data(&self, _binding: &SceneProxyBinding) -> Option<ModelUniform> {
+        None
+    }
+
+    /// Draws all members of `batch`. Only called on proxies returning an [`InstanceKey`].
+    fn render_instances(&self, _renderer: &Renderer, _ctx: &GPUDrawCtx, _batch: &InstanceBatch) {}
+}
+
+/// Identifies proxies that can be drawn with the same mesh and materials.
+/// Borrows from the proxy, so building one per proxy and pass doesn't allocate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstanceKey<'a> {
+    pub mesh: HMesh,
+    pub materials: &'a [HMaterialInstance],
+    pub material_ranges: &'a [Range<u32>],
+}
+
+/// A group of proxies sharing an [`InstanceKey`], with their model data already
+/// uploaded to the instance buffer bound by `bind_group`.
+pub struct InstanceBatch<'a> {
+    pub bind_group: BindGroup,
+    pub instances: Range<u32>,
+    pub members: &'a [&'a SceneProxyBinding],
 }
 
 pub struct SceneProxyBinding {
@@ -19,10 +19,11 @@ use parking_lot::RwLockWriteGuard;
 use static_assertions::const_assert_eq;
 use std::any::Any;
 use std::ops::Range;
+use std::slice;
+use std::sync::Arc;
 use syrillian_asset::mesh::bone::BoneData;
 use syrillian_asset::shader::ShaderType;
-use syrillian_asset::store::H;
-use syrillian_asset::{HComputeShader, HMaterialInstance, HSkinnedMesh, Shader};
+use syrillian_asset::{HComputeShader, HMaterialInstance, HSkinnedMesh};
 use syrillian_macros::UniformIndex;
 use syrillian_render::rendering::mesh::BindMeshBuffers;
 use syrillian_utils::BoundingSphere;
@@ -50,13 +51,15 @@ pub struct RenderSkinnedMeshData {
     pub skinning_uniforms: Vec<ShaderUniform<MeshSkinningComputeUniformIndex>>,
     pub skinning_vertex_counts: Vec<u32>,
     pub skinning_mesh: Option<HSkinnedMesh>,
+    pub morph_weights: Option<wgpu::Buffer>,
+    pub morph_target_count: u32,
 }
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Immutable, IntoBytes, FromBytes, KnownLayout)]
 struct MeshSkinningParams {
     vertex_count: u32,
-    _pad0: u32,
+    morph_target_count: u32,
     _pad1: u32,
     _pad2: u32,
 }
@@ -72,6 +75,8 @@ pub enum MeshSkinningComputeUniformIndex {
     DestPosition = 3,
     DestNormal = 4,
     DestTangent = 5,
+    MorphDeltas = 6,
+    MorphWeights = 7,
 }
 
 #[derive(Debug, Clone)]
@@ -81,8 +86,12 @@ pub struct SkinnedMeshSceneProxy {
     pub material_ranges: Vec<Range<u32>>,
     pub bone_data: BoneData,
     pub bones_dirty: bool,
+    pub morph_weights: Vec<f32>,
+    pub morph_weights_dirty: bool,
     pub bounding: Option<BoundingSphere>,
     pub model_bounding: Option<BoundingSphere>,
+    /// Layer the mesh is on, decals only project onto layers in their mask
+    pub decal_layer: u32,
 }
 
 impl RenderSkinnedMeshData {
@@ -91,8 +100,9 @@ impl RenderSkinnedMeshData {
         shader: &RuntimeShader,
         ctx: &GPUDrawCtx,
         pass: &mut RenderPass,
+        double_sided: bool,
     ) -> bool {
-        shader.activate(pass, ctx);
+        shader.activate_sided(pass, ctx, double_sided);
 
         if let Some(idx) = shader.bind_groups().model {
             pass.set_bind_group(idx, self.mesh_uniform.bind_group(), &[]);
@@ -114,6 +124,8 @@ impl RenderSkinnedMeshData {
         self.skinning_uniforms.clear();
         self.skinning_vertex_counts.clear();
         self.skinning_mesh = Some(mesh_handle);
+        self.morph_weights = None;
+        self.morph_target_count = 0;
 
         let Some(mesh) = renderer.cache.skinned_mesh(mesh_handle) else {
             return false;
@@ -126,6 +138,20 @@ impl RenderSkinnedMeshData {
             .buffer(SkinnedMeshUniformIndex::BoneData)
             .clone();
 
+        let morph_target_count = mesh
+            .meshlets()
+            .iter()
+            .filter_map(|m| m.pre_skin_meshlet())
+            .map(|p| p.morph_target_count)
+            .max()
+            .unwrap_or(0);
+        let morph_weights = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mesh Morph Target Weights Buffer"),
+            size: (morph_target_count.max(4) as usize * size_of::<f32>()) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         for meshlet in mesh.meshlets() {
             let vertex_count = meshlet.vertex_count;
             let Some(pre_skin) = meshlet.pre_skin_meshlet() else {
@@ -143,7 +169,7 @@ impl RenderSkinnedMeshData {
             );
             let params = MeshSkinningParams {
                 vertex_count,
-                _pad0: 0,
+                morph_target_count: pre_skin.morph_target_count,
                 _pad1: 0,
                 _pad2: 0,
             };
@@ -156,6 +182,8 @@ impl RenderSkinnedMeshData {
                     .with_storage_buffer(output.position.clone())
                     .with_storage_buffer(output.normal.clone())
                     .with_storage_buffer(output.tangent.clone())
+                    .with_storage_buffer(pre_skin.morph_deltas.clone())
+                    .with_storage_buffer(morph_weights.clone())
                     .build(device);
 
             self.skinned_meshlets.push(output);
@@ -163,9 +191,28 @@ impl RenderSkinnedMeshData {
             self.skinning_vertex_counts.push(vertex_count);
         }
 
+        self.morph_weights = Some(morph_weights);
+        self.morph_target_count = morph_target_count;
+
         true
     }
 
+    fn write_morph_weights(&self, renderer: &Renderer, weights: &[f32]) {
+        let Some(buffer) = &self.morph_weights else {
+            return;
+        };
+        if self.morph_target_count == 0 {
+            return;
+        }
+
+        let mut padded = weights.to_vec();
+        padded.resize(self.morph_target_count as usize, 0.0);
+        renderer
+            .state
+            .queue
+            .write_buffer(buffer, 0, padded.as_bytes());
+    }
+
     // TODO: Improve dispatching to be centralized so the driver can batch better
     fn dispatch_skinning(&self, renderer: &Renderer) {
         if self.skinning_uniforms.is_empty() {
@@ -252,6 +299,17 @@ impl SceneProxy for SkinnedMeshSceneProxy {
 
         // TODO: Consider Rigid Body render isometry interpolation for mesh local to world
 
+        let decal_layer = data.mesh_data.decal_layer;
+        if decal_layer != self.decal_layer {
+            data.mesh_data.decal_layer = self.decal_layer;
+            renderer.state.queue.write_buffer(
+                data.skinning_uniform
+                    .buffer(SkinnedMeshUniformIndex::MeshData),
+                0,
+                data.mesh_data.as_bytes(),
+            );
+        }
+
         let mut skinning_needs_dispatch = false;
 
         if self.bones_dirty {
@@ -266,6 +324,13 @@ impl SceneProxy for SkinnedMeshSceneProxy {
         }
 
         if data.ensure_skinning_runtime(renderer, self.mesh) {
+            self.morph_weights_dirty = true;
+            skinning_needs_dispatch = true;
+        }
+
+        if self.morph_weights_dirty {
+            data.write_morph_weights(renderer, &self.morph_weights);
+            self.morph_weights_dirty = false;
             skinning_needs_dispatch = true;
         }
 
@@ -399,7 +464,7 @@ impl SkinnedMeshSceneProxy {
         pass: &mut RwLockWriteGuard<RenderPass>,
         pass_type: RenderPassType,
     ) {
-        let mut current_shader: Option<H<Shader>> = None;
+        let mut current_shader: Option<(Arc<RuntimeShader>, bool)> = None;
 
         let ranges: &[Range<u32>] = if self.material_ranges.is_empty() {
             &[Range {
@@ -419,12 +484,6 @@ impl SkinnedMeshSceneProxy {
             let material = cache.material_instance(h_mat);
             let shader_set = material.shader_set;
 
-            let target_shader = match pass_type {
-                RenderPassType::Picking | RenderPassType::PickingUi => shader_set.picking,
-                RenderPassType::Shadow => shader_set.shadow,
-                _ => shader_set.base,
-            };
-
             if pass_type == RenderPassType::Color && material.transparent ^ ctx.transparency_pass {
                 continue; // either transparent in a non-transparency pass, or non-transparent in a transparency pass
             }
@@ -435,21 +494,34 @@ impl SkinnedMeshSceneProxy {
                 continue;
             }
 
-            let shader = cache.shader(target_shader);
+            let shader = match pass_type {
+                RenderPassType::Picking | RenderPassType::PickingUi => {
+                    cache.shader(shader_set.picking)
+                }
+                RenderPassType::Shadow => cache.shader(shader_set.shadow),
+                _ => material.base_shader(cache),
+            };
 
-            if current_shader != Some(target_shader) {
-                if !runtime.activate_shader(&shader, ctx, pass) {
+            let is_current = current_shader
+                .as_ref()
+                .is_some_and(|(current, double_sided)| {
+                    Arc::ptr_eq(current, &shader) && *double_sided == material.double_sided
+                });
+            if !is_current {
+                if !runtime.activate_shader(&shader, ctx, pass, material.double_sided) {
                     return;
                 }
-                current_shader = Some(target_shader);
+                current_shader = Some((shader.clone(), material.double_sided));
             }
 
             if let Some(idx) = shader.bind_groups().material {
                 pass.set_bind_group(idx, &material.bind_group, &[]);
             }
 
-            if matches!(pass_type, RenderPassType::Color | RenderPassType::Picking)
-                && shader.immediate_size > 0
+            if matches!(
+                pass_type,
+                RenderPassType::Color | RenderPassType::Picking | RenderPassType::Shadow
+            ) && shader.immediate_size > 0
             {
                 debug_assert_eq!(
                     shader.immediate_size as usize,
@@ -488,22 +560,23 @@ impl SkinnedMeshSceneProxy {
         let device = &renderer.state.device;
         let model_bgl = renderer.cache.bgl_model();
         let skinning_model_bgl = renderer.cache.bgl_model_skinning();
-        let mesh_data = ModelUniform::from_affine(render_affine);
+        let mut mesh_data = ModelUniform::from_affine(render_affine);
+        mesh_data.decal_layer = self.decal_layer;
 
         let mesh_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: Some("Skinned Mesh Buffer"),
             contents: mesh_data.as_bytes(),
-            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            usage: BufferUsages::UNIFORM | BufferUsages::STORAGE | BufferUsages::COPY_DST,
         });
 
         let mesh_uniform = ShaderUniform::<MeshUniformIndex>::builder(model_bgl.clone())
-            .with_buffer(mesh_buffer.clone())
+            .with_storage_buffer(mesh_buffer.clone())
             .build(device);
 
         #[cfg(debug_assertions)]
         let bounds_uniform = self.bounds_model_uniform().map(|bounds_data| {
             ShaderUniform::<MeshUniformIndex>::builder(model_bgl.clone())
-                .with_buffer_data(&bounds_data)
+                .with_storage_buffer_data(slice::from_ref(&bounds_data))
                 .build(device)
         });
 
@@ -523,9 +596,12 @@ impl SkinnedMeshSceneProxy {
             skinning_uniforms: Vec::new(),
             skinning_vertex_counts: Vec::new(),
             skinning_mesh: None,
+            morph_weights: None,
+            morph_target_count: 0,
         };
 
         self.bones_dirty = data.ensure_skinning_runtime(renderer, self.mesh);
+        self.morph_weights_dirty |= self.bones_dirty;
 
         data
     }
@@ -566,7 +642,7 @@ fn draw_edges(
     const COLOR: Vec4 = Vec4::new(1.0, 0.0, 1.0, 1.0);
 
     let shader = cache.shader(HShader::DEBUG_EDGES);
-    if !runtime.activate_shader(&shader, ctx, pass) {
+    if !runtime.activate_shader(&shader, ctx, pass, false) {
         return;
     }
 
@@ -586,7 +662,7 @@ fn draw_vertex_normals(
     use syrillian_asset::HShader;
 
     let shader = cache.shader(HShader::DEBUG_VERTEX_NORMALS);
-    if !runtime.activate_shader(&shader, ctx, pass) {
+    if !runtime.activate_shader(&shader, ctx, pass, false) {
         return;
     }
 
@@ -613,7 +689,7 @@ fn draw_bounds(
     };
 
     let shader = cache.shader(HShader::DEBUG_MESH_BOUNDS);
-    if !runtime.activate_shader(&shader, ctx, pass) {
+    if !runtime.activate_shader(&shader, ctx, pass, false) {
         return;
     }
 
@@ -2,9 +2,9 @@
 use crate::rendering::debug_renderer::DebugRenderer;
 
 use crate::cache::AssetCache;
+use crate::cache::glyph::{GlyphRenderData, generate_glyph_geometry_stream};
 #[cfg(debug_assertions)]
 use crate::cache::mesh::BindMeshBuffers;
-use crate::cache::glyph::{GlyphRenderData, generate_glyph_geometry_stream};
 use crate::model_uniform::ModelUniform;
 use crate::proxies::mesh_proxy::MeshUniformIndex;
 use crate::proxies::{PROXY_PRIORITY_TRANSPARENT, SceneProxy, SceneProxyBinding};
@@ -23,11 +23,12 @@ use parking_lot::RwLock;
 use std::any::Any;
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::slice;
 use syrillian_asset::shader::immediates::TextImmediate;
 use syrillian_asset::{HFont, HMesh, HShader, ensure_aligned};
+use syrillian_utils::BoundingSphere;
 use syrillian_utils::color::hsv_to_rgb;
 use syrillian_utils::debug_panic;
-use syrillian_utils::BoundingSphere;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu::{Buffer, BufferUsages, RenderPass};
 use zerocopy::IntoBytes;
@@ -452,7 +453,7 @@ impl<const D: u8, DIM: TextDim<D>> TextProxy<D, DIM> {
             } else {
                 data.bounds_uniform = Some(
                     ShaderUniform::<MeshUniformIndex>::builder(renderer.cache.bgl_model().clone())
-                        .with_buffer_data(&bounds_data)
+                        .with_storage_buffer_data(slice::from_ref(&bounds_data))
                         .build(&renderer.state.device),
                 );
             }
@@ -558,16 +559,14 @@ impl<const D: u8, DIM: TextDim<D>> SceneProxy for TextProxy<D, DIM> {
 
         let model_bgl = renderer.cache.bgl_model();
         let uniform = ShaderUniform::<MeshUniformIndex>::builder(model_bgl)
-            .with_buffer_data(&self.translation)
+            .with_storage_buffer_data(slice::from_ref(&self.translation))
             .build(device);
         #[cfg(debug_assertions)]
-        let bounds_uniform = self
-            .bounds_model_uniform()
-            .map(|bounds_data| {
-                ShaderUniform::<MeshUniformIndex>::builder(renderer.cache.bgl_model().clone())
-                    .with_buffer_data(&bounds_data)
-                    .build(device)
-            });
+        let bounds_uniform = self.bounds_model_uniform().map(|bounds_data| {
+            ShaderUniform::<MeshUniformIndex>::builder(renderer.cache.bgl_model().clone())
+                .with_storage_buffer_data(slice::from_ref(&bounds_data))
+                .build(device)
+        });
 
         Box::new(TextRenderData {
             uniform,
@@ -698,11 +697,7 @@ impl<const D: u8, DIM: TextDim<D>> SceneProxy for TextProxy<D, DIM> {
     }
 
     fn bounds(&self) -> Option<BoundingSphere> {
-        if D == 3 {
-            self.model_bounding
-        } else {
-            None
-        }
+        if D == 3 { self.model_bounding } else { None }
     }
 }
 
@@ -1,6 +1,9 @@
 use crate::lighting::proxy::LightProxy;
+use crate::lighting::reflection_probe::ReflectionProbeProxy;
 use crate::proxies::SceneProxy;
+use crate::rendering::decals::DecalProxy;
 use crate::rendering::message::RenderMsg;
+use crate::rendering::render_data::CameraUniform;
 use parking_lot::RwLock;
 use syrillian_utils::TypedComponentId;
 use wgpu::{BindGroup, RenderPass, TextureView};
@@ -25,6 +28,8 @@ pub struct GPUDrawCtx<'a> {
     pub render_bind_group: &'a BindGroup,
     pub light_bind_group: &'a BindGroup,
     pub shadow_bind_group: &'a BindGroup,
+    /// Camera meshes pick their level of detail for, the viewport camera even in shadow passes
+    pub lod_camera: &'a CameraUniform,
     pub transparency_pass: bool,
 }
 
@@ -51,6 +56,19 @@ impl<'a> CPUDrawCtx<'a> {
         self.batch.push(msg);
     }
 
+    pub fn send_reflection_probe_update(
+        &mut self,
+        cmd: impl FnOnce(&mut ReflectionProbeProxy) + Send + 'static,
+    ) {
+        let msg = RenderMsg::ReflectionProbeUpdate(self.current_cid, Box::new(cmd));
+        self.batch.push(msg);
+    }
+
+    pub fn send_decal_update(&mut self, cmd: impl FnOnce(&mut DecalProxy) + Send + 'static) {
+        let msg = RenderMsg::DecalUpdate(self.current_cid, Box::new(cmd));
+        self.batch.push(msg);
+    }
+
     pub fn disable_proxy(&mut self) {
         let msg = RenderMsg::ProxyState(self.current_cid, false);
         self.batch.push(msg);
@@ -11,6 +11,8 @@ pub struct DebugRenderer {
     pub colliders_edges: bool,
     pub text_geometry: bool,
     pub light: bool,
+    pub light_clusters: bool,
+    pub occlusion_culling: bool,
 }
 
 impl DebugRenderer {
@@ -23,6 +25,8 @@ impl DebugRenderer {
             rays: false,
             text_geometry: false,
             light: false,
+            light_clusters: false,
+            occlusion_culling: false,
         }
     }
 
@@ -67,6 +71,18 @@ impl DebugRenderer {
         inner.light
     }
 
+    /// Shades lit surfaces by how many lights their cluster has to evaluate
+    pub fn light_clusters() -> bool {
+        let inner = DEBUG_RENDERER.read();
+        inner.light_clusters
+    }
+
+    /// Outlines the bounds of everything hidden by occlusion culling
+    pub fn occlusion_culling() -> bool {
+        let inner = DEBUG_RENDERER.read();
+        inner.occlusion_culling
+    }
+
     pub fn off() {
         let mut inner = DEBUG_RENDERER.write();
         inner._off();
@@ -92,6 +108,10 @@ impl DebugRenderer {
             6
         } else if self.light {
             7
+        } else if self.light_clusters {
+            8
+        } else if self.occlusion_culling {
+            9
         } else {
             0
         }
@@ -112,6 +132,8 @@ impl DebugRenderer {
             5 => self.colliders_edges = true,
             6 => self.text_geometry = true,
             7 => self.light = true,
+            8 => self.light_clusters = true,
+            9 => self.occlusion_culling = true,
             _ => return 0,
         }
         mode
@@ -125,5 +147,7 @@ impl DebugRenderer {
         self.rays = false;
         self.text_geometry = false;
         self.light = false;
+        self.light_clusters = false;
+        self.occlusion_culling = false;
     }
 }
@@ -0,0 +1,411 @@
+//! Deferred decals.
+//!
+//! A decal is an oriented unit box projecting the default material onto the opaque surfaces
+//! inside of it. After the opaque geometry is drawn, the box is rasterized and every covered
+//! pixel rebuilds its position from the depth buffer. Pixels whose surface lies inside the box
+//! are shaded with the decal material and blended into the color target and the G-buffer, so
+//! post processing picks up the decal normals and roughness as well.
+//!
+//! Meshes are put on one of [`MAX_DECAL_LAYERS`] layers, and decals only project onto the
+//! layers in their mask.
+
+use crate::cache::AssetCache;
+use crate::rendering::message::DecalCommand;
+use crate::rendering::uniform::ShaderUniform;
+use glamx::{Affine3A, Mat4, Vec2};
+use itertools::Itertools;
+use std::borrow::Cow;
+use syrillian_asset::{HMaterialInstance, Material, ensure_aligned};
+use syrillian_macros::UniformIndex;
+use syrillian_shadergen::generator::ShaderGenerator;
+use syrillian_utils::TypedComponentId;
+use tracing::{trace, warn};
+use wgpu::{
+    BindGroup, BindGroupLayout, BlendState, ColorTargetState, ColorWrites, CommandEncoder, Device,
+    Extent3d, Face, FragmentState, MultisampleState, PipelineCompilationOptions,
+    PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPass, RenderPipeline,
+    RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, SurfaceConfiguration, Texture,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor,
+    VertexState,
+};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
+
+/// Amount of layers meshes can be put on. The layer is stored in 8 bits of the G-buffer,
+/// but masks are 32 bits wide.
+pub const MAX_DECAL_LAYERS: u32 = 32;
+
+/// The decal box, a unit cube centered on the origin, as a triangle list
+const CUBE_VERTICES: u32 = 36;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DecalProxy {
+    /// Maps the unit box centered on the origin onto the decal volume in world space.
+    /// The material is projected along the local -Z axis.
+    pub transform: Affine3A,
+    pub material: HMaterialInstance,
+    /// Angle between surface and projection in radians, from where the decal fades out
+    pub angle_fade_start: f32,
+    /// Angle between surface and projection in radians, from where the decal is invisible
+    pub angle_fade_end: f32,
+    /// Decals with a higher order are drawn over ones with a lower order
+    pub sort_order: i32,
+    /// Bit mask of the decal layers the decal projects onto
+    pub layer_mask: u32,
+}
+
+impl Default for DecalProxy {
+    fn default() -> Self {
+        Self {
+            transform: Affine3A::IDENTITY,
+            material: HMaterialInstance::DEFAULT,
+            angle_fade_start: 60f32.to_radians(),
+            angle_fade_end: 80f32.to_radians(),
+            sort_order: 0,
+            layer_mask: u32::MAX,
+        }
+    }
+}
+
+impl DecalProxy {
+    fn data(&self) -> DecalData {
+        let transform = Mat4::from(self.transform);
+        let end = self.angle_fade_end.clamp(0.0, 90f32.to_radians());
+        let start = self.angle_fade_start.clamp(0.0, end);
+
+        DecalData {
+            transform,
+            inv_transform: transform.inverse(),
+            // Keep the fade range from collapsing, smoothstep is undefined for empty ranges
+            angle_fade: Vec2::new(start.cos(), end.cos().min(start.cos() - 1e-4)),
+            layer_mask: self.layer_mask,
+            _pad0: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Immutable, FromBytes, IntoBytes, KnownLayout)]
+pub struct DecalData {
+    pub transform: Mat4,
+    pub inv_transform: Mat4,
+    pub angle_fade: Vec2,
+    pub layer_mask: u32,
+    pub _pad0: u32,
+}
+
+ensure_aligned!(DecalData { transform, inv_transform, angle_fade }, align <= 16 * 9 => size);
+
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, UniformIndex)]
+enum DecalUniformIndex {
+    Data = 0,
+}
+
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, UniformIndex)]
+enum DecalSurfaceIndex {
+    Depth = 0,
+    Normal = 1,
+    Material = 2,
+}
+
+/// Copies of the G-buffer of a viewport, which decals read while they draw into it
+pub struct DecalSurface {
+    g_normal: Texture,
+    g_material: Texture,
+    uniform: ShaderUniform<DecalSurfaceIndex>,
+}
+
+impl DecalSurface {
+    pub fn new(
+        device: &Device,
+        cache: &AssetCache,
+        config: &SurfaceConfiguration,
+        depth: &Texture,
+    ) -> Self {
+        let g_normal = create_copy_texture(
+            device,
+            config,
+            "Decal GBuffer Copy (Normals)",
+            TextureFormat::Rg16Float,
+        );
+        let g_material = create_copy_texture(
+            device,
+            config,
+            "Decal GBuffer Copy (Material)",
+            TextureFormat::Bgra8Unorm,
+        );
+
+        let uniform = ShaderUniform::builder(cache.bgl_decal_surface())
+            .with_texture(depth.create_view(&TextureViewDescriptor::default()))
+            .with_texture(g_normal.create_view(&TextureViewDescriptor::default()))
+            .with_texture(g_material.create_view(&TextureViewDescriptor::default()))
+            .build(device);
+
+        Self {
+            g_normal,
+            g_material,
+            uniform,
+        }
+    }
+
+    /// Snapshots the G-buffer after the opaque geometry was drawn
+    pub fn copy_from(
+        &self,
+        encoder: &mut CommandEncoder,
+        g_normal: &Texture,
+        g_material: &Texture,
+    ) {
+        encoder.copy_texture_to_texture(
+            g_normal.as_image_copy(),
+            self.g_normal.as_image_copy(),
+            self.g_normal.size(),
+        );
+        encoder.copy_texture_to_texture(
+            g_material.as_image_copy(),
+            self.g_material.as_image_copy(),
+            self.g_material.size(),
+        );
+    }
+
+    pub fn bind_group(&self) -> &BindGroup {
+        self.uniform.bind_group()
+    }
+}
+
+struct DecalEntry {
+    owner: TypedComponentId,
+    proxy: DecalProxy,
+    uniform: ShaderUniform<DecalUniformIndex>,
+    /// Registration order, keeps decals of the same sort order stable
+    sequence: u64,
+}
+
+/// Bind groups of the frame the decals are drawn in
+pub struct DecalFrameBindings<'a> {
+    pub render: &'a BindGroup,
+    pub light: &'a BindGroup,
+    pub shadow: &'a BindGroup,
+    pub surface: &'a DecalSurface,
+}
+
+pub struct DecalRenderer {
+    entries: Vec<DecalEntry>,
+    bgl: BindGroupLayout,
+    next_sequence: u64,
+    pipeline: RenderPipeline,
+    material_layout_key: u64,
+}
+
+impl DecalRenderer {
+    pub fn new(device: &Device, cache: &AssetCache) -> Self {
+        let code = ShaderGenerator::build_decal_shader();
+        let module = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Decal Shader"),
+            source: ShaderSource::Wgsl(Cow::Owned(code)),
+        });
+
+        let material_layout = Material::default_layout();
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Decal Pipeline Layout"),
+            bind_group_layouts: &[
+                &cache.bgl_render(),
+                &cache.bgl_decal(),
+                &cache.material_layout(&material_layout),
+                &cache.bgl_light(),
+                &cache.bgl_shadow(),
+                &cache.bgl_decal_surface(),
+            ],
+            immediate_size: material_layout.immediate_size(),
+        });
+
+        let target = |format, write_mask| {
+            Some(ColorTargetState {
+                format,
+                blend: Some(BlendState::ALPHA_BLENDING),
+                write_mask,
+            })
+        };
+        // Material alpha and the layer in blue have to survive the decal
+        let g_writes = ColorWrites::RED | ColorWrites::GREEN;
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Decal Pipeline"),
+            layout: Some(&layout),
+            vertex: VertexState {
+                module: &module,
+                entry_point: Some("vs_main"),
+                compilation_options: PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            // Back faces still cover the screen when the camera is inside of the box
+            primitive: PrimitiveState {
+                cull_mode: Some(Face::Front),
+                ..PrimitiveState::default()
+            },
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            fragment: Some(FragmentState {
+                module: &module,
+                entry_point: Some("fs_main"),
+                compilation_options: PipelineCompilationOptions::default(),
+                targets: &[
+                    target(TextureFormat::Rgba8Unorm, ColorWrites::COLOR),
+                    target(TextureFormat::Rg16Float, g_writes),
+                    target(TextureFormat::Bgra8Unorm, g_writes),
+                ],
+            }),
+            multiview_mask: None,
+            cache: cache.pipeline_cache(),
+        });
+
+        Self {
+            entries: Vec::new(),
+            bgl: cache.bgl_decal(),
+            next_sequence: 0,
+            pipeline,
+            material_layout_key: material_layout.layout_key(),
+        }
+    }
+
+    pub fn has_decals(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    #[profiling::function]
+    pub fn add_proxy(&mut self, device: &Device, owner: TypedComponentId, proxy: DecalProxy) {
+        trace!("Registered Decal for #{:?}", owner.type_id());
+        self.remove_proxy(owner);
+
+        let uniform = ShaderUniform::builder(self.bgl.clone())
+            .with_buffer_data(&proxy.data())
+            .build(device);
+
+        self.entries.push(DecalEntry {
+            owner,
+            proxy,
+            uniform,
+            sequence: self.next_sequence,
+        });
+        self.next_sequence += 1;
+        self.sort();
+    }
+
+    #[profiling::function]
+    pub fn remove_proxy(&mut self, owner: TypedComponentId) {
+        if let Some((pos, _)) = self.entries.iter().find_position(|e| e.owner == owner) {
+            self.entries.remove(pos);
+        }
+    }
+
+    #[profiling::function]
+    pub fn execute_command(&mut self, queue: &Queue, owner: TypedComponentId, cmd: DecalCommand) {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.owner == owner) else {
+            warn!("Requested Decal not found");
+            return;
+        };
+
+        let old_order = entry.proxy.sort_order;
+        cmd(&mut entry.proxy);
+        entry
+            .uniform
+            .write_buffer(DecalUniformIndex::Data, &entry.proxy.data(), queue);
+
+        if old_order != entry.proxy.sort_order {
+            self.sort();
+        }
+    }
+
+    /// Draws all decals with a material of the default layout into the G-buffer of the pass.
+    /// The pass must not have a depth attachment, decals test against the surface copies.
+    pub fn render(&self, cache: &AssetCache, pass: &mut RenderPass, bindings: DecalFrameBindings) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bindings.render, &[]);
+        pass.set_bind_group(3, bindings.light, &[]);
+        pass.set_bind_group(4, bindings.shadow, &[]);
+        pass.set_bind_group(5, bindings.surface.bind_group(), &[]);
+
+        for entry in &self.entries {
+            let material = cache.material_instance(entry.proxy.material);
+            let layout_key = cache.materials.get(material.material).layout().layout_key();
+            if layout_key != self.material_layout_key {
+                trace!("Skipped Decal with a material of a custom layout");
+                continue;
+            }
+
+            pass.set_bind_group(1, entry.uniform.bind_group(), &[]);
+            pass.set_bind_group(2, &material.bind_group, &[]);
+            pass.set_immediates(0, &material.immediates);
+            pass.draw(0..CUBE_VERTICES, 0..1);
+        }
+    }
+
+    fn sort(&mut self) {
+        self.entries
+            .sort_by_key(|e| (e.proxy.sort_order, e.sequence));
+    }
+}
+
+fn create_copy_texture(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    label: &str,
+    format: TextureFormat,
+) -> Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some(label),
+        size: Extent3d {
+            width: config.width.max(1),
+            height: config.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glamx::{Quat, Vec3};
+    use syrillian_asset::shader::checks::validate_wgsl_source;
+
+    #[test]
+    fn decal_shader_is_valid() {
+        validate_wgsl_source(&ShaderGenerator::build_decal_shader()).unwrap();
+    }
+
+    #[test]
+    fn data_maps_world_into_the_unit_box() {
+        let proxy = DecalProxy {
+            transform: Affine3A::from_scale_rotation_translation(
+                Vec3::new(2.0, 1.0, 0.5),
+                Quat::from_rotation_y(0.3),
+                Vec3::new(4.0, 0.0, -1.0),
+            ),
+            ..DecalProxy::default()
+        };
+        let data = proxy.data();
+
+        let corner = data.transform.transform_point3(Vec3::splat(0.5));
+        let back = data.inv_transform.transform_point3(corner);
+        assert!(back.abs_diff_eq(Vec3::splat(0.5), 1e-5));
+    }
+
+    #[test]
+    fn angle_fade_never_collapses() {
+        let proxy = DecalProxy {
+            angle_fade_start: 1.0,
+            angle_fade_end: 0.5,
+            ..DecalProxy::default()
+        };
+        let [start, end] = proxy.data().angle_fade.to_array();
+
+        assert!(start > end);
+        assert!((start - 0.5f32.cos()).abs() < 1e-6);
+    }
+}
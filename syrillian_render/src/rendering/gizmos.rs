@@ -0,0 +1,113 @@
+//! Render side of the immediate mode debug drawing (gizmos).
+//!
+//! The world collects all gizmos drawn during a frame into a [`GizmoFrame`], which replaces the
+//! lines of the previous frame. Lines are drawn in every viewport after the scene, either depth
+//! tested against it, or as an overlay on top of it.
+
+use crate::cache::AssetCache;
+use crate::proxies::{DebugLine, DebugLineVertex};
+use syrillian_asset::HShader;
+use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use wgpu::{BindGroup, Buffer, BufferUsages, Device, Queue, RenderPass};
+use zerocopy::IntoBytes;
+
+#[derive(Debug, Default, Clone)]
+pub struct GizmoFrame {
+    /// Lines hidden by scene geometry in front of them
+    pub lines: Vec<DebugLine>,
+    /// Lines drawn on top of the scene
+    pub overlay_lines: Vec<DebugLine>,
+}
+
+/// Vertex buffer of debug lines that is reused as long as the lines fit
+#[derive(Default)]
+pub(crate) struct GizmoLineBuffer {
+    buffer: Option<Buffer>,
+    vertex_count: u32,
+}
+
+impl GizmoLineBuffer {
+    pub(crate) fn upload(&mut self, device: &Device, queue: &Queue, lines: &[DebugLine]) {
+        let vertices = DebugLineVertex::from_lines(lines);
+        self.vertex_count = vertices.len() as u32;
+        if vertices.is_empty() {
+            return;
+        }
+
+        let bytes = vertices.as_bytes();
+        match &self.buffer {
+            Some(buffer) if buffer.size() >= bytes.len() as u64 => {
+                queue.write_buffer(buffer, 0, bytes);
+            }
+            _ => {
+                self.buffer = Some(device.create_buffer_init(&BufferInitDescriptor {
+                    label: Some("Gizmo Line Buffer"),
+                    contents: bytes,
+                    usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                }));
+            }
+        }
+    }
+
+    pub(crate) fn draw(
+        &self,
+        cache: &AssetCache,
+        shader: HShader,
+        pass: &mut RenderPass,
+        bind: &BindGroup,
+    ) {
+        let Some(buffer) = &self.buffer else {
+            return;
+        };
+        if self.vertex_count == 0 {
+            return;
+        }
+
+        let shader = cache.shader(shader);
+        pass.set_pipeline(shader.solid_pipeline());
+        pass.set_bind_group(shader.bind_groups().render, bind, &[]);
+        pass.set_vertex_buffer(0, buffer.slice(..));
+        pass.draw(0..self.vertex_count, 0..1);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.vertex_count = 0;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.vertex_count == 0
+    }
+}
+
+#[derive(Default)]
+pub struct GizmoRenderer {
+    lines: GizmoLineBuffer,
+    overlay_lines: GizmoLineBuffer,
+}
+
+impl GizmoRenderer {
+    pub fn update_frame(&mut self, device: &Device, queue: &Queue, frame: GizmoFrame) {
+        self.lines.upload(device, queue, &frame.lines);
+        self.overlay_lines
+            .upload(device, queue, &frame.overlay_lines);
+    }
+
+    pub fn has_lines(&self) -> bool {
+        !self.lines.is_empty()
+    }
+
+    pub fn has_overlay_lines(&self) -> bool {
+        !self.overlay_lines.is_empty()
+    }
+
+    /// Draws the depth tested lines. The pass needs the scene depth attached.
+    pub fn render_lines(&self, cache: &AssetCache, pass: &mut RenderPass, render: &BindGroup) {
+        self.lines.draw(cache, HShader::GIZMO_LINES, pass, render);
+    }
+
+    /// Draws the overlay lines. The pass must not have a depth attachment.
+    pub fn render_overlay(&self, cache: &AssetCache, pass: &mut RenderPass, render: &BindGroup) {
+        self.overlay_lines
+            .draw(cache, HShader::GIZMO_LINES_OVERLAY, pass, render);
+    }
+}
@@ -0,0 +1,175 @@
+//! Per-frame model data of instanced batches.
+//!
+//! Batches append their instances to one storage buffer each frame. The draw then
+//! selects its slice through the instance range, because `instance_index` in the
+//! shader already includes the first instance of the draw.
+
+use crate::model_uniform::ModelUniform;
+use crate::proxies::MeshUniformIndex;
+use crate::rendering::uniform::ShaderUniform;
+use std::ops::Range;
+use wgpu::{BindGroup, BindGroupLayout, BufferDescriptor, BufferUsages, Device, Queue};
+use zerocopy::IntoBytes;
+
+const INITIAL_CAPACITY: u64 = 256;
+const INSTANCE_SIZE: u64 = size_of::<ModelUniform>() as u64;
+
+pub struct InstanceBuffer {
+    uniform: ShaderUniform<MeshUniformIndex>,
+    capacity: u64,
+    len: u64,
+}
+
+impl InstanceBuffer {
+    pub fn new(device: &Device, model_bgl: BindGroupLayout) -> Self {
+        Self::with_capacity(device, model_bgl, INITIAL_CAPACITY)
+    }
+
+    fn with_capacity(device: &Device, model_bgl: BindGroupLayout, capacity: u64) -> Self {
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Model Instance Buffer"),
+            size: capacity * INSTANCE_SIZE,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform = ShaderUniform::<MeshUniformIndex>::builder(model_bgl)
+            .with_storage_buffer(buffer)
+            .build(device);
+
+        Self {
+            uniform,
+            capacity,
+            len: 0,
+        }
+    }
+
+    /// Starts a new frame. Instances written before this may still be in flight, but
+    /// queue writes only land after all previously submitted work.
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Appends `instances` and returns the bind group and instance range to draw them with.
+    ///
+    /// If the buffer is full, it is replaced by a larger one. Draws that were already
+    /// recorded keep the previous buffer alive until they're done.
+    pub fn push(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        model_bgl: BindGroupLayout,
+        instances: &[ModelUniform],
+    ) -> (BindGroup, Range<u32>) {
+        let count = instances.len() as u64;
+        if self.len + count > self.capacity {
+            let capacity = (self.capacity * 2).max(count.next_power_of_two());
+            *self = Self::with_capacity(device, model_bgl, capacity);
+        }
+
+        let start = self.len;
+        queue.write_buffer(
+            self.uniform.buffer(MeshUniformIndex::MeshData),
+            start * INSTANCE_SIZE,
+            instances.as_bytes(),
+        );
+        self.len += count;
+
+        (
+            self.uniform.bind_group().clone(),
+            start as u32..self.len as u32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::{
+        BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BufferBindingType,
+        DeviceDescriptor, ShaderStages,
+    };
+
+    fn device() -> (Device, Queue, BindGroupLayout) {
+        let (device, queue) = Device::noop(&DeviceDescriptor::default());
+        let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Model Bind Group Layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::all(),
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        (device, queue, bgl)
+    }
+
+    fn models(count: usize) -> Vec<ModelUniform> {
+        (0..count)
+            .map(|i| ModelUniform::new_at(i as f32, 0.0, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn batches_get_consecutive_ranges() {
+        let (device, queue, bgl) = device();
+        let mut buffer = InstanceBuffer::new(&device, bgl.clone());
+        assert!(buffer.is_empty());
+
+        let (first_group, first) = buffer.push(&device, &queue, bgl.clone(), &models(3));
+        let (second_group, second) = buffer.push(&device, &queue, bgl, &models(2));
+
+        assert_eq!(first, 0..3);
+        assert_eq!(second, 3..5);
+        assert_eq!(buffer.len(), 5);
+        assert_eq!(first_group, second_group);
+    }
+
+    #[test]
+    fn reset_starts_over() {
+        let (device, queue, bgl) = device();
+        let mut buffer = InstanceBuffer::new(&device, bgl.clone());
+
+        buffer.push(&device, &queue, bgl.clone(), &models(4));
+        buffer.reset();
+        assert!(buffer.is_empty());
+
+        let (_, range) = buffer.push(&device, &queue, bgl, &models(1));
+        assert_eq!(range, 0..1);
+        assert_eq!(buffer.capacity(), INITIAL_CAPACITY);
+    }
+
+    #[test]
+    fn grows_when_full() {
+        let (device, queue, bgl) = device();
+        let mut buffer = InstanceBuffer::with_capacity(&device, bgl.clone(), 4);
+
+        let (small_group, _) = buffer.push(&device, &queue, bgl.clone(), &models(3));
+        let (grown_group, range) = buffer.push(&device, &queue, bgl.clone(), &models(3));
+
+        // The grown buffer starts empty, instances already drawn keep the old one
+        assert_eq!(range, 0..3);
+        assert_eq!(buffer.capacity(), 8);
+        assert_ne!(small_group, grown_group);
+
+        let (_, range) = buffer.push(&device, &queue, bgl, &models(20));
+        assert_eq!(range, 0..20);
+        assert_eq!(buffer.capacity(), 32);
+    }
+}
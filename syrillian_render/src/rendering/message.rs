@@ -1,6 +1,9 @@
 use crate::ObjectHash;
 use crate::lighting::proxy::LightProxy;
+use crate::lighting::reflection_probe::ReflectionProbeProxy;
 use crate::proxies::SceneProxy;
+use crate::rendering::decals::DecalProxy;
+use crate::rendering::gizmos::GizmoFrame;
 use crate::rendering::picking::PickRequest;
 use crate::rendering::render_data::CameraUniform;
 use crate::rendering::render_data::{SkyAtmosphereSettings, SkyboxMode};
@@ -10,7 +13,7 @@ use crossbeam_channel::Sender;
 use glamx::Affine3A;
 use std::fmt::{Debug, Formatter};
 use std::path::PathBuf;
-use syrillian_asset::{HCubemap, HTexture2D};
+use syrillian_asset::{HCubemap, HRenderTexture2D, HTexture2D};
 use syrillian_utils::TypedComponentId;
 
 #[derive(Debug, Clone, Copy)]
@@ -21,6 +24,8 @@ pub struct GBufferDebugTargets {
 
 pub type ProxyUpdateCommand = Box<dyn FnOnce(&mut dyn SceneProxy) + Send>;
 pub type LightProxyCommand = Box<dyn FnOnce(&mut LightProxy) + Send>;
+pub type ReflectionProbeCommand = Box<dyn FnOnce(&mut ReflectionProbeProxy) + Send>;
+pub type DecalCommand = Box<dyn FnOnce(&mut DecalProxy) + Send>;
 pub type CameraUpdateCommand = Box<dyn FnOnce(&mut CameraUniform) + Send>;
 
 pub enum RenderMsg {
@@ -32,10 +37,14 @@ pub enum RenderMsg {
         Option<Affine3A>,
     ),
     RegisterLightProxy(TypedComponentId, Box<LightProxy>),
+    RegisterReflectionProbe(TypedComponentId, Box<ReflectionProbeProxy>),
+    RegisterDecal(TypedComponentId, Box<DecalProxy>),
     RemoveProxy(TypedComponentId),
     UpdateTransform(TypedComponentId, Affine3A, Option<Affine3A>),
     ProxyUpdate(TypedComponentId, ProxyUpdateCommand),
     LightProxyUpdate(TypedComponentId, LightProxyCommand),
+    ReflectionProbeUpdate(TypedComponentId, ReflectionProbeCommand),
+    DecalUpdate(TypedComponentId, DecalCommand),
     UpdateActiveCamera(ViewportId, CameraUpdateCommand),
     ProxyState(TypedComponentId, bool), // enabled
     PickRequest(PickRequest),
@@ -43,6 +52,7 @@ pub enum RenderMsg {
     CaptureOffscreenTextures(ViewportId, PathBuf),
     CapturePickingTexture(ViewportId, PathBuf),
     CaptureTexture(HTexture2D, PathBuf),
+    GenerateRenderTextureMips(HRenderTexture2D),
     SetGBufferDebug(ViewportId, Option<GBufferDebugTargets>),
     SetSkybox(ViewportId, Option<HCubemap>),
     SetSkyboxMode(ViewportId, SkyboxMode),
@@ -50,6 +60,7 @@ pub enum RenderMsg {
     SetViewportRect(ViewportId, Option<[f32; 4]>),
     SetDisablePostProcessing(ViewportId, bool),
     UpdateStrobe(StrobeFrame),
+    UpdateGizmos(GizmoFrame),
     FrameEnd(ViewportId, Sender<()>),
 }
 
@@ -58,10 +69,14 @@ impl Debug for RenderMsg {
         let name = match self {
             RenderMsg::RegisterProxy(..) => "Register Proxy",
             RenderMsg::RegisterLightProxy(..) => "Register Light Proxy",
+            RenderMsg::RegisterReflectionProbe(..) => "Register Reflection Probe",
+            RenderMsg::RegisterDecal(..) => "Register Decal",
             RenderMsg::RemoveProxy(_) => "Remove Proxy",
             RenderMsg::UpdateTransform(..) => "Update Transform",
             RenderMsg::ProxyUpdate(..) => "Proxy Update",
             RenderMsg::LightProxyUpdate(..) => "Light Proxy Update",
+            RenderMsg::ReflectionProbeUpdate(..) => "Reflection Probe Update",
+            RenderMsg::DecalUpdate(..) => "Decal Update",
             RenderMsg::UpdateActiveCamera(..) => "Update Active Camera",
             RenderMsg::ProxyState(_, enable) => &format!("Proxy Enabled: {enable}"),
             RenderMsg::PickRequest(..) => "Pick Request",
@@ -69,6 +84,7 @@ impl Debug for RenderMsg {
             RenderMsg::CaptureOffscreenTextures(_, _) => "Capture Offscreen Texture",
             RenderMsg::CapturePickingTexture(_, _) => "Capture Picking Texture",
             RenderMsg::CaptureTexture(_, _) => "Capture Texture",
+            RenderMsg::GenerateRenderTextureMips(_) => "Generate Render Texture Mips",
             RenderMsg::SetGBufferDebug(_, _) => "Set GBuffer Debug",
             RenderMsg::SetSkybox(_, _) => "Set Skybox",
             RenderMsg::SetSkyboxMode(_, _) => "Set Skybox Mode",
@@ -76,6 +92,7 @@ impl Debug for RenderMsg {
             RenderMsg::SetViewportRect(_, _) => "Set Viewport Rect",
             RenderMsg::SetDisablePostProcessing(_, _) => "Set Disable Post Processing",
             RenderMsg::UpdateStrobe(_) => "Update Strobe Draw List",
+            RenderMsg::UpdateGizmos(_) => "Update Gizmos",
             RenderMsg::FrameEnd(_, _) => "Frame End",
         };
 
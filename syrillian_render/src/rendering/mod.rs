@@ -28,7 +28,11 @@
 //! You can create scene proxies in [`Components`](syrillian::engine::components)
 
 pub mod context;
+pub mod decals;
+pub mod gizmos;
+pub mod instance_buffer;
 pub mod message;
+pub mod occlusion;
 pub mod offscreen_surface;
 pub mod picking;
 pub mod render_data;
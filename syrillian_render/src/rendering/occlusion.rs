@@ -0,0 +1,420 @@
+//! Occlusion culling against a hierarchical depth buffer (Hi-Z).
+//!
+//! Culling runs in two phases. The opaque geometry that was visible in the last frame is drawn
+//! first, and its depth is reduced into a pyramid where every texel keeps the farthest depth of
+//! the area it covers. All proxies that survived frustum culling are then tested against the
+//! pyramid on the GPU, and the ones that were hidden before but pass now are drawn in a second
+//! phase. Because the test sees the depth of the current frame, an object that comes into view,
+//! e.g. from behind a corner while the camera moves, still shows up in the frame it does.
+//!
+//! The test results are waited on within the frame, so the culling is opt-in with
+//! `--occlusion-culling`.
+
+use crate::cache::AssetCache;
+use crate::proxies::DebugLine;
+use crate::rendering::gizmos::GizmoLineBuffer;
+use crate::rendering::state::State;
+use crate::rendering::uniform::ShaderUniform;
+use glamx::{Vec3, Vec4};
+use std::collections::HashSet;
+use syrillian_asset::{HComputeShader, HShader, ensure_aligned};
+use syrillian_macros::UniformIndex;
+use syrillian_utils::{BoundingSphere, TypedComponentId};
+use wgpu::{
+    BindGroup, Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor,
+    ComputePassDescriptor, Device, Extent3d, MapMode, PollType, Queue, RenderPass, Texture,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+    TextureViewDescriptor,
+};
+use zerocopy::IntoBytes;
+
+const HIDDEN_BOUNDS_COLOR: Vec4 = Vec4::new(1.0, 0.2, 0.2, 1.0);
+
+#[repr(C)]
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    zerocopy::Immutable,
+    zerocopy::IntoBytes,
+    zerocopy::FromBytes,
+    zerocopy::KnownLayout,
+)]
+struct OcclusionParams {
+    /// Viewport rect in pixels (x, y, width, height)
+    viewport: Vec4,
+    candidate_count: u32,
+    mip_count: u32,
+    _padding: [u32; 2],
+}
+
+ensure_aligned!(OcclusionParams { viewport }, align <= 16 * 2 => size);
+
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, UniformIndex)]
+enum HiZUniformIndex {
+    Source = 0,
+    Target = 1,
+}
+
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, UniformIndex)]
+enum OcclusionTestUniformIndex {
+    Params = 0,
+    Candidates = 1,
+    HiZ = 2,
+    Visibility = 3,
+}
+
+/// Buffers of the occlusion test, sized for a number of candidates
+struct OcclusionTestBuffers {
+    capacity: usize,
+    uniform: ShaderUniform<OcclusionTestUniformIndex>,
+    readback: Buffer,
+}
+
+impl OcclusionTestBuffers {
+    fn new(device: &Device, cache: &AssetCache, hi_z: &TextureView, capacity: usize) -> Self {
+        let candidates = device.create_buffer(&BufferDescriptor {
+            label: Some("Occlusion Candidates Buffer"),
+            size: (capacity * size_of::<Vec4>()) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let visibility = device.create_buffer(&BufferDescriptor {
+            label: Some("Occlusion Visibility Buffer"),
+            size: (capacity * size_of::<u32>()) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&BufferDescriptor {
+            label: Some("Occlusion Readback Buffer"),
+            size: (capacity * size_of::<u32>()) as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let params = OcclusionParams {
+            viewport: Vec4::ZERO,
+            candidate_count: 0,
+            mip_count: 0,
+            _padding: [0; 2],
+        };
+        let uniform =
+            ShaderUniform::<OcclusionTestUniformIndex>::builder(cache.bgl_occlusion_test())
+                .with_buffer_data(&params)
+                .with_storage_buffer(candidates)
+                .with_texture(hi_z.clone())
+                .with_storage_buffer(visibility)
+                .build(device);
+
+        Self {
+            capacity,
+            uniform,
+            readback,
+        }
+    }
+}
+
+pub struct OcclusionCuller {
+    _pyramid: Texture,
+    hi_z: TextureView,
+    mip_count: u32,
+    size: Extent3d,
+    copy_uniform: ShaderUniform<HiZUniformIndex>,
+    downsample_uniforms: Vec<ShaderUniform<HiZUniformIndex>>,
+    test: Option<OcclusionTestBuffers>,
+    hidden: HashSet<TypedComponentId>,
+    debug_lines: GizmoLineBuffer,
+}
+
+impl OcclusionCuller {
+    pub fn new(device: &Device, cache: &AssetCache, depth_texture: &Texture) -> Self {
+        let size = Extent3d {
+            depth_or_array_layers: 1,
+            ..depth_texture.size()
+        };
+        let mip_count = size.max_mips(TextureDimension::D2);
+
+        let pyramid = device.create_texture(&TextureDescriptor {
+            label: Some("Hi-Z Pyramid"),
+            size,
+            mip_level_count: mip_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::R32Float,
+            usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let level_view = |level: u32| {
+            pyramid.create_view(&TextureViewDescriptor {
+                label: Some("Hi-Z Level View"),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                ..TextureViewDescriptor::default()
+            })
+        };
+
+        let depth_view = depth_texture.create_view(&TextureViewDescriptor::default());
+        let copy_uniform = ShaderUniform::<HiZUniformIndex>::builder(cache.bgl_hi_z_copy())
+            .with_texture(depth_view)
+            .with_texture(level_view(0))
+            .build(device);
+
+        let downsample_bgl = cache.bgl_hi_z_downsample();
+        let downsample_uniforms = (1..mip_count)
+            .map(|level| {
+                ShaderUniform::<HiZUniformIndex>::builder(downsample_bgl.clone())
+                    .with_texture(level_view(level - 1))
+                    .with_texture(level_view(level))
+                    .build(device)
+            })
+            .collect();
+
+        let hi_z = pyramid.create_view(&TextureViewDescriptor::default());
+
+        Self {
+            _pyramid: pyramid,
+            hi_z,
+            mip_count,
+            size,
+            copy_uniform,
+            downsample_uniforms,
+            test: None,
+            hidden: HashSet::new(),
+            debug_lines: GizmoLineBuffer::default(),
+        }
+    }
+
+    /// Whether the last test found the proxy to be hidden
+    pub fn is_hidden(&self, proxy: &TypedComponentId) -> bool {
+        self.hidden.contains(proxy)
+    }
+
+    /// Removes all proxies that were hidden in the last test
+    pub fn visible_proxies(&self, proxies: &[TypedComponentId]) -> Vec<TypedComponentId> {
+        proxies
+            .iter()
+            .filter(|proxy| !self.is_hidden(proxy))
+            .copied()
+            .collect()
+    }
+
+    /// Builds the Hi-Z pyramid from the depth drawn so far, and tests `candidates` against it.
+    /// Waits for the results, and returns the candidates that were hidden before but are
+    /// visible now, in their original order. These still have to be drawn.
+    ///
+    /// `viewport` is the pixel rect (x, y, width, height) the camera renders into.
+    #[profiling::function]
+    pub fn cull(
+        &mut self,
+        state: &State,
+        cache: &AssetCache,
+        render_bind_group: &BindGroup,
+        viewport: Vec4,
+        candidates: &[(TypedComponentId, BoundingSphere)],
+    ) -> Vec<TypedComponentId> {
+        if candidates.is_empty() {
+            self.hidden.clear();
+            return Vec::new();
+        }
+
+        if self
+            .test
+            .as_ref()
+            .is_none_or(|test| test.capacity < candidates.len())
+        {
+            let capacity = candidates.len().next_power_of_two().max(64);
+            self.test = Some(OcclusionTestBuffers::new(
+                &state.device,
+                cache,
+                &self.hi_z,
+                capacity,
+            ));
+        }
+        let Some(test) = &self.test else {
+            return Vec::new();
+        };
+
+        let spheres: Vec<Vec4> = candidates
+            .iter()
+            .map(|(_, bounds)| bounds.center.extend(bounds.radius))
+            .collect();
+        state.queue.write_buffer(
+            test.uniform.buffer(OcclusionTestUniformIndex::Candidates),
+            0,
+            spheres.as_bytes(),
+        );
+
+        let params = OcclusionParams {
+            viewport,
+            candidate_count: candidates.len() as u32,
+            mip_count: self.mip_count,
+            _padding: [0; 2],
+        };
+        test.uniform
+            .write_buffer(OcclusionTestUniformIndex::Params, &params, &state.queue);
+
+        let mut encoder = state
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Occlusion Culling Encoder"),
+            });
+
+        {
+            let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("Occlusion Culling Compute Pass"),
+                ..ComputePassDescriptor::default()
+            });
+
+            let copy = cache.compute_shader(HComputeShader::HI_Z_COPY);
+            pass.set_pipeline(copy.pipeline());
+            pass.set_bind_group(0, self.copy_uniform.bind_group(), &[]);
+            pass.dispatch_workgroups(self.size.width.div_ceil(8), self.size.height.div_ceil(8), 1);
+
+            let downsample = cache.compute_shader(HComputeShader::HI_Z_DOWNSAMPLE);
+            pass.set_pipeline(downsample.pipeline());
+            for (level, uniform) in (1..).zip(&self.downsample_uniforms) {
+                let width = (self.size.width >> level).max(1);
+                let height = (self.size.height >> level).max(1);
+                pass.set_bind_group(0, uniform.bind_group(), &[]);
+                pass.dispatch_workgroups(width.div_ceil(8), height.div_ceil(8), 1);
+            }
+
+            let test_shader = cache.compute_shader(HComputeShader::OCCLUSION_TEST);
+            pass.set_pipeline(test_shader.pipeline());
+            pass.set_bind_group(0, render_bind_group, &[]);
+            pass.set_bind_group(1, test.uniform.bind_group(), &[]);
+            pass.dispatch_workgroups((candidates.len() as u32).div_ceil(64), 1, 1);
+        }
+
+        let byte_len = (candidates.len() * size_of::<u32>()) as u64;
+        encoder.copy_buffer_to_buffer(
+            test.uniform.buffer(OcclusionTestUniformIndex::Visibility),
+            0,
+            &test.readback,
+            0,
+            byte_len,
+        );
+        state.queue.submit(Some(encoder.finish()));
+
+        let slice = test.readback.slice(0..byte_len);
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        slice.map_async(MapMode::Read, move |res| {
+            let _ = tx.send(res);
+        });
+        let _ = state.device.poll(PollType::wait_indefinitely());
+
+        let previously_hidden = std::mem::take(&mut self.hidden);
+        if !matches!(rx.try_recv(), Ok(Ok(()))) {
+            // Without results everything counts as visible
+            return candidates
+                .iter()
+                .map(|(proxy, _)| *proxy)
+                .filter(|proxy| previously_hidden.contains(proxy))
+                .collect();
+        }
+
+        {
+            let data = slice.get_mapped_range();
+            self.hidden = candidates
+                .iter()
+                .zip(data.chunks_exact(size_of::<u32>()))
+                .filter(|(_, visible)| visible.iter().all(|byte| *byte == 0))
+                .map(|((proxy, _), _)| *proxy)
+                .collect();
+        }
+        test.readback.unmap();
+
+        candidates
+            .iter()
+            .map(|(proxy, _)| *proxy)
+            .filter(|proxy| previously_hidden.contains(proxy) && !self.hidden.contains(proxy))
+            .collect()
+    }
+
+    /// Outlines the bounds of all hidden proxies for the debug overlay
+    pub fn update_debug_lines(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        bounds: impl Fn(&TypedComponentId) -> Option<BoundingSphere>,
+    ) {
+        let lines: Vec<DebugLine> = self
+            .hidden
+            .iter()
+            .filter_map(&bounds)
+            .flat_map(bounds_outline)
+            .collect();
+
+        self.debug_lines.upload(device, queue, &lines);
+    }
+
+    pub fn clear_debug_lines(&mut self) {
+        self.debug_lines.clear();
+    }
+
+    pub fn render_debug(&self, cache: &AssetCache, pass: &mut RenderPass, render: &BindGroup) {
+        self.debug_lines
+            .draw(cache, HShader::GIZMO_LINES_OVERLAY, pass, render);
+    }
+
+    pub fn has_debug_lines(&self) -> bool {
+        !self.debug_lines.is_empty()
+    }
+}
+
+/// The twelve edges of the box around a bounding sphere
+fn bounds_outline(bounds: BoundingSphere) -> [DebugLine; 12] {
+    let corner = |i: usize| {
+        let signs = Vec3::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+        );
+        bounds.center + signs * bounds.radius
+    };
+
+    // Corner pairs differing in exactly one axis
+    const EDGES: [(usize, usize); 12] = [
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (0, 2),
+        (1, 3),
+        (4, 6),
+        (5, 7),
+        (0, 4),
+        (1, 5),
+        (2, 6),
+        (3, 7),
+    ];
+
+    EDGES.map(|(a, b)| DebugLine {
+        start: corner(a),
+        start_color: HIDDEN_BOUNDS_COLOR,
+        end: corner(b),
+        end_color: HIDDEN_BOUNDS_COLOR,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_outline_follows_box_edges() {
+        let bounds = BoundingSphere {
+            center: Vec3::new(1.0, 2.0, 3.0),
+            radius: 0.5,
+        };
+
+        let lines = bounds_outline(bounds);
+        for line in &lines {
+            assert!((line.start.distance(line.end) - 1.0).abs() < 1e-5);
+            assert!((line.start - bounds.center).abs().max_element() - 0.5 < 1e-5);
+        }
+    }
+}
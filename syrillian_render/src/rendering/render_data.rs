@@ -1,3 +1,4 @@
+use crate::lighting::environment::EnvironmentViews;
 use crate::lighting::proxy::LightProxy;
 use crate::rendering::uniform::ShaderUniform;
 use glamx::{Mat4, UVec2, Vec3};
@@ -5,7 +6,7 @@ use std::f32::consts::FRAC_PI_2;
 use syrillian_asset::ensure_aligned;
 use syrillian_macros::UniformIndex;
 use syrillian_utils::Frustum;
-use wgpu::{BindGroupLayout, Device, Queue, Sampler, TextureView};
+use wgpu::{BindGroupLayout, Device, Queue};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
 // TODO: Use proper matrix types (Affine3, Perspective3)
@@ -204,6 +205,10 @@ pub enum RenderUniformIndex {
     Skybox = 2,
     SkyboxSampler = 3,
     Sky = 4,
+    Irradiance = 5,
+    Prefiltered = 6,
+    BrdfLut = 7,
+    IblSampler = 8,
 }
 
 pub struct RenderUniformData {
@@ -276,19 +281,19 @@ impl RenderUniformData {
     pub fn empty(
         device: &Device,
         render_bgl: &BindGroupLayout,
-        skybox_view: TextureView,
-        skybox_sampler: Sampler,
+        environment: &EnvironmentViews,
     ) -> Self {
         let camera_data = CameraUniform::empty();
         let system_data = SystemUniform::empty();
         let sky_data = SkyUniform::default();
-        let uniform = ShaderUniform::<RenderUniformIndex>::builder((*render_bgl).clone())
-            .with_buffer_data(&camera_data)
-            .with_buffer_data(&system_data)
-            .with_texture(skybox_view)
-            .with_sampler(skybox_sampler)
-            .with_buffer_data(&sky_data)
-            .build(device);
+        let uniform = Self::build_uniform(
+            device,
+            render_bgl,
+            &camera_data,
+            &system_data,
+            &sky_data,
+            environment,
+        );
 
         RenderUniformData {
             camera_data,
@@ -383,15 +388,36 @@ impl RenderUniformData {
         &mut self,
         device: &Device,
         render_bgl: &BindGroupLayout,
-        skybox_view: TextureView,
-        skybox_sampler: Sampler,
+        environment: &EnvironmentViews,
     ) {
-        self.uniform = ShaderUniform::<RenderUniformIndex>::builder((*render_bgl).clone())
-            .with_buffer_data(&self.camera_data)
-            .with_buffer_data(&self.system_data)
-            .with_texture(skybox_view)
-            .with_sampler(skybox_sampler)
-            .with_buffer_data(&self.sky_data)
-            .build(device);
+        self.uniform = Self::build_uniform(
+            device,
+            render_bgl,
+            &self.camera_data,
+            &self.system_data,
+            &self.sky_data,
+            environment,
+        );
+    }
+
+    fn build_uniform(
+        device: &Device,
+        render_bgl: &BindGroupLayout,
+        camera_data: &CameraUniform,
+        system_data: &SystemUniform,
+        sky_data: &SkyUniform,
+        environment: &EnvironmentViews,
+    ) -> ShaderUniform<RenderUniformIndex> {
+        ShaderUniform::<RenderUniformIndex>::builder((*render_bgl).clone())
+            .with_buffer_data(camera_data)
+            .with_buffer_data(system_data)
+            .with_texture(environment.skybox.clone())
+            .with_sampler(environment.skybox_sampler.clone())
+            .with_buffer_data(sky_data)
+            .with_texture(environment.irradiance.clone())
+            .with_texture(environment.prefiltered.clone())
+            .with_texture(environment.brdf_lut.clone())
+            .with_sampler(environment.ibl_sampler.clone())
+            .build(device)
     }
 }
@@ -9,12 +9,17 @@ use crate::cache::{AssetCache, GpuTexture};
 use crate::error::*;
 use crate::lighting::manager::LightManager;
 use crate::lighting::proxy::LightType;
+use crate::lighting::reflection_probe::{REFLECTION_PROBE_SIZE, ReflectionProbeManager};
+use crate::model_uniform::ModelUniform;
 use crate::passes::pipeline::FinalFrameContext;
-use crate::proxies::{SceneProxy, SceneProxyBinding};
+use crate::proxies::{InstanceBatch, SceneProxy, SceneProxyBinding};
 #[cfg(debug_assertions)]
 use crate::rendering::debug_renderer::DebugRenderer;
+use crate::rendering::decals::{DecalFrameBindings, DecalRenderer};
+use crate::rendering::gizmos::GizmoRenderer;
+use crate::rendering::instance_buffer::InstanceBuffer;
 use crate::rendering::message::{GBufferDebugTargets, ProxyUpdateCommand, RenderMsg};
-use crate::rendering::picking::{PickRequest, PickResult, color_bytes_to_hash};
+use crate::rendering::picking::{PickRequest, PickResult, color_bytes_to_hash, hash_to_rgba};
 use crate::rendering::render_data::{CameraUniform, RenderUniformData, SkyboxMode};
 use crate::rendering::state::State;
 use crate::rendering::texture_export::{TextureExportError, save_texture_to_png};
@@ -23,12 +28,14 @@ use crate::rendering::{FrameCtx, GPUDrawCtx, RenderPassType};
 use crate::strobe::StrobeRenderer;
 use crate::strobe::input::HitRect;
 use crossbeam_channel::{Receiver, Sender};
-use glamx::Affine3A;
+use glamx::{Affine3A, Vec4};
 use parking_lot::RwLock;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::hash::Hash;
 use std::mem;
+use std::ops::Range;
 use std::sync::Arc;
 use syrillian_asset::store::AssetRefreshMessage;
 use syrillian_asset::{HShader, HTexture2D};
@@ -41,6 +48,14 @@ use winit::dpi::PhysicalSize;
 
 const PICKING_ROW_PITCH: u32 = 256;
 
+/// Part of the scene drawn by a pass
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ScenePhase {
+    All,
+    Opaque,
+    Transparent,
+}
+
 pub struct RenderedFrame {
     pub target: ViewportId,
     pub frame: Texture,
@@ -55,10 +70,13 @@ pub struct Renderer {
     viewports: HashMap<ViewportId, RenderViewport>,
     proxies: HashMap<TypedComponentId, SceneProxyBinding>,
     strobe: RefCell<StrobeRenderer>,
+    instances: RefCell<InstanceBuffer>,
     start_time: Instant,
     pick_result_tx: Sender<PickResult>,
     pending_pick_requests: Vec<PickRequest>,
     pub lights: LightManager,
+    gizmos: GizmoRenderer,
+    decals: DecalRenderer,
     gbuffer_debug: HashMap<ViewportId, GBufferDebugTargets>,
 }
 
@@ -74,6 +92,8 @@ impl Renderer {
         cache.refresh_dirty();
 
         let lights = LightManager::new(&cache, &state.device, &state.queue);
+        let instances = InstanceBuffer::new(&state.device, cache.bgl_model());
+        let decals = DecalRenderer::new(&state.device, &cache);
         let start_time = Instant::now();
 
         info!("Render Pipeline AA mode: {:?}", EngineArgs::aa_mode());
@@ -94,9 +114,12 @@ impl Renderer {
             start_time,
             proxies: HashMap::new(),
             strobe: RefCell::new(strobe),
+            instances: RefCell::new(instances),
             pick_result_tx,
             pending_pick_requests: Vec::new(),
             lights,
+            gizmos: GizmoRenderer::default(),
+            decals,
             gbuffer_debug: HashMap::new(),
         })
     }
@@ -262,6 +285,7 @@ impl Renderer {
     #[profiling::function]
     fn render_frame_inner(&mut self, viewport: &mut RenderViewport) -> RenderedFrame {
         viewport.refresh_skybox_binding(&self.state.device, &self.cache);
+        self.instances.get_mut().reset();
 
         let mut ctx = viewport.begin_render();
         let frame_count = viewport.frame_count();
@@ -270,6 +294,7 @@ impl Renderer {
             &self.state.queue,
             frame_count,
         );
+        viewport.update_environment(&self.state.device, &self.state.queue, &self.cache);
         self.render(viewport, &mut ctx);
         self.finalize_frame(viewport)
     }
@@ -282,6 +307,8 @@ impl Renderer {
 
     #[profiling::function]
     pub fn render_all(&mut self) -> Vec<RenderedFrame> {
+        self.reflection_probe_pass();
+
         let mut targets = mem::take(&mut self.viewports);
         let mut frames = Vec::with_capacity(targets.len());
 
@@ -369,6 +396,7 @@ impl Renderer {
                 RenderPassType::Picking,
                 sorted_proxies,
                 &viewport.render_data,
+                &viewport.render_data.camera_data,
             );
         }
 
@@ -441,25 +469,100 @@ impl Renderer {
         })
     }
 
+    /// Captures the next due reflection probe, lit like the primary viewport.
     #[instrument(skip_all)]
-    fn render(&mut self, viewport: &RenderViewport, ctx: &mut FrameCtx) {
-        let main_sorted_proxies = self.sorted_proxies(&viewport.render_data.camera_data);
+    #[profiling::function]
+    fn reflection_probe_pass(&mut self) {
+        let Some(viewport) = self.viewports.get(&ViewportId::PRIMARY) else {
+            return;
+        };
+        let Some(capture) = self.lights.reflection_probes.next_capture() else {
+            return;
+        };
+
+        let sky_mode = viewport.sky_mode();
+        let sky_data = viewport.render_data.sky_data;
+        let environment = viewport.environment_views(&self.cache);
+        self.lights.reflection_probes.prepare_capture(
+            &self.state.device,
+            &self.cache,
+            &capture.proxy,
+            &environment,
+            &sky_data,
+        );
+        self.instances.get_mut().reset();
+
+        let probes = &self.lights.reflection_probes;
+        let ctx = FrameCtx {
+            depth_view: probes.depth_view().clone(),
+        };
+        let mut encoder = self
+            .state
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Reflection Probe Encoder"),
+            });
+
+        let size = REFLECTION_PROBE_SIZE as f32;
+        for face in 0..6 {
+            profiling::scope!("capture reflection probe face");
+
+            let (Some(render_data), Some(face_view)) =
+                (probes.face_render_data(face), probes.face_view(face))
+            else {
+                debug_panic!("Reflection probe face {face} is missing");
+                continue;
+            };
+
+            self.lights.cull_light_clusters(
+                &mut encoder,
+                &self.cache,
+                &self.state.queue,
+                render_data.uniform.bind_group(),
+                Vec4::new(0.0, 0.0, size, size),
+            );
+
+            {
+                let mut pass = Self::prepare_capture_skybox_pass(&mut encoder, face_view);
+                self.draw_skybox_background(sky_mode, render_data, &mut pass);
+            }
+
+            let sorted_proxies = self.sorted_proxies(&render_data.camera_data);
+            let pass = Self::prepare_capture_render_pass(&mut encoder, probes, face_view, &ctx);
+            self.render_scene(
+                &ctx,
+                pass,
+                RenderPassType::Color,
+                &sorted_proxies,
+                render_data,
+                &render_data.camera_data,
+            );
+        }
+
+        probes.filter_capture(&mut encoder, &self.cache, capture.slot);
+        self.state.queue.submit(Some(encoder.finish()));
+    }
+
+    #[instrument(skip_all)]
+    fn render(&mut self, viewport: &mut RenderViewport, ctx: &mut FrameCtx) {
+        let frustum_proxies = self.sorted_proxies(&viewport.render_data.camera_data);
+        let occlusion_culling = EngineArgs::get().occlusion_culling;
 
         if let Some(request) = self.take_pick_request(viewport.id) {
-            self.picking_pass(viewport, ctx, request, &main_sorted_proxies);
+            self.picking_pass(viewport, ctx, request, &frustum_proxies);
         }
 
         if !EngineArgs::get().no_shadows {
             // TODO: Make sure to switch to dynamically generated shaders that dont incorporate shadows automatically
-            self.shadow_pass(ctx);
+            self.shadow_pass(ctx, &viewport.render_data.camera_data);
         }
 
-        self.main_pass(viewport, ctx, &main_sorted_proxies);
+        self.main_pass(viewport, ctx, &frustum_proxies, occlusion_culling);
     }
 
     #[instrument(skip_all)]
     #[profiling::function]
-    fn shadow_pass(&mut self, ctx: &mut FrameCtx) {
+    fn shadow_pass(&mut self, ctx: &mut FrameCtx, lod_camera: &CameraUniform) {
         let mut encoder = self
             .state
             .device
@@ -489,11 +592,23 @@ impl Renderer {
 
             match light_type {
                 LightType::Spot if assignment.face == 0 => {
-                    self.prepare_shadow_map(&mut encoder, ctx, render_data, assignment.layer);
+                    self.prepare_shadow_map(
+                        &mut encoder,
+                        ctx,
+                        render_data,
+                        assignment.layer,
+                        lod_camera,
+                    );
                 }
                 LightType::Spot => debug_panic!("Requested to render more than one spotlight face"),
                 LightType::Point => {
-                    self.prepare_shadow_map(&mut encoder, ctx, render_data, assignment.layer);
+                    self.prepare_shadow_map(
+                        &mut encoder,
+                        ctx,
+                        render_data,
+                        assignment.layer,
+                        lod_camera,
+                    );
                 }
                 LightType::Sun => {}
             }
@@ -509,6 +624,7 @@ impl Renderer {
         ctx: &mut FrameCtx,
         render_data: &RenderUniformData,
         layer: u32,
+        lod_camera: &CameraUniform,
     ) {
         let shadow_proxies = self.shadow_proxies(&render_data.camera_data);
 
@@ -525,15 +641,17 @@ impl Renderer {
             RenderPassType::Shadow,
             &shadow_proxies,
             render_data,
+            lod_camera,
         );
     }
 
     #[instrument(skip_all)]
     fn main_pass(
         &mut self,
-        viewport: &RenderViewport,
+        viewport: &mut RenderViewport,
         ctx: &mut FrameCtx,
-        sorted_proxies: &[TypedComponentId],
+        frustum_proxies: &[TypedComponentId],
+        occlusion_culling: bool,
     ) {
         let mut encoder = self
             .state
@@ -542,39 +660,198 @@ impl Renderer {
                 label: Some("Main Encoder"),
             });
 
+        self.lights.cull_light_clusters(
+            &mut encoder,
+            &self.cache,
+            &self.state.queue,
+            viewport.render_data.uniform.bind_group(),
+            Self::viewport_pixel_rect(viewport),
+        );
+
         {
             let mut pass = self.prepare_skybox_render_pass(&mut encoder, viewport);
-            self.draw_skybox_background(viewport, &mut pass);
+            self.draw_skybox_background(viewport.sky_mode(), &viewport.render_data, &mut pass);
         }
 
-        {
-            let pass = self.prepare_main_render_pass(&mut encoder, viewport, ctx);
+        let culled = if occlusion_culling {
+            // The first phase draws what was visible in the last frame, the second one what
+            // the depth of the first phase revealed
+            let visible = viewport.occlusion.visible_proxies(frustum_proxies);
+            {
+                let pass = self.prepare_main_render_pass(&mut encoder, viewport, ctx, false);
+                self.render_scene_phase(
+                    ctx,
+                    pass,
+                    RenderPassType::Color,
+                    &visible,
+                    &viewport.render_data,
+                    &viewport.render_data.camera_data,
+                    ScenePhase::Opaque,
+                );
+            }
+            self.state.queue.submit(Some(encoder.finish()));
+
+            let revealed = self.occlusion_pass(viewport, frustum_proxies);
+            encoder = self
+                .state
+                .device
+                .create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("Main Encoder"),
+                });
+            if !revealed.is_empty() {
+                let pass = self.prepare_main_render_pass(&mut encoder, viewport, ctx, true);
+                self.render_scene_phase(
+                    ctx,
+                    pass,
+                    RenderPassType::Color,
+                    &revealed,
+                    &viewport.render_data,
+                    &viewport.render_data.camera_data,
+                    ScenePhase::Opaque,
+                );
+            }
+
+            Some(viewport.occlusion.visible_proxies(frustum_proxies))
+        } else {
+            None
+        };
+        let opaque_drawn = culled.is_some();
+        let sorted_proxies = culled.as_deref().unwrap_or(frustum_proxies);
+
+        #[cfg(debug_assertions)]
+        if DebugRenderer::occlusion_culling() {
+            viewport
+                .occlusion
+                .update_debug_lines(&self.state.device, &self.state.queue, |tid| {
+                    self.proxies.get(tid)?.bounds()
+                });
+        } else {
+            viewport.occlusion.clear_debug_lines();
+        }
+
+        if opaque_drawn || self.decals.has_decals() {
+            // Decals go between the opaque and transparent geometry, reading the G-buffer
+            // the opaque geometry left behind
+            if !opaque_drawn {
+                let pass = self.prepare_main_render_pass(&mut encoder, viewport, ctx, false);
+                self.render_scene_phase(
+                    ctx,
+                    pass,
+                    RenderPassType::Color,
+                    sorted_proxies,
+                    &viewport.render_data,
+                    &viewport.render_data.camera_data,
+                    ScenePhase::Opaque,
+                );
+            }
+
+            if self.decals.has_decals() {
+                self.decal_pass(&mut encoder, viewport);
+            }
 
+            let pass = self.prepare_main_render_pass(&mut encoder, viewport, ctx, true);
+            self.render_scene_phase(
+                ctx,
+                pass,
+                RenderPassType::Color,
+                sorted_proxies,
+                &viewport.render_data,
+                &viewport.render_data.camera_data,
+                ScenePhase::Transparent,
+            );
+        } else {
+            let pass = self.prepare_main_render_pass(&mut encoder, viewport, ctx, false);
             self.render_scene(
                 ctx,
                 pass,
                 RenderPassType::Color,
                 sorted_proxies,
                 &viewport.render_data,
+                &viewport.render_data.camera_data,
             );
         }
 
+        self.gizmo_pass(&mut encoder, viewport, ctx);
+
         self.state.queue.submit(Some(encoder.finish()));
     }
 
-    fn draw_skybox_background<'a>(&self, viewport: &RenderViewport, pass: &mut RenderPass<'a>) {
-        let shader = match viewport.sky_mode() {
+    fn decal_pass(&self, encoder: &mut CommandEncoder, viewport: &RenderViewport) {
+        let pipeline = &viewport.render_pipeline;
+        pipeline
+            .decal_surface
+            .copy_from(encoder, &pipeline.g_normal, &pipeline.g_material);
+
+        let mut pass = self.prepare_decal_render_pass(encoder, viewport);
+        self.decals.render(
+            &self.cache,
+            &mut pass,
+            DecalFrameBindings {
+                render: viewport.render_data.uniform.bind_group(),
+                light: self.lights.uniform().bind_group(),
+                shadow: self.lights.shadow_uniform().bind_group(),
+                surface: &pipeline.decal_surface,
+            },
+        );
+    }
+
+    fn gizmo_pass(&self, encoder: &mut CommandEncoder, viewport: &RenderViewport, ctx: &FrameCtx) {
+        let render_bind_group = viewport.render_data.uniform.bind_group();
+
+        if self.gizmos.has_lines() {
+            let mut pass = self.prepare_gizmo_render_pass(encoder, viewport, Some(ctx));
+            self.gizmos
+                .render_lines(&self.cache, &mut pass, render_bind_group);
+        }
+
+        if self.gizmos.has_overlay_lines() || viewport.occlusion.has_debug_lines() {
+            let mut pass = self.prepare_gizmo_render_pass(encoder, viewport, None);
+            self.gizmos
+                .render_overlay(&self.cache, &mut pass, render_bind_group);
+            viewport
+                .occlusion
+                .render_debug(&self.cache, &mut pass, render_bind_group);
+        }
+    }
+
+    /// Tests everything that survived frustum culling against the opaque depth drawn so far.
+    /// Returns the proxies that were left out before but turned out to be visible.
+    #[instrument(skip_all)]
+    #[profiling::function]
+    fn occlusion_pass(
+        &self,
+        viewport: &mut RenderViewport,
+        candidates: &[TypedComponentId],
+    ) -> Vec<TypedComponentId> {
+        let candidates: Vec<_> = candidates
+            .iter()
+            .filter_map(|tid| Some((*tid, self.proxies.get(tid)?.bounds()?)))
+            .collect();
+
+        let viewport_rect = Self::viewport_pixel_rect(viewport);
+        viewport.occlusion.cull(
+            &self.state,
+            &self.cache,
+            viewport.render_data.uniform.bind_group(),
+            viewport_rect,
+            &candidates,
+        )
+    }
+
+    fn draw_skybox_background<'a>(
+        &self,
+        sky_mode: SkyboxMode,
+        render_data: &RenderUniformData,
+        pass: &mut RenderPass<'a>,
+    ) {
+        let shader = match sky_mode {
             SkyboxMode::Cubemap => self.cache.shader(HShader::SKYBOX),
             SkyboxMode::Procedural => self.cache.shader(HShader::SKYBOX_PROCEDURAL),
         };
         let groups = shader.bind_groups();
 
         pass.set_pipeline(shader.solid_pipeline());
-        pass.set_bind_group(
-            groups.render,
-            viewport.render_data.uniform.bind_group(),
-            &[],
-        );
+        pass.set_bind_group(groups.render, render_data.uniform.bind_group(), &[]);
         if let Some(light) = groups.light {
             pass.set_bind_group(light, self.lights.uniform().bind_group(), &[]);
         }
@@ -588,7 +865,6 @@ impl Renderer {
         pass.draw(0..6, 0..1);
     }
 
-    #[instrument(skip_all)]
     fn render_scene(
         &self,
         frame_ctx: &FrameCtx,
@@ -596,6 +872,30 @@ impl Renderer {
         pass_type: RenderPassType,
         proxies: &[TypedComponentId],
         render_uniform: &RenderUniformData,
+        lod_camera: &CameraUniform,
+    ) {
+        self.render_scene_phase(
+            frame_ctx,
+            pass,
+            pass_type,
+            proxies,
+            render_uniform,
+            lod_camera,
+            ScenePhase::All,
+        );
+    }
+
+    #[instrument(skip_all)]
+    #[allow(clippy::too_many_arguments)]
+    fn render_scene_phase(
+        &self,
+        frame_ctx: &FrameCtx,
+        pass: RenderPass,
+        pass_type: RenderPassType,
+        proxies: &[TypedComponentId],
+        render_uniform: &RenderUniformData,
+        lod_camera: &CameraUniform,
+        phase: ScenePhase,
     ) {
         let shadow_bind_group = match pass_type {
             RenderPassType::Color | RenderPassType::Color2D => self.lights.shadow_uniform(),
@@ -612,28 +912,47 @@ impl Renderer {
             render_bind_group: render_uniform.uniform.bind_group(),
             light_bind_group: self.lights.uniform().bind_group(),
             shadow_bind_group,
+            lod_camera,
             transparency_pass: false,
         };
 
-        self.render_proxies(&mut draw_ctx, proxies);
+        self.render_proxies(&mut draw_ctx, proxies, phase);
 
         #[cfg(debug_assertions)]
-        if DebugRenderer::light() && pass_type == RenderPassType::Color {
+        if DebugRenderer::light()
+            && pass_type == RenderPassType::Color
+            && phase != ScenePhase::Opaque
+        {
             self.lights.render_debug_lights(self, &draw_ctx);
         }
     }
 
     #[instrument(skip_all)]
-    fn render_proxies(&self, ctx: &mut GPUDrawCtx, proxies: &[TypedComponentId]) {
+    fn render_proxies(
+        &self,
+        ctx: &mut GPUDrawCtx,
+        proxies: &[TypedComponentId],
+        phase: ScenePhase,
+    ) {
         ctx.transparency_pass = false;
 
-        for proxy in proxies {
-            let Some(proxy) = self.proxies.get(proxy) else {
-                debug_panic!("Sorted proxy not in proxy list");
-                continue;
-            };
+        if phase != ScenePhase::Transparent {
+            if EngineArgs::get().no_instancing {
+                for proxy in proxies {
+                    let Some(proxy) = self.proxies.get(proxy) else {
+                        debug_panic!("Sorted proxy not in proxy list");
+                        continue;
+                    };
 
-            proxy.render_by_pass(self, ctx);
+                    proxy.render_by_pass(self, ctx);
+                }
+            } else {
+                self.render_proxies_batched(ctx, proxies);
+            }
+        }
+
+        if phase == ScenePhase::Opaque {
+            return;
         }
 
         match ctx.pass_type {
@@ -657,6 +976,65 @@ impl Renderer {
         }
     }
 
+    /// Renders opaque proxies, drawing proxies with the same [`InstanceKey`] as one
+    /// instanced batch where the first member of the batch would have been drawn.
+    ///
+    /// [`InstanceKey`]: crate::proxies::InstanceKey
+    #[profiling::function]
+    fn render_proxies_batched(&self, ctx: &GPUDrawCtx, proxies: &[TypedComponentId]) {
+        let keyed = proxies.iter().filter_map(|proxy| {
+            let Some(binding) = self.proxies.get(proxy) else {
+                debug_panic!("Sorted proxy not in proxy list");
+                return None;
+            };
+            Some((binding, binding.proxy.instance_key(ctx)))
+        });
+
+        for members in group_by_key(keyed) {
+            match members.as_slice() {
+                [] => {}
+                [binding] => binding.render_by_pass(self, ctx),
+                [first, ..] => self.render_batch(ctx, first, &members),
+            }
+        }
+    }
+
+    fn render_batch(
+        &self,
+        ctx: &GPUDrawCtx,
+        first: &SceneProxyBinding,
+        members: &[&SceneProxyBinding],
+    ) {
+        let picking = ctx.pass_type == RenderPassType::Picking;
+        let Some(instances) = instance_models(members, picking) else {
+            debug_panic!("Batched proxy did not provide instance data");
+            for binding in members {
+                binding.render_by_pass(self, ctx);
+            }
+            return;
+        };
+
+        let (bind_group, instances) = self.push_instances(&instances);
+        let batch = InstanceBatch {
+            bind_group,
+            instances,
+            members,
+        };
+        first.proxy.render_instances(self, ctx, &batch);
+    }
+
+    /// Appends models to this frame's instance buffer, returning the bind group and instance
+    /// range to draw them with. Every pass gets its own range, so views drawn in the same
+    /// submission don't overwrite each other's models.
+    pub(crate) fn push_instances(&self, instances: &[ModelUniform]) -> (BindGroup, Range<u32>) {
+        self.instances.borrow_mut().push(
+            &self.state.device,
+            &self.state.queue,
+            self.cache.bgl_model(),
+            instances,
+        )
+    }
+
     #[instrument(skip_all)]
     #[profiling::function]
     fn finalize_frame(&mut self, viewport: &mut RenderViewport) -> RenderedFrame {
@@ -771,6 +1149,18 @@ impl Renderer {
                 self.update_proxy_transform(&cid, ltw, render_affine)
             }
             RenderMsg::ProxyUpdate(cid, command) => self.update_proxy(&cid, command),
+            RenderMsg::RegisterReflectionProbe(cid, proxy) => {
+                self.lights.reflection_probes.add_proxy(cid, *proxy);
+            }
+            RenderMsg::ReflectionProbeUpdate(cid, command) => {
+                self.lights.reflection_probes.execute_command(cid, command);
+            }
+            RenderMsg::RegisterDecal(cid, proxy) => {
+                self.decals.add_proxy(&self.state.device, cid, *proxy);
+            }
+            RenderMsg::DecalUpdate(cid, command) => {
+                self.decals.execute_command(&self.state.queue, cid, command);
+            }
             RenderMsg::LightProxyUpdate(cid, command) => {
                 self.lights.execute_light_command(cid, command)
             }
@@ -816,6 +1206,9 @@ impl Renderer {
                     warn!("Couldn't capture picking texture: {e}");
                 }
             }
+            RenderMsg::GenerateRenderTextureMips(texture) => {
+                self.cache.generate_render_texture_mips(texture);
+            }
             RenderMsg::SetGBufferDebug(target, targets) => {
                 if let Some(targets) = targets {
                     self.gbuffer_debug.insert(target, targets);
@@ -851,6 +1244,10 @@ impl Renderer {
             RenderMsg::UpdateStrobe(frame) => {
                 self.strobe.borrow_mut().update_frame(frame);
             }
+            RenderMsg::UpdateGizmos(frame) => {
+                self.gizmos
+                    .update_frame(&self.state.device, &self.state.queue, frame);
+            }
             RenderMsg::FrameEnd(_, _) => {}
         }
     }
@@ -875,6 +1272,8 @@ impl Renderer {
     fn remove_proxy(&mut self, cid: &TypedComponentId) {
         self.proxies.remove(cid);
         self.lights.remove_proxy(*cid);
+        self.lights.reflection_probes.remove_proxy(*cid);
+        self.decals.remove_proxy(*cid);
     }
 
     fn register_proxy(
@@ -938,6 +1337,15 @@ impl Renderer {
     }
 
     /// Apply viewport rect clipping for editor-style sub-viewport rendering.
+    /// The pixel rect (x, y, width, height) the camera of the viewport renders into
+    fn viewport_pixel_rect(viewport: &RenderViewport) -> Vec4 {
+        let [x, y, w, h] = viewport.viewport_rect.unwrap_or_else(|| {
+            let size = viewport.size();
+            [0.0, 0.0, size.width as f32, size.height as f32]
+        });
+        Vec4::new(x, y, w, h)
+    }
+
     fn apply_viewport_rect(pass: &mut RenderPass, viewport: &RenderViewport) {
         if let Some([x, y, w, h]) = viewport.viewport_rect {
             pass.set_viewport(x, y, w, h, 0.0, 1.0);
@@ -945,13 +1353,26 @@ impl Renderer {
         }
     }
 
+    /// Begins the scene pass, which clears the G-buffer and depth, unless it `resume`s
+    /// drawing into them.
     #[instrument(skip_all)]
     fn prepare_main_render_pass<'a>(
         &self,
         encoder: &'a mut CommandEncoder,
         viewport: &RenderViewport,
         ctx: &mut FrameCtx,
+        resume: bool,
     ) -> RenderPass<'a> {
+        let g_load = if resume {
+            LoadOp::Load
+        } else {
+            LoadOp::Clear(Color::BLACK)
+        };
+        let depth_load = if resume {
+            LoadOp::Load
+        } else {
+            LoadOp::Clear(1.0)
+        };
         let color_view = viewport.render_pipeline.offscreen_surface.view();
         let g_normal_view = viewport
             .render_pipeline
@@ -978,7 +1399,7 @@ impl Renderer {
                     depth_slice: None,
                     resolve_target: None,
                     ops: Operations {
-                        load: LoadOp::Clear(Color::BLACK),
+                        load: g_load,
                         store: StoreOp::Store,
                     },
                 }),
@@ -987,7 +1408,7 @@ impl Renderer {
                     depth_slice: None,
                     resolve_target: None,
                     ops: Operations {
-                        load: LoadOp::Clear(Color::BLACK),
+                        load: g_load,
                         store: StoreOp::Store,
                     },
                 }),
@@ -995,7 +1416,7 @@ impl Renderer {
             depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
                 view: &ctx.depth_view,
                 depth_ops: Some(Operations {
-                    load: LoadOp::Clear(1.0),
+                    load: depth_load,
                     store: StoreOp::Store,
                 }),
                 stencil_ops: None,
@@ -1006,6 +1427,44 @@ impl Renderer {
         pass
     }
 
+    fn prepare_decal_render_pass<'a>(
+        &self,
+        encoder: &'a mut CommandEncoder,
+        viewport: &RenderViewport,
+    ) -> RenderPass<'a> {
+        let color_view = viewport.render_pipeline.offscreen_surface.view();
+        let g_normal_view = viewport
+            .render_pipeline
+            .g_normal
+            .create_view(&TextureViewDescriptor::default());
+        let g_material_view = viewport
+            .render_pipeline
+            .g_material
+            .create_view(&TextureViewDescriptor::default());
+        let load = |view| {
+            Some(RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })
+        };
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Decal Render Pass"),
+            color_attachments: &[
+                load(color_view),
+                load(&g_normal_view),
+                load(&g_material_view),
+            ],
+            ..RenderPassDescriptor::default()
+        });
+        Self::apply_viewport_rect(&mut pass, viewport);
+        pass
+    }
+
     fn prepare_skybox_render_pass<'a>(
         &self,
         encoder: &'a mut CommandEncoder,
@@ -1029,6 +1488,106 @@ impl Renderer {
         Self::apply_viewport_rect(&mut pass, viewport);
         pass
     }
+
+    /// Draws onto the finished scene, depth tested if the frame's depth is given.
+    fn prepare_gizmo_render_pass<'a>(
+        &self,
+        encoder: &'a mut CommandEncoder,
+        viewport: &RenderViewport,
+        ctx: Option<&FrameCtx>,
+    ) -> RenderPass<'a> {
+        let color_view = viewport.render_pipeline.offscreen_surface.view();
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Gizmo Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: color_view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: ctx.map(|ctx| RenderPassDepthStencilAttachment {
+                view: &ctx.depth_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..RenderPassDescriptor::default()
+        });
+        Self::apply_viewport_rect(&mut pass, viewport);
+        pass
+    }
+
+    fn prepare_capture_skybox_pass<'a>(
+        encoder: &'a mut CommandEncoder,
+        face_view: &TextureView,
+    ) -> RenderPass<'a> {
+        encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Reflection Probe Skybox Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: face_view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            ..RenderPassDescriptor::default()
+        })
+    }
+
+    fn prepare_capture_render_pass<'a>(
+        encoder: &'a mut CommandEncoder,
+        probes: &ReflectionProbeManager,
+        face_view: &TextureView,
+        ctx: &FrameCtx,
+    ) -> RenderPass<'a> {
+        let clear = Operations {
+            load: LoadOp::Clear(Color::BLACK),
+            store: StoreOp::Discard,
+        };
+        encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Reflection Probe Render Pass"),
+            color_attachments: &[
+                Some(RenderPassColorAttachment {
+                    view: face_view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Load,
+                        store: StoreOp::Store,
+                    },
+                }),
+                Some(RenderPassColorAttachment {
+                    view: probes.g_normal_view(),
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: clear,
+                }),
+                Some(RenderPassColorAttachment {
+                    view: probes.g_material_view(),
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: clear,
+                }),
+            ],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: &ctx.depth_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            ..RenderPassDescriptor::default()
+        })
+    }
 }
 
 #[instrument(skip_all)]
@@ -1040,8 +1599,7 @@ fn sorted_enabled_proxy_ids(
 ) -> Vec<TypedComponentId> {
     let is_culling_enabled = !EngineArgs::get().no_frustum_culling;
     let near_plane = frustum.map(|f| f.side(FrustumSide::Near));
-    let mut filtered =
-        Vec::<(TypedComponentId, u32, i64)>::with_capacity(proxies.len());
+    let mut filtered = Vec::<(TypedComponentId, u32, i64)>::with_capacity(proxies.len());
 
     for (tid, binding) in proxies {
         if !binding.enabled {
@@ -1097,6 +1655,43 @@ fn culled_enabled_proxy_ids(
     filtered
 }
 
+/// The models of a batch in draw order, with the object hash of every member for picking.
+/// `None` if a member can't be drawn instanced.
+fn instance_models(members: &[&SceneProxyBinding], picking: bool) -> Option<Vec<ModelUniform>> {
+    members
+        .iter()
+        .map(|binding| {
+            let mut data = binding.proxy.instance_data(binding)?;
+            if picking {
+                data.object_hash = hash_to_rgba(binding.object_hash);
+            }
+            Some(data)
+        })
+        .collect()
+}
+
+/// Groups `items` by key, in the order each key first appears. Items without a key get
+/// a group of their own at their position.
+fn group_by_key<T, K: Hash + Eq>(items: impl IntoIterator<Item = (T, Option<K>)>) -> Vec<Vec<T>> {
+    let mut groups: Vec<Vec<T>> = Vec::new();
+    let mut group_of_key: HashMap<K, usize> = HashMap::new();
+
+    for (item, key) in items {
+        match key {
+            Some(key) => {
+                let group = *group_of_key.entry(key).or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+                groups[group].push(item);
+            }
+            None => groups.push(vec![item]),
+        }
+    }
+
+    groups
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1167,6 +1762,128 @@ mod tests {
         assert!(!sorted.contains(&id_disabled));
     }
 
+    #[test]
+    fn group_by_key_batches_equal_instance_keys() {
+        use crate::proxies::InstanceKey;
+        use syrillian_asset::{HMaterialInstance, HMesh};
+
+        let materials = [HMaterialInstance::DEFAULT; 2];
+        let other_materials = [HMaterialInstance::DEFAULT, HMaterialInstance::FALLBACK];
+        let full = [0..24, 24..36];
+        let lod = [0..8, 8..12];
+        let key = |mesh, materials, material_ranges| {
+            Some(InstanceKey {
+                mesh,
+                materials,
+                material_ranges,
+            })
+        };
+
+        let groups = group_by_key([
+            (0, key(HMesh::UNIT_CUBE, &materials[..], &full[..])),
+            (1, key(HMesh::SPHERE, &materials, &full)),
+            (2, None),
+            (3, key(HMesh::UNIT_CUBE, &materials, &full)),
+            (4, key(HMesh::UNIT_CUBE, &other_materials, &full)),
+            (5, key(HMesh::UNIT_CUBE, &materials, &lod)),
+            (6, key(HMesh::SPHERE, &materials, &full)),
+            (7, None),
+        ]);
+
+        // Batches sit where their first member was, a different mesh, material or
+        // level of detail starts a new batch and keyless proxies are drawn alone
+        assert_eq!(
+            groups,
+            vec![vec![0, 3], vec![1, 6], vec![2], vec![4], vec![5], vec![7]]
+        );
+    }
+
+    #[test]
+    fn group_by_key_keeps_keyless_order() {
+        let groups = group_by_key([(0, None::<u32>), (1, None), (2, None)]);
+        assert_eq!(groups, vec![vec![0], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn instance_models_keep_decal_layers() {
+        use crate::proxies::{MeshSceneProxy, MeshUniformIndex, RenderMeshData};
+        use crate::rendering::uniform::ShaderUniform;
+        use glamx::Vec3;
+        use syrillian_asset::{HMaterialInstance, HMesh};
+
+        struct MarkerA;
+        struct MarkerB;
+
+        let (device, _queue) = Device::noop(&DeviceDescriptor::default());
+        let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Model Bind Group Layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::all(),
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let binding = |tid, object_hash, decal_layer| {
+            // Like the proxy sets up its model, then moved by a transform refresh
+            let mut model = ModelUniform::from_affine(&Affine3A::IDENTITY);
+            model.decal_layer = decal_layer;
+            model.update(&Affine3A::from_translation(Vec3::X));
+
+            let uniform = ShaderUniform::<MeshUniformIndex>::builder(bgl.clone())
+                .with_storage_buffer_data(std::slice::from_ref(&model))
+                .build(&device);
+            let proxy = MeshSceneProxy {
+                mesh: HMesh::UNIT_CUBE,
+                materials: vec![HMaterialInstance::DEFAULT; 2],
+                material_ranges: vec![0..24, 24..36],
+                lods: Vec::new(),
+                lod_selection: Default::default(),
+                bounding: None,
+                model_bounding: None,
+                decal_layer,
+            };
+            SceneProxyBinding::new(
+                tid,
+                object_hash,
+                Affine3A::IDENTITY,
+                None,
+                Box::new(RenderMeshData::new(model, uniform)),
+                Box::new(proxy),
+            )
+        };
+
+        let a = binding(TypedComponentId::new::<MarkerA>(ComponentId::null()), 1, 3);
+        let b = binding(TypedComponentId::new::<MarkerB>(ComponentId::null()), 2, 17);
+        let members = [&a, &b];
+
+        for picking in [false, true] {
+            let models = instance_models(&members, picking).unwrap();
+            let layers: Vec<_> = models.iter().map(|m| m.decal_layer).collect();
+            assert_eq!(layers, [3, 17]);
+        }
+
+        let picked = instance_models(&members, true).unwrap();
+        let object_hash = picked[1].object_hash;
+        assert_eq!(object_hash, hash_to_rgba(2));
+
+        // Proxies without instance data can't join a batch
+        let without_data = SceneProxyBinding::new(
+            TypedComponentId::new::<MarkerB>(ComponentId::null()),
+            3,
+            Affine3A::IDENTITY,
+            None,
+            Box::new(()),
+            Box::new(TestProxy { priority: 0 }),
+        );
+        assert!(instance_models(&[&a, &without_data], false).is_none());
+    }
+
     fn insert_proxy<T: 'static>(
         proxies: &mut HashMap<TypedComponentId, SceneProxyBinding>,
         priority: u32,
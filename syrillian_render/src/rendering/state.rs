@@ -122,7 +122,11 @@ impl State {
                     | Features::IMMEDIATES
                     | Features::ADDRESS_MODE_CLAMP_TO_BORDER
                     | Features::TEXTURE_FORMAT_16BIT_NORM
-                    | Features::TEXTURE_COMPRESSION_BC,
+                    | (adapter.features()
+                        & (Features::PIPELINE_CACHE
+                            | Features::TEXTURE_COMPRESSION_BC
+                            | Features::TEXTURE_COMPRESSION_ETC2
+                            | Features::TEXTURE_COMPRESSION_ASTC)),
                 required_limits: Limits {
                     max_bind_groups: 6,
                     max_immediate_size: 128,
@@ -1,7 +1,9 @@
 use crate::cache::AssetCache;
+use crate::lighting::environment::{EnvironmentLighting, EnvironmentViews};
 use crate::lighting::proxy::{LightProxy, LightType};
 use crate::passes::pipeline::RenderPipeline;
 use crate::rendering::FrameCtx;
+use crate::rendering::occlusion::OcclusionCuller;
 use crate::rendering::picking::PickingSurface;
 use crate::rendering::render_data::{RenderUniformData, SkyAtmosphereSettings, SkyboxMode};
 use glamx::UVec2;
@@ -41,7 +43,9 @@ pub struct RenderViewport {
     pub config: SurfaceConfiguration,
     pub render_pipeline: RenderPipeline,
     pub picking_surface: PickingSurface,
+    pub occlusion: OcclusionCuller,
     pub render_data: RenderUniformData,
+    environment: EnvironmentLighting,
     pub start_time: Instant,
     pub delta_time: Duration,
     pub last_frame_time: Instant,
@@ -69,21 +73,23 @@ impl RenderViewport {
         let render_bgl = cache.bgl_render();
 
         let picking_surface = PickingSurface::new(device, &config);
-        let fallback_skybox = cache.cubemap_fallback();
+        let environment = EnvironmentLighting::new(device, cache);
         let render_data = RenderUniformData::empty(
             device,
             &render_bgl,
-            fallback_skybox.view().clone(),
-            fallback_skybox.sampler().clone(),
+            &environment.views(&cache.cubemap_fallback()),
         );
         let post_pipeline = RenderPipeline::new(device, cache, &config);
+        let occlusion = OcclusionCuller::new(device, cache, &post_pipeline.depth_texture);
 
         RenderViewport {
             id,
             config,
             render_pipeline: post_pipeline,
             picking_surface,
+            occlusion,
             render_data,
+            environment,
             start_time: Instant::now(),
             delta_time: Duration::default(),
             last_frame_time: Instant::now(),
@@ -113,6 +119,7 @@ impl RenderViewport {
         self.config = config;
 
         self.render_pipeline.recreate(device, cache, &self.config);
+        self.occlusion = OcclusionCuller::new(device, cache, &self.render_pipeline.depth_texture);
         self.picking_surface.recreate(device, &self.config);
     }
 
@@ -212,12 +219,24 @@ impl RenderViewport {
             .and_then(|handle| cache.cubemap(handle))
             .unwrap_or_else(|| cache.cubemap_fallback());
         let render_bgl = cache.bgl_render();
-        self.render_data.rebuild_bind_group(
-            device,
-            &render_bgl,
-            skybox.view().clone(),
-            skybox.sampler().clone(),
-        );
+        self.environment.set_source(device, cache, &skybox);
+        self.render_data
+            .rebuild_bind_group(device, &render_bgl, &self.environment.views(&skybox));
         self.resolved_skybox = resolved;
     }
+
+    /// The sky and image based lighting this viewport is currently lit by.
+    pub fn environment_views(&self, cache: &AssetCache) -> EnvironmentViews {
+        let skybox = self
+            .resolved_skybox
+            .and_then(|handle| cache.cubemap(handle))
+            .unwrap_or_else(|| cache.cubemap_fallback());
+        self.environment.views(&skybox)
+    }
+
+    /// Refilters the image based lighting when the sky changed.
+    pub fn update_environment(&mut self, device: &Device, queue: &Queue, cache: &AssetCache) {
+        self.environment
+            .update(cache, device, queue, &self.render_data.sky_data);
+    }
 }
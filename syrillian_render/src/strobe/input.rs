@@ -25,6 +25,8 @@ pub struct StrobeInputState {
     active_node: Option<UiNodeId>,
     press_origin: Option<Vec2>,
     last_mouse_pos: Vec2,
+    released: bool,
+    text_input: String,
 }
 
 impl StrobeInputState {
@@ -93,6 +95,17 @@ impl StrobeInputState {
         }
 
         self.last_mouse_pos = mouse_pos;
+        self.released = just_released;
+    }
+
+    /// Sets the text typed since the last frame. Backspace is passed as `'\u{8}'`.
+    pub fn set_text_input(&mut self, text: &str) {
+        self.text_input.clear();
+        self.text_input.push_str(text);
+    }
+
+    pub fn text_input(&self) -> &str {
+        &self.text_input
     }
 
     pub fn interaction(&self, node_id: UiNodeId) -> UiInteraction {
@@ -103,6 +116,24 @@ impl StrobeInputState {
         self.active_node
     }
 
+    pub fn mouse_position(&self) -> Vec2 {
+        self.last_mouse_pos
+    }
+
+    /// Returns true if the pointer was released this frame, regardless of what was under it.
+    pub fn pointer_released(&self) -> bool {
+        self.released
+    }
+
+    /// The rect a node occupied when the UI was last laid out.
+    pub fn hit_rect(&self, node_id: UiNodeId) -> Option<Rect> {
+        self.hit_rects
+            .iter()
+            .rev()
+            .find(|hr| hr.node_id == node_id)
+            .map(|hr| hr.rect)
+    }
+
     /// Returns true if any UI element is currently hovered or being pressed.
     pub fn has_any_interaction(&self) -> bool {
         self.active_node.is_some() || self.interactions.values().any(|i| i.hovered)
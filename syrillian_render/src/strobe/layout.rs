@@ -1,7 +1,7 @@
 use crate::rendering::viewport::ViewportId;
 use crate::strobe::UiSpacing;
 use crate::strobe::input::{StrobeInputState, UiInteraction};
-use crate::strobe::style::{Align, Size, Style};
+use crate::strobe::style::{Align, Size, Style, Theme};
 use crate::strobe::ui_element::Padding;
 use crate::strobe::ui_element::{Rect, UiElement};
 use crate::strobe::{CacheId, UiDrawContext};
@@ -34,15 +34,61 @@ impl LayoutElement<UiDrawContext<'_, '_, '_, '_, '_>> for Box<dyn UiElement> {
     }
 }
 
+/// Where a [`UiLayer`] is placed inside the area the root is rendered into.
+#[derive(Debug, Clone, Copy)]
+pub enum LayerAnchor {
+    /// Places the top left corner at a point, shifted as needed to stay inside the area.
+    At(Vec2),
+    /// Centers the layer at its measured size.
+    Center,
+    /// Covers the whole area.
+    Fill,
+}
+
+impl LayerAnchor {
+    pub fn resolve(self, size: Vec2, area: Rect) -> Rect {
+        match self {
+            LayerAnchor::At(point) => {
+                let max = (area.max() - size).max(area.min());
+                Rect::new(point.clamp(area.min(), max), size)
+            }
+            LayerAnchor::Center => {
+                let offset = ((area.size - size) * 0.5).max(Vec2::ZERO);
+                Rect::new(area.position + offset, size)
+            }
+            LayerAnchor::Fill => area,
+        }
+    }
+}
+
+/// A subtree that is rendered after the regular tree, on top of it.
+///
+/// Layers are sorted by `z` across the whole root; layers with equal `z` keep the order they were
+/// added in. Since hit testing prefers whatever was registered last, layers also receive input
+/// before anything below them.
+pub struct UiLayer<T = Box<dyn UiElement>> {
+    pub z: u32,
+    pub anchor: LayerAnchor,
+    pub node: StrobeNode<T>,
+}
+
+impl UiLayer {
+    pub const MODAL: u32 = 100;
+    pub const POPUP: u32 = 200;
+    pub const TOOLTIP: u32 = 300;
+}
+
 pub struct StrobeNode<T = Box<dyn UiElement>> {
     pub direction: LayoutDirection,
     pub padding: Padding,
     pub children: Vec<StrobeNode<T>>,
+    pub layers: Vec<UiLayer<T>>,
     pub element: Option<T>,
     pub id: u32,
     pub width: Size,
     pub height: Size,
     pub align: Align,
+    pub passthrough: bool,
 }
 
 impl<T> Default for StrobeNode<T> {
@@ -51,11 +97,13 @@ impl<T> Default for StrobeNode<T> {
             direction: LayoutDirection::Vertical,
             padding: Padding::default(),
             children: Vec::new(),
+            layers: Vec::new(),
             element: None,
             id: 0,
             width: Size::Auto,
             height: Size::Auto,
             align: Align::Start,
+            passthrough: false,
         }
     }
 }
@@ -66,11 +114,13 @@ impl<T> StrobeNode<T> {
             direction,
             padding: Padding::default(),
             children: Vec::new(),
+            layers: Vec::new(),
             element: None,
             id: 0,
             width: Size::Auto,
             height: Size::Auto,
             align: Align::Start,
+            passthrough: false,
         }
     }
 
@@ -79,11 +129,13 @@ impl<T> StrobeNode<T> {
             direction: LayoutDirection::Horizontal,
             padding: Padding::default(),
             children: Vec::new(),
+            layers: Vec::new(),
             element: Some(element),
             id: 0,
             width: Size::Auto,
             height: Size::Auto,
             align: Align::Start,
+            passthrough: false,
         }
     }
 
@@ -94,6 +146,47 @@ impl<T> StrobeNode<T> {
             LayoutDirection::Stack => Size::Auto,
         }
     }
+
+    fn apply_style(&mut self, style: &Style) {
+        self.padding = style.padding;
+        self.width = style.width;
+        self.height = style.height;
+        self.align = style.align;
+        self.passthrough = style.passthrough;
+    }
+
+    fn collect_layers<'s>(&'s self, out: &mut Vec<&'s UiLayer<T>>) {
+        for layer in &self.layers {
+            out.push(layer);
+            layer.node.collect_layers(out);
+        }
+        for child in &self.children {
+            child.collect_layers(out);
+        }
+    }
+
+    /// Renders the tree into `rect`, followed by all of its layers in z order.
+    pub fn render_root<C: ?Sized + ContextWithId>(&self, ctx: &mut C, rect: Rect)
+    where
+        T: LayoutElement<C>,
+    {
+        self.render_layout(ctx, rect);
+
+        let mut layers = Vec::new();
+        self.collect_layers(&mut layers);
+        layers.sort_by_key(|layer| layer.z);
+
+        for layer in layers {
+            let size = layer.node.measure(ctx);
+            let layer_rect = layer.anchor.resolve(size, rect);
+
+            ctx.set_id(layer.node.id);
+            if !layer.node.passthrough {
+                ctx.register_hit_rect(layer_rect, layer.node.id);
+            }
+            layer.node.render_layout(ctx, layer_rect);
+        }
+    }
 }
 
 fn main_axis(v: Vec2, direction: LayoutDirection) -> f32 {
@@ -193,7 +286,9 @@ where
         if matches!(self.direction, LayoutDirection::Stack) {
             for child in &self.children {
                 ctx.set_id(child.id);
-                ctx.register_hit_rect(rect, child.id);
+                if !child.passthrough {
+                    ctx.register_hit_rect(rect, child.id);
+                }
                 child.render_layout(ctx, rect);
             }
             return;
@@ -281,7 +376,9 @@ where
             let child_rect = Rect::new(child_pos, child_size);
 
             ctx.set_id(child.id);
-            ctx.register_hit_rect(child_rect, child.id);
+            if !child.passthrough {
+                ctx.register_hit_rect(child_rect, child.id);
+            }
             child.render_layout(ctx, child_rect);
 
             main_cursor += child_main;
@@ -292,6 +389,7 @@ where
 pub struct UiBuilder<'a, T = Box<dyn UiElement>> {
     node: &'a mut StrobeNode<T>,
     pub style: Style,
+    pub theme: Theme,
     size: Vec2,
     current_id: u32,
     input_state: Option<&'a StrobeInputState>,
@@ -302,6 +400,7 @@ impl<'a, T> UiBuilder<'a, T> {
         Self {
             node,
             style: Style::default(),
+            theme: Theme::default(),
             size,
             current_id: 0,
             input_state: None,
@@ -316,6 +415,7 @@ impl<'a, T> UiBuilder<'a, T> {
         Self {
             node,
             style: Style::default(),
+            theme: Theme::default(),
             size,
             current_id: 0,
             input_state: Some(input_state),
@@ -347,10 +447,7 @@ impl<'a, T> UiBuilder<'a, T> {
     pub fn vertical(&mut self, f: impl FnOnce(&mut UiBuilder<T>)) {
         let mut node = StrobeNode::new(LayoutDirection::Vertical);
         node.id = self.current_id;
-        node.padding = self.style.padding;
-        node.width = self.style.width;
-        node.height = self.style.height;
-        node.align = self.style.align;
+        node.apply_style(&self.style);
 
         self.current_id += 1;
 
@@ -364,10 +461,7 @@ impl<'a, T> UiBuilder<'a, T> {
     pub fn horizontal(&mut self, f: impl FnOnce(&mut UiBuilder<T>)) {
         let mut node = StrobeNode::new(LayoutDirection::Horizontal);
         node.id = self.current_id;
-        node.padding = self.style.padding;
-        node.width = self.style.width;
-        node.height = self.style.height;
-        node.align = self.style.align;
+        node.apply_style(&self.style);
 
         self.current_id += 1;
 
@@ -381,10 +475,7 @@ impl<'a, T> UiBuilder<'a, T> {
     pub fn stack(&mut self, f: impl FnOnce(&mut UiBuilder<T>)) {
         let mut node = StrobeNode::new(LayoutDirection::Stack);
         node.id = self.current_id;
-        node.padding = self.style.padding;
-        node.width = self.style.width;
-        node.height = self.style.height;
-        node.align = self.style.align;
+        node.apply_style(&self.style);
 
         self.current_id += 1;
 
@@ -399,10 +490,7 @@ impl<'a, T> UiBuilder<'a, T> {
         let id = self.current_id;
         let mut node = StrobeNode::leaf(element);
         node.id = id;
-        node.padding = self.style.padding;
-        node.width = self.style.width;
-        node.height = self.style.height;
-        node.align = self.style.align;
+        node.apply_style(&self.style);
 
         self.current_id += 1;
 
@@ -410,6 +498,48 @@ impl<'a, T> UiBuilder<'a, T> {
         id
     }
 
+    /// Adds a stack that is rendered on top of the regular tree. See [`UiLayer`].
+    pub fn layer(&mut self, z: u32, anchor: LayerAnchor, f: impl FnOnce(&mut UiBuilder<T>)) {
+        let mut node = StrobeNode::new(LayoutDirection::Stack);
+        node.id = self.current_id;
+        node.apply_style(&self.style);
+
+        self.current_id += 1;
+
+        let mut builder = self.enter(&mut node);
+        f(&mut builder);
+        self.current_id = builder.current_id;
+
+        self.node.layers.push(UiLayer { z, anchor, node });
+    }
+
+    /// The id the next added node will receive.
+    pub fn next_id(&self) -> u32 {
+        self.current_id
+    }
+
+    /// The rect a node occupied when the UI was last laid out, if it took part in hit testing.
+    pub fn previous_rect(&self, id: u32) -> Option<Rect> {
+        self.input_state.and_then(|s| s.hit_rect(id))
+    }
+
+    pub fn mouse_position(&self) -> Vec2 {
+        self.input_state
+            .map(StrobeInputState::mouse_position)
+            .unwrap_or_default()
+    }
+
+    pub fn text_input(&self) -> &str {
+        self.input_state
+            .map(StrobeInputState::text_input)
+            .unwrap_or_default()
+    }
+
+    pub fn pointer_released(&self) -> bool {
+        self.input_state
+            .is_some_and(StrobeInputState::pointer_released)
+    }
+
     pub fn window_size(&self) -> Vec2 {
         self.size
     }
@@ -418,9 +548,16 @@ impl<'a, T> UiBuilder<'a, T> {
     where
         'a: 'b,
     {
+        // children of a node that doesn't take part in hit testing don't either
+        let style = Style {
+            passthrough: node.passthrough,
+            ..Style::default()
+        };
+
         UiBuilder {
             node,
-            style: Style::default(),
+            style,
+            theme: self.theme,
             size: self.size,
             current_id: self.current_id,
             input_state: self.input_state,
@@ -443,6 +580,7 @@ pub struct StrobeRoot {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::strobe::input::HitRect;
     use std::cell::RefCell;
     use std::rc::Rc;
 
@@ -467,12 +605,12 @@ mod tests {
         }
     }
 
-    impl LayoutElement<MockContext> for MockElement {
-        fn measure(&self, _ctx: &mut MockContext) -> Vec2 {
+    impl<C: ?Sized> LayoutElement<C> for MockElement {
+        fn measure(&self, _ctx: &mut C) -> Vec2 {
             self.size
         }
 
-        fn render_layout(&self, _ctx: &mut MockContext, rect: Rect) {
+        fn render_layout(&self, _ctx: &mut C, rect: Rect) {
             self.layout_log.borrow_mut().push(rect);
         }
     }
@@ -681,4 +819,168 @@ mod tests {
         assert_eq!(calls[3].position.y, 0.0);
         assert_eq!(calls[3].size.y, 100.0);
     }
+    #[test]
+    fn layers_render_after_tree_in_z_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut root: StrobeNode<MockElement> = StrobeNode::default();
+        let mut builder = UiBuilder::new(&mut root, Vec2::ZERO);
+
+        builder.vertical(|ui| {
+            ui.layer(
+                UiLayer::TOOLTIP,
+                LayerAnchor::At(Vec2::new(490.0, 0.0)),
+                |ui| {
+                    ui.add(MockElement::new(30.0, 30.0, log.clone()));
+                },
+            );
+            ui.add(MockElement::new(100.0, 50.0, log.clone()));
+            ui.layer(UiLayer::MODAL, LayerAnchor::Center, |ui| {
+                ui.add(MockElement::new(100.0, 100.0, log.clone()));
+            });
+        });
+
+        let mut ctx = MockContext;
+        let rect = Rect::new(Vec2::ZERO, Vec2::new(500.0, 500.0));
+        root.render_root(&mut ctx, rect);
+
+        let calls = log.borrow();
+        assert_eq!(calls.len(), 3);
+
+        // regular tree first
+        assert_eq!(calls[0].size, Vec2::new(100.0, 50.0));
+
+        // modal layer, centered
+        assert_eq!(calls[1].position, Vec2::new(200.0, 200.0));
+
+        // tooltip layer last, shifted back inside the area
+        assert_eq!(calls[2].position, Vec2::new(470.0, 0.0));
+    }
+
+    #[test]
+    fn passthrough_nodes_skip_hit_testing() {
+        struct HitContext(Vec<u32>);
+
+        impl ContextWithId for HitContext {
+            fn set_id(&mut self, _id: u32) {}
+
+            fn register_hit_rect(&mut self, _rect: Rect, id: u32) {
+                self.0.push(id);
+            }
+        }
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut root: StrobeNode<MockElement> = StrobeNode::default();
+        let mut builder = UiBuilder::new(&mut root, Vec2::ZERO);
+
+        let mut widget_id = 0;
+        builder.stack(|ui| {
+            widget_id = ui.next_id();
+            ui.stack(|ui| {
+                ui.style.passthrough = true;
+                ui.add(MockElement::new(10.0, 10.0, log.clone()));
+                ui.horizontal(|ui| {
+                    ui.add(MockElement::new(10.0, 10.0, log.clone()));
+                });
+            });
+        });
+
+        let mut ctx = HitContext(Vec::new());
+        let rect = Rect::new(Vec2::ZERO, Vec2::new(100.0, 100.0));
+        root.render_root(&mut ctx, rect);
+
+        assert_eq!(ctx.0.last(), Some(&widget_id));
+        assert_eq!(log.borrow().len(), 2);
+    }
+
+    struct HitRectContext(Vec<HitRect>);
+
+    impl ContextWithId for HitRectContext {
+        fn set_id(&mut self, _id: u32) {}
+
+        fn register_hit_rect(&mut self, rect: Rect, id: u32) {
+            self.0.push(HitRect { rect, node_id: id });
+        }
+    }
+
+    #[test]
+    fn layer_hit_rects_register_in_z_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut root: StrobeNode<MockElement> = StrobeNode::default();
+        let mut builder = UiBuilder::new(&mut root, Vec2::ZERO);
+
+        let (mut tooltip, mut popup, mut nested_modal, mut modal) = (0, 0, 0, 0);
+        builder.vertical(|ui| {
+            tooltip = ui.next_id();
+            ui.layer(UiLayer::TOOLTIP, LayerAnchor::Fill, |ui| {
+                ui.add(MockElement::new(10.0, 10.0, log.clone()));
+            });
+            popup = ui.next_id();
+            ui.layer(UiLayer::POPUP, LayerAnchor::Fill, |ui| {
+                // layers nested in layers are still sorted across the whole root
+                nested_modal = ui.next_id();
+                ui.layer(UiLayer::MODAL, LayerAnchor::Fill, |ui| {
+                    ui.add(MockElement::new(10.0, 10.0, log.clone()));
+                });
+            });
+            modal = ui.next_id();
+            ui.layer(UiLayer::MODAL, LayerAnchor::Fill, |ui| {
+                ui.add(MockElement::new(10.0, 10.0, log.clone()));
+            });
+        });
+
+        let mut ctx = HitRectContext(Vec::new());
+        let rect = Rect::new(Vec2::ZERO, Vec2::new(100.0, 100.0));
+        root.render_root(&mut ctx, rect);
+
+        let layer_ids: Vec<u32> = ctx
+            .0
+            .iter()
+            .map(|hr| hr.node_id)
+            .filter(|id| [tooltip, popup, nested_modal, modal].contains(id))
+            .collect();
+
+        // equal z keeps the order the layers were added in
+        assert_eq!(layer_ids, [nested_modal, modal, popup, tooltip]);
+    }
+
+    #[test]
+    fn topmost_layer_wins_hit_test() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut root: StrobeNode<MockElement> = StrobeNode::default();
+        let mut builder = UiBuilder::new(&mut root, Vec2::ZERO);
+
+        let (mut below, mut modal, mut tooltip) = (0, 0, 0);
+        builder.stack(|ui| {
+            ui.layer(UiLayer::TOOLTIP, LayerAnchor::At(Vec2::ZERO), |ui| {
+                tooltip = ui.add(MockElement::new(20.0, 20.0, log.clone()));
+            });
+            ui.layer(UiLayer::MODAL, LayerAnchor::At(Vec2::ZERO), |ui| {
+                modal = ui.add(MockElement::new(50.0, 50.0, log.clone()));
+            });
+            below = ui.add(MockElement::new(100.0, 100.0, log.clone()));
+        });
+
+        let mut ctx = HitRectContext(Vec::new());
+        let rect = Rect::new(Vec2::ZERO, Vec2::new(100.0, 100.0));
+        root.render_root(&mut ctx, rect);
+
+        let mut input = StrobeInputState::default();
+        input.update_hit_rects(ctx.0);
+
+        let hovered = |input: &StrobeInputState| {
+            [below, modal, tooltip]
+                .into_iter()
+                .filter(|id| input.interaction(*id).hovered)
+                .collect::<Vec<_>>()
+        };
+
+        input.begin_frame(Vec2::new(10.0, 10.0), false, false, false);
+        assert_eq!(hovered(&input), [tooltip]);
+
+        input.begin_frame(Vec2::new(40.0, 40.0), false, false, false);
+        assert_eq!(hovered(&input), [modal]);
+
+        input.begin_frame(Vec2::new(80.0, 80.0), false, false, false);
+        assert_eq!(hovered(&input), [below]);
+    }
 }
@@ -3,18 +3,22 @@ pub mod image;
 pub mod input;
 pub mod layout;
 pub mod line;
+pub mod rect;
 pub mod renderer;
 pub mod slider;
 pub mod spacing;
 pub mod style;
 pub mod text;
 pub mod ui_element;
+pub mod widgets;
 
 pub use frame::*;
 pub use image::*;
 pub use layout::*;
 pub use line::*;
+pub use rect::*;
 pub use renderer::*;
 pub use slider::*;
 pub use spacing::*;
 pub use text::*;
+pub use widgets::*;
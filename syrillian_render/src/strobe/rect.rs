@@ -0,0 +1,108 @@
+use crate::ObjectHash;
+use crate::rendering::RenderPassType;
+use crate::rendering::picking::hash_to_rgba;
+use crate::strobe::UiDrawContext;
+use crate::strobe::ui_element::{Rect, UiElement};
+use glamx::{Vec2, Vec4};
+use syrillian_asset::HShader;
+use syrillian_asset::shader::immediates::UiRectImmediate;
+use zerocopy::IntoBytes;
+
+#[derive(Debug, Clone)]
+pub struct UiRect {
+    pub draw_order: u32,
+    pub size: Vec2,
+    pub color: Vec4,
+    pub border_color: Vec4,
+    pub border_thickness: f32,
+    pub corner_radius: f32,
+    pub object_hash: ObjectHash,
+}
+
+impl Default for UiRect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UiRect {
+    /// Creates a rectangle that fills whatever space the layout gives it.
+    pub fn new() -> Self {
+        Self {
+            draw_order: 0,
+            size: Vec2::ZERO,
+            color: Vec4::ONE,
+            border_color: Vec4::ZERO,
+            border_thickness: 0.0,
+            corner_radius: 0.0,
+            object_hash: ObjectHash::default(),
+        }
+    }
+
+    pub fn size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn border(mut self, color: Vec4, thickness: f32) -> Self {
+        self.border_color = color;
+        self.border_thickness = thickness.max(0.0);
+        self
+    }
+
+    pub fn corner_radius(mut self, px: f32) -> Self {
+        self.corner_radius = px.max(0.0);
+        self
+    }
+
+    pub fn click_listener(mut self, hash: ObjectHash) -> Self {
+        self.object_hash = hash;
+        self
+    }
+}
+
+impl UiElement for UiRect {
+    fn draw_order(&self) -> u32 {
+        self.draw_order
+    }
+
+    fn render(&self, ctx: &mut UiDrawContext, rect: Rect) {
+        if rect.size.x <= 0.0 || rect.size.y <= 0.0 {
+            return;
+        }
+
+        let shader = ctx.cache().shader(HShader::RECT_2D);
+
+        let mut pc = UiRectImmediate {
+            position: rect.position,
+            size: rect.size,
+            color: self.color.to_array(),
+            border_color: self.border_color.to_array(),
+            corner_radius: self.corner_radius,
+            border_thickness: self.border_thickness,
+            padding: [0.0; 2],
+        };
+
+        if ctx.gpu_ctx().pass_type == RenderPassType::PickingUi {
+            let color = hash_to_rgba(self.object_hash);
+            pc.color = color;
+            pc.border_color = color;
+        }
+
+        let mut pass = ctx.gpu_ctx().pass.write();
+
+        shader.activate_ui(&mut pass, ctx);
+
+        pass.set_immediates(0, pc.as_bytes());
+        pass.draw(0..6, 0..1);
+    }
+
+    fn measure(&self, _ctx: &mut UiDrawContext) -> Vec2 {
+        self.size
+    }
+}
@@ -9,13 +9,14 @@ use crate::rendering::uniform::ShaderUniform;
 use crate::rendering::viewport::{RenderViewport, ViewportId};
 use crate::strobe::input::HitRect;
 use crate::strobe::ui_element::Rect;
-use crate::strobe::{CacheId, ContextWithId, LayoutElement, StrobeRoot};
+use crate::strobe::{CacheId, ContextWithId, StrobeRoot};
 use crossbeam_channel::Sender;
 use delegate::delegate;
 use glamx::{Mat4, Vec2};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::mem;
+use std::slice;
 use web_time::Instant;
 use wgpu::{BindGroup, BufferDescriptor, BufferUsages, RenderPass};
 use winit::dpi::PhysicalSize;
@@ -99,7 +100,7 @@ impl<'a, 'b, 'c, 'd, 'e> UiDrawContext<'a, 'b, 'c, 'd, 'e> {
             let model_bgl = self.cache.bgl_model();
             let model = ModelUniform::empty();
             let uniform = ShaderUniform::<MeshUniformIndex>::builder(model_bgl)
-                .with_buffer_data(&model)
+                .with_storage_buffer_data(slice::from_ref(&model))
                 .build(&self.state.device);
 
             let glyph_vbo = self.state.device.create_buffer(&BufferDescriptor {
@@ -125,7 +126,7 @@ impl<'a, 'b, 'c, 'd, 'e> UiDrawContext<'a, 'b, 'c, 'd, 'e> {
             let mesh_data = ModelUniform::from_matrix(model_mat);
 
             let uniform = ShaderUniform::<MeshUniformIndex>::builder(model_bgl)
-                .with_buffer_data(&mesh_data)
+                .with_storage_buffer_data(slice::from_ref(&mesh_data))
                 .build(&self.state.device);
 
             RenderMeshData::new(mesh_data, uniform)
@@ -192,7 +193,7 @@ impl StrobeRenderer {
             for root in roots {
                 current_context.cache_id = root.cache_id;
                 current_context.render_id = root.root.id;
-                root.root.render_layout(&mut current_context, full_rect);
+                root.root.render_root(&mut current_context, full_rect);
             }
         }
 
@@ -1,4 +1,5 @@
 use crate::strobe::ui_element::Padding;
+use glamx::{Vec3, Vec4};
 
 #[derive(Debug, Clone, Copy, Default)]
 pub enum Size {
@@ -23,4 +24,46 @@ pub struct Style {
     pub width: Size,
     pub height: Size,
     pub align: Align,
+    /// Excludes the node from hit testing, so input goes to whatever is below it.
+    pub passthrough: bool,
+}
+
+/// Colors and metrics used by the built-in widgets.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub font_size: f32,
+    pub text_color: Vec3,
+    pub widget_fill: Vec4,
+    pub widget_hovered: Vec4,
+    pub widget_pressed: Vec4,
+    pub accent: Vec4,
+    pub border: Vec4,
+    pub panel_fill: Vec4,
+    pub backdrop: Vec4,
+    pub corner_radius: f32,
+    pub check_size: f32,
+    pub spacing: f32,
+    pub widget_padding: Padding,
+    pub panel_padding: Padding,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            font_size: 12.0,
+            text_color: Vec3::new(0.94, 0.94, 0.94),
+            widget_fill: Vec4::new(0.18, 0.20, 0.24, 1.0),
+            widget_hovered: Vec4::new(0.25, 0.28, 0.33, 1.0),
+            widget_pressed: Vec4::new(0.12, 0.13, 0.16, 1.0),
+            accent: Vec4::new(0.31, 0.66, 1.0, 1.0),
+            border: Vec4::new(0.35, 0.38, 0.44, 1.0),
+            panel_fill: Vec4::new(0.10, 0.11, 0.13, 0.96),
+            backdrop: Vec4::new(0.0, 0.0, 0.0, 0.5),
+            corner_radius: 4.0,
+            check_size: 14.0,
+            spacing: 6.0,
+            widget_padding: Padding::new(5.0, 5.0, 8.0, 8.0),
+            panel_padding: Padding::all(8.0),
+        }
+    }
 }
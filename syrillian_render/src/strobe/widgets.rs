@@ -0,0 +1,419 @@
+use crate::strobe::input::UiInteraction;
+use crate::strobe::layout::{LayerAnchor, UiBuilder, UiLayer};
+use crate::strobe::style::{Align, Style, Theme};
+use crate::strobe::ui_element::{Padding, UiElement};
+use crate::strobe::{UiRect, UiText};
+use glamx::{Vec2, Vec4, vec2};
+use std::mem;
+
+type Ui<'a> = UiBuilder<'a, Box<dyn UiElement>>;
+
+/// Persistent state of a [`UiBuilder::dropdown`], owned by the caller.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DropdownState {
+    pub selected: usize,
+    pub open: bool,
+}
+
+impl DropdownState {
+    pub fn new(selected: usize) -> Self {
+        Self {
+            selected,
+            open: false,
+        }
+    }
+}
+
+impl Theme {
+    pub fn widget_fill_for(&self, interaction: UiInteraction) -> Vec4 {
+        if interaction.pressed {
+            self.widget_pressed
+        } else if interaction.hovered {
+            self.widget_hovered
+        } else {
+            self.widget_fill
+        }
+    }
+}
+
+impl Ui<'_> {
+    /// Adds a text element using the theme's font size and color.
+    pub fn label(&mut self, text: impl Into<String>) -> u32 {
+        let text = UiText::new(text)
+            .font_size(self.theme.font_size)
+            .color(self.theme.text_color);
+        self.add(text.into())
+    }
+
+    pub fn button(&mut self, label: impl Into<String>) -> UiInteraction {
+        let id = self.next_id();
+        let interaction = self.interaction(id);
+        let fill = self.theme.widget_fill_for(interaction);
+
+        self.framed(fill, |ui| {
+            ui.label(label);
+        });
+
+        interaction
+    }
+
+    /// Draws a checkbox with a label and flips `checked` when clicked.
+    /// Returns true if the value changed this frame.
+    pub fn checkbox(&mut self, checked: &mut bool, label: impl Into<String>) -> bool {
+        let id = self.next_id();
+        let interaction = self.interaction(id);
+        if interaction.clicked {
+            *checked = !*checked;
+        }
+
+        let radius = self.theme.corner_radius;
+        self.check_row(*checked, interaction, radius, label);
+
+        interaction.clicked
+    }
+
+    /// Draws one radio button per option. Returns true if the selection changed this frame.
+    pub fn radio_group(&mut self, selected: &mut usize, options: &[&str]) -> bool {
+        let mut changed = false;
+
+        self.vertical(|ui| {
+            for (i, option) in options.iter().enumerate() {
+                let id = ui.next_id();
+                let interaction = ui.interaction(id);
+                if interaction.clicked && *selected != i {
+                    *selected = i;
+                    changed = true;
+                }
+
+                let radius = ui.theme.check_size * 0.5;
+                ui.check_row(*selected == i, interaction, radius, *option);
+            }
+        });
+
+        changed
+    }
+
+    /// Draws a combobox showing the selected option, with a popup listing all options while open.
+    /// Returns true if the selection changed this frame.
+    pub fn dropdown(&mut self, state: &mut DropdownState, options: &[&str]) -> bool {
+        let header_id = self.next_id();
+        let header = self.interaction(header_id);
+        if header.clicked {
+            state.open = !state.open;
+        }
+
+        let fill = self.theme.widget_fill_for(header);
+        let current = options.get(state.selected).copied().unwrap_or_default();
+
+        self.framed(fill, |ui| {
+            let spacing = ui.theme.spacing * 2.0;
+            ui.label(current);
+            ui.spacing(vec2(spacing, 0.0));
+            ui.label("v");
+        });
+
+        if !state.open {
+            return false;
+        }
+
+        let header_rect = self.previous_rect(header_id);
+        let anchor = match header_rect {
+            Some(rect) => LayerAnchor::At(vec2(rect.position.x, rect.max().y)),
+            None => LayerAnchor::At(self.mouse_position()),
+        };
+        let min_width = header_rect.map_or(0.0, |rect| rect.size.x);
+
+        let popup_id = self.next_id();
+        let previous = mem::take(&mut self.style);
+
+        let mut changed = false;
+        self.panel_layer(UiLayer::POPUP, anchor, |ui| {
+            let padding = ui.theme.panel_padding;
+            let width = (min_width - padding.left - padding.right).max(0.0);
+            ui.spacing(vec2(width, 0.0));
+
+            for (i, option) in options.iter().enumerate() {
+                let id = ui.next_id();
+                let interaction = ui.interaction(id);
+                if interaction.clicked {
+                    changed = state.selected != i;
+                    state.selected = i;
+                    state.open = false;
+                }
+
+                let fill = if interaction.hovered || state.selected == i {
+                    ui.theme.widget_hovered
+                } else {
+                    Vec4::ZERO
+                };
+
+                ui.style.align = Align::Stretch;
+                ui.framed(fill, |ui| {
+                    ui.label(*option);
+                });
+            }
+        });
+
+        self.style = previous;
+
+        let mouse = self.mouse_position();
+        let inside_popup = self
+            .previous_rect(popup_id)
+            .is_some_and(|rect| rect.contains(mouse));
+        if self.pointer_released() && !header.clicked && !inside_popup {
+            state.open = false;
+        }
+
+        changed
+    }
+
+    /// Draws a list entry that is highlighted while `selected`.
+    pub fn selectable(&mut self, label: impl Into<String>, selected: bool) -> UiInteraction {
+        let id = self.next_id();
+        let interaction = self.interaction(id);
+        let fill = if selected {
+            self.theme.widget_pressed
+        } else if interaction.hovered {
+            self.theme.widget_hovered
+        } else {
+            Vec4::ZERO
+        };
+
+        self.framed(fill, |ui| {
+            ui.label(label);
+        });
+
+        interaction
+    }
+
+    /// Draws a number that changes by `speed` per pixel while dragged horizontally.
+    /// Returns true if the value changed this frame.
+    pub fn drag_value(&mut self, value: &mut f32, speed: f32) -> bool {
+        let id = self.next_id();
+        let interaction = self.interaction(id);
+
+        let delta = interaction.drag_delta.x * speed;
+        let changed = interaction.pressed && delta != 0.0;
+        if changed {
+            *value += delta;
+        }
+
+        let fill = self.theme.widget_fill_for(interaction);
+        let text = format!("{:.3}", *value);
+
+        self.framed(fill, |ui| {
+            ui.label(text);
+        });
+
+        changed
+    }
+
+    /// Draws a single line text box. Clicking it sets `focused`, releasing the pointer anywhere
+    /// else clears it. While focused, typed text is appended to `text`.
+    /// Returns true if the text changed this frame.
+    pub fn text_field(&mut self, text: &mut String, focused: &mut bool) -> bool {
+        let id = self.next_id();
+        let interaction = self.interaction(id);
+
+        if interaction.clicked {
+            *focused = true;
+        } else if self.pointer_released() {
+            *focused = false;
+        }
+
+        let mut changed = false;
+        if *focused {
+            for ch in self.text_input().chars() {
+                match ch {
+                    '\u{8}' => changed |= text.pop().is_some(),
+                    ch if ch.is_control() => {}
+                    ch => {
+                        text.push(ch);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        let fill = if *focused {
+            self.theme.widget_pressed
+        } else {
+            self.theme.widget_fill_for(interaction)
+        };
+        let shown = if *focused {
+            format!("{text}|")
+        } else {
+            text.clone()
+        };
+
+        self.framed(fill, |ui| {
+            ui.label(shown);
+        });
+
+        changed
+    }
+
+    /// Wraps `content` in a padded background panel.
+    pub fn panel(&mut self, content: impl FnOnce(&mut Ui)) {
+        let theme = self.theme;
+
+        self.stack(|ui| {
+            ui.add(
+                UiRect::new()
+                    .color(theme.panel_fill)
+                    .border(theme.border, 1.0)
+                    .corner_radius(theme.corner_radius)
+                    .into(),
+            );
+
+            ui.style.padding = theme.panel_padding;
+            ui.vertical(content);
+        });
+    }
+
+    /// Draws a bar of the given size, filled to `progress` (0 to 1). Returns the node id.
+    pub fn progress_bar(&mut self, progress: f32, size: Vec2) -> u32 {
+        let id = self.next_id();
+        let progress = progress.clamp(0.0, 1.0);
+        let theme = self.theme;
+
+        self.stack(|ui| {
+            ui.style.passthrough = true;
+            ui.add(
+                UiRect::new()
+                    .size(size)
+                    .color(theme.widget_fill)
+                    .corner_radius(theme.corner_radius)
+                    .into(),
+            );
+
+            ui.style.padding = Padding::right(size.x * (1.0 - progress));
+            ui.add(
+                UiRect::new()
+                    .color(theme.accent)
+                    .corner_radius(theme.corner_radius)
+                    .into(),
+            );
+        });
+
+        id
+    }
+
+    /// Shows a text popup next to the pointer while the node `target` is hovered.
+    pub fn tooltip(&mut self, target: u32, text: impl Into<String>) {
+        if !self.is_hovered(target) {
+            return;
+        }
+
+        let anchor = LayerAnchor::At(self.mouse_position() + vec2(14.0, 18.0));
+        let previous = mem::replace(
+            &mut self.style,
+            Style {
+                passthrough: true,
+                ..Style::default()
+            },
+        );
+
+        self.panel_layer(UiLayer::TOOLTIP, anchor, |ui| {
+            ui.label(text);
+        });
+
+        self.style = previous;
+    }
+
+    /// Shows a centered dialog above a backdrop that blocks input to everything below it.
+    /// Clicking the backdrop closes the dialog, and so can the content through its `&mut bool`.
+    pub fn modal(&mut self, open: &mut bool, content: impl FnOnce(&mut Ui, &mut bool)) {
+        if !*open {
+            return;
+        }
+
+        let previous = mem::take(&mut self.style);
+
+        let backdrop_id = self.next_id();
+        let backdrop = self.theme.backdrop;
+        self.layer(UiLayer::MODAL, LayerAnchor::Fill, |ui| {
+            ui.style.passthrough = true;
+            ui.add(UiRect::new().color(backdrop).into());
+        });
+
+        self.panel_layer(UiLayer::MODAL, LayerAnchor::Center, |ui| content(ui, open));
+
+        self.style = previous;
+
+        if self.interaction(backdrop_id).clicked {
+            *open = false;
+        }
+    }
+
+    /// A stack with a rounded background that takes part in hit testing as a whole.
+    fn framed(&mut self, fill: Vec4, content: impl FnOnce(&mut Ui)) {
+        let theme = self.theme;
+
+        self.stack(|ui| {
+            ui.style.passthrough = true;
+            ui.add(
+                UiRect::new()
+                    .color(fill)
+                    .border(theme.border, 1.0)
+                    .corner_radius(theme.corner_radius)
+                    .into(),
+            );
+
+            ui.style.padding = theme.widget_padding;
+            ui.horizontal(content);
+        });
+    }
+
+    fn check_row(
+        &mut self,
+        checked: bool,
+        interaction: UiInteraction,
+        radius: f32,
+        label: impl Into<String>,
+    ) {
+        let theme = self.theme;
+        let fill = theme.widget_fill_for(interaction);
+        // keep the mark node around while unchecked so ids after it stay stable
+        let mark = if checked { theme.accent } else { Vec4::ZERO };
+
+        self.horizontal(|ui| {
+            ui.style.passthrough = true;
+            ui.style.align = Align::Center;
+
+            ui.stack(|ui| {
+                ui.add(
+                    UiRect::new()
+                        .size(Vec2::splat(theme.check_size))
+                        .color(fill)
+                        .border(theme.border, 1.0)
+                        .corner_radius(radius)
+                        .into(),
+                );
+
+                ui.style.padding = Padding::all(theme.check_size * 0.25);
+                ui.add(UiRect::new().color(mark).corner_radius(radius * 0.5).into());
+            });
+
+            ui.spacing(vec2(theme.spacing, 0.0));
+            ui.label(label);
+        });
+    }
+
+    fn panel_layer(&mut self, z: u32, anchor: LayerAnchor, content: impl FnOnce(&mut Ui)) {
+        let theme = self.theme;
+
+        self.layer(z, anchor, |ui| {
+            ui.add(
+                UiRect::new()
+                    .color(theme.panel_fill)
+                    .border(theme.border, 1.0)
+                    .corner_radius(theme.corner_radius)
+                    .into(),
+            );
+
+            ui.style.padding = theme.panel_padding;
+            ui.vertical(content);
+        });
+    }
+}
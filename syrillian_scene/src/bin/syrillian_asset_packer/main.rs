@@ -2,12 +2,14 @@ mod human_format;
 mod writer;
 
 use crate::human_format::{format_cook_time, format_size};
-use crate::writer::StreamingAssetFileWriter;
-use clap::{ArgAction, Parser, Subcommand, ValueHint};
+use crate::writer::{MeshCookStats, PackOptions, StreamingAssetFileWriter};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum, ValueHint};
 use serde_json::Value as JsonValue;
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use syrillian_asset::mesh::LodSettings;
 use syrillian_asset::store::streaming::asset_store::StreamingAssetFile;
+use syrillian_asset::{TextureCompression, TextureCookSettings};
 
 #[derive(Debug, Parser)]
 #[command(
@@ -30,6 +32,22 @@ enum Command {
         input: PathBuf,
         #[arg(short, long, value_name = "OUTPUT", value_hint = ValueHint::FilePath)]
         output: PathBuf,
+        /// Most simplified levels of detail generated per mesh, 0 to skip simplification
+        #[arg(long, value_name = "LEVELS", default_value_t = LodSettings::default().max_levels)]
+        lod_levels: usize,
+        /// Welds duplicate vertices and reorders static meshes for the vertex cache, overdraw
+        /// and vertex fetch. Without it, meshes are packed as imported
+        #[arg(long, action = ArgAction::SetTrue)]
+        optimize: bool,
+        /// Stores static mesh normals and uvs in compact 16 bit formats
+        #[arg(long, action = ArgAction::SetTrue)]
+        quantize: bool,
+        /// Block compression textures are encoded with
+        #[arg(long, value_enum, value_name = "FORMAT", default_value_t = CompressionArg::Bc)]
+        texture_compression: CompressionArg,
+        /// Packs textures with their base level only
+        #[arg(long, action = ArgAction::SetTrue)]
+        no_mips: bool,
     },
     Ls {
         #[arg(value_name = "PACKAGE", value_hint = ValueHint::FilePath)]
@@ -43,11 +61,56 @@ enum Command {
     },
 }
 
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum CompressionArg {
+    /// Uncompressed RGBA8
+    None,
+    /// BC1/BC3/BC5/BC7, for desktop GPUs
+    Bc,
+    /// ETC2/EAC, for mobile GPUs
+    Etc2,
+    /// ASTC 4x4, for mobile GPUs
+    Astc,
+}
+
+impl From<CompressionArg> for TextureCompression {
+    fn from(value: CompressionArg) -> Self {
+        match value {
+            CompressionArg::None => TextureCompression::None,
+            CompressionArg::Bc => TextureCompression::Bc,
+            CompressionArg::Etc2 => TextureCompression::Etc2,
+            CompressionArg::Astc => TextureCompression::Astc,
+        }
+    }
+}
+
 fn main() {
     let args = Cli::parse();
 
     match args.command {
-        Command::Package { input, output } => package_command(input, output, args.verbose),
+        Command::Package {
+            input,
+            output,
+            lod_levels,
+            optimize,
+            quantize,
+            texture_compression,
+            no_mips,
+        } => {
+            let options = PackOptions {
+                lods: (lod_levels > 0).then(|| LodSettings {
+                    max_levels: lod_levels,
+                    ..LodSettings::default()
+                }),
+                optimize_meshes: optimize,
+                quantize_meshes: quantize,
+                textures: TextureCookSettings {
+                    compression: texture_compression.into(),
+                    generate_mips: !no_mips,
+                },
+            };
+            package_command(input, output, &options, args.verbose)
+        }
         Command::Ls { package } => ls_command(package),
         Command::View {
             package,
@@ -56,7 +119,7 @@ fn main() {
     }
 }
 
-fn package_command(input: PathBuf, output: PathBuf, verbose: bool) {
+fn package_command(input: PathBuf, output: PathBuf, options: &PackOptions, verbose: bool) {
     if !input.is_dir() {
         eprintln!("Input path is not a directory: {}", input.display());
         exit(2);
@@ -65,15 +128,23 @@ fn package_command(input: PathBuf, output: PathBuf, verbose: bool) {
     let output_path = with_extension(&output);
 
     let result = if verbose {
-        StreamingAssetFile::pack_folder_with_progress(&input, &output, |asset_type, path, cook| {
-            println!(
-                "Packaging {:<18} {:>10} {path}",
-                asset_type.name(),
-                format_cook_time(cook)
-            );
-        })
+        StreamingAssetFile::pack_folder_with_progress(
+            &input,
+            &output,
+            options,
+            |asset_type, path, cook, mesh_stats| {
+                println!(
+                    "Packaging {:<18} {:>10} {path}",
+                    asset_type.name(),
+                    format_cook_time(cook)
+                );
+                if let Some(stats) = mesh_stats {
+                    print_mesh_stats(stats);
+                }
+            },
+        )
     } else {
-        StreamingAssetFile::pack_folder(&input, &output)
+        StreamingAssetFile::pack_folder(&input, &output, options)
     };
 
     if let Err(err) = result {
@@ -84,6 +155,29 @@ fn package_command(input: PathBuf, output: PathBuf, verbose: bool) {
     println!("Generated package: {}", output_path.display());
 }
 
+fn print_mesh_stats(stats: &MeshCookStats) {
+    let MeshCookStats {
+        before,
+        after,
+        size_before,
+        size_after,
+    } = stats;
+
+    println!(
+        "    vertices {} -> {}, triangles {} -> {}, ACMR {:.3} -> {:.3}, ATVR {:.3} -> {:.3}, size {} -> {}",
+        before.vertices,
+        after.vertices,
+        before.triangles,
+        after.triangles,
+        before.acmr,
+        after.acmr,
+        before.atvr,
+        after.atvr,
+        format_size(*size_before),
+        format_size(*size_after),
+    );
+}
+
 fn ls_command(package: PathBuf) {
     let package_path = with_extension(&package);
     let package_file = match StreamingAssetFile::load(&package_path) {
@@ -270,3 +364,27 @@ fn scalar_to_string(value: &JsonValue) -> String {
         _ => value.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package_optimize(args: &[&str]) -> bool {
+        let base = ["sypack", "package", "-i", "assets", "-o", "assets.sya"];
+        let cli = Cli::try_parse_from(base.iter().chain(args)).unwrap();
+        let Command::Package { optimize, .. } = cli.command else {
+            panic!("expected the package command");
+        };
+        optimize
+    }
+
+    #[test]
+    fn meshes_are_only_optimized_on_request() {
+        assert!(!package_optimize(&[]));
+        assert!(package_optimize(&["--optimize"]));
+        assert!(
+            Cli::try_parse_from(["sypack", "package", "-i", "a", "-o", "b", "--no-optimize"])
+                .is_err()
+        );
+    }
+}
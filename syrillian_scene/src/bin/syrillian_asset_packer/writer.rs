@@ -4,45 +4,103 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 use std::{fs, io};
+use syrillian_asset::mesh::{LodSettings, MeshStats};
 use syrillian_asset::store::streaming::AssetStreamingError;
 use syrillian_asset::store::streaming::asset_store::{
     AssetType, MAGIC_SIGNATURE, STREAMING_ASSET_VERSION, StreamingAssetBlobIndexEntryRaw,
     StreamingAssetFile, StreamingAssetHeader, StreamingAssetIndexEntryRaw, hash_relative_path,
 };
+use syrillian_asset::store::streaming::decode_helper::ParseDecode;
 use syrillian_asset::store::streaming::error::{PathTooLongErr, Result};
 use syrillian_asset::store::streaming::packaged_scene::{BuiltPayload, PackagedScene, PackedAsset};
 use syrillian_asset::store::streaming::payload::StreamableAsset;
-use syrillian_asset::{Cubemap, Mesh, Shader, Texture2D};
+use syrillian_asset::{
+    Cubemap, MaterialGraphAsset, Mesh, Shader, Texture2D, TextureCookSettings, TextureUsage,
+};
 use syrillian_scene::GltfLoader;
 use zerocopy::IntoBytes;
 use zerocopy::native_endian::{I32, U32, U64};
 
+/// Cook steps applied to the source assets while packaging
+#[derive(Debug, Clone)]
+pub struct PackOptions {
+    /// Generates simplified levels of detail for every static mesh
+    pub lods: Option<LodSettings>,
+    /// Welds duplicate vertices and reorders static meshes for the vertex cache, overdraw
+    /// and vertex fetch
+    pub optimize_meshes: bool,
+    /// Stores static mesh normals and uvs in compact 16 bit formats
+    pub quantize_meshes: bool,
+    /// Mip generation and block compression for textures
+    pub textures: TextureCookSettings,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        Self {
+            lods: Some(LodSettings::default()),
+            optimize_meshes: false,
+            quantize_meshes: false,
+            textures: TextureCookSettings::default(),
+        }
+    }
+}
+
+/// A static mesh before and after it was cooked
+#[derive(Debug, Copy, Clone)]
+pub struct MeshCookStats {
+    pub before: MeshStats,
+    pub after: MeshStats,
+    /// Bytes of vertex and index data as imported
+    pub size_before: u64,
+    /// Bytes of vertex and index data written to the package, including levels of detail
+    pub size_after: u64,
+}
+
+/// Receives every packaged asset with its cook time, and the mesh stats for static meshes
+pub type PackProgress<'a> = dyn FnMut(AssetType, &str, Duration, Option<&MeshCookStats>) + 'a;
+
 pub trait StreamingAssetFileWriter {
-    fn pack_folder<P: AsRef<Path>>(folder_path: P, out_file_path: P) -> Result<()>;
+    fn pack_folder<P: AsRef<Path>>(
+        folder_path: P,
+        out_file_path: P,
+        options: &PackOptions,
+    ) -> Result<()>;
 
     fn pack_folder_with_progress<P, F>(
         folder_path: P,
         out_file_path: P,
+        options: &PackOptions,
         on_asset_packaged: F,
     ) -> Result<()>
     where
         P: AsRef<Path>,
-        F: FnMut(AssetType, &str, Duration);
+        F: FnMut(AssetType, &str, Duration, Option<&MeshCookStats>);
 }
 
 impl StreamingAssetFileWriter for StreamingAssetFile {
-    fn pack_folder<P: AsRef<Path>>(folder_path: P, out_file_path: P) -> Result<()> {
-        Self::pack_folder_with_progress(folder_path, out_file_path, |_asset_type, _path, _time| {})
+    fn pack_folder<P: AsRef<Path>>(
+        folder_path: P,
+        out_file_path: P,
+        options: &PackOptions,
+    ) -> Result<()> {
+        Self::pack_folder_with_progress(
+            folder_path,
+            out_file_path,
+            options,
+            |_asset_type, _path, _time, _stats| {},
+        )
     }
 
     fn pack_folder_with_progress<P, F>(
         folder_path: P,
         out_file_path: P,
+        options: &PackOptions,
         mut on_asset_packaged: F,
     ) -> Result<()>
     where
         P: AsRef<Path>,
-        F: FnMut(AssetType, &str, Duration),
+        F: FnMut(AssetType, &str, Duration, Option<&MeshCookStats>),
     {
         let folder_path = folder_path.as_ref();
         let out_path = with_sya_extension(out_file_path.as_ref());
@@ -57,6 +115,7 @@ impl StreamingAssetFileWriter for StreamingAssetFile {
         collect_assets(
             folder_path,
             folder_path,
+            options,
             &mut assets,
             &mut on_asset_packaged,
         )?;
@@ -166,8 +225,9 @@ impl StreamingAssetFileWriter for StreamingAssetFile {
 pub fn collect_assets(
     root: &Path,
     current: &Path,
+    options: &PackOptions,
     out: &mut Vec<PackedAsset>,
-    on_asset_packaged: &mut dyn FnMut(AssetType, &str, Duration),
+    on_asset_packaged: &mut PackProgress,
 ) -> Result<()> {
     for entry in fs::read_dir(current)? {
         let entry = entry?;
@@ -175,7 +235,7 @@ pub fn collect_assets(
         let path = entry.path();
 
         if file_type.is_dir() {
-            collect_assets(root, &path, out, on_asset_packaged)?;
+            collect_assets(root, &path, options, out, on_asset_packaged)?;
             continue;
         }
 
@@ -203,14 +263,19 @@ pub fn collect_assets(
             let virtual_root = normalize_relative_path(relative);
 
             let extract_start = Instant::now();
-            let packaged_scene = GltfLoader::extract_packaged_scene_from_path(&path, &virtual_root)
-                .map_err(|source| AssetStreamingError::AssetParse {
-                    path: path.display().to_string(),
-                    reason: source.to_string(),
-                })?;
+            let packaged_scene = GltfLoader::extract_packaged_scene_from_path(
+                &path,
+                &virtual_root,
+                &options.textures,
+            )
+            .map_err(|source| AssetStreamingError::AssetParse {
+                path: path.display().to_string(),
+                reason: source.to_string(),
+            })?;
 
             append_packaged_scene_assets(
                 packaged_scene,
+                options,
                 out,
                 on_asset_packaged,
                 extract_start.elapsed(),
@@ -235,14 +300,19 @@ pub fn collect_assets(
 
         let relative_path = normalize_relative_path(relative);
         let cook_start = Instant::now();
-        let built = build_packaged_payload(&path, asset_type)?;
+        let (built, mesh_stats) = build_packaged_payload(&path, asset_type, options)?;
         out.push(PackedAsset {
             asset_type,
             relative_path: relative_path.clone(),
             payload: built.payload.into_bytes(),
             blobs: built.blobs,
         });
-        on_asset_packaged(asset_type, &relative_path, cook_start.elapsed());
+        on_asset_packaged(
+            asset_type,
+            &relative_path,
+            cook_start.elapsed(),
+            mesh_stats.as_ref(),
+        );
     }
 
     Ok(())
@@ -250,8 +320,9 @@ pub fn collect_assets(
 
 fn append_packaged_scene_assets(
     scene: PackagedScene,
+    options: &PackOptions,
     out: &mut Vec<PackedAsset>,
-    on_asset_packaged: &mut dyn FnMut(AssetType, &str, Duration),
+    on_asset_packaged: &mut PackProgress,
     extract_duration: Duration,
 ) {
     let asset_count = scene.meshes.len()
@@ -262,9 +333,9 @@ fn append_packaged_scene_assets(
         + 1;
     let shared_extract = duration_per_asset(extract_duration, asset_count);
 
-    for mesh_asset in scene.meshes {
+    for mut mesh_asset in scene.meshes {
         let cook_start = Instant::now();
-        let built = mesh_asset.asset.encode();
+        let (built, stats) = cook_mesh(&mut mesh_asset.asset, options);
         out.push(PackedAsset {
             asset_type: AssetType::Mesh,
             relative_path: mesh_asset.virtual_path.clone(),
@@ -275,6 +346,7 @@ fn append_packaged_scene_assets(
             AssetType::Mesh,
             &mesh_asset.virtual_path,
             shared_extract.saturating_add(cook_start.elapsed()),
+            Some(&stats),
         );
     }
 
@@ -291,6 +363,7 @@ fn append_packaged_scene_assets(
             AssetType::SkinnedMesh,
             &skinned_mesh_asset.virtual_path,
             shared_extract.saturating_add(cook_start.elapsed()),
+            None,
         );
     }
 
@@ -307,6 +380,7 @@ fn append_packaged_scene_assets(
             AssetType::Texture2D,
             &texture_asset.virtual_path,
             shared_extract.saturating_add(cook_start.elapsed()),
+            None,
         );
     }
 
@@ -323,6 +397,7 @@ fn append_packaged_scene_assets(
             AssetType::Material,
             &material_asset.virtual_path,
             shared_extract.saturating_add(cook_start.elapsed()),
+            None,
         );
     }
 
@@ -339,6 +414,7 @@ fn append_packaged_scene_assets(
             AssetType::AnimationClip,
             &animation_asset.virtual_path,
             shared_extract.saturating_add(cook_start.elapsed()),
+            None,
         );
     }
 
@@ -354,6 +430,7 @@ fn append_packaged_scene_assets(
         AssetType::Prefab,
         &scene.prefab.virtual_path,
         shared_extract.saturating_add(cook_start.elapsed()),
+        None,
     );
 }
 
@@ -373,7 +450,49 @@ fn normalize_relative_path(path: &Path) -> String {
         .join("/")
 }
 
-fn build_packaged_payload(path: &Path, asset_type: AssetType) -> Result<BuiltPayload> {
+fn cook_mesh(mesh: &mut Mesh, options: &PackOptions) -> (BuiltPayload, MeshCookStats) {
+    let before = mesh.stats();
+    let size_before = blob_size(&mesh.encode());
+
+    if options.optimize_meshes {
+        mesh.weld_vertices();
+    }
+
+    if let Some(lods) = &options.lods {
+        mesh.generate_lods(lods);
+    }
+
+    if options.optimize_meshes {
+        mesh.optimize_vertex_cache();
+        mesh.optimize_overdraw();
+        mesh.optimize_vertex_fetch();
+    }
+
+    let built = if options.quantize_meshes {
+        mesh.encode_quantized()
+    } else {
+        mesh.encode()
+    };
+
+    let stats = MeshCookStats {
+        before,
+        after: mesh.stats(),
+        size_before,
+        size_after: blob_size(&built),
+    };
+
+    (built, stats)
+}
+
+fn blob_size(built: &BuiltPayload) -> u64 {
+    built.blobs.iter().map(|blob| blob.data.len() as u64).sum()
+}
+
+fn build_packaged_payload(
+    path: &Path,
+    asset_type: AssetType,
+    options: &PackOptions,
+) -> Result<(BuiltPayload, Option<MeshCookStats>)> {
     match asset_type {
         AssetType::Mesh => {
             let source = fs::read(path)?;
@@ -382,7 +501,7 @@ fn build_packaged_payload(path: &Path, asset_type: AssetType) -> Result<BuiltPay
                 .and_then(|ext| ext.to_str())
                 .unwrap_or_default()
                 .to_ascii_lowercase();
-            let mesh = match extension.as_str() {
+            let mut mesh = match extension.as_str() {
                 "obj" => Mesh::load_from_obj_slice(&source).map_err(|source| {
                     AssetStreamingError::AssetParse {
                         path: path.display().to_string(),
@@ -397,7 +516,8 @@ fn build_packaged_payload(path: &Path, asset_type: AssetType) -> Result<BuiltPay
                 }
             };
 
-            Ok(mesh.encode())
+            let (built, stats) = cook_mesh(&mut mesh, options);
+            Ok((built, Some(stats)))
         }
         AssetType::Texture2D => {
             let source = fs::read(path)?;
@@ -408,7 +528,8 @@ fn build_packaged_payload(path: &Path, asset_type: AssetType) -> Result<BuiltPay
                 }
             })?;
 
-            Ok(texture.encode())
+            let texture = texture.cook(texture_usage_for_path(path), &options.textures);
+            Ok((texture.encode(), None))
         }
         AssetType::Shader => {
             let source = fs::read_to_string(path)?;
@@ -418,7 +539,7 @@ fn build_packaged_payload(path: &Path, asset_type: AssetType) -> Result<BuiltPay
                 .unwrap_or("Shader")
                 .to_string();
             let shader = Shader::new_default(name, source);
-            Ok(shader.encode())
+            Ok((shader.encode(), None))
         }
         AssetType::Cubemap => {
             let source = fs::read(path)?;
@@ -428,7 +549,23 @@ fn build_packaged_payload(path: &Path, asset_type: AssetType) -> Result<BuiltPay
                     reason: source.to_string(),
                 }
             })?;
-            Ok(cubemap.encode())
+            Ok((cubemap.encode(), None))
+        }
+        AssetType::Material => {
+            let source = fs::read_to_string(path)?;
+            let parse_error = |reason: String| AssetStreamingError::AssetParse {
+                path: path.display().to_string(),
+                reason,
+            };
+            let json: serde_json::Value =
+                serde_json::from_str(&source).map_err(|e| parse_error(e.to_string()))?;
+            let material: MaterialGraphAsset = json
+                .expect_parse("material graph")
+                .map_err(|e| parse_error(e.to_string()))?;
+            material
+                .validate()
+                .map_err(|e| parse_error(e.to_string()))?;
+            Ok((material.encode(), None))
         }
         _ => Err(AssetStreamingError::AssetParse {
             path: path.display().to_string(),
@@ -446,12 +583,100 @@ fn asset_type_for_path(path: &Path) -> Option<AssetType> {
         "obj" => Some(AssetType::Mesh),
         "hdr" | "exr" => Some(AssetType::Cubemap),
         "wgsl" => Some(AssetType::Shader),
+        "symat" => Some(AssetType::Material),
         _ => None,
     }
 }
 
+/// Guesses how a loose image is sampled from common naming conventions in its file name
+fn texture_usage_for_path(path: &Path) -> TextureUsage {
+    const NORMAL_HINTS: &[&str] = &["normal", "_n", "_nrm", "_nor"];
+    const MASK_HINTS: &[&str] = &[
+        "rough",
+        "metal",
+        "_orm",
+        "_ao",
+        "occlusion",
+        "mask",
+        "_spec",
+        "height",
+    ];
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let has_hint = |hints: &[&str]| {
+        hints.iter().any(|hint| {
+            if hint.starts_with('_') {
+                stem.ends_with(hint) || stem.contains(&format!("{hint}_"))
+            } else {
+                stem.contains(hint)
+            }
+        })
+    };
+
+    if has_hint(NORMAL_HINTS) {
+        TextureUsage::Normal
+    } else if has_hint(MASK_HINTS) {
+        TextureUsage::Mask
+    } else {
+        TextureUsage::Color
+    }
+}
+
 fn with_sya_extension(path: &Path) -> PathBuf {
     let mut path = path.to_path_buf();
     path.set_extension("sya");
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use syrillian::math::{Vec3, Vec4};
+    use syrillian_asset::mesh::static_mesh_data::{RawVertexBuffers, VertexBufferExt};
+
+    /// A quad whose two triangles don't share their vertices
+    fn unwelded_quad() -> Mesh {
+        let positions = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+        ];
+        let mut buffers = RawVertexBuffers::from_positions(positions, None);
+        buffers.normals.fill(Vec3::Y);
+        buffers.tangents.fill(Vec4::X);
+
+        Mesh::builder().data(Arc::new(buffers)).build()
+    }
+
+    #[test]
+    fn meshes_are_packed_as_imported_by_default() {
+        let options = PackOptions {
+            lods: None,
+            ..PackOptions::default()
+        };
+        assert!(!options.optimize_meshes);
+
+        let (_, stats) = cook_mesh(&mut unwelded_quad(), &options);
+        assert_eq!(stats.after.vertices, 6);
+    }
+
+    #[test]
+    fn optimizing_welds_meshes() {
+        let options = PackOptions {
+            lods: None,
+            optimize_meshes: true,
+            ..PackOptions::default()
+        };
+
+        let (_, stats) = cook_mesh(&mut unwelded_quad(), &options);
+        assert_eq!(stats.before.vertices, 6);
+        assert_eq!(stats.after.vertices, 4);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::GltfScene;
+use crate::utils::json_to_reflection_value;
 use gltf::animation::util::ReadOutputs;
+use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use syrillian::math::{Quat, Vec3};
-use syrillian_asset::{AnimationChannel, AnimationClip, TransformKeys};
+use syrillian_asset::{AnimationChannel, AnimationClip, AnimationEvent, TransformKeys};
 
 impl GltfScene {
     /// Builds animation clips from the glTF scene
@@ -22,13 +24,17 @@ impl GltfScene {
     /// Converts a glTF animation into an engine animation clip
     pub fn build_animation_clip(&self, anim: gltf::Animation) -> AnimationClip {
         let name = anim.name().unwrap_or("Animation").to_string();
+        let events = read_animation_events(&anim);
         let (channels, duration) = self.collect_animation_channels(anim);
 
-        AnimationClip {
+        let mut clip = AnimationClip {
             name,
             duration,
             channels,
-        }
+            events,
+        };
+        clip.sort_events();
+        clip
     }
 
     /// Collects all channels of a glTF animation
@@ -77,6 +83,39 @@ impl GltfScene {
     }
 }
 
+/// Reads animation events from the animation extras.
+///
+/// Expected layout: `{ "events": [{ "name": "footstep", "time": 0.25, "payload": ... }] }`.
+/// Entries without a name or time are skipped, the payload is optional.
+fn read_animation_events(anim: &gltf::Animation) -> Vec<AnimationEvent> {
+    let Some(extras) = anim.extras().as_ref() else {
+        return Vec::new();
+    };
+    let Ok(JsonValue::Object(mut extras)) = serde_json::from_str::<JsonValue>(extras.get()) else {
+        return Vec::new();
+    };
+    let Some(JsonValue::Array(events)) = extras.remove("events") else {
+        return Vec::new();
+    };
+
+    events
+        .into_iter()
+        .filter_map(|event| {
+            let JsonValue::Object(mut event) = event else {
+                return None;
+            };
+            let name = event.get("name")?.as_str()?.to_string();
+            let time = event.get("time")?.as_f64()? as f32;
+            let payload = event
+                .remove("payload")
+                .map(json_to_reflection_value)
+                .unwrap_or(syrillian::core::reflection::Value::None);
+
+            Some(AnimationEvent::new(name, time).with_payload(payload))
+        })
+        .collect()
+}
+
 /// Builds transform keyframes from glTF animation outputs
 fn build_transform_keys(outputs: ReadOutputs, times: &[f32]) -> Option<TransformKeys> {
     let keys = match outputs {
@@ -104,7 +143,11 @@ fn build_transform_keys(outputs: ReadOutputs, times: &[f32]) -> Option<Transform
                 ..TransformKeys::default()
             }
         }
-        _ => return None,
+        ReadOutputs::MorphTargetWeights(values) => TransformKeys {
+            w_times: times.to_vec(),
+            w_values: values.into_f32().collect(),
+            ..TransformKeys::default()
+        },
     };
 
     Some(keys)
@@ -0,0 +1,825 @@
+use image::{ImageFormat, RgbaImage};
+use serde_json::{Map, Value as JsonValue, json};
+use snafu::{ResultExt, Snafu};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::Path;
+use syrillian::World;
+use syrillian::assets::store::StoreType;
+use syrillian::assets::{
+    AnimationClip, HMaterialInstance, HMesh, HSkinnedMesh, HTexture2D, MaterialInstance, Texture2D,
+};
+use syrillian::core::GameObjectId;
+use syrillian::math::{Quat, Vec2, Vec3, Vec4};
+use syrillian::tracing::warn;
+use syrillian::wgpu::{AddressMode, FilterMode};
+use syrillian_asset::mesh::static_mesh_data::{
+    RawSkinningVertexBuffers, RawVertexBuffers, VertexBufferExt,
+};
+use syrillian_asset::mesh::{Bones, MorphTargets};
+use syrillian_components::{AnimationComponent, MeshRenderer, SkinnedMeshRenderer};
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+pub type Result<T, E = ExportError> = std::result::Result<T, E>;
+
+#[derive(Debug, Snafu)]
+#[snafu(context(suffix(Err)), visibility(pub(crate)))]
+pub enum ExportError {
+    #[snafu(display("object to export does not exist in the world"))]
+    MissingRoot,
+    #[snafu(display("failed to encode texture as PNG: {source}"))]
+    EncodeImage { source: image::ImageError },
+    #[snafu(display("failed to write glTF binary: {source}"))]
+    WriteGlb { source: gltf::Error },
+    #[snafu(display("failed to write exported file: {source}"))]
+    WriteFile { source: std::io::Error },
+}
+
+/// Writes a subtree of the world out as a binary glTF (`.glb`).
+///
+/// Exports node transforms, static and skinned meshes (including morph targets),
+/// material instance PBR values and textures, skins and the clips of every
+/// [`AnimationComponent`] in the subtree. Textures are re-encoded as PNG, block
+/// compressed textures are decompressed first.
+pub struct GltfExporter<'a> {
+    world: &'a World,
+    bin: Vec<u8>,
+    nodes: Vec<ExportNode>,
+    buffer_views: Vec<JsonValue>,
+    accessors: Vec<JsonValue>,
+    meshes: Vec<JsonValue>,
+    materials: Vec<JsonValue>,
+    textures: Vec<JsonValue>,
+    images: Vec<JsonValue>,
+    samplers: Vec<JsonValue>,
+    skins: Vec<JsonValue>,
+    animations: Vec<JsonValue>,
+    extensions_used: BTreeSet<&'static str>,
+    mesh_cache: HashMap<(MeshKey, Vec<HMaterialInstance>), usize>,
+    material_cache: HashMap<HMaterialInstance, Option<usize>>,
+    texture_cache: HashMap<HTexture2D, Option<usize>>,
+    pending_skins: Vec<(usize, Bones)>,
+    clips: Vec<AnimationClip>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum MeshKey {
+    Static(HMesh),
+    Skinned(HSkinnedMesh),
+}
+
+#[derive(Debug, Default)]
+struct ExportNode {
+    name: String,
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    skin: Option<usize>,
+    weights: Vec<f32>,
+}
+
+impl<'a> GltfExporter<'a> {
+    /// Exports `root` and all of its children into an in-memory `.glb`.
+    pub fn export_glb(world: &'a World, root: GameObjectId) -> Result<Vec<u8>> {
+        let mut exporter = Self::new(world);
+        let root_index = exporter
+            .collect_node(root)?
+            .ok_or(ExportError::MissingRoot)?;
+        exporter.resolve_skins();
+        exporter.export_animations();
+        exporter.finish(root_index)
+    }
+
+    /// Exports `root` and all of its children into a `.glb` file at `path`.
+    pub fn write_glb(world: &'a World, root: GameObjectId, path: impl AsRef<Path>) -> Result<()> {
+        let glb = Self::export_glb(world, root)?;
+        std::fs::write(path, glb).context(WriteFileErr)
+    }
+
+    fn new(world: &'a World) -> Self {
+        Self {
+            world,
+            bin: Vec::new(),
+            nodes: Vec::new(),
+            buffer_views: Vec::new(),
+            accessors: Vec::new(),
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            textures: Vec::new(),
+            images: Vec::new(),
+            samplers: Vec::new(),
+            skins: Vec::new(),
+            animations: Vec::new(),
+            extensions_used: BTreeSet::new(),
+            mesh_cache: HashMap::new(),
+            material_cache: HashMap::new(),
+            texture_cache: HashMap::new(),
+            pending_skins: Vec::new(),
+            clips: Vec::new(),
+        }
+    }
+
+    fn collect_node(&mut self, id: GameObjectId) -> Result<Option<usize>> {
+        let world = self.world;
+        let Some(object) = world.objects.get(id) else {
+            return Ok(None);
+        };
+
+        let node_index = self.nodes.len();
+        self.nodes.push(ExportNode {
+            name: object.name.clone(),
+            translation: *object.transform.local_position(),
+            rotation: *object.transform.local_rotation(),
+            scale: *object.transform.local_scale(),
+            ..ExportNode::default()
+        });
+
+        if let Some(renderer) = object.get_component::<MeshRenderer>() {
+            let key = MeshKey::Static(renderer.mesh());
+            match world.assets.meshes.try_get(renderer.mesh()) {
+                Some(mesh) => {
+                    let mesh = self.push_mesh(
+                        key,
+                        renderer.materials(),
+                        MeshSource {
+                            data: &mesh.data,
+                            skinning: None,
+                            material_ranges: &mesh.material_ranges,
+                            morph_targets: &mesh.morph_targets,
+                        },
+                    )?;
+                    self.nodes[node_index].mesh = Some(mesh);
+                }
+                None => warn!("Mesh of {:?} is not loaded; skipping it", object.name),
+            }
+        }
+
+        if let Some(renderer) = object.get_component::<SkinnedMeshRenderer>() {
+            let key = MeshKey::Skinned(renderer.mesh());
+            match world.assets.skinned_meshes.try_get(renderer.mesh()) {
+                Some(mesh) => {
+                    let has_bones = !mesh.bones.is_empty();
+                    let mesh_index = self.push_mesh(
+                        key,
+                        renderer.materials(),
+                        MeshSource {
+                            data: &mesh.data,
+                            skinning: has_bones.then_some(&*mesh.skinning_data),
+                            material_ranges: &mesh.material_ranges,
+                            morph_targets: &mesh.morph_targets,
+                        },
+                    )?;
+                    let node = &mut self.nodes[node_index];
+                    node.mesh = Some(mesh_index);
+                    node.weights = renderer.morph_weights().to_vec();
+                    if has_bones {
+                        self.pending_skins.push((node_index, mesh.bones.clone()));
+                    }
+                }
+                None => warn!(
+                    "Skinned mesh of {:?} is not loaded; skipping it",
+                    object.name
+                ),
+            }
+        }
+
+        if let Some(animation) = object.get_component::<AnimationComponent>() {
+            for clip in animation.clips() {
+                if !self.clips.iter().any(|c| c.name == clip.name) {
+                    self.clips.push(clip.clone());
+                }
+            }
+        }
+
+        for &child in object.children() {
+            if let Some(child_index) = self.collect_node(child)? {
+                self.nodes[node_index].children.push(child_index);
+            }
+        }
+
+        Ok(Some(node_index))
+    }
+
+    fn node_by_name(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n.name == name)
+    }
+
+    /// Binds every skinned mesh to joint nodes. Joints that already exist in the exported
+    /// hierarchy are reused by name, missing ones are recreated in their bind pose.
+    fn resolve_skins(&mut self) {
+        for (mesh_node, bones) in std::mem::take(&mut self.pending_skins) {
+            // Bones can come before their parent, so all joints exist before any is attached
+            let mut created = Vec::new();
+            let mut joints = Vec::with_capacity(bones.len());
+            for (bone, name) in bones.names.iter().enumerate() {
+                let joint = match self.node_by_name(name) {
+                    Some(existing) => existing,
+                    None => {
+                        let (scale, rotation, translation) =
+                            bones.bind_local[bone].to_scale_rotation_translation();
+                        self.nodes.push(ExportNode {
+                            name: name.clone(),
+                            translation,
+                            rotation,
+                            scale,
+                            ..ExportNode::default()
+                        });
+                        created.push(bone);
+                        self.nodes.len() - 1
+                    }
+                };
+                joints.push(joint);
+            }
+
+            for bone in created {
+                let parent = bones.parents[bone]
+                    .and_then(|parent| joints.get(parent).copied())
+                    .unwrap_or(mesh_node);
+                self.nodes[parent].children.push(joints[bone]);
+            }
+
+            let inverse_binds: Vec<f32> = bones
+                .inverse_bind
+                .iter()
+                .flat_map(|m| m.to_cols_array())
+                .collect();
+            let accessor = self.push_data_accessor(&inverse_binds, "MAT4", false);
+
+            self.skins.push(json!({
+                "joints": joints,
+                "inverseBindMatrices": accessor,
+            }));
+            self.nodes[mesh_node].skin = Some(self.skins.len() - 1);
+        }
+    }
+
+    fn export_animations(&mut self) {
+        for clip in std::mem::take(&mut self.clips) {
+            let mut samplers = Vec::new();
+            let mut channels = Vec::new();
+
+            for channel in &clip.channels {
+                let Some(node) = self.node_by_name(&channel.target_name) else {
+                    warn!(
+                        "Animation {:?} targets unknown node {:?}; skipping channel",
+                        clip.name, channel.target_name
+                    );
+                    continue;
+                };
+
+                let keys = &channel.keys;
+                let tracks = [
+                    (
+                        "translation",
+                        &keys.t_times,
+                        vec3_floats(&keys.t_values),
+                        "VEC3",
+                    ),
+                    (
+                        "rotation",
+                        &keys.r_times,
+                        keys.r_values.iter().flat_map(|q| q.to_array()).collect(),
+                        "VEC4",
+                    ),
+                    ("scale", &keys.s_times, vec3_floats(&keys.s_values), "VEC3"),
+                    ("weights", &keys.w_times, keys.w_values.clone(), "SCALAR"),
+                ];
+
+                for (path, times, values, ty) in tracks {
+                    if times.is_empty() {
+                        continue;
+                    }
+                    let input = self.push_data_accessor(times, "SCALAR", true);
+                    let output = self.push_data_accessor(&values, ty, false);
+
+                    samplers.push(json!({
+                        "input": input,
+                        "output": output,
+                        "interpolation": "LINEAR",
+                    }));
+                    channels.push(json!({
+                        "sampler": samplers.len() - 1,
+                        "target": { "node": node, "path": path },
+                    }));
+                }
+            }
+
+            if channels.is_empty() {
+                continue;
+            }
+
+            self.animations.push(json!({
+                "name": clip.name,
+                "samplers": samplers,
+                "channels": channels,
+            }));
+        }
+    }
+
+    fn push_mesh(
+        &mut self,
+        key: MeshKey,
+        materials: &[HMaterialInstance],
+        source: MeshSource,
+    ) -> Result<usize> {
+        let cache_key = (key, materials.to_vec());
+        if let Some(&index) = self.mesh_cache.get(&cache_key) {
+            return Ok(index);
+        }
+
+        let data = source.data;
+        let vertex_count = data.positions.len();
+        let mut attributes = Map::new();
+
+        let accessor = self.push_position_accessor(&data.positions);
+        attributes.insert("POSITION".into(), accessor.into());
+        if data.normals.len() == vertex_count {
+            let accessor = self.push_vertex_accessor(&vec3_floats(&data.normals), "VEC3");
+            attributes.insert("NORMAL".into(), accessor.into());
+        }
+        if data.tangents.len() == vertex_count {
+            let accessor = self.push_vertex_accessor(&vec4_floats(&data.tangents), "VEC4");
+            attributes.insert("TANGENT".into(), accessor.into());
+        }
+        if data.uvs.len() == vertex_count {
+            let accessor = self.push_vertex_accessor(&vec2_floats(&data.uvs), "VEC2");
+            attributes.insert("TEXCOORD_0".into(), accessor.into());
+        }
+        if let Some(skinning) = source.skinning {
+            let joints: Vec<u8> = skinning
+                .bone_indices
+                .iter()
+                .flatten()
+                .flat_map(|i| i.to_le_bytes())
+                .collect();
+            let view = self.push_view(&joints, Some(ARRAY_BUFFER));
+            let accessor = self.push_accessor(view, UNSIGNED_SHORT, vertex_count, "VEC4", None);
+            attributes.insert("JOINTS_0".into(), accessor.into());
+
+            let weights: Vec<f32> = skinning.bone_weights.iter().flatten().copied().collect();
+            let accessor = self.push_vertex_accessor(&weights, "VEC4");
+            attributes.insert("WEIGHTS_0".into(), accessor.into());
+        }
+
+        let mut targets = Vec::new();
+        for target in &source.morph_targets.targets {
+            let mut attributes = Map::new();
+            if target.position_deltas.len() == vertex_count {
+                let accessor = self.push_position_accessor(&target.position_deltas);
+                attributes.insert("POSITION".into(), accessor.into());
+            }
+            if target.normal_deltas.len() == vertex_count {
+                let accessor =
+                    self.push_vertex_accessor(&vec3_floats(&target.normal_deltas), "VEC3");
+                attributes.insert("NORMAL".into(), accessor.into());
+            }
+            targets.push(JsonValue::Object(attributes));
+        }
+
+        let whole_mesh = 0..data.len() as u32;
+        let ranges = if source.material_ranges.is_empty() {
+            std::slice::from_ref(&whole_mesh)
+        } else {
+            source.material_ranges
+        };
+
+        let mut primitives = Vec::with_capacity(ranges.len());
+        for (slot, range) in ranges.iter().enumerate() {
+            let indices = primitive_indices(data, range);
+            if indices.is_empty() {
+                continue;
+            }
+            let bytes: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+            let view = self.push_view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+            let accessor = self.push_accessor(view, UNSIGNED_INT, indices.len(), "SCALAR", None);
+
+            let mut primitive = json!({
+                "attributes": attributes,
+                "indices": accessor,
+            });
+            let material = materials
+                .get(slot)
+                .copied()
+                .unwrap_or(HMaterialInstance::DEFAULT);
+            if let Some(material) = self.push_material(material)? {
+                primitive["material"] = material.into();
+            }
+            if !targets.is_empty() {
+                primitive["targets"] = targets.clone().into();
+            }
+            primitives.push(primitive);
+        }
+
+        let mut mesh = json!({ "primitives": primitives });
+        let morph_targets = source.morph_targets;
+        if !morph_targets.is_empty() {
+            mesh["weights"] = morph_targets.initial_weights().into();
+            mesh["extras"] = json!({ "targetNames": morph_targets.names().collect::<Vec<_>>() });
+        }
+
+        self.meshes.push(mesh);
+        let index = self.meshes.len() - 1;
+        self.mesh_cache.insert(cache_key, index);
+        Ok(index)
+    }
+
+    fn push_material(&mut self, handle: HMaterialInstance) -> Result<Option<usize>> {
+        if let Some(&index) = self.material_cache.get(&handle) {
+            return Ok(index);
+        }
+
+        let world = self.world;
+        let Some(material) = world.assets.material_instances.try_get(handle) else {
+            self.material_cache.insert(handle, None);
+            return Ok(None);
+        };
+
+        let base_color = material.value_vec3("diffuse").unwrap_or(Vec3::ONE);
+        let alpha = material.value_f32("alpha").unwrap_or(1.0);
+        let mut pbr = json!({
+            "baseColorFactor": [base_color.x, base_color.y, base_color.z, alpha],
+            "metallicFactor": material.value_f32("metallic").unwrap_or(0.0),
+            "roughnessFactor": material.value_f32("roughness").unwrap_or(1.0),
+        });
+
+        let mut out = Map::new();
+        out.insert("name".into(), material.name.clone().into());
+
+        let uv_transform = texture_transform(&material);
+        if uv_transform.is_some() {
+            self.extensions_used.insert("KHR_texture_transform");
+        }
+
+        let texture_info = |exporter: &mut Self, name: &str| -> Result<Option<JsonValue>> {
+            let Some(Some(texture)) = material.texture(name) else {
+                return Ok(None);
+            };
+            let Some(index) = exporter.push_texture(texture)? else {
+                return Ok(None);
+            };
+            let mut info = json!({ "index": index });
+            if let Some(transform) = &uv_transform {
+                info["extensions"] = json!({ "KHR_texture_transform": transform });
+            }
+            Ok(Some(info))
+        };
+
+        if let Some(info) = texture_info(self, "diffuse")? {
+            pbr["baseColorTexture"] = info;
+        }
+        if material.value_bool("use_metallic_texture") != Some(false)
+            && let Some(info) = texture_info(self, "roughness")?
+        {
+            pbr["metallicRoughnessTexture"] = info;
+        }
+        if let Some(info) = texture_info(self, "normal")? {
+            out.insert("normalTexture".into(), info);
+        }
+        if let Some(info) = texture_info(self, "emissive")? {
+            out.insert("emissiveTexture".into(), info);
+        }
+        if let Some(mut info) = texture_info(self, "occlusion")? {
+            if let Some(strength) = material.value_f32("occlusion_strength") {
+                info["strength"] = strength.into();
+            }
+            out.insert("occlusionTexture".into(), info);
+        }
+        out.insert("pbrMetallicRoughness".into(), pbr);
+
+        let emissive = material.value_vec3("emissive").unwrap_or(Vec3::ZERO);
+        out.insert("emissiveFactor".into(), emissive.to_array().into());
+
+        let cutoff = material.value_f32("alpha_cutoff").unwrap_or(0.0);
+        if cutoff > 0.0 {
+            out.insert("alphaMode".into(), "MASK".into());
+            out.insert("alphaCutoff".into(), cutoff.into());
+        } else if material.value_bool("has_transparency") == Some(true) {
+            out.insert("alphaMode".into(), "BLEND".into());
+        }
+        if material.value_bool("double_sided") == Some(true) {
+            out.insert("doubleSided".into(), true.into());
+        }
+
+        let mut extensions = Map::new();
+        if material.value_bool("lit") == Some(false) {
+            extensions.insert("KHR_materials_unlit".into(), json!({}));
+        }
+        if let Some(strength) = material.value_f32("emissive_strength")
+            && strength != 1.0
+        {
+            extensions.insert(
+                "KHR_materials_emissive_strength".into(),
+                json!({ "emissiveStrength": strength }),
+            );
+        }
+        if let Some(clearcoat) = material.value_f32("clearcoat")
+            && clearcoat > 0.0
+        {
+            extensions.insert(
+                "KHR_materials_clearcoat".into(),
+                json!({
+                    "clearcoatFactor": clearcoat,
+                    "clearcoatRoughnessFactor":
+                        material.value_f32("clearcoat_roughness").unwrap_or(0.0),
+                }),
+            );
+        }
+        if let Some(transmission) = material.value_f32("transmission")
+            && transmission > 0.0
+        {
+            extensions.insert(
+                "KHR_materials_transmission".into(),
+                json!({ "transmissionFactor": transmission }),
+            );
+        }
+        for name in [
+            "KHR_materials_unlit",
+            "KHR_materials_emissive_strength",
+            "KHR_materials_clearcoat",
+            "KHR_materials_transmission",
+        ] {
+            if extensions.contains_key(name) {
+                self.extensions_used.insert(name);
+            }
+        }
+        if !extensions.is_empty() {
+            out.insert("extensions".into(), extensions.into());
+        }
+
+        self.materials.push(out.into());
+        let index = self.materials.len() - 1;
+        self.material_cache.insert(handle, Some(index));
+        Ok(Some(index))
+    }
+
+    fn push_texture(&mut self, handle: HTexture2D) -> Result<Option<usize>> {
+        if let Some(&index) = self.texture_cache.get(&handle) {
+            return Ok(index);
+        }
+
+        // Fallback textures stand in for "no texture" and are not part of the scene
+        let texture = if Texture2D::is_builtin(handle) {
+            None
+        } else {
+            self.world.assets.textures.try_get(handle)
+        };
+        let Some(rgba) = texture.as_deref().and_then(texture_to_rgba8) else {
+            self.texture_cache.insert(handle, None);
+            return Ok(None);
+        };
+        let texture = texture.expect("texture was decoded");
+
+        let mut png = Vec::new();
+        RgbaImage::from_raw(texture.width, texture.height, rgba)
+            .expect("decoded texture size matches its dimensions")
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .context(EncodeImageErr)?;
+
+        let view = self.push_view(&png, None);
+        self.images
+            .push(json!({ "bufferView": view, "mimeType": "image/png" }));
+        self.samplers.push(json!({
+            "magFilter": gl_filter(texture.filter_mode),
+            "minFilter": gl_filter(texture.filter_mode),
+            "wrapS": gl_wrap(texture.repeat_mode),
+            "wrapT": gl_wrap(texture.repeat_mode),
+        }));
+        self.textures.push(json!({
+            "source": self.images.len() - 1,
+            "sampler": self.samplers.len() - 1,
+        }));
+
+        let index = self.textures.len() - 1;
+        self.texture_cache.insert(handle, Some(index));
+        Ok(Some(index))
+    }
+
+    fn push_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.bin.len(),
+            "byteLength": bytes.len(),
+        });
+        if let Some(target) = target {
+            view["target"] = target.into();
+        }
+        self.bin.extend_from_slice(bytes);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn push_accessor(
+        &mut self,
+        view: usize,
+        component_type: u32,
+        count: usize,
+        ty: &str,
+        bounds: Option<(Vec<f32>, Vec<f32>)>,
+    ) -> usize {
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": component_type,
+            "count": count,
+            "type": ty,
+        });
+        if let Some((min, max)) = bounds {
+            accessor["min"] = min.into();
+            accessor["max"] = max.into();
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    /// Float vertex attribute accessor.
+    fn push_vertex_accessor(&mut self, values: &[f32], ty: &str) -> usize {
+        let view = self.push_view(&le_bytes(values), Some(ARRAY_BUFFER));
+        self.push_accessor(view, FLOAT, values.len() / component_count(ty), ty, None)
+    }
+
+    /// POSITION accessors are required to carry their bounds.
+    fn push_position_accessor(&mut self, values: &[Vec3]) -> usize {
+        let view = self.push_view(&le_bytes(&vec3_floats(values)), Some(ARRAY_BUFFER));
+        let min = values.iter().copied().fold(Vec3::INFINITY, Vec3::min);
+        let max = values.iter().copied().fold(Vec3::NEG_INFINITY, Vec3::max);
+        let bounds = (!values.is_empty()).then(|| (min.to_array().into(), max.to_array().into()));
+        self.push_accessor(view, FLOAT, values.len(), "VEC3", bounds)
+    }
+
+    /// Float accessor for non-vertex data such as animation keys and inverse bind matrices.
+    /// Animation sampler inputs are required to carry their bounds.
+    fn push_data_accessor(&mut self, values: &[f32], ty: &str, with_bounds: bool) -> usize {
+        let view = self.push_view(&le_bytes(values), None);
+        let bounds = with_bounds.then(|| {
+            let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            (vec![min], vec![max])
+        });
+        self.push_accessor(view, FLOAT, values.len() / component_count(ty), ty, bounds)
+    }
+
+    fn finish(mut self, root: usize) -> Result<Vec<u8>> {
+        let nodes: Vec<JsonValue> = self.nodes.iter().map(ExportNode::to_json).collect();
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "Syrillian GltfExporter" },
+            "scene": 0,
+            "scenes": [{ "nodes": [root] }],
+            "nodes": nodes,
+        });
+        let sections = [
+            ("meshes", self.meshes),
+            ("materials", self.materials),
+            ("textures", self.textures),
+            ("images", self.images),
+            ("samplers", self.samplers),
+            ("skins", self.skins),
+            ("animations", self.animations),
+            ("accessors", self.accessors),
+            ("bufferViews", self.buffer_views),
+        ];
+        for (name, section) in sections {
+            if !section.is_empty() {
+                document[name] = section.into();
+            }
+        }
+        if !self.bin.is_empty() {
+            document["buffers"] = json!([{ "byteLength": self.bin.len() }]);
+        }
+        if !self.extensions_used.is_empty() {
+            document["extensionsUsed"] = self.extensions_used.into_iter().collect();
+        }
+
+        let json = serde_json::to_vec(&document).expect("glTF document serializes");
+        let glb = gltf::Glb {
+            header: gltf::binary::Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: json.into(),
+            bin: (!self.bin.is_empty()).then_some(self.bin.into()),
+        };
+        glb.to_vec().context(WriteGlbErr)
+    }
+}
+
+struct MeshSource<'m> {
+    data: &'m RawVertexBuffers,
+    skinning: Option<&'m RawSkinningVertexBuffers>,
+    material_ranges: &'m [Range<u32>],
+    morph_targets: &'m MorphTargets,
+}
+
+impl ExportNode {
+    fn to_json(&self) -> JsonValue {
+        let mut node = Map::new();
+        node.insert("name".into(), self.name.clone().into());
+        if self.translation != Vec3::ZERO {
+            node.insert("translation".into(), self.translation.to_array().into());
+        }
+        if self.rotation != Quat::IDENTITY {
+            node.insert("rotation".into(), self.rotation.to_array().into());
+        }
+        if self.scale != Vec3::ONE {
+            node.insert("scale".into(), self.scale.to_array().into());
+        }
+        if !self.children.is_empty() {
+            node.insert("children".into(), self.children.clone().into());
+        }
+        if let Some(mesh) = self.mesh {
+            node.insert("mesh".into(), mesh.into());
+        }
+        if let Some(skin) = self.skin {
+            node.insert("skin".into(), skin.into());
+        }
+        if !self.weights.is_empty() {
+            node.insert("weights".into(), self.weights.clone().into());
+        }
+        node.into()
+    }
+}
+
+fn primitive_indices(data: &RawVertexBuffers, range: &Range<u32>) -> Vec<u32> {
+    let range = range.start as usize..(range.end as usize).min(data.len());
+    match &data.indices {
+        Some(indices) => indices.get(range).unwrap_or_default().to_vec(),
+        None => (range.start as u32..range.end as u32).collect(),
+    }
+}
+
+fn texture_transform(material: &MaterialInstance) -> Option<JsonValue> {
+    let offset = material.value_vec2("uv_offset").unwrap_or(Vec2::ZERO);
+    let rotation = material.value_f32("uv_rotation").unwrap_or(0.0);
+    let scale = material.value_vec2("uv_scale").unwrap_or(Vec2::ONE);
+    if offset == Vec2::ZERO && rotation == 0.0 && scale == Vec2::ONE {
+        return None;
+    }
+    Some(json!({
+        "offset": offset.to_array(),
+        "rotation": rotation,
+        "scale": scale.to_array(),
+    }))
+}
+
+/// Expands the texture's pixel data to tightly packed RGBA8, if the format is supported.
+fn texture_to_rgba8(texture: &Texture2D) -> Option<Vec<u8>> {
+    let rgba = texture.to_rgba8();
+    if rgba.is_none() && texture.data.is_some() {
+        warn!(
+            "Cannot export textures in format {:?}; skipping it",
+            texture.format
+        );
+    }
+    rgba
+}
+
+fn gl_filter(filter: FilterMode) -> u32 {
+    match filter {
+        FilterMode::Nearest => 9728,
+        FilterMode::Linear => 9729,
+    }
+}
+
+fn gl_wrap(mode: AddressMode) -> u32 {
+    match mode {
+        AddressMode::MirrorRepeat => 33648,
+        AddressMode::ClampToEdge | AddressMode::ClampToBorder => 33071,
+        AddressMode::Repeat => 10497,
+    }
+}
+
+fn component_count(ty: &str) -> usize {
+    match ty {
+        "VEC2" => 2,
+        "VEC3" => 3,
+        "VEC4" => 4,
+        "MAT4" => 16,
+        _ => 1,
+    }
+}
+
+fn le_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn vec2_floats(values: &[Vec2]) -> Vec<f32> {
+    values.iter().flat_map(|v| v.to_array()).collect()
+}
+
+fn vec3_floats(values: &[Vec3]) -> Vec<f32> {
+    values.iter().flat_map(|v| v.to_array()).collect()
+}
+
+fn vec4_floats(values: &[Vec4]) -> Vec<f32> {
+    values.iter().flat_map(|v| v.to_array()).collect()
+}
@@ -10,12 +10,12 @@ use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use syrillian::World;
 use syrillian::core::GameObjectId;
-use syrillian_asset::PrefabAsset;
 use syrillian_asset::store::streaming::asset_store::hash_relative_path;
 use syrillian_asset::store::streaming::packaged_scene::{
     PackagedAnimationAsset, PackagedMaterialAsset, PackagedMeshAsset, PackagedPrefabAsset,
     PackagedScene, PackagedSkinnedMeshAsset, PackagedTextureAsset,
 };
+use syrillian_asset::{PrefabAsset, TextureCookSettings};
 
 pub(crate) type Result<T, E = Error> = std::result::Result<T, E>;
 #[derive(Debug, Snafu)]
@@ -34,13 +34,21 @@ impl GltfLoader {
     /// Loads a glTF file by first extracting it to packaged assets and then instantiating it in the world
     pub fn spawn(world: &mut World, path: &str) -> Result<GameObjectId, Error> {
         let virtual_root = virtual_root_from_path(path);
-        let packaged_scene = Self::extract_packaged_scene_from_path(path, virtual_root)?;
+        let packaged_scene = Self::extract_packaged_scene_from_path(
+            path,
+            virtual_root,
+            &TextureCookSettings::RUNTIME,
+        )?;
         Ok(SceneLoader::load_packaged_scene(world, packaged_scene))
     }
 
     /// Loads a glTF memory buffer by extracting it and immediately instantiating it in the world
     pub fn load_buffer(world: &mut World, model: &[u8]) -> Result<GameObjectId, Error> {
-        let packaged_scene = Self::extract_packaged_scene_from_buffer(model, "memory_scene")?;
+        let packaged_scene = Self::extract_packaged_scene_from_buffer(
+            model,
+            "memory_scene",
+            &TextureCookSettings::RUNTIME,
+        )?;
         Ok(SceneLoader::load_packaged_scene(world, packaged_scene))
     }
 
@@ -65,24 +73,28 @@ impl GltfLoader {
     pub fn extract_packaged_scene_from_path<P: AsRef<Path>>(
         path: P,
         virtual_root: impl Into<String>,
+        textures: &TextureCookSettings,
     ) -> Result<PackagedScene, Error> {
         let scene = GltfScene::import(path)?;
-        Self::extract_packaged_scene(&scene, virtual_root)
+        Self::extract_packaged_scene(&scene, virtual_root, textures)
     }
 
     /// Extracts a glTF memory buffer into packaged representation
     pub fn extract_packaged_scene_from_buffer(
         model: &[u8],
         virtual_root: impl Into<String>,
+        textures: &TextureCookSettings,
     ) -> Result<PackagedScene, Error> {
         let scene = GltfScene::from_slice(model)?;
-        Self::extract_packaged_scene(&scene, virtual_root)
+        Self::extract_packaged_scene(&scene, virtual_root, textures)
     }
 
-    /// Extracts meshes/textures/animations and prefab layout from a parsed glTF scene
+    /// Extracts meshes/textures/animations and prefab layout from a parsed glTF scene.
+    /// Textures are cooked with `textures`.
     pub fn extract_packaged_scene(
         scene: &GltfScene,
         virtual_root: impl Into<String>,
+        textures: &TextureCookSettings,
     ) -> Result<PackagedScene, Error> {
         let root_scene = scene
             .doc
@@ -236,7 +248,7 @@ impl GltfLoader {
                 continue;
             };
 
-            let Some(decoded) = scene.decode_texture(&texture, usage) else {
+            let Some(decoded) = scene.decode_texture(&texture, usage, textures) else {
                 continue;
             };
 
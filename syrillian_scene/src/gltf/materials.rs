@@ -1,7 +1,7 @@
 use crate::GltfScene;
 use gltf::Material;
 use std::collections::HashMap;
-use syrillian::math::{Vec3, Vec4};
+use syrillian::math::{Vec2, Vec3, Vec4};
 use syrillian_asset::PrefabMaterial;
 
 impl GltfScene {
@@ -14,6 +14,16 @@ impl GltfScene {
         let pbr = material.pbr_metallic_roughness();
         let base = pbr.base_color_factor();
         let emissive = material.emissive_factor();
+        let transform = pbr
+            .base_color_texture()
+            .and_then(|info| info.texture_transform());
+        let clearcoat = material.extension_value("KHR_materials_clearcoat");
+        let clearcoat_factor = |key: &str| {
+            clearcoat
+                .and_then(|ext| ext.get(key))
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0) as f32
+        };
 
         PrefabMaterial {
             name: material
@@ -43,6 +53,22 @@ impl GltfScene {
             occlusion_texture: material
                 .occlusion_texture()
                 .and_then(|info| texture_path_of.get(&info.texture().index()).cloned()),
+            emissive_strength: material.emissive_strength().unwrap_or(1.0),
+            occlusion_strength: material
+                .occlusion_texture()
+                .map_or(1.0, |info| info.strength()),
+            uv_offset: transform
+                .as_ref()
+                .map_or(Vec2::ZERO, |t| Vec2::from(t.offset())),
+            uv_rotation: transform.as_ref().map_or(0.0, |t| t.rotation()),
+            uv_scale: transform
+                .as_ref()
+                .map_or(Vec2::ONE, |t| Vec2::from(t.scale())),
+            clearcoat: clearcoat_factor("clearcoatFactor"),
+            clearcoat_roughness: clearcoat_factor("clearcoatRoughnessFactor"),
+            transmission: material
+                .transmission()
+                .map_or(0.0, |t| t.transmission_factor()),
         }
     }
 }
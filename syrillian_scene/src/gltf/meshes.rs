@@ -11,6 +11,7 @@ use syrillian::utils::iter::interpolate_zeros;
 use syrillian_asset::SkinnedMesh;
 use syrillian_asset::mesh::PartialMesh;
 use syrillian_asset::mesh::static_mesh_data::{RawSkinningVertexBuffers, RawVertexBuffers};
+use syrillian_asset::mesh::{MorphTarget, MorphTargets};
 use syrillian_utils::BoundingSphere;
 
 /// Mesh and associated material indices for each sub-mesh range
@@ -44,6 +45,7 @@ pub struct PrimitiveBuffers {
     indices: Option<Vec<u32>>,
     ranges: Vec<std::ops::Range<u32>>,
     materials: Vec<u32>,
+    morph_targets: MorphTargets,
 }
 
 pub struct VertexSources<'a> {
@@ -54,6 +56,14 @@ pub struct VertexSources<'a> {
     pub skin: Option<SkinAttributes>,
     pub indices: Option<Vec<u32>>,
     pub joint_map: &'a HashMap<usize, usize>,
+    pub morph_targets: Vec<PrimitiveMorphTarget>,
+}
+
+/// Position and normal deltas of one morph target, for a single primitive
+#[derive(Clone, Default)]
+pub struct PrimitiveMorphTarget {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
 }
 
 /// Normalizes texture coordinates from the glTF accessor format
@@ -132,6 +142,7 @@ impl PrimitiveBuffers {
             bone_weights,
             indices,
             material_index,
+            morph_targets,
         } = data;
 
         let vertex_offset = self.positions.len() as u32;
@@ -141,6 +152,7 @@ impl PrimitiveBuffers {
             .map_or(vertex_count, |indices| indices.len() as u32);
         let range_start = self.total_point_count();
         let new_vertices_len = self.positions.len() + positions.len();
+        self.extend_morph_targets(morph_targets, vertex_offset as usize, new_vertices_len);
         self.positions.extend(positions);
         self.uvs.extend(uvs);
         self.normals.extend(normals);
@@ -176,6 +188,52 @@ impl PrimitiveBuffers {
         self.materials.push(material_index);
     }
 
+    /// Appends the primitive's morph deltas. Targets are matched by index across primitives,
+    /// vertices of primitives that lack a target get zero deltas.
+    fn extend_morph_targets(
+        &mut self,
+        morph_targets: Vec<PrimitiveMorphTarget>,
+        vertex_offset: usize,
+        new_vertices_len: usize,
+    ) {
+        let targets = &mut self.morph_targets.targets;
+        if targets.len() < morph_targets.len() {
+            targets.resize_with(morph_targets.len(), MorphTarget::default);
+        }
+
+        let mut primitive_targets = morph_targets.into_iter();
+        for target in targets.iter_mut() {
+            let primitive = primitive_targets.next().unwrap_or_default();
+            extend_deltas(
+                &mut target.position_deltas,
+                primitive.positions,
+                vertex_offset,
+                new_vertices_len,
+            );
+            extend_deltas(
+                &mut target.normal_deltas,
+                primitive.normals,
+                vertex_offset,
+                new_vertices_len,
+            );
+        }
+    }
+
+    /// Names the collected morph targets and sets their default weights
+    pub fn set_morph_target_info(&mut self, names: &[String], weights: &[f32]) {
+        for (i, target) in self.morph_targets.targets.iter_mut().enumerate() {
+            target.name = names
+                .get(i)
+                .cloned()
+                .unwrap_or_else(|| format!("target{i}"));
+        }
+        self.morph_targets.default_weights = weights.to_vec();
+    }
+
+    pub fn has_morph_targets(&self) -> bool {
+        !self.morph_targets.is_empty()
+    }
+
     /// Returns true when no vertex data has been collected yet
     pub fn is_empty(&self) -> bool {
         self.positions.is_empty()
@@ -196,13 +254,17 @@ impl PrimitiveBuffers {
             uvs,
             normals,
             tangents,
-            bone_indices,
+            mut bone_indices,
             bone_weights,
             indices,
             ranges,
             materials,
+            morph_targets,
         } = self;
 
+        // meshes that only carry morph targets are still evaluated by the skinning pass
+        bone_indices.resize(positions.len(), [0; 4]);
+
         let buffers = RawVertexBuffers {
             positions,
             uvs,
@@ -222,6 +284,7 @@ impl PrimitiveBuffers {
             .data(Arc::new(buffers))
             .skinning_data(Arc::new(skinning_buffers))
             .material_ranges(ranges)
+            .morph_targets(Arc::new(morph_targets))
             .bones(bones)
             .build();
         (mesh, materials)
@@ -238,6 +301,7 @@ impl PrimitiveBuffers {
             bone_indices: _,
             bone_weights: _,
             materials,
+            morph_targets,
         } = self;
 
         let buffers = RawVertexBuffers {
@@ -254,6 +318,7 @@ impl PrimitiveBuffers {
         let mesh = Mesh::builder()
             .data(Arc::new(buffers))
             .material_ranges(ranges)
+            .morph_targets(Arc::new(morph_targets))
             .bounding_sphere(bounding_sphere)
             .build();
         (mesh, materials)
@@ -269,6 +334,7 @@ pub struct PrimitiveResult {
     bone_weights: Option<Vec<[f32; 4]>>,
     indices: Option<Vec<u32>>,
     material_index: u32,
+    morph_targets: Vec<PrimitiveMorphTarget>,
 }
 
 impl PrimitiveResult {
@@ -320,6 +386,7 @@ impl PrimitiveResult {
             bone_weights,
             indices: sources.indices,
             material_index,
+            morph_targets: sources.morph_targets,
         }
     }
 
@@ -364,6 +431,18 @@ impl GltfScene {
             .read_indices()
             .map(|indices| indices.into_u32().collect::<Vec<_>>());
 
+        let morph_targets = reader
+            .read_morph_targets()
+            .map(|(positions, normals, _tangents)| PrimitiveMorphTarget {
+                positions: positions
+                    .map(|it| it.map(Vec3::from_array).collect())
+                    .unwrap_or_default(),
+                normals: normals
+                    .map(|it| it.map(Vec3::from_array).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
         let skin = read_skin_attributes(joints_raw, weights_raw);
         let sources = VertexSources {
             positions,
@@ -373,6 +452,7 @@ impl GltfScene {
             skin,
             indices,
             joint_map: joint_node_index_of,
+            morph_targets,
         };
 
         let material_index = prim
@@ -416,6 +496,10 @@ impl GltfScene {
         }
 
         buffers.fill_missing();
+        if node_bones.is_none() && buffers.has_morph_targets() {
+            node_bones = Some(Bones::default());
+        }
+
         match node_bones {
             None => {
                 let res = buffers.build_mesh();
@@ -458,6 +542,12 @@ impl GltfScene {
             }
         }
 
+        if buffers.has_morph_targets() {
+            let names = read_morph_target_names(&mesh);
+            let weights = mesh.weights().unwrap_or_default();
+            buffers.set_morph_target_info(&names, weights);
+        }
+
         Some(buffers)
     }
 }
@@ -528,6 +618,37 @@ impl MeshLoadResult {
     }
 }
 
+/// Reads morph target names from the conventional `targetNames` mesh extras entry
+fn read_morph_target_names(mesh: &gltf::Mesh) -> Vec<String> {
+    let Some(extras) = mesh.extras().as_ref() else {
+        return Vec::new();
+    };
+    let Ok(serde_json::Value::Object(extras)) = serde_json::from_str(extras.get()) else {
+        return Vec::new();
+    };
+    let Some(serde_json::Value::Array(names)) = extras.get("targetNames") else {
+        return Vec::new();
+    };
+
+    names
+        .iter()
+        .map(|name| name.as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+/// Pads `deltas` with zeros up to `vertex_offset`, appends the primitive's deltas and pads
+/// again up to `new_vertices_len`
+fn extend_deltas(
+    deltas: &mut Vec<Vec3>,
+    primitive: Vec<Vec3>,
+    vertex_offset: usize,
+    new_vertices_len: usize,
+) {
+    deltas.resize(vertex_offset, Vec3::ZERO);
+    deltas.extend(primitive);
+    deltas.resize(new_vertices_len, Vec3::ZERO);
+}
+
 /// Maps glTF joint indices to the corresponding engine bone indices
 #[inline]
 pub fn map_joint_indices(
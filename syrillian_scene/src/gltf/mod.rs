@@ -1,5 +1,6 @@
 mod animations;
 pub mod bones;
+pub mod exporter;
 pub mod loader;
 pub mod materials;
 pub mod meshes;
@@ -7,5 +8,6 @@ mod prefabs;
 pub mod scene;
 pub mod textures;
 
+pub use exporter::GltfExporter;
 pub use loader::GltfLoader;
 pub use scene::GltfScene;
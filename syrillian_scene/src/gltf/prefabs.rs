@@ -1,7 +1,10 @@
 use gltf::Node;
+use gltf::camera::Projection;
+use gltf::khr_lights_punctual::Kind;
 use std::collections::HashMap;
 use syrillian::math::{Quat, Vec3};
-use syrillian_asset::{PrefabMeshBinding, PrefabNode};
+use syrillian::tracing::warn;
+use syrillian_asset::{PrefabCamera, PrefabLight, PrefabLightKind, PrefabMeshBinding, PrefabNode};
 
 pub fn build_prefab_node(
     node: Node,
@@ -56,6 +59,8 @@ pub fn build_prefab_node(
         mesh: mesh_binding,
         extras_json,
         components: Vec::new(),
+        camera: node.camera().and_then(|camera| read_camera(&camera)),
+        light: node.light().map(|light| read_light(&light)),
     });
 
     let children = node
@@ -74,3 +79,46 @@ pub fn build_prefab_node(
 
     node_index
 }
+
+/// Converts a glTF perspective camera. Orthographic cameras have no engine equivalent yet.
+fn read_camera(camera: &gltf::Camera) -> Option<PrefabCamera> {
+    match camera.projection() {
+        Projection::Perspective(perspective) => Some(PrefabCamera {
+            yfov: perspective.yfov().to_degrees(),
+            znear: perspective.znear(),
+            zfar: perspective.zfar().unwrap_or(1000.0),
+        }),
+        Projection::Orthographic(_) => {
+            warn!(
+                "Orthographic camera {:?} is not supported; skipping.",
+                camera.name()
+            );
+            None
+        }
+    }
+}
+
+/// Converts a `KHR_lights_punctual` light, cone angles end up in degrees.
+fn read_light(light: &gltf::khr_lights_punctual::Light) -> PrefabLight {
+    let (kind, inner_angle, outer_angle) = match light.kind() {
+        Kind::Directional => (PrefabLightKind::Sun, 0.0, 0.0),
+        Kind::Point => (PrefabLightKind::Point, 0.0, 0.0),
+        Kind::Spot {
+            inner_cone_angle,
+            outer_cone_angle,
+        } => (
+            PrefabLightKind::Spot,
+            inner_cone_angle.to_degrees(),
+            outer_cone_angle.to_degrees(),
+        ),
+    };
+
+    PrefabLight {
+        kind,
+        color: Vec3::from(light.color()),
+        intensity: light.intensity(),
+        range: light.range(),
+        inner_angle,
+        outer_angle,
+    }
+}
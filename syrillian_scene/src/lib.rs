@@ -4,6 +4,6 @@ pub mod scene_loader;
 pub mod scene_saver;
 mod utils;
 
-pub use gltf::{GltfLoader, GltfScene};
+pub use gltf::{GltfExporter, GltfLoader, GltfScene};
 pub use scene_loader::SceneLoader;
 pub use scene_saver::SceneSaver;
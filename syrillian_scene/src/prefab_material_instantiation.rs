@@ -21,7 +21,16 @@ impl PrefabMaterialInstantiation {
             .metallic_roughness_texture
             .as_deref()
             .and_then(&mut resolve_texture);
+        let emissive_texture = material
+            .emissive_texture
+            .as_deref()
+            .and_then(&mut resolve_texture);
+        let occlusion_texture = material
+            .occlusion_texture
+            .as_deref()
+            .and_then(&mut resolve_texture);
         let use_metallic_texture = roughness_texture.is_some();
+        let masked = material.alpha_mode.eq_ignore_ascii_case("Mask");
 
         let mut builder = MaterialInstance::builder()
             .name(material.name.clone())
@@ -38,10 +47,25 @@ impl PrefabMaterialInstantiation {
             .normal_texture(normal_texture)
             .roughness_texture(roughness_texture)
             .use_metallic_texture(use_metallic_texture)
+            .emissive_texture(emissive_texture)
+            .occlusion_texture(occlusion_texture)
+            .emissive(material.emissive_factor)
+            .emissive_strength(material.emissive_strength)
+            .occlusion_strength(material.occlusion_strength)
+            .double_sided(material.double_sided)
+            .clearcoat(material.clearcoat, material.clearcoat_roughness)
+            .transmission(material.transmission)
+            .uv_transform(material.uv_offset, material.uv_rotation, material.uv_scale)
             .lit(!material.unlit);
 
-        let has_transparency =
-            material.alpha_mode.eq_ignore_ascii_case("Blend") || material.base_color.w < 1.0;
+        if masked {
+            // glTF defaults the cutoff to 0.5 when it is omitted
+            builder = builder.alpha_cutoff(material.alpha_cutoff.unwrap_or(0.5));
+        }
+
+        let has_transparency = material.alpha_mode.eq_ignore_ascii_case("Blend")
+            || material.transmission > 0.0
+            || (!masked && material.base_color.w < 1.0);
         builder = builder.has_transparency(has_transparency);
 
         builder.build()
@@ -4,6 +4,7 @@ use snafu::{ResultExt, Snafu};
 use std::collections::{HashMap, HashSet};
 use syrillian::World;
 use syrillian::assets::{HMaterialInstance, HMesh, HTexture2D, Mesh, Texture2D};
+use syrillian::components::CameraComponent;
 use syrillian::core::GameObjectId;
 use syrillian::core::component_factory::ComponentFactory;
 use syrillian::core::reflection::Value;
@@ -13,11 +14,13 @@ use syrillian_asset::store::streaming::AssetStreamingError;
 use syrillian_asset::store::streaming::asset_store::{hash_relative_path, normalize_asset_path};
 use syrillian_asset::store::streaming::packaged_scene::PackagedScene;
 use syrillian_asset::{
-    AnimationClip, AssetStore, HSkinnedMesh, PrefabAsset, PrefabMaterial, PrefabMeshBinding,
-    SkinnedMesh, StreamingLoadableAsset,
+    AnimationClip, AssetStore, HSkinnedMesh, PrefabAsset, PrefabCamera, PrefabLight,
+    PrefabLightKind, PrefabMaterial, PrefabMeshBinding, SkinnedMesh, StreamingLoadableAsset,
 };
+use syrillian_components::light::Light;
 use syrillian_components::{
-    AnimationComponent, MeshRenderer, SkeletalComponent, SkinnedMeshRenderer,
+    AnimationComponent, MeshRenderer, PointLightComponent, SkeletalComponent, SkinnedMeshRenderer,
+    SpotLightComponent, SunLightComponent,
 };
 
 #[derive(Debug, Snafu)]
@@ -180,6 +183,14 @@ impl<'a> PrefabInstantiationContext<'a> {
             self.attach_mesh_binding(&mut object, mesh_binding);
         }
 
+        if let Some(camera) = node.camera.as_ref() {
+            attach_camera(&mut object, camera);
+        }
+
+        if let Some(light) = node.light.as_ref() {
+            attach_light(&mut object, light);
+        }
+
         // Spawn reflected components from prefab data
         for prefab_comp in &node.components {
             let fields = Value::Object(prefab_comp.fields.clone());
@@ -335,6 +346,38 @@ impl<'a> PrefabInstantiationContext<'a> {
     }
 }
 
+fn attach_camera(object: &mut GameObjectId, camera: &PrefabCamera) {
+    let mut component = object.add_component::<CameraComponent>();
+    component.set_fov_instant(camera.yfov);
+    component.set_near(camera.znear);
+    component.set_far(camera.zfar);
+}
+
+fn attach_light(object: &mut GameObjectId, light: &PrefabLight) {
+    fn configure(component: &mut impl Light, light: &PrefabLight) {
+        component.set_color_vec(&light.color);
+        component.set_intensity(light.intensity);
+        if let Some(range) = light.range {
+            component.set_range(range);
+        }
+    }
+
+    match light.kind {
+        PrefabLightKind::Point => {
+            configure(&mut *object.add_component::<PointLightComponent>(), light);
+        }
+        PrefabLightKind::Sun => {
+            configure(&mut *object.add_component::<SunLightComponent>(), light);
+        }
+        PrefabLightKind::Spot => {
+            let mut component = object.add_component::<SpotLightComponent>();
+            configure(&mut *component, light);
+            component.set_outer_angle(light.outer_angle);
+            component.set_inner_angle(light.inner_angle);
+        }
+    }
+}
+
 fn resolve_cached_by_path<A: StreamingLoadableAsset>(
     assets: &AssetStore,
     cache: &mut HashMap<String, H<A>>,
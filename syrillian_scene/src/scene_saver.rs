@@ -113,6 +113,8 @@ impl SceneSaver {
             mesh: None, // TODO: serialize mesh bindings
             extras_json,
             components,
+            camera: None,
+            light: None,
         };
 
         Some(node_index)
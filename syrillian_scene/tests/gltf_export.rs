@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use syrillian::World;
+use syrillian::assets::{HMesh, MaterialInstance, Mesh, SkinnedMesh};
+use syrillian::math::{Mat4, Vec2, Vec3, Vec4};
+use syrillian_asset::mesh::Bones;
+use syrillian_asset::mesh::static_mesh_data::{RawSkinningVertexBuffers, RawVertexBuffers};
+use syrillian_components::{MeshRenderer, SkinnedMeshRenderer};
+use syrillian_scene::{GltfExporter, GltfLoader};
+
+fn asset_path(relative: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+#[test]
+fn exported_hierarchy_round_trips_through_the_loader() {
+    let (mut world, ..) = World::fresh();
+
+    let material = MaterialInstance::builder()
+        .name("Red")
+        .diffuse(Vec3::new(1.0, 0.0, 0.0))
+        .roughness(0.25)
+        .metallic(0.5)
+        .double_sided(true)
+        .store(&world.assets.material_instances);
+
+    let mut root = world.new_object("Root");
+    root.transform.set_local_position(0.0, 2.0, 0.0);
+
+    let mut cube = world.new_object("Cube");
+    cube.transform.set_local_position(1.0, 0.0, -3.0);
+    cube.add_component::<MeshRenderer>()
+        .change_mesh(HMesh::UNIT_CUBE, Some(vec![material]));
+    root.add_child(cube);
+
+    let glb = GltfExporter::export_glb(&world, root).expect("export should succeed");
+    let scene = GltfLoader::load_scene_from_buffer(&glb).expect("exported glb should parse");
+
+    let nodes: Vec<_> = scene.doc.nodes().collect();
+    assert_eq!(nodes[0].name(), Some("Root"));
+    assert_eq!(nodes[1].name(), Some("Cube"));
+    assert_eq!(nodes[0].children().next().map(|n| n.index()), Some(1));
+    let (translation, ..) = nodes[1].transform().decomposed();
+    assert_eq!(translation, [1.0, 0.0, -3.0]);
+
+    let exported = scene
+        .doc
+        .materials()
+        .next()
+        .expect("material should be exported");
+    assert_eq!(exported.name(), Some("Red"));
+    let pbr = exported.pbr_metallic_roughness();
+    assert_eq!(pbr.base_color_factor(), [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(pbr.roughness_factor(), 0.25);
+    assert_eq!(pbr.metallic_factor(), 0.5);
+    assert!(exported.double_sided());
+
+    let cube_mesh = world.assets.meshes.try_get(HMesh::UNIT_CUBE).unwrap();
+    let (mesh, materials) = GltfLoader::load_first_mesh_from_buffer(&glb)
+        .expect("exported glb should load")
+        .expect("mesh should be present");
+    assert_eq!(mesh.vertex_count(), cube_mesh.data.positions.len());
+    assert_eq!(materials.len(), 1);
+}
+
+#[test]
+fn imported_model_survives_export() {
+    let bytes = std::fs::read(asset_path(
+        "../syrillian_examples/examples/assets/testmodels/hampter/hampter.glb",
+    ))
+    .expect("failed to read test model");
+    let (original, _) = GltfLoader::load_first_mesh_from_buffer(&bytes)
+        .expect("model should load")
+        .expect("mesh should be present");
+
+    let (mut world, ..) = World::fresh();
+    let root = GltfLoader::load_buffer(world.as_mut(), &bytes).expect("model should spawn");
+
+    let glb = GltfExporter::export_glb(&world, root).expect("export should succeed");
+    let (exported, _) = GltfLoader::load_first_mesh_from_buffer(&glb)
+        .expect("exported glb should load")
+        .expect("exported mesh should be present");
+
+    assert_eq!(exported.vertex_count(), original.vertex_count());
+}
+
+#[test]
+fn bones_listed_before_their_parent_export() {
+    let (mut world, ..) = World::fresh();
+
+    // "Hand" comes before its parent "Arm"
+    let bind_local = vec![
+        Mat4::from_translation(Vec3::new(0.0, 1.0, 0.0)),
+        Mat4::from_translation(Vec3::new(0.0, 2.0, 0.0)),
+    ];
+    let bind_global = vec![bind_local[1] * bind_local[0], bind_local[1]];
+    let bones = Bones {
+        names: vec!["Hand".to_string(), "Arm".to_string()],
+        parents: vec![Some(1), None],
+        children: vec![Vec::new(), vec![0]],
+        roots: vec![1],
+        inverse_bind: bind_global.iter().map(Mat4::inverse).collect(),
+        bind_global,
+        bind_local,
+        index_of: [("Hand".to_string(), 0), ("Arm".to_string(), 1)].into(),
+    };
+
+    let mesh = SkinnedMesh::builder()
+        .bones(bones)
+        .data(Arc::new(RawVertexBuffers {
+            positions: vec![Vec3::ZERO, Vec3::X, Vec3::Z],
+            uvs: vec![Vec2::ZERO, Vec2::X, Vec2::Y],
+            normals: vec![Vec3::Y; 3],
+            tangents: vec![Vec4::X; 3],
+            indices: None,
+        }))
+        .skinning_data(Arc::new(RawSkinningVertexBuffers {
+            bone_indices: vec![[0, 1, 0, 0]; 3],
+            bone_weights: vec![[0.5, 0.5, 0.0, 0.0]; 3],
+        }))
+        .build();
+    let mesh = world.assets.skinned_meshes.add(mesh);
+
+    let mut body = world.new_object("Body");
+    body.add_component::<SkinnedMeshRenderer>()
+        .change_mesh(mesh, None);
+
+    let glb = GltfExporter::export_glb(&world, body).expect("export should succeed");
+    let scene = GltfLoader::load_scene_from_buffer(&glb).expect("exported glb should parse");
+
+    let skin = scene.doc.skins().next().expect("skin should be exported");
+    let joints: Vec<_> = skin.joints().map(|joint| joint.name()).collect();
+    assert_eq!(joints, [Some("Hand"), Some("Arm")]);
+
+    let node = |name: &str| {
+        scene
+            .doc
+            .nodes()
+            .find(|node| node.name() == Some(name))
+            .unwrap()
+    };
+    let children = |name: &str| -> Vec<_> {
+        node(name)
+            .children()
+            .map(|child| child.name().map(str::to_string))
+            .collect()
+    };
+    assert_eq!(children("Body"), [Some("Arm".to_string())]);
+    assert_eq!(children("Arm"), [Some("Hand".to_string())]);
+}
+
+#[test]
+fn obj_writer_round_trips_material_groups() {
+    let (world, ..) = World::fresh();
+    let cube = world.assets.meshes.try_get(HMesh::UNIT_CUBE).unwrap();
+    let vertex_count = cube.data.positions.len() as u32;
+
+    let mesh = Mesh {
+        material_ranges: vec![0..18, 18..vertex_count],
+        data: Arc::clone(&cube.data),
+        ..(*cube).clone()
+    };
+
+    let obj = mesh.to_obj_string();
+    let reloaded = Mesh::load_from_obj_slice(obj.as_bytes()).expect("written OBJ should load");
+
+    assert_eq!(reloaded.material_ranges, mesh.material_ranges);
+    assert_eq!(reloaded.data.positions, mesh.data.positions);
+    assert_eq!(reloaded.data.normals, mesh.data.normals);
+    assert_eq!(reloaded.data.uvs, mesh.data.uvs);
+}
@@ -0,0 +1,148 @@
+use syrillian::World;
+use syrillian::components::CameraComponent;
+use syrillian::math::Vec3;
+use syrillian_components::light::Light;
+use syrillian_components::{SkinnedMeshRenderer, SpotLightComponent};
+use syrillian_scene::GltfLoader;
+use syrillian_scene::gltf::meshes::MeshLoadResult;
+
+const GLTF_JSON: &str = r#"{
+    "asset": { "version": "2.0" },
+    "extensionsUsed": ["KHR_lights_punctual"],
+    "extensions": {
+        "KHR_lights_punctual": {
+            "lights": [{
+                "type": "spot",
+                "color": [1.0, 0.5, 0.25],
+                "intensity": 3.0,
+                "range": 12.0,
+                "spot": { "innerConeAngle": 0.2, "outerConeAngle": 0.5 }
+            }]
+        }
+    },
+    "scene": 0,
+    "scenes": [{ "nodes": [0, 1, 2] }],
+    "nodes": [
+        { "name": "Face", "mesh": 0 },
+        { "name": "Cam", "camera": 0, "translation": [0.0, 1.0, 4.0] },
+        { "name": "Lamp", "extensions": { "KHR_lights_punctual": { "light": 0 } } }
+    ],
+    "cameras": [{
+        "type": "perspective",
+        "perspective": { "yfov": 1.0, "znear": 0.5, "zfar": 250.0 }
+    }],
+    "meshes": [{
+        "primitives": [{ "attributes": { "POSITION": 0 }, "targets": [{ "POSITION": 1 }] }],
+        "weights": [0.25],
+        "extras": { "targetNames": ["Smile"] }
+    }],
+    "animations": [{
+        "name": "Talk",
+        "samplers": [{ "input": 2, "output": 3 }],
+        "channels": [{ "sampler": 0, "target": { "node": 0, "path": "weights" } }]
+    }],
+    "buffers": [{ "byteLength": 88 }],
+    "bufferViews": [
+        { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+        { "buffer": 0, "byteOffset": 36, "byteLength": 36 },
+        { "buffer": 0, "byteOffset": 72, "byteLength": 8 },
+        { "buffer": 0, "byteOffset": 80, "byteLength": 8 }
+    ],
+    "accessors": [
+        { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+          "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] },
+        { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3",
+          "min": [0.0, 0.0, 0.0], "max": [0.0, 0.0, 1.0] },
+        { "bufferView": 2, "componentType": 5126, "count": 2, "type": "SCALAR",
+          "min": [0.0], "max": [1.0] },
+        { "bufferView": 3, "componentType": 5126, "count": 2, "type": "SCALAR" }
+    ]
+}"#;
+
+fn build_glb() -> Vec<u8> {
+    let floats: [f32; 22] = [
+        // positions
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, //
+        // morph target position deltas
+        0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, //
+        // animation times and weights
+        0.0, 1.0, 0.0, 1.0,
+    ];
+    let bin: Vec<u8> = floats.iter().flat_map(|f| f.to_le_bytes()).collect();
+
+    let mut json = GLTF_JSON.as_bytes().to_vec();
+    json.resize(json.len().next_multiple_of(4), b' ');
+
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+    let mut glb = Vec::with_capacity(total);
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json);
+    glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&bin);
+    glb
+}
+
+#[test]
+fn morph_targets_are_imported_onto_the_mesh() {
+    let (mesh, _) = GltfLoader::load_first_mesh_from_buffer(&build_glb())
+        .expect("scene should load")
+        .expect("mesh should be present");
+
+    let MeshLoadResult::Skinned(mesh) = mesh else {
+        panic!("meshes with morph targets should go through the skinning path");
+    };
+    let morphs = &mesh.morph_targets;
+    assert_eq!(morphs.names().collect::<Vec<_>>(), ["Smile"]);
+    assert_eq!(morphs.initial_weights(), [0.25]);
+    assert!(mesh.bones.is_empty());
+
+    let morphed = morphs.morphed_positions(&mesh.data.positions, &[0.5]);
+    assert_eq!(morphed[2], Vec3::new(0.0, 1.0, 0.5));
+}
+
+#[test]
+fn weight_animation_channels_are_sampled() {
+    let scene = GltfLoader::load_scene_from_buffer(&build_glb()).expect("scene should parse");
+    let clips = scene.decode_animations();
+    assert_eq!(clips.len(), 1);
+
+    let keys = &clips[0].channels[0].keys;
+    assert_eq!(keys.weight_count(), 1);
+    assert_eq!(keys.sample_weights(0.5), Some(vec![0.5]));
+    assert_eq!(keys.sample_weights(2.0), Some(vec![1.0]));
+}
+
+#[test]
+fn cameras_and_lights_spawn_components() {
+    let (mut world, ..) = World::fresh();
+    GltfLoader::load_buffer(world.as_mut(), &build_glb()).expect("scene should load");
+
+    let camera = world
+        .find_object_by_name("Cam")
+        .and_then(|cam| cam.get_component::<CameraComponent>())
+        .expect("camera node should have a camera component");
+    assert!((camera.fov() - 1.0f32.to_degrees()).abs() < 1e-4);
+    assert_eq!(camera.near(), 0.5);
+    assert_eq!(camera.far(), 250.0);
+
+    let lamp = world
+        .find_object_by_name("Lamp")
+        .and_then(|lamp| lamp.get_component::<SpotLightComponent>())
+        .expect("light node should have a spot light");
+    assert_eq!(lamp.color(), Vec3::new(1.0, 0.5, 0.25));
+    assert_eq!(lamp.intensity(), 3.0);
+    assert!((lamp.data().outer_angle - 0.5).abs() < 1e-4);
+
+    let face = world
+        .find_object_by_name("Face")
+        .expect("mesh node should be spawned");
+    assert!(
+        face.get_component::<SkinnedMeshRenderer>().is_some(),
+        "morphed mesh should use a skinned mesh renderer"
+    );
+}
@@ -0,0 +1,55 @@
+use syrillian::math::{Vec2, Vec3, Vec4};
+use syrillian_asset::PrefabMaterial;
+use syrillian_scene::prefab_material_instantiation::PrefabMaterialInstantiation;
+
+fn prefab_material(alpha_mode: &str) -> PrefabMaterial {
+    PrefabMaterial {
+        name: "Foliage".to_string(),
+        base_color: Vec4::new(0.2, 0.8, 0.3, 0.9),
+        metallic: 0.0,
+        roughness: 0.7,
+        alpha_cutoff: None,
+        alpha_mode: alpha_mode.to_string(),
+        double_sided: true,
+        unlit: false,
+        emissive_factor: Vec3::new(1.0, 0.5, 0.0),
+        base_color_texture: None,
+        normal_texture: None,
+        metallic_roughness_texture: None,
+        emissive_texture: None,
+        occlusion_texture: None,
+        emissive_strength: 4.0,
+        occlusion_strength: 0.5,
+        uv_offset: Vec2::new(0.25, 0.0),
+        uv_rotation: 0.0,
+        uv_scale: Vec2::splat(2.0),
+        clearcoat: 0.0,
+        clearcoat_roughness: 0.0,
+        transmission: 0.0,
+    }
+}
+
+#[test]
+fn masked_material_uses_cutoff_instead_of_blending() {
+    let material = PrefabMaterialInstantiation::instantiate(&prefab_material("Mask"), |_| None);
+
+    assert_eq!(material.value_f32("alpha_cutoff"), Some(0.5));
+    assert_eq!(material.value_bool("has_transparency"), Some(false));
+    assert_eq!(material.value_bool("double_sided"), Some(true));
+    assert_eq!(material.value_f32("emissive_strength"), Some(4.0));
+    assert_eq!(material.value_f32("occlusion_strength"), Some(0.5));
+}
+
+#[test]
+fn blended_and_transmissive_materials_are_transparent() {
+    let blended = PrefabMaterialInstantiation::instantiate(&prefab_material("Opaque"), |_| None);
+    assert_eq!(blended.value_bool("has_transparency"), Some(true));
+    assert_eq!(blended.value_f32("alpha_cutoff"), None);
+
+    let mut glass = prefab_material("Opaque");
+    glass.base_color.w = 1.0;
+    glass.transmission = 0.9;
+    let glass = PrefabMaterialInstantiation::instantiate(&glass, |_| None);
+    assert_eq!(glass.value_bool("has_transparency"), Some(true));
+    assert_eq!(glass.value_f32("transmission"), Some(0.9));
+}
@@ -1,3 +1,6 @@
+use crate::compiler::PbrSurface;
+use crate::value::MaterialValueType;
+
 pub type NodeId = u32;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -18,13 +21,13 @@ impl NodeExpressionInput {
     }
 
     pub fn expr(self, ctx: &EmitCtx) -> String {
-        let value = ctx.expr(self.node);
-        debug_assert_eq!(
-            self.output_index, 0,
-            "output_index {} is not yet implemented for WGSL node emission",
-            self.output_index
-        );
-        value
+        ctx.output_expr(self.node, self.output_index)
+    }
+
+    pub fn constant_u32(self, ctx: &EmitCtx) -> Option<u32> {
+        (self.output_index == 0)
+            .then(|| ctx.constant_u32(self.node))
+            .flatten()
     }
 }
 
@@ -46,12 +49,65 @@ impl<'a> EmitCtx<'a> {
         let chunk = self.nodes.get(idx).expect("Invalid NodeId");
         chunk.node.expr(id, self)
     }
+
+    pub fn output_expr(&self, id: NodeId, output_index: u32) -> String {
+        let idx = id as usize;
+        let chunk = self.nodes.get(idx).expect("Invalid NodeId");
+        chunk.node.output_expr(id, output_index, self)
+    }
+
+    /// Value of `id` if it's a `u32` or `bool` known while compiling.
+    pub fn constant_u32(&self, id: NodeId) -> Option<u32> {
+        self.nodes.get(id as usize)?.node.constant_u32()
+    }
 }
 
 pub trait NodeChunk {
     fn deps(&self) -> &[NodeId];
     fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String>;
     fn expr(&self, id: NodeId, ctx: &EmitCtx) -> String;
+
+    /// Value type of every output. `None` marks an output whose type isn't tracked,
+    /// such as raw function calls.
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        vec![None]
+    }
+
+    /// Expression of the output at `output_index`. Multi-output nodes declare one WGSL
+    /// local per output in [`emit`](Self::emit) and return it here.
+    fn output_expr(&self, id: NodeId, output_index: u32, ctx: &EmitCtx) -> String {
+        debug_assert_eq!(output_index, 0, "node {id} only has a single output");
+        self.expr(id, ctx)
+    }
+
+    /// Value of a `u32` or `bool` node known at compile time, like a permutation key.
+    fn constant_u32(&self) -> Option<u32> {
+        None
+    }
+}
+
+fn typed(ty: MaterialValueType) -> Vec<Option<MaterialValueType>> {
+    vec![Some(ty)]
+}
+
+/// Declares `_pv{id}` as `if_true` when `condition` is set and `if_false` otherwise.
+/// Conditions known at compile time only emit the taken branch.
+fn emit_branch(
+    id: NodeId,
+    value_type: &str,
+    condition: ExpressionInput,
+    if_true: String,
+    if_false: String,
+    ctx: &EmitCtx,
+) -> String {
+    match condition.constant_u32(ctx) {
+        Some(0) => format!("let _pv{id}: {value_type} = {if_false};"),
+        Some(_) => format!("let _pv{id}: {value_type} = {if_true};"),
+        None => format!(
+            "var _pv{id}: {value_type};\n    if ({} != 0) {{\n        _pv{id} = {if_true};\n    }} else {{\n        _pv{id} = {if_false};\n    }}",
+            condition.expr(ctx)
+        ),
+    }
 }
 
 static EMPTY_DEPS: [NodeId; 0] = [];
@@ -70,15 +126,54 @@ impl NodeChunk for VertexUvNode {
     fn expr(&self, _id: NodeId, _ctx: &EmitCtx) -> String {
         "in.uv".to_string()
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::Vec2)
+    }
+}
+
+/// A value provided by the generated shader stage, like `system.time` or the
+/// vertex stage's `world_position`.
+pub(crate) struct StageValueNode {
+    expr: &'static str,
+    value_type: MaterialValueType,
+}
+
+impl StageValueNode {
+    pub const fn new(expr: &'static str, value_type: MaterialValueType) -> Self {
+        Self { expr, value_type }
+    }
+}
+
+impl NodeChunk for StageValueNode {
+    fn deps(&self) -> &[NodeId] {
+        &EMPTY_DEPS
+    }
+
+    fn emit(&self, _id: NodeId, _ctx: &EmitCtx) -> Option<String> {
+        None
+    }
+
+    fn expr(&self, _id: NodeId, _ctx: &EmitCtx) -> String {
+        self.expr.to_string()
+    }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(self.value_type)
+    }
 }
 
 pub(crate) struct MaterialInputNode {
     name: String,
+    value_type: Option<MaterialValueType>,
 }
 
 impl MaterialInputNode {
-    pub fn new(name: impl Into<String>) -> Self {
-        Self { name: name.into() }
+    pub fn new(name: impl Into<String>, value_type: Option<MaterialValueType>) -> Self {
+        Self {
+            name: name.into(),
+            value_type,
+        }
     }
 }
 
@@ -94,6 +189,10 @@ impl NodeChunk for MaterialInputNode {
     fn expr(&self, _id: NodeId, _ctx: &EmitCtx) -> String {
         format!("material.{}", self.name)
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        vec![self.value_type]
+    }
 }
 
 pub(crate) struct MaterialTextureNode {
@@ -186,22 +285,25 @@ impl NodeChunk for MaterialBaseColorNode {
     fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
         let uv = self.uv.expr(ctx);
         let color = self.color.expr(ctx);
-        let use_tex = self.use_texture.expr(ctx);
         let tex = self.texture.expr(ctx);
         let sampler = self.sampler.expr(ctx);
-        Some(format!(
-            "var _pv{id}: vec4f;\n    if ({use_tex} != 0) {{\n        _pv{id} = textureSample({tex}, {sampler}, {uv});\n    }} else {{\n        _pv{id} = vec4f({color}, 1.0);\n    }}",
-            tex = tex,
-            sampler = sampler,
-            uv = uv,
-            color = color,
-            use_tex = use_tex,
+        Some(emit_branch(
+            id,
+            "vec4f",
+            self.use_texture,
+            format!("textureSample({tex}, {sampler}, {uv})"),
+            format!("vec4f({color}, 1.0)"),
+            ctx,
         ))
     }
 
     fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
         format!("_pv{id}")
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::Vec4)
+    }
 }
 
 pub(crate) struct MaterialRoughnessNode {
@@ -246,22 +348,25 @@ impl NodeChunk for MaterialRoughnessNode {
     fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
         let uv = self.uv.expr(ctx);
         let roughness = self.roughness.expr(ctx);
-        let use_tex = self.use_texture.expr(ctx);
         let tex = self.texture.expr(ctx);
         let sampler = self.sampler.expr(ctx);
-        Some(format!(
-            "var _pv{id}: f32;\n    if ({use_tex} != 0) {{\n        _pv{id} = {roughness} * textureSample({tex}, {sampler}, {uv}).g;\n    }} else {{\n        _pv{id} = {roughness};\n    }}",
-            tex = tex,
-            sampler = sampler,
-            uv = uv,
-            roughness = roughness,
-            use_tex = use_tex,
+        Some(emit_branch(
+            id,
+            "f32",
+            self.use_texture,
+            format!("{roughness} * textureSample({tex}, {sampler}, {uv}).g"),
+            roughness,
+            ctx,
         ))
     }
 
     fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
         format!("_pv{id}")
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::F32)
+    }
 }
 
 pub(crate) struct MaterialMetallicNode {
@@ -306,22 +411,25 @@ impl NodeChunk for MaterialMetallicNode {
     fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
         let uv = self.uv.expr(ctx);
         let metallic = self.metallic.expr(ctx);
-        let use_tex = self.use_texture.expr(ctx);
         let tex = self.texture.expr(ctx);
         let sampler = self.sampler.expr(ctx);
-        Some(format!(
-            "var _pv{id}: f32;\n    if ({use_tex} != 0) {{\n        _pv{id} = {metallic} * textureSample({tex}, {sampler}, {uv}).b;\n    }} else {{\n        _pv{id} = {metallic};\n    }}",
-            tex = tex,
-            sampler = sampler,
-            uv = uv,
-            metallic = metallic,
-            use_tex = use_tex,
+        Some(emit_branch(
+            id,
+            "f32",
+            self.use_texture,
+            format!("{metallic} * textureSample({tex}, {sampler}, {uv}).b"),
+            metallic,
+            ctx,
         ))
     }
 
     fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
         format!("_pv{id}")
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::F32)
+    }
 }
 
 pub(crate) struct MaterialNormalNode {
@@ -361,21 +469,192 @@ impl NodeChunk for MaterialNormalNode {
 
     fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
         let uv = self.uv.expr(ctx);
-        let use_tex = self.use_texture.expr(ctx);
         let tex = self.texture.expr(ctx);
         let sampler = self.sampler.expr(ctx);
+        Some(emit_branch(
+            id,
+            "vec3f",
+            self.use_texture,
+            format!("normal_from_map({tex}, {sampler}, {uv}, in.normal, in.tangent, in.bitangent)"),
+            "safe_normalize(in.normal)".to_string(),
+            ctx,
+        ))
+    }
+
+    fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
+        format!("_pv{id}")
+    }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::Vec3)
+    }
+}
+
+pub(crate) struct MaterialEmissiveNode {
+    uv: ExpressionInput,
+    color: ExpressionInput,
+    strength: ExpressionInput,
+    use_texture: ExpressionInput,
+    texture: ExpressionInput,
+    sampler: ExpressionInput,
+    deps: [NodeId; 6],
+}
+
+impl MaterialEmissiveNode {
+    pub fn new(
+        uv: ExpressionInput,
+        color: ExpressionInput,
+        strength: ExpressionInput,
+        use_texture: ExpressionInput,
+        texture: ExpressionInput,
+        sampler: ExpressionInput,
+    ) -> Self {
+        Self {
+            uv,
+            color,
+            strength,
+            use_texture,
+            texture,
+            sampler,
+            deps: [
+                uv.node(),
+                color.node(),
+                strength.node(),
+                use_texture.node(),
+                texture.node(),
+                sampler.node(),
+            ],
+        }
+    }
+}
+
+impl NodeChunk for MaterialEmissiveNode {
+    fn deps(&self) -> &[NodeId] {
+        &self.deps
+    }
+
+    fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
+        let uv = self.uv.expr(ctx);
+        let color = self.color.expr(ctx);
+        let strength = self.strength.expr(ctx);
+        let tex = self.texture.expr(ctx);
+        let sampler = self.sampler.expr(ctx);
+        Some(emit_branch(
+            id,
+            "vec3f",
+            self.use_texture,
+            format!("{color} * {strength} * textureSample({tex}, {sampler}, {uv}).rgb"),
+            format!("{color} * {strength}"),
+            ctx,
+        ))
+    }
+
+    fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
+        format!("_pv{id}")
+    }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::Vec3)
+    }
+}
+
+pub(crate) struct MaterialOcclusionNode {
+    uv: ExpressionInput,
+    strength: ExpressionInput,
+    use_texture: ExpressionInput,
+    texture: ExpressionInput,
+    sampler: ExpressionInput,
+    deps: [NodeId; 5],
+}
+
+impl MaterialOcclusionNode {
+    pub fn new(
+        uv: ExpressionInput,
+        strength: ExpressionInput,
+        use_texture: ExpressionInput,
+        texture: ExpressionInput,
+        sampler: ExpressionInput,
+    ) -> Self {
+        Self {
+            uv,
+            strength,
+            use_texture,
+            texture,
+            sampler,
+            deps: [
+                uv.node(),
+                strength.node(),
+                use_texture.node(),
+                texture.node(),
+                sampler.node(),
+            ],
+        }
+    }
+}
+
+impl NodeChunk for MaterialOcclusionNode {
+    fn deps(&self) -> &[NodeId] {
+        &self.deps
+    }
+
+    fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
+        let uv = self.uv.expr(ctx);
+        let strength = self.strength.expr(ctx);
+        let tex = self.texture.expr(ctx);
+        let sampler = self.sampler.expr(ctx);
+        Some(emit_branch(
+            id,
+            "f32",
+            self.use_texture,
+            format!("mix(1.0, textureSample({tex}, {sampler}, {uv}).r, {strength})"),
+            "1.0".to_string(),
+            ctx,
+        ))
+    }
+
+    fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
+        format!("_pv{id}")
+    }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::F32)
+    }
+}
+
+pub(crate) struct UvTransformNode {
+    deps: [NodeId; 4],
+}
+
+impl UvTransformNode {
+    pub fn new(uv: NodeId, offset: NodeId, rotation: NodeId, scale: NodeId) -> Self {
+        Self {
+            deps: [uv, offset, rotation, scale],
+        }
+    }
+}
+
+impl NodeChunk for UvTransformNode {
+    fn deps(&self) -> &[NodeId] {
+        &self.deps
+    }
+
+    fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
+        let uv = ctx.expr(self.deps[0]);
+        let offset = ctx.expr(self.deps[1]);
+        let rotation = ctx.expr(self.deps[2]);
+        let scale = ctx.expr(self.deps[3]);
         Some(format!(
-            "var _pv{id}: vec3f;\n    if ({use_tex} != 0) {{\n        _pv{id} = normal_from_map({tex}, {sampler}, {uv}, in.normal, in.tangent, in.bitangent);\n    }} else {{\n        _pv{id} = safe_normalize(in.normal);\n    }}",
-            tex = tex,
-            sampler = sampler,
-            uv = uv,
-            use_tex = use_tex,
+            "let _pv{id}: vec2f = transform_uv({uv}, {offset}, {rotation}, {scale});"
         ))
     }
 
     fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
         format!("_pv{id}")
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::Vec2)
+    }
 }
 
 pub(crate) struct PostSurfaceTextureNode;
@@ -439,6 +718,10 @@ impl NodeChunk for TextureSampleNode {
     fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
         format!("_pv{id}")
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::Vec4)
+    }
 }
 
 pub(crate) struct ConstantF32Node {
@@ -467,34 +750,69 @@ impl NodeChunk for ConstantF32Node {
     fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
         format!("_pv{id}")
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(MaterialValueType::F32)
+    }
+}
+
+/// A `u32` or `bool` baked into the shader, see [`MaterialPermutation`](crate::MaterialPermutation).
+pub(crate) struct ConstantU32Node {
+    value: u32,
+    value_type: MaterialValueType,
+}
+
+impl ConstantU32Node {
+    pub fn new(value: u32, value_type: MaterialValueType) -> Self {
+        Self { value, value_type }
+    }
+}
+
+impl NodeChunk for ConstantU32Node {
+    fn deps(&self) -> &[NodeId] {
+        &EMPTY_DEPS
+    }
+
+    fn emit(&self, _id: NodeId, _ctx: &EmitCtx) -> Option<String> {
+        None
+    }
+
+    fn expr(&self, _id: NodeId, _ctx: &EmitCtx) -> String {
+        format!("{}u", self.value)
+    }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        typed(self.value_type)
+    }
+
+    fn constant_u32(&self) -> Option<u32> {
+        Some(self.value)
+    }
 }
 
 pub(crate) struct PbrShaderNode {
-    deps: [NodeId; 8],
+    deps: [NodeId; 15],
 }
 
 impl PbrShaderNode {
-    #[allow(clippy::too_many_arguments)]
-    pub fn new(
-        base_rgba: NodeId,
-        normal: NodeId,
-        roughness: NodeId,
-        metallic: NodeId,
-        alpha: NodeId,
-        lit: NodeId,
-        cast_shadows: NodeId,
-        grayscale: NodeId,
-    ) -> Self {
+    pub fn new(surface: PbrSurface) -> Self {
         Self {
             deps: [
-                base_rgba,
-                normal,
-                roughness,
-                metallic,
-                alpha,
-                lit,
-                cast_shadows,
-                grayscale,
+                surface.base_color,
+                surface.normal,
+                surface.roughness,
+                surface.metallic,
+                surface.alpha,
+                surface.lit,
+                surface.cast_shadows,
+                surface.grayscale,
+                surface.emissive,
+                surface.occlusion,
+                surface.alpha_cutoff,
+                surface.double_sided,
+                surface.clearcoat,
+                surface.clearcoat_roughness,
+                surface.transmission,
             ],
         }
     }
@@ -506,16 +824,10 @@ impl NodeChunk for PbrShaderNode {
     }
 
     fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
-        let base_rgba = ctx.expr(self.deps[0]);
-        let normal = ctx.expr(self.deps[1]);
-        let roughness = ctx.expr(self.deps[2]);
-        let metallic = ctx.expr(self.deps[3]);
-        let alpha = ctx.expr(self.deps[4]);
-        let lit = ctx.expr(self.deps[5]);
-        let cast_shadows = ctx.expr(self.deps[6]);
-        let grayscale = ctx.expr(self.deps[7]);
+        let args: Vec<String> = self.deps.iter().map(|&d| ctx.expr(d)).collect();
         Some(format!(
-            "let _pv{id}: FOutput = pbr_fragment(in, {base_rgba}, {normal}, {roughness}, {metallic}, {alpha}, {lit}, {cast_shadows}, {grayscale});"
+            "let _pv{id}: FOutput = pbr_fragment(in, front_facing, {});",
+            args.join(", ")
         ))
     }
 
@@ -540,6 +852,7 @@ impl NodeChunk for PickColorNode {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum MathOp {
     Add,
     Sub,
@@ -547,14 +860,34 @@ pub(crate) enum MathOp {
     Div,
 }
 
+impl MathOp {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            MathOp::Add => "add",
+            MathOp::Sub => "sub",
+            MathOp::Mul => "mul",
+            MathOp::Div => "div",
+        }
+    }
+}
+
 pub(crate) struct MathNode {
     deps: [NodeId; 2],
     op: MathOp,
+    value_type: Option<MaterialValueType>,
 }
 
 impl MathNode {
     pub fn new(a: NodeId, b: NodeId, op: MathOp) -> Self {
-        Self { deps: [a, b], op }
+        Self::typed(a, b, op, None)
+    }
+
+    pub fn typed(a: NodeId, b: NodeId, op: MathOp, value_type: Option<MaterialValueType>) -> Self {
+        Self {
+            deps: [a, b],
+            op,
+            value_type,
+        }
     }
 }
 
@@ -578,6 +911,10 @@ impl NodeChunk for MathNode {
         };
         format!("({a} {op} {b})")
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        vec![self.value_type]
+    }
 }
 
 pub(crate) struct FunctionCallNode {
@@ -612,13 +949,23 @@ impl NodeChunk for FunctionCallNode {
 pub(crate) struct SwizzleNode {
     deps: [NodeId; 1],
     component: String,
+    value_type: Option<MaterialValueType>,
 }
 
 impl SwizzleNode {
     pub fn new(id: NodeId, component: impl Into<String>) -> Self {
+        Self::typed(id, component, None)
+    }
+
+    pub fn typed(
+        id: NodeId,
+        component: impl Into<String>,
+        value_type: Option<MaterialValueType>,
+    ) -> Self {
         Self {
             deps: [id],
             component: component.into(),
+            value_type,
         }
     }
 }
@@ -636,4 +983,248 @@ impl NodeChunk for SwizzleNode {
         let expr = ctx.expr(self.deps[0]);
         format!("{}.{}", expr, self.component)
     }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        vec![self.value_type]
+    }
+}
+
+/// Scalar comparison used by [`MaterialCompiler::compare`](crate::MaterialCompiler::compare).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompareOp {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+impl CompareOp {
+    fn wgsl(self) -> &'static str {
+        match self {
+            CompareOp::Less => "<",
+            CompareOp::LessEqual => "<=",
+            CompareOp::Greater => ">",
+            CompareOp::GreaterEqual => ">=",
+            CompareOp::Equal => "==",
+            CompareOp::NotEqual => "!=",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum BuiltinOp {
+    Lerp,
+    Clamp,
+    Dot,
+    Cross,
+    Normalize,
+    Length,
+    Step,
+    Smoothstep,
+    Compare(CompareOp),
+    Select,
+    Construct,
+}
+
+impl BuiltinOp {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            BuiltinOp::Lerp => "lerp",
+            BuiltinOp::Clamp => "clamp",
+            BuiltinOp::Dot => "dot",
+            BuiltinOp::Cross => "cross",
+            BuiltinOp::Normalize => "normalize",
+            BuiltinOp::Length => "length",
+            BuiltinOp::Step => "step",
+            BuiltinOp::Smoothstep => "smoothstep",
+            BuiltinOp::Compare(_) => "compare",
+            BuiltinOp::Select => "select",
+            BuiltinOp::Construct => "construct",
+        }
+    }
+
+    fn wgsl_function(self) -> Option<&'static str> {
+        match self {
+            BuiltinOp::Lerp => Some("mix"),
+            BuiltinOp::Clamp => Some("clamp"),
+            BuiltinOp::Dot => Some("dot"),
+            BuiltinOp::Cross => Some("cross"),
+            BuiltinOp::Normalize => Some("normalize"),
+            BuiltinOp::Length => Some("length"),
+            BuiltinOp::Step => Some("step"),
+            BuiltinOp::Smoothstep => Some("smoothstep"),
+            BuiltinOp::Compare(_) | BuiltinOp::Select | BuiltinOp::Construct => None,
+        }
+    }
+}
+
+/// Operand of a [`BuiltinNode`]. `splat` widens a scalar operand to the given vector type.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BuiltinArg {
+    pub input: ExpressionInput,
+    pub splat: Option<MaterialValueType>,
+}
+
+impl BuiltinArg {
+    fn expr(&self, ctx: &EmitCtx) -> String {
+        let expr = self.input.expr(ctx);
+        match self.splat {
+            Some(ty) => format!("{}({expr})", ty.wgsl_type()),
+            None => expr,
+        }
+    }
+}
+
+/// A WGSL builtin or operator whose operand types were checked by the compiler.
+pub(crate) struct BuiltinNode {
+    args: Vec<BuiltinArg>,
+    deps: Vec<NodeId>,
+    op: BuiltinOp,
+    value_type: Option<MaterialValueType>,
+}
+
+impl BuiltinNode {
+    pub fn new(
+        op: BuiltinOp,
+        args: Vec<BuiltinArg>,
+        value_type: Option<MaterialValueType>,
+    ) -> Self {
+        Self {
+            deps: args.iter().map(|arg| arg.input.node()).collect(),
+            args,
+            op,
+            value_type,
+        }
+    }
+}
+
+impl NodeChunk for BuiltinNode {
+    fn deps(&self) -> &[NodeId] {
+        &self.deps
+    }
+
+    fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
+        let args: Vec<String> = self.args.iter().map(|arg| arg.expr(ctx)).collect();
+        let expr = match self.op {
+            BuiltinOp::Compare(op) => {
+                format!("select(0u, 1u, {} {} {})", args[0], op.wgsl(), args[1])
+            }
+            // WGSL's select takes the false value first
+            BuiltinOp::Select => format!("select({}, {}, {} != 0u)", args[2], args[1], args[0]),
+            BuiltinOp::Construct => {
+                let ty = self
+                    .value_type
+                    .map_or("vec4<f32>", MaterialValueType::wgsl_type);
+                format!("{ty}({})", args.join(", "))
+            }
+            op => format!(
+                "{}({})",
+                op.wgsl_function().unwrap_or_default(),
+                args.join(", ")
+            ),
+        };
+
+        Some(match self.value_type {
+            Some(ty) => format!("let _pv{id}: {} = {expr};", ty.wgsl_type()),
+            None => format!("let _pv{id} = {expr};"),
+        })
+    }
+
+    fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
+        format!("_pv{id}")
+    }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        vec![self.value_type]
+    }
+}
+
+/// Splits a vector into one `f32` output per component.
+pub(crate) struct SplitNode {
+    input: ExpressionInput,
+    deps: [NodeId; 1],
+    components: u32,
+}
+
+impl SplitNode {
+    pub fn new(input: ExpressionInput, components: u32) -> Self {
+        Self {
+            input,
+            deps: [input.node()],
+            components,
+        }
+    }
+}
+
+impl NodeChunk for SplitNode {
+    fn deps(&self) -> &[NodeId] {
+        &self.deps
+    }
+
+    fn emit(&self, id: NodeId, ctx: &EmitCtx) -> Option<String> {
+        let source = self.input.expr(ctx);
+        let mut lines = vec![format!("let _pv{id} = {source};")];
+        for (index, component) in ["x", "y", "z", "w"]
+            .iter()
+            .take(self.components as usize)
+            .enumerate()
+        {
+            lines.push(format!("let _pv{id}_{index}: f32 = _pv{id}.{component};"));
+        }
+        Some(lines.join("\n"))
+    }
+
+    fn expr(&self, id: NodeId, _ctx: &EmitCtx) -> String {
+        format!("_pv{id}_0")
+    }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        vec![Some(MaterialValueType::F32); self.components as usize]
+    }
+
+    fn output_expr(&self, id: NodeId, output_index: u32, _ctx: &EmitCtx) -> String {
+        debug_assert!(
+            output_index < self.components,
+            "split node {id} has no output {output_index}"
+        );
+        format!("_pv{id}_{output_index}")
+    }
+}
+
+/// Forwards a single output of a multi-output node, so it can be used wherever a
+/// plain [`NodeId`] is expected.
+pub(crate) struct NodeOutputNode {
+    input: ExpressionInput,
+    deps: [NodeId; 1],
+    value_type: Option<MaterialValueType>,
+}
+
+impl NodeOutputNode {
+    pub fn new(input: ExpressionInput, value_type: Option<MaterialValueType>) -> Self {
+        Self {
+            input,
+            deps: [input.node()],
+            value_type,
+        }
+    }
+}
+
+impl NodeChunk for NodeOutputNode {
+    fn deps(&self) -> &[NodeId] {
+        &self.deps
+    }
+
+    fn emit(&self, _id: NodeId, _ctx: &EmitCtx) -> Option<String> {
+        None
+    }
+
+    fn expr(&self, _id: NodeId, ctx: &EmitCtx) -> String {
+        self.input.expr(ctx)
+    }
+
+    fn output_types(&self) -> Vec<Option<MaterialValueType>> {
+        vec![self.value_type]
+    }
 }
@@ -1,27 +1,197 @@
 use crate::chunks::{
-    ConstantF32Node, EmitCtx, FunctionCallNode, MaterialBaseColorNode, MaterialInputNode,
-    MaterialMetallicNode, MaterialNormalNode, MaterialRoughnessNode, MaterialSamplerNode,
-    MaterialTextureNode, MathNode, MathOp, NodeChunk, NodeExpressionInput, PbrShaderNode,
-    PickColorNode, PostSurfaceSamplerNode, PostSurfaceTextureNode, RawChunk, SwizzleNode,
-    TextureSampleNode, VertexUvNode,
+    BuiltinArg, BuiltinNode, BuiltinOp, CompareOp, ConstantF32Node, ConstantU32Node, EmitCtx,
+    FunctionCallNode, MaterialBaseColorNode, MaterialEmissiveNode, MaterialInputNode,
+    MaterialMetallicNode, MaterialNormalNode, MaterialOcclusionNode, MaterialRoughnessNode,
+    MaterialSamplerNode, MaterialTextureNode, MathNode, MathOp, NodeChunk, NodeExpressionInput,
+    NodeOutputNode, PbrShaderNode, PickColorNode, PostSurfaceSamplerNode, PostSurfaceTextureNode,
+    RawChunk, SplitNode, StageValueNode, SwizzleNode, TextureSampleNode, UvTransformNode,
+    VertexUvNode,
 };
 use crate::function::{
     ExpressionInput, ExpressionTexture, MaterialExpression, MaterialPinType,
     PostProcessMaterialExpression,
 };
-use crate::generator::{MaterialShaderSetCode, MeshPass, ShaderCompilationOutput};
-use crate::{NodeId, ShaderGenerator};
-use glamx::Vec3;
+use crate::generator::{
+    MaterialShaderSetCode, MeshPass, ShaderCompilationOutput, VertexCompilationOutput,
+};
+use crate::value::MaterialValueType;
+use crate::{MaterialPermutation, NodeId, ShaderGenerator};
+use glamx::{Vec2, Vec3};
+use std::fmt;
 use syrillian_utils::debug_panic;
 
+/// A material graph that would not produce valid WGSL.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MaterialGraphError {
+    /// An input of `op` received a value of the wrong type.
+    InputType {
+        op: &'static str,
+        input: &'static str,
+        expected: &'static str,
+        found: MaterialValueType,
+    },
+    /// The operands of `op` can't be combined, e.g. a `vec2` with a `vec3`.
+    IncompatibleTypes {
+        op: &'static str,
+        a: MaterialValueType,
+        b: MaterialValueType,
+    },
+    /// A vector was constructed from the wrong number of components.
+    ComponentCount {
+        value_type: MaterialValueType,
+        found: u32,
+    },
+    /// An output index past the outputs of `node` was requested.
+    MissingOutput { node: NodeId, output_index: u32 },
+    /// `op` received the wrong number of inputs.
+    InputCount {
+        op: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// A graph node connects to a node that doesn't exist or doesn't come before it.
+    InvalidConnection {
+        node: u32,
+        target: u32,
+        output_index: u32,
+    },
+}
+
+impl fmt::Display for MaterialGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaterialGraphError::InputType {
+                op,
+                input,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{op}` expects {expected} for its `{input}` input, but got {found:?}"
+            ),
+            MaterialGraphError::IncompatibleTypes { op, a, b } => {
+                write!(f, "`{op}` cannot combine {a:?} with {b:?}")
+            }
+            MaterialGraphError::ComponentCount { value_type, found } => write!(
+                f,
+                "constructing {value_type:?} needs {} components, but got {found}",
+                value_type.component_count()
+            ),
+            MaterialGraphError::MissingOutput { node, output_index } => {
+                write!(f, "node {node} has no output {output_index}")
+            }
+            MaterialGraphError::InputCount {
+                op,
+                expected,
+                found,
+            } => write!(f, "`{op}` takes {expected} inputs, but got {found}"),
+            MaterialGraphError::InvalidConnection {
+                node,
+                target,
+                output_index,
+            } => write!(
+                f,
+                "node {node} connects to output {output_index} of node {target}, which isn't an earlier node"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MaterialGraphError {}
+
+/// Nodes feeding the PBR lighting function, see [`MaterialCompiler::pbr_surface`].
+#[derive(Clone, Copy, Debug)]
+pub struct PbrSurface {
+    pub base_color: NodeId,
+    pub normal: NodeId,
+    pub roughness: NodeId,
+    pub metallic: NodeId,
+    pub alpha: NodeId,
+    pub lit: NodeId,
+    pub cast_shadows: NodeId,
+    pub grayscale: NodeId,
+    pub emissive: NodeId,
+    pub occlusion: NodeId,
+    pub alpha_cutoff: NodeId,
+    pub double_sided: NodeId,
+    pub clearcoat: NodeId,
+    pub clearcoat_roughness: NodeId,
+    pub transmission: NodeId,
+}
+
+/// Nodes driving the generated vertex stage, see [`MaterialExpression::compile_vertex`].
+///
+/// Both are evaluated per vertex, so only vertex stage values, material inputs and
+/// math can feed them. Texture sampling is fragment-only.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaterialVertexOutput {
+    /// World space `vec3` added to the vertex position in every pass.
+    pub world_position_offset: Option<NodeId>,
+    /// World space `vec3` replacing the vertex normal. It's normalized for you.
+    pub normal: Option<NodeId>,
+}
+
+impl MaterialVertexOutput {
+    pub fn is_empty(&self) -> bool {
+        self.world_position_offset.is_none() && self.normal.is_none()
+    }
+}
+
 #[derive(Default)]
 pub struct MaterialCompiler {
     nodes: Vec<RawChunk>,
+    errors: Vec<MaterialGraphError>,
+    permutation: MaterialPermutation,
 }
 
 impl MaterialCompiler {
     pub fn new() -> Self {
-        Self { nodes: Vec::new() }
+        Self {
+            nodes: Vec::new(),
+            errors: Vec::new(),
+            permutation: MaterialPermutation::default(),
+        }
+    }
+
+    /// A compiler that bakes the `bool` and `u32` material inputs of `permutation`
+    /// into the shader instead of reading them from the material.
+    pub fn with_permutation(permutation: MaterialPermutation) -> Self {
+        Self {
+            permutation,
+            ..Self::new()
+        }
+    }
+
+    pub fn permutation(&self) -> &MaterialPermutation {
+        &self.permutation
+    }
+
+    /// Type errors found while building the graph so far.
+    pub fn errors(&self) -> &[MaterialGraphError] {
+        &self.errors
+    }
+
+    /// Records an error found by an expression, failing the compilation.
+    pub fn report(&mut self, error: MaterialGraphError) {
+        self.errors.push(error);
+    }
+
+    /// Number of outputs of `node`.
+    pub fn output_count(&self, node: NodeId) -> u32 {
+        self.nodes
+            .get(node as usize)
+            .map_or(0, |chunk| chunk.node.output_types().len() as u32)
+    }
+
+    /// Value type of an output of `node`, `None` if the node doesn't track its type.
+    pub fn output_type(&self, node: NodeId, output_index: u32) -> Option<MaterialValueType> {
+        self.nodes
+            .get(node as usize)?
+            .node
+            .output_types()
+            .get(output_index as usize)
+            .copied()
+            .flatten()
     }
 
     #[allow(dead_code)]
@@ -37,18 +207,66 @@ impl MaterialCompiler {
         self.allocate(VertexUvNode)
     }
 
-    pub fn input_value(&mut self, name: &'static str) -> NodeId {
-        self.allocate(MaterialInputNode::new(name))
+    /// World space position of the vertex, before any offset. Vertex stage only.
+    pub fn vertex_world_position(&mut self) -> NodeId {
+        self.allocate(StageValueNode::new(
+            "world_position",
+            MaterialValueType::Vec3,
+        ))
+    }
+
+    /// World space normal of the vertex. Vertex stage only.
+    pub fn vertex_world_normal(&mut self) -> NodeId {
+        self.allocate(StageValueNode::new("world_normal", MaterialValueType::Vec3))
+    }
+
+    /// Object space position of the vertex. Vertex stage only.
+    pub fn vertex_object_position(&mut self) -> NodeId {
+        self.allocate(StageValueNode::new("in.position", MaterialValueType::Vec3))
     }
 
-    pub fn bind_texture(&mut self, name: &'static str) -> NodeId {
+    /// World space normal interpolated from the vertex stage. Fragment stage only.
+    pub fn surface_normal(&mut self) -> NodeId {
+        self.allocate(StageValueNode::new("in.normal", MaterialValueType::Vec3))
+    }
+
+    /// Seconds since startup, from the render system uniform.
+    pub fn time(&mut self) -> NodeId {
+        self.allocate(StageValueNode::new("system.time", MaterialValueType::F32))
+    }
+
+    pub fn input_value(&mut self, name: impl Into<String>) -> NodeId {
+        self.allocate(MaterialInputNode::new(name, None))
+    }
+
+    /// Reads a material input. `bool` and `u32` inputs that are part of the
+    /// permutation become constants instead.
+    pub fn typed_input_value(
+        &mut self,
+        name: impl Into<String>,
+        value_type: MaterialValueType,
+    ) -> NodeId {
+        let name = name.into();
+        if !value_type.is_float()
+            && let Some(value) = self.permutation.get(&name)
+        {
+            return self.allocate(ConstantU32Node::new(value, value_type));
+        }
+        self.allocate(MaterialInputNode::new(name, Some(value_type)))
+    }
+
+    pub fn bind_texture(&mut self, name: impl Into<String>) -> NodeId {
         self.allocate(MaterialTextureNode::new(name))
     }
 
-    pub fn bind_sampler(&mut self, name: &'static str) -> NodeId {
+    pub fn bind_sampler(&mut self, name: impl Into<String>) -> NodeId {
         self.allocate(MaterialSamplerNode::new(name))
     }
 
+    pub fn texture_sample(&mut self, texture: NodeId, sampler: NodeId, uv: NodeId) -> NodeId {
+        self.allocate(TextureSampleNode::new(texture, sampler, uv))
+    }
+
     pub fn bind_expression_input<T: MaterialPinType>(&mut self, input: &mut ExpressionInput<T>) {
         if input.is_unbound() {
             debug_panic!(
@@ -59,7 +277,7 @@ impl MaterialCompiler {
         let Some(name) = input.material_name() else {
             return;
         };
-        let node = self.input_value(name);
+        let node = self.typed_input_value(name, T::VALUE_TYPE);
         input.set_bound(node, 0);
     }
 
@@ -140,6 +358,58 @@ impl MaterialCompiler {
         ))
     }
 
+    pub fn emissive(
+        &mut self,
+        uv: NodeId,
+        color: &ExpressionInput<Vec3>,
+        strength: &ExpressionInput<f32>,
+        use_texture: &ExpressionInput<bool>,
+        texture: &ExpressionTexture,
+    ) -> NodeId {
+        self.allocate(MaterialEmissiveNode::new(
+            NodeExpressionInput::new(uv, 0),
+            color.as_chunk_input(),
+            strength.as_chunk_input(),
+            use_texture.as_chunk_input(),
+            texture.texture_input(),
+            texture.sampler_input(),
+        ))
+    }
+
+    pub fn occlusion(
+        &mut self,
+        uv: NodeId,
+        strength: &ExpressionInput<f32>,
+        use_texture: &ExpressionInput<bool>,
+        texture: &ExpressionTexture,
+    ) -> NodeId {
+        self.allocate(MaterialOcclusionNode::new(
+            NodeExpressionInput::new(uv, 0),
+            strength.as_chunk_input(),
+            use_texture.as_chunk_input(),
+            texture.texture_input(),
+            texture.sampler_input(),
+        ))
+    }
+
+    /// Applies a `KHR_texture_transform` style offset, rotation and scale to `uv`.
+    pub fn uv_transform(
+        &mut self,
+        uv: NodeId,
+        offset: &ExpressionInput<Vec2>,
+        rotation: &ExpressionInput<f32>,
+        scale: &ExpressionInput<Vec2>,
+    ) -> NodeId {
+        self.allocate(UvTransformNode::new(
+            uv,
+            offset.node(),
+            rotation.node(),
+            scale.node(),
+        ))
+    }
+
+    /// Shades a surface that only provides the basic PBR inputs. Everything else
+    /// falls back to neutral values: no emission, no occlusion, no clearcoat.
     #[allow(clippy::too_many_arguments)]
     pub fn pbr_shader(
         &mut self,
@@ -152,7 +422,12 @@ impl MaterialCompiler {
         cast_shadows: NodeId,
         grayscale: NodeId,
     ) -> NodeId {
-        self.allocate(PbrShaderNode::new(
+        let zero = self.constant_f32(0.0);
+        let one = self.constant_f32(1.0);
+        let emissive = self.call("vec3f", vec![zero]);
+        let single_sided = self.call("u32", vec![zero]);
+
+        self.pbr_surface(PbrSurface {
             base_color,
             normal,
             roughness,
@@ -161,7 +436,18 @@ impl MaterialCompiler {
             lit,
             cast_shadows,
             grayscale,
-        ))
+            emissive,
+            occlusion: one,
+            alpha_cutoff: zero,
+            double_sided: single_sided,
+            clearcoat: zero,
+            clearcoat_roughness: zero,
+            transmission: zero,
+        })
+    }
+
+    pub fn pbr_surface(&mut self, surface: PbrSurface) -> NodeId {
+        self.allocate(PbrShaderNode::new(surface))
     }
 
     pub fn pick_color(&mut self) -> NodeId {
@@ -173,78 +459,605 @@ impl MaterialCompiler {
     }
 
     pub fn add(&mut self, a: NodeId, b: NodeId) -> NodeId {
-        self.allocate(MathNode::new(a, b, MathOp::Add))
+        self.math(a, b, MathOp::Add)
     }
 
     pub fn sub(&mut self, a: NodeId, b: NodeId) -> NodeId {
-        self.allocate(MathNode::new(a, b, MathOp::Sub))
+        self.math(a, b, MathOp::Sub)
     }
 
     pub fn mul(&mut self, a: NodeId, b: NodeId) -> NodeId {
-        self.allocate(MathNode::new(a, b, MathOp::Mul))
+        self.math(a, b, MathOp::Mul)
     }
 
     pub fn div(&mut self, a: NodeId, b: NodeId) -> NodeId {
-        self.allocate(MathNode::new(a, b, MathOp::Div))
+        self.math(a, b, MathOp::Div)
     }
 
+    /// Calls a WGSL function by name. The result is untyped and not checked.
     pub fn call(&mut self, name: impl Into<String>, args: Vec<NodeId>) -> NodeId {
         self.allocate(FunctionCallNode::new(name, args))
     }
 
     pub fn swizzle(&mut self, id: NodeId, component: impl Into<String>) -> NodeId {
-        self.allocate(SwizzleNode::new(id, component))
+        let component = component.into();
+        let source = self.output_type(id, 0);
+        let valid = !component.is_empty() && component.chars().all(|c| "xyzwrgba".contains(c));
+        let value_type = match source {
+            Some(ty) if !ty.is_vector() => {
+                self.errors.push(MaterialGraphError::InputType {
+                    op: "swizzle",
+                    input: "value",
+                    expected: "a vector",
+                    found: ty,
+                });
+                None
+            }
+            Some(_) if valid => MaterialValueType::float_with_components(component.len() as u32),
+            _ => None,
+        };
+        self.allocate(SwizzleNode::typed(id, component, value_type))
     }
 
+    /// Selects one output of a multi-output node, such as [`split`](Self::split).
+    pub fn output(&mut self, node: NodeId, output_index: u32) -> NodeId {
+        if output_index >= self.output_count(node) {
+            self.errors
+                .push(MaterialGraphError::MissingOutput { node, output_index });
+        }
+        let value_type = self.output_type(node, output_index);
+        self.allocate(NodeOutputNode::new(
+            NodeExpressionInput::new(node, output_index),
+            value_type,
+        ))
+    }
+
+    /// Splits a vector into one `f32` output per component, see [`output`](Self::output).
+    pub fn split(&mut self, vector: NodeId) -> NodeId {
+        let components = match self.output_type(vector, 0) {
+            Some(ty) if ty.is_vector() => ty.component_count(),
+            found => {
+                if let Some(found) = found {
+                    self.errors.push(MaterialGraphError::InputType {
+                        op: "split",
+                        input: "vector",
+                        expected: "a float vector",
+                        found,
+                    });
+                }
+                4
+            }
+        };
+        self.allocate(SplitNode::new(
+            NodeExpressionInput::new(vector, 0),
+            components,
+        ))
+    }
+
+    /// Builds a float vector out of scalars and smaller vectors, e.g. `vec4(rgb, alpha)`.
+    /// A single scalar is splatted across all components.
+    pub fn construct(&mut self, value_type: MaterialValueType, parts: &[NodeId]) -> NodeId {
+        if !value_type.is_vector() {
+            debug_panic!("construct only builds vectors, got {value_type:?}");
+        }
+
+        let mut known_components = Some(0);
+        for &part in parts {
+            match self.output_type(part, 0) {
+                Some(ty) if ty.is_float() => {
+                    known_components = known_components.map(|n| n + ty.component_count());
+                }
+                Some(found) => {
+                    self.errors.push(MaterialGraphError::InputType {
+                        op: "construct",
+                        input: "part",
+                        expected: "a float scalar or vector",
+                        found,
+                    });
+                }
+                None => known_components = None,
+            }
+        }
+        if let Some(found) = known_components
+            && found != value_type.component_count()
+            && !(found == 1 && parts.len() == 1)
+        {
+            self.errors
+                .push(MaterialGraphError::ComponentCount { value_type, found });
+        }
+
+        let args = parts
+            .iter()
+            .map(|&part| BuiltinArg {
+                input: NodeExpressionInput::new(part, 0),
+                splat: None,
+            })
+            .collect();
+        self.allocate(BuiltinNode::new(
+            BuiltinOp::Construct,
+            args,
+            Some(value_type),
+        ))
+    }
+
+    pub fn vec2(&mut self, x: NodeId, y: NodeId) -> NodeId {
+        self.construct(MaterialValueType::Vec2, &[x, y])
+    }
+
+    pub fn vec3(&mut self, x: NodeId, y: NodeId, z: NodeId) -> NodeId {
+        self.construct(MaterialValueType::Vec3, &[x, y, z])
+    }
+
+    pub fn vec4(&mut self, x: NodeId, y: NodeId, z: NodeId, w: NodeId) -> NodeId {
+        self.construct(MaterialValueType::Vec4, &[x, y, z, w])
+    }
+
+    /// Linear interpolation between `a` and `b` (WGSL `mix`). Scalars are widened to
+    /// match vector operands.
+    pub fn lerp(&mut self, a: NodeId, b: NodeId, t: NodeId) -> NodeId {
+        self.float_builtin(BuiltinOp::Lerp, &[("a", a), ("b", b), ("t", t)])
+    }
+
+    pub fn clamp(&mut self, value: NodeId, min: NodeId, max: NodeId) -> NodeId {
+        self.float_builtin(
+            BuiltinOp::Clamp,
+            &[("value", value), ("min", min), ("max", max)],
+        )
+    }
+
+    /// `0.0` where `x < edge`, `1.0` otherwise.
+    pub fn step(&mut self, edge: NodeId, x: NodeId) -> NodeId {
+        self.float_builtin(BuiltinOp::Step, &[("edge", edge), ("x", x)])
+    }
+
+    pub fn smoothstep(&mut self, edge0: NodeId, edge1: NodeId, x: NodeId) -> NodeId {
+        self.float_builtin(
+            BuiltinOp::Smoothstep,
+            &[("edge0", edge0), ("edge1", edge1), ("x", x)],
+        )
+    }
+
+    pub fn dot(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        let ty = self.matching_vectors(BuiltinOp::Dot, a, b, "a float vector");
+        self.builtin(BuiltinOp::Dot, &[a, b], ty.map(|_| MaterialValueType::F32))
+    }
+
+    pub fn cross(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        let ty = self.matching_vectors(BuiltinOp::Cross, a, b, "a vec3");
+        if let Some(found) = ty.filter(|ty| *ty != MaterialValueType::Vec3) {
+            self.errors.push(MaterialGraphError::InputType {
+                op: BuiltinOp::Cross.name(),
+                input: "a",
+                expected: "a vec3",
+                found,
+            });
+        }
+        self.builtin(BuiltinOp::Cross, &[a, b], Some(MaterialValueType::Vec3))
+    }
+
+    pub fn normalize(&mut self, vector: NodeId) -> NodeId {
+        let ty = self.output_type(vector, 0);
+        self.expect_type(BuiltinOp::Normalize, "vector", ty, "a float vector", |ty| {
+            ty.is_vector()
+        });
+        self.builtin(BuiltinOp::Normalize, &[vector], ty)
+    }
+
+    pub fn length(&mut self, value: NodeId) -> NodeId {
+        let ty = self.output_type(value, 0);
+        self.expect_type(
+            BuiltinOp::Length,
+            "value",
+            ty,
+            "a float scalar or vector",
+            MaterialValueType::is_float,
+        );
+        self.builtin(BuiltinOp::Length, &[value], Some(MaterialValueType::F32))
+    }
+
+    /// Compares two scalars. The result is a material `bool`, `1u` if the comparison holds.
+    pub fn compare(&mut self, op: CompareOp, a: NodeId, b: NodeId) -> NodeId {
+        let compare = BuiltinOp::Compare(op);
+        let a_ty = self.output_type(a, 0);
+        let b_ty = self.output_type(b, 0);
+        for (input, ty) in [("a", a_ty), ("b", b_ty)] {
+            self.expect_type(compare, input, ty, "a scalar", |ty| !ty.is_vector());
+        }
+        if let (Some(a_ty), Some(b_ty)) = (a_ty, b_ty)
+            && !a_ty.is_vector()
+            && !b_ty.is_vector()
+            && a_ty.is_float() != b_ty.is_float()
+        {
+            self.errors.push(MaterialGraphError::IncompatibleTypes {
+                op: compare.name(),
+                a: a_ty,
+                b: b_ty,
+            });
+        }
+        self.builtin(compare, &[a, b], Some(MaterialValueType::Bool))
+    }
+
+    pub fn less_than(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        self.compare(CompareOp::Less, a, b)
+    }
+
+    pub fn greater_than(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        self.compare(CompareOp::Greater, a, b)
+    }
+
+    /// Picks `if_true` when the material `bool` `condition` is set, `if_false` otherwise.
+    pub fn select(&mut self, condition: NodeId, if_true: NodeId, if_false: NodeId) -> NodeId {
+        let condition_ty = self.output_type(condition, 0);
+        self.expect_type(
+            BuiltinOp::Select,
+            "condition",
+            condition_ty,
+            "a bool or u32",
+            |ty| !ty.is_float(),
+        );
+
+        let true_ty = self.output_type(if_true, 0);
+        let false_ty = self.output_type(if_false, 0);
+        let value_type = match (true_ty, false_ty) {
+            (Some(a), Some(b)) if a.is_float() && b.is_float() => {
+                return self.float_builtin_with(
+                    BuiltinOp::Select,
+                    &[("if_true", if_true), ("if_false", if_false)],
+                    Some(condition),
+                );
+            }
+            (Some(a), Some(b)) if a != b => {
+                self.errors.push(MaterialGraphError::IncompatibleTypes {
+                    op: BuiltinOp::Select.name(),
+                    a,
+                    b,
+                });
+                None
+            }
+            (a, b) => a.or(b),
+        };
+        self.builtin(
+            BuiltinOp::Select,
+            &[condition, if_true, if_false],
+            value_type,
+        )
+    }
+
+    fn math(&mut self, a: NodeId, b: NodeId, op: MathOp) -> NodeId {
+        let a_ty = self.output_type(a, 0);
+        let b_ty = self.output_type(b, 0);
+        let value_type = match (a_ty, b_ty) {
+            (Some(a_ty), Some(b_ty)) if !a_ty.is_float() || !b_ty.is_float() => {
+                if a_ty.is_float() != b_ty.is_float() {
+                    self.errors.push(MaterialGraphError::IncompatibleTypes {
+                        op: op.name(),
+                        a: a_ty,
+                        b: b_ty,
+                    });
+                    None
+                } else {
+                    Some(MaterialValueType::U32)
+                }
+            }
+            _ => self.broadcast_type(op.name(), &[("a", a_ty), ("b", b_ty)]),
+        };
+        self.allocate(MathNode::typed(a, b, op, value_type))
+    }
+
+    /// Allocates a builtin whose float operands share one type, widening scalars
+    /// to the vector type when needed.
+    fn float_builtin(&mut self, op: BuiltinOp, operands: &[(&'static str, NodeId)]) -> NodeId {
+        self.float_builtin_with(op, operands, None)
+    }
+
+    fn float_builtin_with(
+        &mut self,
+        op: BuiltinOp,
+        operands: &[(&'static str, NodeId)],
+        condition: Option<NodeId>,
+    ) -> NodeId {
+        let types: Vec<_> = operands
+            .iter()
+            .map(|&(name, node)| (name, self.output_type(node, 0)))
+            .collect();
+        let value_type = self.broadcast_type(op.name(), &types);
+
+        let splat_target = value_type.filter(|ty| ty.is_vector());
+        let condition = condition.map(|node| BuiltinArg {
+            input: NodeExpressionInput::new(node, 0),
+            splat: None,
+        });
+        let args = condition
+            .into_iter()
+            .chain(operands.iter().zip(&types).map(|(&(_, node), &(_, ty))| {
+                let is_scalar = ty == Some(MaterialValueType::F32);
+                BuiltinArg {
+                    input: NodeExpressionInput::new(node, 0),
+                    splat: splat_target.filter(|_| is_scalar),
+                }
+            }))
+            .collect();
+        self.allocate(BuiltinNode::new(op, args, value_type))
+    }
+
+    fn builtin(
+        &mut self,
+        op: BuiltinOp,
+        operands: &[NodeId],
+        value_type: Option<MaterialValueType>,
+    ) -> NodeId {
+        let args = operands
+            .iter()
+            .map(|&node| BuiltinArg {
+                input: NodeExpressionInput::new(node, 0),
+                splat: None,
+            })
+            .collect();
+        self.allocate(BuiltinNode::new(op, args, value_type))
+    }
+
+    /// Resulting type of float operands that may mix scalars with one vector type.
+    /// `None` if an operand type is unknown and the result can't be inferred.
+    fn broadcast_type(
+        &mut self,
+        op: &'static str,
+        operands: &[(&'static str, Option<MaterialValueType>)],
+    ) -> Option<MaterialValueType> {
+        let mut vector: Option<MaterialValueType> = None;
+        let mut all_known = true;
+
+        for &(input, ty) in operands {
+            let Some(ty) = ty else {
+                all_known = false;
+                continue;
+            };
+            if !ty.is_float() {
+                self.errors.push(MaterialGraphError::InputType {
+                    op,
+                    input,
+                    expected: "a float scalar or vector",
+                    found: ty,
+                });
+                return None;
+            }
+            if !ty.is_vector() {
+                continue;
+            }
+            match vector {
+                Some(existing) if existing != ty => {
+                    self.errors.push(MaterialGraphError::IncompatibleTypes {
+                        op,
+                        a: existing,
+                        b: ty,
+                    });
+                    return None;
+                }
+                _ => vector = Some(ty),
+            }
+        }
+
+        vector.or(all_known.then_some(MaterialValueType::F32))
+    }
+
+    fn matching_vectors(
+        &mut self,
+        op: BuiltinOp,
+        a: NodeId,
+        b: NodeId,
+        expected: &'static str,
+    ) -> Option<MaterialValueType> {
+        let a_ty = self.output_type(a, 0);
+        let b_ty = self.output_type(b, 0);
+        self.expect_type(op, "a", a_ty, expected, |ty| ty.is_vector());
+        self.expect_type(op, "b", b_ty, expected, |ty| ty.is_vector());
+        if let (Some(a_ty), Some(b_ty)) = (a_ty, b_ty)
+            && a_ty != b_ty
+        {
+            self.errors.push(MaterialGraphError::IncompatibleTypes {
+                op: op.name(),
+                a: a_ty,
+                b: b_ty,
+            });
+        }
+        a_ty.or(b_ty)
+    }
+
+    fn expect_type(
+        &mut self,
+        op: BuiltinOp,
+        input: &'static str,
+        ty: Option<MaterialValueType>,
+        expected: &'static str,
+        accepts: impl Fn(MaterialValueType) -> bool,
+    ) {
+        if let Some(found) = ty
+            && !accepts(found)
+        {
+            self.errors.push(MaterialGraphError::InputType {
+                op: op.name(),
+                input,
+                expected,
+                found,
+            });
+        }
+    }
+
+    /// Compiles every pass of `material`.
+    ///
+    /// # Panics
+    /// If the material graph has type errors, see [`try_compile_shader_set`](Self::try_compile_shader_set).
     pub fn compile_shader_set<M: MaterialExpression>(material: &mut M) -> MaterialShaderSetCode {
-        let base = Self::compile_mesh(material, 0, MeshPass::Base);
-        let picking = Self::compile_mesh_picking_with_material(material, 0);
-        let shadow = Self::compile_mesh(material, 0, MeshPass::Shadow);
-        MaterialShaderSetCode {
+        Self::try_compile_shader_set(material)
+            .unwrap_or_else(|e| panic!("invalid material graph: {e}"))
+    }
+
+    pub fn try_compile_shader_set<M: MaterialExpression>(
+        material: &mut M,
+    ) -> Result<MaterialShaderSetCode, MaterialGraphError> {
+        let base = Self::try_compile_mesh(material, 0, MeshPass::Base)?;
+        let picking = Self::try_compile_mesh_picking_with_material(material, 0)?;
+        let shadow = Self::try_compile_mesh(material, 0, MeshPass::Shadow)?;
+
+        let mut compiler = Self::new();
+        material.bind_inputs(&mut compiler);
+        let custom_vertex = !material.compile_vertex(&mut compiler).is_empty();
+
+        Ok(MaterialShaderSetCode {
             base,
             picking,
             shadow,
-        }
+            custom_vertex,
+        })
     }
 
+    /// # Panics
+    /// If the material graph has type errors, see [`try_compile_mesh`](Self::try_compile_mesh).
     pub fn compile_mesh<M: MaterialExpression>(
         material: &mut M,
         output_index: u32,
         pass: MeshPass,
     ) -> String {
-        let mut compiler = Self::new();
+        Self::try_compile_mesh(material, output_index, pass)
+            .unwrap_or_else(|e| panic!("invalid material graph: {e}"))
+    }
+
+    pub fn try_compile_mesh<M: MaterialExpression>(
+        material: &mut M,
+        output_index: u32,
+        pass: MeshPass,
+    ) -> Result<String, MaterialGraphError> {
+        Self::try_compile_mesh_permutation(
+            material,
+            output_index,
+            pass,
+            &MaterialPermutation::new(),
+        )
+    }
+
+    /// # Panics
+    /// If the material graph has type errors, see
+    /// [`try_compile_mesh_permutation`](Self::try_compile_mesh_permutation).
+    pub fn compile_mesh_permutation<M: MaterialExpression>(
+        material: &mut M,
+        output_index: u32,
+        pass: MeshPass,
+        permutation: &MaterialPermutation,
+    ) -> String {
+        Self::try_compile_mesh_permutation(material, output_index, pass, permutation)
+            .unwrap_or_else(|e| panic!("invalid material graph: {e}"))
+    }
+
+    /// Compiles a variant of `material` specialized for the keys in `permutation`.
+    pub fn try_compile_mesh_permutation<M: MaterialExpression>(
+        material: &mut M,
+        output_index: u32,
+        pass: MeshPass,
+        permutation: &MaterialPermutation,
+    ) -> Result<String, MaterialGraphError> {
+        let mut compiler = Self::with_permutation(permutation.clone());
         material.bind_inputs(&mut compiler);
         let output = material.compile(&mut compiler, output_index);
-        let compiled = compiler.compile_output(output);
-        let position_only = pass != MeshPass::Base;
-        ShaderGenerator::build_mesh_shader(&compiled, pass, position_only)
+        let vertex_output = material.compile_vertex(&mut compiler);
+        let vertex = compiler.compile_vertex_output(vertex_output)?;
+        let compiled = compiler.compile_output(output)?;
+        // Offset vertices may read any attribute, so every pass needs the full vertex input
+        let position_only = pass != MeshPass::Base && vertex.is_none();
+        Ok(ShaderGenerator::build_mesh_shader(
+            &compiled,
+            vertex.as_ref(),
+            pass,
+            position_only,
+        ))
     }
 
     pub fn compile_mesh_picking() -> String {
         let mut compiler = Self::new();
         let output = compiler.pick_color();
-        let compiled = compiler.compile_output(output);
-        ShaderGenerator::build_mesh_shader(&compiled, MeshPass::Picking, true)
+        let compiled = compile_output_from_nodes(&compiler.nodes, output);
+        ShaderGenerator::build_mesh_shader(&compiled, None, MeshPass::Picking, true)
     }
 
+    /// # Panics
+    /// If the material graph has type errors, see
+    /// [`try_compile_mesh_picking_with_material`](Self::try_compile_mesh_picking_with_material).
     pub fn compile_mesh_picking_with_material<M: MaterialExpression>(
         material: &mut M,
         output_index: u32,
     ) -> String {
+        Self::try_compile_mesh_picking_with_material(material, output_index)
+            .unwrap_or_else(|e| panic!("invalid material graph: {e}"))
+    }
+
+    pub fn try_compile_mesh_picking_with_material<M: MaterialExpression>(
+        material: &mut M,
+        output_index: u32,
+    ) -> Result<String, MaterialGraphError> {
         let mut compiler = Self::new();
         material.bind_inputs(&mut compiler);
 
         let material_output = material.compile(&mut compiler, output_index);
+        let vertex_output = material.compile_vertex(&mut compiler);
         let pick_output = compiler.pick_color();
 
-        let compiled_material = compiler.compile_output(material_output);
-        let compiled_pick = compiler.compile_output(pick_output);
+        let vertex = compiler.compile_vertex_output(vertex_output)?;
+        let compiled_material = compiler.compile_output(material_output)?;
+        let compiled_pick = compiler.compile_output(pick_output)?;
 
-        ShaderGenerator::build_mesh_picking_shader(&compiled_pick, &compiled_material, false)
+        Ok(ShaderGenerator::build_mesh_picking_shader(
+            &compiled_pick,
+            &compiled_material,
+            vertex.as_ref(),
+            false,
+        ))
     }
 
-    fn compile_output(&self, output: NodeId) -> ShaderCompilationOutput {
-        compile_output_from_nodes(&self.nodes, output)
+    fn compile_vertex_output(
+        &mut self,
+        output: MaterialVertexOutput,
+    ) -> Result<Option<VertexCompilationOutput>, MaterialGraphError> {
+        if output.is_empty() {
+            return Ok(None);
+        }
+
+        for (input, node) in [
+            ("world_position_offset", output.world_position_offset),
+            ("normal", output.normal),
+        ] {
+            if let Some(found) = node.and_then(|node| self.output_type(node, 0))
+                && found != MaterialValueType::Vec3
+            {
+                self.errors.push(MaterialGraphError::InputType {
+                    op: "vertex output",
+                    input,
+                    expected: "a vec3",
+                    found,
+                });
+            }
+        }
+        if let Some(error) = self.errors.first() {
+            return Err(error.clone());
+        }
+
+        let roots: Vec<NodeId> = [output.world_position_offset, output.normal]
+            .into_iter()
+            .flatten()
+            .collect();
+        let ctx = EmitCtx::new(&self.nodes);
+        Ok(Some(VertexCompilationOutput {
+            lines: emit_lines(&self.nodes, &roots),
+            world_position_offset: output.world_position_offset.map(|node| ctx.expr(node)),
+            normal: output.normal.map(|node| ctx.expr(node)),
+        }))
+    }
+
+    fn compile_output(
+        &self,
+        output: NodeId,
+    ) -> Result<ShaderCompilationOutput, MaterialGraphError> {
+        if let Some(error) = self.errors.first() {
+            return Err(error.clone());
+        }
+        Ok(compile_output_from_nodes(&self.nodes, output))
     }
 }
 
@@ -336,24 +1149,22 @@ impl PostProcessCompiler {
 }
 
 fn compile_output_from_nodes(nodes: &[RawChunk], output: NodeId) -> ShaderCompilationOutput {
-    let order = topo_order_from_nodes(nodes, output);
-    let ctx = EmitCtx::new(nodes);
-    let mut lines = Vec::new();
-
-    for id in order {
-        let chunk = &nodes[id as usize];
-        if let Some(stmt) = chunk.node.emit(id, &ctx) {
-            lines.push(stmt);
-        }
-    }
-
     ShaderCompilationOutput {
-        lines,
-        result_expr: ctx.expr(output),
+        lines: emit_lines(nodes, std::slice::from_ref(&output)),
+        result_expr: EmitCtx::new(nodes).expr(output),
     }
 }
 
-fn topo_order_from_nodes(nodes: &[RawChunk], output: NodeId) -> Vec<NodeId> {
+/// Statements of every node the `outputs` depend on, each node emitted once.
+fn emit_lines(nodes: &[RawChunk], outputs: &[NodeId]) -> Vec<String> {
+    let ctx = EmitCtx::new(nodes);
+    topo_order_from_nodes(nodes, outputs)
+        .into_iter()
+        .filter_map(|id| nodes[id as usize].node.emit(id, &ctx))
+        .collect()
+}
+
+fn topo_order_from_nodes(nodes: &[RawChunk], outputs: &[NodeId]) -> Vec<NodeId> {
     let mut visited = vec![false; nodes.len()];
     let mut order = Vec::new();
 
@@ -370,6 +1181,8 @@ fn topo_order_from_nodes(nodes: &[RawChunk], output: NodeId) -> Vec<NodeId> {
         order.push(id);
     }
 
-    dfs(output, nodes, &mut visited, &mut order);
+    for &output in outputs {
+        dfs(output, nodes, &mut visited, &mut order);
+    }
     order
 }
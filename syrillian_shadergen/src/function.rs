@@ -1,6 +1,6 @@
 use crate::MaterialCompiler;
 use crate::chunks::{NodeExpressionInput as ChunkInput, NodeId};
-use crate::compiler::PostProcessCompiler;
+use crate::compiler::{MaterialVertexOutput, PbrSurface, PostProcessCompiler};
 use crate::value::MaterialValueType;
 use glamx::{Vec2, Vec3, Vec4};
 use std::marker::PhantomData;
@@ -272,6 +272,12 @@ pub trait MaterialExpression {
     fn bind_inputs(&mut self, _compiler: &mut MaterialCompiler) {}
     fn outputs(&self) -> Vec<MaterialExpressionValue>;
     fn compile(&self, compiler: &mut MaterialCompiler, output_index: u32) -> NodeId;
+
+    /// Builds the vertex stage outputs. The default keeps the mesh's own positions and
+    /// normals, which selects the fixed vertex shader.
+    fn compile_vertex(&self, _compiler: &mut MaterialCompiler) -> MaterialVertexOutput {
+        MaterialVertexOutput::default()
+    }
 }
 
 pub trait PostProcessMaterialExpression {
@@ -295,6 +301,21 @@ pub struct PbrShader {
     pub lit: ExpressionInput<bool>,
     pub cast_shadows: ExpressionInput<bool>,
     pub grayscale_diffuse: ExpressionInput<bool>,
+    pub emissive: ExpressionInput<Vec3>,
+    pub emissive_strength: ExpressionInput<f32>,
+    pub use_emissive_texture: ExpressionInput<bool>,
+    pub emissive_texture: ExpressionTexture,
+    pub occlusion_strength: ExpressionInput<f32>,
+    pub use_occlusion_texture: ExpressionInput<bool>,
+    pub occlusion_texture: ExpressionTexture,
+    pub alpha_cutoff: ExpressionInput<f32>,
+    pub double_sided: ExpressionInput<bool>,
+    pub clearcoat: ExpressionInput<f32>,
+    pub clearcoat_roughness: ExpressionInput<f32>,
+    pub transmission: ExpressionInput<f32>,
+    pub uv_offset: ExpressionInput<Vec2>,
+    pub uv_rotation: ExpressionInput<f32>,
+    pub uv_scale: ExpressionInput<Vec2>,
 }
 
 impl Default for PbrShader {
@@ -314,6 +335,21 @@ impl Default for PbrShader {
             lit: ExpressionInput::material("lit"),
             cast_shadows: ExpressionInput::material("cast_shadows"),
             grayscale_diffuse: ExpressionInput::material("grayscale_diffuse"),
+            emissive: ExpressionInput::material("emissive"),
+            emissive_strength: ExpressionInput::material("emissive_strength"),
+            use_emissive_texture: ExpressionInput::material("use_emissive_texture"),
+            emissive_texture: ExpressionTexture::material("emissive"),
+            occlusion_strength: ExpressionInput::material("occlusion_strength"),
+            use_occlusion_texture: ExpressionInput::material("use_occlusion_texture"),
+            occlusion_texture: ExpressionTexture::material("occlusion"),
+            alpha_cutoff: ExpressionInput::material("alpha_cutoff"),
+            double_sided: ExpressionInput::material("double_sided"),
+            clearcoat: ExpressionInput::material("clearcoat"),
+            clearcoat_roughness: ExpressionInput::material("clearcoat_roughness"),
+            transmission: ExpressionInput::material("transmission"),
+            uv_offset: ExpressionInput::material("uv_offset"),
+            uv_rotation: ExpressionInput::material("uv_rotation"),
+            uv_scale: ExpressionInput::material("uv_scale"),
         }
     }
 }
@@ -334,6 +370,21 @@ impl MaterialExpression for PbrShader {
         self.lit.bind(compiler);
         self.cast_shadows.bind(compiler);
         self.grayscale_diffuse.bind(compiler);
+        self.emissive.bind(compiler);
+        self.emissive_strength.bind(compiler);
+        self.use_emissive_texture.bind(compiler);
+        self.emissive_texture.bind(compiler);
+        self.occlusion_strength.bind(compiler);
+        self.use_occlusion_texture.bind(compiler);
+        self.occlusion_texture.bind(compiler);
+        self.alpha_cutoff.bind(compiler);
+        self.double_sided.bind(compiler);
+        self.clearcoat.bind(compiler);
+        self.clearcoat_roughness.bind(compiler);
+        self.transmission.bind(compiler);
+        self.uv_offset.bind(compiler);
+        self.uv_rotation.bind(compiler);
+        self.uv_scale.bind(compiler);
     }
 
     fn outputs(&self) -> Vec<MaterialExpressionValue> {
@@ -346,7 +397,13 @@ impl MaterialExpression for PbrShader {
     fn compile(&self, compiler: &mut MaterialCompiler, output_index: u32) -> NodeId {
         debug_assert_eq!(output_index, 0, "output_index must be 0 for PBR shader");
 
-        let uv = compiler.vertex_uv();
+        let vertex_uv = compiler.vertex_uv();
+        let uv = compiler.uv_transform(
+            vertex_uv,
+            &self.uv_offset,
+            &self.uv_rotation,
+            &self.uv_scale,
+        );
         let base_color = compiler.base_color(
             uv,
             &self.diffuse,
@@ -366,20 +423,36 @@ impl MaterialExpression for PbrShader {
             &self.use_metallic_texture,
             &self.roughness_texture,
         );
-        let alpha = self.alpha.node();
-        let lit = self.lit.node();
-        let cast_shadows = self.cast_shadows.node();
-        let grayscale = self.grayscale_diffuse.node();
-        compiler.pbr_shader(
+        let emissive = compiler.emissive(
+            uv,
+            &self.emissive,
+            &self.emissive_strength,
+            &self.use_emissive_texture,
+            &self.emissive_texture,
+        );
+        let occlusion = compiler.occlusion(
+            uv,
+            &self.occlusion_strength,
+            &self.use_occlusion_texture,
+            &self.occlusion_texture,
+        );
+        compiler.pbr_surface(PbrSurface {
             base_color,
             normal,
             roughness,
             metallic,
-            alpha,
-            lit,
-            cast_shadows,
-            grayscale,
-        )
+            alpha: self.alpha.node(),
+            lit: self.lit.node(),
+            cast_shadows: self.cast_shadows.node(),
+            grayscale: self.grayscale_diffuse.node(),
+            emissive,
+            occlusion,
+            alpha_cutoff: self.alpha_cutoff.node(),
+            double_sided: self.double_sided.node(),
+            clearcoat: self.clearcoat.node(),
+            clearcoat_roughness: self.clearcoat_roughness.node(),
+            transmission: self.transmission.node(),
+        })
     }
 }
 
@@ -0,0 +1,145 @@
+// Projects the default material onto the opaque surfaces inside a unit box.
+// The box projects along its local -Z, positions are rebuilt from the scene depth.
+
+struct DecalData {
+    transform: mat4x4<f32>,
+    inv_transform: mat4x4<f32>,
+    // cosines of the angles where the fade starts and ends
+    angle_fade: vec2<f32>,
+    layer_mask: u32,
+    _pad0: u32,
+}
+
+struct DecalVOut {
+    @builtin(position) clip: vec4<f32>,
+    @location(0) ndc: vec4<f32>,
+}
+
+@group(1) @binding(0) var<uniform> decal: DecalData;
+
+@group(5) @binding(0) var scene_depth: texture_depth_2d;
+@group(5) @binding(1) var scene_normal: texture_2d<f32>;
+@group(5) @binding(2) var scene_material: texture_2d<f32>;
+
+// corner bits of the 12 outward facing triangles, x = 1, y = 2, z = 4
+const DECAL_CUBE_CORNERS = array<u32, 36>(
+    1u, 3u, 7u, 1u, 7u, 5u,
+    0u, 4u, 6u, 0u, 6u, 2u,
+    2u, 6u, 7u, 2u, 7u, 3u,
+    0u, 1u, 5u, 0u, 5u, 4u,
+    4u, 5u, 7u, 4u, 7u, 6u,
+    0u, 2u, 3u, 0u, 3u, 1u,
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vid: u32) -> DecalVOut {
+    let corner = DECAL_CUBE_CORNERS[vid];
+    let local = vec3<f32>(
+        f32(corner & 1u),
+        f32((corner >> 1u) & 1u),
+        f32((corner >> 2u) & 1u),
+    ) - 0.5;
+
+    var out: DecalVOut;
+    out.clip = camera.view_proj_mat * decal.transform * vec4<f32>(local, 1.0);
+    out.ndc = out.clip;
+    return out;
+}
+
+@fragment
+fn fs_main(in: DecalVOut) -> FOutput {
+    let pixel = vec2<i32>(in.clip.xy);
+    let depth = textureLoad(scene_depth, pixel, 0);
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    let surface_material = textureLoad(scene_material, pixel, 0);
+    // Shaders without a model group may leave anything in the layer channel
+    let layer = min(u32(round(surface_material.b * 255.0)), 31u);
+    if ((decal.layer_mask & (1u << layer)) == 0u) {
+        discard;
+    }
+
+    let ndc = in.ndc.xy / in.ndc.w;
+    let world_h = camera.inv_view_proj_mat * vec4<f32>(ndc, depth, 1.0);
+    let world = world_h.xyz / world_h.w;
+    let local = (decal.inv_transform * vec4<f32>(world, 1.0)).xyz;
+    if (any(abs(local) > vec3<f32>(0.5))) {
+        discard;
+    }
+
+    // Surfaces facing away from the projector fade out
+    let N = safe_normalize(oct_decode(textureLoad(scene_normal, pixel, 0).xy));
+    let forward = safe_normalize(decal.transform[2].xyz);
+    let fade = smoothstep(decal.angle_fade.y, decal.angle_fade.x, dot(N, forward));
+    if (fade <= 0.0) {
+        discard;
+    }
+
+    let right = decal.transform[0].xyz;
+    let T = safe_normalize(right - N * dot(N, right));
+    let B = cross(N, T);
+
+    let box_uv = vec2<f32>(local.x + 0.5, 0.5 - local.y);
+    let uv = transform_uv(box_uv, material.uv_offset, material.uv_rotation, material.uv_scale);
+
+    var base_rgba = vec4<f32>(material.diffuse, 1.0);
+    if (material.use_diffuse_texture != 0u) {
+        base_rgba = textureSample(t_diffuse, s_diffuse, uv);
+    }
+
+    var normal = N;
+    if (material.use_normal_texture != 0u) {
+        normal = normal_from_map(t_normal, s_normal, uv, N, vec4<f32>(T, 1.0), B);
+    }
+
+    let roughness_sample = textureSample(t_roughness, s_roughness, uv);
+    var roughness = material.roughness;
+    if (material.use_roughness_texture != 0u) {
+        roughness *= roughness_sample.g;
+    }
+    var metallic = material.metallic;
+    if (material.use_metallic_texture != 0u) {
+        metallic *= roughness_sample.b;
+    }
+
+    var emissive = material.emissive * material.emissive_strength;
+    if (material.use_emissive_texture != 0u) {
+        emissive *= textureSample(t_emissive, s_emissive, uv).rgb;
+    }
+
+    var surface: FInput;
+    surface.clip = in.clip;
+    surface.uv = uv;
+    surface.position = world;
+    surface.normal = N;
+    surface.tangent = vec4<f32>(T, 1.0);
+    surface.bitangent = B;
+
+    var out = pbr_fragment(
+        surface,
+        true,
+        base_rgba,
+        normal,
+        roughness,
+        metallic,
+        material.alpha,
+        material.lit,
+        material.cast_shadows,
+        material.grayscale_diffuse,
+        emissive,
+        1.0,
+        material.alpha_cutoff,
+        0u,
+        material.clearcoat,
+        material.clearcoat_roughness,
+        0.0
+    );
+
+    let coverage = saturate(base_rgba.a * material.alpha) * fade;
+    out.out_color.a = coverage;
+    out.out_normal.a = coverage;
+    out.out_material.a = coverage;
+    return out;
+}
@@ -1,13 +1,24 @@
 const AMBIENT_STRENGTH: f32 = 0.0;
 const IBL_STRENGTH: f32 = 1.0;
 const EPS: f32 = 1e-7;
+const CLEARCOAT_F0: f32 = 0.04;
+
+// KHR_texture_transform: scale, then rotate, then offset
+fn transform_uv(uv: vec2<f32>, offset: vec2<f32>, rotation: f32, scale: vec2<f32>) -> vec2<f32> {
+    let s = sin(rotation);
+    let c = cos(rotation);
+    let p = uv * scale;
+    return offset + vec2<f32>(c * p.x + s * p.y, -s * p.x + c * p.y);
+}
 
 // fetch tangent-space normal and bring it to world space with a proper tbn
+// z is rebuilt from xy, so two channel formats (BC5, EAC RG11) work as well
 fn normal_from_map(
     tex: texture_2d<f32>, samp: sampler, uv: vec2<f32>,
     N: vec3<f32>, T: vec4<f32>, B: vec3<f32>
 ) -> vec3<f32> {
-    let n_ts = textureSample(tex, samp, uv).xyz * 2.0 - 1.0; // [-1..1]
+    let xy = textureSample(tex, samp, uv).xy * 2.0 - 1.0; // [-1..1]
+    let n_ts = vec3<f32>(xy, sqrt(max(1.0 - dot(xy, xy), 0.0)));
     let TBN = mat3x3<f32>(T.xyz, B, N);
     return safe_normalize(TBN * n_ts);
 }
@@ -38,9 +49,14 @@ fn diffuse_lambert(base: vec3<f32>) -> vec3<f32> {
     return base / PI;
 }
 
+// coat.x = clearcoat factor, coat.y = clearcoat roughness
+fn clearcoat_fresnel(cosTheta: f32, coat: vec2<f32>) -> f32 {
+    return fresnel_schlick(cosTheta, vec3<f32>(CLEARCOAT_F0)).x * coat.x;
+}
+
 fn brdf_term(
     N: vec3<f32>, V: vec3<f32>, L: vec3<f32>,
-    base: vec3<f32>, metallic: f32, roughness: f32
+    base: vec3<f32>, metallic: f32, roughness: f32, coat: vec2<f32>
 ) -> vec3<f32> {
     let a = roughness * roughness;
 
@@ -64,7 +80,14 @@ fn brdf_term(
     let kD = (vec3<f32>(1.0) - F) * (1.0 - metallic);
     let diff = diffuse_lambert(base) * kD * NdotL;
 
-    return diff + spec;
+    if (coat.x <= 0.0) { return diff + spec; }
+
+    // Clearcoat: a dielectric GGX lobe on top that attenuates the base layer
+    let ca = max(coat.y * coat.y, 0.002);
+    let Fc = clearcoat_fresnel(LdotH, coat);
+    let coat_spec = Fc * D_ggx(NdotH, ca) * V_smith_ggx_correlated(NdotV, NdotL, ca) * NdotL;
+
+    return (diff + spec) * (1.0 - Fc) + vec3<f32>(coat_spec);
 }
 
 fn fresnel_schlick_roughness(NdotV: f32, F0: vec3<f32>, roughness: f32) -> vec3<f32> {
@@ -76,21 +99,49 @@ fn fresnel_schlick_roughness(NdotV: f32, F0: vec3<f32>, roughness: f32) -> vec3<
     return F0 + (F90 - F0) * x5;
 }
 
-fn env_brdf_approx(roughness: f32, NdotV: f32) -> vec2<f32> {
-    let c0 = vec4<f32>(-1.0, -0.0275, -0.572, 0.022);
-    let c1 = vec4<f32>( 1.0,  0.0425,  1.04, -0.04);
-    let r = roughness * c0 + c1;
+// Split sum scale and bias to F0, integrated for the GGX specular lobe
+fn env_brdf(roughness: f32, NdotV: f32) -> vec2<f32> {
+    return textureSampleLevel(brdf_lut, ibl_sampler, vec2<f32>(NdotV, roughness), 0.0).rg;
+}
 
-    let a004 = min(r.x * r.x, exp2(-9.28 * saturate(NdotV))) * r.x + r.y;
-    return vec2<f32>(-1.04, 1.04) * a004 + r.zw;
+// Prefiltered specular radiance along `R`, blended from the reflection probes around `P`.
+// Whatever the probes don't cover falls back to the sky.
+fn specular_environment(P: vec3<f32>, R: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let sky_mip = roughness * f32(textureNumLevels(prefiltered_map) - 1u);
+    let probe_mip = roughness * f32(textureNumLevels(reflection_probe_maps) - 1u);
+
+    var color = vec3<f32>(0.0);
+    var weight = 0.0;
+    let count = min(reflection_probes.count, MAX_REFLECTION_PROBES);
+    for (var i: u32 = 0u; i < count; i = i + 1u) {
+        let probe = reflection_probes.probes[i];
+        let w = reflection_probe_weight(probe, P);
+        if (w <= 0.0) {
+            continue;
+        }
+
+        let dir = reflection_probe_dir(probe, P, R);
+        let probe_color = textureSampleLevel(reflection_probe_maps, ibl_sampler, dir, i, probe_mip).rgb;
+        color += probe_color * probe.intensity * w;
+        weight += w;
+    }
+
+    if (weight > 1.0) {
+        return color / weight;
+    }
+
+    let sky = textureSampleLevel(prefiltered_map, ibl_sampler, R, sky_mip).rgb;
+    return color + sky * (1.0 - weight);
 }
 
 fn ibl_term(
+    P: vec3<f32>,
     N: vec3<f32>,
     V: vec3<f32>,
     base: vec3<f32>,
     metallic: f32,
-    roughness: f32
+    roughness: f32,
+    coat: vec2<f32>
 ) -> vec3<f32> {
     let Nn = normalize(N);
     let Vn = normalize(V);
@@ -103,23 +154,26 @@ fn ibl_term(
     let Fd = fresnel_schlick_roughness(NdotV, F0, perceptual_roughness);
     let kD = (vec3<f32>(1.0) - Fd) * (1.0 - metallic);
 
-    let mip_count = f32(textureNumLevels(skybox_map));
-    let max_mip = max(mip_count - 1.0, 0.0);
-
-    let diffuse_lod = max(max_mip - 2.0, 0.0);
-    let env_diffuse = textureSampleLevel(skybox_map, skybox_sampler, Nn, diffuse_lod).rgb;
+    let env_diffuse = textureSampleLevel(irradiance_map, ibl_sampler, Nn, 0.0).rgb;
 
     let R = reflect(-Vn, Nn);
-    let spec_lod = (perceptual_roughness * perceptual_roughness) * max_mip;
-    let prefiltered = textureSampleLevel(skybox_map, skybox_sampler, R, spec_lod).rgb;
+    let prefiltered = specular_environment(P, R, perceptual_roughness);
 
-    let brdf = env_brdf_approx(perceptual_roughness, NdotV);
+    let brdf = env_brdf(perceptual_roughness, NdotV);
     let specular = prefiltered * (F0 * brdf.x + brdf.y);
 
-    // if using real irradiance later, this becomes straight passthrough
+    // The irradiance map is already convolved with the cosine lobe
     let diffuse = env_diffuse * base * kD;
 
-    return (diffuse + specular) * IBL_STRENGTH;
+    if (coat.x <= 0.0) { return (diffuse + specular) * IBL_STRENGTH; }
+
+    let coat_roughness = clamp(coat.y, 0.04, 1.0);
+    let Fc = clearcoat_fresnel(NdotV, coat);
+    let coat_env = specular_environment(P, R, coat_roughness);
+    let coat_brdf = env_brdf(coat_roughness, NdotV);
+    let coat_spec = coat_env * (CLEARCOAT_F0 * coat_brdf.x + coat_brdf.y) * coat.x;
+
+    return ((diffuse + specular) * (1.0 - Fc) + coat_spec) * IBL_STRENGTH;
 }
 
 fn eval_sky_sun(
@@ -127,7 +181,8 @@ fn eval_sky_sun(
     V: vec3<f32>,
     base: vec3<f32>,
     metallic: f32,
-    roughness: f32
+    roughness: f32,
+    coat: vec2<f32>
 ) -> vec3<f32> {
     let strength = max(sky.sun_strength, 0.0);
     if (strength <= 0.0) { return vec3<f32>(0.0); }
@@ -142,7 +197,7 @@ fn eval_sky_sun(
 
     let sun_rgb = sky_sun_color_base(L) * T_sun;
 
-    let brdf = brdf_term(N, V, L, base, metallic, roughness);
+    let brdf = brdf_term(N, V, L, base, metallic, roughness, coat);
     let radiance = sun_rgb * strength;
     return brdf * radiance;
 }
@@ -277,7 +332,7 @@ fn shadow_visibility_point(
 
 fn eval_spot(
     in_pos: vec3<f32>, N: vec3<f32>, V: vec3<f32>,
-    base: vec3<f32>, metallic: f32, roughness: f32, light: Light, cast_shadows: bool
+    base: vec3<f32>, metallic: f32, roughness: f32, coat: vec2<f32>, light: Light, cast_shadows: bool
 ) -> vec3<f32> {
     let toL = light.position - in_pos;
     let dist_sq = dot(toL, toL);
@@ -303,14 +358,14 @@ fn eval_spot(
     if (NdotL <= 0.0) { return vec3<f32>(0.0); }
 
     let vis = shadow_visibility_spot_fast(in_pos, N, L, light, cast_shadows);
-    let brdf = brdf_term(N, V, L, base, metallic, roughness);
+    let brdf = brdf_term(N, V, L, base, metallic, roughness, coat);
     let radiance = light.color * (light.intensity * geom_att) * spot * vis;
     return brdf * radiance;
 }
 
 fn eval_point(
     in_pos: vec3<f32>, N: vec3<f32>, V: vec3<f32>,
-    base: vec3<f32>, metallic: f32, roughness: f32, light: Light, cast_shadows: bool
+    base: vec3<f32>, metallic: f32, roughness: f32, coat: vec2<f32>, light: Light, cast_shadows: bool
 ) -> vec3<f32> {
     let toL = light.position - in_pos;
     let dist_sq = dot(toL, toL);
@@ -334,13 +389,14 @@ fn eval_point(
     if (NdotL <= 0.0) { return vec3<f32>(0.0); }
 
     let vis = shadow_visibility_point(in_pos, N, L, light, cast_shadows);
-    let brdf = brdf_term(N, V, L, base, metallic, roughness);
+    let brdf = brdf_term(N, V, L, base, metallic, roughness, coat);
     let radiance = light.color * (light.intensity * geom_att) * vis;
     return brdf * radiance;
 }
 
 fn pbr_fragment(
     in: FInput,
+    front_facing: bool,
     base_rgba: vec4<f32>,
     normal_in: vec3<f32>,
     roughness_in: f32,
@@ -348,17 +404,38 @@ fn pbr_fragment(
     alpha_in: f32,
     lit: u32,
     cast_shadows: u32,
-    grayscale_diffuse: u32
+    grayscale_diffuse: u32,
+    emissive: vec3<f32>,
+    occlusion: f32,
+    alpha_cutoff: f32,
+    double_sided: u32,
+    clearcoat: f32,
+    clearcoat_roughness: f32,
+    transmission: f32
 ) -> FOutput {
     var out: FOutput;
 
+    var alpha = base_rgba.a * alpha_in;
+    if (alpha_cutoff > 0.0) {
+        if (alpha < alpha_cutoff) {
+            discard;
+        }
+        alpha = 1.0;
+    }
+
     let base = saturate(base_rgba.rgb);
 
     let metallic = clamp(metallic_in, 0.0, 1.0);
     let roughness = clamp(roughness_in, 0.045, 1.0);
-
-    // World normal
-    let N = safe_normalize(normal_in);
+    let coat = vec2<f32>(saturate(clearcoat), clamp(clearcoat_roughness, 0.045, 1.0));
+    let transmission_factor = saturate(transmission);
+    let ao = saturate(occlusion);
+
+    // World normal, flipped for back faces of double-sided materials
+    var N = safe_normalize(normal_in);
+    if (double_sided != 0u && !front_facing) {
+        N = -N;
+    }
     let V = safe_normalize(camera.position - in.position);   // to viewer
     let n_enc = oct_encode(N);
     out.out_normal = vec4(n_enc, 0.0, 1.0);
@@ -369,34 +446,45 @@ fn pbr_fragment(
         return out;
     }
 
+    // Transmitted light is not scattered diffusely
+    let diffuse_base = base * (1.0 - transmission_factor);
+
     var Lo = vec3<f32>(0.0);
 
     if lit == 0 {
         Lo = base;
     } else {
-        Lo += ibl_term(N, V, base, metallic, roughness);
-        Lo += base * (AMBIENT_STRENGTH * (1.0 - 0.04));
+        Lo += ibl_term(in.position, N, V, diffuse_base, metallic, roughness, coat) * ao;
+        Lo += diffuse_base * (AMBIENT_STRENGTH * (1.0 - 0.04)) * ao;
     }
 
     let can_cast_shadows = cast_shadows != 0;
 
-    // Lights
-    const MAX_LIGHTS: u32 = 64u;
-    for (var i: u32 = 0u; i < MAX_LIGHTS; i = i + 1u) {
-        if (i >= light_count) { break; }
-        let Ld = lights[i];
+    // Lights reaching this fragment's cluster
+    let cluster = light_cluster_base(in.clip.xy, in.position);
+    let cluster_lights = min(light_clusters[cluster], MAX_LIGHTS_PER_CLUSTER);
+
+    if (light_cluster_params.debug_view != 0u) {
+        out.out_color = vec4(light_cluster_heatmap(cluster_lights), 1.0);
+        return out;
+    }
+
+    for (var i: u32 = 0u; i < cluster_lights; i = i + 1u) {
+        let Ld = lights[light_clusters[cluster + 1u + i]];
         if (Ld.type_id == LIGHT_TYPE_POINT) {
-            Lo += eval_point(in.position, N, V, base, metallic, roughness, Ld, can_cast_shadows);
+            Lo += eval_point(in.position, N, V, diffuse_base, metallic, roughness, coat, Ld, can_cast_shadows);
         } else if (Ld.type_id == LIGHT_TYPE_SPOT) {
-            Lo += eval_spot(in.position, N, V, base, metallic, roughness, Ld, can_cast_shadows);
+            Lo += eval_spot(in.position, N, V, diffuse_base, metallic, roughness, coat, Ld, can_cast_shadows);
         }
     }
 
     if (lit != 0) {
-        Lo += eval_sky_sun(N, V, base, metallic, roughness);
+        Lo += eval_sky_sun(N, V, diffuse_base, metallic, roughness, coat);
     }
 
-    out.out_color = vec4(Lo, base_rgba.a * alpha_in);
+    Lo += emissive;
+
+    out.out_color = vec4(Lo, alpha * (1.0 - transmission_factor));
     return out;
 }
 
@@ -1,18 +1,43 @@
+use crate::preprocess::{ATMOSPHERE_MODULE, MATH_MODULE, PreprocessError, Preprocessor};
+
 const POST_PROCESS_VERTEX: &str = include_str!("functions/vertex_postprocess_quad.wgsl");
 const MESH3D_GROUP: &str = include_str!("groups/mesh3d.wgsl");
 const MESH3D_POSITION_ONLY_GROUP: &str = include_str!("groups/mesh3d_position_only.wgsl");
 const MESH3D_VERTEX: &str = include_str!("functions/vertex_mesh3d.wgsl");
 const MESH3D_POSITION_ONLY_VERTEX: &str =
     include_str!("functions/vertex_mesh3d_position_only.wgsl");
-const MATH_HELPERS: &str = include_str!("functions/helpers/math.wgsl");
-const MESH3D_PBR: &str = include_str!("functions/pbr_mesh3d.wgsl");
+pub(crate) const MATH_HELPERS: &str = include_str!("functions/helpers/math.wgsl");
+pub(crate) const MESH3D_PBR: &str = include_str!("functions/pbr_mesh3d.wgsl");
+pub(crate) const ATMOSPHERE: &str = include_str!("functions/atmosphere.wgsl");
+const DECAL: &str = include_str!("functions/decal.wgsl");
 
 const POST_PROCESS_GROUP: &str = include_str!("groups/post_process.wgsl");
 const RENDER_GROUP: &str = include_str!("groups/render.wgsl");
 const MODEL_GROUP: &str = include_str!("groups/model.wgsl");
+const MODEL_INSTANCES_GROUP: &str = include_str!("groups/model_instances.wgsl");
 const MATERIAL_GROUP: &str = include_str!("groups/material.wgsl");
 const MATERIAL_TEXTURES_GROUP: &str = include_str!("groups/material_textures.wgsl");
-const LIGHT_GROUP: &str = include_str!("groups/light.wgsl");
+const LIGHT_GROUP: &str = concat!(
+    include_str!("groups/light.wgsl"),
+    "\n",
+    include_str!("functions/atmosphere.wgsl")
+);
+
+/// Binding of the per-instance model data in generated mesh shaders.
+pub const MODEL_INSTANCES: &str = "model_instances";
+
+const VERTEX_ENTRY: &str = "fn vs_main(in: VInput) -> FInput {\n    var out: FInput;\n";
+const INSTANCED_VERTEX_ENTRY: &str =
+    "fn vs_main(in: VInput, @builtin(instance_index) instance: u32) -> FInput {
+    let model = model_instances[instance];
+    var out: FInput;
+    out.instance = instance;
+";
+const INSTANCED_FRAGMENT_PROLOGUE: &str = "    let model = model_instances[in.instance];
+    if (lod_fade_discards(model.lod_fade, in.clip.xy)) {
+        discard;
+    }
+";
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum MeshPass {
@@ -25,6 +50,9 @@ pub struct MaterialShaderSetCode {
     pub base: String,
     pub picking: String,
     pub shadow: String,
+    /// The material drives the vertex stage. Its shadow shader then reads the material
+    /// and all vertex attributes instead of positions only.
+    pub custom_vertex: bool,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -47,15 +75,74 @@ pub struct ShaderCompilationOutput {
     pub result_expr: String,
 }
 
+/// Compiled vertex stage of a material, injected into the generated `vs_main`.
+#[derive(Debug, Clone)]
+pub struct VertexCompilationOutput {
+    pub lines: Vec<String>,
+    pub world_position_offset: Option<String>,
+    pub normal: Option<String>,
+}
+
 pub struct ShaderGenerator;
 
 impl ShaderGenerator {
+    /// # Panics
+    /// If the source has invalid preprocessor directives, see
+    /// [`try_assemble_shader`](Self::try_assemble_shader).
     pub fn assemble_shader(
         source: &str,
         fragment_only: bool,
         kind: ShaderKind,
         depth_enabled: bool,
         material_groups: Option<MaterialGroupOverrides<'_>>,
+    ) -> String {
+        Self::try_assemble_shader(
+            source,
+            &[],
+            fragment_only,
+            kind,
+            depth_enabled,
+            material_groups,
+        )
+        .unwrap_or_else(|e| panic!("invalid shader source: {e}"))
+    }
+
+    /// Expands the `#import` and `#ifdef` directives of `source` with `defines` set,
+    /// then wraps it into a complete shader of the given kind.
+    pub fn try_assemble_shader(
+        source: &str,
+        defines: &[String],
+        fragment_only: bool,
+        kind: ShaderKind,
+        depth_enabled: bool,
+        material_groups: Option<MaterialGroupOverrides<'_>>,
+    ) -> Result<String, PreprocessError> {
+        let mut preprocessor = Preprocessor::new();
+        preprocessor.mark_included(MATH_MODULE);
+        // The light group already carries the sky model
+        if (kind == ShaderKind::Default && depth_enabled) || uses_light_group(source) {
+            preprocessor.mark_included(ATMOSPHERE_MODULE);
+        }
+        for define in defines {
+            preprocessor.define(define.clone());
+        }
+        let source = preprocessor.process(source)?;
+
+        Ok(Self::wrap_shader(
+            &source,
+            fragment_only,
+            kind,
+            depth_enabled,
+            material_groups,
+        ))
+    }
+
+    fn wrap_shader(
+        source: &str,
+        fragment_only: bool,
+        kind: ShaderKind,
+        depth_enabled: bool,
+        material_groups: Option<MaterialGroupOverrides<'_>>,
     ) -> String {
         let (material_group, material_textures_group) = material_groups
             .map(|g| (g.material, g.material_textures))
@@ -103,7 +190,7 @@ impl ShaderGenerator {
                     out.push('\n');
                 }
 
-                out.push_str(source);
+                out.push_str(&store_decal_layer(source));
                 out
             }
             ShaderKind::Custom => {
@@ -128,6 +215,9 @@ impl ShaderGenerator {
                     },
                     &mut imported,
                 );
+                if imported.model && imported.default_vertex {
+                    out = store_decal_layer(&out);
+                }
 
                 if fragment_only {
                     out.push_str(MESH3D_VERTEX);
@@ -166,8 +256,15 @@ impl ShaderGenerator {
         ShaderGenerator::assemble_shader(source, false, ShaderKind::Compute, false, None)
     }
 
+    /// Whether `code` reads its model data from [`MODEL_INSTANCES`], so one draw can
+    /// render many instances. Other shaders only read the first model.
+    pub fn is_instanced(code: &str) -> bool {
+        code.contains(&format!("var<storage, read> {MODEL_INSTANCES}:"))
+    }
+
     pub fn build_mesh_shader(
         compiled: &ShaderCompilationOutput,
+        vertex: Option<&VertexCompilationOutput>,
         pass: MeshPass,
         position_only: bool,
     ) -> String {
@@ -175,13 +272,21 @@ impl ShaderGenerator {
             !(position_only && pass == MeshPass::Base),
             "Base mesh shaders require full vertex attributes"
         );
+        debug_assert!(
+            !(position_only && vertex.is_some()),
+            "Material vertex stages require full vertex attributes"
+        );
 
         let mut out = String::new();
 
-        let needs_pbr = pass == MeshPass::Base || (pass == MeshPass::Shadow && !position_only);
-        if needs_pbr {
+        let vertex_shadow = pass == MeshPass::Shadow && vertex.is_some();
+        let needs_pbr = pass == MeshPass::Base
+            || (pass == MeshPass::Shadow && !position_only && !vertex_shadow);
+        if needs_pbr || vertex_shadow {
             out.push_str("#use material\n");
             out.push_str("#use material_textures\n");
+        }
+        if needs_pbr {
             out.push_str("#use light\n");
         }
 
@@ -195,7 +300,7 @@ impl ShaderGenerator {
         }
         out.push('\n');
 
-        out.push_str(MODEL_GROUP);
+        out.push_str(MODEL_INSTANCES_GROUP);
         out.push('\n');
 
         if needs_pbr {
@@ -203,23 +308,32 @@ impl ShaderGenerator {
             out.push('\n');
         }
 
-        if position_only {
-            out.push_str(MESH3D_POSITION_ONLY_VERTEX);
-        } else {
-            out.push_str(MESH3D_VERTEX);
-        }
+        append_mesh_vertex(&mut out, vertex, position_only);
         out.push('\n');
 
+        // Non-opaque shadow pipelines need a fragment entry, even one writing nothing
+        if vertex_shadow {
+            out.push_str("@fragment\nfn fs_main(in: FInput) {}\n");
+            return out;
+        }
+
         let ret = match pass {
             MeshPass::Picking => "@location(0) vec4f",
             MeshPass::Shadow if position_only => return out,
             MeshPass::Base | MeshPass::Shadow => "FOutput",
         };
 
-        out.push_str("@fragment\nfn fs_main(in: FInput) -> ");
+        out.push_str(
+            "@fragment\nfn fs_main(in: FInput, @builtin(front_facing) front_facing: bool) -> ",
+        );
         out.push_str(ret);
         out.push_str(" {\n");
-        append_compilation_output(&mut out, compiled);
+        out.push_str(INSTANCED_FRAGMENT_PROLOGUE);
+        if pass == MeshPass::Base {
+            append_surface_output(&mut out, compiled);
+        } else {
+            append_compilation_output(&mut out, compiled);
+        }
         out.push_str("}\n");
 
         out
@@ -228,6 +342,7 @@ impl ShaderGenerator {
     pub fn build_mesh_picking_shader(
         pick: &ShaderCompilationOutput,
         material: &ShaderCompilationOutput,
+        vertex: Option<&VertexCompilationOutput>,
         position_only: bool,
     ) -> String {
         let mut out = String::new();
@@ -246,19 +361,16 @@ impl ShaderGenerator {
         }
         out.push('\n');
 
-        out.push_str(MODEL_GROUP);
+        out.push_str(MODEL_INSTANCES_GROUP);
         out.push('\n');
         out.push_str(MESH3D_PBR);
         out.push('\n');
 
-        if position_only {
-            out.push_str(MESH3D_POSITION_ONLY_VERTEX);
-        } else {
-            out.push_str(MESH3D_VERTEX);
-        }
+        append_mesh_vertex(&mut out, vertex, position_only);
         out.push('\n');
 
-        out.push_str("@fragment\nfn fs_main(in: FInput) -> @location(0) vec4f {\n");
+        out.push_str("@fragment\nfn fs_main(in: FInput, @builtin(front_facing) front_facing: bool) -> @location(0) vec4f {\n");
+        out.push_str(INSTANCED_FRAGMENT_PROLOGUE);
         for stmt in &material.lines {
             for line in stmt.lines() {
                 out.push_str("    ");
@@ -279,6 +391,28 @@ impl ShaderGenerator {
         out
     }
 
+    /// Shader projecting the default material of a decal box onto the scene surfaces.
+    ///
+    /// The decal data is bound in place of the model group, the scene depth and G-buffer
+    /// copies in group 5.
+    pub fn build_decal_shader() -> String {
+        let mut out = String::new();
+        for block in [
+            MATH_HELPERS,
+            RENDER_GROUP,
+            MESH3D_GROUP,
+            MATERIAL_GROUP,
+            MATERIAL_TEXTURES_GROUP,
+            LIGHT_GROUP,
+            MESH3D_PBR,
+            DECAL,
+        ] {
+            out.push_str(block);
+            out.push('\n');
+        }
+        out
+    }
+
     pub fn build_post_process_shader(compiled: &ShaderCompilationOutput) -> String {
         let mut out = String::new();
         out.push_str(POST_PROCESS_GROUP);
@@ -324,6 +458,13 @@ fn has_use_directive(source: &str) -> bool {
     source.lines().any(|line| line.contains("#use "))
 }
 
+fn uses_light_group(source: &str) -> bool {
+    source
+        .lines()
+        .filter_map(|line| line.find("#use ").map(|import| line[import + 5..].trim()))
+        .any(|group| group == "light" || group == "shadow")
+}
+
 fn expand_use_directives(
     out: &mut String,
     source: &str,
@@ -392,6 +533,207 @@ fn append_block_once(out: &mut String, is_included: &mut bool, block: &str) {
     *is_included = true;
 }
 
+/// Appends `vs_main`, either the fixed one or one running the material's vertex stage.
+/// Either reads its model data from [`MODEL_INSTANCES`] by instance index.
+fn append_mesh_vertex(
+    out: &mut String,
+    vertex: Option<&VertexCompilationOutput>,
+    position_only: bool,
+) {
+    let Some(vertex) = vertex else {
+        let source = if position_only {
+            MESH3D_POSITION_ONLY_VERTEX
+        } else {
+            MESH3D_VERTEX
+        };
+        debug_assert!(source.contains(VERTEX_ENTRY));
+        out.push_str(&source.replacen(VERTEX_ENTRY, INSTANCED_VERTEX_ENTRY, 1));
+        return;
+    };
+
+    out.push_str("@vertex\n");
+    out.push_str(INSTANCED_VERTEX_ENTRY);
+    out.push('\n');
+    out.push_str("    let world_position = (model.transform * vec4(in.position, 1.0)).xyz;\n");
+    out.push_str("    let world_normal = normalize(model.normal * in.normal);\n\n");
+    for stmt in &vertex.lines {
+        for line in stmt.lines() {
+            out.push_str("    ");
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+
+    let offset = vertex
+        .world_position_offset
+        .as_deref()
+        .unwrap_or("vec3(0.0)");
+    out.push_str("    let ws_pos = vec4(world_position + (");
+    out.push_str(offset);
+    out.push_str("), 1.0);\n");
+    match &vertex.normal {
+        Some(normal) => {
+            out.push_str("    let N = normalize(");
+            out.push_str(normal);
+            out.push_str(");\n");
+        }
+        None => out.push_str("    let N = world_normal;\n"),
+    }
+
+    out.push_str(
+        "
+    let T_obj = in.tangent;
+    let Tm = (model.transform[0].xyz * T_obj.x)
+        + (model.transform[1].xyz * T_obj.y)
+        + (model.transform[2].xyz * T_obj.z);
+    let T = normalize(Tm - N * dot(N, Tm));
+    let B = T_obj.w * normalize(cross(N, T));
+
+    out.position = ws_pos.xyz;
+    out.clip = camera.view_proj_mat * ws_pos;
+
+    out.uv = in.uv;
+
+    out.normal = N;
+    out.tangent = vec4f(T, in.tangent.w);
+    out.bitangent = B;
+
+    return out;
+}
+",
+    );
+}
+
+/// Makes every fragment entry point in `source` that returns an `FOutput` store the decal
+/// layer of its model, whatever it wrote there itself. The entry point is renamed and
+/// called by a new one with its original name and parameters.
+fn store_decal_layer(source: &str) -> String {
+    const ENTRY: &str = "@fragment";
+
+    let mut out = String::with_capacity(source.len());
+    let mut wrappers = String::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find(ENTRY) {
+        let after = &rest[start + ENTRY.len()..];
+        let Some(entry) = parse_surface_entry(after) else {
+            out.push_str(&rest[..start + ENTRY.len()]);
+            rest = after;
+            continue;
+        };
+
+        let surface = format!("{}_surface", entry.name);
+        out.push_str(&rest[..start]);
+        out.push_str(&after[..entry.name_start]);
+        out.push_str(&surface);
+        rest = &after[entry.name_start + entry.name.len()..];
+
+        wrappers.push_str(&format!(
+            "\n@fragment\nfn {}({}) -> FOutput {{\n    var surface = {surface}({});\n    surface.out_material.b = f32(model.decal_layer) / 255.0;\n    return surface;\n}}\n",
+            entry.name,
+            entry.params,
+            entry.args.join(", "),
+        ));
+    }
+
+    out.push_str(rest);
+    out.push_str(&wrappers);
+    out
+}
+
+struct SurfaceEntry<'a> {
+    name: &'a str,
+    /// Offset of the name after the `@fragment` attribute
+    name_start: usize,
+    params: &'a str,
+    args: Vec<&'a str>,
+}
+
+/// Parses `fn name(params) -> FOutput` following a `@fragment` attribute.
+fn parse_surface_entry(source: &str) -> Option<SurfaceEntry<'_>> {
+    let after_fn = source.trim_start().strip_prefix("fn")?;
+    let name_trimmed = after_fn.trim_start();
+    if name_trimmed.len() == after_fn.len() {
+        return None;
+    }
+    let name_start = source.len() - name_trimmed.len();
+    let name_len = name_trimmed
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(name_trimmed.len());
+    let name = &name_trimmed[..name_len];
+
+    let after_name = name_trimmed[name_len..].trim_start().strip_prefix('(')?;
+    let mut depth = 0;
+    let params_len = after_name.find(|c: char| {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' if depth > 0 => depth -= 1,
+            ')' => return true,
+            _ => {}
+        }
+        false
+    })?;
+    let params = &after_name[..params_len];
+
+    let ret = after_name[params_len + 1..]
+        .trim_start()
+        .strip_prefix("->")?;
+    let ret = ret.trim_start().strip_prefix("FOutput")?;
+    if !ret.trim_start().starts_with('{') || name.is_empty() {
+        return None;
+    }
+
+    let args = split_params(params)
+        .into_iter()
+        .map(param_name)
+        .collect::<Option<_>>()?;
+
+    Some(SurfaceEntry {
+        name,
+        name_start,
+        params,
+        args,
+    })
+}
+
+/// Splits a parameter list at the commas that aren't nested in a type or attribute.
+fn split_params(params: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&params[start..]);
+    parts.retain(|part| !part.trim().is_empty());
+    parts
+}
+
+/// The name of a parameter like `@builtin(front_facing) front_facing: bool`.
+fn param_name(param: &str) -> Option<&str> {
+    let mut rest = param.trim_start();
+    while let Some(attribute) = rest.strip_prefix('@') {
+        let end = attribute
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(attribute.len());
+        rest = attribute[end..].trim_start();
+        if let Some(args) = rest.strip_prefix('(') {
+            rest = args[args.find(')')? + 1..].trim_start();
+        }
+    }
+
+    let name = rest.split(':').next()?.trim();
+    (!name.is_empty()).then_some(name)
+}
+
 fn append_compilation_output(out: &mut String, compiled: &ShaderCompilationOutput) {
     for stmt in &compiled.lines {
         for line in stmt.lines() {
@@ -405,3 +747,21 @@ fn append_compilation_output(out: &mut String, compiled: &ShaderCompilationOutpu
     out.push_str(&compiled.result_expr);
     out.push_str(";\n");
 }
+
+/// Like [`append_compilation_output`], but stores the decal layer of the model in the
+/// otherwise unused third channel of the material output.
+fn append_surface_output(out: &mut String, compiled: &ShaderCompilationOutput) {
+    for stmt in &compiled.lines {
+        for line in stmt.lines() {
+            out.push_str("    ");
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+
+    out.push_str("    var surface: FOutput = ");
+    out.push_str(&compiled.result_expr);
+    out.push_str(";\n");
+    out.push_str("    surface.out_material.b = f32(model.decal_layer) / 255.0;\n");
+    out.push_str("    return surface;\n");
+}
@@ -0,0 +1,465 @@
+//! Material node graphs as plain data.
+//!
+//! A [`MaterialGraph`] describes the same kind of shader a hand written
+//! [`MaterialExpression`] does, but as a list of nodes and connections that can be
+//! stored in asset packages and edited by tools. It is compiled by [`MaterialCompiler`]
+//! when the material is loaded.
+
+use crate::compiler::{MaterialGraphError, MaterialVertexOutput, PbrSurface};
+use crate::function::{MaterialExpression, MaterialExpressionValue};
+use crate::value::MaterialValueType;
+use crate::{CompareOp, MaterialCompiler, NodeId};
+
+/// Connection to one output of a node in a [`MaterialGraph`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GraphPin {
+    /// Index into [`MaterialGraph::nodes`].
+    pub node: u32,
+    /// Output of that node, `0` for single output nodes.
+    pub output: u32,
+}
+
+impl GraphPin {
+    pub const fn new(node: u32, output: u32) -> Self {
+        Self { node, output }
+    }
+
+    /// The first output of `node`.
+    pub const fn node(node: u32) -> Self {
+        Self { node, output: 0 }
+    }
+}
+
+/// Operations on other node outputs, with the semantics of the matching
+/// [`MaterialCompiler`] method.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GraphOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lerp,
+    Clamp,
+    Step,
+    Smoothstep,
+    Dot,
+    Cross,
+    Normalize,
+    Length,
+    Compare(CompareOp),
+    Select,
+}
+
+impl GraphOp {
+    pub const ALL: [GraphOp; 19] = [
+        GraphOp::Add,
+        GraphOp::Sub,
+        GraphOp::Mul,
+        GraphOp::Div,
+        GraphOp::Lerp,
+        GraphOp::Clamp,
+        GraphOp::Step,
+        GraphOp::Smoothstep,
+        GraphOp::Dot,
+        GraphOp::Cross,
+        GraphOp::Normalize,
+        GraphOp::Length,
+        GraphOp::Compare(CompareOp::Less),
+        GraphOp::Compare(CompareOp::LessEqual),
+        GraphOp::Compare(CompareOp::Greater),
+        GraphOp::Compare(CompareOp::GreaterEqual),
+        GraphOp::Compare(CompareOp::Equal),
+        GraphOp::Compare(CompareOp::NotEqual),
+        GraphOp::Select,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            GraphOp::Add => "add",
+            GraphOp::Sub => "sub",
+            GraphOp::Mul => "mul",
+            GraphOp::Div => "div",
+            GraphOp::Lerp => "lerp",
+            GraphOp::Clamp => "clamp",
+            GraphOp::Step => "step",
+            GraphOp::Smoothstep => "smoothstep",
+            GraphOp::Dot => "dot",
+            GraphOp::Cross => "cross",
+            GraphOp::Normalize => "normalize",
+            GraphOp::Length => "length",
+            GraphOp::Compare(CompareOp::Less) => "less",
+            GraphOp::Compare(CompareOp::LessEqual) => "less_equal",
+            GraphOp::Compare(CompareOp::Greater) => "greater",
+            GraphOp::Compare(CompareOp::GreaterEqual) => "greater_equal",
+            GraphOp::Compare(CompareOp::Equal) => "equal",
+            GraphOp::Compare(CompareOp::NotEqual) => "not_equal",
+            GraphOp::Select => "select",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|op| op.name() == name)
+    }
+
+    /// Number of inputs the operation takes.
+    pub const fn arity(self) -> usize {
+        match self {
+            GraphOp::Normalize | GraphOp::Length => 1,
+            GraphOp::Add
+            | GraphOp::Sub
+            | GraphOp::Mul
+            | GraphOp::Div
+            | GraphOp::Step
+            | GraphOp::Dot
+            | GraphOp::Cross
+            | GraphOp::Compare(_) => 2,
+            GraphOp::Lerp | GraphOp::Clamp | GraphOp::Smoothstep | GraphOp::Select => 3,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MaterialGraphNode {
+    /// Texture coordinates of the mesh.
+    VertexUv,
+    /// World space position before any offset. Vertex stage only.
+    VertexWorldPosition,
+    /// World space normal of the mesh. Vertex stage only.
+    VertexWorldNormal,
+    /// Object space position. Vertex stage only.
+    VertexObjectPosition,
+    /// Interpolated world space normal. Fragment stage only.
+    SurfaceNormal,
+    /// Seconds since startup.
+    Time,
+    Constant(f32),
+    /// An immediate of the material's input layout.
+    Parameter {
+        name: String,
+        value_type: MaterialValueType,
+    },
+    /// Samples a texture of the material's input layout. Fragment stage only.
+    TextureSample {
+        texture: String,
+        uv: GraphPin,
+    },
+    Op {
+        op: GraphOp,
+        inputs: Vec<GraphPin>,
+    },
+    Swizzle {
+        input: GraphPin,
+        components: String,
+    },
+    Construct {
+        value_type: MaterialValueType,
+        parts: Vec<GraphPin>,
+    },
+    /// Splits a vector, one output per component.
+    Split(GraphPin),
+    /// Calls a WGSL function. The result type isn't checked.
+    Call {
+        function: String,
+        args: Vec<GraphPin>,
+    },
+}
+
+/// Inputs of the PBR shading function. Unconnected inputs use neutral defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MaterialGraphSurface {
+    pub base_color: Option<GraphPin>,
+    pub normal: Option<GraphPin>,
+    pub roughness: Option<GraphPin>,
+    pub metallic: Option<GraphPin>,
+    pub alpha: Option<GraphPin>,
+    pub emissive: Option<GraphPin>,
+    pub occlusion: Option<GraphPin>,
+    pub lit: Option<GraphPin>,
+    pub cast_shadows: Option<GraphPin>,
+}
+
+/// Outputs of the generated vertex stage, see [`MaterialVertexOutput`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MaterialGraphVertex {
+    pub world_position_offset: Option<GraphPin>,
+    pub normal: Option<GraphPin>,
+}
+
+/// A material described as data. Nodes may only connect to nodes before them,
+/// which keeps every graph acyclic.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MaterialGraph {
+    pub nodes: Vec<MaterialGraphNode>,
+    pub surface: MaterialGraphSurface,
+    pub vertex: MaterialGraphVertex,
+}
+
+impl MaterialGraph {
+    /// Appends a node and returns a pin to its first output.
+    pub fn add(&mut self, node: MaterialGraphNode) -> GraphPin {
+        self.nodes.push(node);
+        GraphPin::node(self.nodes.len() as u32 - 1)
+    }
+
+    /// Names and types of every [`Parameter`](MaterialGraphNode::Parameter) node.
+    pub fn parameters(&self) -> impl Iterator<Item = (&str, MaterialValueType)> {
+        self.nodes.iter().filter_map(|node| match node {
+            MaterialGraphNode::Parameter { name, value_type } => Some((name.as_str(), *value_type)),
+            _ => None,
+        })
+    }
+
+    /// Names of every texture the graph samples.
+    pub fn textures(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().filter_map(|node| match node {
+            MaterialGraphNode::TextureSample { texture, .. } => Some(texture.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Checks the graph and compiles every pass, see [`MaterialCompiler::try_compile_shader_set`].
+    pub fn validate(&self) -> Result<(), MaterialGraphError> {
+        MaterialCompiler::try_compile_shader_set(&mut self.clone()).map(|_| ())
+    }
+
+    fn build(&self, compiler: &mut MaterialCompiler) -> Vec<Vec<NodeId>> {
+        let mut outputs: Vec<Vec<NodeId>> = Vec::with_capacity(self.nodes.len());
+        for (index, node) in self.nodes.iter().enumerate() {
+            let id = self.build_node(compiler, index as u32, node, &outputs);
+            let count = compiler.output_count(id);
+            let node_outputs = if count > 1 {
+                (0..count).map(|i| compiler.output(id, i)).collect()
+            } else {
+                vec![id]
+            };
+            outputs.push(node_outputs);
+        }
+        outputs
+    }
+
+    fn build_node(
+        &self,
+        compiler: &mut MaterialCompiler,
+        index: u32,
+        node: &MaterialGraphNode,
+        built: &[Vec<NodeId>],
+    ) -> NodeId {
+        let pin =
+            |compiler: &mut MaterialCompiler, pin: GraphPin| resolve(compiler, built, index, pin);
+
+        match node {
+            MaterialGraphNode::VertexUv => compiler.vertex_uv(),
+            MaterialGraphNode::VertexWorldPosition => compiler.vertex_world_position(),
+            MaterialGraphNode::VertexWorldNormal => compiler.vertex_world_normal(),
+            MaterialGraphNode::VertexObjectPosition => compiler.vertex_object_position(),
+            MaterialGraphNode::SurfaceNormal => compiler.surface_normal(),
+            MaterialGraphNode::Time => compiler.time(),
+            MaterialGraphNode::Constant(value) => compiler.constant_f32(*value),
+            MaterialGraphNode::Parameter { name, value_type } => {
+                compiler.typed_input_value(name.clone(), *value_type)
+            }
+            MaterialGraphNode::TextureSample { texture, uv } => {
+                let uv = pin(compiler, *uv);
+                let texture_node = compiler.bind_texture(texture.clone());
+                let sampler_node = compiler.bind_sampler(texture.clone());
+                compiler.texture_sample(texture_node, sampler_node, uv)
+            }
+            MaterialGraphNode::Op { op, inputs } => {
+                if inputs.len() != op.arity() {
+                    compiler.report(MaterialGraphError::InputCount {
+                        op: op.name(),
+                        expected: op.arity(),
+                        found: inputs.len(),
+                    });
+                    return compiler.constant_f32(0.0);
+                }
+                let args: Vec<NodeId> = inputs.iter().map(|&p| pin(compiler, p)).collect();
+                match *op {
+                    GraphOp::Add => compiler.add(args[0], args[1]),
+                    GraphOp::Sub => compiler.sub(args[0], args[1]),
+                    GraphOp::Mul => compiler.mul(args[0], args[1]),
+                    GraphOp::Div => compiler.div(args[0], args[1]),
+                    GraphOp::Lerp => compiler.lerp(args[0], args[1], args[2]),
+                    GraphOp::Clamp => compiler.clamp(args[0], args[1], args[2]),
+                    GraphOp::Step => compiler.step(args[0], args[1]),
+                    GraphOp::Smoothstep => compiler.smoothstep(args[0], args[1], args[2]),
+                    GraphOp::Dot => compiler.dot(args[0], args[1]),
+                    GraphOp::Cross => compiler.cross(args[0], args[1]),
+                    GraphOp::Normalize => compiler.normalize(args[0]),
+                    GraphOp::Length => compiler.length(args[0]),
+                    GraphOp::Compare(op) => compiler.compare(op, args[0], args[1]),
+                    GraphOp::Select => compiler.select(args[0], args[1], args[2]),
+                }
+            }
+            MaterialGraphNode::Swizzle { input, components } => {
+                let input = pin(compiler, *input);
+                compiler.swizzle(input, components.clone())
+            }
+            MaterialGraphNode::Construct { value_type, parts } => {
+                let parts: Vec<NodeId> = parts.iter().map(|&p| pin(compiler, p)).collect();
+                compiler.construct(*value_type, &parts)
+            }
+            MaterialGraphNode::Split(input) => {
+                let input = pin(compiler, *input);
+                compiler.split(input)
+            }
+            MaterialGraphNode::Call { function, args } => {
+                let args = args.iter().map(|&p| pin(compiler, p)).collect();
+                compiler.call(function.clone(), args)
+            }
+        }
+    }
+
+    fn surface_input(
+        compiler: &mut MaterialCompiler,
+        built: &[Vec<NodeId>],
+        name: &'static str,
+        pin: Option<GraphPin>,
+        expected: MaterialValueType,
+        default: &dyn Fn(&mut MaterialCompiler) -> NodeId,
+    ) -> NodeId {
+        let Some(pin) = pin else {
+            return default(compiler);
+        };
+
+        let node = resolve(compiler, built, built.len() as u32, pin);
+        let compatible = |found: MaterialValueType| match expected {
+            MaterialValueType::Bool | MaterialValueType::U32 => !found.is_float(),
+            _ => found == expected,
+        };
+        if let Some(found) = compiler.output_type(node, 0)
+            && !compatible(found)
+        {
+            compiler.report(MaterialGraphError::InputType {
+                op: "material output",
+                input: name,
+                expected: expected.wgsl_type(),
+                found,
+            });
+        }
+        node
+    }
+}
+
+/// Compiler node behind `pin`, reporting connections to missing or later nodes.
+fn resolve(
+    compiler: &mut MaterialCompiler,
+    built: &[Vec<NodeId>],
+    from: u32,
+    pin: GraphPin,
+) -> NodeId {
+    let output = built
+        .get(pin.node as usize)
+        .filter(|_| pin.node < from)
+        .and_then(|outputs| outputs.get(pin.output as usize));
+
+    match output {
+        Some(&node) => node,
+        None => {
+            compiler.report(MaterialGraphError::InvalidConnection {
+                node: from,
+                target: pin.node,
+                output_index: pin.output,
+            });
+            compiler.constant_f32(0.0)
+        }
+    }
+}
+
+fn flag(compiler: &mut MaterialCompiler, value: bool) -> NodeId {
+    let value = compiler.constant_f32(if value { 1.0 } else { 0.0 });
+    compiler.call("u32", vec![value])
+}
+
+impl MaterialExpression for MaterialGraph {
+    fn outputs(&self) -> Vec<MaterialExpressionValue> {
+        vec![MaterialExpressionValue {
+            name: "out",
+            value_type: MaterialValueType::Vec4,
+        }]
+    }
+
+    fn compile(&self, compiler: &mut MaterialCompiler, output_index: u32) -> NodeId {
+        debug_assert_eq!(output_index, 0, "material graphs have a single output");
+
+        use MaterialValueType::{Bool, F32, Vec3, Vec4};
+
+        let built = self.build(compiler);
+        let surface = &self.surface;
+        let input = |compiler: &mut MaterialCompiler,
+                     name: &'static str,
+                     pin: Option<GraphPin>,
+                     ty: MaterialValueType,
+                     default: &dyn Fn(&mut MaterialCompiler) -> NodeId| {
+            Self::surface_input(compiler, &built, name, pin, ty, default)
+        };
+
+        let base_color = input(compiler, "base_color", surface.base_color, Vec4, &|c| {
+            let gray = c.constant_f32(0.7);
+            let one = c.constant_f32(1.0);
+            c.vec4(gray, gray, gray, one)
+        });
+        let normal = input(compiler, "normal", surface.normal, Vec3, &|c| {
+            c.surface_normal()
+        });
+        let roughness = input(compiler, "roughness", surface.roughness, F32, &|c| {
+            c.constant_f32(0.5)
+        });
+        let metallic = input(compiler, "metallic", surface.metallic, F32, &|c| {
+            c.constant_f32(0.0)
+        });
+        let alpha = input(compiler, "alpha", surface.alpha, F32, &|c| {
+            c.constant_f32(1.0)
+        });
+        let emissive = input(compiler, "emissive", surface.emissive, Vec3, &|c| {
+            let zero = c.constant_f32(0.0);
+            c.construct(Vec3, &[zero])
+        });
+        let occlusion = input(compiler, "occlusion", surface.occlusion, F32, &|c| {
+            c.constant_f32(1.0)
+        });
+        let lit = input(compiler, "lit", surface.lit, Bool, &|c| flag(c, true));
+        let cast_shadows = input(compiler, "cast_shadows", surface.cast_shadows, Bool, &|c| {
+            flag(c, true)
+        });
+
+        let zero = compiler.constant_f32(0.0);
+        let off = flag(compiler, false);
+        compiler.pbr_surface(PbrSurface {
+            base_color,
+            normal,
+            roughness,
+            metallic,
+            alpha,
+            lit,
+            cast_shadows,
+            grayscale: off,
+            emissive,
+            occlusion,
+            alpha_cutoff: zero,
+            double_sided: off,
+            clearcoat: zero,
+            clearcoat_roughness: zero,
+            transmission: zero,
+        })
+    }
+
+    fn compile_vertex(&self, compiler: &mut MaterialCompiler) -> MaterialVertexOutput {
+        if self.vertex == MaterialGraphVertex::default() {
+            return MaterialVertexOutput::default();
+        }
+
+        let built = self.build(compiler);
+        let from = built.len() as u32;
+        MaterialVertexOutput {
+            world_position_offset: self
+                .vertex
+                .world_position_offset
+                .map(|pin| resolve(compiler, &built, from, pin)),
+            normal: self
+                .vertex
+                .normal
+                .map(|pin| resolve(compiler, &built, from, pin)),
+        }
+    }
+}
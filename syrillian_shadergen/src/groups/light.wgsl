@@ -19,191 +19,92 @@ struct Light {
     shadow_mat_base: u32,
 }
 
-@group(3) @binding(0) var<uniform> light_count: u32;
-@group(3) @binding(1) var<storage, read> lights: array<Light>;
-
-@group(4) @binding(0) var shadow_maps: texture_depth_2d_array;
-@group(4) @binding(1) var shadow_sampler: sampler_comparison;
-@group(4) @binding(2) var<storage, read> shadow_mats: array<mat4x4<f32>>;
-@group(4) @binding(3) var<uniform> shadow_texel: vec2<f32>;
-
-const LUMA: vec3<f32> = vec3<f32>(0.2126, 0.7152, 0.0722);
-
-fn luma(c: vec3<f32>) -> f32 { return dot(c, LUMA); }
-
-fn sky_sun_direction() -> vec3<f32> {
-    let dir_len2 = dot(sky.sun_direction, sky.sun_direction);
-    if (dir_len2 > 1e-6) {
-        return normalize(sky.sun_direction);
-    }
-
-    let ce = cos(sky.sun_elevation);
-    return normalize(vec3<f32>(
-        ce * sin(sky.sun_rotation),
-        sin(sky.sun_elevation),
-        -ce * cos(sky.sun_rotation)
-    ));
-}
-
-fn sky_sun_color_base(sun_dir: vec3<f32>) -> vec3<f32> {
-    let elev01 = saturate(sun_dir.y); // 0 at horizon, 1 at zenith
-    let warm = mix(vec3<f32>(1.00, 0.60, 0.40), vec3<f32>(1.00, 0.98, 0.95), elev01);
-
-    let haze = saturate(max(sky.air_density, 0.0) * 0.25 + max(sky.aerosols, 0.0) * 0.75);
-    let neutral = vec3<f32>(luma(warm));
-    return mix(warm, neutral, haze * 0.22);
+struct LightClusterParams {
+    viewport: vec4<f32>,
+    grid: vec3<u32>,
+    debug_view: u32,
 }
 
-fn rayleigh_phase(mu: f32) -> f32 {
-    return (3.0 / (16.0 * PI)) * (1.0 + mu * mu);
+struct ReflectionProbe {
+    position: vec3<f32>,
+    blend_distance: f32,
+    box_min: vec3<f32>,
+    intensity: f32,
+    box_max: vec3<f32>,
+    box_projection: u32,
 }
 
-fn hg_phase(mu: f32, g: f32) -> f32 {
-    let gg = g * g;
-    let denom = pow(max(1.0 + gg - 2.0 * g * mu, 1e-4), 1.5);
-    return (1.0 - gg) / (4.0 * PI * denom);
-}
+const MAX_REFLECTION_PROBES: u32 = 8u;
 
-fn air_mass(cos_zenith: f32) -> f32 {
-    let cz = clamp(cos_zenith, 0.0, 1.0);
-    let z_deg = acos(cz) * RAD_TO_DEG;
-    let denom = cz + 0.50572 * pow(max(96.07995 - z_deg, 0.0001), -1.6364);
-    return 1.0 / max(denom, 0.02);
+struct ReflectionProbes {
+    count: u32,
+    probes: array<ReflectionProbe, MAX_REFLECTION_PROBES>,
 }
 
-struct AtmosphereEval {
-    transmittance_view: vec3<f32>,
-    transmittance_sun: vec3<f32>,
-    sky_radiance: vec3<f32>,
-    sun_color: vec3<f32>,
-    haze: f32,
-};
-
-const HR: f32 = 8000.0;
-const HM: f32 = 1200.0;
-const HR_KM: f32 = HR * 0.001;
-const mie_len_ratio: f32 = HM / HR;
-
-fn atmosphere_eval(view_dir: vec3<f32>, sun_dir: vec3<f32>) -> AtmosphereEval {
-    let air = max(sky.air_density, 0.0);
-    let aerosol = max(sky.aerosols, 0.0);
-    let haze = saturate(air * 0.35 + aerosol * 0.65);
-
-    let alt = max(sky.altitude, 0.0);
-    let rayleigh_density = exp(-alt / HR);
-    let mie_density = exp(-alt / HM);
-
-    let rayleigh_scale = air * rayleigh_density;
-    let mie_scale = (aerosol + air * 0.05) * mie_density;
-
-    let betaR = vec3<f32>(0.0058, 0.0135, 0.0331) * rayleigh_scale;
-
-    let betaM = vec3<f32>(0.0200) * mie_scale * mie_len_ratio;
-
-    let betaExt = betaR + betaM + vec3<f32>(1e-6);
-
-    let m_view = air_mass(saturate(view_dir.y));
-    let m_sun = air_mass(saturate(sun_dir.y));
-
-    let L_view = m_view * HR_KM;
-    let L_sun = m_sun * HR_KM;
-
-    let T_view = exp(-betaExt * L_view);
-    let T_sun = exp(-betaExt * L_sun);
-
-    let mu = clamp(dot(view_dir, sun_dir), -1.0, 1.0);
-    let pr = rayleigh_phase(mu);
-    let g = mix(0.76, 0.92, haze);
-    let pm = hg_phase(mu, g);
-
-    let sun_color = sky_sun_color_base(sun_dir) * T_sun;
+@group(3) @binding(0) var<uniform> light_count: u32;
+@group(3) @binding(1) var<storage, read> lights: array<Light>;
+@group(3) @binding(2) var<uniform> light_cluster_params: LightClusterParams;
+@group(3) @binding(3) var<storage, read> light_clusters: array<u32>;
+@group(3) @binding(4) var reflection_probe_maps: texture_cube_array<f32>;
+@group(3) @binding(5) var<uniform> reflection_probes: ReflectionProbes;
 
-    let sun_strength = max(sky.sun_strength, 0.0);
-    let sun_light = sun_color * sun_strength;
+@group(4) @binding(0) var shadow_maps: texture_depth_2d_array;
+@group(4) @binding(1) var shadow_sampler: sampler_comparison;
+@group(4) @binding(2) var<storage, read> shadow_mats: array<mat4x4<f32>>;
+@group(4) @binding(3) var<uniform> shadow_texel: vec2<f32>;
 
-    let betaS = betaR * pr + betaM * pm;
-    var sky_L = sun_light * 5.0 * betaS * (vec3<f32>(1.0) - T_view) / betaExt;
+const MAX_LIGHTS_PER_CLUSTER: u32 = 64u;
+const LIGHT_CLUSTER_STRIDE: u32 = MAX_LIGHTS_PER_CLUSTER + 1u;
 
-    let multi = sun_light * (vec3<f32>(0.015) + betaR * 0.20) * (vec3<f32>(1.0) - T_view);
-    sky_L += multi;
+// Offset of the cluster a fragment falls into. The cluster holds its light count,
+// followed by the indices of the lights that reach it.
+fn light_cluster_base(frag_coord: vec2<f32>, world_pos: vec3<f32>) -> u32 {
+    let grid = light_cluster_params.grid;
+    let viewport = light_cluster_params.viewport;
 
-    let horizon = 1.0 - saturate(view_dir.y);
-    let fog = 1.0 - exp(-(air * 0.55 + aerosol * 1.35) * horizon * horizon * 1.15);
-    let grey = vec3<f32>(luma(sky_L));
-    sky_L = mix(sky_L, grey, saturate(fog * 0.60));
+    let uv = clamp((frag_coord - viewport.xy) / max(viewport.zw, vec2<f32>(1.0)), vec2<f32>(0.0), vec2<f32>(0.9999));
+    let tile = vec2<u32>(uv * vec2<f32>(grid.xy));
 
-    let hemi = smoothstep(-0.05, 0.00, view_dir.y);
-    sky_L *= hemi;
+    let near = max(camera.near, 1e-4);
+    let far = max(camera.far, near + 1e-3);
+    let depth = -(camera.view_mat * vec4<f32>(world_pos, 1.0)).z;
+    let slice_f = log(max(depth, near) / near) / log(far / near) * f32(grid.z);
+    let slice = min(u32(max(slice_f, 0.0)), grid.z - 1u);
 
-    return AtmosphereEval(T_view, T_sun, sky_L, sun_color, haze);
+    let idx = tile.x + tile.y * grid.x + slice * grid.x * grid.y;
+    return idx * LIGHT_CLUSTER_STRIDE;
 }
 
-fn sky_sun_disk_add(view_dir: vec3<f32>, sun_dir: vec3<f32>, sky_color: vec3<f32>, atm: AtmosphereEval) -> vec3<f32> {
-    let sun_strength = max(sky.sun_strength, 0.0);
-    let sun_intensity = max(sky.sun_intensity, 0.0);
+fn light_cluster_heatmap(count: u32) -> vec3<f32> {
+    let t = saturate(f32(count) / 16.0);
+    let cold = mix(vec3<f32>(0.0, 0.0, 0.3), vec3<f32>(0.0, 0.9, 0.2), saturate(t * 2.0));
+    return mix(cold, vec3<f32>(1.0, 0.1, 0.0), saturate(t * 2.0 - 1.0));
+}
 
-    if (sun_strength <= 0.0 || sun_intensity <= 0.0) {
-        return vec3<f32>(0.0);
+// Influence of a probe at `world_pos`, fading out over the blend distance towards the box faces
+fn reflection_probe_weight(probe: ReflectionProbe, world_pos: vec3<f32>) -> f32 {
+    let inside = min(world_pos - probe.box_min, probe.box_max - world_pos);
+    let distance = min(min(inside.x, inside.y), inside.z);
+    if (distance < 0.0) {
+        return 0.0;
     }
-
-    if (sun_dir.y <= 0.0) {
-        return vec3<f32>(0.0);
+    if (probe.blend_distance <= 0.0) {
+        return 1.0;
     }
+    return saturate(distance / probe.blend_distance);
+}
 
-    let d = clamp(dot(view_dir, sun_dir), -1.0, 1.0);
-    let aa = max(fwidth(d) * 2.0, 1e-6);
-
-    let sun_disk_cos: f32 = 0.9999892;
-
-    let sun_halo_cos: f32 = mix(0.99990, 0.99920, atm.haze);
-
-    if (d <= sun_halo_cos) {
-        return vec3<f32>(0.0);
+// Lookup direction into the probe cube. With box projection, the reflection ray is
+// intersected with the probe box, so nearby surfaces line up with their reflections.
+fn reflection_probe_dir(probe: ReflectionProbe, world_pos: vec3<f32>, dir: vec3<f32>) -> vec3<f32> {
+    if (probe.box_projection == 0u) {
+        return dir;
     }
 
-    let disk = smoothstep(sun_disk_cos - aa, sun_disk_cos + aa, d);
-
-    let halo_t = saturate((d - sun_halo_cos) / max(sun_disk_cos - sun_halo_cos, 1e-6));
-    let halo_wide  = pow(halo_t, mix(1.6, 2.6, atm.haze)) * (1.0 - disk);
-    let halo_tight = pow(halo_t, mix(8.0, 14.0, atm.haze)) * (1.0 - disk);
-
-    let center_t = saturate((d - sun_disk_cos) / max(1.0 - sun_disk_cos, 1e-6));
-    let limb = mix(0.65, 1.0, pow(center_t, 0.35));
-
-    let core = atm.sun_color;
-    let halo_color = mix(core, sky_color, mix(0.55, 0.75, atm.haze));
-
-    let direct = sun_strength * sun_intensity;
-
-    let disk_gain   = mix(7.0, 3.5, atm.haze);
-    let corona_gain = mix(0.25, 0.75, atm.haze);
-    let halo_gain   = mix(0.06, 0.22, atm.haze);
-
-    return direct * (
-        core * (disk * disk_gain * limb) +
-        halo_color * (halo_tight * corona_gain + halo_wide * halo_gain)
-    );
+    let safe_dir = select(dir, vec3<f32>(1e-5), abs(dir) < vec3<f32>(1e-5));
+    let to_max = (probe.box_max - world_pos) / safe_dir;
+    let to_min = (probe.box_min - world_pos) / safe_dir;
+    let exit = max(to_max, to_min);
+    let distance = min(min(exit.x, exit.y), exit.z);
+    return world_pos + dir * distance - probe.position;
 }
-
-fn sun_transmittance_rgb(sun_dir: vec3<f32>) -> vec3<f32> {
-    let air = max(sky.air_density, 0.0);
-    let aerosol = max(sky.aerosols, 0.0);
-
-    let alt = max(sky.altitude, 0.0);
-    let rayleigh_density = exp(-alt / HR);
-    let mie_density = exp(-alt / HM);
-
-    let rayleigh_scale = air * rayleigh_density;
-    let mie_scale = (aerosol + air * 0.05) * mie_density;
-
-    let betaR = vec3<f32>(0.0058, 0.0135, 0.0331) * rayleigh_scale;
-
-    let betaM = vec3<f32>(0.0200) * mie_scale * mie_len_ratio;
-
-    let betaExt = betaR + betaM + vec3<f32>(1e-6);
-
-    let m_sun = air_mass(saturate(sun_dir.y));
-    let L_sun = m_sun * HR_KM;
-
-    return exp(-betaExt * L_sun);
-}
\ No newline at end of file
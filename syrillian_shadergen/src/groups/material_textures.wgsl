@@ -4,3 +4,7 @@
 @group(2) @binding(3) var s_normal: sampler;
 @group(2) @binding(4) var t_roughness: texture_2d<f32>;
 @group(2) @binding(5) var s_roughness: sampler;
+@group(2) @binding(6) var t_emissive: texture_2d<f32>;
+@group(2) @binding(7) var s_emissive: sampler;
+@group(2) @binding(8) var t_occlusion: texture_2d<f32>;
+@group(2) @binding(9) var s_occlusion: sampler;
@@ -12,8 +12,12 @@ struct FInput {
     @location(2) normal:     vec3<f32>,
     @location(3) tangent:    vec4<f32>,
     @location(4) bitangent:  vec3<f32>,
+    @location(5) @interpolate(flat) instance: u32,
 }
 
+// `out_material` holds roughness, metallic, the decal layer of the model and alpha.
+// The engine stores the layer after the fragment entry point returns, so whatever a
+// shader writes to `out_material.b` is replaced.
 struct FOutput {
       @location(0) out_color    : vec4<f32>,
       @location(1) out_normal   : vec4<f32>,
@@ -5,6 +5,7 @@ struct VInput {
 struct FInput {
     @builtin(position) clip: vec4<f32>,
     @location(0) position:   vec3<f32>,
+    @location(1) @interpolate(flat) instance: u32,
 }
 
 struct FOutput {
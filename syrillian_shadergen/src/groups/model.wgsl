@@ -2,5 +2,7 @@ struct ModelData {
     transform: mat4x4<f32>,
     normal: mat3x3<f32>,
     pick_color: vec4<f32>,
+    lod_fade: f32,
+    decal_layer: u32,
 }
-@group(1) @binding(0) var<uniform> model: ModelData;
+@group(1) @binding(0) var<storage, read> model: ModelData;
@@ -0,0 +1,30 @@
+struct ModelData {
+    transform: mat4x4<f32>,
+    normal: mat3x3<f32>,
+    pick_color: vec4<f32>,
+    lod_fade: f32,
+    decal_layer: u32,
+}
+@group(1) @binding(0) var<storage, read> model_instances: array<ModelData>;
+
+// Complementary 4x4 ordered dither for crossfading levels of detail. A positive fade keeps
+// that fraction of the pixels, a negative one keeps the pixels a positive fade would drop.
+fn lod_fade_discards(fade: f32, frag_coord: vec2<f32>) -> bool {
+    if (fade == 0.0) {
+        return false;
+    }
+
+    var bayer = array<f32, 16>(
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0,
+    );
+    let texel = vec2<u32>(frag_coord) % 4u;
+    let threshold = (bayer[texel.y * 4u + texel.x] + 0.5) / 16.0;
+
+    if (fade > 0.0) {
+        return threshold >= fade;
+    }
+    return threshold < -fade;
+}
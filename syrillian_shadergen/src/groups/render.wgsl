@@ -35,3 +35,7 @@ struct SkyData {
 @group(0) @binding(2) var skybox_map: texture_cube<f32>;
 @group(0) @binding(3) var skybox_sampler: sampler;
 @group(0) @binding(4) var<uniform> sky: SkyData;
+@group(0) @binding(5) var irradiance_map: texture_cube<f32>;
+@group(0) @binding(6) var prefiltered_map: texture_cube<f32>;
+@group(0) @binding(7) var brdf_lut: texture_2d<f32>;
+@group(0) @binding(8) var ibl_sampler: sampler;
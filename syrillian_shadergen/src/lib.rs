@@ -2,8 +2,14 @@ pub mod chunks;
 pub mod compiler;
 pub mod function;
 pub mod generator;
+pub mod graph;
+pub mod permutation;
+pub mod preprocess;
 pub mod value;
 
+pub use chunks::CompareOp;
 pub use chunks::NodeId;
-pub use compiler::{MaterialCompiler, PostProcessCompiler};
+pub use compiler::{MaterialCompiler, MaterialGraphError, PostProcessCompiler};
 pub use generator::ShaderGenerator;
+pub use permutation::MaterialPermutation;
+pub use preprocess::PreprocessError;
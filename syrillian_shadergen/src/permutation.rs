@@ -0,0 +1,80 @@
+//! Compile-time values for material inputs.
+//!
+//! A material can mark `bool` and `u32` inputs as permutation keys. Instead of
+//! reading them from the material at runtime, a [`MaterialCompiler`](crate::MaterialCompiler)
+//! created with [`with_permutation`](crate::MaterialCompiler::with_permutation) bakes
+//! their values into the shader, so only the branches a material actually uses end up
+//! in its WGSL.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Values of the permutation keys of one shader variant. `bool` keys are stored as
+/// `0` or `1`, matching their `u32` representation in WGSL.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MaterialPermutation {
+    values: BTreeMap<String, u32>,
+}
+
+impl MaterialPermutation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_bool(mut self, name: impl Into<String>, value: bool) -> Self {
+        self.set_bool(name, value);
+        self
+    }
+
+    pub fn with_u32(mut self, name: impl Into<String>, value: u32) -> Self {
+        self.set_u32(name, value);
+        self
+    }
+
+    pub fn set_bool(&mut self, name: impl Into<String>, value: bool) {
+        self.values.insert(name.into(), value as u32);
+    }
+
+    pub fn set_u32(&mut self, name: impl Into<String>, value: u32) {
+        self.values.insert(name.into(), value);
+    }
+
+    /// Baked value of `name`, `None` if it's read from the material at runtime.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.values.get(name).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Hash identifying this permutation, independent of insertion order.
+    pub fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl fmt::Display for MaterialPermutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, value)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name}={value}")?;
+        }
+        Ok(())
+    }
+}
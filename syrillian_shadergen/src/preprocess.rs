@@ -0,0 +1,378 @@
+//! `#import` and `#ifdef` support for WGSL sources.
+//!
+//! Shader sources can pull in named modules and compile parts conditionally:
+//!
+//! ```wgsl
+//! #import syrillian::pbr
+//! #import my_game::noise
+//!
+//! #ifdef WIND
+//! fn sway(p: vec3f) -> vec3f { return p + noise(p) * 0.1; }
+//! #else
+//! fn sway(p: vec3f) -> vec3f { return p; }
+//! #endif
+//! ```
+//!
+//! Modules are either provided by the engine (`syrillian::math`, `syrillian::pbr`,
+//! `syrillian::atmosphere`) or registered at runtime with [`register_module`]. Every module is included
+//! once per shader, no matter how often it's imported.
+//!
+//! The output carries `// #line <n> <origin>` markers, which [`source_location`]
+//! uses to map lines of the generated shader back to the module they came from.
+
+use crate::generator::{ATMOSPHERE, MATH_HELPERS, MESH3D_PBR};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+/// Origin of the shader's own source in line markers and errors.
+pub const ROOT_ORIGIN: &str = "<shader>";
+/// Origin of code the generator appends after the shader's source.
+const GENERATED_ORIGIN: &str = "<generated>";
+const LINE_MARKER: &str = "// #line ";
+const ENGINE_PREFIX: &str = "syrillian::";
+
+/// Always part of generated shaders, so imports of it are no-ops.
+pub const MATH_MODULE: &str = "syrillian::math";
+pub const PBR_MODULE: &str = "syrillian::pbr";
+pub const ATMOSPHERE_MODULE: &str = "syrillian::atmosphere";
+
+static MODULES: LazyLock<RwLock<HashMap<String, Arc<str>>>> = LazyLock::new(Default::default);
+
+/// A shader source that couldn't be preprocessed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PreprocessError {
+    /// `#import` of a module that is neither built-in nor registered.
+    UnknownModule {
+        origin: String,
+        line: usize,
+        name: String,
+    },
+    /// A module imports itself, directly or through other modules.
+    ImportCycle {
+        origin: String,
+        line: usize,
+        name: String,
+    },
+    /// A directive that needs a name was used without one.
+    MissingName {
+        origin: String,
+        line: usize,
+        directive: &'static str,
+    },
+    /// `#else` or `#endif` without a matching `#ifdef`/`#ifndef`.
+    UnexpectedDirective {
+        origin: String,
+        line: usize,
+        directive: &'static str,
+    },
+    /// `#ifdef`/`#ifndef` without a matching `#endif`.
+    UnterminatedConditional { origin: String, line: usize },
+    /// Module names starting with `syrillian::` belong to the engine.
+    ReservedModule { name: String },
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreprocessError::UnknownModule { origin, line, name } => {
+                write!(f, "{origin}:{line}: unknown shader module `{name}`")
+            }
+            PreprocessError::ImportCycle { origin, line, name } => {
+                write!(f, "{origin}:{line}: importing `{name}` creates a cycle")
+            }
+            PreprocessError::MissingName {
+                origin,
+                line,
+                directive,
+            } => write!(f, "{origin}:{line}: `#{directive}` needs a name"),
+            PreprocessError::UnexpectedDirective {
+                origin,
+                line,
+                directive,
+            } => write!(f, "{origin}:{line}: `#{directive}` without `#ifdef`"),
+            PreprocessError::UnterminatedConditional { origin, line } => {
+                write!(f, "{origin}:{line}: missing `#endif`")
+            }
+            PreprocessError::ReservedModule { name } => {
+                write!(
+                    f,
+                    "shader module `{name}` uses the reserved `syrillian::` prefix"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {}
+
+/// Registers a module that shader sources can `#import` by `name`, replacing any
+/// module of the same name. Shaders that were already compiled keep the old source.
+pub fn register_module(
+    name: impl Into<String>,
+    source: impl Into<String>,
+) -> Result<(), PreprocessError> {
+    let name = name.into();
+    if name.starts_with(ENGINE_PREFIX) {
+        return Err(PreprocessError::ReservedModule { name });
+    }
+
+    MODULES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(name, source.into().into());
+    Ok(())
+}
+
+/// Removes a registered module. Returns whether it existed.
+pub fn unregister_module(name: &str) -> bool {
+    MODULES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(name)
+        .is_some()
+}
+
+fn module_source(name: &str) -> Option<Arc<str>> {
+    match name {
+        MATH_MODULE => Some(MATH_HELPERS.into()),
+        PBR_MODULE => Some(MESH3D_PBR.into()),
+        ATMOSPHERE_MODULE => Some(ATMOSPHERE.into()),
+        _ => MODULES
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned(),
+    }
+}
+
+enum Directive<'a> {
+    Import(&'a str),
+    Define(&'a str),
+    Undef(&'a str),
+    If { name: &'a str, defined: bool },
+    Else,
+    EndIf,
+}
+
+impl<'a> Directive<'a> {
+    /// Parses a preprocessor line. Other `#` lines, like `#use`, are left to the generator.
+    fn parse(line: &'a str) -> Option<(&'static str, Option<Self>)> {
+        let rest = line.trim().strip_prefix('#')?;
+        let (keyword, name) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let name = name.trim();
+        let named = |directive| (!name.is_empty()).then_some(directive);
+
+        Some(match keyword {
+            "import" => ("import", named(Directive::Import(name))),
+            "define" => ("define", named(Directive::Define(name))),
+            "undef" => ("undef", named(Directive::Undef(name))),
+            "ifdef" => (
+                "ifdef",
+                named(Directive::If {
+                    name,
+                    defined: true,
+                }),
+            ),
+            "ifndef" => (
+                "ifndef",
+                named(Directive::If {
+                    name,
+                    defined: false,
+                }),
+            ),
+            "else" => ("else", Some(Directive::Else)),
+            "endif" => ("endif", Some(Directive::EndIf)),
+            _ => return None,
+        })
+    }
+}
+
+struct Conditional {
+    line: usize,
+    parent_active: bool,
+    active: bool,
+    has_else: bool,
+}
+
+/// Expands `#import`, `#define` and `#ifdef` directives of a shader source.
+#[derive(Debug, Default)]
+pub struct Preprocessor {
+    defines: HashSet<String>,
+    included: HashSet<String>,
+    stack: Vec<String>,
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn define(&mut self, name: impl Into<String>) -> &mut Self {
+        self.defines.insert(name.into());
+        self
+    }
+
+    /// Treats `module` as already part of the output, so imports of it are skipped.
+    pub fn mark_included(&mut self, module: impl Into<String>) -> &mut Self {
+        self.included.insert(module.into());
+        self
+    }
+
+    pub fn process(&mut self, source: &str) -> Result<String, PreprocessError> {
+        let mut out = String::new();
+        self.expand(&mut out, source, ROOT_ORIGIN)?;
+        push_marker(&mut out, 1, GENERATED_ORIGIN);
+        Ok(out)
+    }
+
+    fn expand(
+        &mut self,
+        out: &mut String,
+        source: &str,
+        origin: &str,
+    ) -> Result<(), PreprocessError> {
+        push_marker(out, 1, origin);
+
+        let mut conditions: Vec<Conditional> = Vec::new();
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let active = conditions.last().is_none_or(|c| c.active);
+
+            let Some((keyword, directive)) = Directive::parse(text) else {
+                if active {
+                    out.push_str(text);
+                    out.push('\n');
+                    // `#use` expands to whole bind groups, so counting restarts after it
+                    if text.trim_start().starts_with("#use ") {
+                        push_marker(out, line + 1, origin);
+                    }
+                } else {
+                    out.push('\n');
+                }
+                continue;
+            };
+
+            let Some(directive) = directive else {
+                return Err(PreprocessError::MissingName {
+                    origin: origin.to_string(),
+                    line,
+                    directive: keyword,
+                });
+            };
+
+            let unexpected = || PreprocessError::UnexpectedDirective {
+                origin: origin.to_string(),
+                line,
+                directive: keyword,
+            };
+
+            match directive {
+                Directive::If { name, defined } => conditions.push(Conditional {
+                    line,
+                    parent_active: active,
+                    active: active && self.defines.contains(name) == defined,
+                    has_else: false,
+                }),
+                Directive::Else => {
+                    let condition = conditions.last_mut().ok_or_else(unexpected)?;
+                    if condition.has_else {
+                        return Err(unexpected());
+                    }
+                    condition.has_else = true;
+                    condition.active = condition.parent_active && !condition.active;
+                }
+                Directive::EndIf => {
+                    conditions.pop().ok_or_else(unexpected)?;
+                }
+                Directive::Define(name) if active => {
+                    self.defines.insert(name.to_string());
+                }
+                Directive::Undef(name) if active => {
+                    self.defines.remove(name);
+                }
+                Directive::Import(name) if active && self.import(out, name, origin, line)? => {
+                    push_marker(out, line + 1, origin);
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Keep the line so the rest of the module keeps its line numbers
+            out.push('\n');
+        }
+
+        match conditions.first() {
+            Some(condition) => Err(PreprocessError::UnterminatedConditional {
+                origin: origin.to_string(),
+                line: condition.line,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Appends `name` unless it was included before. Returns whether it was appended.
+    fn import(
+        &mut self,
+        out: &mut String,
+        name: &str,
+        origin: &str,
+        line: usize,
+    ) -> Result<bool, PreprocessError> {
+        if self.stack.iter().any(|module| module == name) {
+            return Err(PreprocessError::ImportCycle {
+                origin: origin.to_string(),
+                line,
+                name: name.to_string(),
+            });
+        }
+        if self.included.contains(name) {
+            return Ok(false);
+        }
+
+        let Some(source) = module_source(name) else {
+            return Err(PreprocessError::UnknownModule {
+                origin: origin.to_string(),
+                line,
+                name: name.to_string(),
+            });
+        };
+
+        self.included.insert(name.to_string());
+        self.stack.push(name.to_string());
+        self.expand(out, &source, name)?;
+        self.stack.pop();
+        Ok(true)
+    }
+}
+
+fn push_marker(out: &mut String, line: usize, origin: &str) {
+    out.push_str(LINE_MARKER);
+    out.push_str(&line.to_string());
+    out.push(' ');
+    out.push_str(origin);
+    out.push('\n');
+}
+
+/// Maps a 1-based line of a generated shader to its origin and line there.
+/// Returns `None` for lines the generator added around the preprocessed source.
+pub fn source_location(generated: &str, line: usize) -> Option<(&str, usize)> {
+    let mut marker = None;
+    for (index, text) in generated.lines().enumerate().take(line) {
+        let Some(rest) = text.trim_start().strip_prefix(LINE_MARKER) else {
+            continue;
+        };
+        let Some((number, origin)) = rest.split_once(' ') else {
+            continue;
+        };
+        if let Ok(number) = number.parse::<usize>() {
+            marker = Some((index + 1, number, origin));
+        }
+    }
+
+    let (marker_line, number, origin) = marker?;
+    if origin == GENERATED_ORIGIN || marker_line == line {
+        return None;
+    }
+    Some((origin, number + line - marker_line - 1))
+}
@@ -72,6 +72,60 @@ impl MaterialValueType {
         }
     }
 
+    /// Number of scalar components, `1` for scalar types.
+    pub fn component_count(self) -> u32 {
+        match self {
+            MaterialValueType::F32 | MaterialValueType::U32 | MaterialValueType::Bool => 1,
+            MaterialValueType::Vec2 => 2,
+            MaterialValueType::Vec3 => 3,
+            MaterialValueType::Vec4 => 4,
+        }
+    }
+
+    /// `f32` or one of the float vectors.
+    pub fn is_float(self) -> bool {
+        !matches!(self, MaterialValueType::U32 | MaterialValueType::Bool)
+    }
+
+    pub fn is_vector(self) -> bool {
+        self.component_count() > 1
+    }
+
+    /// The float scalar or vector type with `components` components.
+    pub fn float_with_components(components: u32) -> Option<Self> {
+        match components {
+            1 => Some(MaterialValueType::F32),
+            2 => Some(MaterialValueType::Vec2),
+            3 => Some(MaterialValueType::Vec3),
+            4 => Some(MaterialValueType::Vec4),
+            _ => None,
+        }
+    }
+
+    /// Name used for the type in serialized material data.
+    pub const fn name(self) -> &'static str {
+        match self {
+            MaterialValueType::F32 => "f32",
+            MaterialValueType::U32 => "u32",
+            MaterialValueType::Bool => "bool",
+            MaterialValueType::Vec2 => "vec2",
+            MaterialValueType::Vec3 => "vec3",
+            MaterialValueType::Vec4 => "vec4",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "f32" => Some(MaterialValueType::F32),
+            "u32" => Some(MaterialValueType::U32),
+            "bool" => Some(MaterialValueType::Bool),
+            "vec2" => Some(MaterialValueType::Vec2),
+            "vec3" => Some(MaterialValueType::Vec3),
+            "vec4" => Some(MaterialValueType::Vec4),
+            _ => None,
+        }
+    }
+
     pub fn wgsl_type(self) -> &'static str {
         match self {
             MaterialValueType::F32 => "f32",
@@ -0,0 +1,39 @@
+use syrillian_shadergen::generator::{ShaderGenerator, ShaderKind};
+
+const SURFACE: &str = "\
+@fragment
+fn fs_main(in: FInput, @builtin(front_facing) front: bool) -> FOutput {
+    var out: FOutput;
+    out.out_material = vec4(1.0, 0.0, 1.0, 1.0);
+    return out;
+}";
+
+#[test]
+fn hand_written_surfaces_store_their_model_layer() {
+    let code = ShaderGenerator::assemble_shader(SURFACE, true, ShaderKind::Default, true, None);
+
+    assert!(code.contains("fn fs_main_surface(in: FInput, @builtin(front_facing) front: bool)"));
+    assert!(code.contains(
+        "@fragment\nfn fs_main(in: FInput, @builtin(front_facing) front: bool) -> FOutput {"
+    ));
+    assert!(code.contains("var surface = fs_main_surface(in, front);"));
+    assert!(code.contains("surface.out_material.b = f32(model.decal_layer) / 255.0;"));
+    assert_eq!(code.matches("@fragment").count(), 1, "{code}");
+}
+
+#[test]
+fn custom_mesh_shaders_store_their_model_layer() {
+    let source = format!("#use default_vertex\n#use model\n\n{SURFACE}");
+    let code = ShaderGenerator::assemble_shader(&source, false, ShaderKind::Custom, false, None);
+
+    assert!(code.contains("var surface = fs_main_surface(in, front);"));
+}
+
+#[test]
+fn shaders_without_a_model_are_left_alone() {
+    let source = format!("#use default_vertex\n\n{SURFACE}");
+    let code = ShaderGenerator::assemble_shader(&source, false, ShaderKind::Custom, false, None);
+
+    assert!(!code.contains("fs_main_surface"));
+    assert!(!code.contains("model.decal_layer"));
+}
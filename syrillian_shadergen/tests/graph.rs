@@ -0,0 +1,114 @@
+use syrillian_shadergen::graph::{GraphOp, GraphPin, MaterialGraph, MaterialGraphNode};
+use syrillian_shadergen::value::MaterialValueType;
+use syrillian_shadergen::{MaterialCompiler, MaterialGraphError};
+
+fn tinted_graph() -> MaterialGraph {
+    let mut graph = MaterialGraph::default();
+    let uv = graph.add(MaterialGraphNode::VertexUv);
+    let color = graph.add(MaterialGraphNode::TextureSample {
+        texture: "diffuse".to_string(),
+        uv,
+    });
+    let tint = graph.add(MaterialGraphNode::Parameter {
+        name: "tint".to_string(),
+        value_type: MaterialValueType::Vec4,
+    });
+    let tinted = graph.add(MaterialGraphNode::Op {
+        op: GraphOp::Mul,
+        inputs: vec![color, tint],
+    });
+    graph.surface.base_color = Some(tinted);
+    graph
+}
+
+#[test]
+fn graph_compiles_to_material_shaders() {
+    let graph = tinted_graph();
+    assert_eq!(
+        graph.parameters().collect::<Vec<_>>(),
+        [("tint", MaterialValueType::Vec4)]
+    );
+    assert_eq!(graph.textures().collect::<Vec<_>>(), ["diffuse"]);
+
+    let set = MaterialCompiler::try_compile_shader_set(&mut graph.clone()).unwrap();
+    assert!(!set.custom_vertex);
+    assert!(
+        set.base.contains("textureSample(t_diffuse, s_diffuse"),
+        "{}",
+        set.base
+    );
+    assert!(set.base.contains("material.tint"), "{}", set.base);
+}
+
+#[test]
+fn split_outputs_are_addressable() {
+    let mut graph = MaterialGraph::default();
+    let uv = graph.add(MaterialGraphNode::VertexUv);
+    let parts = graph.add(MaterialGraphNode::Split(uv));
+    let one = graph.add(MaterialGraphNode::Constant(1.0));
+    let color = graph.add(MaterialGraphNode::Construct {
+        value_type: MaterialValueType::Vec4,
+        parts: vec![
+            GraphPin::new(parts.node, 1),
+            GraphPin::new(parts.node, 0),
+            one,
+            one,
+        ],
+    });
+    graph.surface.base_color = Some(color);
+
+    graph.validate().unwrap();
+
+    graph.surface.base_color = Some(GraphPin::new(parts.node, 2));
+    assert_eq!(
+        graph.validate(),
+        Err(MaterialGraphError::InvalidConnection {
+            node: 4,
+            target: 1,
+            output_index: 2,
+        })
+    );
+}
+
+#[test]
+fn malformed_graphs_are_rejected() {
+    let mut graph = tinted_graph();
+    graph.nodes[3] = MaterialGraphNode::Op {
+        op: GraphOp::Lerp,
+        inputs: vec![GraphPin::node(1), GraphPin::node(2)],
+    };
+    assert_eq!(
+        graph.validate(),
+        Err(MaterialGraphError::InputCount {
+            op: "lerp",
+            expected: 3,
+            found: 2,
+        })
+    );
+
+    let mut graph = tinted_graph();
+    graph.nodes[1] = MaterialGraphNode::TextureSample {
+        texture: "diffuse".to_string(),
+        uv: GraphPin::node(3),
+    };
+    assert_eq!(
+        graph.validate(),
+        Err(MaterialGraphError::InvalidConnection {
+            node: 1,
+            target: 3,
+            output_index: 0,
+        })
+    );
+
+    let mut graph = tinted_graph();
+    graph.surface.roughness = Some(GraphPin::node(3));
+    assert_eq!(
+        graph.validate(),
+        Err(MaterialGraphError::InputType {
+            op: "material output",
+            input: "roughness",
+            expected: "f32",
+            found: MaterialValueType::Vec4,
+        })
+    );
+}
@@ -0,0 +1,32 @@
+use syrillian_shadergen::MaterialCompiler;
+use syrillian_shadergen::function::PbrShader;
+use syrillian_shadergen::generator::{MeshPass, ShaderGenerator};
+
+#[test]
+fn mesh_shaders_read_model_per_instance() {
+    let mut pbr = PbrShader::default();
+
+    for pass in [MeshPass::Base, MeshPass::Shadow, MeshPass::Picking] {
+        let code = MaterialCompiler::compile_mesh(&mut pbr, 0, pass);
+
+        assert!(ShaderGenerator::is_instanced(&code), "{pass:?}");
+        assert!(code.contains("@builtin(instance_index) instance: u32"));
+        assert!(code.contains("let model = model_instances[instance];"));
+        assert!(!code.contains("var<storage, read> model:"));
+    }
+}
+
+#[test]
+fn picking_fragment_reads_its_instance() {
+    let code = MaterialCompiler::compile_mesh_picking();
+
+    assert!(ShaderGenerator::is_instanced(&code));
+    assert!(code.contains("let model = model_instances[in.instance];"));
+}
+
+#[test]
+fn single_model_shaders_are_not_instanced() {
+    let code = "@group(1) @binding(0) var<storage, read> model: ModelData;";
+
+    assert!(!ShaderGenerator::is_instanced(code));
+}
@@ -0,0 +1,58 @@
+use syrillian_shadergen::function::PbrShader;
+use syrillian_shadergen::generator::MeshPass;
+use syrillian_shadergen::{MaterialCompiler, MaterialPermutation};
+
+#[test]
+fn empty_permutation_matches_generic_shader() {
+    let mut pbr = PbrShader::default();
+
+    let generic = MaterialCompiler::compile_mesh(&mut pbr, 0, MeshPass::Base);
+    let permuted = MaterialCompiler::compile_mesh_permutation(
+        &mut pbr,
+        0,
+        MeshPass::Base,
+        &MaterialPermutation::new(),
+    );
+
+    assert_eq!(generic, permuted);
+}
+
+#[test]
+fn keys_replace_runtime_branches() {
+    let mut pbr = PbrShader::default();
+    let permutation = MaterialPermutation::new()
+        .with_bool("use_diffuse_texture", true)
+        .with_bool("use_normal_texture", false)
+        .with_bool("lit", false);
+
+    let generic = MaterialCompiler::compile_mesh(&mut pbr, 0, MeshPass::Base);
+    let code =
+        MaterialCompiler::compile_mesh_permutation(&mut pbr, 0, MeshPass::Base, &permutation);
+
+    assert!(generic.contains("material.use_diffuse_texture != 0"));
+    assert!(!code.contains("material.use_diffuse_texture"));
+    assert!(!code.contains("material.use_normal_texture"));
+    assert!(!code.contains("material.lit"));
+    assert!(code.contains("textureSample(t_diffuse"));
+    assert!(!code.contains("normal_from_map(t_normal"));
+    assert!(generic.contains("normal_from_map(t_normal"));
+    // Keys that aren't part of the permutation still branch at runtime
+    assert!(code.contains("material.use_roughness_texture != 0"));
+}
+
+#[test]
+fn key_ignores_insertion_order() {
+    let a = MaterialPermutation::new()
+        .with_bool("lit", true)
+        .with_u32("mode", 2);
+    let b = MaterialPermutation::new()
+        .with_u32("mode", 2)
+        .with_bool("lit", true);
+    let c = MaterialPermutation::new()
+        .with_u32("mode", 3)
+        .with_bool("lit", true);
+
+    assert_eq!(a.key(), b.key());
+    assert_ne!(a.key(), c.key());
+    assert_eq!(a.to_string(), "lit=1, mode=2");
+}
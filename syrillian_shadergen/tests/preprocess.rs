@@ -0,0 +1,117 @@
+use syrillian_shadergen::PreprocessError;
+use syrillian_shadergen::preprocess::{
+    Preprocessor, ROOT_ORIGIN, register_module, source_location,
+};
+
+fn code_lines(out: &str) -> Vec<&str> {
+    out.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with("// #line"))
+        .collect()
+}
+
+#[test]
+fn imports_are_included_once() {
+    register_module("test::a", "fn a() {}").unwrap();
+    register_module("test::b", "#import test::a\nfn b() { a(); }").unwrap();
+
+    let out = Preprocessor::new()
+        .process("#import test::b\n#import test::a\nfn main() { b(); }")
+        .unwrap();
+
+    assert_eq!(
+        code_lines(&out),
+        ["fn a() {}", "fn b() { a(); }", "fn main() { b(); }"]
+    );
+}
+
+#[test]
+fn conditionals_follow_defines() {
+    let source = "\
+#ifdef FAST
+fn f() -> f32 { return 0.0; }
+#else
+fn f() -> f32 { return 1.0; }
+#endif
+#ifndef FAST
+#define SLOW
+#endif
+#ifdef SLOW
+fn slow() {}
+#endif";
+
+    let out = Preprocessor::new().define("FAST").process(source).unwrap();
+    assert_eq!(code_lines(&out), ["fn f() -> f32 { return 0.0; }"]);
+
+    let out = Preprocessor::new().process(source).unwrap();
+    assert_eq!(
+        code_lines(&out),
+        ["fn f() -> f32 { return 1.0; }", "fn slow() {}"]
+    );
+}
+
+#[test]
+fn generated_lines_map_to_their_origin() {
+    register_module("test::mapped", "fn one() {}\n\nfn three() {}").unwrap();
+
+    let out = Preprocessor::new()
+        .process("fn first() {}\n#import test::mapped\nfn third() {}")
+        .unwrap();
+    let line_of = |needle: &str| out.lines().position(|l| l == needle).unwrap() + 1;
+
+    assert_eq!(
+        source_location(&out, line_of("fn first() {}")),
+        Some((ROOT_ORIGIN, 1))
+    );
+    assert_eq!(
+        source_location(&out, line_of("fn three() {}")),
+        Some(("test::mapped", 3))
+    );
+    assert_eq!(
+        source_location(&out, line_of("fn third() {}")),
+        Some((ROOT_ORIGIN, 3))
+    );
+}
+
+#[test]
+fn invalid_directives_are_reported() {
+    register_module("test::cycle_a", "#import test::cycle_b").unwrap();
+    register_module("test::cycle_b", "\n#import test::cycle_a").unwrap();
+
+    assert_eq!(
+        Preprocessor::new().process("#import test::cycle_a"),
+        Err(PreprocessError::ImportCycle {
+            origin: "test::cycle_b".to_string(),
+            line: 2,
+            name: "test::cycle_a".to_string(),
+        })
+    );
+    assert_eq!(
+        Preprocessor::new().process("\n#import test::missing"),
+        Err(PreprocessError::UnknownModule {
+            origin: ROOT_ORIGIN.to_string(),
+            line: 2,
+            name: "test::missing".to_string(),
+        })
+    );
+    assert_eq!(
+        Preprocessor::new().process("#ifdef A\n#ifdef B\n#endif"),
+        Err(PreprocessError::UnterminatedConditional {
+            origin: ROOT_ORIGIN.to_string(),
+            line: 1,
+        })
+    );
+    assert_eq!(
+        Preprocessor::new().process("#else"),
+        Err(PreprocessError::UnexpectedDirective {
+            origin: ROOT_ORIGIN.to_string(),
+            line: 1,
+            directive: "else",
+        })
+    );
+    assert_eq!(
+        register_module("syrillian::mine", ""),
+        Err(PreprocessError::ReservedModule {
+            name: "syrillian::mine".to_string(),
+        })
+    );
+}
@@ -0,0 +1,120 @@
+use syrillian_shadergen::chunks::NodeId;
+use syrillian_shadergen::function::{MaterialExpression, MaterialExpressionValue};
+use syrillian_shadergen::generator::MeshPass;
+use syrillian_shadergen::value::MaterialValueType;
+use syrillian_shadergen::{CompareOp, MaterialCompiler, MaterialGraphError};
+
+struct GraphMaterial<F>(F);
+
+impl<F: Fn(&mut MaterialCompiler) -> NodeId> MaterialExpression for GraphMaterial<F> {
+    fn outputs(&self) -> Vec<MaterialExpressionValue> {
+        vec![MaterialExpressionValue {
+            name: "out",
+            value_type: MaterialValueType::Vec4,
+        }]
+    }
+
+    fn compile(&self, compiler: &mut MaterialCompiler, _output_index: u32) -> NodeId {
+        let base_color = (self.0)(compiler);
+        let normal = compiler.typed_input_value("normal", MaterialValueType::Vec3);
+        let half = compiler.constant_f32(0.5);
+        let flag = compiler.typed_input_value("flag", MaterialValueType::Bool);
+        compiler.pbr_shader(base_color, normal, half, half, half, flag, flag, flag)
+    }
+}
+
+fn compile<F: Fn(&mut MaterialCompiler) -> NodeId>(graph: F) -> Result<String, MaterialGraphError> {
+    MaterialCompiler::try_compile_mesh(&mut GraphMaterial(graph), 0, MeshPass::Base)
+}
+
+#[test]
+fn split_outputs_get_their_own_locals() {
+    let wgsl = compile(|c| {
+        let color = c.typed_input_value("tint", MaterialValueType::Vec4);
+        let parts = c.split(color);
+        assert_eq!(c.output_count(parts), 4);
+
+        let r = c.output(parts, 0);
+        let a = c.output(parts, 3);
+        assert_eq!(c.output_type(r, 0), Some(MaterialValueType::F32));
+
+        let rgb = c.swizzle(color, "rgb");
+        let opaque = c.construct(MaterialValueType::Vec4, &[rgb, a]);
+        let gray = c.vec4(r, r, r, a);
+        c.mul(opaque, gray)
+    })
+    .expect("graph should type check");
+
+    assert!(wgsl.contains("let _pv1_0: f32 = _pv1.x;"), "{wgsl}");
+    assert!(wgsl.contains("let _pv1_3: f32 = _pv1.w;"), "{wgsl}");
+    assert!(
+        wgsl.contains("vec4<f32>(material.tint.rgb, _pv1_3)"),
+        "{wgsl}"
+    );
+    assert!(
+        wgsl.contains("vec4<f32>(_pv1_0, _pv1_0, _pv1_0, _pv1_3)"),
+        "{wgsl}"
+    );
+}
+
+#[test]
+fn builtins_widen_scalars_and_compile_conditions() {
+    let wgsl = compile(|c| {
+        let a = c.typed_input_value("a", MaterialValueType::Vec4);
+        let b = c.typed_input_value("b", MaterialValueType::Vec4);
+        let t = c.constant_f32(0.25);
+        let mixed = c.lerp(a, b, t);
+        assert_eq!(c.output_type(mixed, 0), Some(MaterialValueType::Vec4));
+
+        let threshold = c.constant_f32(0.5);
+        let t_greater = c.compare(CompareOp::Greater, t, threshold);
+        assert_eq!(c.output_type(t_greater, 0), Some(MaterialValueType::Bool));
+        c.select(t_greater, mixed, b)
+    })
+    .expect("graph should type check");
+
+    assert!(
+        wgsl.contains("mix(material.a, material.b, vec4<f32>(_pv2))"),
+        "{wgsl}"
+    );
+    assert!(wgsl.contains("select(0u, 1u, _pv2 > _pv4)"), "{wgsl}");
+    assert!(
+        wgsl.contains("select(material.b, _pv3, _pv5 != 0u)"),
+        "{wgsl}"
+    );
+}
+
+#[test]
+fn mismatched_vectors_are_rejected() {
+    let result = compile(|c| {
+        let color = c.typed_input_value("tint", MaterialValueType::Vec4);
+        let normal = c.typed_input_value("dir", MaterialValueType::Vec3);
+        c.add(color, normal)
+    });
+
+    assert_eq!(
+        result,
+        Err(MaterialGraphError::IncompatibleTypes {
+            op: "add",
+            a: MaterialValueType::Vec4,
+            b: MaterialValueType::Vec3,
+        })
+    );
+}
+
+#[test]
+fn wrong_component_counts_are_rejected() {
+    let result = compile(|c| {
+        let uv = c.vertex_uv();
+        let one = c.constant_f32(1.0);
+        c.vec4(uv, one, one, one)
+    });
+
+    assert_eq!(
+        result,
+        Err(MaterialGraphError::ComponentCount {
+            value_type: MaterialValueType::Vec4,
+            found: 5,
+        })
+    );
+}
@@ -169,6 +169,22 @@ impl BoundingSphere {
         }
     }
 
+    /// The smallest sphere enclosing both spheres.
+    pub fn union(&self, other: &BoundingSphere) -> Self {
+        let offset = other.center - self.center;
+        let distance = offset.length();
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+
+        let radius = (distance + self.radius + other.radius) * 0.5;
+        let center = self.center + offset * ((radius - self.radius) / distance);
+        Self { center, radius }
+    }
+
     pub fn from_positions<P>(positions: P) -> Self
     where
         P: IntoIterator<Item = Vec3> + Clone,
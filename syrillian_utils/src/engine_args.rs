@@ -79,6 +79,11 @@ pub struct EngineArgs {
     pub no_fullscreen: bool, // TODO: Implement
     #[argh(switch, hidden_help)]
     pub no_frustum_culling: bool,
+    /// skip drawing what is hidden behind opaque geometry. Everything visible in the last
+    /// frame is drawn first, then the rest is tested against its depth on the GPU, which
+    /// the frame waits for
+    #[argh(switch)]
+    pub occlusion_culling: bool,
     #[argh(switch, hidden_help)]
     pub no_shadows: bool,
     #[argh(switch, hidden_help)]
@@ -87,6 +92,10 @@ pub struct EngineArgs {
     pub no_ssao: bool,
     #[argh(switch, hidden_help)]
     pub no_bloom: bool,
+    #[argh(switch, hidden_help)]
+    pub no_shader_cache: bool,
+    #[argh(switch, hidden_help)]
+    pub no_instancing: bool,
 
     #[argh(option, hidden_help)]
     pub max_frames_in_flight: Option<u32>,
@@ -151,3 +160,20 @@ impl EngineArgs {
         EngineArgs::get().aa_mode.flatten().unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argh::EarlyExit;
+
+    #[test]
+    fn occlusion_culling_is_documented() {
+        let Err(EarlyExit { output, .. }) = EngineArgs::from_args(&["engine"], &["--help"]) else {
+            panic!("--help didn't exit early");
+        };
+        assert!(output.contains("--occlusion-culling"), "{output}");
+
+        let args = EngineArgs::from_args(&["engine"], &["--occlusion-culling"]).unwrap();
+        assert!(args.occlusion_culling);
+    }
+}